@@ -13,10 +13,7 @@ mod utils;
 
 use bevy::prelude::*;
 use bevy::sprite_render::{ColorMaterial, MeshMaterial2d};
-use bevy_mortar_bond::{
-    MortarAudioSettings, MortarDialoguePlugin, MortarEvent, MortarFunctions, MortarGameEvent,
-    MortarNumber, MortarPlugin, MortarRegistry, MortarRuntime, MortarString, mortar_functions,
-};
+use bevy_mortar_bond::prelude::*;
 use std::time::Duration;
 use utils::typewriter::TypewriterPlugin;
 use utils::ui::*;
@@ -25,7 +22,7 @@ use utils::ui::*;
 #[derive(Component)]
 struct TriangleSprite;
 
-/// Resource that cycles through bundled `.mortar` files with a button.
+/// Resource that cycles through bundled Mortar files with a button.
 #[derive(Resource)]
 struct DialogueFiles {
     files: Vec<String>,
@@ -45,6 +42,7 @@ impl Default for DialogueFiles {
                 "branch_interpolation.mortar".into(),
                 "enum_branch.mortar".into(),
                 "master_test.mortar".into(),
+                "choice_layout.mortared".into(),
             ],
             current_index: 0,
         }
@@ -65,8 +63,8 @@ fn main() {
     App::new()
         .add_plugins((
             DefaultPlugins.set(ImagePlugin::default_nearest()),
-            MortarPlugin,
-            MortarDialoguePlugin,
+            MortarPlugin::default(),
+            MortarDialoguePlugin::default(),
             TypewriterPlugin,
             DialogueUiPlugin,
         ))
@@ -169,7 +167,7 @@ fn setup_mortar_functions(mut runtime: ResMut<MortarRuntime>) {
 fn load_initial_dialogue(
     asset_server: Res<AssetServer>,
     mut registry: ResMut<MortarRegistry>,
-    mut events: MessageWriter<MortarEvent>,
+    mut events: MessageWriter<MortarCommand>,
     dialogue_files: Res<DialogueFiles>,
 ) {
     let path = dialogue_files.current().to_string();
@@ -179,11 +177,7 @@ fn load_initial_dialogue(
 
     const START_NODE: &str = "Start";
     info!("Example: Send StartNode event: {} / {}", &path, START_NODE);
-    events.write(MortarEvent::StartNode {
-        path,
-        node: START_NODE.to_string(),
-        target: None,
-    });
+    events.write(MortarCommand::start_node(path, START_NODE));
 }
 
 /// Read `MortarGameEvent`s and convert them into Bevy gameplay actions.
@@ -4,7 +4,9 @@
 //! placeholder gameplay viewport on the right.
 //!
 //! This example demonstrates how to integrate `MortarDialoguePlugin` with a custom
-//! UI system (the terminal typewriter) by using a proxy entity to capture text.
+//! UI system (the terminal typewriter) by reading `MortarTextChanged` instead of a text target.
+//! Saving a broken script keeps the dialogue on the last good version and prints the compiler's
+//! error, with its line and column, in the terminal and the editor status line.
 //!
 //! Sprite from https://opengameart.org/content/animated-rogue
 #[path = "utils/live_terminal.rs"]
@@ -24,12 +26,7 @@ use bevy::{
     prelude::*,
     window::{PresentMode, WindowResolution},
 };
-use bevy_mortar_bond::{
-    MortarBoolean, MortarDialoguePlugin, MortarDialogueSystemSet, MortarDialogueText,
-    MortarDialogueVariables, MortarEvent, MortarEventBinding, MortarFunctions, MortarGameEvent,
-    MortarPlugin, MortarRegistry, MortarRuntime, MortarString, MortarTextTarget,
-    MortarVariableValue, mortar_functions,
-};
+use bevy_mortar_bond::{MortarLifecycleEvent, prelude::*};
 use live_terminal::{
     ASSET_DIR, ChoiceButton, ChoicePanel, ChoicePanelFont, CursorBlink, DEFAULT_FILE,
     DIALOGUE_CHAR_SPEED, GameDialogueText, RogueAnimationEvent, RoguePreviewImage, TerminalMachine,
@@ -72,8 +69,8 @@ fn main() {
         .add_plugins((
             TypewriterPlugin,
             RogueSpritePlugin,
-            MortarPlugin,
-            MortarDialoguePlugin,
+            MortarPlugin::default(),
+            MortarDialoguePlugin::default(),
         ))
         .init_resource::<LiveScriptSource>()
         .init_resource::<ScriptWatcher>()
@@ -101,6 +98,7 @@ fn main() {
                 bridge_mortar_events.after(MortarDialogueSystemSet::TriggerEvents),
                 sync_choice_panel,
                 monitor_script_changes,
+                report_reload_results,
                 sync_gender_from_variable,
             ),
         )
@@ -117,11 +115,6 @@ fn main() {
         .run();
 }
 
-/// A hidden entity used to receive processed text from MortarDialoguePlugin.
-/// We read from this and feed the visible typewriter.
-#[derive(Component)]
-struct MortarTextProxy;
-
 #[derive(SystemSet, Debug, Hash, PartialEq, Eq, Clone)]
 enum LiveTerminalSystemSet {
     CopyMortarText,
@@ -148,7 +141,7 @@ impl TerminalFunctions {
 
 fn sync_typewriter_progress(
     typewriter_query: Query<&Typewriter, With<GameDialogueText>>,
-    mut binding_query: Query<&mut MortarEventBinding, With<MortarTextProxy>>,
+    mut binding_query: Query<&mut MortarEventBinding, With<GameDialogueText>>,
 ) {
     let Ok(typewriter) = typewriter_query.single() else {
         return;
@@ -168,8 +161,8 @@ fn sync_gender_from_variable(
         return;
     };
 
-    if let Some(MortarVariableValue::Boolean(is_female)) = state.get("isFemale") {
-        let target_gender = if *is_female {
+    if let Some(is_female) = state.get_bool("isFemale") {
+        let target_gender = if is_female {
             RogueGender::Female
         } else {
             RogueGender::Male
@@ -335,16 +328,38 @@ fn monitor_script_changes(
     }
 }
 
+/// Prints hot reload results to the terminal, and to the editor status line while it is open.
+fn report_reload_results(
+    mut lifecycle: MessageReader<MortarLifecycleEvent>,
+    mut machine: ResMut<TerminalMachine>,
+) {
+    for event in lifecycle.read() {
+        let line = match event {
+            MortarLifecycleEvent::ReloadFailed { path, error } => {
+                format!("[mortar] {path} failed to reload, keeping the last good version: {error}")
+            }
+            MortarLifecycleEvent::ReloadSucceeded { path } => format!("[mortar] reloaded {path}"),
+            _ => continue,
+        };
+        if let Some(editor) = machine.view.as_vim_mut() {
+            editor.set_status(line.clone());
+        }
+        machine.shell.push_history(line);
+        machine.dirty = true;
+    }
+}
+
 fn setup_mortar_integration(
-    mut commands: Commands,
     asset_server: Res<AssetServer>,
     mut registry: ResMut<MortarRegistry>,
-    mut events: MessageWriter<MortarEvent>,
+    mut events: MessageWriter<MortarCommand>,
     mut runtime: ResMut<MortarRuntime>,
     mut source: ResMut<LiveScriptSource>,
 ) {
     // Register functions
     TerminalFunctions::bind_functions(&mut runtime.functions);
+    // Clicks pick an option outright; keyboard navigation would still select then confirm.
+    runtime.choice_confirm = ChoiceConfirmMode::PerSource;
 
     // Load the live example script
     let path = format!("live/{}", DEFAULT_FILE);
@@ -357,30 +372,15 @@ fn setup_mortar_integration(
         *source = initial_source;
     }
 
-    // Create a hidden proxy entity to receive Mortar text updates.
-    // MortarDialoguePlugin expects a Text component to write into.
-    commands.spawn((
-        Name::new("MortarTextProxy"),
-        Text::new(""),
-        MortarTextTarget,
-        MortarDialogueText::default(),
-        MortarTextProxy,
-        Visibility::Hidden,
-    ));
-
     // Start the dialogue
-    events.write(MortarEvent::StartNode {
-        path,
-        node: "Start".to_string(),
-        target: None,
-    });
+    events.write(MortarCommand::start_node(path, "Start"));
 }
 
 fn handle_dialogue_input(
     mut inputs: MessageReader<KeyboardInput>,
     machine: Res<TerminalMachine>,
     runtime: Res<MortarRuntime>,
-    mut events: MessageWriter<MortarEvent>,
+    mut events: MessageWriter<MortarCommand>,
     mut text_query: Query<&mut Typewriter, With<GameDialogueText>>,
 ) {
     if machine.focused {
@@ -412,29 +412,34 @@ fn handle_dialogue_input(
                 || typewriter.state == TypewriterState::Idle
             {
                 // Request next text from Mortar
-                events.write(MortarEvent::NextText { target: None });
+                events.write(MortarCommand::NextText { target: None });
             }
         }
     }
 }
 
-/// Watches the hidden proxy entity. When MortarDialoguePlugin updates it,
-/// we forward the text to the visible Typewriter.
+/// Forwards each new line from MortarDialoguePlugin to the visible Typewriter, which also
+/// tracks the line's events against its typing progress.
 fn sync_mortar_text_to_terminal(
-    proxy_query: Query<&MortarDialogueText, (With<MortarTextProxy>, Changed<MortarDialogueText>)>,
-    mut terminal_query: Query<&mut Typewriter, With<GameDialogueText>>,
+    mut commands: Commands,
+    mut changes: MessageReader<MortarTextChanged>,
+    mut terminal_query: Query<(Entity, &mut Typewriter), With<GameDialogueText>>,
     mut machine: ResMut<TerminalMachine>,
 ) {
-    let Ok(mortar_text) = proxy_query.single() else {
+    let Some(change) = changes.read().last() else {
         return;
     };
-    let Ok(mut typewriter) = terminal_query.single_mut() else {
+    let Ok((entity, mut typewriter)) = terminal_query.single_mut() else {
         return;
     };
 
     // Start typing the new text
-    *typewriter = Typewriter::new(mortar_text.body.clone(), DIALOGUE_CHAR_SPEED);
+    *typewriter = Typewriter::new(change.body.clone(), DIALOGUE_CHAR_SPEED);
     typewriter.play();
+    commands.entity(entity).insert((
+        MortarEventTracker::new(change.events.clone()),
+        MortarEventBinding::default(),
+    ));
 
     // Force refresh terminal to update highlight line in the editor
     machine.dirty = true;
@@ -515,15 +520,21 @@ fn sync_choice_panel(
 
 fn handle_choice_buttons(
     mut buttons: ChoiceButtonQuery<'_, '_>,
-    mut events: MessageWriter<MortarEvent>,
+    presented: Res<MortarChoicesPresented>,
+    mut events: MessageWriter<MortarCommand>,
 ) {
     for (interaction, button) in &mut buttons {
         if *interaction == Interaction::Pressed {
-            events.write(MortarEvent::SelectChoice {
-                index: button.index,
-                target: None,
-            });
-            events.write(MortarEvent::ConfirmChoice { target: None });
+            // Stamp the selection with the group, so a click meant for a parent group is dropped
+            // once its nested group takes over. As pointer input it confirms in the same frame.
+            //
+            // 为选择标记选项组，使针对父级选项组的点击在其嵌套组接管后被丢弃。作为指针输入，
+            // 它会在同一帧内完成确认。
+            let group = presented.group_token;
+            events.write(
+                MortarCommand::select_choice_in(group, button.index)
+                    .from_source(ChoiceInputSource::Pointer),
+            );
             break; // Only handle one click per frame
         }
     }
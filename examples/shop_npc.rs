@@ -0,0 +1,140 @@
+//! RPG shop demo for `bevy_mortar_bond`.
+//!
+//! A shopkeeper conversation (`assets/shop.mortar`) wired into a small game: prices come from
+//! bound functions, the player's gold and the shop's stock are game state seeded into script
+//! variables, purchases and gifts are game actions that fill an inventory, and a timeline plays
+//! a short haggling sequence. The gameplay bindings live in `utils/shop.rs`, which the crate's
+//! tests also drive headlessly; this file only adds a window and keyboard controls.
+//!
+//! Controls: `Space` continues, `1`-`4` pick an option, `R` walks into the shop again.
+//!
+//! `bevy_mortar_bond` 的 RPG 商店演示示例。
+//!
+//! 将店主对话（`assets/shop.mortar`）接入一个小游戏：价格来自绑定函数，玩家的金币与商店库存是
+//! 预设到脚本变量中的游戏状态，购买与赠送是填充物品栏的游戏动作，时间线播放一段简短的讨价还价。
+//! 玩法绑定位于 `utils/shop.rs`，crate 的测试也会以无头方式驱动它；本文件只添加窗口与键盘控制。
+//!
+//! 操作：`Space` 继续，`1`-`4` 选择选项，`R` 再次进入商店。
+
+#[path = "utils/shop.rs"]
+mod shop;
+
+use bevy::prelude::*;
+use bevy_mortar_bond::prelude::*;
+use shop::{Inventory, SHOP_PATH, ShopPlugin, ShopStock, ShopkeeperPose};
+
+/// Text listing the presented options.
+#[derive(Component)]
+struct ChoiceList;
+
+/// Text showing gold, items and the shopkeeper's pose.
+#[derive(Component)]
+struct StatusLine;
+
+fn main() {
+    App::new()
+        .add_plugins((
+            DefaultPlugins,
+            MortarPlugin::default(),
+            MortarDialoguePlugin::default(),
+            ShopPlugin,
+        ))
+        .add_systems(Startup, (setup_ui, enter_shop).chain())
+        .add_systems(Update, (handle_keys, show_choices, show_status))
+        .run();
+}
+
+fn setup_ui(mut commands: Commands) {
+    commands.spawn(Camera2d);
+    commands
+        .spawn(Node {
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(16.0),
+            padding: UiRect::all(Val::Px(24.0)),
+            ..default()
+        })
+        .with_children(|root| {
+            root.spawn((Text::new(""), MortarTextTarget));
+            root.spawn((Text::new(""), ChoiceList));
+            root.spawn((Text::new(""), StatusLine));
+        });
+}
+
+fn enter_shop(
+    asset_server: Res<AssetServer>,
+    mut registry: ResMut<MortarRegistry>,
+    inventory: Res<Inventory>,
+    stock: Res<ShopStock>,
+    mut variables: ResMut<MortarDialogueVariables>,
+    mut events: MessageWriter<MortarCommand>,
+) {
+    registry.register(SHOP_PATH, asset_server.load(SHOP_PATH));
+    events.write(shop::open_shop(&inventory, &stock, &mut variables));
+}
+
+fn handle_keys(
+    keys: Res<ButtonInput<KeyCode>>,
+    inventory: Res<Inventory>,
+    stock: Res<ShopStock>,
+    mut variables: ResMut<MortarDialogueVariables>,
+    mut events: MessageWriter<MortarCommand>,
+) {
+    if keys.just_pressed(KeyCode::Space) {
+        events.write(MortarCommand::next_text());
+    }
+    let digits = [
+        KeyCode::Digit1,
+        KeyCode::Digit2,
+        KeyCode::Digit3,
+        KeyCode::Digit4,
+    ];
+    if let Some(index) = digits.iter().position(|key| keys.just_pressed(*key)) {
+        events.write(MortarCommand::select_choice(index));
+        events.write(MortarCommand::confirm_choice());
+    }
+    if keys.just_pressed(KeyCode::KeyR) {
+        events.write(MortarCommand::stop_dialogue());
+        events.write(shop::open_shop(&inventory, &stock, &mut variables));
+    }
+}
+
+fn show_choices(
+    presented: Res<MortarChoicesPresented>,
+    mut list: Single<&mut Text, With<ChoiceList>>,
+) {
+    if !presented.is_changed() {
+        return;
+    }
+    let lines: Vec<String> = presented
+        .views
+        .iter()
+        .filter(|view| view.kind == MortarChoiceViewKind::Choice)
+        .map(|view| match view.enabled {
+            true => format!("{}. {}", view.index + 1, view.text),
+            false => format!("{}. {} (sold out)", view.index + 1, view.text),
+        })
+        .collect();
+    list.0 = lines.join("\n");
+}
+
+fn show_status(
+    inventory: Res<Inventory>,
+    pose: Res<ShopkeeperPose>,
+    mut status: Single<&mut Text, (With<StatusLine>, Without<ChoiceList>)>,
+) {
+    if !inventory.is_changed() && !pose.is_changed() {
+        return;
+    }
+    let mut items: Vec<String> = inventory
+        .items
+        .iter()
+        .map(|(id, count)| format!("{id} x{count}"))
+        .collect();
+    items.sort();
+    status.0 = format!(
+        "Gold: {}  Items: [{}]  Shopkeeper: {}",
+        inventory.gold,
+        items.join(", "),
+        pose.0
+    );
+}
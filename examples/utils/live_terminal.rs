@@ -25,7 +25,7 @@ use bevy::{
     prelude::*,
     ui::widget::NodeImageMode,
 };
-use bevy_mortar_bond::MortarRuntime;
+use bevy_mortar_bond::prelude::*;
 use std::time::Duration;
 pub const DEFAULT_FILE: &str = "live_example.mortar";
 pub const ASSET_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/assets");
@@ -0,0 +1,196 @@
+//! Gameplay side of the shop example: the bindings `shop.mortar` needs and the inventory its
+//! actions fill. Kept apart from the window and UI so the headless tests drive the same code.
+//!
+//! 商店示例的玩法部分：`shop.mortar` 所需的绑定，以及其动作所填充的物品栏。与窗口和 UI 分开，
+//! 以便无头测试驱动同一份代码。
+
+use bevy::prelude::*;
+use bevy_mortar_bond::CoercionPolicy;
+use bevy_mortar_bond::prelude::*;
+use std::collections::HashMap;
+
+/// Path the shop conversation is registered under.
+pub const SHOP_PATH: &str = "shop.mortar";
+
+/// The player's gold and the items they own, by id. The game owns these; the conversation
+/// sees them as script variables.
+#[derive(Resource, Debug)]
+pub struct Inventory {
+    pub gold: f64,
+    pub items: HashMap<String, u32>,
+}
+
+impl Default for Inventory {
+    fn default() -> Self {
+        Self {
+            gold: 20.0,
+            items: HashMap::new(),
+        }
+    }
+}
+
+impl Inventory {
+    fn add(&mut self, id: &str, count: u32) {
+        *self.items.entry(id.to_owned()).or_default() += count;
+    }
+}
+
+/// Whether the shop's only elixir was sold. Kept by the game, so it outlives the conversation.
+#[derive(Resource, Debug, Default)]
+pub struct ShopStock {
+    pub sold_out: bool,
+}
+
+/// Last pose the shopkeeper was asked to strike.
+#[derive(Resource, Debug, Default)]
+pub struct ShopkeeperPose(pub String);
+
+/// Typed form of the `give_item(id, count)` action.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GiveItem {
+    pub id: String,
+    pub count: u32,
+}
+
+impl GiveItem {
+    /// Reads the action from a game event. `buy(item, count)` carries the same two arguments.
+    fn from_args(args: &[String]) -> Option<Self> {
+        let [id, count] = args else {
+            return None;
+        };
+        Some(Self {
+            id: id.clone(),
+            count: count.parse::<f64>().ok()? as u32,
+        })
+    }
+}
+
+#[derive(MortarFunctions)]
+struct ShopFunctions;
+
+#[mortar_functions]
+impl ShopFunctions {
+    fn price_of(item: MortarString) -> f64 {
+        match item.as_str() {
+            "potion" => 8.0,
+            "elixir" => 25.0,
+            _ => 0.0,
+        }
+    }
+}
+
+/// Opens the shop. Script variables only live while a conversation runs, so the values the
+/// game owns are seeded into them first.
+pub fn open_shop(
+    inventory: &Inventory,
+    stock: &ShopStock,
+    variables: &mut MortarDialogueVariables,
+) -> MortarCommand {
+    // Step 3: seed the variables the script reads from the game's own state. Without this,
+    // every visit would start from the `gold` and `in_stock` declared in `shop.mortar`.
+    //
+    // 第三步：用游戏自身的状态预设脚本读取的变量。否则每次进店都会从 `shop.mortar` 中声明的
+    // `gold` 与 `in_stock` 重新开始。
+    variables.seed("gold", MortarVariableValue::Number(inventory.gold));
+    variables.seed("in_stock", MortarVariableValue::Boolean(!stock.sold_out));
+    MortarCommand::start_node(SHOP_PATH, "Start")
+}
+
+/// Binds the shop's functions and handles the actions of `shop.mortar`.
+pub struct ShopPlugin;
+
+impl Plugin for ShopPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Inventory>()
+            .init_resource::<ShopStock>()
+            .init_resource::<ShopkeeperPose>()
+            .add_systems(Startup, bind_shop_functions)
+            .add_systems(
+                Update,
+                handle_shop_actions
+                    .after(MortarDialogueSystemSet::RunStatements)
+                    .before(MortarDialogueSystemSet::UpdateText),
+            );
+    }
+}
+
+fn bind_shop_functions(mut runtime: ResMut<MortarRuntime>) {
+    // Step 1: bind the functions the script calls. Prices are looked up by item name.
+    //
+    // 第一步：绑定脚本调用的函数，价格按物品名称查询。
+    ShopFunctions::bind_functions(&mut runtime.functions);
+
+    // Step 2: make the price lookup strict. A script passing `price_of(3)` is refused and
+    // reported as a `MortarFunctionError` instead of quietly pricing item "3" at zero.
+    //
+    // 第二步：让价格查询采用严格转换。脚本传入 `price_of(3)` 时调用会被拒绝，并以
+    // `MortarFunctionError` 报告，而不是把物品 "3" 悄悄定价为零。
+    runtime
+        .functions
+        .set_policy("price_of", CoercionPolicy::Strict);
+}
+
+fn handle_shop_actions(
+    mut events: MessageReader<MortarGameEvent>,
+    runtime: Res<MortarRuntime>,
+    mut variables: ResMut<MortarDialogueVariables>,
+    mut inventory: ResMut<Inventory>,
+    mut stock: ResMut<ShopStock>,
+    mut pose: ResMut<ShopkeeperPose>,
+) {
+    for event in events.read() {
+        match event.name.as_str() {
+            // Step 4: a purchase. The script already chose its line from `gold`; the game
+            // checks the price again before it takes the gold and hands over the item, then
+            // mirrors the new balance into the script.
+            //
+            // 第四步：购买。脚本已经根据 `gold` 选好了台词；游戏在扣除金币并交付物品前会再次
+            // 检查价格，然后把新的余额同步回脚本。
+            "buy" => {
+                let Some(purchase) = GiveItem::from_args(&event.args) else {
+                    continue;
+                };
+                let price = runtime.functions.try_call(
+                    "price_of",
+                    &[MortarString::from(purchase.id.as_str()).into()],
+                );
+                let Ok(MortarValue::Number(price)) = price else {
+                    continue;
+                };
+                let total = price.0 * f64::from(purchase.count);
+                if inventory.gold < total {
+                    info!(
+                        "Shop: {} costs {}, player has {}",
+                        purchase.id, total, inventory.gold
+                    );
+                    continue;
+                }
+                inventory.gold -= total;
+                inventory.add(&purchase.id, purchase.count);
+                stock.sold_out |= purchase.id == "elixir";
+                if let Some(state) = variables.state.as_mut() {
+                    state.set_number("gold", inventory.gold);
+                }
+            }
+            // Step 5: a typed action. `give_item(id, count)` arrives as strings and is
+            // read into `GiveItem` before it touches the inventory.
+            //
+            // 第五步：强类型动作。`give_item(id, count)` 以字符串形式到达，先读取为
+            // `GiveItem`，再修改物品栏。
+            "give_item" => {
+                if let Some(item) = GiveItem::from_args(&event.args) {
+                    inventory.add(&item.id, item.count);
+                }
+            }
+            // Step 6: the haggling timeline plays poses with durations between them.
+            //
+            // 第六步：讨价还价时间线按间隔依次播放姿势。
+            "shopkeeper_pose" => {
+                if let Some(name) = event.args.first() {
+                    pose.0.clone_from(name);
+                }
+            }
+            _ => {}
+        }
+    }
+}
@@ -7,15 +7,11 @@
 //! 这里集中处理布局和按钮交互，示例文件可专注于讲解绑定。
 
 use super::typewriter::{Typewriter, TypewriterState};
-use bevy::asset::Assets;
 use bevy::ecs::system::{Local, SystemParam};
 use bevy::log::info;
 use bevy::prelude::*;
 use bevy::ui::FlexDirection;
-use bevy_mortar_bond::{
-    DialogueState, MortarAsset, MortarDialogueSystemSet, MortarDialogueText, MortarEvent,
-    MortarEventBinding, MortarRegistry, MortarRunsExecuting, MortarRuntime, MortarTextTarget,
-};
+use bevy_mortar_bond::prelude::*;
 
 use crate::DialogueFiles;
 
@@ -35,6 +31,7 @@ impl Plugin for DialogueUiPlugin {
                 (
                     button_interaction_system,
                     handle_continue_button,
+                    show_finished_on_end,
                     handle_choice_buttons,
                     handle_reload_button,
                     handle_switch_file_button,
@@ -43,16 +40,7 @@ impl Plugin for DialogueUiPlugin {
                     update_button_states,
                 ),
             )
-            .add_systems(
-                Update,
-                (
-                    sync_typewriter_with_dialogue_texts.after(MortarDialogueSystemSet::UpdateText),
-                    update_event_binding_from_typewriter
-                        .after(sync_typewriter_with_dialogue_texts)
-                        .before(MortarDialogueSystemSet::TriggerEvents),
-                ),
-            )
-            .add_systems(PostUpdate, apply_typewriter_output_to_texts);
+            .add_plugins(MortarTypewriterPlugin::<Typewriter>::default());
     }
 }
 
@@ -76,6 +64,12 @@ pub struct ChoiceButton {
 #[derive(Component)]
 pub struct ChoiceContainer;
 
+/// A run of choice buttons under one heading, laid out in the group's columns.
+///
+/// 同一标题下的一组选项按钮，按选项组的列数排布。
+#[derive(Component)]
+struct ChoiceSection;
+
 /// A component for the "Continue" button.
 ///
 /// "继续"按钮的组件。
@@ -135,8 +129,7 @@ type SwitchButtonFilter = (
 #[derive(SystemParam)]
 struct ChoiceButtonResources<'w> {
     asset_server: Res<'w, AssetServer>,
-    registry: Res<'w, MortarRegistry>,
-    assets: Res<'w, Assets<MortarAsset>>,
+    presented: Res<'w, MortarChoicesPresented>,
 }
 
 /// Snapshot of choice selection state to detect changes.
@@ -196,6 +189,7 @@ pub fn setup_dialogue_ui(mut commands: Commands, asset_server: Res<AssetServer>)
                         DialogueText,
                         MortarTextTarget,
                         Typewriter::new("", TYPEWRITER_SPEED),
+                        MortarTypewriterAdapter::new(TYPEWRITER_SPEED),
                     ));
                 });
 
@@ -319,104 +313,32 @@ pub fn setup_dialogue_ui(mut commands: Commands, asset_server: Res<AssetServer>)
         });
 }
 
-fn sync_typewriter_with_dialogue_texts(
-    mut query: Query<
-        (&MortarDialogueText, &mut Typewriter),
-        (Changed<MortarDialogueText>, With<DialogueText>),
-    >,
-) {
-    for (dialogue_text, mut typewriter) in &mut query {
-        *typewriter = Typewriter::new(dialogue_text.body.clone(), TYPEWRITER_SPEED);
-        typewriter.play();
+impl MortarTypewriter for Typewriter {
+    fn start(&mut self, text: &str, seconds_per_char: f32, auto_play: bool) {
+        *self = Typewriter::new(text, seconds_per_char);
+        if auto_play {
+            self.play();
+        }
     }
-}
 
-fn apply_typewriter_output_to_texts(
-    mut query: Query<(&Typewriter, &MortarDialogueText, &mut Text), With<DialogueText>>,
-) {
-    for (typewriter, dialogue_text, mut text) in &mut query {
-        **text = format!("{}{}", dialogue_text.header, typewriter.current_text);
+    fn typed_chars(&self) -> usize {
+        self.current_char_index
     }
-}
 
-fn update_event_binding_from_typewriter(
-    mut query: Query<(&Typewriter, Option<&mut MortarEventBinding>), With<DialogueText>>,
-) {
-    for (typewriter, binding) in &mut query {
-        if let Some(mut binding) = binding {
-            binding.current_index = typewriter.current_char_index as f32;
-        }
+    fn fast_forward(&mut self) {
+        self.current_text = self.source_text.clone();
+        self.current_char_index = self.source_text.chars().count();
+        self.state = TypewriterState::Finished;
     }
 }
 
 fn show_finished_message(
     dialogue_text_query: &mut Query<&mut MortarDialogueText, With<DialogueText>>,
-    text_query: &mut Query<&mut Text, With<DialogueText>>,
-    typewriter_query: &mut Query<&mut Typewriter, With<DialogueText>>,
 ) {
     if let Ok(mut dialogue_text) = dialogue_text_query.single_mut() {
         dialogue_text.header.clear();
         dialogue_text.body = FINISHED_TEXT.to_string();
     }
-
-    if let Ok(mut text) = text_query.single_mut() {
-        **text = FINISHED_TEXT.to_string();
-    }
-
-    if let Ok(mut typewriter) = typewriter_query.single_mut() {
-        typewriter.source_text = FINISHED_TEXT.to_string();
-        typewriter.current_text = FINISHED_TEXT.to_string();
-        typewriter.current_char_index = FINISHED_TEXT.chars().count();
-        typewriter.timer.reset();
-        typewriter.state = TypewriterState::Finished;
-    }
-}
-
-#[derive(Copy, Clone, PartialEq, Eq)]
-enum ChoiceFinishKind {
-    None,
-    Immediate,
-    NeedsNextText,
-}
-
-fn determine_choice_finish_kind(state: &DialogueState, choice_index: usize) -> ChoiceFinishKind {
-    let Some(choices) = state.get_current_choices().or_else(|| state.get_choices()) else {
-        return ChoiceFinishKind::None;
-    };
-    let Some(choice) = choices.get(choice_index) else {
-        return ChoiceFinishKind::None;
-    };
-
-    if choice.choice.is_some() {
-        return ChoiceFinishKind::None;
-    }
-
-    if let Some(action) = choice.action.as_deref() {
-        return match action {
-            "return" => ChoiceFinishKind::Immediate,
-            "break" => {
-                let has_more_text = state.has_next_text();
-                let has_follow_up_node =
-                    matches!(state.get_next_node(), Some(next) if next != "return");
-                if !has_more_text && !has_follow_up_node {
-                    ChoiceFinishKind::NeedsNextText
-                } else {
-                    ChoiceFinishKind::None
-                }
-            }
-            _ => ChoiceFinishKind::Immediate,
-        };
-    }
-
-    if let Some(next_node) = choice.next.as_deref() {
-        if next_node == "return" {
-            ChoiceFinishKind::Immediate
-        } else {
-            ChoiceFinishKind::None
-        }
-    } else {
-        ChoiceFinishKind::Immediate
-    }
 }
 
 /// Handles the visual feedback for button interactions.
@@ -507,82 +429,52 @@ pub fn button_interaction_system(
     }
 }
 
-fn apply_choice_finish(
-    finish_kind: ChoiceFinishKind,
-    events: &mut MessageWriter<MortarEvent>,
-    dialogue_text_query: &mut Query<&mut MortarDialogueText, With<DialogueText>>,
-    text_query: &mut Query<&mut Text, With<DialogueText>>,
-    typewriter_query: &mut Query<&mut Typewriter, With<DialogueText>>,
+/// Shows the end message once a dialogue finishes, whether by advancing past its last line or
+/// by confirming a choice that leaves it.
+///
+/// 对话结束时显示结束提示，无论是推进越过最后一行，还是确认了离开对话的选项。
+fn show_finished_on_end(
+    mut finished: MessageReader<MortarDialogueFinished>,
+    mut dialogue_text_query: Query<&mut MortarDialogueText, With<DialogueText>>,
 ) {
-    match finish_kind {
-        ChoiceFinishKind::Immediate => {
-            info!("Example: Choice ends dialogue immediately");
-            show_finished_message(dialogue_text_query, text_query, typewriter_query);
-        }
-        ChoiceFinishKind::NeedsNextText => {
-            info!("Example: Choice ends dialogue after break; advancing");
-            events.write(MortarEvent::NextText { target: None });
-            show_finished_message(dialogue_text_query, text_query, typewriter_query);
-        }
-        ChoiceFinishKind::None => {}
+    if finished.read().last().is_some() {
+        info!("Example: Dialogue finished; showing end message");
+        show_finished_message(&mut dialogue_text_query);
     }
 }
+
 /// Handles clicks on the "Continue" button.
 ///
 /// 处理“继续”按钮点击。
 fn handle_continue_button(
     interaction_query: Query<&Interaction, (Changed<Interaction>, With<ContinueButton>)>,
-    mut events: MessageWriter<MortarEvent>,
+    mut events: MessageWriter<MortarCommand>,
     runtime: Res<MortarRuntime>,
-    runs_executing: Res<MortarRunsExecuting>,
+    presented: Res<MortarChoicesPresented>,
     mut dialogue_text_query: Query<&mut MortarDialogueText, With<DialogueText>>,
-    mut text_query: Query<&mut Text, With<DialogueText>>,
-    mut typewriter_query: Query<&mut Typewriter, With<DialogueText>>,
 ) {
-    if runs_executing.executing {
-        return;
-    }
-
     for interaction in &interaction_query {
         if *interaction != Interaction::Pressed {
             continue;
         }
 
-        let Some(state) = runtime.primary_dialogue() else {
-            show_finished_message(
-                &mut dialogue_text_query,
-                &mut text_query,
-                &mut typewriter_query,
-            );
-            continue;
-        };
-
-        if let Some(choice_index) = state.selected_choice {
-            info!("Example: Confirming choice selection");
-            let finish_kind = determine_choice_finish_kind(state, choice_index);
-            events.write(MortarEvent::ConfirmChoice { target: None });
-            apply_choice_finish(
-                finish_kind,
-                &mut events,
-                &mut dialogue_text_query,
-                &mut text_query,
-                &mut typewriter_query,
-            );
-            continue;
-        }
-
-        if state.has_next_text() {
-            events.write(MortarEvent::NextText { target: None });
-        } else if state.has_choices() && !state.choices_broken {
-            info!("Example: Waiting for choice resolution before finishing");
-        } else {
-            events.write(MortarEvent::NextText { target: None });
-            info!("Example: Dialogue finished; showing end message");
-            show_finished_message(
-                &mut dialogue_text_query,
-                &mut text_query,
-                &mut typewriter_query,
-            );
+        let intent = runtime.advance_intent();
+        match intent {
+            AdvanceIntent::ConfirmChoice { .. } => {
+                info!("Example: Confirming choice selection");
+                events.write(MortarCommand::confirm_choice_in(presented.group_token));
+            }
+            AdvanceIntent::NeedsSelection => {
+                info!("Example: Waiting for choice resolution before finishing");
+            }
+            AdvanceIntent::Nothing { .. } if runtime.primary_dialogue().is_none() => {
+                show_finished_message(&mut dialogue_text_query);
+            }
+            _ => {
+                if let Some(event) = intent.event() {
+                    events.write(event);
+                }
+            }
         }
     }
 }
@@ -592,15 +484,16 @@ fn handle_continue_button(
 /// 处理选项按钮点击。
 fn handle_choice_buttons(
     choice_query: Query<(&Interaction, &ChoiceButton), Changed<Interaction>>,
-    mut events: MessageWriter<MortarEvent>,
+    presented: Res<MortarChoicesPresented>,
+    mut events: MessageWriter<MortarCommand>,
 ) {
     for (interaction, choice_button) in &choice_query {
         if *interaction == Interaction::Pressed {
             info!("Example: Choice button {} pressed", choice_button.index);
-            events.write(MortarEvent::SelectChoice {
-                index: choice_button.index,
-                target: None,
-            });
+            events.write(
+                MortarCommand::select_choice_in(presented.group_token, choice_button.index)
+                    .from_source(ChoiceInputSource::Pointer),
+            );
         }
     }
 }
@@ -613,10 +506,11 @@ fn manage_choice_buttons(
     runtime: Res<MortarRuntime>,
     container_query: Query<Entity, With<ChoiceContainer>>,
     button_query: Query<Entity, With<ChoiceButton>>,
+    section_query: Query<Entity, With<ChoiceSection>>,
     resources: ChoiceButtonResources,
     mut last_state: Local<ChoiceUiState>,
 ) {
-    if !runtime.is_changed() {
+    if !runtime.is_changed() && !resources.presented.is_changed() {
         return;
     }
 
@@ -631,90 +525,166 @@ fn manage_choice_buttons(
         choices_broken: state.choices_broken,
     });
 
-    if *last_state == current_state && !button_query.is_empty() {
+    if *last_state == current_state && !button_query.is_empty() && !resources.presented.is_changed()
+    {
         return;
     }
 
     *last_state = current_state.clone();
 
-    for entity in button_query.iter() {
+    for entity in section_query.iter() {
         commands.entity(entity).despawn();
     }
 
-    if let Some(state) = runtime.primary_dialogue()
-        && let Some(choices) = state.get_choices()
-    {
-        let should_show_choices = !state.has_next_text_before_choice();
-        if !should_show_choices {
-            return;
-        }
+    let Some(state) = runtime.primary_dialogue() else {
+        return;
+    };
+    if state.get_choices().is_none() || state.has_next_text_before_choice() {
+        return;
+    }
 
-        let font = resources.asset_server.load("font/Unifont.otf");
-        let function_decls = resources
-            .registry
-            .get(&state.mortar_path)
-            .and_then(|handle| resources.assets.get(handle))
-            .map(|asset| asset.data.functions.as_slice())
-            .unwrap_or(&[]);
-
-        for (index, choice) in choices.iter().enumerate() {
-            let is_selected = state.selected_choice == Some(index);
-            let is_enabled = choice
-                .condition
-                .as_ref()
-                .map(|cond| {
-                    bevy_mortar_bond::evaluate_condition(cond, &runtime.functions, function_decls)
-                })
-                .unwrap_or(true);
+    let font = resources.asset_server.load("font/Unifont.otf");
+    // The script's `ui` hints: the group sets the columns, and consecutive options sharing a
+    // `heading` are grouped under it. Views already follow any `order` hints.
+    //
+    // 脚本的 `ui` 提示：选项组设置列数，连续且 `heading` 相同的选项归入同一标题下。视图已按
+    // `order` 提示排好序。
+    let columns = u16::from(
+        resources
+            .presented
+            .group_ui_hints()
+            .columns()
+            .unwrap_or(1)
+            .max(1),
+    );
+    let mut sections: Vec<(Option<String>, Vec<&MortarChoiceView>)> = Vec::new();
+    let views = resources.presented.views.iter();
+    for view in views.filter(|view| view.kind == MortarChoiceViewKind::Choice) {
+        let heading = view.ui_hints().heading();
+        match sections.last_mut() {
+            Some((current, items)) if *current == heading => items.push(view),
+            _ => sections.push((heading, vec![view])),
+        }
+    }
 
-            let (bg_color, border_color, text_color) = if !is_enabled {
-                (
-                    Color::srgb(0.15, 0.15, 0.15),
-                    Color::srgb(0.25, 0.25, 0.25),
-                    Color::srgb(0.4, 0.4, 0.4),
-                )
-            } else if is_selected {
-                (
-                    Color::srgb(0.4, 0.6, 0.2),
-                    Color::srgb(0.6, 0.9, 0.3),
-                    Color::srgb(1.0, 1.0, 1.0),
-                )
-            } else {
-                (
-                    Color::srgb(0.2, 0.25, 0.35),
-                    Color::srgb(0.4, 0.5, 0.65),
-                    Color::srgb(0.85, 0.85, 0.85),
-                )
+    for (heading, items) in sections {
+        commands.entity(container).with_children(|parent| {
+            let layout = SectionLayout {
+                columns,
+                font: font.clone(),
             };
+            spawn_choice_section(parent, &layout, heading, &items, state);
+        });
+    }
+}
 
-            commands.entity(container).with_children(|parent| {
-                parent
-                    .spawn((
-                        Button,
-                        Node {
-                            width: Val::Percent(100.0),
-                            height: Val::Px(60.0),
-                            justify_content: JustifyContent::Center,
-                            align_items: AlignItems::Center,
-                            border: UiRect::all(Val::Px(3.0)),
-                            ..default()
-                        },
-                        BackgroundColor(bg_color),
-                        BorderColor::all(border_color),
-                        ChoiceButton { index },
-                    ))
-                    .with_child((
-                        Text::new(&choice.text),
-                        TextFont {
-                            font: font.clone(),
-                            font_size: 20.0,
-                            ..default()
-                        },
-                        TextColor(text_color),
-                    ));
-            });
+/// What every section of one choice group shares.
+///
+/// 同一选项组中所有分区共享的设置。
+struct SectionLayout {
+    columns: u16,
+    font: Handle<Font>,
+}
+
+/// Spawns a grid of choice buttons, with its heading spanning every column.
+///
+/// 生成一组网格排布的选项按钮，其标题横跨所有列。
+fn spawn_choice_section(
+    parent: &mut ChildSpawnerCommands,
+    layout: &SectionLayout,
+    heading: Option<String>,
+    items: &[&MortarChoiceView],
+    state: &DialogueState,
+) {
+    let mut section = parent.spawn((
+        Node {
+            display: Display::Grid,
+            width: Val::Percent(100.0),
+            grid_template_columns: RepeatedGridTrack::flex(layout.columns, 1.0),
+            column_gap: Val::Px(10.0),
+            row_gap: Val::Px(10.0),
+            ..default()
+        },
+        ChoiceSection,
+    ));
+    section.with_children(|section| {
+        if let Some(heading) = heading {
+            section.spawn((
+                Text::new(heading),
+                TextFont {
+                    font: layout.font.clone(),
+                    font_size: 18.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(0.7, 0.75, 0.85)),
+                Node {
+                    grid_column: GridPlacement::span(layout.columns),
+                    ..default()
+                },
+            ));
         }
-    }
+        for view in items {
+            spawn_choice_button(section, state, view, layout.font.clone());
+        }
+    });
+}
+
+/// Spawns the button of one presented option.
+///
+/// 生成单个已呈现选项的按钮。
+fn spawn_choice_button(
+    section: &mut ChildSpawnerCommands,
+    state: &DialogueState,
+    view: &MortarChoiceView,
+    font: Handle<Font>,
+) {
+    let is_selected = state.selected_choice == Some(view.index);
+    // Enablement is kept up to date by the plugin as variables change.
+    //
+    // 可用状态由插件随变量变化实时维护。
+    let (bg_color, border_color, text_color) = if !view.enabled {
+        (
+            Color::srgb(0.15, 0.15, 0.15),
+            Color::srgb(0.25, 0.25, 0.25),
+            Color::srgb(0.4, 0.4, 0.4),
+        )
+    } else if is_selected {
+        (
+            Color::srgb(0.4, 0.6, 0.2),
+            Color::srgb(0.6, 0.9, 0.3),
+            Color::srgb(1.0, 1.0, 1.0),
+        )
+    } else {
+        (
+            Color::srgb(0.2, 0.25, 0.35),
+            Color::srgb(0.4, 0.5, 0.65),
+            Color::srgb(0.85, 0.85, 0.85),
+        )
+    };
+
+    section
+        .spawn((
+            Button,
+            Node {
+                height: Val::Px(60.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                border: UiRect::all(Val::Px(3.0)),
+                ..default()
+            },
+            BackgroundColor(bg_color),
+            BorderColor::all(border_color),
+            ChoiceButton { index: view.index },
+        ))
+        .with_child((
+            Text::new(&view.text),
+            TextFont {
+                font,
+                font_size: 20.0,
+                ..default()
+            },
+            TextColor(text_color),
+        ));
 }
 
 /// Highlights choice buttons when the selection changes.
@@ -784,7 +754,12 @@ fn update_button_states(
         };
 
         if state.has_choices() && !state.has_next_text() {
-            let has_selection = state.selected_choice.is_some();
+            // Only a selection still waiting for its confirm needs the button; under a one-step
+            // confirm mode the click already confirmed it.
+            let has_selection = matches!(
+                runtime.advance_intent(),
+                AdvanceIntent::ConfirmChoice { .. }
+            );
             *visibility = if has_selection {
                 Visibility::Visible
             } else {
@@ -807,7 +782,7 @@ fn handle_reload_button(
     interaction_query: Query<&Interaction, (Changed<Interaction>, With<ReloadButton>)>,
     asset_server: Res<AssetServer>,
     mut registry: ResMut<MortarRegistry>,
-    mut events: MessageWriter<MortarEvent>,
+    mut events: MessageWriter<MortarCommand>,
     dialogue_files: Res<DialogueFiles>,
     runtime: Res<MortarRuntime>,
 ) {
@@ -816,7 +791,7 @@ fn handle_reload_button(
             let path = dialogue_files.current().to_string();
             info!("Example: Reload file: {}", &path);
 
-            events.write(MortarEvent::StopDialogue { target: None });
+            events.write(MortarCommand::StopDialogue { target: None });
 
             let handle = asset_server.load(&path);
             registry.register(path.clone(), handle);
@@ -827,11 +802,7 @@ fn handle_reload_button(
                 .unwrap_or_else(|| "Start".to_string());
 
             info!("Example: Restart node {} / {}", &path, &start_node);
-            events.write(MortarEvent::StartNode {
-                path,
-                node: start_node,
-                target: None,
-            });
+            events.write(MortarCommand::start_node(path, start_node));
         }
     }
 }
@@ -843,12 +814,12 @@ fn handle_switch_file_button(
     interaction_query: Query<&Interaction, (Changed<Interaction>, With<SwitchFileButton>)>,
     asset_server: Res<AssetServer>,
     mut registry: ResMut<MortarRegistry>,
-    mut events: MessageWriter<MortarEvent>,
+    mut events: MessageWriter<MortarCommand>,
     mut dialogue_files: ResMut<DialogueFiles>,
 ) {
     for interaction in &interaction_query {
         if *interaction == Interaction::Pressed {
-            events.write(MortarEvent::StopDialogue { target: None });
+            events.write(MortarCommand::StopDialogue { target: None });
 
             dialogue_files.next();
             let path = dialogue_files.current().to_string();
@@ -859,11 +830,7 @@ fn handle_switch_file_button(
 
             const START_NODE: &str = "Start";
             info!("Example: Start a new file node: {} / {}", &path, START_NODE);
-            events.write(MortarEvent::StartNode {
-                path,
-                node: START_NODE.to_string(),
-                target: None,
-            });
+            events.write(MortarCommand::start_node(path, START_NODE));
         }
     }
 }
@@ -0,0 +1,286 @@
+//! # animation.rs
+//!
+//! # animation.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Optional bridge from Mortar game events to Bevy animation. A [`MortarAnimationMap`] on a
+//! character rig maps script animation names to nodes of its animation graph, and
+//! [`MortarAnimationPlugin`] plays them through [`AnimationTransitions`] whenever a
+//! `set_animation("name")` or `play_anim("name")` event fires. The rig is picked by the event's
+//! explicit [`MortarGameEvent::targets`] (a second argument written `@name`), then by the second
+//! argument as the rig's [`Name`], then by the event source, then by being the only mapped rig.
+//! When an entry declares a duration, or the event passes one as its third argument, the map's
+//! `revert_to` entry plays once it elapses. Names with no entry are logged and collected in
+//! [`MortarAnimationDiagnostics`].
+//!
+//! Mortar 游戏事件到 Bevy 动画的可选桥接。角色骨架上的 [`MortarAnimationMap`] 把脚本中的动画名称映
+//! 射到其动画图的节点，每当 `set_animation("name")` 或 `play_anim("name")` 事件触发时，
+//! [`MortarAnimationPlugin`] 通过 [`AnimationTransitions`] 播放对应动画。骨架依次按事件的显式
+//! [`MortarGameEvent::targets`]（写作 `@name` 的第二个参数）、作为骨架 [`Name`] 的第二个参数、事件
+//! 来源、以及是否为唯一带映射的骨架来选择。当条目声明了时长或事件以第三个参数传入时长时，时长结束后
+//! 会播放映射的 `revert_to` 条目。没有条目的名称会被记录日志并收集到
+//! [`MortarAnimationDiagnostics`] 中。
+
+use crate::MortarGameEvent;
+use crate::debug::LOG_EVENTS;
+use bevy::animation::graph::AnimationNodeIndex;
+use bevy::animation::transition::AnimationTransitions;
+use bevy::animation::{AnimationPlayer, RepeatAnimation};
+use bevy::prelude::*;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::time::Duration;
+
+/// One animation a script can ask for.
+///
+/// 脚本可以请求的一个动画。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MortarAnimationEntry {
+    pub node: AnimationNodeIndex,
+    /// Cross-fade from the previous animation.
+    ///
+    /// 从上一个动画交叉淡入的时长。
+    pub transition: Duration,
+    pub repeat: RepeatAnimation,
+    /// Plays the map's `revert_to` entry after this long, unless the event passes a duration.
+    ///
+    /// 经过该时长后播放映射的 `revert_to` 条目，除非事件传入了时长。
+    pub duration: Option<Duration>,
+}
+
+impl MortarAnimationEntry {
+    /// Plays `node` once, switching instantly.
+    ///
+    /// 播放一次 `node`，立即切换。
+    pub fn new(node: AnimationNodeIndex) -> Self {
+        Self {
+            node,
+            transition: Duration::ZERO,
+            repeat: RepeatAnimation::Never,
+            duration: None,
+        }
+    }
+
+    pub fn with_transition(mut self, transition: Duration) -> Self {
+        self.transition = transition;
+        self
+    }
+
+    pub fn with_repeat(mut self, repeat: RepeatAnimation) -> Self {
+        self.repeat = repeat;
+        self
+    }
+
+    pub fn with_duration(mut self, duration: Duration) -> Self {
+        self.duration = Some(duration);
+        self
+    }
+}
+
+/// Maps script animation names to the animation graph of a rig.
+///
+/// 将脚本中的动画名称映射到骨架的动画图。
+#[derive(Component, Debug, Clone, Default)]
+pub struct MortarAnimationMap {
+    pub entries: HashMap<String, MortarAnimationEntry>,
+    /// Entry played when a timed animation ends, usually an idle loop.
+    ///
+    /// 定时动画结束时播放的条目，通常是待机循环。
+    pub revert_to: Option<String>,
+    /// Entity holding the [`AnimationPlayer`]; the map's own entity when `None`.
+    ///
+    /// 持有 [`AnimationPlayer`] 的实体；为 `None` 时即映射所在实体。
+    pub player: Option<Entity>,
+}
+
+impl MortarAnimationMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(mut self, name: impl Into<String>, entry: MortarAnimationEntry) -> Self {
+        self.entries.insert(name.into(), entry);
+        self
+    }
+
+    pub fn with_revert_to(mut self, name: impl Into<String>) -> Self {
+        self.revert_to = Some(name.into());
+        self
+    }
+
+    pub fn with_player(mut self, player: Entity) -> Self {
+        self.player = Some(player);
+        self
+    }
+}
+
+/// Settings for the animation bridge.
+///
+/// 动画桥接的设置。
+#[derive(Resource, Debug, Clone, PartialEq, Eq)]
+pub struct MortarAnimationSettings {
+    /// Game event names that play an animation.
+    ///
+    /// 会播放动画的游戏事件名称。
+    pub event_names: HashSet<String>,
+}
+
+impl Default for MortarAnimationSettings {
+    fn default() -> Self {
+        Self {
+            event_names: HashSet::from(["set_animation".to_owned(), "play_anim".to_owned()]),
+        }
+    }
+}
+
+/// Animation names scripts asked for that no map knows.
+///
+/// 脚本请求了但没有任何映射认识的动画名称。
+#[derive(Resource, Debug, Clone, Default)]
+pub struct MortarAnimationDiagnostics {
+    pub unmapped: BTreeSet<String>,
+}
+
+/// Counts down to the `revert_to` entry of the rig's map.
+///
+/// 倒计时，结束后播放骨架映射的 `revert_to` 条目。
+#[derive(Component, Debug, Clone)]
+pub struct MortarAnimationRevert(pub Timer);
+
+/// Plays mapped animations for Mortar game events.
+///
+/// 为 Mortar 游戏事件播放映射的动画。
+pub struct MortarAnimationPlugin;
+
+impl Plugin for MortarAnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_message::<MortarGameEvent>()
+            .init_resource::<MortarAnimationSettings>()
+            .init_resource::<MortarAnimationDiagnostics>()
+            .add_systems(
+                Update,
+                (play_animation_events, revert_timed_animations).chain(),
+            );
+    }
+}
+
+fn arg(event: &MortarGameEvent, index: usize) -> Option<&str> {
+    event
+        .args
+        .get(index)
+        .map(|arg| arg.trim().trim_matches('"'))
+        .filter(|arg| !arg.is_empty())
+}
+
+/// Picks the rig an event is meant for.
+fn resolve_rig(
+    event: &MortarGameEvent,
+    rigs: &Query<(Entity, &MortarAnimationMap, Option<&Name>)>,
+) -> Option<Entity> {
+    if let Some(target) = event.targets.iter().find(|target| rigs.contains(**target)) {
+        return Some(*target);
+    }
+    let bare_target = event
+        .args
+        .get(1)
+        .is_some_and(|raw| crate::dialogue::target_name(raw).is_some());
+    if let Some(name) = arg(event, 1).filter(|_| !bare_target) {
+        return rigs
+            .iter()
+            .find(|(_, _, rig_name)| rig_name.is_some_and(|rig_name| rig_name.as_str() == name))
+            .map(|(entity, ..)| entity);
+    }
+    if let Some(source) = event.source
+        && rigs.contains(source)
+    {
+        return Some(source);
+    }
+    rigs.single().ok().map(|(entity, ..)| entity)
+}
+
+fn play_entry(
+    entry: &MortarAnimationEntry,
+    player: Entity,
+    players: &mut Query<(&mut AnimationPlayer, &mut AnimationTransitions)>,
+) -> bool {
+    let Ok((mut animation_player, mut transitions)) = players.get_mut(player) else {
+        warn!(target: LOG_EVENTS, "Animation player {player} has no AnimationTransitions");
+        return false;
+    };
+    transitions
+        .play(&mut animation_player, entry.node, entry.transition)
+        .set_repeat(entry.repeat);
+    true
+}
+
+fn play_animation_events(
+    mut commands: Commands,
+    settings: Res<MortarAnimationSettings>,
+    mut diagnostics: ResMut<MortarAnimationDiagnostics>,
+    mut events: MessageReader<MortarGameEvent>,
+    rigs: Query<(Entity, &MortarAnimationMap, Option<&Name>)>,
+    mut players: Query<(&mut AnimationPlayer, &mut AnimationTransitions)>,
+) {
+    for event in events.read() {
+        if !settings.event_names.contains(&event.name) {
+            continue;
+        }
+        let Some(name) = arg(event, 0) else {
+            warn!(target: LOG_EVENTS, "'{}' needs an animation name", event.name);
+            continue;
+        };
+        let Some(rig) = resolve_rig(event, &rigs) else {
+            warn!(target: LOG_EVENTS, "No animation rig found for '{name}'");
+            continue;
+        };
+        let Ok((_, map, _)) = rigs.get(rig) else {
+            continue;
+        };
+        let Some(entry) = map.entries.get(name) else {
+            warn!(target: LOG_EVENTS, "Animation '{name}' is not mapped on {rig}");
+            diagnostics.unmapped.insert(name.to_owned());
+            continue;
+        };
+        if !play_entry(entry, map.player.unwrap_or(rig), &mut players) {
+            continue;
+        }
+        let duration = arg(event, 2)
+            .and_then(|seconds| seconds.parse::<f32>().ok())
+            .map(Duration::from_secs_f32)
+            .or(entry.duration);
+        match duration {
+            Some(duration) if map.revert_to.is_some() => {
+                commands
+                    .entity(rig)
+                    .insert(MortarAnimationRevert(Timer::new(duration, TimerMode::Once)));
+            }
+            _ => {
+                commands.entity(rig).remove::<MortarAnimationRevert>();
+            }
+        }
+    }
+}
+
+fn revert_timed_animations(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut rigs: Query<(Entity, &MortarAnimationMap, &mut MortarAnimationRevert)>,
+    mut players: Query<(&mut AnimationPlayer, &mut AnimationTransitions)>,
+) {
+    for (rig, map, mut revert) in &mut rigs {
+        if !revert.0.tick(time.delta()).is_finished() {
+            continue;
+        }
+        commands.entity(rig).remove::<MortarAnimationRevert>();
+        let Some(entry) = map
+            .revert_to
+            .as_ref()
+            .and_then(|name| map.entries.get(name))
+        else {
+            continue;
+        };
+        play_entry(entry, map.player.unwrap_or(rig), &mut players);
+    }
+}
@@ -4,10 +4,54 @@
 
 use bevy::asset::io::Reader;
 use bevy::asset::{Asset, AssetLoader, LoadContext};
-use bevy::prelude::TypePath;
+use bevy::prelude::{TypePath, warn};
 use bevy::tasks::ConditionalSendFuture;
-use mortar_compiler::{Deserializer, Language, MortaredData, ParseHandler, Serializer};
+use mortar_compiler::{
+    Deserializer, DiagnosticCollector, Language, MortaredData, Node, ParseHandler, Serializer,
+    Severity,
+};
 use std::path::Path;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::debug::LOG_ASSET;
+use crate::{MortarIssueSeverity, MortarLint, MortarLintConfig, MortarValidationIssue};
+
+/// Source of [`MortarAsset`] revisions; every constructed asset takes the next one.
+static NEXT_REVISION: AtomicU64 = AtomicU64::new(0);
+
+/// Error type produced while decoding Mortar files.
+///
+/// 解码 Mortar 文件时产生的错误类型。
+pub type LoadError = Box<dyn std::error::Error + Send + Sync>;
+
+mod definitions;
+#[cfg(feature = "tools")]
+mod graph;
+mod last_good;
+mod metadata;
+mod overlay;
+mod parsed_nodes;
+mod progress;
+#[cfg(feature = "tools")]
+mod strings;
+
+#[cfg(test)]
+pub(crate) use definitions::comparisons;
+
+#[cfg(feature = "tools")]
+pub use graph::GraphFormat;
+pub use last_good::MortarLastGood;
+pub(crate) use last_good::retain_last_good_versions;
+pub use metadata::MortarMetadata;
+pub use overlay::MortarOverlayShadow;
+pub(crate) use overlay::merge_layers;
+pub(crate) use progress::{LoadStageQueue, drain_load_stages};
+pub use progress::{MortarAssetLoadStage, MortarLoadStage};
+#[cfg(feature = "tools")]
+pub use strings::{StringsExport, StringsFormat};
+#[cfg(feature = "tools")]
+pub(crate) use strings::{render_rows, string_rows};
 
 /// A Bevy asset representing a Mortar dialogue file.
 ///
@@ -18,15 +62,217 @@ pub struct MortarAsset {
     ///
     /// 从 Mortar 文件解析的数据。
     pub data: MortaredData,
+    /// Author-provided file header (title, author, version, custom keys).
+    ///
+    /// 作者提供的文件头部信息（标题、作者、版本、自定义键）。
+    pub metadata: MortarMetadata,
+    /// Lints found when the asset loaded, see [`MortarAsset::lints`].
+    lints: Vec<MortarLint>,
+    /// Validation findings of the load-time analysis, see [`MortarAsset::validation_issues`].
+    issues: Vec<MortarValidationIssue>,
+    /// Whether the load-time analysis of this version has completed.
+    analyzed: bool,
+    /// Identifies this version of the file; a hot reload replaces the asset with a new revision.
+    revision: u64,
+    /// Name lookup tables, built on first use.
+    definitions: OnceLock<definitions::MortarDefinitions>,
+    /// Parsed nodes, see [`MortarAsset::parsed_node`].
+    parsed_nodes: OnceLock<parsed_nodes::ParsedNodes>,
+}
+
+impl MortarAsset {
+    /// Wraps compiled data with empty metadata.
+    ///
+    /// 用空元数据包装编译后的数据。
+    pub fn new(data: MortaredData) -> Self {
+        Self::with_metadata(data, MortarMetadata::default())
+    }
+
+    pub(crate) fn with_metadata(data: MortaredData, metadata: MortarMetadata) -> Self {
+        Self {
+            data,
+            metadata,
+            lints: Vec::new(),
+            issues: Vec::new(),
+            analyzed: false,
+            revision: NEXT_REVISION.fetch_add(1, Ordering::Relaxed),
+            definitions: OnceLock::new(),
+            parsed_nodes: OnceLock::new(),
+        }
+    }
+
+    /// Lints reported for this asset, by the load-time pass or [`MortarAsset::lint`].
+    ///
+    /// 此资源的 lint 结果，来自加载时的 lint 流程或 [`MortarAsset::lint`]。
+    pub fn lints(&self) -> &[MortarLint] {
+        &self.lints
+    }
+
+    /// Lints the asset with `config` and keeps the report.
+    ///
+    /// 使用 `config` 对资源进行 lint 并保存报告。
+    pub fn lint(&mut self, config: &MortarLintConfig) -> &[MortarLint] {
+        self.lints = crate::lint_mortared_data(&self.data, config);
+        &self.lints
+    }
+
+    /// Validation findings of the latest completed load-time analysis; empty until
+    /// [`MortarAsset::is_analyzed`].
+    ///
+    /// 最近一次完成的加载时分析得到的校验结果；在 [`MortarAsset::is_analyzed`] 之前为空。
+    pub fn validation_issues(&self) -> &[MortarValidationIssue] {
+        &self.issues
+    }
+
+    /// Whether the load-time analysis of this version of the file has completed.
+    ///
+    /// 该版本文件的加载时分析是否已完成。
+    pub fn is_analyzed(&self) -> bool {
+        self.analyzed
+    }
+
+    /// Whether the completed analysis found an error-level validation issue.
+    ///
+    /// 已完成的分析是否发现了错误级别的校验问题。
+    pub fn has_validation_errors(&self) -> bool {
+        self.issues
+            .iter()
+            .any(|issue| issue.severity == MortarIssueSeverity::Error)
+    }
+
+    pub(crate) fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Stores the results of an analysis; lints are only replaced when the analysis linted.
+    pub(crate) fn apply_analysis(
+        &mut self,
+        issues: Vec<MortarValidationIssue>,
+        lints: Option<Vec<MortarLint>>,
+    ) {
+        self.issues = issues;
+        if let Some(lints) = lints {
+            self.lints = lints;
+        }
+        self.analyzed = true;
+    }
+
+    /// Whether the file declares a node named `name`.
+    ///
+    /// 文件是否声明了名为 `name` 的节点。
+    pub fn has_node(&self, name: &str) -> bool {
+        node_index(&self.data, name).is_some()
+    }
+
+    /// Names of the nodes tagged `tag`, in declaration order.
+    ///
+    /// 带有 `tag` 标签的节点名称，按声明顺序排列。
+    pub fn nodes_with_tag(&self, tag: &str) -> Vec<&str> {
+        self.data
+            .nodes
+            .iter()
+            .enumerate()
+            .filter(|(index, node)| node_index(&self.data, &node.name) == Some(*index))
+            .map(|(_, node)| node.name.as_str())
+            .filter(|name| self.metadata.node_tags(name).iter().any(|t| t == tag))
+            .collect()
+    }
+
+    /// Returns the file-level metadata written by the authors.
+    ///
+    /// 返回作者编写的文件级元数据。
+    pub fn metadata(&self) -> &MortarMetadata {
+        &self.metadata
+    }
+
+    /// Lists `(line_id, raw text)` for every line of `node` in order, for localization manifests.
+    /// Returns `None` when the node does not exist.
+    ///
+    /// 按顺序列出 `node` 中每一行的 `(line_id, 原始文本)`，用于生成本地化清单。
+    /// 节点不存在时返回 `None`。
+    pub fn line_ids(&self, node: &str) -> Option<Vec<(String, String)>> {
+        let parsed = self.parsed_node(node)?;
+        let lines = parsed
+            .text_items()
+            .iter()
+            .map(|item| (item.line_id.clone(), item.value.clone()))
+            .collect();
+        Some(lines)
+    }
+}
+
+/// Position of the node named `name`. When several nodes share the name, the first declaration
+/// wins; every node lookup goes through here so starts, jumps and tools agree.
+pub(crate) fn node_index(data: &MortaredData, name: &str) -> Option<usize> {
+    data.nodes.iter().position(|node| node.name == name)
 }
 
-/// An asset loader for `.mortar` and `.mortared` files.
+/// The node named `name`, resolved like [`node_index`].
+pub(crate) fn find_node<'a>(data: &'a MortaredData, name: &str) -> Option<&'a Node> {
+    node_index(data, name).map(|index| &data.nodes[index])
+}
+
+/// Positions of every node named `name`.
+pub(crate) fn node_declarations(data: &MortaredData, name: &str) -> Vec<usize> {
+    data.nodes
+        .iter()
+        .enumerate()
+        .filter(|(_, node)| node.name == name)
+        .map(|(index, _)| index)
+        .collect()
+}
+
+/// One-based line and column of the byte `offset` in `source`.
+fn line_column(source: &str, offset: usize) -> (usize, usize) {
+    let before = &source[..source.floor_char_boundary(offset.min(source.len()))];
+    let line = before.matches('\n').count() + 1;
+    let column = before
+        .rsplit('\n')
+        .next()
+        .map_or(0, |last| last.chars().count())
+        + 1;
+    (line, column)
+}
+
+#[cfg(feature = "tools")]
+impl MortarAsset {
+    /// Exports the conversation flow as a Graphviz DOT or Mermaid graph for documentation and review.
+    ///
+    /// 将对话流程导出为 Graphviz DOT 或 Mermaid 图，便于编写文档和评审。
+    pub fn export_graph(&self, format: GraphFormat) -> String {
+        graph::export_graph(&self.data, format)
+    }
+
+    /// Exports every text line and choice label as a localization and voice-over table, in node
+    /// declaration order and then content order. Pass a [`StringsFormat`] or a [`StringsExport`].
+    ///
+    /// 将所有文本行与选项标签导出为本地化与配音用的表格，按节点声明顺序、再按内容顺序排列。可传入
+    /// [`StringsFormat`] 或 [`StringsExport`]。
+    pub fn export_strings(&self, export: impl Into<StringsExport>) -> String {
+        let rows: Vec<_> = string_rows(&self.data, &self.metadata)
+            .into_iter()
+            .map(|row| (None, row))
+            .collect();
+        render_rows(&rows, export.into())
+    }
+}
+
+/// An asset loader for `.mortar` and `.mortared` files. The loader [`crate::MortarPlugin`]
+/// registers reports its stages as [`MortarAssetLoadStage`] messages.
 ///
-/// 用于 `.mortar` 和 `.mortared` 文件的资源加载器。
+/// 用于 `.mortar` 和 `.mortared` 文件的资源加载器。[`crate::MortarPlugin`] 注册的加载器会以
+/// [`MortarAssetLoadStage`] 消息报告其加载阶段。
 #[derive(Default, bevy::prelude::TypePath)]
-pub struct MortarAssetLoader;
+pub struct MortarAssetLoader {
+    stages: LoadStageQueue,
+}
 
 impl MortarAssetLoader {
+    /// A loader reporting its stages into `stages`.
+    pub(crate) fn reporting_to(stages: LoadStageQueue) -> Self {
+        Self { stages }
+    }
+
     /// Detects the system language to provide better diagnostics.
     ///
     /// 检测系统语言以提供更好的诊断信息。
@@ -43,20 +289,16 @@ impl MortarAssetLoader {
         }
     }
 
-    /// Compiles a `.mortar` source file into `MortaredData`.
+    /// Compiles `.mortar` source text, returning the compiler diagnostics alongside the result.
     ///
-    /// 将 `.mortar` 源文件编译为 `MortaredData`。
-    async fn compile_mortar_source(
-        reader: &mut dyn Reader,
+    /// 编译 `.mortar` 源文本，并随结果一并返回编译器诊断信息。
+    fn compile_with_diagnostics(
+        source_content: &str,
         source_path: &Path,
-    ) -> Result<MortaredData, Box<dyn std::error::Error + Send + Sync>> {
-        dev_info!("Compiling .mortar file: {:?}", source_path);
-
-        let mut bytes = Vec::new();
-        reader.read_to_end(&mut bytes).await?;
-        let source_content = std::str::from_utf8(&bytes)?;
-
+        report: &(dyn Fn(MortarLoadStage) + Sync),
+    ) -> (Result<MortaredData, LoadError>, DiagnosticCollector) {
         let language = Self::detect_language();
+        report(MortarLoadStage::Parsing);
         let (parse_result, diagnostics) =
             ParseHandler::parse_source_code_with_diagnostics_and_language(
                 source_content,
@@ -66,14 +308,101 @@ impl MortarAssetLoader {
             );
 
         if diagnostics.has_errors() {
-            diagnostics.print_diagnostics(source_content);
-            return Err("Mortar compilation failed with errors".into());
+            let file = source_path.display();
+            let messages: Vec<String> = diagnostics
+                .get_diagnostics()
+                .iter()
+                .filter(|diagnostic| matches!(diagnostic.severity, Severity::Error))
+                .map(|diagnostic| match diagnostic.span {
+                    Some((start, _)) => {
+                        let (line, column) = line_column(source_content, start);
+                        format!("{file}:{line}:{column}: {}", diagnostic.message)
+                    }
+                    None => format!("{file}: {}", diagnostic.message),
+                })
+                .collect();
+            let error = format!(
+                "Mortar compilation failed with errors: {}",
+                messages.join("; ")
+            );
+            return (Err(error.into()), diagnostics);
         }
 
-        let program = parse_result?;
-        let json = Serializer::serialize_to_json(&program, true)?;
+        let result = parse_result
+            .map_err(LoadError::from)
+            .and_then(|program| {
+                report(MortarLoadStage::Compiling);
+                Serializer::serialize_to_json(&program, true).map_err(Into::into)
+            })
+            .and_then(|json| {
+                report(MortarLoadStage::Decoding);
+                Deserializer::from_json(&json).map_err(Into::into)
+            });
+        (result, diagnostics)
+    }
+
+    /// Compiles `.mortar` source text into `MortaredData`.
+    /// Shared by the asset loader and offline tooling so both see the same result.
+    ///
+    /// 将 `.mortar` 源文本编译为 `MortaredData`。
+    /// 资源加载器与离线工具共用此函数，保证两者结果一致。
+    pub fn compile_source(
+        source_content: &str,
+        source_path: &Path,
+    ) -> Result<MortaredData, LoadError> {
+        Self::compile_with_diagnostics(source_content, source_path, &|_| {}).0
+    }
+
+    /// Decodes file bytes into `MortaredData`, compiling `.mortar` sources and parsing
+    /// `.mortared` JSON based on the extension of `path`.
+    ///
+    /// 根据 `path` 的扩展名把文件字节解码为 `MortaredData`：编译 `.mortar` 源码或解析 `.mortared` JSON。
+    pub fn load_bytes(bytes: &[u8], path: &Path) -> Result<MortaredData, LoadError> {
+        match path.extension().and_then(std::ffi::OsStr::to_str) {
+            Some("mortar") => Self::compile_source(std::str::from_utf8(bytes)?, path),
+            Some("mortared") => {
+                Deserializer::from_json(std::str::from_utf8(bytes)?).map_err(Into::into)
+            }
+            _ => Err("Unsupported file extension".into()),
+        }
+    }
+
+    /// Decodes file bytes into a complete [`MortarAsset`], including its metadata header.
+    ///
+    /// 将文件字节解码为完整的 [`MortarAsset`]，包括元数据头部。
+    pub fn load_asset_bytes(bytes: &[u8], path: &Path) -> Result<MortarAsset, LoadError> {
+        let data = Self::load_bytes(bytes, path)?;
+        let text = std::str::from_utf8(bytes)?;
+        let metadata = match path.extension().and_then(std::ffi::OsStr::to_str) {
+            Some("mortar") => MortarMetadata::from_source(text),
+            _ => MortarMetadata::from_mortared_json(text),
+        };
+        Ok(MortarAsset::with_metadata(data, metadata))
+    }
 
-        Deserializer::from_json(&json).map_err(Into::into)
+    /// Compiles a `.mortar` source file into a [`MortarAsset`].
+    ///
+    /// 将 `.mortar` 源文件编译为 [`MortarAsset`]。
+    async fn compile_mortar_source(
+        reader: &mut dyn Reader,
+        source_path: &Path,
+        report: &(dyn Fn(MortarLoadStage) + Sync),
+    ) -> Result<MortarAsset, LoadError> {
+        dev_info!(target: LOG_ASSET, "Compiling .mortar file: {:?}", source_path);
+
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let source_content = std::str::from_utf8(&bytes)?;
+
+        let (result, diagnostics) =
+            Self::compile_with_diagnostics(source_content, source_path, report);
+        if diagnostics.has_errors() {
+            diagnostics.print_diagnostics(source_content);
+        }
+        Ok(MortarAsset::with_metadata(
+            result?,
+            MortarMetadata::from_source(source_content),
+        ))
     }
 
     /// Loads a `.mortared` file directly from the asset reader.
@@ -82,16 +411,21 @@ impl MortarAssetLoader {
     async fn load_mortared_direct(
         reader: &mut dyn Reader,
         path: &Path,
-    ) -> Result<MortaredData, Box<dyn std::error::Error + Send + Sync>> {
+        report: &(dyn Fn(MortarLoadStage) + Sync),
+    ) -> Result<MortarAsset, LoadError> {
         #[cfg(not(feature = "dev-logs"))]
         let _ = path;
-        dev_info!("Loading .mortared file: {:?}", path);
+        dev_info!(target: LOG_ASSET, "Loading .mortared file: {:?}", path);
 
         let mut bytes = Vec::new();
         reader.read_to_end(&mut bytes).await?;
         let json = std::str::from_utf8(&bytes)?;
 
-        Deserializer::from_json(json).map_err(Into::into)
+        report(MortarLoadStage::Decoding);
+        Ok(MortarAsset::with_metadata(
+            Deserializer::from_json(json)?,
+            MortarMetadata::from_mortared_json(json),
+        ))
     }
 
     /// Logs all public constants contained within a Mortar program.
@@ -111,12 +445,13 @@ impl MortarAssetLoader {
         #[cfg(not(feature = "dev-logs"))]
         let _ = path;
 
-        dev_info!("Public constants exported by {}:", path.display());
+        dev_info!(target: LOG_ASSET, "Public constants exported by {}:", path.display());
 
         for constant in public_constants {
             #[cfg(not(feature = "dev-logs"))]
             let _ = constant;
             dev_info!(
+                target: LOG_ASSET,
                 "    {} ({}): {}",
                 constant.name,
                 constant.const_type,
@@ -154,7 +489,7 @@ impl MortarAssetLoader {
 impl AssetLoader for MortarAssetLoader {
     type Asset = MortarAsset;
     type Settings = ();
-    type Error = Box<dyn std::error::Error + Send + Sync>;
+    type Error = LoadError;
 
     /// Loads a Mortar asset.
     ///
@@ -169,28 +504,38 @@ impl AssetLoader for MortarAssetLoader {
             // In Bevy 0.18, path() returns &AssetPath
             let asset_path = load_context.path().clone();
             let path = asset_path.path().to_path_buf();
+            let stage_path = asset_path.without_label().to_string();
+            let report = |stage| self.stages.report(&stage_path, stage);
+            report(MortarLoadStage::Reading);
 
-            let data = match path.extension().and_then(std::ffi::OsStr::to_str) {
+            let loaded = match path.extension().and_then(std::ffi::OsStr::to_str) {
                 Some("mortar") => {
                     // Always compile from source to ensure hot reloading gets the latest changes.
                     //
                     // 始终从源代码编译以确保热重载获取最新更改。
-                    Self::compile_mortar_source(reader, &path).await?
+                    Self::compile_mortar_source(reader, &path, &report).await
                 }
-                Some("mortared") => Self::load_mortared_direct(reader, &path).await?,
-                _ => return Err("Unsupported file extension".into()),
+                Some("mortared") => Self::load_mortared_direct(reader, &path, &report).await,
+                _ => Err("Unsupported file extension".into()),
             };
+            let asset = loaded.inspect_err(|_| report(MortarLoadStage::Failed))?;
+            asset.parse_nodes();
+            for diagnostic in asset.content_diagnostics() {
+                warn!(target: LOG_ASSET, "{}: {}", stage_path, diagnostic);
+            }
+            report(MortarLoadStage::Loaded);
 
             dev_info!(
+                target: LOG_ASSET,
                 "Successfully loaded mortar asset: {:?} (nodes: {}, functions: {}, variables: {})",
                 asset_path,
-                data.nodes.len(),
-                data.functions.len(),
-                data.variables.len()
+                asset.data.nodes.len(),
+                asset.data.functions.len(),
+                asset.data.variables.len()
             );
-            Self::log_public_constants(&path, &data);
+            Self::log_public_constants(&path, &asset.data);
 
-            Ok(MortarAsset { data })
+            Ok(asset)
         })
     }
 
@@ -0,0 +1,111 @@
+//! # definitions.rs
+//!
+//! # definitions.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Name lookup tables for the functions, events and timelines a Mortar file declares. Interpolation,
+//! text events and the run scheduler look definitions up by name on every line, so each asset
+//! builds its tables once, on first use. When several definitions share a name the first one wins,
+//! as with a linear search. A hot reload replaces the asset and with it the tables; code that edits
+//! [`MortarAsset::data`] in place calls [`MortarAsset::reindex`].
+//!
+//! Mortar 文件所声明的函数、事件与时间线的名称查找表。插值、文本事件与 run 调度器每一行都会按名称
+//! 查找定义，因此每个资源只在首次使用时构建一次查找表。多个定义同名时，与线性查找一样以第一个为准。
+//! 热重载会替换资源及其查找表；直接修改 [`MortarAsset::data`] 的代码需调用 [`MortarAsset::reindex`]。
+
+use mortar_compiler::{EventDef, Function, MortaredData, TimelineDef};
+use std::collections::HashMap;
+
+use super::MortarAsset;
+
+/// Positions of the definitions of a file, by name.
+#[derive(Debug, Default)]
+pub(crate) struct MortarDefinitions {
+    functions: HashMap<String, usize>,
+    events: HashMap<String, usize>,
+    timelines: HashMap<String, usize>,
+}
+
+impl MortarDefinitions {
+    pub(crate) fn build(data: &MortaredData) -> Self {
+        Self {
+            functions: index_names(data.functions.iter().map(|function| &function.name)),
+            events: index_names(data.events.iter().map(|event| &event.name)),
+            timelines: index_names(data.timelines.iter().map(|timeline| &timeline.name)),
+        }
+    }
+}
+
+fn index_names<'a>(names: impl Iterator<Item = &'a String>) -> HashMap<String, usize> {
+    let mut index = HashMap::new();
+    for (position, name) in names.enumerate() {
+        index.entry(name.clone()).or_insert(position);
+    }
+    index
+}
+
+fn lookup<'a, T>(index: &HashMap<String, usize>, items: &'a [T], name: &str) -> Option<&'a T> {
+    #[cfg(test)]
+    comparisons::add(1);
+    index.get(name).and_then(|&position| items.get(position))
+}
+
+impl MortarAsset {
+    pub(crate) fn definitions(&self) -> &MortarDefinitions {
+        self.definitions
+            .get_or_init(|| MortarDefinitions::build(&self.data))
+    }
+
+    /// Rebuilds the name lookup tables and the parsed nodes after [`MortarAsset::data`] was
+    /// edited in place.
+    ///
+    /// 在直接修改 [`MortarAsset::data`] 后重建名称查找表与已解析的节点。
+    pub fn reindex(&mut self) {
+        self.definitions = std::sync::OnceLock::new();
+        self.parsed_nodes = std::sync::OnceLock::new();
+    }
+
+    /// The function declared as `name`.
+    ///
+    /// 以 `name` 声明的函数。
+    pub fn function_decl(&self, name: &str) -> Option<&Function> {
+        lookup(&self.definitions().functions, &self.data.functions, name)
+    }
+
+    /// The event defined as `name`.
+    ///
+    /// 以 `name` 定义的事件。
+    pub fn event_def(&self, name: &str) -> Option<&EventDef> {
+        lookup(&self.definitions().events, &self.data.events, name)
+    }
+
+    /// The timeline defined as `name`.
+    ///
+    /// 以 `name` 定义的时间线。
+    pub fn timeline_def(&self, name: &str) -> Option<&TimelineDef> {
+        lookup(&self.definitions().timelines, &self.data.timelines, name)
+    }
+}
+
+/// Counts name comparisons made by lookups on this thread, so tests can weigh the tables against
+/// a linear search. A table lookup counts as one comparison: the hash hit is confirmed once.
+#[cfg(test)]
+pub(crate) mod comparisons {
+    use std::cell::Cell;
+
+    thread_local! {
+        static COUNT: Cell<usize> = const { Cell::new(0) };
+    }
+
+    pub(crate) fn add(count: usize) {
+        COUNT.with(|cell| cell.set(cell.get() + count));
+    }
+
+    /// Returns the count and resets it.
+    pub(crate) fn take() -> usize {
+        COUNT.with(|cell| cell.replace(0))
+    }
+}
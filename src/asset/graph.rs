@@ -0,0 +1,333 @@
+//! # graph.rs
+//!
+//! # graph.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Renders the conversation flow of a compiled Mortar file as a Graphviz DOT or Mermaid
+//! flowchart. Dialogue nodes become boxes, `next` links and choice targets become edges, and
+//! targets that live outside the file or terminate the dialogue get their own shapes so narrative
+//! reviews can read the whole structure at a glance.
+//!
+//! 把编译后的 Mortar 文件的对话流程渲染为 Graphviz DOT 或 Mermaid 流程图。对话节点渲染为方框，
+//! `next` 链接和选项目标渲染为边，文件外的目标和结束状态使用独立形状，方便剧情评审一眼看清整体结构。
+
+use mortar_compiler::{Choice, MortaredData, Node};
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// Maximum number of characters kept in a rendered label.
+///
+/// 渲染标签保留的最大字符数。
+const MAX_LABEL_CHARS: usize = 40;
+
+/// Output format for [`crate::MortarAsset::export_graph`].
+///
+/// [`crate::MortarAsset::export_graph`] 的输出格式。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphFormat {
+    /// Graphviz DOT (`digraph`).
+    ///
+    /// Graphviz DOT（`digraph`）。
+    Dot,
+    /// Mermaid `flowchart`.
+    ///
+    /// Mermaid `flowchart`。
+    Mermaid,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum VertexKind {
+    Node,
+    External,
+    End,
+}
+
+struct Vertex {
+    id: String,
+    label: String,
+    kind: VertexKind,
+}
+
+struct Edge {
+    from: String,
+    to: String,
+    label: Option<String>,
+    conditional: bool,
+}
+
+#[derive(Default)]
+struct FlowGraph {
+    vertices: Vec<Vertex>,
+    edges: Vec<Edge>,
+    /// Vertex id of each external target, keyed by the full target since labels are clipped.
+    externals: HashMap<String, String>,
+}
+
+const END_ID: &str = "terminal";
+
+impl FlowGraph {
+    fn build(data: &MortaredData) -> Self {
+        let mut graph = Self::default();
+        for (idx, node) in data.nodes.iter().enumerate() {
+            graph.vertices.push(Vertex {
+                id: format!("n{idx}"),
+                label: node_label(node),
+                kind: VertexKind::Node,
+            });
+        }
+
+        for (idx, node) in data.nodes.iter().enumerate() {
+            let from = format!("n{idx}");
+            let mut has_choices = false;
+            for options in node.content.iter().filter_map(choice_options) {
+                has_choices = true;
+                graph.add_choice_edges(data, &from, &options, "", false);
+            }
+
+            match node.next.as_deref() {
+                Some(target) => {
+                    let to = graph.target_id(data, &from, target);
+                    graph.push_edge(from, to, None, false);
+                }
+                None if !has_choices => graph.push_edge(from, END_ID.to_string(), None, false),
+                None => {}
+            }
+        }
+
+        graph
+    }
+
+    fn add_choice_edges(
+        &mut self,
+        data: &MortaredData,
+        from: &str,
+        options: &[Choice],
+        prefix: &str,
+        inherited_condition: bool,
+    ) {
+        for option in options {
+            let label = if prefix.is_empty() {
+                option.text.clone()
+            } else {
+                format!("{prefix} / {}", option.text)
+            };
+            let conditional = inherited_condition || option.condition.is_some();
+
+            if let Some(nested) = &option.choice {
+                self.add_choice_edges(data, from, nested, &label, conditional);
+                continue;
+            }
+
+            let to = match (option.action.as_deref(), option.next.as_deref()) {
+                (Some("break"), _) => from.to_string(),
+                (Some(_), _) | (None, None) => END_ID.to_string(),
+                (None, Some(target)) => self.target_id(data, from, target),
+            };
+            self.push_edge(from.to_string(), to, Some(label), conditional);
+        }
+    }
+
+    /// Resolves a jump target to a vertex id, creating terminal or external vertices on demand.
+    fn target_id(&mut self, data: &MortaredData, from: &str, target: &str) -> String {
+        if target == "return" {
+            return self.end_id();
+        }
+        if target == "break" {
+            return from.to_string();
+        }
+        if let Some(idx) = super::node_index(data, target) {
+            return format!("n{idx}");
+        }
+        if let Some(id) = self.externals.get(target) {
+            return id.clone();
+        }
+        let id = format!("x{}", self.externals.len());
+        self.externals.insert(target.to_string(), id.clone());
+        self.vertices.push(Vertex {
+            id: id.clone(),
+            label: clip(target),
+            kind: VertexKind::External,
+        });
+        id
+    }
+
+    fn end_id(&mut self) -> String {
+        if !self
+            .vertices
+            .iter()
+            .any(|vertex| vertex.kind == VertexKind::End)
+        {
+            self.vertices.push(Vertex {
+                id: END_ID.to_string(),
+                label: "END".to_string(),
+                kind: VertexKind::End,
+            });
+        }
+        END_ID.to_string()
+    }
+
+    fn push_edge(&mut self, from: String, to: String, label: Option<String>, conditional: bool) {
+        if to == END_ID {
+            self.end_id();
+        }
+        self.edges.push(Edge {
+            from,
+            to,
+            label,
+            conditional,
+        });
+    }
+
+    fn to_dot(&self) -> String {
+        let mut out = String::from("digraph mortar {\n    rankdir=TB;\n    node [shape=box];\n");
+        for vertex in &self.vertices {
+            let attrs = match vertex.kind {
+                VertexKind::Node => String::new(),
+                VertexKind::External => ", shape=box, style=dashed".to_string(),
+                VertexKind::End => ", shape=doublecircle".to_string(),
+            };
+            let _ = writeln!(
+                out,
+                "    {} [label=\"{}\"{}];",
+                vertex.id,
+                escape_dot(&vertex.label),
+                attrs
+            );
+        }
+        for edge in &self.edges {
+            let mut attrs = Vec::new();
+            if let Some(label) = &edge.label {
+                attrs.push(format!("label=\"{}\"", escape_dot(&clip(label))));
+            }
+            if edge.conditional {
+                attrs.push("style=dashed".to_string());
+                attrs.push("color=gray50".to_string());
+            }
+            if attrs.is_empty() {
+                let _ = writeln!(out, "    {} -> {};", edge.from, edge.to);
+            } else {
+                let _ = writeln!(
+                    out,
+                    "    {} -> {} [{}];",
+                    edge.from,
+                    edge.to,
+                    attrs.join(", ")
+                );
+            }
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    fn to_mermaid(&self) -> String {
+        let mut out = String::from("flowchart TD\n");
+        for vertex in &self.vertices {
+            let label = escape_mermaid(&vertex.label);
+            let _ = match vertex.kind {
+                VertexKind::Node => writeln!(out, "    {}[\"{}\"]", vertex.id, label),
+                VertexKind::External => writeln!(out, "    {}[/\"{}\"/]", vertex.id, label),
+                VertexKind::End => writeln!(out, "    {}((\"{}\"))", vertex.id, label),
+            };
+        }
+        for edge in &self.edges {
+            let arrow = if edge.conditional { "-.->" } else { "-->" };
+            let _ = match &edge.label {
+                Some(label) => writeln!(
+                    out,
+                    "    {} {}|\"{}\"| {}",
+                    edge.from,
+                    arrow,
+                    escape_mermaid(&clip(label)),
+                    edge.to
+                ),
+                None => writeln!(out, "    {} {} {}", edge.from, arrow, edge.to),
+            };
+        }
+        out
+    }
+}
+
+/// Parses the options of a `choice` content item.
+fn choice_options(content: &serde_json::Value) -> Option<Vec<Choice>> {
+    if content.get("type").and_then(|value| value.as_str()) != Some("choice") {
+        return None;
+    }
+    serde_json::from_value(content.get("options")?.clone()).ok()
+}
+
+/// Builds the multi-line label of a dialogue node: name, text count, choices and run annotations.
+fn node_label(node: &Node) -> String {
+    let mut text_count = 0;
+    let mut has_choices = false;
+    let mut runs = Vec::new();
+    for content in &node.content {
+        match content.get("type").and_then(|value| value.as_str()) {
+            Some("text" | "line") => text_count += 1,
+            Some("choice") => has_choices = true,
+            Some(kind @ ("run_event" | "run_timeline")) => {
+                let name = content
+                    .get("name")
+                    .and_then(|value| value.as_str())
+                    .unwrap_or("?");
+                let prefix = if kind == "run_timeline" {
+                    "timeline"
+                } else {
+                    "run"
+                };
+                runs.push(format!("{prefix}: {}", clip(name)));
+            }
+            _ => {}
+        }
+    }
+
+    let mut lines = vec![
+        clip(&node.name),
+        format!(
+            "texts: {text_count}{}",
+            if has_choices { ", choices" } else { "" }
+        ),
+    ];
+    lines.extend(runs);
+    lines.join("\n")
+}
+
+/// Clips a label to [`MAX_LABEL_CHARS`] characters, appending an ellipsis when truncated.
+fn clip(label: &str) -> String {
+    let single_line = label.replace(['\r', '\n'], " ");
+    if single_line.chars().count() <= MAX_LABEL_CHARS {
+        return single_line;
+    }
+    let mut clipped: String = single_line.chars().take(MAX_LABEL_CHARS - 1).collect();
+    clipped.push('…');
+    clipped
+}
+
+fn escape_dot(label: &str) -> String {
+    label
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn escape_mermaid(label: &str) -> String {
+    label
+        .replace('"', "#quot;")
+        .replace('<', "#lt;")
+        .replace('>', "#gt;")
+        .replace('|', "#124;")
+        .replace('\n', "<br/>")
+}
+
+/// Renders the conversation flow of `data` in the requested format.
+///
+/// 按指定格式渲染 `data` 的对话流程。
+pub(crate) fn export_graph(data: &MortaredData, format: GraphFormat) -> String {
+    let graph = FlowGraph::build(data);
+    match format {
+        GraphFormat::Dot => graph.to_dot(),
+        GraphFormat::Mermaid => graph.to_mermaid(),
+    }
+}
@@ -0,0 +1,162 @@
+//! # last_good.rs
+//!
+//! # last_good.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Keeps a copy of the last version of each registered file that loaded, so a reload that fails
+//! (a live editor saving a broken file) does not leave running dialogues without data. When the
+//! failed file's asset is gone, the copy is put back under the same id, which every runtime
+//! lookup then finds as before. Failures and later successful reloads are reported as
+//! [`MortarLifecycleEvent`]s. Retention is controlled by `MortarPlugin::retain_last_good`.
+//!
+//! 为每个已注册文件保留最近一次成功加载的版本副本，使失败的重载（实时编辑器保存了有错误的文件）
+//! 不会让运行中的对话失去数据。失败文件的资源不存在时，副本会以相同的 id 放回，所有运行时查询
+//! 因此照常找到它。失败以及之后成功的重载会以 [`MortarLifecycleEvent`] 报告。是否保留由
+//! `MortarPlugin::retain_last_good` 控制。
+
+use bevy::asset::{AssetLoadError, AssetLoadFailedEvent};
+use bevy::prelude::*;
+use mortar_compiler::MortaredData;
+use std::collections::HashMap;
+
+use super::{MortarAsset, MortarMetadata};
+use crate::debug::LOG_ASSET;
+use crate::preparation::reloaded_paths;
+use crate::{MortarLifecycleEvent, MortarRegistry};
+
+/// The version of a file that loaded last.
+#[derive(Debug)]
+struct LastGoodVersion {
+    revision: u64,
+    /// Copy of the version's data; `None` when retention is off.
+    copy: Option<(MortaredData, MortarMetadata)>,
+}
+
+/// The last version of each Mortar file that loaded, by asset id.
+///
+/// 每个 Mortar 文件最近一次成功加载的版本，按资源 id 索引。
+#[derive(Resource, Debug)]
+pub struct MortarLastGood {
+    retain: bool,
+    versions: HashMap<AssetId<MortarAsset>, LastGoodVersion>,
+}
+
+impl Default for MortarLastGood {
+    fn default() -> Self {
+        Self::new(true)
+    }
+}
+
+impl MortarLastGood {
+    /// Creates the cache; with `retain` off, failed reloads are reported but nothing is kept.
+    ///
+    /// 创建缓存；`retain` 关闭时仍会报告失败的重载，但不会保留任何内容。
+    pub fn new(retain: bool) -> Self {
+        Self {
+            retain,
+            versions: HashMap::new(),
+        }
+    }
+
+    pub fn is_retaining(&self) -> bool {
+        self.retain
+    }
+
+    /// The data of the last version of the file `id` that loaded, while retaining.
+    ///
+    /// 保留开启时，文件 `id` 最近一次成功加载的版本数据。
+    pub fn get(&self, id: impl Into<AssetId<MortarAsset>>) -> Option<&MortaredData> {
+        let version = self.versions.get(&id.into())?;
+        version.copy.as_ref().map(|(data, _)| data)
+    }
+
+    /// Records `asset` as the last good version of `id`, returning whether it replaced an
+    /// earlier version.
+    fn record(&mut self, id: AssetId<MortarAsset>, asset: &MortarAsset) -> bool {
+        let copy = self
+            .retain
+            .then(|| (asset.data.clone(), asset.metadata.clone()));
+        let version = LastGoodVersion {
+            revision: asset.revision(),
+            copy,
+        };
+        self.versions.insert(id, version).is_some()
+    }
+}
+
+/// The compiler's message for `error`, without the asset server's wrapping.
+fn failure_message(error: &AssetLoadError) -> String {
+    match error {
+        AssetLoadError::AssetLoaderError(error) => error.error().to_string(),
+        error => error.to_string(),
+    }
+}
+
+/// Caches every new version of a file, puts it back when a reload of the file fails, and reports
+/// both for registered files.
+pub(crate) fn retain_last_good_versions(
+    mut last_good: ResMut<MortarLastGood>,
+    registry: Res<MortarRegistry>,
+    mut assets: ResMut<Assets<MortarAsset>>,
+    mut asset_events: MessageReader<AssetEvent<MortarAsset>>,
+    mut failures: MessageReader<AssetLoadFailedEvent<MortarAsset>>,
+    mut lifecycle: MessageWriter<MortarLifecycleEvent>,
+) {
+    for event in asset_events.read() {
+        let id = match event {
+            AssetEvent::Added { id } | AssetEvent::Modified { id } => *id,
+            AssetEvent::Unused { id } => {
+                last_good.versions.remove(id);
+                continue;
+            }
+            _ => continue,
+        };
+        let Some(asset) = assets.get(id) else {
+            continue;
+        };
+        // Writes to the asset that keep its revision are not new versions.
+        //
+        // 保持修订号不变的资源写入不是新版本。
+        let known = last_good.versions.get(&id).map(|version| version.revision);
+        if known == Some(asset.revision()) {
+            continue;
+        }
+        if last_good.record(id, asset) {
+            for path in reloaded_paths(&registry, id) {
+                lifecycle.write(MortarLifecycleEvent::ReloadSucceeded { path });
+            }
+        }
+    }
+
+    for failure in failures.read() {
+        let paths = reloaded_paths(&registry, failure.id);
+        if paths.is_empty() {
+            continue;
+        }
+        let error = failure_message(&failure.error);
+        warn!(target: LOG_ASSET, "Failed to reload '{}': {}", failure.path, error);
+        let copy = last_good
+            .versions
+            .get_mut(&failure.id)
+            .filter(|_| !assets.contains(failure.id))
+            .and_then(|version| Some((version.copy.as_ref()?, &mut version.revision)));
+        if let Some(((data, metadata), revision)) = copy {
+            let restored = MortarAsset::with_metadata(data.clone(), metadata.clone());
+            *revision = restored.revision();
+            if let Err(error) = assets.insert(failure.id, restored) {
+                warn!(target: LOG_ASSET, "Could not restore '{}': {}", failure.path, error);
+            } else {
+                debug!(target: LOG_ASSET, "Serving the last good version of '{}'", failure.path);
+            }
+        }
+        for path in paths {
+            lifecycle.write(MortarLifecycleEvent::ReloadFailed {
+                path,
+                error: error.clone(),
+            });
+        }
+    }
+}
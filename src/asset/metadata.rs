@@ -0,0 +1,237 @@
+//! # metadata.rs
+//!
+//! # metadata.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! File-level metadata written by authors at the top of a Mortar script: title, author, script
+//! version and any custom keys such as content warnings. The Mortar language has no metadata
+//! syntax, so this crate reads a convention of its own: in `.mortar` sources the header is the
+//! first block of leading `// @key: value` comments, ended by a blank line. The compiler skips
+//! these as plain comments. In `.mortared` files the header is every top-level key (and every
+//! `metadata` key) the compiler does not know about. `.mortared` nodes may also carry a `header`
+//! key overriding the dialogue text header, a `tags` array that gameplay systems and scripts can
+//! query, and an `overrides` object mapping function names to the ones called instead while the
+//! node is active. A node marked `"patch": true` is spliced into the base file's node of the same
+//! name when the file is registered as an overlay. Missing or malformed headers simply produce
+//! defaults.
+//!
+//! 作者写在 Mortar 脚本顶部的文件级元数据：标题、作者、脚本版本以及内容警告等自定义键。Mortar
+//! 语言本身没有元数据语法，因此本 crate 采用自己的约定：`.mortar` 源文件中，头部是开头第一段
+//! `// @key: value` 注释，遇到空行即结束，编译器会把它们当作普通注释跳过。`.mortared` 文件中，
+//! 头部是编译器不认识的所有顶层键（以及 `metadata` 内的键）。`.mortared` 的节点还可以带有用于覆盖
+//! 对话文本头部的 `header` 键、供游戏系统与脚本查询的 `tags` 数组，以及把函数名映射到节点活跃
+//! 期间改为调用的函数的 `overrides` 对象。标记为 `"patch": true` 的节点在该文件作为覆盖层注册时，
+//! 会被拼接进基础文件中同名的节点。头部缺失或格式错误时只会得到默认值。
+
+use std::collections::{HashMap, HashSet};
+
+/// Top-level keys produced by the compiler itself.
+const COMPILER_KEYS: &[&str] = &[
+    "metadata",
+    "variables",
+    "constants",
+    "enums",
+    "nodes",
+    "functions",
+    "events",
+    "timelines",
+];
+
+/// Keys of the compiler's own `metadata` object.
+const COMPILER_METADATA_KEYS: &[&str] = &["version", "generated_at"];
+
+/// Author-provided metadata of a Mortar file.
+///
+/// Mortar 文件中由作者提供的元数据。
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MortarMetadata {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    /// Script (e.g. localization) version, not the compiler format version.
+    ///
+    /// 脚本（例如本地化）版本，而不是编译器格式版本。
+    pub version: Option<String>,
+    /// Every other header key.
+    ///
+    /// 其余所有头部键。
+    pub custom: HashMap<String, serde_json::Value>,
+    /// `header` keys of nodes, by node name.
+    ///
+    /// 各节点的 `header` 键，按节点名称索引。
+    pub node_headers: HashMap<String, String>,
+    /// `tags` arrays of nodes, by node name. Untagged nodes are absent.
+    ///
+    /// 各节点的 `tags` 数组，按节点名称索引。没有标签的节点不在其中。
+    pub node_tags: HashMap<String, Vec<String>>,
+    /// `overrides` objects of nodes, by node name: each function name mapped to the one called
+    /// instead while the node is active. Nodes without overrides are absent.
+    ///
+    /// 各节点的 `overrides` 对象，按节点名称索引：每个函数名映射到节点活跃期间改为调用的函数。
+    /// 没有覆盖的节点不在其中。
+    pub node_overrides: HashMap<String, HashMap<String, String>>,
+    /// Names of nodes marked `"patch": true`, see [`crate::MortarRegistry::register_overlay`].
+    ///
+    /// 标记为 `"patch": true` 的节点名称，参见 [`crate::MortarRegistry::register_overlay`]。
+    pub patch_nodes: HashSet<String>,
+}
+
+impl MortarMetadata {
+    /// Reads the leading `// @key: value` comment block of a `.mortar` source. Values parse as
+    /// JSON when they can, e.g. `// @chapter: 3`, and are kept as strings otherwise.
+    ///
+    /// 读取 `.mortar` 源文件开头的 `// @key: value` 注释块。值能按 JSON 解析时按 JSON 解析（例如
+    /// `// @chapter: 3`），否则保留为字符串。
+    pub fn from_source(source: &str) -> Self {
+        let mut fields = HashMap::new();
+        for line in source.lines().map(str::trim) {
+            if line.is_empty() && !fields.is_empty() {
+                break;
+            }
+            if line.is_empty() {
+                continue;
+            }
+            let Some(comment) = line.strip_prefix("//") else {
+                break;
+            };
+            let Some((key, value)) = comment
+                .trim()
+                .strip_prefix('@')
+                .and_then(|entry| entry.split_once(':'))
+            else {
+                continue;
+            };
+            let value = value.trim();
+            let value = serde_json::from_str(value)
+                .unwrap_or_else(|_| serde_json::Value::String(value.to_string()));
+            fields.insert(key.trim().to_string(), value);
+        }
+        Self::from_fields(fields)
+    }
+
+    /// Collects the keys of a `.mortared` JSON document that the compiler does not define.
+    ///
+    /// 收集 `.mortared` JSON 文档中编译器未定义的键。
+    pub fn from_mortared_json(json: &str) -> Self {
+        let Ok(serde_json::Value::Object(root)) = serde_json::from_str(json) else {
+            return Self::default();
+        };
+
+        let mut fields = HashMap::new();
+        if let Some(serde_json::Value::Object(metadata)) = root.get("metadata") {
+            let authored = metadata
+                .iter()
+                .filter(|(key, _)| !COMPILER_METADATA_KEYS.contains(&key.as_str()));
+            fields.extend(authored.map(|(key, value)| (key.clone(), value.clone())));
+        }
+        let nodes = || {
+            root.get("nodes")
+                .and_then(|nodes| nodes.as_array())
+                .into_iter()
+                .flatten()
+                .filter_map(|node| Some((node.get("name")?.as_str()?.to_owned(), node)))
+        };
+        let node_headers = nodes()
+            .filter_map(|(name, node)| Some((name, node.get("header")?.as_str()?.to_owned())))
+            .collect();
+        let node_tags = nodes()
+            .filter_map(|(name, node)| Some((name, node_tag_list(node.get("tags")?))))
+            .filter(|(_, tags)| !tags.is_empty())
+            .collect();
+        let node_overrides = nodes()
+            .filter_map(|(name, node)| Some((name, node_override_map(node.get("overrides")?))))
+            .filter(|(_, overrides)| !overrides.is_empty())
+            .collect();
+        let patch_nodes = nodes()
+            .filter(|(_, node)| {
+                node.get("patch").and_then(serde_json::Value::as_bool) == Some(true)
+            })
+            .map(|(name, _)| name)
+            .collect();
+        for (key, value) in root {
+            if !COMPILER_KEYS.contains(&key.as_str()) {
+                fields.insert(key, value);
+            }
+        }
+        Self {
+            node_headers,
+            node_tags,
+            node_overrides,
+            patch_nodes,
+            ..Self::from_fields(fields)
+        }
+    }
+
+    fn from_fields(mut custom: HashMap<String, serde_json::Value>) -> Self {
+        let mut take = |key: &str| {
+            custom.remove(key).and_then(|value| match value {
+                serde_json::Value::String(text) => Some(text),
+                serde_json::Value::Null => None,
+                other => Some(other.to_string()),
+            })
+        };
+        let title = take("title");
+        let author = take("author");
+        let version = take("version");
+        Self {
+            title,
+            author,
+            version,
+            custom,
+            node_headers: HashMap::new(),
+            node_tags: HashMap::new(),
+            node_overrides: HashMap::new(),
+            patch_nodes: HashSet::new(),
+        }
+    }
+
+    /// Looks up a custom key.
+    ///
+    /// 查找自定义键。
+    pub fn get(&self, key: &str) -> Option<&serde_json::Value> {
+        self.custom.get(key)
+    }
+
+    /// The `header` key of the node named `node`.
+    ///
+    /// 名为 `node` 的节点的 `header` 键。
+    pub fn node_header(&self, node: &str) -> Option<&str> {
+        self.node_headers.get(node).map(String::as_str)
+    }
+
+    /// The tags of the node named `node`; empty for untagged or unknown nodes.
+    ///
+    /// 名为 `node` 的节点的标签；没有标签或节点不存在时为空。
+    pub fn node_tags(&self, node: &str) -> &[String] {
+        self.node_tags.get(node).map_or(&[], Vec::as_slice)
+    }
+
+    /// The function overrides of the node named `node`, see [`Self::node_overrides`].
+    ///
+    /// 名为 `node` 的节点的函数覆盖，参见 [`Self::node_overrides`]。
+    pub fn overrides_of(&self, node: &str) -> Option<&HashMap<String, String>> {
+        self.node_overrides.get(node)
+    }
+}
+
+/// The string entries of a node's `overrides` object, ignoring anything else.
+fn node_override_map(overrides: &serde_json::Value) -> HashMap<String, String> {
+    overrides
+        .as_object()
+        .into_iter()
+        .flatten()
+        .filter_map(|(name, target)| Some((name.clone(), target.as_str()?.to_owned())))
+        .collect()
+}
+
+/// The string entries of a node's `tags` value, ignoring anything else.
+fn node_tag_list(tags: &serde_json::Value) -> Vec<String> {
+    tags.as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|tag| tag.as_str())
+        .map(str::to_owned)
+        .collect()
+}
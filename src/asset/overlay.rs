@@ -0,0 +1,213 @@
+//! # overlay.rs
+//!
+//! # overlay.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Merges a base file with the overlays registered on it, see
+//! [`crate::MortarRegistry::register_overlay`]. Overlays apply from the lowest priority to the
+//! highest. A node of an overlay replaces the base's node of the same name or is added; a node
+//! marked `"patch": true` is spliced into the existing node instead: each content item carrying
+//! `replace_index: N` replaces item N, one carrying `insert_after: N` goes after item N, and the
+//! rest are appended. Indices refer to the node as it stood before that overlay. Functions,
+//! variables, constants, enums, events and timelines replace or add by name. When an overlay
+//! replaces a definition a lower-priority overlay made, the merge reports it as shadowed.
+//!
+//! 将基础文件与注册在其上的覆盖层合并，参见 [`crate::MortarRegistry::register_overlay`]。覆盖层按
+//! 优先级从低到高依次应用。覆盖层中的节点会替换基础文件中的同名节点，或作为新节点加入；标记为
+//! `"patch": true` 的节点则拼接进已有节点：带有 `replace_index: N` 的内容项替换第 N 项，带有
+//! `insert_after: N` 的内容项插在第 N 项之后，其余内容项追加到末尾。索引指的是该覆盖层应用之前的
+//! 节点。函数、变量、常量、枚举、事件与时间线按名称替换或加入。当覆盖层替换了较低优先级覆盖层的
+//! 定义时，合并会将其报告为被遮蔽。
+
+use bevy::asset::AssetId;
+use bevy::prelude::*;
+use mortar_compiler::{MortaredData, Node};
+use serde_json::Value;
+use std::collections::HashMap;
+
+use super::MortarAsset;
+use crate::debug::LOG_ASSET;
+
+/// Hint of a patch item replacing the base item at its index.
+const REPLACE_INDEX: &str = "replace_index";
+/// Hint of a patch item going after the base item at its index.
+const INSERT_AFTER: &str = "insert_after";
+
+/// A definition of an overlay that a higher-priority overlay replaced.
+///
+/// 被更高优先级覆盖层替换掉的某个覆盖层定义。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MortarOverlayShadow {
+    /// `"node"`, `"function"`, `"variable"`, `"constant"`, `"enum"`, `"event"` or `"timeline"`.
+    ///
+    /// `"node"`、`"function"`、`"variable"`、`"constant"`、`"enum"`、`"event"` 或 `"timeline"`。
+    pub kind: &'static str,
+    pub name: String,
+    /// The overlay whose definition was replaced.
+    ///
+    /// 定义被替换的覆盖层。
+    pub shadowed: AssetId<MortarAsset>,
+    /// The overlay whose definition won.
+    ///
+    /// 定义最终生效的覆盖层。
+    pub by: AssetId<MortarAsset>,
+}
+
+/// Merges `overlays`, ordered from the lowest priority to the highest, onto `base`.
+pub(crate) fn merge_layers(
+    base: &MortarAsset,
+    overlays: &[(AssetId<MortarAsset>, &MortarAsset)],
+) -> (MortarAsset, Vec<MortarOverlayShadow>) {
+    let mut data = base.data.clone();
+    let mut metadata = base.metadata.clone();
+    let mut owners: HashMap<(&'static str, String), AssetId<MortarAsset>> = HashMap::new();
+    let mut shadows = Vec::new();
+    for &(id, overlay) in overlays {
+        let mut define = |kind: &'static str, name: &str| {
+            if let Some(previous) = owners.insert((kind, name.to_owned()), id)
+                && previous != id
+            {
+                shadows.push(MortarOverlayShadow {
+                    kind,
+                    name: name.to_owned(),
+                    shadowed: previous,
+                    by: id,
+                });
+            }
+        };
+        for node in &overlay.data.nodes {
+            if overlay.metadata.patch_nodes.contains(&node.name) {
+                patch_node(&mut data, node);
+                continue;
+            }
+            define("node", &node.name);
+            merge_named(&mut data.nodes, std::slice::from_ref(node), |node| {
+                &node.name
+            });
+            let header = overlay.metadata.node_headers.get(&node.name).cloned();
+            match header {
+                Some(header) => metadata.node_headers.insert(node.name.clone(), header),
+                None => metadata.node_headers.remove(&node.name),
+            };
+            let tags = overlay.metadata.node_tags.get(&node.name).cloned();
+            match tags {
+                Some(tags) => metadata.node_tags.insert(node.name.clone(), tags),
+                None => metadata.node_tags.remove(&node.name),
+            };
+            let overrides = overlay.metadata.node_overrides.get(&node.name).cloned();
+            match overrides {
+                Some(overrides) => metadata.node_overrides.insert(node.name.clone(), overrides),
+                None => metadata.node_overrides.remove(&node.name),
+            };
+        }
+        let named = &overlay.data;
+        named
+            .functions
+            .iter()
+            .for_each(|item| define("function", &item.name));
+        named
+            .variables
+            .iter()
+            .for_each(|item| define("variable", &item.name));
+        named
+            .constants
+            .iter()
+            .for_each(|item| define("constant", &item.name));
+        named
+            .enums
+            .iter()
+            .for_each(|item| define("enum", &item.name));
+        named
+            .events
+            .iter()
+            .for_each(|item| define("event", &item.name));
+        named
+            .timelines
+            .iter()
+            .for_each(|item| define("timeline", &item.name));
+        merge_named(&mut data.functions, &named.functions, |item| &item.name);
+        merge_named(&mut data.variables, &named.variables, |item| &item.name);
+        merge_named(&mut data.constants, &named.constants, |item| &item.name);
+        merge_named(&mut data.enums, &named.enums, |item| &item.name);
+        merge_named(&mut data.events, &named.events, |item| &item.name);
+        merge_named(&mut data.timelines, &named.timelines, |item| &item.name);
+    }
+    metadata.patch_nodes.clear();
+    (MortarAsset::with_metadata(data, metadata), shadows)
+}
+
+/// Replaces the items of `base` named like one of `overlay`, and adds the others.
+fn merge_named<T: Clone>(base: &mut Vec<T>, overlay: &[T], name: fn(&T) -> &String) {
+    for item in overlay {
+        match base.iter_mut().find(|own| name(own) == name(item)) {
+            Some(own) => *own = item.clone(),
+            None => base.push(item.clone()),
+        }
+    }
+}
+
+/// Takes the index hint `key` off a patch item.
+fn take_hint(item: &mut Value, key: &str) -> Option<usize> {
+    let hint = item.as_object_mut()?.remove(key)?;
+    hint.as_u64().and_then(|index| usize::try_from(index).ok())
+}
+
+/// Splices the content of the patch node `patch` into the node of the same name in `data`.
+fn patch_node(data: &mut MortaredData, patch: &Node) {
+    let mut items = patch.content.clone();
+    let hints: Vec<_> = items
+        .iter_mut()
+        .map(|item| {
+            (
+                take_hint(item, REPLACE_INDEX),
+                take_hint(item, INSERT_AFTER),
+            )
+        })
+        .collect();
+    let Some(node) = data.nodes.iter_mut().find(|node| node.name == patch.name) else {
+        warn!(
+            target: LOG_ASSET,
+            "Patch node '{}' has no base node to patch; adding it as a new node",
+            patch.name
+        );
+        data.nodes.push(Node {
+            content: items,
+            ..patch.clone()
+        });
+        return;
+    };
+
+    let len = node.content.len();
+    let mut replaced: Vec<Option<Value>> = vec![None; len];
+    let mut inserted: Vec<Vec<Value>> = vec![Vec::new(); len];
+    let mut appended = Vec::new();
+    for (item, hint) in items.into_iter().zip(hints) {
+        match hint {
+            (Some(index), _) if index < len => replaced[index] = Some(item),
+            (None, Some(index)) if index < len => inserted[index].push(item),
+            (None, None) => appended.push(item),
+            (replace, insert) => {
+                warn!(
+                    target: LOG_ASSET,
+                    "Patch of node '{}' targets item {:?} of {}; appending it instead",
+                    patch.name,
+                    replace.or(insert),
+                    len
+                );
+                appended.push(item);
+            }
+        }
+    }
+    let own = std::mem::take(&mut node.content);
+    for ((item, replacement), after) in own.into_iter().zip(replaced).zip(inserted) {
+        node.content.push(replacement.unwrap_or(item));
+        node.content.extend(after);
+    }
+    node.content.extend(appended);
+    if patch.next.is_some() {
+        node.next.clone_from(&patch.next);
+    }
+}
@@ -0,0 +1,79 @@
+//! # parsed_nodes.rs
+//!
+//! # parsed_nodes.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! The parsed form of every node of a file. Starting a node used to read its whole content from
+//! JSON each time, which made hubs entered over and over stall the frame handling the start. Each
+//! asset now reads a node once into a [`ParsedNode`] and hands out shared copies; the loader reads
+//! all of them while the file loads, off the main thread. As with the name lookup tables, a hot
+//! reload replaces the asset and its nodes, and code that edits [`MortarAsset::data`] in place
+//! calls [`MortarAsset::reindex`].
+//!
+//! 文件中每个节点的解析形式。过去每次开始节点都会从 JSON 重新读取其全部内容，使反复进入的枢纽节点
+//! 在处理开始请求的那一帧造成卡顿。现在每个资源只把节点读取一次为 [`ParsedNode`] 并分发共享的副本；
+//! 加载器会在文件加载期间（不在主线程上）读取全部节点。与名称查找表一样，热重载会替换资源及其节点，
+//! 直接修改 [`MortarAsset::data`] 的代码需调用 [`MortarAsset::reindex`]。
+
+use mortar_compiler::Node;
+use std::sync::{Arc, OnceLock};
+
+use super::{MortarAsset, find_node};
+use crate::{MortarDiagnostic, ParsedNode};
+
+/// One slot per node of the file, by declaration position.
+pub(crate) type ParsedNodes = Box<[OnceLock<Arc<ParsedNode>>]>;
+
+impl MortarAsset {
+    fn parsed_slots(&self) -> &ParsedNodes {
+        self.parsed_nodes
+            .get_or_init(|| self.data.nodes.iter().map(|_| OnceLock::new()).collect())
+    }
+
+    /// The node named `name`, read once and shared; `None` when the file does not declare it.
+    ///
+    /// 名为 `name` 的节点，只读取一次并共享；文件未声明该节点时为 `None`。
+    pub fn parsed_node(&self, name: &str) -> Option<Arc<ParsedNode>> {
+        find_node(&self.data, name).map(|node_data| self.parsed(node_data))
+    }
+
+    /// The parsed form of `node_data`, one of this file's nodes. Node data from elsewhere is
+    /// parsed on the spot.
+    pub(crate) fn parsed(&self, node_data: &Node) -> Arc<ParsedNode> {
+        let slot = self
+            .data
+            .nodes
+            .iter()
+            .position(|node| std::ptr::eq(node, node_data))
+            .and_then(|index| self.parsed_slots().get(index));
+        let parse = || Arc::new(ParsedNode::parse(&node_data.name, node_data.clone()));
+        match slot {
+            Some(slot) => slot.get_or_init(parse).clone(),
+            None => parse(),
+        }
+    }
+
+    /// Fields of the file's content items that failed to deserialize, by node. They are read as
+    /// absent, so a text with a malformed `condition` always shows; the loader logs them as
+    /// warnings once per load.
+    ///
+    /// 文件内容项中无法反序列化的字段，按节点排列。它们按缺失处理，因此 `condition` 格式错误的文本
+    /// 总会显示；加载器会在每次加载时将其作为警告记录一次。
+    pub fn content_diagnostics(&self) -> Vec<MortarDiagnostic> {
+        self.data
+            .nodes
+            .iter()
+            .flat_map(|node_data| self.parsed(node_data).diagnostics().to_vec())
+            .collect()
+    }
+
+    /// Reads every node of the file ahead of its first start.
+    pub(crate) fn parse_nodes(&self) {
+        for node_data in &self.data.nodes {
+            self.parsed(node_data);
+        }
+    }
+}
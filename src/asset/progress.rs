@@ -0,0 +1,118 @@
+//! # progress.rs
+//!
+//! # progress.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Load progress of Mortar files. The asset loader runs off the main thread, so it reports each
+//! stage it enters (reading, parsing, compiling, decoding) into a shared queue; a system drains the
+//! queue every frame into [`MortarAssetLoadStage`] messages and into the status of the start
+//! requests waiting on that file, see [`crate::MortarRuntime::pending_start_status`].
+//!
+//! Mortar 文件的加载进度。资源加载器在主线程之外运行，因此会把它进入的每个阶段（读取、解析、编译、
+//! 解码）写入共享队列；一个系统每帧将队列取出，转为 [`MortarAssetLoadStage`] 消息，并更新等待该
+//! 文件的开始请求的状态，见 [`crate::MortarRuntime::pending_start_status`]。
+
+use bevy::prelude::*;
+use std::sync::{Arc, Mutex};
+
+use crate::{MortarRegistry, MortarRuntime};
+
+/// How far the load of a Mortar file has come.
+///
+/// Mortar 文件的加载进展到了哪一步。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MortarLoadStage {
+    /// Requested, but the loader has not reached the file yet.
+    ///
+    /// 已请求，但加载器尚未处理该文件。
+    Queued,
+    /// Reading the file bytes.
+    ///
+    /// 正在读取文件字节。
+    Reading,
+    /// Parsing and checking `.mortar` source.
+    ///
+    /// 正在解析并检查 `.mortar` 源码。
+    Parsing,
+    /// Compiling the parsed program into its `.mortared` form.
+    ///
+    /// 正在把解析后的程序编译为 `.mortared` 形式。
+    Compiling,
+    /// Decoding `.mortared` JSON into the runtime data.
+    ///
+    /// 正在把 `.mortared` JSON 解码为运行时数据。
+    Decoding,
+    /// Loaded, with a strict start waiting for the validation of this version of the file, see
+    /// [`crate::MortarStrictStart::Wait`].
+    ///
+    /// 已加载，严格启动正在等待该版本文件的校验结果，参见 [`crate::MortarStrictStart::Wait`]。
+    Analyzing,
+    /// The asset is ready; waiting starts activate on the next frame.
+    ///
+    /// 资源已就绪；等待中的开始请求会在下一帧激活。
+    Loaded,
+    /// The file could not be read or compiled.
+    ///
+    /// 文件无法读取或编译。
+    Failed,
+}
+
+impl MortarLoadStage {
+    /// Whether the load has ended, successfully or not.
+    ///
+    /// 加载是否已结束（无论成功与否）。
+    pub fn is_terminal(self) -> bool {
+        matches!(self, Self::Loaded | Self::Failed)
+    }
+}
+
+/// Emitted when the loader of a Mortar file enters a stage.
+///
+/// 当 Mortar 文件的加载器进入某个阶段时发出。
+#[derive(Message, Debug, Clone, PartialEq, Eq)]
+pub struct MortarAssetLoadStage {
+    /// Asset path of the file, as the asset server names it.
+    ///
+    /// 资源服务器所使用的文件资源路径。
+    pub path: String,
+    pub stage: MortarLoadStage,
+}
+
+/// Stages reported by loader tasks, waiting for the main thread.
+#[derive(Resource, Clone, Default)]
+pub(crate) struct LoadStageQueue(Arc<Mutex<Vec<MortarAssetLoadStage>>>);
+
+impl LoadStageQueue {
+    pub(crate) fn report(&self, path: &str, stage: MortarLoadStage) {
+        if let Ok(mut stages) = self.0.lock() {
+            stages.push(MortarAssetLoadStage {
+                path: path.to_owned(),
+                stage,
+            });
+        }
+    }
+
+    fn drain(&self) -> Vec<MortarAssetLoadStage> {
+        self.0
+            .lock()
+            .map(|mut stages| std::mem::take(&mut *stages))
+            .unwrap_or_default()
+    }
+}
+
+/// Forwards the stages the loader reported since last frame.
+pub(crate) fn drain_load_stages(
+    queue: Res<LoadStageQueue>,
+    registry: Res<MortarRegistry>,
+    mut runtime: ResMut<MortarRuntime>,
+    mut writer: MessageWriter<MortarAssetLoadStage>,
+) {
+    for message in queue.drain() {
+        let key = registry.canonical_key(&message.path);
+        runtime.note_load_stage(key, message.stage);
+        writer.write(message);
+    }
+}
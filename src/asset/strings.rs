@@ -0,0 +1,338 @@
+//! # strings.rs
+//!
+//! # strings.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Exports every translatable string of a compiled Mortar file as a CSV or JSON table for
+//! localization and voice-over recording. There is one row per text item, per voice of a
+//! `parallel_text` item and per choice option, in node declaration order and then content order,
+//! keyed by the stable line ids of `line_id.rs`. Rows carry the speaker, the raw text, the
+//! `{...}` placeholders a translation must keep, the voice file the line plays and the node tags.
+//!
+//! 将编译后的 Mortar 文件中所有可翻译字符串导出为 CSV 或 JSON 表，供本地化与配音录制使用。每个文本
+//! 项、每个 `parallel_text` 声部以及每个选项各占一行，按节点声明顺序、再按内容顺序排列，并以
+//! `line_id.rs` 的稳定行标识符为键。每行包含说话者、原始文本、译文必须保留的 `{...}` 占位符、该行
+//! 播放的语音文件以及节点标签。
+
+use mortar_compiler::{Condition, MortaredData};
+use serde_json::Value;
+use std::collections::HashMap;
+
+use super::MortarMetadata;
+use crate::dialogue_state::{TextData, choice_line_id};
+use crate::{DialogueState, MortarVariableState};
+
+/// Event actions whose first argument names the voice file of a line, by priority.
+const VOICE_ACTIONS: &[&str] = &["play_voice", "play_sound"];
+
+/// Output format for [`crate::MortarAsset::export_strings`].
+///
+/// [`crate::MortarAsset::export_strings`] 的输出格式。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StringsFormat {
+    /// Comma-separated values with a header row.
+    ///
+    /// 带表头行的逗号分隔值。
+    Csv,
+    /// A JSON array with one object per row.
+    ///
+    /// 每行一个对象的 JSON 数组。
+    Json,
+}
+
+/// Options of a strings export. A bare [`StringsFormat`] converts into one without conditions.
+///
+/// 字符串导出的选项。单独的 [`StringsFormat`] 可转换为不含条件列的选项。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StringsExport {
+    pub format: StringsFormat,
+    /// Adds a `condition` column with the line's condition in script form.
+    ///
+    /// 添加 `condition` 列，以脚本形式写出该行的条件。
+    pub include_conditions: bool,
+}
+
+impl From<StringsFormat> for StringsExport {
+    fn from(format: StringsFormat) -> Self {
+        Self {
+            format,
+            include_conditions: false,
+        }
+    }
+}
+
+/// One translatable string.
+pub(crate) struct StringRow {
+    line_id: String,
+    node: String,
+    kind: &'static str,
+    /// `text_index` of a text, or the option path of a choice such as `1.0`.
+    index: String,
+    speaker: String,
+    raw_text: String,
+    placeholders: Vec<String>,
+    voice_key: String,
+    tags: Vec<String>,
+    condition: String,
+}
+
+/// The rows of every node of `data`, in declaration order.
+pub(crate) fn string_rows(data: &MortaredData, metadata: &MortarMetadata) -> Vec<StringRow> {
+    let mut rows = Vec::new();
+    for node in &data.nodes {
+        let state = DialogueState::new(String::new(), node.name.clone(), node.clone());
+        let context = NodeContext {
+            node: &node.name,
+            header: metadata.node_header(&node.name).unwrap_or_default(),
+            tags: metadata.node_tags(&node.name),
+        };
+        let mut occurrences = HashMap::new();
+        for (content_index, content) in node.content.iter().enumerate() {
+            // Texts a `continuation` adds share the content index and follow in declaration order.
+            let texts: Vec<usize> = (0..state.text_positions().len())
+                .filter(|&index| state.text_positions()[index].content_index == content_index)
+                .collect();
+            match texts.as_slice() {
+                [] if content.get("type").and_then(Value::as_str) == Some("choice") => {
+                    let options = content.get("options").and_then(Value::as_array);
+                    context.choice_rows(
+                        options.map(Vec::as_slice).unwrap_or_default(),
+                        "",
+                        &mut occurrences,
+                        &mut rows,
+                    );
+                }
+                [] => {}
+                texts => texts.iter().for_each(|&text_index| {
+                    let raw = state.text_source(text_index).unwrap_or(content);
+                    context.item_rows(&state, text_index, raw, &mut rows);
+                }),
+            }
+        }
+    }
+    rows
+}
+
+/// What every row of one node shares.
+struct NodeContext<'a> {
+    node: &'a str,
+    header: &'a str,
+    tags: &'a [String],
+}
+
+impl NodeContext<'_> {
+    /// Rows of a text item, or of each voice of a `parallel_text` item.
+    fn item_rows(
+        &self,
+        state: &DialogueState,
+        text_index: usize,
+        content: &Value,
+        rows: &mut Vec<StringRow>,
+    ) {
+        let item = &state.text_items()[text_index];
+        if item.parallel.is_empty() {
+            rows.push(self.text_row(item, content, text_index));
+            return;
+        }
+        let voices = content.get("texts").and_then(Value::as_array);
+        for (voice, raw) in item.parallel.iter().zip(voices.into_iter().flatten()) {
+            rows.push(self.text_row(voice, raw, text_index));
+        }
+    }
+
+    fn text_row(&self, item: &TextData, raw: &Value, text_index: usize) -> StringRow {
+        let string = |key| raw.get(key).and_then(Value::as_str);
+        let speaker = string("speaker")
+            .or(item.channel.as_deref())
+            .or(item.header.as_deref())
+            .unwrap_or(self.header);
+        let voice_key = string("voice").map(str::to_owned).or_else(|| {
+            let actions = item
+                .events
+                .iter()
+                .flatten()
+                .flat_map(|event| &event.actions);
+            VOICE_ACTIONS.iter().find_map(|name| {
+                actions
+                    .clone()
+                    .find(|action| action.action_type == *name)
+                    .and_then(|action| action.args.first().cloned())
+            })
+        });
+        let condition = item
+            .condition
+            .as_ref()
+            .map_or_else(String::new, |condition| {
+                crate::dialogue::describe_condition(condition, &MortarVariableState::default())
+            });
+        StringRow {
+            line_id: item.line_id.clone(),
+            node: self.node.to_owned(),
+            kind: "text",
+            index: text_index.to_string(),
+            speaker: speaker.to_owned(),
+            raw_text: item.value.clone(),
+            placeholders: placeholders(&item.value),
+            voice_key: voice_key.unwrap_or_default(),
+            tags: self.tags.to_vec(),
+            condition,
+        }
+    }
+
+    /// Rows of the options in `options` and their nested groups; `prefix` is the group's path.
+    fn choice_rows(
+        &self,
+        options: &[Value],
+        prefix: &str,
+        occurrences: &mut HashMap<String, usize>,
+        rows: &mut Vec<StringRow>,
+    ) {
+        for (index, option) in options.iter().enumerate() {
+            let string = |key| option.get(key).and_then(Value::as_str);
+            let text = string("text").unwrap_or_default();
+            let path = format!("{prefix}{index}");
+            let occurrence = occurrences.entry(text.to_owned()).or_default();
+            let line_id = string("id").map_or_else(
+                || choice_line_id(self.node, text, *occurrence),
+                str::to_owned,
+            );
+            *occurrence += 1;
+            let condition = option
+                .get("condition")
+                .and_then(|condition| serde_json::from_value::<Condition>(condition.clone()).ok())
+                .map_or_else(String::new, |condition| {
+                    format!(
+                        "{}({})",
+                        condition.condition_type,
+                        condition.args.join(", ")
+                    )
+                });
+            rows.push(StringRow {
+                line_id,
+                node: self.node.to_owned(),
+                kind: "choice",
+                index: path.clone(),
+                speaker: String::new(),
+                raw_text: text.to_owned(),
+                placeholders: placeholders(text),
+                voice_key: String::new(),
+                tags: self.tags.to_vec(),
+                condition,
+            });
+            if let Some(nested) = option.get("choice").and_then(Value::as_array) {
+                self.choice_rows(nested, &format!("{path}."), occurrences, rows);
+            }
+        }
+    }
+}
+
+/// The distinct `{...}` tokens of `text`, in order of appearance.
+fn placeholders(text: &str) -> Vec<String> {
+    let mut found: Vec<String> = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('{') {
+        let Some(length) = rest[start..].find('}') else {
+            break;
+        };
+        let token = &rest[start..=start + length];
+        if !found.iter().any(|known| known == token) {
+            found.push(token.to_owned());
+        }
+        rest = &rest[start + length + 1..];
+    }
+    found
+}
+
+/// Renders `rows`, each with the file it came from when exporting several.
+pub(crate) fn render_rows(rows: &[(Option<&str>, StringRow)], export: StringsExport) -> String {
+    let with_file = rows.iter().any(|(file, _)| file.is_some());
+    match export.format {
+        StringsFormat::Csv => render_csv(rows, with_file, export.include_conditions),
+        StringsFormat::Json => render_json(rows, with_file, export.include_conditions),
+    }
+}
+
+const COLUMNS: &[&str] = &[
+    "line_id",
+    "node",
+    "kind",
+    "index",
+    "speaker",
+    "raw_text",
+    "placeholders",
+    "voice_key",
+    "tags",
+];
+
+fn render_csv(rows: &[(Option<&str>, StringRow)], with_file: bool, conditions: bool) -> String {
+    let mut header: Vec<&str> = COLUMNS.to_vec();
+    if with_file {
+        header.insert(0, "file");
+    }
+    if conditions {
+        header.push("condition");
+    }
+    let mut out = header.join(",");
+    out.push('\n');
+    for (file, row) in rows {
+        let mut fields = vec![
+            row.line_id.clone(),
+            row.node.clone(),
+            row.kind.to_owned(),
+            row.index.clone(),
+            row.speaker.clone(),
+            row.raw_text.clone(),
+            row.placeholders.join(" "),
+            row.voice_key.clone(),
+            row.tags.join(";"),
+        ];
+        if with_file {
+            fields.insert(0, file.unwrap_or_default().to_owned());
+        }
+        if conditions {
+            fields.push(row.condition.clone());
+        }
+        let escaped: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+        out.push_str(&escaped.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+/// Quotes a field holding a separator, a quote or a line break, doubling its quotes.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+fn render_json(rows: &[(Option<&str>, StringRow)], with_file: bool, conditions: bool) -> String {
+    let objects: Vec<Value> = rows
+        .iter()
+        .map(|(file, row)| {
+            let mut object = serde_json::Map::new();
+            if with_file {
+                object.insert("file".into(), file.unwrap_or_default().into());
+            }
+            object.insert("line_id".into(), row.line_id.clone().into());
+            object.insert("node".into(), row.node.clone().into());
+            object.insert("kind".into(), row.kind.into());
+            object.insert("index".into(), row.index.clone().into());
+            object.insert("speaker".into(), row.speaker.clone().into());
+            object.insert("raw_text".into(), row.raw_text.clone().into());
+            object.insert("placeholders".into(), row.placeholders.clone().into());
+            object.insert("voice_key".into(), row.voice_key.clone().into());
+            object.insert("tags".into(), row.tags.clone().into());
+            if conditions {
+                object.insert("condition".into(), row.condition.clone().into());
+            }
+            Value::Object(object)
+        })
+        .collect();
+    serde_json::to_string_pretty(&objects).unwrap_or_default()
+}
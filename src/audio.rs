@@ -8,12 +8,14 @@
 //!
 //! Contains the optional audio bridge for Mortar dialogue events. It interprets
 //! `play_sound` gameplay events emitted by the dialogue layer and, when enabled, spawns Bevy audio
-//! players using the configured playback policy.
+//! players using the configured playback policy. An event naming an entity, as in
+//! `play_sound("creak.wav", @door)`, attaches its player to that entity and plays it spatially.
 //!
 //! 包含 Mortar 对话事件到音频系统的可选桥接。它会解释对话层发出的 `play_sound`
-//! 游戏事件，并在启用时按照配置好的播放策略生成 Bevy 音频播放器。
+//! 游戏事件，并在启用时按照配置好的播放策略生成 Bevy 音频播放器。指定了实体的事件（例如
+//! `play_sound("creak.wav", @door)`）会把播放器挂到该实体上并以空间音频播放。
 
-use crate::MortarGameEvent;
+use crate::{MortarGameEvent, MortarInternal};
 use bevy::prelude::*;
 
 /// Configures how Mortar handles `play_sound` events.
@@ -57,7 +59,18 @@ pub(crate) fn auto_play_sound_events(
 
         if let Some(path) = event.args.first() {
             let audio_handle = asset_server.load::<AudioSource>(path.clone());
-            commands.spawn((AudioPlayer::new(audio_handle), settings.playback_settings));
+            let mut player = commands.spawn((
+                AudioPlayer::new(audio_handle),
+                settings.playback_settings,
+                MortarInternal,
+            ));
+            if let Some(&target) = event.targets.first() {
+                player.insert((
+                    settings.playback_settings.with_spatial(true),
+                    Transform::default(),
+                    ChildOf(target),
+                ));
+            }
         }
     }
 }
@@ -64,11 +64,137 @@ fn generate_arg_conversion(
     }
 }
 
-/// Generate registration code for a single method.
-fn generate_registration(method: &syn::ImplItemFn) -> proc_macro2::TokenStream {
+/// The `MortarParamKind` the registry coerces a script argument of type `ty` to.
+fn param_kind(ty: &syn::Type) -> proc_macro2::TokenStream {
+    let type_str = quote!(#ty).to_string().replace(" ", "");
+    if type_str.contains("MortarString") {
+        quote!(bevy_mortar_bond::MortarParamKind::String)
+    } else if type_str.contains("MortarNumber") {
+        quote!(bevy_mortar_bond::MortarParamKind::Number)
+    } else if type_str.contains("MortarBoolean") {
+        quote!(bevy_mortar_bond::MortarParamKind::Boolean)
+    } else {
+        quote!(bevy_mortar_bond::MortarParamKind::Any)
+    }
+}
+
+/// Reads `#[mortar(strict)]` or `#[mortar(lenient)]` from a method.
+fn coercion_policy(method: &syn::ImplItemFn) -> syn::Result<Option<proc_macro2::TokenStream>> {
+    let mut policy = None;
+    for attr in method
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("mortar"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("strict") {
+                policy = Some(quote!(bevy_mortar_bond::CoercionPolicy::Strict));
+                Ok(())
+            } else if meta.path.is_ident("lenient") {
+                policy = Some(quote!(bevy_mortar_bond::CoercionPolicy::Lenient));
+                Ok(())
+            } else {
+                Err(meta.error("expected `strict` or `lenient`"))
+            }
+        })?;
+    }
+    Ok(policy)
+}
+
+/// Generate the parameter kinds and coercion policy declarations for a method.
+fn generate_coercion_setup(
+    method: &syn::ImplItemFn,
+    script_args: &[&syn::Type],
+) -> proc_macro2::TokenStream {
+    let fn_name_str = method.sig.ident.to_string();
+    let kinds = script_args.iter().map(|ty| param_kind(ty));
+    let policy = match coercion_policy(method) {
+        Ok(Some(policy)) => quote! { registry.set_policy(#fn_name_str, #policy); },
+        Ok(None) => quote! {},
+        Err(err) => err.to_compile_error(),
+    };
+    quote! {
+        registry.set_param_kinds(#fn_name_str, vec![#(#kinds),*]);
+        #policy
+    }
+}
+
+/// Whether a parameter is the call context (`&MortarCallContext`), which is not a script argument.
+fn is_call_context(ty: &syn::Type) -> bool {
+    matches!(ty, syn::Type::Reference(reference)
+        if quote!(#reference).to_string().replace(" ", "").ends_with("MortarCallContext"))
+}
+
+/// Generate registration code for a method whose first parameter is `&MortarCallContext`.
+fn generate_context_registration(
+    fn_name_str: &str,
+    callee: &proc_macro2::TokenStream,
+    args: &[&syn::Type],
+    returns_void: bool,
+) -> proc_macro2::TokenStream {
+    let arity = args.len();
+    let arg_names: Vec<syn::Ident> = (0..arity)
+        .map(|i| syn::Ident::new(&format!("arg{i}"), proc_macro2::Span::call_site()))
+        .collect();
+    let arg_conversions: Vec<_> = args
+        .iter()
+        .enumerate()
+        .zip(arg_names.iter())
+        .map(|((idx, ty), name)| generate_arg_conversion(ty, idx, name))
+        .collect();
+    let call = quote! { #callee(context, #(#arg_names),*) };
+    let result = if returns_void {
+        quote! { #call; bevy_mortar_bond::MortarValue::Void }
+    } else {
+        quote! { #call.into() }
+    };
+
+    let args_param = if arity == 0 {
+        quote!(_args)
+    } else {
+        quote!(args)
+    };
+
+    quote! {
+        registry.register_with_context_arity(#fn_name_str, #arity, move |context, #args_param| {
+            #(#arg_conversions)*
+            #result
+        });
+    }
+}
+
+/// Whether a method takes `&self` or `&mut self`, or an error for receivers an instance behind a
+/// lock cannot be called with, such as `self` by value.
+fn takes_reference_receiver(method: &syn::ImplItemFn) -> syn::Result<bool> {
+    match method.sig.receiver() {
+        None => Ok(false),
+        Some(receiver) if receiver.reference.is_some() && receiver.colon_token.is_none() => {
+            Ok(true)
+        }
+        Some(receiver) => Err(syn::Error::new_spanned(
+            receiver,
+            "`#[mortar_functions]` methods take `&self` or `&mut self`; \
+             the bound instance is shared, so it cannot be taken by value",
+        )),
+    }
+}
+
+/// Generate registration code for a single method, calling `Self::method` or, for methods with
+/// a receiver, the method on the locked `instance`.
+fn generate_registration(method: &syn::ImplItemFn, receiver: bool) -> proc_macro2::TokenStream {
     let fn_name = &method.sig.ident;
     let fn_name_str = fn_name.to_string();
     let returns_void = matches!(method.sig.output, ReturnType::Default);
+    let callee = if receiver {
+        quote! {
+            instance
+                .lock()
+                .unwrap_or_else(::std::sync::PoisonError::into_inner)
+                .#fn_name
+        }
+    } else {
+        quote! { Self::#fn_name }
+    };
 
     // Extract argument types (skip self parameter).
     let args: Vec<_> = method
@@ -81,11 +207,42 @@ fn generate_registration(method: &syn::ImplItemFn) -> proc_macro2::TokenStream {
         })
         .collect();
 
-    if args.is_empty() {
-        return generate_no_arg_registration(&fn_name_str, fn_name, returns_void);
+    // A leading `&MortarCallContext` receives the call context; script args map to the rest.
+    let registration = if let Some((first, rest)) = args.split_first()
+        && is_call_context(first)
+    {
+        let registration = generate_context_registration(&fn_name_str, &callee, rest, returns_void);
+        let setup = generate_coercion_setup(method, rest);
+        quote! { #registration #setup }
+    } else {
+        let registration = generate_plain_registration(&fn_name_str, &callee, &args, returns_void);
+        let setup = generate_coercion_setup(method, &args);
+        quote! { #registration #setup }
+    };
+
+    // Each closure holds its own handle on the instance.
+    if receiver {
+        quote! {
+            {
+                let instance = ::std::sync::Arc::clone(&instance);
+                #registration
+            }
+        }
+    } else {
+        registration
     }
+}
 
-    let arg_names: Vec<syn::Ident> = (0..args.len())
+/// Generate registration code for a method taking only script arguments.
+fn generate_plain_registration(
+    fn_name_str: &str,
+    callee: &proc_macro2::TokenStream,
+    args: &[&syn::Type],
+    returns_void: bool,
+) -> proc_macro2::TokenStream {
+    let arity = args.len();
+
+    let arg_names: Vec<syn::Ident> = (0..arity)
         .map(|i| syn::Ident::new(&format!("arg{i}"), proc_macro2::Span::call_site()))
         .collect();
 
@@ -96,68 +253,220 @@ fn generate_registration(method: &syn::ImplItemFn) -> proc_macro2::TokenStream {
         .map(|((idx, ty), name)| generate_arg_conversion(ty, idx, name))
         .collect();
 
+    let args_param = if arity == 0 {
+        quote!(_args)
+    } else {
+        quote!(args)
+    };
+
     if returns_void {
         quote! {
-            registry.register(#fn_name_str, |args| {
+            registry.register_with_arity(#fn_name_str, #arity, move |#args_param| {
                 #(#arg_conversions)*
-                Self::#fn_name(#(#arg_names),*);
+                #callee(#(#arg_names),*);
                 bevy_mortar_bond::MortarValue::Void
             });
         }
     } else {
         quote! {
-            registry.register(#fn_name_str, |args| {
+            registry.register_with_arity(#fn_name_str, #arity, move |#args_param| {
                 #(#arg_conversions)*
-                Self::#fn_name(#(#arg_names),*).into()
+                #callee(#(#arg_names),*).into()
             });
         }
     }
 }
 
-fn generate_no_arg_registration(
-    fn_name_str: &str,
-    fn_name: &syn::Ident,
-    returns_void: bool,
-) -> proc_macro2::TokenStream {
-    if returns_void {
-        quote! {
-            registry.register(#fn_name_str, |_args| {
-                Self::#fn_name();
-                bevy_mortar_bond::MortarValue::Void
-            });
-        }
+/// The Mortar type named in docs for a script argument of type `ty`.
+fn arg_doc_type(ty: &syn::Type) -> &'static str {
+    let type_str = quote!(#ty).to_string().replace(" ", "");
+    if type_str.contains("MortarString") {
+        "String"
+    } else if type_str.contains("MortarNumber") {
+        "Number"
+    } else if type_str.contains("MortarBoolean") {
+        "Boolean"
     } else {
+        "Any"
+    }
+}
+
+/// The Mortar type named in docs for what a method returns, after `.into()` a `MortarValue`.
+fn return_doc_type(output: &ReturnType) -> &'static str {
+    let ReturnType::Type(_, ty) = output else {
+        return "Void";
+    };
+    let type_str = quote!(#ty).to_string().replace(" ", "");
+    match type_str.as_str() {
+        "MortarVoid" | "()" => "Void",
+        "bool" | "MortarBoolean" => "Boolean",
+        "f64" | "i32" | "usize" | "MortarNumber" => "Number",
+        _ if type_str.contains("MortarVoid") => "Void",
+        _ if type_str.contains("MortarBoolean") => "Boolean",
+        _ if type_str.contains("MortarNumber") => "Number",
+        _ if type_str.contains("String") || type_str.contains("str") => "String",
+        _ => "Any",
+    }
+}
+
+/// The text of a method's doc comment, one line per `///` line, trimmed.
+fn doc_text(attrs: &[syn::Attribute]) -> String {
+    let lines: Vec<String> = attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .filter_map(|attr| match &attr.meta {
+            syn::Meta::NameValue(syn::MetaNameValue {
+                value:
+                    syn::Expr::Lit(syn::ExprLit {
+                        lit: syn::Lit::Str(text),
+                        ..
+                    }),
+                ..
+            }) => Some(text.value()),
+            _ => None,
+        })
+        .map(|line| {
+            line.strip_prefix(' ')
+                .unwrap_or(&line)
+                .trim_end()
+                .to_owned()
+        })
+        .collect();
+    lines.join("\n").trim().to_owned()
+}
+
+/// Generate the `MortarFnDoc` of a method bound from the impl of `source`.
+fn generate_doc(method: &syn::ImplItemFn, source: &str) -> proc_macro2::TokenStream {
+    let name = method.sig.ident.to_string();
+    let mut args: Vec<(String, &syn::Type)> = method
+        .sig
+        .inputs
+        .iter()
+        .filter_map(|arg| match arg {
+            syn::FnArg::Typed(pat_type) => {
+                let pat = &pat_type.pat;
+                let name = match pat.as_ref() {
+                    syn::Pat::Ident(ident) => ident.ident.to_string(),
+                    _ => quote!(#pat).to_string(),
+                };
+                Some((name, pat_type.ty.as_ref()))
+            }
+            _ => None,
+        })
+        .collect();
+    if args.first().is_some_and(|(_, ty)| is_call_context(ty)) {
+        args.remove(0);
+    }
+    let args = args.iter().map(|(name, ty)| {
+        let name = name.trim_start_matches('_');
+        let ty = arg_doc_type(ty);
         quote! {
-            registry.register(#fn_name_str, |_args| {
-                Self::#fn_name().into()
-            });
+            bevy_mortar_bond::MortarFnArgDoc {
+                name: ::std::borrow::Cow::Borrowed(#name),
+                ty: ::std::borrow::Cow::Borrowed(#ty),
+            }
+        }
+    });
+    let returns = return_doc_type(&method.sig.output);
+    let description = doc_text(&method.attrs);
+    quote! {
+        bevy_mortar_bond::MortarFnDoc {
+            source: ::std::borrow::Cow::Borrowed(#source),
+            name: ::std::borrow::Cow::Borrowed(#name),
+            args: ::std::borrow::Cow::Borrowed(&[#(#args),*]),
+            returns: ::std::borrow::Cow::Borrowed(#returns),
+            description: ::std::borrow::Cow::Borrowed(#description),
         }
     }
 }
 
+/// Binds the methods of an impl as Mortar functions. Associated functions are bound by the
+/// generated `bind_functions`; methods taking `&self` or `&mut self` are bound, with the rest, by
+/// `bind_functions_with`, which calls them on a shared instance.
 #[proc_macro_attribute]
 pub fn mortar_functions(_attr: TokenStream, item: TokenStream) -> TokenStream {
     let input = parse_macro_input!(item as ItemImpl);
+    expand_mortar_functions(input).into()
+}
+
+fn expand_mortar_functions(mut input: ItemImpl) -> proc_macro2::TokenStream {
     let self_ty = &input.self_ty;
+    let source = quote!(#self_ty).to_string().replace(" ", "");
 
-    let function_registrations: Vec<_> = input
-        .items
-        .iter()
-        .filter_map(|item| match item {
-            syn::ImplItem::Fn(method) => Some(generate_registration(method)),
-            _ => None,
-        })
-        .collect();
+    let mut function_registrations = Vec::new();
+    let mut instance_registrations = Vec::new();
+    let mut function_docs = Vec::new();
+    let mut static_docs = Vec::new();
+    for item in &input.items {
+        let syn::ImplItem::Fn(method) = item else {
+            continue;
+        };
+        let doc = generate_doc(method, &source);
+        match takes_reference_receiver(method) {
+            Ok(true) => instance_registrations.push(generate_registration(method, true)),
+            Ok(false) => {
+                function_registrations.push(generate_registration(method, false));
+                static_docs.push(doc.clone());
+            }
+            Err(err) => function_registrations.push(err.to_compile_error()),
+        }
+        function_docs.push(doc);
+    }
 
-    let expanded = quote! {
+    // `#[mortar(...)]` is only read here, so it must not reach the compiler.
+    for item in &mut input.items {
+        if let syn::ImplItem::Fn(method) = item {
+            method.attrs.retain(|attr| !attr.path().is_ident("mortar"));
+        }
+    }
+
+    let self_ty = &input.self_ty;
+    let (install_docs, bind_with) = if instance_registrations.is_empty() {
+        (
+            quote! { registry.install_docs(Self::MORTAR_FUNCTION_DOCS); },
+            quote! {},
+        )
+    } else {
+        let bind_with = quote! {
+            /// Binds every function of this impl, calling the `&self` and `&mut self` methods on
+            /// `instance`. Keep a clone of the `Arc`, for example in a resource, to read or change
+            /// the state the script sees.
+            pub fn bind_functions_with(
+                registry: &mut bevy_mortar_bond::MortarFunctionRegistry,
+                instance: ::std::sync::Arc<::std::sync::Mutex<Self>>,
+            ) where
+                Self: Send + 'static,
+            {
+                Self::bind_functions(registry);
+                #(#instance_registrations)*
+                registry.install_docs(Self::MORTAR_FUNCTION_DOCS);
+            }
+        };
+        (
+            quote! { registry.install_docs(&[#(#static_docs),*]); },
+            bind_with,
+        )
+    };
+
+    quote! {
         #input
 
         impl #self_ty {
+            /// Writer-facing docs of the functions this impl binds.
+            pub const MORTAR_FUNCTION_DOCS: &'static [bevy_mortar_bond::MortarFnDoc] =
+                &[#(#function_docs),*];
+
+            /// Binds the associated functions of this impl; methods with a receiver are bound by
+            /// `bind_functions_with`, when there are any.
             pub fn bind_functions(registry: &mut bevy_mortar_bond::MortarFunctionRegistry) {
                 #(#function_registrations)*
+                #install_docs
             }
-        }
-    };
 
-    expanded.into()
+            #bind_with
+        }
+    }
 }
+
+#[cfg(test)]
+mod tests;
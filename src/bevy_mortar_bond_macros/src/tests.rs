@@ -0,0 +1,61 @@
+//! Covers the expansion of `#[mortar_functions]` for methods with a receiver: `&self` and
+//! `&mut self` methods get a `bind_functions_with` that calls them on a shared instance, impls
+//! without them get none, and receivers taken by value are rejected with a compile error.
+//!
+//! 覆盖 `#[mortar_functions]` 对带接收者方法的展开：`&self` 与 `&mut self` 方法会得到在共享实例上
+//! 调用它们的 `bind_functions_with`；没有此类方法的 impl 不会生成它；按值获取的接收者会以编译错误
+//! 拒绝。
+
+use super::expand_mortar_functions;
+use syn::parse_quote;
+
+fn expand(input: syn::ItemImpl) -> String {
+    expand_mortar_functions(input).to_string().replace(' ', "")
+}
+
+#[test]
+fn test_reference_receivers_are_bound_on_the_instance() {
+    let expanded = expand(parse_quote! {
+        impl Inventory {
+            fn has_item(&self, item: MortarString) -> bool { true }
+            fn give(&mut self, item: MortarString) {}
+            fn capacity() -> f64 { 8.0 }
+        }
+    });
+    assert!(expanded.contains("pubfnbind_functions_with("));
+    assert!(expanded.contains(".unwrap_or_else(::std::sync::PoisonError::into_inner).has_item("));
+    assert!(expanded.contains(".unwrap_or_else(::std::sync::PoisonError::into_inner).give("));
+    assert!(expanded.contains("Self::capacity("));
+    assert!(!expanded.contains("compile_error"));
+}
+
+#[test]
+fn test_associated_functions_only_get_no_instance_binding() {
+    let expanded = expand(parse_quote! {
+        impl Weather {
+            fn daylight() -> f64 { 6.0 }
+        }
+    });
+    assert!(expanded.contains("pubfnbind_functions("));
+    assert!(!expanded.contains("pubfnbind_functions_with"));
+}
+
+#[test]
+fn test_receivers_taken_by_value_are_rejected() {
+    for input in [
+        parse_quote! {
+            impl Inventory {
+                fn consume(self) {}
+            }
+        },
+        parse_quote! {
+            impl Inventory {
+                fn consume(self: Box<Self>) {}
+            }
+        },
+    ] {
+        let expanded = expand(input);
+        assert!(expanded.contains("compile_error"), "{expanded}");
+        assert!(expanded.contains("cannotbetakenbyvalue"), "{expanded}");
+    }
+}
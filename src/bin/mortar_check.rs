@@ -0,0 +1,289 @@
+//! # mortar_check.rs
+//!
+//! # mortar_check.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Headless validator for CI pipelines. Compiles every matching `.mortar` / `.mortared` file with
+//! the same loader code the plugin uses, checks node references, run targets and (optionally)
+//! function bindings, and exits non-zero when any file has errors. With `--lint` it also reports
+//! soft lints, which count as warnings. With `--docs` it also writes a Markdown reference of the
+//! functions the files declare, one section per file.
+//!
+//! 面向 CI 流水线的无界面校验工具。使用与插件相同的加载代码编译每个匹配的 `.mortar` /
+//! `.mortared` 文件，检查节点引用、run 目标以及（可选的）函数绑定，任意文件存在错误时以非零状态退出。
+//! 使用 `--lint` 时还会报告软性 lint，它们按警告计算。使用 `--docs` 时还会写出这些文件所声明函数的
+//! Markdown 参考文档，每个文件一节。
+//!
+//! ```text
+//! cargo run --features cli --bin mortar-check -- 'assets/**/*.mortar' --bindings bindings.json
+//! ```
+
+use bevy_mortar_bond::{
+    MortarAssetLoader, MortarFnDoc, MortarFunctionManifest, MortarLintConfig,
+    MortarValidationReport, lint_mortar_file, render_markdown, validate_mortar_file,
+};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+const USAGE: &str = "usage: mortar-check <PATH|GLOB>... [--bindings <FILE>] [--format text|json] [--deny-warnings] [--lint] [--docs <FILE>]";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OutputFormat {
+    Text,
+    Json,
+}
+
+struct Options {
+    patterns: Vec<String>,
+    bindings: Option<PathBuf>,
+    format: OutputFormat,
+    deny_warnings: bool,
+    lint: bool,
+    docs: Option<PathBuf>,
+}
+
+fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options {
+        patterns: Vec::new(),
+        bindings: None,
+        format: OutputFormat::Text,
+        deny_warnings: false,
+        lint: false,
+        docs: None,
+    };
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--bindings" => {
+                let path = args.next().ok_or("--bindings expects a file path")?;
+                options.bindings = Some(PathBuf::from(path));
+            }
+            "--format" => {
+                options.format = match args.next().as_deref() {
+                    Some("text") => OutputFormat::Text,
+                    Some("json") => OutputFormat::Json,
+                    other => return Err(format!("unknown format: {other:?}")),
+                };
+            }
+            "--deny-warnings" => options.deny_warnings = true,
+            "--lint" => options.lint = true,
+            "--docs" => {
+                let path = args.next().ok_or("--docs expects a file path")?;
+                options.docs = Some(PathBuf::from(path));
+            }
+            "-h" | "--help" => return Err(String::new()),
+            flag if flag.starts_with("--") => return Err(format!("unknown flag: {flag}")),
+            _ => options.patterns.push(arg),
+        }
+    }
+
+    if options.patterns.is_empty() {
+        return Err("no input files given".to_string());
+    }
+    Ok(options)
+}
+
+/// Matches a single path component against a pattern supporting `*` and `?`.
+///
+/// 将单个路径段与支持 `*` 和 `?` 的模式进行匹配。
+fn matches_component(pattern: &[char], name: &[char]) -> bool {
+    match (pattern.first(), name.first()) {
+        (None, None) => true,
+        (Some('*'), _) => {
+            matches_component(&pattern[1..], name)
+                || (!name.is_empty() && matches_component(pattern, &name[1..]))
+        }
+        (Some('?'), Some(_)) => matches_component(&pattern[1..], &name[1..]),
+        (Some(p), Some(n)) if p == n => matches_component(&pattern[1..], &name[1..]),
+        _ => false,
+    }
+}
+
+fn read_dir_sorted(dir: &Path) -> Vec<PathBuf> {
+    let mut entries: Vec<PathBuf> = std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .collect()
+        })
+        .unwrap_or_default();
+    entries.sort();
+    entries
+}
+
+/// Walks `base` applying the remaining glob components, `**` matching any depth.
+///
+/// 从 `base` 出发按剩余的 glob 段遍历，`**` 匹配任意层级。
+fn expand_components(base: &Path, components: &[&str], out: &mut Vec<PathBuf>) {
+    let Some((first, rest)) = components.split_first() else {
+        if base.is_file() {
+            out.push(base.to_path_buf());
+        }
+        return;
+    };
+
+    if *first == "**" {
+        expand_components(base, rest, out);
+        for entry in read_dir_sorted(base)
+            .into_iter()
+            .filter(|path| path.is_dir())
+        {
+            expand_components(&entry, components, out);
+        }
+        return;
+    }
+
+    if !first.contains(['*', '?']) {
+        expand_components(&base.join(first), rest, out);
+        return;
+    }
+
+    let pattern: Vec<char> = first.chars().collect();
+    for entry in read_dir_sorted(base) {
+        let name: Vec<char> = entry
+            .file_name()
+            .map(|name| name.to_string_lossy().chars().collect())
+            .unwrap_or_default();
+        if matches_component(&pattern, &name) {
+            expand_components(&entry, rest, out);
+        }
+    }
+}
+
+/// Expands a path or glob pattern into the files it names.
+///
+/// 将路径或 glob 模式展开为对应的文件列表。
+fn expand_pattern(pattern: &str) -> Vec<PathBuf> {
+    if !pattern.contains(['*', '?']) {
+        return vec![PathBuf::from(pattern)];
+    }
+    let (base, relative) = match pattern.strip_prefix('/') {
+        Some(relative) => (PathBuf::from("/"), relative),
+        None => (PathBuf::from("."), pattern),
+    };
+    let components: Vec<&str> = relative.split('/').filter(|c| !c.is_empty()).collect();
+    let mut out = Vec::new();
+    expand_components(&base, &components, &mut out);
+    out.dedup();
+    out
+}
+
+fn load_manifest(path: &Path) -> Result<MortarFunctionManifest, String> {
+    let json = std::fs::read_to_string(path)
+        .map_err(|err| format!("failed to read {}: {err}", path.display()))?;
+    MortarFunctionManifest::from_json(&json)
+        .map_err(|err| format!("invalid bindings manifest {}: {err}", path.display()))
+}
+
+/// Writes the functions `files` declare to `out` as Markdown. Files that fail to decode are
+/// skipped; the validation report names them.
+///
+/// 将 `files` 所声明的函数以 Markdown 写入 `out`。无法解码的文件会被跳过，校验报告会列出它们。
+fn write_docs(files: &[PathBuf], out: &Path) -> Result<(), String> {
+    let mut docs = Vec::new();
+    for path in files {
+        let Ok(bytes) = std::fs::read(path) else {
+            continue;
+        };
+        let Ok(asset) = MortarAssetLoader::load_asset_bytes(&bytes, path) else {
+            continue;
+        };
+        let source = path.display().to_string();
+        docs.extend(
+            asset
+                .data
+                .functions
+                .iter()
+                .map(|decl| MortarFnDoc::from_declaration(source.clone(), decl)),
+        );
+    }
+    std::fs::write(out, render_markdown(&docs))
+        .map_err(|err| format!("failed to write {}: {err}", out.display()))
+}
+
+fn file_failed(report: &MortarValidationReport, deny_warnings: bool) -> bool {
+    report.has_errors() || (deny_warnings && !(report.issues.is_empty() && report.lints.is_empty()))
+}
+
+fn main() -> ExitCode {
+    let options = match parse_args(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(message) => {
+            if !message.is_empty() {
+                eprintln!("error: {message}");
+            }
+            eprintln!("{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+
+    let manifest = match options.bindings.as_deref().map(load_manifest).transpose() {
+        Ok(manifest) => manifest,
+        Err(message) => {
+            eprintln!("error: {message}");
+            return ExitCode::from(2);
+        }
+    };
+
+    let mut files = Vec::new();
+    for pattern in &options.patterns {
+        let expanded = expand_pattern(pattern);
+        if expanded.is_empty() {
+            eprintln!("warning: pattern '{pattern}' matched no files");
+        }
+        files.extend(expanded);
+    }
+
+    if let Some(out) = options.docs.as_deref()
+        && let Err(message) = write_docs(&files, out)
+    {
+        eprintln!("error: {message}");
+        return ExitCode::from(2);
+    }
+
+    let lint_config = MortarLintConfig::default();
+    let reports: Vec<MortarValidationReport> = files
+        .iter()
+        .map(|path| {
+            if options.lint {
+                lint_mortar_file(path, manifest.as_ref(), &lint_config)
+            } else {
+                validate_mortar_file(path, manifest.as_ref())
+            }
+        })
+        .collect();
+    let failed = reports
+        .iter()
+        .filter(|report| file_failed(report, options.deny_warnings))
+        .count();
+
+    match options.format {
+        OutputFormat::Text => {
+            for report in &reports {
+                print!("{report}");
+            }
+            println!("{} file(s) checked, {failed} failed", reports.len());
+        }
+        OutputFormat::Json => {
+            let json = serde_json::json!({
+                "files": reports.iter().map(MortarValidationReport::to_json_value).collect::<Vec<_>>(),
+                "checked": reports.len(),
+                "failed": failed,
+            });
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&json).unwrap_or_default()
+            );
+        }
+    }
+
+    if failed > 0 {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
@@ -35,7 +35,32 @@
 //!
 //! 上述代码展示了如何编写一个类型安全且语义清晰的 Mortar 函数。
 
-use std::collections::HashMap;
+mod call_guard;
+mod coercion;
+mod context;
+mod docs;
+mod introspection;
+mod manifest;
+mod scoped;
+
+use bevy::log::warn;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use crate::debug::LOG_BINDER;
+
+use call_guard::CallGuard;
+pub use call_guard::{DEFAULT_MAX_CALL_DEPTH, MortarCallError};
+use coercion::CoercionLog;
+pub use coercion::{CoercionPolicy, MortarFunctionError, MortarParamKind};
+pub(crate) use coercion::{emit_function_errors, value_kind};
+pub(crate) use context::{CallContextGuard, with_current_context};
+pub use context::{MortarCallContext, MortarCallOrigin};
+pub use docs::{MortarFnArgDoc, MortarFnDoc, render_markdown};
+pub(crate) use introspection::warn_unbound_on_load;
+pub use introspection::{MissingBinding, MissingBindingKind};
+pub use manifest::{MortarFunctionManifest, MortarFunctionSignature};
+pub use scoped::MortarScopedFunctions;
 
 /// String type for Mortar functions.
 ///
@@ -306,19 +331,72 @@ impl From<()> for MortarValue {
     }
 }
 
-/// A function that can be called from Mortar.
+/// A function that can be called from Mortar, receiving the context of each call. Shared, so one
+/// callable can be registered under several names with [`MortarFunctionRegistry::register_arc`].
 ///
-/// 可以从 Mortar 调用的函数。
-pub type MortarFunction = Box<dyn Fn(&[MortarValue]) -> MortarValue + Send + Sync>;
+/// 可以从 Mortar 调用的函数，每次调用都会收到调用上下文。它是共享的，因此同一个可调用对象可以通过
+/// [`MortarFunctionRegistry::register_arc`] 以多个名称注册。
+pub type MortarFunction =
+    Arc<dyn Fn(&MortarCallContext, &[MortarValue]) -> MortarValue + Send + Sync + 'static>;
 
 /// A registry for Mortar functions.
 ///
+/// The registry is `Send + Sync`, and so is the [`crate::MortarRuntime`] that owns it: every
+/// callable must be `Send + Sync`, and the coercion log behind `&self` calls is locked. Closures
+/// may capture channels, handles or other thread-safe state. An `Arc` of the registry can be
+/// called from other threads; each thread keeps its own call stack for the re-entrancy guard
+/// and its own installed [`MortarCallContext`], so calls there see an unknown context.
+///
 /// Mortar 函数注册表。
-#[derive(Default)]
+///
+/// 注册表是 `Send + Sync` 的，拥有它的 [`crate::MortarRuntime`] 也是：每个可调用对象都必须是
+/// `Send + Sync`，`&self` 调用背后的转换日志也有锁保护。闭包可以捕获通道、句柄或其他线程安全的状态。
+/// 注册表的 `Arc` 可以在其他线程上调用；每个线程都有各自用于重入保护的调用栈以及各自设置的
+/// [`MortarCallContext`]，因此那里的调用看到的是未知上下文。
 pub struct MortarFunctionRegistry {
     functions: HashMap<String, MortarFunction>,
+    /// Functions bound for one file, keyed by path, see [`Self::register_scoped`].
+    scoped: HashMap<String, HashMap<String, MortarFunction>>,
+    arities: HashMap<String, usize>,
+    max_call_depth: usize,
+    unbound_warnings: bool,
+    param_kinds: HashMap<String, Vec<MortarParamKind>>,
+    policies: HashMap<String, CoercionPolicy>,
+    default_policy: CoercionPolicy,
+    coercion_log: CoercionLog,
+    generation: u64,
+    docs: Vec<MortarFnDoc>,
+    volatile: HashSet<String>,
+}
+
+impl Default for MortarFunctionRegistry {
+    fn default() -> Self {
+        Self {
+            functions: HashMap::new(),
+            scoped: HashMap::new(),
+            arities: HashMap::new(),
+            max_call_depth: DEFAULT_MAX_CALL_DEPTH,
+            unbound_warnings: true,
+            param_kinds: HashMap::new(),
+            policies: HashMap::new(),
+            default_policy: CoercionPolicy::default(),
+            coercion_log: CoercionLog::default(),
+            generation: 0,
+            docs: Vec::new(),
+            volatile: HashSet::new(),
+        }
+    }
 }
 
+// Compile-time check of the thread-safety guarantee documented on the registry.
+//
+// 在编译期检查注册表文档中说明的线程安全保证。
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<MortarFunctionRegistry>();
+    assert_send_sync::<crate::MortarRuntime>();
+};
+
 impl MortarFunctionRegistry {
     /// Creates a new function registry.
     ///
@@ -327,19 +405,202 @@ impl MortarFunctionRegistry {
         Self::default()
     }
 
-    /// Registers a function (internal use by macros).
+    /// Registers a function or capturing closure; the `#[mortar_functions]` macro uses it too.
+    ///
+    /// 注册函数或捕获变量的闭包；`#[mortar_functions]` 宏也使用它。
     pub fn register<F>(&mut self, name: impl Into<String>, func: F)
     where
         F: Fn(&[MortarValue]) -> MortarValue + Send + Sync + 'static,
     {
-        self.functions.insert(name.into(), Box::new(func));
+        self.register_with_context(name, move |_, args| func(args));
+    }
+
+    /// Registers a function that also receives the [`MortarCallContext`] of each call.
+    ///
+    /// 注册一个同时接收每次调用的 [`MortarCallContext`] 的函数。
+    pub fn register_with_context<F>(&mut self, name: impl Into<String>, func: F)
+    where
+        F: Fn(&MortarCallContext, &[MortarValue]) -> MortarValue + Send + Sync + 'static,
+    {
+        self.register_arc(name, Arc::new(func));
+    }
+
+    /// Registers an already shared callable, so one closure can serve several names without
+    /// being wrapped again. It replaces an earlier binding of `name` together with its arity.
+    ///
+    /// 注册一个已共享的可调用对象，使同一个闭包无需再次包装即可对应多个名称。它会连同参数个数一起
+    /// 替换 `name` 先前的绑定。
+    pub fn register_arc(&mut self, name: impl Into<String>, func: MortarFunction) {
+        let name = name.into();
+        self.arities.remove(&name);
+        self.functions.insert(name, func);
+        self.generation += 1;
+    }
+
+    /// Number of registrations so far. The dialogue pipeline compares it to re-render a line of
+    /// the first node when a function is registered after the line was shown, as happens when
+    /// `StartNode` is written before the game's binding system ran.
+    ///
+    /// 到目前为止的注册次数。对话流程会比较该值：若函数在某行显示之后才注册（例如 `StartNode`
+    /// 先于游戏的绑定系统写入），则重新渲染第一个节点中的该行。
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Marks `name` as volatile, or not: its condition calls run every time instead of once per
+    /// frame, see [`crate::MortarConditionCache`]. For functions whose result changes between
+    /// calls, such as random rolls.
+    ///
+    /// 将 `name` 标记为易变（或取消标记）：其条件调用每次都会执行，而不是每帧一次，参见
+    /// [`crate::MortarConditionCache`]。用于结果在两次调用之间会变化的函数，例如随机判定。
+    pub fn set_volatile(&mut self, name: impl Into<String>, volatile: bool) {
+        let name = name.into();
+        if volatile {
+            self.volatile.insert(name);
+        } else {
+            self.volatile.remove(&name);
+        }
+    }
+
+    pub fn is_volatile(&self, name: &str) -> bool {
+        self.volatile.contains(name)
+    }
+
+    /// Registers a function together with its parameter count, so it shows up in manifests.
+    ///
+    /// 注册函数并记录其参数个数，使其出现在导出的清单中。
+    pub fn register_with_arity<F>(&mut self, name: impl Into<String>, arity: usize, func: F)
+    where
+        F: Fn(&[MortarValue]) -> MortarValue + Send + Sync + 'static,
+    {
+        let name = name.into();
+        self.register(name.clone(), func);
+        self.arities.insert(name, arity);
+    }
+
+    /// [`Self::register_with_context`] with the number of script arguments, so the function
+    /// shows up in manifests.
+    ///
+    /// 带脚本参数个数的 [`Self::register_with_context`]，使函数出现在导出的清单中。
+    pub fn register_with_context_arity<F>(&mut self, name: impl Into<String>, arity: usize, func: F)
+    where
+        F: Fn(&MortarCallContext, &[MortarValue]) -> MortarValue + Send + Sync + 'static,
+    {
+        let name = name.into();
+        self.register_with_context(name.clone(), func);
+        self.arities.insert(name, arity);
+    }
+
+    /// Sets how deeply registry calls may nest (default [`DEFAULT_MAX_CALL_DEPTH`]).
+    ///
+    /// 设置注册表调用允许的最大嵌套深度（默认 [`DEFAULT_MAX_CALL_DEPTH`]）。
+    pub fn set_max_call_depth(&mut self, depth: usize) {
+        self.max_call_depth = depth;
+    }
+
+    /// Enables or disables warnings for calls to unbound functions. Apps normally set this
+    /// through [`crate::MortarLogConfig`].
+    ///
+    /// 启用或关闭调用未绑定函数时的警告。应用通常通过 [`crate::MortarLogConfig`] 设置。
+    pub fn set_unbound_warnings(&mut self, enabled: bool) {
+        self.unbound_warnings = enabled;
+    }
+
+    /// Whether calls to unbound functions are reported.
+    ///
+    /// 是否报告对未绑定函数的调用。
+    pub fn unbound_warnings(&self) -> bool {
+        self.unbound_warnings
+    }
+
+    /// Reports a call to an unbound function unless such warnings are disabled.
+    pub(crate) fn warn_unbound(&self, message: std::fmt::Arguments) {
+        if self.unbound_warnings {
+            warn!(target: LOG_BINDER, "{}", message);
+        }
+    }
+
+    /// Calls a function by name, refusing calls that re-enter a running function or nest too
+    /// deeply.
+    ///
+    /// 按名称调用函数；若调用会重入正在运行的函数或嵌套过深，则拒绝调用。
+    pub fn try_call(
+        &self,
+        name: &str,
+        args: &[MortarValue],
+    ) -> Result<MortarValue, MortarCallError> {
+        self.try_call_with_context(&MortarCallContext::unknown(), name, args)
+    }
+
+    /// [`Self::try_call`] passing `context` to functions that take one. Functions scoped to the
+    /// context's path shadow global ones.
+    ///
+    /// 向接收上下文的函数传入 `context` 的 [`Self::try_call`]。作用于该上下文路径的函数会遮蔽全局
+    /// 函数。
+    pub fn try_call_with_context(
+        &self,
+        context: &MortarCallContext,
+        name: &str,
+        args: &[MortarValue],
+    ) -> Result<MortarValue, MortarCallError> {
+        // The calling node's overrides redirect the name before any binding is looked up.
+        //
+        // 调用方节点的覆盖会在查找任何绑定之前改写名称。
+        let name = context.overrides.get(name).map_or(name, String::as_str);
+        let function = self
+            .resolve(context.path.as_deref(), name)
+            .ok_or_else(|| MortarCallError::NotFound(name.to_owned()))?;
+        let args = match self.param_kinds.get(name) {
+            Some(kinds) => {
+                coercion::coerce_args(name, kinds, self.policy(name), args, &self.coercion_log)?
+            }
+            None => std::borrow::Cow::Borrowed(args),
+        };
+        let _guard = CallGuard::enter(name, self.max_call_depth)?;
+        Ok(function(context, &args))
     }
 
     /// Calls a function by name with the given arguments.
+    /// A refused re-entrant or too deep call is reported and yields `Void`.
     ///
     /// 按名称调用函数，并传递参数。
+    /// 被拒绝的重入或过深调用会被报告并返回 `Void`。
     pub fn call(&self, name: &str, args: &[MortarValue]) -> Option<MortarValue> {
-        self.functions.get(name).map(|f| f(args))
+        self.call_with_context(&MortarCallContext::unknown(), name, args)
+    }
+
+    /// [`Self::call`] passing `context` to functions that take one.
+    ///
+    /// 向接收上下文的函数传入 `context` 的 [`Self::call`]。
+    pub fn call_with_context(
+        &self,
+        context: &MortarCallContext,
+        name: &str,
+        args: &[MortarValue],
+    ) -> Option<MortarValue> {
+        match self.try_call_with_context(context, name, args) {
+            Ok(value) => Some(value),
+            Err(MortarCallError::NotFound(_)) => None,
+            Err(err) => {
+                warn!(target: LOG_BINDER, "{}", err);
+                self.coercion_log.report(MortarFunctionError {
+                    function: name.to_owned(),
+                    context: context.clone(),
+                    error: err,
+                });
+                Some(MortarValue::Void)
+            }
+        }
+    }
+
+    /// Calls a function from script, with the context the dialogue pipeline installed.
+    pub(crate) fn call_from(
+        &self,
+        origin: MortarCallOrigin,
+        name: &str,
+        args: &[MortarValue],
+    ) -> Option<MortarValue> {
+        self.call_with_context(&context::current(origin), name, args)
     }
 }
 
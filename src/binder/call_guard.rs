@@ -0,0 +1,118 @@
+//! # call_guard.rs
+//!
+//! # call_guard.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Protects [`super::MortarFunctionRegistry`] against re-entrant calls. Every call made through
+//! the registry is pushed onto a per-thread call stack; a call to a function that is already on
+//! the stack, or one that would exceed the configured depth, is refused with a
+//! [`MortarCallError`] instead of recursing until the stack overflows.
+//!
+//! 保护 [`super::MortarFunctionRegistry`] 免受重入调用的影响。通过注册表发起的每次调用都会压入
+//! 当前线程的调用栈；若调用的函数已在栈上，或调用会超过配置的深度，则以 [`MortarCallError`]
+//! 拒绝，而不是一直递归直到栈溢出。
+
+use std::cell::RefCell;
+
+/// Default maximum nesting of registry calls.
+///
+/// 注册表调用的默认最大嵌套深度。
+pub const DEFAULT_MAX_CALL_DEPTH: usize = 16;
+
+/// Why a registry call was refused.
+///
+/// 注册表调用被拒绝的原因。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MortarCallError {
+    /// No function is registered under the name.
+    ///
+    /// 没有以该名称注册的函数。
+    NotFound(String),
+    /// The function is already running further up the call stack; `cycle` lists the chain
+    /// ending with the repeated function.
+    ///
+    /// 该函数已在调用栈上方运行；`cycle` 列出以重复函数结尾的调用链。
+    Reentrant { cycle: Vec<String> },
+    /// The call would nest deeper than the registry allows.
+    ///
+    /// 调用嵌套深度超过注册表允许的上限。
+    DepthExceeded { depth: usize, stack: Vec<String> },
+    /// A strict function got an argument of the wrong kind; `found` is `"missing"` when the
+    /// argument was not passed.
+    ///
+    /// 严格函数收到了类型不符的参数；未传入该参数时 `found` 为 `"missing"`。
+    Coercion {
+        function: String,
+        index: usize,
+        expected: super::MortarParamKind,
+        found: &'static str,
+    },
+}
+
+impl std::fmt::Display for MortarCallError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotFound(name) => write!(f, "function '{name}' is not registered"),
+            Self::Reentrant { cycle } => {
+                write!(f, "re-entrant function call: {}", cycle.join(" → "))
+            }
+            Self::DepthExceeded { depth, stack } => write!(
+                f,
+                "function call depth limit {depth} exceeded: {}",
+                stack.join(" → ")
+            ),
+            Self::Coercion {
+                function,
+                index,
+                expected,
+                found,
+            } => write!(
+                f,
+                "'{function}' argument {index} must be a {}, got {found}",
+                expected.as_str()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for MortarCallError {}
+
+thread_local! {
+    static CALL_STACK: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Keeps a function on the call stack for as long as it runs, popping it even on panic.
+pub(super) struct CallGuard;
+
+impl CallGuard {
+    pub(super) fn enter(name: &str, max_depth: usize) -> Result<Self, MortarCallError> {
+        CALL_STACK.with_borrow_mut(|stack| {
+            if let Some(start) = stack.iter().position(|active| active == name) {
+                let mut cycle = stack[start..].to_vec();
+                cycle.push(name.to_owned());
+                return Err(MortarCallError::Reentrant { cycle });
+            }
+            if stack.len() >= max_depth {
+                let mut stack = stack.clone();
+                stack.push(name.to_owned());
+                return Err(MortarCallError::DepthExceeded {
+                    depth: max_depth,
+                    stack,
+                });
+            }
+            stack.push(name.to_owned());
+            Ok(Self)
+        })
+    }
+}
+
+impl Drop for CallGuard {
+    fn drop(&mut self) {
+        CALL_STACK.with_borrow_mut(|stack| {
+            stack.pop();
+        });
+    }
+}
@@ -0,0 +1,288 @@
+//! # coercion.rs
+//!
+//! # coercion.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Argument coercion for bound functions. A function registered with parameter kinds (the
+//! `#[mortar_functions]` macro records them) has its script arguments checked before every call,
+//! whether the call comes from interpolation, a condition, an event or a run. Under
+//! [`CoercionPolicy::Lenient`] a mismatched argument is converted where a documented conversion
+//! exists and otherwise replaced by the kind's default, with a one-time warning. Under
+//! [`CoercionPolicy::Strict`] the call is refused with [`super::MortarCallError::Coercion`],
+//! yields `Void`, and is reported as a [`MortarFunctionError`] message.
+//!
+//! Lenient conversions:
+//! - to string: numbers and booleans are formatted;
+//! - to number: numeric strings are parsed, booleans become `0` or `1`;
+//! - to boolean: `0` and `1`, and the strings `"true"` and `"false"`.
+//!
+//! 绑定函数的参数转换。以参数类型注册的函数（`#[mortar_functions]` 宏会记录这些类型）在每次调用前
+//! 都会检查脚本参数，无论调用来自插值、条件、事件还是 run。在 [`CoercionPolicy::Lenient`] 下，
+//! 类型不符的参数若有文档所列的转换则进行转换，否则替换为该类型的默认值，并只警告一次。在
+//! [`CoercionPolicy::Strict`] 下，调用会以 [`super::MortarCallError::Coercion`] 拒绝，返回
+//! `Void`，并作为 [`MortarFunctionError`] 消息报告。
+//!
+//! 宽松转换：
+//! - 转为字符串：格式化数字与布尔值；
+//! - 转为数字：解析数字字符串，布尔值转为 `0` 或 `1`；
+//! - 转为布尔值：`0` 与 `1`，以及字符串 `"true"` 与 `"false"`。
+
+use bevy::prelude::*;
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::sync::Mutex;
+
+use super::{
+    MortarBoolean, MortarCallContext, MortarCallError, MortarFunctionRegistry, MortarNumber,
+    MortarValue,
+};
+use crate::MortarRuntime;
+use crate::debug::LOG_BINDER;
+
+/// How a function treats script arguments of the wrong kind.
+///
+/// 函数如何对待类型不符的脚本参数。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CoercionPolicy {
+    /// Convert, or fall back to the default, and warn once.
+    ///
+    /// 进行转换或回退到默认值，并警告一次。
+    #[default]
+    Lenient,
+    /// Refuse the call.
+    ///
+    /// 拒绝调用。
+    Strict,
+}
+
+/// Kind of value a bound function expects for one parameter.
+///
+/// 绑定函数对某个参数期望的值类型。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MortarParamKind {
+    String,
+    Number,
+    Boolean,
+    /// Takes any value as is.
+    ///
+    /// 原样接受任意值。
+    Any,
+}
+
+impl MortarParamKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::String => "string",
+            Self::Number => "number",
+            Self::Boolean => "boolean",
+            Self::Any => "any",
+        }
+    }
+
+    /// Whether `value` needs no conversion.
+    ///
+    /// `value` 是否无需转换。
+    pub fn accepts(self, value: &MortarValue) -> bool {
+        matches!(
+            (self, value),
+            (Self::Any, _)
+                | (Self::String, MortarValue::String(_))
+                | (Self::Number, MortarValue::Number(_))
+                | (Self::Boolean, MortarValue::Boolean(_))
+        )
+    }
+
+    /// The lenient conversion of `value`, if one is documented.
+    ///
+    /// `value` 的宽松转换（若文档中有定义）。
+    pub fn convert(self, value: &MortarValue) -> Option<MortarValue> {
+        match (self, value) {
+            (_, value) if self.accepts(value) => Some(value.clone()),
+            (Self::String, MortarValue::Number(_) | MortarValue::Boolean(_)) => {
+                Some(MortarValue::from(value.to_display_string()))
+            }
+            (Self::Number, MortarValue::String(s)) => {
+                s.0.trim().parse::<f64>().ok().map(Into::into)
+            }
+            (Self::Number, MortarValue::Boolean(b)) => {
+                Some(MortarNumber(f64::from(u8::from(b.0))).into())
+            }
+            (Self::Boolean, MortarValue::Number(n)) if n.0 == 0.0 || n.0 == 1.0 => {
+                Some(MortarBoolean(n.0 == 1.0).into())
+            }
+            (Self::Boolean, MortarValue::String(s)) => match s.0.trim() {
+                "true" => Some(true.into()),
+                "false" => Some(false.into()),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Value a lenient call receives when no conversion applies.
+    ///
+    /// 无可用转换时宽松调用收到的值。
+    pub fn default_value(self) -> MortarValue {
+        match self {
+            Self::String => MortarValue::from(""),
+            Self::Number => MortarValue::from(0.0),
+            Self::Boolean => MortarValue::from(false),
+            Self::Any => MortarValue::Void,
+        }
+    }
+}
+
+/// Name of the kind of `value`, for messages.
+pub(crate) fn value_kind(value: &MortarValue) -> &'static str {
+    match value {
+        MortarValue::String(_) => "string",
+        MortarValue::Number(_) => "number",
+        MortarValue::Boolean(_) => "boolean",
+        MortarValue::Void => "void",
+    }
+}
+
+/// Emitted when the registry refuses a script call, e.g. a strict function given a bad argument.
+///
+/// 当注册表拒绝脚本调用时发出，例如严格函数收到了错误的参数。
+#[derive(Message, Debug, Clone, PartialEq)]
+pub struct MortarFunctionError {
+    pub function: String,
+    pub context: MortarCallContext,
+    pub error: MortarCallError,
+}
+
+/// Refused calls and issued warnings, shared by every call through the registry.
+#[derive(Default)]
+pub(super) struct CoercionLog {
+    errors: Mutex<Vec<MortarFunctionError>>,
+    noted: Mutex<HashSet<(String, usize)>>,
+}
+
+impl CoercionLog {
+    pub(super) fn report(&self, error: MortarFunctionError) {
+        if let Ok(mut errors) = self.errors.lock() {
+            errors.push(error);
+        }
+    }
+
+    pub(super) fn take_errors(&self) -> Vec<MortarFunctionError> {
+        self.errors
+            .lock()
+            .map(|mut errors| std::mem::take(&mut *errors))
+            .unwrap_or_default()
+    }
+
+    /// Warns about a lenient conversion the first time it happens for a parameter.
+    fn note(&self, function: &str, index: usize, message: std::fmt::Arguments) {
+        let first = self
+            .noted
+            .lock()
+            .is_ok_and(|mut noted| noted.insert((function.to_owned(), index)));
+        if first {
+            warn!(target: LOG_BINDER, "{}", message);
+        }
+    }
+}
+
+impl MortarFunctionRegistry {
+    /// Declares the parameter kinds of `name`, so its script arguments are coerced before each
+    /// call. The `#[mortar_functions]` macro declares them for every method.
+    ///
+    /// 声明 `name` 的参数类型，使其脚本参数在每次调用前被转换。`#[mortar_functions]` 宏会为每个
+    /// 方法声明参数类型。
+    pub fn set_param_kinds(&mut self, name: impl Into<String>, kinds: Vec<MortarParamKind>) {
+        self.param_kinds.insert(name.into(), kinds);
+    }
+
+    /// Sets the coercion policy of functions without their own (default lenient).
+    ///
+    /// 设置未单独指定策略的函数所用的转换策略（默认为宽松）。
+    pub fn set_default_policy(&mut self, policy: CoercionPolicy) {
+        self.default_policy = policy;
+    }
+
+    /// Overrides the coercion policy of `name`.
+    ///
+    /// 覆盖 `name` 的转换策略。
+    pub fn set_policy(&mut self, name: impl Into<String>, policy: CoercionPolicy) {
+        self.policies.insert(name.into(), policy);
+    }
+
+    /// The coercion policy `name` is called with.
+    ///
+    /// 调用 `name` 时使用的转换策略。
+    pub fn policy(&self, name: &str) -> CoercionPolicy {
+        self.policies
+            .get(name)
+            .copied()
+            .unwrap_or(self.default_policy)
+    }
+
+    pub(crate) fn take_errors(&self) -> Vec<MortarFunctionError> {
+        self.coercion_log.take_errors()
+    }
+}
+
+/// Checks `args` against `kinds`, borrowing them when nothing needs converting.
+pub(super) fn coerce_args<'a>(
+    function: &str,
+    kinds: &[MortarParamKind],
+    policy: CoercionPolicy,
+    args: &'a [MortarValue],
+    log: &CoercionLog,
+) -> Result<Cow<'a, [MortarValue]>, MortarCallError> {
+    let fits = |(index, kind): (usize, &MortarParamKind)| {
+        args.get(index).is_some_and(|value| kind.accepts(value))
+    };
+    if kinds.iter().enumerate().all(fits) {
+        return Ok(Cow::Borrowed(args));
+    }
+    let mut coerced = args.to_vec();
+    for (index, &kind) in kinds.iter().enumerate() {
+        let value = args.get(index);
+        if value.is_some_and(|value| kind.accepts(value)) {
+            continue;
+        }
+        let found = value.map_or("missing", value_kind);
+        if policy == CoercionPolicy::Strict {
+            return Err(MortarCallError::Coercion {
+                function: function.to_owned(),
+                index,
+                expected: kind,
+                found,
+            });
+        }
+        let converted = value.and_then(|value| kind.convert(value));
+        let outcome = if converted.is_some() {
+            "converted"
+        } else {
+            "replaced by the default"
+        };
+        log.note(
+            function,
+            index,
+            format_args!(
+                "'{function}' argument {index}: {found} {outcome} for a {} parameter",
+                kind.as_str()
+            ),
+        );
+        let converted = converted.unwrap_or_else(|| kind.default_value());
+        match coerced.get_mut(index) {
+            Some(slot) => *slot = converted,
+            None => coerced.push(converted),
+        }
+    }
+    Ok(Cow::Owned(coerced))
+}
+
+/// Sends the calls the registry refused since the last frame as [`MortarFunctionError`]s.
+pub(crate) fn emit_function_errors(
+    runtime: Res<MortarRuntime>,
+    mut writer: MessageWriter<MortarFunctionError>,
+) {
+    writer.write_batch(runtime.functions.take_errors());
+}
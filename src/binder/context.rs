@@ -0,0 +1,137 @@
+//! # context.rs
+//!
+//! # context.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Tells bound functions where they are called from. The dialogue pipeline installs a
+//! [`MortarCallContext`] (file, node and its tags, line and controller) around the work it does
+//! for a line, and every registry call made during that work hands it, tagged with a
+//! [`MortarCallOrigin`], to functions registered with
+//! [`super::MortarFunctionRegistry::register_with_context`]. Calls made directly through
+//! [`super::MortarFunctionRegistry::call`] see [`MortarCallContext::unknown`].
+//! Like the call guard, the installed context is kept per thread.
+//!
+//! 告诉绑定函数它们是从哪里被调用的。对话流程在处理一行时会设置 [`MortarCallContext`]
+//! （文件、节点及其标签、行与控制器），处理期间的每次注册表调用都会带上 [`MortarCallOrigin`]，
+//! 把它传给通过 [`super::MortarFunctionRegistry::register_with_context`] 注册的函数。直接通过
+//! [`super::MortarFunctionRegistry::call`] 发起的调用看到的是 [`MortarCallContext::unknown`]。
+//! 与调用保护一样，设置的上下文按线程保存。
+
+use bevy::prelude::Entity;
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+/// What kind of script construct made a call.
+///
+/// 发起调用的脚本结构类型。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MortarCallOrigin {
+    /// Called from outside the dialogue pipeline.
+    ///
+    /// 从对话流程之外调用。
+    #[default]
+    Unknown,
+    /// A `{function()}` placeholder in a line.
+    ///
+    /// 行中的 `{function()}` 占位符。
+    Interpolation,
+    /// A condition on a line, a branch or a choice.
+    ///
+    /// 行、分支或选项上的条件。
+    Condition,
+    /// An event action fired while a line is shown.
+    ///
+    /// 显示一行时触发的事件动作。
+    EventAction,
+    /// A mandatory event action fired because its line was left before the reveal reached it.
+    ///
+    /// 因所在行在逐字显示到达之前就被离开而强制触发的必需事件动作。
+    ForcedOnAdvance,
+}
+
+/// Where a bound function is being called from. Fields are `None` when unknown.
+///
+/// 绑定函数被调用的位置。未知的字段为 `None`。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MortarCallContext {
+    pub path: Option<String>,
+    pub node: Option<String>,
+    pub text_index: Option<usize>,
+    /// Dialogue controller the line belongs to; `None` for the untargeted controller.
+    ///
+    /// 该行所属的对话控制器；未指定目标的控制器为 `None`。
+    pub source_entity: Option<Entity>,
+    /// Tags of `node`.
+    ///
+    /// `node` 的标签。
+    pub tags: Vec<String>,
+    /// Capabilities of the player's setup, see [`crate::MortarCapabilities`].
+    ///
+    /// 玩家设备情况的能力，参见 [`crate::MortarCapabilities`]。
+    pub capabilities: crate::MortarCapabilities,
+    /// Function overrides of `node`: a call to a key runs the function named by its value.
+    ///
+    /// `node` 的函数覆盖：对某个键的调用会执行其值所命名的函数。
+    pub overrides: HashMap<String, String>,
+    pub origin: MortarCallOrigin,
+}
+
+impl MortarCallContext {
+    /// Context of a call made outside the dialogue pipeline.
+    ///
+    /// 在对话流程之外发起的调用的上下文。
+    pub fn unknown() -> Self {
+        Self::default()
+    }
+
+    /// Context of a call made at `text_index` of `node` in `path`.
+    ///
+    /// 在 `path` 中 `node` 的 `text_index` 处发起的调用的上下文。
+    pub fn at(path: impl Into<String>, node: impl Into<String>, text_index: usize) -> Self {
+        Self {
+            path: Some(path.into()),
+            node: Some(node.into()),
+            text_index: Some(text_index),
+            ..Self::default()
+        }
+    }
+}
+
+thread_local! {
+    static CURRENT: RefCell<Option<MortarCallContext>> = const { RefCell::new(None) };
+}
+
+/// Keeps a context installed for registry calls until dropped, then restores the previous one,
+/// even on panic.
+#[must_use = "the context is uninstalled when the guard is dropped"]
+pub(crate) struct CallContextGuard(Option<MortarCallContext>);
+
+impl CallContextGuard {
+    pub(crate) fn enter(context: MortarCallContext) -> Self {
+        Self(CURRENT.with_borrow_mut(|current| current.replace(context)))
+    }
+}
+
+impl Drop for CallContextGuard {
+    fn drop(&mut self) {
+        let previous = self.0.take();
+        CURRENT.with_borrow_mut(|current| *current = previous);
+    }
+}
+
+/// Runs `f` with the installed context, or an unknown one, without cloning it.
+pub(crate) fn with_current_context<R>(f: impl FnOnce(&MortarCallContext) -> R) -> R {
+    CURRENT.with_borrow(|current| match current {
+        Some(context) => f(context),
+        None => f(&MortarCallContext::unknown()),
+    })
+}
+
+/// The installed context tagged with `origin`, or an unknown one.
+pub(super) fn current(origin: MortarCallOrigin) -> MortarCallContext {
+    let context = CURRENT.with_borrow(Clone::clone).unwrap_or_default();
+    MortarCallContext { origin, ..context }
+}
@@ -0,0 +1,139 @@
+//! # docs.rs
+//!
+//! # docs.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Writer-facing documentation of bound functions. `#[mortar_functions]` emits a
+//! `MORTAR_FUNCTION_DOCS` const on each annotated impl, one [`MortarFnDoc`] per method with its
+//! argument names and Mortar types, its return type and its doc comment, and the generated
+//! `bind_functions` installs them on the registry. [`render_markdown`] turns docs into a reference
+//! grouped by the struct that binds them.
+//!
+//! 面向编剧的已绑定函数文档。`#[mortar_functions]` 会在每个带注解的 impl 上生成
+//! `MORTAR_FUNCTION_DOCS` 常量，每个方法对应一个 [`MortarFnDoc`]，包含参数名称与 Mortar 类型、
+//! 返回类型以及文档注释；生成的 `bind_functions` 会把它们安装到注册表上。[`render_markdown`]
+//! 将文档渲染为按绑定结构体分组的参考文档。
+
+use std::borrow::Cow;
+
+use super::MortarFunctionRegistry;
+
+/// One argument of a documented function: its name and Mortar type (`String`, `Number`,
+/// `Boolean` or `Any`).
+///
+/// 文档化函数的一个参数：其名称与 Mortar 类型（`String`、`Number`、`Boolean` 或 `Any`）。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MortarFnArgDoc {
+    pub name: Cow<'static, str>,
+    pub ty: Cow<'static, str>,
+}
+
+/// Documentation of one function callable from scripts.
+///
+/// 一个可从脚本调用的函数的文档。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MortarFnDoc {
+    /// The type whose impl binds the function, or the file declaring it.
+    ///
+    /// 绑定该函数的 impl 所属的类型，或声明它的文件。
+    pub source: Cow<'static, str>,
+    pub name: Cow<'static, str>,
+    pub args: Cow<'static, [MortarFnArgDoc]>,
+    /// Mortar return type; `Void` when the function returns nothing.
+    ///
+    /// Mortar 返回类型；函数没有返回值时为 `Void`。
+    pub returns: Cow<'static, str>,
+    /// The doc comment text, empty when there is none.
+    ///
+    /// 文档注释文本；没有时为空。
+    pub description: Cow<'static, str>,
+}
+
+impl MortarFnDoc {
+    /// The documentation a script declaration gives: names and types, no description.
+    ///
+    /// 脚本声明所提供的文档：名称与类型，没有描述。
+    pub fn from_declaration(source: impl Into<String>, decl: &mortar_compiler::Function) -> Self {
+        let args: Vec<MortarFnArgDoc> = decl
+            .params
+            .iter()
+            .map(|param| MortarFnArgDoc {
+                name: Cow::Owned(param.name.clone()),
+                ty: Cow::Owned(param.param_type.clone()),
+            })
+            .collect();
+        Self {
+            source: Cow::Owned(source.into()),
+            name: Cow::Owned(decl.name.clone()),
+            args: Cow::Owned(args),
+            returns: Cow::Owned(
+                decl.return_type
+                    .clone()
+                    .unwrap_or_else(|| "Void".to_owned()),
+            ),
+            description: Cow::Borrowed(""),
+        }
+    }
+
+    /// The signature in Mortar declaration syntax, such as `has_item(item: String) -> Boolean`.
+    ///
+    /// 以 Mortar 声明语法表示的签名，例如 `has_item(item: String) -> Boolean`。
+    pub fn signature(&self) -> String {
+        let args: Vec<String> = self
+            .args
+            .iter()
+            .map(|arg| format!("{}: {}", arg.name, arg.ty))
+            .collect();
+        match self.returns.as_ref() {
+            "Void" => format!("{}({})", self.name, args.join(", ")),
+            returns => format!("{}({}) -> {}", self.name, args.join(", "), returns),
+        }
+    }
+}
+
+impl MortarFunctionRegistry {
+    /// Attaches `docs`, replacing the docs of functions already documented under the same name.
+    ///
+    /// 附加 `docs`，替换同名函数已有的文档。
+    pub fn install_docs(&mut self, docs: &[MortarFnDoc]) {
+        for doc in docs {
+            self.docs.retain(|installed| installed.name != doc.name);
+            self.docs.push(doc.clone());
+        }
+    }
+
+    /// The installed docs, in installation order.
+    ///
+    /// 已安装的文档，按安装顺序排列。
+    pub fn docs(&self) -> &[MortarFnDoc] {
+        &self.docs
+    }
+}
+
+/// Renders `docs` as a Markdown reference for writers: one section per source, in order of first
+/// appearance, listing each function's signature and description.
+///
+/// 将 `docs` 渲染为面向编剧的 Markdown 参考：每个来源一节，按首次出现的顺序排列，列出每个函数的
+/// 签名与描述。
+pub fn render_markdown(docs: &[MortarFnDoc]) -> String {
+    let mut sources: Vec<&str> = Vec::new();
+    for doc in docs {
+        if !sources.contains(&doc.source.as_ref()) {
+            sources.push(&doc.source);
+        }
+    }
+    let mut out = String::from("# Mortar functions\n");
+    for source in sources {
+        out.push_str(&format!("\n## {source}\n"));
+        for doc in docs.iter().filter(|doc| doc.source == source) {
+            out.push_str(&format!("\n### `{}`\n", doc.signature()));
+            if !doc.description.is_empty() {
+                out.push_str(&format!("\n{}\n", doc.description));
+            }
+        }
+    }
+    out
+}
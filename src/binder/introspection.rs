@@ -0,0 +1,188 @@
+//! # introspection.rs
+//!
+//! # introspection.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! What the game has bound, compared with what a script declares. The registry can be asked
+//! whether a name is bound, list its names and drop a binding, and
+//! [`MortarFunctionRegistry::validate_against`] lists both the declared functions without a
+//! binding and the bindings no declaration names. When a file first loads, its unbound functions
+//! are logged together in one warning, before any line calls them.
+//!
+//! 游戏已绑定的内容与脚本所声明内容的对比。可以询问注册表某个名称是否已绑定、列出其名称或移除绑定；
+//! [`MortarFunctionRegistry::validate_against`] 会同时列出没有绑定的已声明函数，以及没有任何声明
+//! 提及的绑定。文件首次加载时，其未绑定的函数会在任何行调用它们之前，合并记录在一条警告中。
+
+use bevy::asset::{AssetEvent, AssetId, AssetServer, Assets};
+use bevy::prelude::*;
+use std::collections::HashSet;
+
+use super::MortarFunctionRegistry;
+use crate::debug::LOG_BINDER;
+use crate::{MortarAsset, MortarRegistry, MortarRuntime};
+
+/// Which side of a binding is missing.
+///
+/// 绑定缺少的是哪一侧。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MissingBindingKind {
+    /// The script declares the function but the game has not bound it.
+    ///
+    /// 脚本声明了该函数，但游戏尚未绑定它。
+    Unbound,
+    /// The game binds the function but the script does not declare it.
+    ///
+    /// 游戏绑定了该函数，但脚本没有声明它。
+    Undeclared,
+}
+
+/// A function declared without a binding, or bound without a declaration.
+///
+/// 已声明但没有绑定，或已绑定但没有声明的函数。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissingBinding {
+    pub name: String,
+    /// The declared signature, such as `has_item(name: String) -> Boolean`. An undeclared binding
+    /// shows one `_` per parameter, such as `roll(_, _)`, or `roll(..)` when its arity is unknown.
+    ///
+    /// 声明的签名，例如 `has_item(name: String) -> Boolean`。未声明的绑定每个参数显示一个 `_`，例如
+    /// `roll(_, _)`；参数个数未知时显示 `roll(..)`。
+    pub signature: String,
+    pub kind: MissingBindingKind,
+}
+
+impl MortarFunctionRegistry {
+    /// Whether `name` has a global binding. Scoped bindings are checked through
+    /// [`Self::scoped`].
+    ///
+    /// `name` 是否有全局绑定。作用域绑定通过 [`Self::scoped`] 检查。
+    pub fn contains(&self, name: &str) -> bool {
+        self.functions.contains_key(name)
+    }
+
+    /// Drops the global binding of `name` with its arity, parameter kinds, coercion policy and
+    /// docs, returning whether it was bound.
+    ///
+    /// 移除 `name` 的全局绑定及其参数个数、参数类型、转换策略与文档，并返回之前是否已绑定。
+    pub fn unregister(&mut self, name: &str) -> bool {
+        self.docs.retain(|doc| doc.name != name);
+        self.arities.remove(name);
+        self.param_kinds.remove(name);
+        self.policies.remove(name);
+        let removed = self.functions.remove(name).is_some();
+        if removed {
+            self.generation += 1;
+        }
+        removed
+    }
+
+    /// Names of the global bindings, in no particular order.
+    ///
+    /// 全局绑定的名称，无特定顺序。
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.functions.keys().map(String::as_str)
+    }
+
+    /// Compares the global bindings with `decls`, listing the declared functions that are not
+    /// bound, in declaration order, then the bindings not declared, sorted by name.
+    ///
+    /// 将全局绑定与 `decls` 比较，先按声明顺序列出未绑定的已声明函数，再按名称排序列出未声明的绑定。
+    pub fn validate_against(&self, decls: &[mortar_compiler::Function]) -> Vec<MissingBinding> {
+        let mut missing: Vec<MissingBinding> = decls
+            .iter()
+            .filter(|decl| !self.contains(&decl.name))
+            .map(|decl| MissingBinding {
+                name: decl.name.clone(),
+                signature: declared_signature(decl),
+                kind: MissingBindingKind::Unbound,
+            })
+            .collect();
+        let declared: HashSet<&str> = decls.iter().map(|decl| decl.name.as_str()).collect();
+        let mut undeclared: Vec<MissingBinding> = self
+            .names()
+            .filter(|name| !declared.contains(name))
+            .map(|name| {
+                let signature = match self.arities.get(name) {
+                    Some(arity) => format!("{name}({})", vec!["_"; *arity].join(", ")),
+                    None => format!("{name}(..)"),
+                };
+                MissingBinding {
+                    name: name.to_owned(),
+                    signature,
+                    kind: MissingBindingKind::Undeclared,
+                }
+            })
+            .collect();
+        undeclared.sort_by(|a, b| a.name.cmp(&b.name));
+        missing.extend(undeclared);
+        missing
+    }
+}
+
+fn declared_signature(decl: &mortar_compiler::Function) -> String {
+    let params: Vec<String> = decl
+        .params
+        .iter()
+        .map(|param| format!("{}: {}", param.name, param.param_type))
+        .collect();
+    match &decl.return_type {
+        Some(ret) => format!("{}({}) -> {}", decl.name, params.join(", "), ret),
+        None => format!("{}({})", decl.name, params.join(", ")),
+    }
+}
+
+/// Logs the unbound functions of each file once, when it first loads. Functions bound for one
+/// of the file's registered paths count as bound.
+pub(crate) fn warn_unbound_on_load(
+    runtime: Res<MortarRuntime>,
+    registry: Res<MortarRegistry>,
+    assets: Res<Assets<MortarAsset>>,
+    asset_server: Res<AssetServer>,
+    mut asset_events: MessageReader<AssetEvent<MortarAsset>>,
+    mut warned: Local<HashSet<AssetId<MortarAsset>>>,
+) {
+    for event in asset_events.read() {
+        let (AssetEvent::Added { id } | AssetEvent::LoadedWithDependencies { id }) = event else {
+            continue;
+        };
+        if !runtime.functions.unbound_warnings() || warned.contains(id) {
+            continue;
+        }
+        let Some(asset) = assets.get(*id) else {
+            continue;
+        };
+        warned.insert(*id);
+        let paths = crate::preparation::reloaded_paths(&registry, *id);
+        let unbound: Vec<String> = runtime
+            .functions
+            .validate_against(&asset.data.functions)
+            .into_iter()
+            .filter(|missing| missing.kind == MissingBindingKind::Unbound)
+            .filter(|missing| {
+                !paths.iter().any(|path| {
+                    runtime
+                        .functions
+                        .scoped(path.as_str())
+                        .is_scoped(&missing.name)
+                })
+            })
+            .map(|missing| missing.signature)
+            .collect();
+        if unbound.is_empty() {
+            continue;
+        }
+        let path = asset_server
+            .get_path(*id)
+            .map_or_else(|| id.to_string(), |path| path.to_string());
+        warn!(
+            target: LOG_BINDER,
+            "'{}' declares {} function(s) the game has not bound: {}",
+            path,
+            unbound.len(),
+            unbound.join(", ")
+        );
+    }
+}
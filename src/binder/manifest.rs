@@ -0,0 +1,116 @@
+//! # manifest.rs
+//!
+//! # manifest.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Function manifests: the names and arities a game binds, exported for offline validation and
+//! imported by headless tools as `Void` stubs.
+//!
+//! 函数清单：游戏所绑定函数的名称与参数个数，可导出供离线校验使用，也可由无头工具导入为返回 `Void`
+//! 的占位实现。
+
+use super::{MortarFunctionRegistry, MortarValue};
+
+/// A bound function's name and, when known, its parameter count.
+///
+/// 已绑定函数的名称，以及（已知时的）参数个数。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MortarFunctionSignature {
+    pub name: String,
+    pub arity: Option<usize>,
+}
+
+/// Serializable list of the functions a game registers, used by offline validation.
+///
+/// 游戏所注册函数的可序列化清单，供离线校验使用。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MortarFunctionManifest {
+    pub functions: Vec<MortarFunctionSignature>,
+}
+
+impl MortarFunctionManifest {
+    /// Looks up a function by name.
+    ///
+    /// 按名称查找函数。
+    pub fn get(&self, name: &str) -> Option<&MortarFunctionSignature> {
+        self.functions.iter().find(|function| function.name == name)
+    }
+
+    /// Serializes the manifest as `{"functions": [{"name": ..., "arity": ...}]}`.
+    ///
+    /// 将清单序列化为 `{"functions": [{"name": ..., "arity": ...}]}`。
+    pub fn to_json(&self) -> String {
+        let functions: Vec<serde_json::Value> = self
+            .functions
+            .iter()
+            .map(|function| serde_json::json!({ "name": function.name, "arity": function.arity }))
+            .collect();
+        serde_json::to_string_pretty(&serde_json::json!({ "functions": functions }))
+            .unwrap_or_default()
+    }
+
+    /// Parses a manifest produced by [`MortarFunctionManifest::to_json`].
+    ///
+    /// 解析由 [`MortarFunctionManifest::to_json`] 生成的清单。
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let value: serde_json::Value = serde_json::from_str(json).map_err(|err| err.to_string())?;
+        let entries = value
+            .get("functions")
+            .and_then(|functions| functions.as_array())
+            .ok_or_else(|| "manifest is missing a `functions` array".to_string())?;
+
+        let mut functions = Vec::with_capacity(entries.len());
+        for entry in entries {
+            let name = entry
+                .get("name")
+                .and_then(|name| name.as_str())
+                .ok_or_else(|| format!("manifest entry without a name: {entry}"))?;
+            let arity = entry
+                .get("arity")
+                .and_then(|arity| arity.as_u64())
+                .map(|arity| arity as usize);
+            functions.push(MortarFunctionSignature {
+                name: name.to_string(),
+                arity,
+            });
+        }
+        Ok(Self { functions })
+    }
+}
+
+impl MortarFunctionRegistry {
+    /// Exports the names and known arities of every registered function, sorted by name.
+    ///
+    /// 导出所有已注册函数的名称与已知参数个数，按名称排序。
+    pub fn export_manifest(&self) -> MortarFunctionManifest {
+        let mut functions: Vec<MortarFunctionSignature> = self
+            .functions
+            .keys()
+            .map(|name| MortarFunctionSignature {
+                name: name.clone(),
+                arity: self.arities.get(name).copied(),
+            })
+            .collect();
+        functions.sort_by(|a, b| a.name.cmp(&b.name));
+        MortarFunctionManifest { functions }
+    }
+
+    /// Registers `Void`-returning stubs for manifest entries that are not bound yet.
+    /// Useful for headless tooling that evaluates scripts without the game's code.
+    ///
+    /// 为清单中尚未绑定的函数注册返回 `Void` 的占位实现，便于在没有游戏代码的无头工具中求值脚本。
+    pub fn import_manifest(&mut self, manifest: &MortarFunctionManifest) {
+        for function in &manifest.functions {
+            if self.functions.contains_key(&function.name) {
+                continue;
+            }
+            self.register(function.name.clone(), |_| MortarValue::Void);
+            if let Some(arity) = function.arity {
+                self.arities.insert(function.name.clone(), arity);
+            }
+        }
+    }
+}
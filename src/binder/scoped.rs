@@ -0,0 +1,153 @@
+//! # scoped.rs
+//!
+//! # scoped.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Functions bound for one Mortar file. Two files may declare functions of the same name that
+//! the game implements differently; a binding registered for a path with
+//! [`MortarFunctionRegistry::register_scoped`] answers calls made while a dialogue of that path
+//! runs, and shadows the global binding of the same name there. Calls resolve through the path
+//! of the installed [`MortarCallContext`], so conditions, interpolation and event actions of a
+//! dialogue all see its scoped bindings; calls from anywhere else see the global ones only.
+//! [`MortarScopedFunctions`] is the same layered lookup for calls made by hand.
+//!
+//! 为单个 Mortar 文件绑定的函数。两个文件可能声明同名函数，而游戏对它们的实现不同；通过
+//! [`MortarFunctionRegistry::register_scoped`] 为某个路径注册的绑定会响应该路径的对话运行时发起的
+//! 调用，并在那里遮蔽同名的全局绑定。调用依据当前设置的 [`MortarCallContext`] 中的路径解析，因此
+//! 对话中的条件、插值与事件动作都能看到其作用域绑定；其他地方发起的调用只能看到全局绑定。
+//! [`MortarScopedFunctions`] 为手动发起的调用提供同样的分层查找。
+
+use std::sync::Arc;
+
+use super::{
+    MortarCallContext, MortarCallError, MortarFunction, MortarFunctionRegistry, MortarValue,
+};
+
+impl MortarFunctionRegistry {
+    /// Registers a function for the file at `path` only, named as dialogues of that file are
+    /// started with. Within those dialogues it shadows a global function of the same name.
+    ///
+    /// 仅为 `path` 处的文件注册函数，路径与启动该文件对话时使用的写法一致。在这些对话中，它会遮蔽
+    /// 同名的全局函数。
+    pub fn register_scoped<F>(&mut self, path: impl Into<String>, name: impl Into<String>, func: F)
+    where
+        F: Fn(&[MortarValue]) -> MortarValue + Send + Sync + 'static,
+    {
+        self.register_scoped_arc(path, name, Arc::new(move |_, args| func(args)));
+    }
+
+    /// [`Self::register_scoped`] for a function that also receives the [`MortarCallContext`].
+    ///
+    /// 接收 [`MortarCallContext`] 的函数版本的 [`Self::register_scoped`]。
+    pub fn register_scoped_with_context<F>(
+        &mut self,
+        path: impl Into<String>,
+        name: impl Into<String>,
+        func: F,
+    ) where
+        F: Fn(&MortarCallContext, &[MortarValue]) -> MortarValue + Send + Sync + 'static,
+    {
+        self.register_scoped_arc(path, name, Arc::new(func));
+    }
+
+    /// [`Self::register_scoped`] for an already shared callable.
+    ///
+    /// 已共享的可调用对象版本的 [`Self::register_scoped`]。
+    pub fn register_scoped_arc(
+        &mut self,
+        path: impl Into<String>,
+        name: impl Into<String>,
+        func: MortarFunction,
+    ) {
+        self.scoped
+            .entry(path.into())
+            .or_default()
+            .insert(name.into(), func);
+        self.generation += 1;
+    }
+
+    /// Drops every function registered for `path`, returning whether there were any.
+    ///
+    /// 移除为 `path` 注册的所有函数，并返回之前是否存在。
+    pub fn remove_scoped(&mut self, path: &str) -> bool {
+        let removed = self.scoped.remove(path).is_some();
+        if removed {
+            self.generation += 1;
+        }
+        removed
+    }
+
+    /// The functions dialogues of `path` call, see [`MortarScopedFunctions`].
+    ///
+    /// `path` 的对话所调用的函数，参见 [`MortarScopedFunctions`]。
+    pub fn scoped(&self, path: impl Into<String>) -> MortarScopedFunctions<'_> {
+        MortarScopedFunctions {
+            registry: self,
+            path: path.into(),
+        }
+    }
+
+    /// The binding of `name` for calls made from `path`: the scoped one, else the global one.
+    pub(super) fn resolve(&self, path: Option<&str>, name: &str) -> Option<&MortarFunction> {
+        path.and_then(|path| self.scoped.get(path)?.get(name))
+            .or_else(|| self.functions.get(name))
+    }
+}
+
+/// The functions of a [`MortarFunctionRegistry`] as dialogues of one path see them: bindings
+/// scoped to the path first, then the global ones.
+///
+/// 某个路径的对话所看到的 [`MortarFunctionRegistry`] 中的函数：先查找该路径的作用域绑定，再查找
+/// 全局绑定。
+pub struct MortarScopedFunctions<'a> {
+    registry: &'a MortarFunctionRegistry,
+    path: String,
+}
+
+impl MortarScopedFunctions<'_> {
+    /// Whether `name` is bound for the path, scoped or globally.
+    ///
+    /// `name` 是否已为该路径绑定（作用域绑定或全局绑定）。
+    pub fn contains(&self, name: &str) -> bool {
+        self.registry.resolve(Some(&self.path), name).is_some()
+    }
+
+    /// Whether `name` has a binding scoped to the path.
+    ///
+    /// `name` 是否有该路径的作用域绑定。
+    pub fn is_scoped(&self, name: &str) -> bool {
+        self.registry
+            .scoped
+            .get(&self.path)
+            .is_some_and(|functions| functions.contains_key(name))
+    }
+
+    /// [`MortarFunctionRegistry::try_call`] as made from a dialogue of the path.
+    ///
+    /// 以该路径对话的身份发起的 [`MortarFunctionRegistry::try_call`]。
+    pub fn try_call(
+        &self,
+        name: &str,
+        args: &[MortarValue],
+    ) -> Result<MortarValue, MortarCallError> {
+        self.registry
+            .try_call_with_context(&self.context(), name, args)
+    }
+
+    /// [`MortarFunctionRegistry::call`] as made from a dialogue of the path.
+    ///
+    /// 以该路径对话的身份发起的 [`MortarFunctionRegistry::call`]。
+    pub fn call(&self, name: &str, args: &[MortarValue]) -> Option<MortarValue> {
+        self.registry.call_with_context(&self.context(), name, args)
+    }
+
+    fn context(&self) -> MortarCallContext {
+        MortarCallContext {
+            path: Some(self.path.clone()),
+            ..MortarCallContext::unknown()
+        }
+    }
+}
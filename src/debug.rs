@@ -1,20 +1,219 @@
-//! This module provides a development-only logging macro.
+//! # debug.rs
 //!
-//! 本模块提供了一个仅在开发时使用的日志宏。
+//! # debug.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Logging support for the library. Every log site uses one of the `mortar::*` targets below, so
+//! Mortar output can be filtered apart from game logs (`RUST_LOG=mortar=debug,mortar::runs=trace`).
+//! Per-frame chatter is logged at `debug`/`trace` level, and [`MortarLogConfig`] controls the few
+//! messages whose verbosity is adjustable at runtime. The `verbose-debug` feature compiles in
+//! `trace` statements at the runtime's decision points (condition results with their operands,
+//! interpolated values, event index mapping and firing, runs and choice evaluation), each
+//! switched at runtime by [`MortarDebugCategories`]; without the feature they compile to nothing.
+//!
+//! 库的日志支持。所有日志点都使用下面的 `mortar::*` 目标之一，因此可以把 Mortar 的输出与游戏日志
+//! 分开过滤（`RUST_LOG=mortar=debug,mortar::runs=trace`）。逐帧的琐碎信息使用 `debug`/`trace`
+//! 级别记录，[`MortarLogConfig`] 用于在运行时控制少数可调节详细程度的消息。`verbose-debug` 功能会在
+//! 运行时的各个决策点（带操作数的条件结果、插值结果、事件索引映射与触发、run 执行及选项求值）
+//! 编译进 `trace` 语句，并由 [`MortarDebugCategories`] 在运行时逐类开关；未启用该功能时它们不会
+//! 产生任何代码。
+
+use bevy::prelude::*;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use crate::MortarRuntime;
+
+/// Asset loading, compilation and registry paths.
+pub(crate) const LOG_ASSET: &str = "mortar::asset";
+/// Function registry calls.
+pub(crate) const LOG_BINDER: &str = "mortar::binder";
+/// Dialogue flow: starting, advancing, choices and text output.
+pub(crate) const LOG_DIALOGUE: &str = "mortar::dialogue";
+/// Condition evaluation, interpolation and variables.
+pub(crate) const LOG_EVAL: &str = "mortar::eval";
+/// Text events and the effects they apply.
+pub(crate) const LOG_EVENTS: &str = "mortar::events";
+/// `run` statement execution.
+pub(crate) const LOG_RUNS: &str = "mortar::runs";
 
 /// A macro for logging development-only information.
 ///
-/// This macro wraps `bevy::log::info!` and is only enabled when the `dev-logs` feature is active.
+/// This macro wraps `bevy::log::debug!` and is only enabled when the `dev-logs` feature is active.
+/// Without an explicit `target:` it logs to the `mortar` target.
 ///
 /// 用于记录仅开发信息的宏。
 ///
-/// 此宏包装了 `bevy::log::info!`，仅在 `dev-logs` 功能激活时启用。
+/// 此宏包装了 `bevy::log::debug!`，仅在 `dev-logs` 功能激活时启用。未指定 `target:` 时记录到
+/// `mortar` 目标。
 #[macro_export]
 macro_rules! dev_info {
-    ($($arg:tt)*) => {
+    (target: $target:expr, $($arg:tt)+) => {
+        #[cfg(feature = "dev-logs")]
+        {
+            bevy::log::debug!(target: $target, $($arg)+);
+        }
+        #[cfg(not(feature = "dev-logs"))]
+        {
+            let _ = $target;
+        }
+    };
+    ($($arg:tt)+) => {
         #[cfg(feature = "dev-logs")]
         {
-            bevy::log::info!($($arg)*);
+            bevy::log::debug!(target: "mortar", $($arg)+);
         }
     };
 }
+
+/// Emits a `trace` message of a [`DebugCategory`] when the `verbose-debug` feature is on and the
+/// category is enabled in [`MortarDebugCategories`]. The arguments are not evaluated otherwise.
+///
+/// 在启用 `verbose-debug` 功能且该类别在 [`MortarDebugCategories`] 中开启时，发出一条
+/// [`DebugCategory`] 类别的 `trace` 消息；否则不会对参数求值。
+macro_rules! verbose_trace {
+    ($category:ident, $($arg:tt)+) => {
+        #[cfg(feature = "verbose-debug")]
+        if $crate::debug::DebugCategory::$category.enabled() {
+            bevy::log::trace!(
+                target: $crate::debug::DebugCategory::$category.target(),
+                $($arg)+
+            );
+        }
+    };
+}
+
+/// Kinds of verbose trace statements, see [`MortarDebugCategories`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum DebugCategory {
+    Conditions,
+    Interpolation,
+    Events,
+    Runs,
+    Choices,
+}
+
+/// Enabled categories as a bit mask, copied from [`MortarDebugCategories`]. Kept process-wide
+/// because the trace statements sit in evaluation code that has no access to the world.
+static ENABLED_CATEGORIES: AtomicU8 = AtomicU8::new(u8::MAX);
+
+impl DebugCategory {
+    const fn bit(self) -> u8 {
+        1 << self as u8
+    }
+
+    #[cfg_attr(not(feature = "verbose-debug"), allow(dead_code))]
+    pub(crate) const fn target(self) -> &'static str {
+        match self {
+            Self::Conditions | Self::Interpolation => LOG_EVAL,
+            Self::Events => LOG_EVENTS,
+            Self::Runs => LOG_RUNS,
+            Self::Choices => LOG_DIALOGUE,
+        }
+    }
+
+    #[cfg_attr(not(feature = "verbose-debug"), allow(dead_code))]
+    pub(crate) fn enabled(self) -> bool {
+        ENABLED_CATEGORIES.load(Ordering::Relaxed) & self.bit() != 0
+    }
+}
+
+/// Per-category switches for the trace statements compiled in by the `verbose-debug` feature.
+/// Messages are logged at `trace` level to the `mortar::*` targets, so a log filter must let them
+/// through as well. Without the feature this resource has no effect.
+///
+/// `verbose-debug` 功能所编译进的 trace 语句的逐类开关。消息以 `trace` 级别记录到 `mortar::*`
+/// 目标，因此日志过滤器也需要放行它们。未启用该功能时此资源不起作用。
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MortarDebugCategories {
+    /// Condition results with the values of their operands.
+    ///
+    /// 条件结果及其操作数的值。
+    pub conditions: bool,
+    /// The value each placeholder and function call of a line rendered to.
+    ///
+    /// 行中每个占位符与函数调用渲染出的值。
+    pub interpolation: bool,
+    /// Index mapping of text events and the decision to fire each one.
+    ///
+    /// 文本事件的索引映射以及每个事件是否触发的判断。
+    pub events: bool,
+    /// `run` statements as they dispatch their event or start their timeline.
+    ///
+    /// `run` 语句派发其事件或启动其时间线的时刻。
+    pub runs: bool,
+    /// Visibility and enablement of each presented option.
+    ///
+    /// 每个已呈现选项的可见性与可用状态。
+    pub choices: bool,
+}
+
+impl Default for MortarDebugCategories {
+    fn default() -> Self {
+        Self {
+            conditions: true,
+            interpolation: true,
+            events: true,
+            runs: true,
+            choices: true,
+        }
+    }
+}
+
+impl MortarDebugCategories {
+    fn mask(&self) -> u8 {
+        [
+            (self.conditions, DebugCategory::Conditions),
+            (self.interpolation, DebugCategory::Interpolation),
+            (self.events, DebugCategory::Events),
+            (self.runs, DebugCategory::Runs),
+            (self.choices, DebugCategory::Choices),
+        ]
+        .into_iter()
+        .filter(|(enabled, _)| *enabled)
+        .fold(0, |mask, (_, category)| mask | category.bit())
+    }
+}
+
+/// Runtime switches for log messages whose verbosity users can control.
+///
+/// 可在运行时控制详细程度的日志消息开关。
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MortarLogConfig {
+    /// Warn when a script calls a function that is not bound.
+    ///
+    /// 脚本调用未绑定的函数时发出警告。
+    pub unbound_functions: bool,
+    /// Log a `debug` notice when a line is skipped because its condition failed or it was empty.
+    ///
+    /// 当某行因条件不成立或内容为空而被跳过时，记录一条 `debug` 提示。
+    pub skipped_lines: bool,
+}
+
+impl Default for MortarLogConfig {
+    fn default() -> Self {
+        Self {
+            unbound_functions: true,
+            skipped_lines: true,
+        }
+    }
+}
+
+/// Copies [`MortarLogConfig`] into the function registry, which reports unbound functions.
+pub(crate) fn apply_log_config(config: Res<MortarLogConfig>, mut runtime: ResMut<MortarRuntime>) {
+    if config.is_changed() {
+        runtime
+            .bypass_change_detection()
+            .functions
+            .set_unbound_warnings(config.unbound_functions);
+    }
+}
+
+/// Publishes [`MortarDebugCategories`] to the trace statements.
+pub(crate) fn apply_debug_categories(categories: Res<MortarDebugCategories>) {
+    if categories.is_changed() {
+        ENABLED_CATEGORIES.store(categories.mask(), Ordering::Relaxed);
+    }
+}
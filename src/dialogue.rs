@@ -13,34 +13,178 @@
 //! `bevy_mortar_bond` 面向对话层的插件入口。它把 Mortar 运行时状态连接到 Bevy
 //! 文本实体和游戏消息上，并把条件缓存、run 执行和文本事件收集这些更细的工作分发给专门的辅助模块。
 
-use crate::{
-    MortarAsset, MortarAudioSettings, MortarEvent, MortarEventTracker, MortarRegistry,
-    MortarRuntime, MortarVariableState, audio::auto_play_sound_events, evaluate_if_condition,
-    process_interpolated_text,
-};
-use bevy::asset::Assets;
 use bevy::ecs::schedule::SystemSet;
-use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
-use std::collections::HashSet;
 
+#[cfg(feature = "ui")]
+mod auto_advance;
+#[cfg(feature = "window")]
+mod background;
+mod backlog;
+mod choice_availability;
+mod choice_capture;
+mod components;
 mod condition_cache;
+#[cfg(feature = "ui")]
+mod display_animation;
+mod effects;
+mod event_schemas;
+mod event_targets;
+mod experiments;
+#[cfg(feature = "ui")]
+mod forced_line;
+mod header;
+mod history;
+mod icons;
+mod line_explanation;
+mod line_group;
+mod mandatory_events;
+mod parallel;
+mod public_constants;
+mod reveal;
+mod reveal_policy;
 mod run_execution;
+mod schedule;
+mod scoped;
+mod script_flow;
+mod speaker_focus;
+mod speech;
+mod switch_resolution;
+mod target_output;
 mod text_events;
-
-pub use condition_cache::{CachedCondition, evaluate_condition_cached};
-use text_events::collect_text_events;
+mod text_targets;
+#[cfg(feature = "typewriter")]
+mod typewriter;
+mod variables;
+
+#[cfg(feature = "ui")]
+pub use auto_advance::MortarAutoAdvance;
+#[cfg(feature = "window")]
+pub use background::{
+    MortarBackgroundPause, MortarBackgroundWindows, MortarFocusPolicy, MortarFocusSettings,
+};
+pub use backlog::{
+    DEFAULT_DIALOGUE_HISTORY_CAPACITY, MortarDialogueHistory, MortarDialogueHistoryEntry,
+};
+pub use choice_availability::{
+    MortarChoiceDeselected, MortarChoiceReevaluation, MortarChoiceView, MortarChoiceViewKind,
+    MortarChoicesPresented,
+};
+pub(crate) use choice_capture::capture_writes;
+pub use components::{
+    MortarDialogueText, MortarEventBinding, MortarGameEvent, MortarTextAdvanced, MortarTextChanged,
+    MortarTextChannel, MortarTextTarget,
+};
+pub use condition_cache::{CachedCondition, MortarConditionCache, evaluate_condition_cached};
+#[cfg(feature = "ui")]
+pub use display_animation::{
+    MortarAnimatedDisplay, MortarDisplayAnimations, MortarDisplayPolicy, MortarDisplayRoll,
+};
+pub use effects::{
+    MortarAppliedEffect, MortarEffectHandler, MortarEffectScope, MortarReversibleEffects,
+};
+pub use event_schemas::{
+    MortarEventArgError, MortarEventDiagnostic, MortarEventDiagnostics, MortarEventSchemas,
+    MortarInvalidEventPolicy,
+};
+pub(crate) use event_targets::arg_value;
+#[cfg(feature = "animation")]
+pub(crate) use event_targets::target_name;
+pub use event_targets::{MortarSpeaker, MortarSpeakerRegistry, MortarTargetDiagnostics};
+pub use experiments::{MortarExperimentDiagnostics, MortarExperimentTag, MortarExperiments};
+#[cfg(feature = "ui")]
+pub use forced_line::MortarRenderedLine;
+pub use header::{MortarHeaderChanged, MortarHeaderSettings};
+pub use history::{
+    DEFAULT_STATE_HISTORY_CAPACITY, MortarHistoryEvent, MortarStateDiff, MortarStateHistory,
+    MortarStateRecord,
+};
+pub(crate) use icons::is_icon_token;
+pub use icons::{DEFAULT_ICON_PLACEHOLDER, InlineIcon, MortarIconSettings, extract_inline_icons};
+#[cfg(feature = "ui-icons")]
+pub use icons::{
+    MISSING_ICON_GLYPH, MortarIconAtlas, MortarIconDiagnostics, MortarIconPlugin, MortarInlineIcon,
+};
+pub(crate) use line_explanation::LineExplanation;
+#[cfg(feature = "tools")]
+pub(crate) use line_explanation::describe_condition;
+pub use mandatory_events::{MortarEventFlush, MortarMandatoryEvents};
+pub use public_constants::LoggedConstants;
+pub(crate) use reveal::is_pause_token;
+pub use reveal::{
+    LinePosition, MortarRevealStep, MortarTextReveal, PAUSE_REVEAL_ACTION, PAUSE_TOKEN,
+};
+pub use reveal_policy::{
+    MortarRevealPolicy, MortarRevealPolicySettings, READING_CHARS_PER_SECOND, estimate_read_seconds,
+};
+pub use run_execution::{
+    DEFAULT_IMMEDIATE_STEPS_PER_FRAME, MortarLineStatus, MortarTimelineSettings, RunTextBehavior,
+};
+pub use schedule::MortarSchedule;
+pub use scoped::{MortarScopeGenerations, MortarScoped, MortarScopedCommands};
+pub use script_flow::{MortarScriptAdvance, MortarScriptFlow, MortarScriptFlowSettings};
+pub use speaker_focus::{MortarFocusHint, MortarFocusKind, MortarFocusTarget, MortarSpeakerFocus};
+pub use speech::{
+    MortarIconSpeechMap, MortarSpeakableChanged, MortarSpeechFormat, MortarSpeechFormatter,
+};
+use text_targets::update_mortar_text_targets;
+#[cfg(feature = "typewriter")]
+pub use typewriter::{MortarTypewriter, MortarTypewriterAdapter, MortarTypewriterPlugin};
+pub use variables::MortarDialogueVariables;
 
 /// Plugin that turns Mortar runtime data into ready-to-render UI text plus gameplay events.
 ///
 /// 负责把 Mortar 运行时数据转换成可直接渲染的文本和可监听的游戏事件。
-pub struct MortarDialoguePlugin;
+pub struct MortarDialoguePlugin {
+    /// Whether lines are written into the `Text` of each [`MortarTextTarget`]. On by default;
+    /// renderers drawing lines from [`MortarDialogueText`] or `MortarTextChanged` can turn it off.
+    ///
+    /// 是否将行写入每个 [`MortarTextTarget`] 的 `Text`。默认开启；根据 [`MortarDialogueText`] 或
+    /// `MortarTextChanged` 绘制行的渲染器可以将其关闭。
+    pub write_text: bool,
+    /// Schedule of the run scheduler (timeline waits, event durations and signal-wait timeouts),
+    /// of timed lines and of the auto mode of [`MortarAutoAdvance`]. With
+    /// [`MortarSchedule::FixedUpdate`] they read `Time<Fixed>` and a wait of `d` seconds ends
+    /// exactly `ceil(d / step)` fixed ticks after it starts, at any frame rate. Starting,
+    /// advancing and writing lines stay in `Update`, and the window focus policy pauses these
+    /// timers in either schedule.
+    ///
+    /// run 调度器（时间线等待、事件持续时间与信号等待超时）、定时行以及 [`MortarAutoAdvance`]
+    /// 自动模式所在的调度。使用 [`MortarSchedule::FixedUpdate`] 时它们读取 `Time<Fixed>`，持续
+    /// `d` 秒的等待会在开始后恰好 `ceil(d / step)` 个固定刻结束，与帧率无关。开始、推进与写入行仍在
+    /// `Update` 中进行，窗口焦点策略在任一调度中都会暂停这些计时器。
+    pub schedule: MortarSchedule,
+    /// Schedule of the reveal driver of [`MortarTextReveal`], set apart from
+    /// [`Self::schedule`] so a typewriter can follow the screen while timelines follow the
+    /// simulation.
+    ///
+    /// [`MortarTextReveal`] 显示驱动所在的调度，与 [`Self::schedule`] 分开设置，使打字机可以跟随
+    /// 屏幕而时间线跟随模拟。
+    pub reveal_schedule: MortarSchedule,
+}
+
+impl Default for MortarDialoguePlugin {
+    fn default() -> Self {
+        Self {
+            write_text: true,
+            schedule: MortarSchedule::Update,
+            reveal_schedule: MortarSchedule::Update,
+        }
+    }
+}
 
 /// System sets exposed by [`MortarDialoguePlugin`] for ordering customization.
 ///
 /// [`MortarDialoguePlugin`] 暴露的系统集合，方便自定义执行顺序。
 #[derive(SystemSet, Debug, Hash, PartialEq, Eq, Clone)]
 pub enum MortarDialogueSystemSet {
+    /// Systems that dispatch the gameplay events of `run` statements the dialogue reaches. A game
+    /// that mirrors those events into script variables runs after this set and before
+    /// [`MortarDialogueSystemSet::UpdateText`], so the next line reads the new values.
+    ///
+    /// 分发对话所到达的 `run` 语句的游戏事件的系统。把这些事件同步回脚本变量的游戏系统应在此集合
+    /// 之后、[`MortarDialogueSystemSet::UpdateText`] 之前运行，这样下一行会读取到新的值。
+    RunStatements,
     /// Systems that update dialogue text output.
     ///
     /// 更新对话文本输出的系统。
@@ -49,136 +193,223 @@ pub enum MortarDialogueSystemSet {
     ///
     /// 基于绑定索引发出游戏事件的系统。
     TriggerEvents,
+    /// Time-driven systems: the run scheduler, timed lines, the auto mode and the reveal driver.
+    /// They live in the schedules picked by [`MortarDialoguePlugin::schedule`] and
+    /// [`MortarDialoguePlugin::reveal_schedule`]; every set is configured in both `Update` and
+    /// `FixedUpdate`, so ordering against them works in either.
+    ///
+    /// 时间驱动的系统：run 调度器、定时行、自动模式与显示驱动。它们位于
+    /// [`MortarDialoguePlugin::schedule`] 与 [`MortarDialoguePlugin::reveal_schedule`] 所选的调度中；
+    /// 每个集合在 `Update` 与 `FixedUpdate` 中都有配置，因此在两者中都可以相对它们排序。
+    Timers,
+}
+
+/// Where the line is written onto the text targets. Text systems order after it whether or not
+/// the `ui` feature compiles the writer in.
+#[derive(SystemSet, Debug, Hash, PartialEq, Eq, Clone)]
+struct WriteTextTargets;
+
+/// Time-driven systems, by whether the dialogue waits on them before it moves on. The window
+/// focus policy stops them while the app is in the background.
+#[derive(SystemSet, Debug, Hash, PartialEq, Eq, Clone)]
+enum DialogueClock {
+    Blocking,
+    #[cfg_attr(
+        not(any(feature = "ui", feature = "window")),
+        expect(dead_code, reason = "only display rolls and the focus policy use it")
+    )]
+    Cosmetic,
 }
 
 impl Plugin for MortarDialoguePlugin {
     fn build(&self, app: &mut App) {
-        app.configure_sets(
-            Update,
-            (
-                MortarDialogueSystemSet::UpdateText,
-                MortarDialogueSystemSet::TriggerEvents,
+        for schedule in [MortarSchedule::Update, MortarSchedule::FixedUpdate] {
+            app.configure_sets(
+                schedule.label(),
+                (
+                    MortarDialogueSystemSet::RunStatements,
+                    MortarDialogueSystemSet::UpdateText,
+                    MortarDialogueSystemSet::TriggerEvents,
+                )
+                    .chain(),
+            );
+        }
+        app.init_resource::<MortarDialogueVariables>()
+            .init_resource::<MortarRunsExecuting>()
+            .init_resource::<RunTextBehavior>()
+            .init_resource::<MortarTimelineSettings>()
+            .init_resource::<crate::MortarTrackerMode>()
+            .init_resource::<MortarReversibleEffects>()
+            .init_resource::<MortarChoicesPresented>()
+            .init_resource::<MortarChoiceReevaluation>()
+            .init_resource::<LoggedConstants>()
+            .init_resource::<MortarIconSettings>()
+            .init_resource::<MortarRevealPolicySettings>()
+            .init_resource::<MortarScopeGenerations>()
+            .init_resource::<MortarScriptFlowSettings>()
+            .init_resource::<MortarStateHistory>()
+            .init_resource::<MortarDialogueHistory>()
+            .init_resource::<MortarHeaderSettings>()
+            .init_resource::<MortarEventDiagnostics>()
+            .init_resource::<MortarSpeakerRegistry>()
+            .init_resource::<MortarTargetDiagnostics>()
+            .init_resource::<MortarConditionCache>()
+            .init_resource::<MortarMandatoryEvents>()
+            .init_resource::<MortarExperiments>()
+            .init_resource::<MortarExperimentDiagnostics>()
+            .add_message::<MortarGameEvent>()
+            .add_message::<MortarRevealStep>()
+            .add_message::<MortarTextAdvanced>()
+            .add_message::<MortarChoiceDeselected>()
+            .add_message::<MortarScriptFlow>()
+            .add_message::<MortarHeaderChanged>()
+            .add_message::<MortarSpeakableChanged>()
+            .add_systems(First, condition_cache::clear_condition_cache)
+            .add_systems(
+                Update,
+                (
+                    public_constants::log_public_constants_once,
+                    reveal::sync_advance_gate.before(crate::system::process_mortar_events_system),
+                    choice_availability::refresh_presented_choices
+                        .after(crate::runtime::sync_capabilities)
+                        .before(crate::system::process_mortar_events_system),
+                    choice_capture::apply_choice_captures
+                        .after(crate::system::process_mortar_events_system)
+                        .before(MortarDialogueSystemSet::UpdateText),
+                    switch_resolution::resolve_content_switches
+                        .after(choice_capture::apply_choice_captures)
+                        .after(crate::system::handle_pending_jump_system)
+                        .before(MortarDialogueSystemSet::UpdateText),
+                    run_execution::process_run_statements_after_text
+                        .in_set(MortarDialogueSystemSet::RunStatements)
+                        .before(reveal::sync_advance_gate),
+                    scoped::despawn_ended_scopes
+                        .after(crate::system::handle_pending_jump_system)
+                        .before(MortarDialogueSystemSet::UpdateText),
+                    event_schemas::validate_collected_events
+                        .in_set(MortarDialogueSystemSet::UpdateText)
+                        .after(WriteTextTargets),
+                    backlog::record_dialogue_history
+                        .in_set(MortarDialogueSystemSet::UpdateText)
+                        .after(WriteTextTargets),
+                    (
+                        speech::update_speakable_text,
+                        speech::announce_presented_choices,
+                    )
+                        .run_if(speech::speech_enabled)
+                        .in_set(MortarDialogueSystemSet::UpdateText)
+                        .after(WriteTextTargets),
+                    run_execution::trigger_bound_events
+                        .in_set(MortarDialogueSystemSet::TriggerEvents),
+                    effects::update_reversible_effects
+                        .after(MortarDialogueSystemSet::TriggerEvents)
+                        .after(run_execution::process_pending_run_executions)
+                        .after(run_execution::process_run_statements_after_text),
+                    script_flow::apply_script_flow_control
+                        .after(MortarDialogueSystemSet::TriggerEvents)
+                        .after(run_execution::process_pending_run_executions)
+                        .after(run_execution::process_run_statements_after_text),
+                ),
+            )
+            .add_systems(
+                PostUpdate,
+                (
+                    run_execution::clear_runs_executing_flag,
+                    history::record_state_history,
+                )
+                    .chain(),
             )
-                .chain(),
-        )
-        .init_resource::<MortarAudioSettings>()
-        .init_resource::<MortarDialogueVariables>()
-        .init_resource::<MortarRunsExecuting>()
-        .init_resource::<LoggedConstants>()
-        .add_message::<MortarGameEvent>()
-        .add_systems(
-            Update,
-            (
-                log_public_constants_once,
-                run_execution::process_run_statements_after_text,
-                update_mortar_text_targets.in_set(MortarDialogueSystemSet::UpdateText),
-                run_execution::trigger_bound_events.in_set(MortarDialogueSystemSet::TriggerEvents),
-                run_execution::process_pending_run_executions,
-                auto_play_sound_events
+            .add_systems(
+                self.schedule.label(),
+                run_execution::process_pending_run_executions
+                    .in_set(DialogueClock::Blocking)
+                    .in_set(MortarDialogueSystemSet::Timers),
+            )
+            .add_message::<MortarTextChanged>()
+            .init_resource::<MortarSpeakerFocus>()
+            .add_message::<MortarFocusHint>()
+            .add_systems(
+                Update,
+                (
+                    update_mortar_text_targets
+                        .in_set(MortarDialogueSystemSet::UpdateText)
+                        .in_set(WriteTextTargets),
+                    speaker_focus::update_speaker_focus
+                        .in_set(MortarDialogueSystemSet::UpdateText)
+                        .after(WriteTextTargets),
+                ),
+            );
+        #[cfg(feature = "ui")]
+        app.init_resource::<MortarDisplayAnimations>()
+            .init_resource::<MortarAutoAdvance>()
+            .insert_resource(text_targets::WriteText(self.write_text))
+            .add_systems(
+                Update,
+                (
+                    run_execution::restore_text_after_runs
+                        .in_set(MortarDialogueSystemSet::UpdateText)
+                        .before(WriteTextTargets),
+                    display_animation::animate_display_rolls
+                        .in_set(MortarDialogueSystemSet::UpdateText)
+                        .in_set(DialogueClock::Cosmetic)
+                        .after(WriteTextTargets),
+                ),
+            )
+            .add_systems(
+                self.reveal_schedule.label(),
+                (
+                    reveal::advance_text_reveal
+                        .in_set(DialogueClock::Blocking)
+                        .in_set(MortarDialogueSystemSet::Timers),
+                    reveal::handle_line_seeks,
+                )
+                    .chain()
+                    .in_set(MortarDialogueSystemSet::UpdateText)
+                    .after(WriteTextTargets),
+            )
+            .add_systems(
+                self.schedule.label(),
+                (
+                    reveal_policy::apply_reveal_policies
+                        .in_set(MortarDialogueSystemSet::UpdateText)
+                        .after(reveal::handle_line_seeks),
+                    auto_advance::auto_advance_dialogue.after(MortarDialogueSystemSet::UpdateText),
+                )
+                    .in_set(MortarDialogueSystemSet::Timers)
+                    .in_set(DialogueClock::Blocking),
+            );
+        #[cfg(feature = "audio")]
+        app.init_resource::<crate::MortarAudioSettings>()
+            .add_systems(
+                Update,
+                crate::audio::auto_play_sound_events
                     .after(MortarDialogueSystemSet::TriggerEvents)
                     .after(run_execution::process_pending_run_executions),
-            ),
-        )
-        .add_systems(PostUpdate, run_execution::clear_runs_executing_flag);
-    }
-}
-
-/// Marker for `Text` entities that should display Mortar dialogue output.
-///
-/// 标记需要显示 Mortar 对话文本的 UI 实体。
-#[derive(Component)]
-pub struct MortarTextTarget;
-
-/// Stores the current Mortar dialogue text so users can bind custom render effects.
-///
-/// 存储当前 Mortar 对话文本，便于绑定自定义渲染效果。
-#[derive(Component, Debug, Clone, Default)]
-pub struct MortarDialogueText {
-    /// Prefix header string (`[file / node]`).
-    ///
-    /// 前缀头字符串（`[文件 / 节点]`）。
-    pub header: String,
-    /// Body text processed from Mortar.
-    ///
-    /// Mortar 处理后的正文文本。
-    pub body: String,
-}
-
-impl MortarDialogueText {
-    /// Returns the concatenated header + body string.
-    ///
-    /// 返回拼接后的头部与正文文本。
-    pub fn full_text(&self) -> String {
-        format!("{}{}", self.header, self.body)
-    }
-}
-
-/// Component that exposes the current playback index for Mortar events.
-///
-/// 用户可以将 `current_index` 绑定到任意系统（打字机、
-/// 音频时间线等），由 [`MortarDialoguePlugin`] 自动触发事件。
-#[derive(Component, Debug, Clone, Copy, Default)]
-pub struct MortarEventBinding {
-    /// Progress index used by [`MortarEventTracker`].
-    ///
-    /// [`MortarEventTracker`] 使用的进度索引。
-    pub current_index: f32,
-}
-
-/// Event emitted whenever Mortar timelines or text events ask the game to do something.
-///
-/// 由 Mortar 文本事件或时间线触发的游戏事件。
-#[derive(Message, Debug, Clone)]
-pub struct MortarGameEvent {
-    /// The logical source entity (usually the dialogue text). `None` for timeline-only events.
-    ///
-    /// 逻辑来源实体（通常是对话文本）；仅时间线事件时为 `None`。
-    pub source: Option<Entity>,
-    /// Event name defined inside Mortar (e.g. "set_animation").
-    ///
-    /// Mortar 中定义的事件名称（如 "set_animation"）。
-    pub name: String,
-    /// Raw argument list from Mortar.
-    ///
-    /// 来自 Mortar 的原始参数列表。
-    pub args: Vec<String>,
-}
-
-/// Resource that caches variable state for the currently loaded mortar file.
-///
-/// 缓存当前 mortar 文件变量状态的资源。
-#[derive(Resource, Default)]
-pub struct MortarDialogueVariables {
-    pub state: Option<MortarVariableState>,
-    active_path: Option<String>,
-}
-
-impl MortarDialogueVariables {
-    fn reset(&mut self) {
-        self.state = None;
-        self.active_path = None;
-    }
-
-    fn ensure_for(
-        &mut self,
-        path: &str,
-        asset: &mortar_compiler::MortaredData,
-    ) -> &mut MortarVariableState {
-        if self.active_path.as_deref() != Some(path) {
-            self.state = Some(MortarVariableState::from_variables(
-                &asset.variables,
-                &asset.constants,
-                &asset.enums,
-            ));
-            self.active_path = Some(path.to_string());
-        } else if self.state.is_none() {
-            self.state = Some(MortarVariableState::from_variables(
-                &asset.variables,
-                &asset.constants,
-                &asset.enums,
-            ));
+            );
+        #[cfg(feature = "window")]
+        {
+            for schedule in [MortarSchedule::Update, MortarSchedule::FixedUpdate] {
+                app.configure_sets(
+                    schedule.label(),
+                    (
+                        DialogueClock::Blocking.run_if(background::blocking_clocks_run),
+                        DialogueClock::Cosmetic.run_if(background::cosmetic_clocks_run),
+                    ),
+                );
+            }
+            app.init_resource::<background::MortarFocusSettings>()
+                .init_resource::<background::MortarBackgroundPause>()
+                .add_message::<bevy::window::WindowFocused>()
+                .add_message::<bevy::window::WindowOccluded>()
+                .add_message::<bevy::window::WindowClosed>()
+                .add_message::<crate::MortarLifecycleEvent>()
+                .add_systems(PreUpdate, background::track_window_focus);
+            #[cfg(feature = "audio")]
+            app.add_systems(
+                Update,
+                background::pause_background_sounds.after(crate::audio::auto_play_sound_events),
+            );
         }
-        self.state.as_mut().expect("variable state initialized")
     }
 }
 
@@ -189,417 +420,3 @@ impl MortarDialogueVariables {
 pub struct MortarRunsExecuting {
     pub executing: bool,
 }
-
-/// Records which mortar files already printed their public constants.
-///
-/// 记录哪些 mortar 文件已经打印过其公共常量。
-#[derive(Resource, Default)]
-pub struct LoggedConstants {
-    seen_paths: HashSet<String>,
-}
-
-#[derive(SystemParam)]
-struct TextUpdateParams<'w, 's> {
-    commands: Commands<'w, 's>,
-    runtime: Res<'w, MortarRuntime>,
-    registry: Res<'w, MortarRegistry>,
-    assets: Res<'w, Assets<MortarAsset>>,
-    texts: Query<'w, 's, (Entity, &'static mut Text), With<MortarTextTarget>>,
-    variable_cache: ResMut<'w, MortarDialogueVariables>,
-    runs_executing: Res<'w, MortarRunsExecuting>,
-    events: MessageWriter<'w, MortarEvent>,
-}
-
-fn log_public_constants_once(
-    runtime: Res<MortarRuntime>,
-    registry: Res<MortarRegistry>,
-    assets: Res<Assets<MortarAsset>>,
-    mut logged: ResMut<LoggedConstants>,
-) {
-    let Some(state) = runtime.primary_dialogue_state() else {
-        return;
-    };
-
-    if logged.seen_paths.contains(&state.mortar_path) {
-        return;
-    }
-
-    let Some(handle) = registry.get(&state.mortar_path) else {
-        return;
-    };
-    let Some(asset) = assets.get(handle) else {
-        return;
-    };
-
-    let public_consts: Vec<_> = asset.data.constants.iter().filter(|c| c.public).collect();
-    if public_consts.is_empty() {
-        logged.seen_paths.insert(state.mortar_path.clone());
-        return;
-    }
-
-    info!("Mortar public constants exposed by {}:", state.mortar_path);
-    for constant in public_consts {
-        let value_repr = match &constant.value {
-            serde_json::Value::String(s) => s.clone(),
-            _ => constant.value.to_string(),
-        };
-        info!(
-            "  {} ({}): {}",
-            constant.name, constant.const_type, value_repr
-        );
-    }
-
-    logged.seen_paths.insert(state.mortar_path.clone());
-}
-
-/// Processes a line group: evaluates conditions per-line, processes interpolation,
-/// and joins passing lines with `\n`. Returns `None` if all lines are empty/skipped.
-///
-/// 处理 line 组：逐行评估条件、处理插值，用 `\n` 拼接通过的行。
-fn process_line_group(
-    group: &[crate::TextData],
-    functions: &crate::MortarFunctionRegistry,
-    func_decls: &[mortar_compiler::Function],
-    variable_state: &mut MortarVariableState,
-) -> Option<String> {
-    let mut result_lines = Vec::new();
-    for line_data in group {
-        if let Some(condition) = &line_data.condition
-            && !evaluate_if_condition(condition, functions, variable_state)
-        {
-            continue;
-        }
-        for stmt in &line_data.pre_statements {
-            if stmt.stmt_type == "assignment"
-                && let (Some(var_name), Some(value)) = (&stmt.var_name, &stmt.value)
-            {
-                variable_state.execute_assignment(var_name, value);
-            }
-        }
-        let line_text = process_interpolated_text(line_data, functions, func_decls, variable_state);
-        if !line_text.is_empty() {
-            result_lines.push(line_text);
-        }
-    }
-    if result_lines.is_empty() {
-        return None;
-    }
-    Some(result_lines.join("\n"))
-}
-
-fn update_mortar_text_targets(
-    mut asset_events: MessageReader<AssetEvent<MortarAsset>>,
-    params: TextUpdateParams,
-    mut last_key: Local<Option<(String, String, usize)>>,
-    mut skip_next_conditional: Local<bool>,
-    mut cached_condition: Local<Option<CachedCondition>>,
-) {
-    let TextUpdateParams {
-        mut commands,
-        runtime,
-        registry,
-        assets,
-        mut texts,
-        mut variable_cache,
-        runs_executing,
-        mut events,
-    } = params;
-
-    // Check if any Mortar asset has been modified (hot reloaded)
-    for event in asset_events.read() {
-        if let AssetEvent::Modified { id: _ } = event {
-            // If an asset changed, force a reload of variables
-            info!("Mortar asset modified, reloading variables...");
-            variable_cache.reset();
-            *last_key = None; // Also reset last_key to ensure text re-evaluation
-            *cached_condition = None;
-        }
-    }
-
-    if runs_executing.executing {
-        return;
-    }
-
-    if !runtime.has_active_dialogues() {
-        variable_cache.reset();
-        for (_, mut text) in &mut texts {
-            **text = "等待加载对话...".to_string();
-        }
-        *last_key = None;
-        *cached_condition = None;
-        return;
-    }
-
-    if !runtime.is_changed() {
-        return;
-    }
-
-    for (entity, mut text) in &mut texts {
-        let Some(state) = runtime.primary_dialogue_state() else {
-            **text = "等待加载对话...".to_string();
-            *last_key = None;
-            continue;
-        };
-
-        let asset_data = registry
-            .get(&state.mortar_path)
-            .and_then(|handle| assets.get(handle))
-            .map(|asset| &asset.data);
-
-        let current_key = (
-            state.mortar_path.clone(),
-            state.current_node.clone(),
-            state.text_index,
-        );
-
-        if last_key.as_ref() == Some(&current_key) {
-            continue;
-        }
-
-        let Some(text_data) = state.current_text_data() else {
-            continue;
-        };
-
-        let variable_state = if let Some(asset_data) = asset_data {
-            variable_cache.ensure_for(&state.mortar_path, asset_data)
-        } else {
-            variable_cache
-                .state
-                .get_or_insert_with(MortarVariableState::new)
-        };
-
-        let func_decls = asset_data
-            .map(|data| data.functions.as_slice())
-            .unwrap_or(&[]);
-
-        // Line groups: collect all consecutive lines, evaluate conditions per-line,
-        // join passing lines with '\n'.
-        //
-        // Line 组：收集所有连续 line，逐行评估条件，用 '\n' 拼接通过的行。
-        if text_data.is_line {
-            let group = state.current_line_group().unwrap_or(&[]);
-            let Some(processed_text) =
-                process_line_group(group, &runtime.functions, func_decls, variable_state)
-            else {
-                *last_key = Some(current_key);
-                events.write(MortarEvent::next_text());
-                continue;
-            };
-
-            *skip_next_conditional = false;
-            commands.entity(entity).remove::<MortarEventTracker>();
-            commands.entity(entity).remove::<MortarEventBinding>();
-
-            *last_key = Some(current_key);
-
-            let header = format!("[{} / {}]\n\n", state.mortar_path, state.current_node);
-            let final_text = format!("{}{}", header, processed_text);
-            **text = final_text.clone();
-            commands.entity(entity).insert(MortarDialogueText {
-                header,
-                body: processed_text,
-            });
-            continue;
-        }
-
-        // Regular text: handling (existing logic)
-        //
-        // 常规 text: 处理（现有逻辑）
-
-        if *skip_next_conditional && text_data.condition.is_some() {
-            *skip_next_conditional = false;
-            *last_key = Some(current_key);
-            events.write(MortarEvent::next_text());
-            continue;
-        }
-
-        if let Some(condition) = &text_data.condition {
-            let result = evaluate_condition_cached(
-                condition,
-                &runtime.functions,
-                variable_state,
-                &mut cached_condition,
-            );
-            if !result {
-                *last_key = Some(current_key.clone());
-                events.write(MortarEvent::next_text());
-                continue;
-            }
-        }
-
-        let mut executed_statements = false;
-        for stmt in &text_data.pre_statements {
-            if stmt.stmt_type == "assignment"
-                && let (Some(var_name), Some(value)) = (&stmt.var_name, &stmt.value)
-            {
-                variable_state.execute_assignment(var_name, value);
-                executed_statements = true;
-            }
-        }
-
-        let processed_text =
-            process_interpolated_text(text_data, &runtime.functions, func_decls, variable_state);
-
-        if processed_text.is_empty() {
-            if executed_statements && text_data.condition.is_some() {
-                *skip_next_conditional = true;
-            }
-            *last_key = Some(current_key);
-            events.write(MortarEvent::next_text());
-            continue;
-        }
-
-        *skip_next_conditional = false;
-
-        commands.entity(entity).remove::<MortarEventTracker>();
-        commands.entity(entity).remove::<MortarEventBinding>();
-
-        let all_events = collect_text_events(
-            text_data,
-            variable_state,
-            asset_data,
-            state.current_text_content_index(),
-            state.node_data(),
-        );
-
-        if !all_events.is_empty() {
-            commands
-                .entity(entity)
-                .insert(MortarEventTracker::new(all_events))
-                .insert(MortarEventBinding::default());
-        }
-
-        *last_key = Some(current_key);
-
-        let header = format!("[{} / {}]\n\n", state.mortar_path, state.current_node);
-        let final_text = format!("{}{}", header, processed_text);
-        **text = final_text.clone();
-        commands.entity(entity).insert(MortarDialogueText {
-            header,
-            body: processed_text,
-        });
-    }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::{TextData, binder::MortarFunctionRegistry};
-
-    fn make_line(value: &str) -> TextData {
-        TextData {
-            value: value.to_string(),
-            interpolated_parts: None,
-            condition: None,
-            pre_statements: vec![],
-            events: None,
-            is_line: true,
-        }
-    }
-
-    fn make_conditional_line(value: &str, cond: mortar_compiler::IfCondition) -> TextData {
-        TextData {
-            value: value.to_string(),
-            interpolated_parts: None,
-            condition: Some(cond),
-            pre_statements: vec![],
-            events: None,
-            is_line: true,
-        }
-    }
-
-    fn true_condition() -> mortar_compiler::IfCondition {
-        // A variable set to "1" evaluates to truthy
-        mortar_compiler::IfCondition {
-            cond_type: "identifier".to_string(),
-            operator: None,
-            left: None,
-            right: None,
-            operand: None,
-            value: Some("truthy_var".to_string()),
-        }
-    }
-
-    fn false_condition() -> mortar_compiler::IfCondition {
-        // A variable not set evaluates to falsy
-        mortar_compiler::IfCondition {
-            cond_type: "identifier".to_string(),
-            operator: None,
-            left: None,
-            right: None,
-            operand: None,
-            value: Some("unset_var".to_string()),
-        }
-    }
-
-    #[test]
-    fn test_process_line_group_basic() {
-        let group = vec![make_line("Line A"), make_line("Line B")];
-        let functions = MortarFunctionRegistry::new();
-        let func_decls = vec![];
-        let mut vs = MortarVariableState::default();
-
-        let result = process_line_group(&group, &functions, &func_decls, &mut vs);
-        assert_eq!(result, Some("Line A\nLine B".to_string()));
-    }
-
-    #[test]
-    fn test_process_line_group_single_line() {
-        let group = vec![make_line("Only line")];
-        let functions = MortarFunctionRegistry::new();
-        let func_decls = vec![];
-        let mut vs = MortarVariableState::default();
-
-        let result = process_line_group(&group, &functions, &func_decls, &mut vs);
-        assert_eq!(result, Some("Only line".to_string()));
-    }
-
-    #[test]
-    fn test_process_line_group_all_conditions_false() {
-        let group = vec![
-            make_conditional_line("Line A", false_condition()),
-            make_conditional_line("Line B", false_condition()),
-        ];
-        let functions = MortarFunctionRegistry::new();
-        let func_decls = vec![];
-        let mut vs = MortarVariableState::default();
-
-        let result = process_line_group(&group, &functions, &func_decls, &mut vs);
-        assert_eq!(result, None, "All conditions false → None");
-    }
-
-    #[test]
-    fn test_process_line_group_mixed_conditions() {
-        let group = vec![
-            make_line("Always shown"),
-            make_conditional_line("True line", true_condition()),
-            make_conditional_line("False line", false_condition()),
-        ];
-        let functions = MortarFunctionRegistry::new();
-        let func_decls = vec![];
-        let mut vs = MortarVariableState::default();
-        vs.set("truthy_var", crate::MortarVariableValue::Boolean(true));
-
-        let result = process_line_group(&group, &functions, &func_decls, &mut vs);
-        assert_eq!(
-            result,
-            Some("Always shown\nTrue line".to_string()),
-            "False line should be excluded"
-        );
-    }
-
-    #[test]
-    fn test_process_line_group_empty_lines_skipped() {
-        let group = vec![make_line(""), make_line("Non-empty")];
-        let functions = MortarFunctionRegistry::new();
-        let func_decls = vec![];
-        let mut vs = MortarVariableState::default();
-
-        let result = process_line_group(&group, &functions, &func_decls, &mut vs);
-        assert_eq!(
-            result,
-            Some("Non-empty".to_string()),
-            "Empty lines should be excluded from join"
-        );
-    }
-}
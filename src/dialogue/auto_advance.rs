@@ -0,0 +1,119 @@
+//! # auto_advance.rs
+//!
+//! # auto_advance.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! The "auto mode" of visual novels: with [`MortarAutoAdvance::enabled`], the primary dialogue
+//! moves on by itself once its line is fully shown and has stayed on screen for the configured
+//! delay. Whether a line is fully shown, and what advancing would do, comes from
+//! [`crate::MortarRuntime::advance_intent`], so the delay only starts once the reveal or
+//! typewriter has finished and every checkpoint was passed. The clock holds while `run`
+//! statements execute and restarts on each new line. Choices are never confirmed: when the
+//! dialogue reaches a choice group it waits for the player, selected option or not.
+//!
+//! 视觉小说的“自动模式”：开启 [`MortarAutoAdvance::enabled`] 后，主对话会在当前行完整显示并停留
+//! 设定的延迟后自行推进。行是否已完整显示、推进会产生什么效果，都取自
+//! [`crate::MortarRuntime::advance_intent`]，因此延迟只会在逐字显示或打字机结束、且越过所有检查点
+//! 之后开始。`run` 语句执行期间计时暂停，每到新的一行重新计时。选项永远不会被确认：对话到达选项组时
+//! 会等待玩家，无论是否已选中选项。
+
+use bevy::prelude::*;
+
+use super::{MortarDialogueText, MortarRunsExecuting, MortarTextTarget};
+use crate::{AdvanceIntent, LineCursor, MortarCommand, MortarRuntime};
+
+/// Settings of the auto mode.
+///
+/// 自动模式的设置。
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct MortarAutoAdvance {
+    /// Whether the dialogue advances by itself. Off by default.
+    ///
+    /// 对话是否自行推进。默认关闭。
+    pub enabled: bool,
+    /// Seconds a fully shown line stays before the dialogue advances.
+    ///
+    /// 完整显示的行在对话推进前停留的秒数。
+    pub delay_seconds: f32,
+    /// Seconds added for each character of the line's body, so longer lines stay longer; `0.0`
+    /// for none.
+    ///
+    /// 行正文每个字符额外增加的秒数，使较长的行停留更久；`0.0` 表示不增加。
+    pub per_char_seconds: f32,
+}
+
+impl Default for MortarAutoAdvance {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            delay_seconds: 1.5,
+            per_char_seconds: 0.0,
+        }
+    }
+}
+
+impl MortarAutoAdvance {
+    /// Seconds a line whose body is `body` stays before the dialogue advances.
+    ///
+    /// 正文为 `body` 的行在对话推进前停留的秒数。
+    pub fn delay_for(&self, body: &str) -> f32 {
+        self.delay_seconds + self.per_char_seconds * body.chars().count() as f32
+    }
+}
+
+/// Time the current line has been fully shown.
+#[derive(Default)]
+pub(super) struct AutoAdvanceClock {
+    line: Option<LineCursor>,
+    elapsed: f32,
+    /// Whether the line was already advanced, so a request still in the queue is not repeated.
+    advanced: bool,
+}
+
+/// Advances the primary dialogue once its line has been fully shown for the configured delay.
+pub(super) fn auto_advance_dialogue(
+    settings: Res<MortarAutoAdvance>,
+    time: Res<Time>,
+    runtime: Res<MortarRuntime>,
+    runs: Res<MortarRunsExecuting>,
+    targets: Query<&MortarDialogueText, With<MortarTextTarget>>,
+    mut clock: Local<AutoAdvanceClock>,
+    mut events: MessageWriter<MortarCommand>,
+) {
+    let line = runtime.primary_dialogue_state().map(|state| state.cursor());
+    if !settings.enabled || line.is_none() {
+        *clock = AutoAdvanceClock::default();
+        return;
+    }
+    if clock.line != line {
+        *clock = AutoAdvanceClock { line, ..default() };
+    }
+    match runtime.advance_intent() {
+        AdvanceIntent::BlockedByRuns { .. } => return,
+        _ if runs.executing => return,
+        AdvanceIntent::NextLine | AdvanceIntent::WouldFinishDialogue => {}
+        _ => {
+            clock.elapsed = 0.0;
+            return;
+        }
+    }
+    if clock.advanced {
+        return;
+    }
+    clock.elapsed += time.delta_secs();
+    let body = targets
+        .iter()
+        .filter(|text| Some(text.cursor) == line)
+        .map(|text| text.body.as_str())
+        .max_by_key(|body| body.chars().count())
+        .unwrap_or_default();
+    if clock.elapsed >= settings.delay_for(body) {
+        clock.advanced = true;
+        events.write(MortarCommand::NextText {
+            target: runtime.primary_dialogue,
+        });
+    }
+}
@@ -0,0 +1,194 @@
+//! # background.rs
+//!
+//! # background.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Pausing dialogue while the app is in the background. Window focus and occlusion messages say
+//! which windows the player is looking at; a minimized window counts as unfocused. Once every
+//! window is away, or any of them under [`MortarBackgroundWindows::AnyUnfocused`], the
+//! [`MortarFocusPolicy`] decides what stops. Clocks the dialogue waits on before it moves on (the
+//! reveal driver, timed lines and timeline waits) stop under both pausing policies, while display
+//! rolls and Mortar's sound players only stop under [`MortarFocusPolicy::PauseAll`]. A stopped
+//! clock drops the frames it misses, so nothing is caught up on return. Entering and leaving the
+//! pause are reported as [`MortarLifecycleEvent`]s.
+//!
+//! 应用处于后台时暂停对话。窗口焦点与遮挡消息说明玩家正在看哪些窗口；最小化的窗口视为失去焦点。
+//! 当所有窗口都离开（在 [`MortarBackgroundWindows::AnyUnfocused`] 下为任一窗口离开）时，由
+//! [`MortarFocusPolicy`] 决定停止哪些内容。对话在继续之前需要等待的时钟（逐字显示驱动、定时行与
+//! 时间线等待）在两种暂停策略下都会停止，而显示滚动与 Mortar 的声音播放器只在
+//! [`MortarFocusPolicy::PauseAll`] 下停止。停止的时钟会丢弃错过的帧，因此返回时不会补追进度。
+//! 进入与离开暂停会以 [`MortarLifecycleEvent`] 报告。
+
+use bevy::prelude::*;
+use bevy::window::{WindowClosed, WindowFocused, WindowOccluded};
+use std::collections::HashMap;
+
+use crate::MortarLifecycleEvent;
+use crate::debug::LOG_DIALOGUE;
+
+/// What pauses while the app is in the background.
+///
+/// 应用处于后台时暂停哪些内容。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MortarFocusPolicy {
+    /// Nothing pauses; dialogue keeps running unwatched.
+    ///
+    /// 不暂停任何内容；对话在无人观看时继续运行。
+    #[default]
+    ContinueInBackground,
+    /// Every dialogue clock and Mortar's sound players pause.
+    ///
+    /// 所有对话时钟与 Mortar 的声音播放器都暂停。
+    PauseAll,
+    /// Only the clocks the dialogue waits on pause; display rolls and sounds play out.
+    ///
+    /// 只暂停对话需要等待的时钟；显示滚动与声音照常播放。
+    PauseBlockingOnly,
+}
+
+/// Which windows must be away before the app counts as in the background.
+///
+/// 需要哪些窗口离开，应用才算处于后台。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MortarBackgroundWindows {
+    /// Every window is unfocused or minimized.
+    ///
+    /// 所有窗口都失去焦点或被最小化。
+    #[default]
+    AllUnfocused,
+    /// Any window is unfocused or minimized.
+    ///
+    /// 任一窗口失去焦点或被最小化。
+    AnyUnfocused,
+}
+
+/// How dialogue reacts to the app going into the background.
+///
+/// 对话如何响应应用进入后台。
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MortarFocusSettings {
+    pub policy: MortarFocusPolicy,
+    pub background_when: MortarBackgroundWindows,
+}
+
+/// Whether a window is looked at, as its focus and occlusion messages reported.
+#[derive(Debug, Clone, Copy, Default)]
+struct WindowAttention {
+    unfocused: bool,
+    occluded: bool,
+}
+
+impl WindowAttention {
+    fn away(self) -> bool {
+        self.unfocused || self.occluded
+    }
+}
+
+/// The windows heard from and the pause they put dialogue in.
+///
+/// 已收到消息的窗口，以及它们使对话进入的暂停状态。
+#[derive(Resource, Debug, Default)]
+pub struct MortarBackgroundPause {
+    windows: HashMap<Entity, WindowAttention>,
+    paused: Option<MortarFocusPolicy>,
+    /// Sound players this pause stopped, resumed when it ends.
+    #[cfg(feature = "audio")]
+    paused_sinks: Vec<Entity>,
+}
+
+impl MortarBackgroundPause {
+    pub fn is_paused(&self) -> bool {
+        self.paused.is_some()
+    }
+
+    /// The policy the current pause follows.
+    ///
+    /// 当前暂停所遵循的策略。
+    pub fn paused_by(&self) -> Option<MortarFocusPolicy> {
+        self.paused
+    }
+
+    fn in_background(&self, windows: MortarBackgroundWindows) -> bool {
+        let mut attention = self.windows.values();
+        match windows {
+            _ if self.windows.is_empty() => false,
+            MortarBackgroundWindows::AllUnfocused => attention.all(|window| window.away()),
+            MortarBackgroundWindows::AnyUnfocused => attention.any(|window| window.away()),
+        }
+    }
+}
+
+/// Follows window focus and enters or leaves the pause the policy asks for.
+pub(super) fn track_window_focus(
+    settings: Res<MortarFocusSettings>,
+    mut pause: ResMut<MortarBackgroundPause>,
+    mut focused: MessageReader<WindowFocused>,
+    mut occluded: MessageReader<WindowOccluded>,
+    mut closed: MessageReader<WindowClosed>,
+    mut lifecycle: MessageWriter<MortarLifecycleEvent>,
+) {
+    for message in focused.read() {
+        pause.windows.entry(message.window).or_default().unfocused = !message.focused;
+    }
+    for message in occluded.read() {
+        pause.windows.entry(message.window).or_default().occluded = message.occluded;
+    }
+    for message in closed.read() {
+        pause.windows.remove(&message.window);
+    }
+    let wanted = (settings.policy != MortarFocusPolicy::ContinueInBackground
+        && pause.in_background(settings.background_when))
+    .then_some(settings.policy);
+    if pause.paused == wanted {
+        return;
+    }
+    match (pause.paused, wanted) {
+        (None, Some(policy)) => {
+            debug!(target: LOG_DIALOGUE, "App in the background, pausing dialogue ({:?})", policy);
+            lifecycle.write(MortarLifecycleEvent::BackgroundPaused);
+        }
+        (Some(_), None) => {
+            debug!(target: LOG_DIALOGUE, "App back in the foreground, resuming dialogue");
+            lifecycle.write(MortarLifecycleEvent::BackgroundResumed);
+        }
+        // The policy changed during the pause.
+        //
+        // 暂停期间策略发生了变化。
+        _ => {}
+    }
+    pause.paused = wanted;
+}
+
+pub(super) fn blocking_clocks_run(pause: Res<MortarBackgroundPause>) -> bool {
+    pause.paused.is_none()
+}
+
+pub(super) fn cosmetic_clocks_run(pause: Res<MortarBackgroundPause>) -> bool {
+    pause.paused != Some(MortarFocusPolicy::PauseAll)
+}
+
+/// Pauses Mortar's playing sounds under [`MortarFocusPolicy::PauseAll`], including ones started
+/// during the pause, and resumes those it paused afterwards.
+#[cfg(feature = "audio")]
+pub(super) fn pause_background_sounds(
+    mut pause: ResMut<MortarBackgroundPause>,
+    sinks: Query<(Entity, &AudioSink), With<crate::MortarInternal>>,
+) {
+    if pause.paused == Some(MortarFocusPolicy::PauseAll) {
+        for (entity, sink) in &sinks {
+            if !sink.is_paused() {
+                sink.pause();
+                pause.paused_sinks.push(entity);
+            }
+        }
+        return;
+    }
+    for entity in std::mem::take(&mut pause.paused_sinks) {
+        if let Ok((_, sink)) = sinks.get(entity) {
+            sink.play();
+        }
+    }
+}
@@ -0,0 +1,190 @@
+//! # backlog.rs
+//!
+//! # backlog.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! The dialogue backlog: every line the primary dialogue showed on a text target and every option
+//! confirmed in it, oldest first, for a "previous lines" screen. [`MortarDialogueHistory`] keeps
+//! one entry per line, per voice of a `parallel_text` line and per confirmed option, and drops the
+//! oldest once full. Lines are kept as shown, after interpolation; lines skipped because their
+//! condition failed never reach a target and are not kept. With the `save` feature it can be
+//! exported within a size budget and imported again, see `MortarDialogueHistory::export`.
+//!
+//! 对话回顾记录：主对话在文本目标上显示过的每一行以及其中确认过的每个选项，按从旧到新排列，用于
+//! "往期对话"界面。[`MortarDialogueHistory`] 为每一行、`parallel_text` 行的每个声部以及每个确认的
+//! 选项各保留一条记录，写满后丢弃最旧的记录。行按显示时的样子（插值之后）保存；因条件不成立而被
+//! 跳过的行不会到达文本目标，也不会被保存。启用 `save` 功能后，它可以在大小预算内导出并再次导入，
+//! 参见 `MortarDialogueHistory::export`。
+
+use bevy::prelude::*;
+use std::collections::VecDeque;
+
+use super::{MortarDialogueText, MortarTextTarget};
+use crate::{LineCursor, MortarChoiceResolved, MortarRuntime};
+
+/// Entries kept by a [`MortarDialogueHistory`] unless configured otherwise.
+///
+/// [`MortarDialogueHistory`] 默认保留的记录数量。
+pub const DEFAULT_DIALOGUE_HISTORY_CAPACITY: usize = 512;
+
+/// One line shown by the dialogue, or one option confirmed in it.
+///
+/// 对话显示过的一行，或其中确认过的一个选项。
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MortarDialogueHistoryEntry {
+    pub mortar_path: String,
+    pub node: String,
+    /// Stable id of the line, see [`crate::TextData::line_id`].
+    ///
+    /// 该行的稳定标识符，参见 [`crate::TextData::line_id`]。
+    pub line_id: String,
+    pub header: String,
+    /// The line as shown. `None` for an entry imported without its body whose line could not be
+    /// found in the loaded files.
+    ///
+    /// 显示时的行文本。对于导入时不含正文、且在已加载文件中找不到对应行的记录为 `None`。
+    pub body: Option<String>,
+    /// Declared index of the confirmed option when the entry records a choice, whose text is then
+    /// the body; `None` for a line.
+    ///
+    /// 记录选项时为被确认选项的声明索引，此时正文为该选项的文本；行记录为 `None`。
+    pub choice: Option<usize>,
+}
+
+impl MortarDialogueHistoryEntry {
+    /// Whether the entry records a confirmed option rather than a line.
+    ///
+    /// 该记录是否为确认的选项而不是行。
+    pub fn is_choice(&self) -> bool {
+        self.choice.is_some()
+    }
+}
+
+/// Lines shown and options confirmed by the primary dialogue, oldest first.
+///
+/// 主对话显示过的行与确认过的选项，从旧到新排列。
+#[derive(Resource, Debug)]
+pub struct MortarDialogueHistory {
+    /// On by default; a line or a confirmed option costs one entry.
+    ///
+    /// 默认开启；每一行或每个确认的选项占用一条记录。
+    pub enabled: bool,
+    capacity: usize,
+    pub(crate) entries: VecDeque<MortarDialogueHistoryEntry>,
+}
+
+impl Default for MortarDialogueHistory {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            capacity: DEFAULT_DIALOGUE_HISTORY_CAPACITY,
+            entries: VecDeque::new(),
+        }
+    }
+}
+
+impl MortarDialogueHistory {
+    /// A history keeping the last `capacity` lines.
+    ///
+    /// 保留最近 `capacity` 行的历史。
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            ..Self::default()
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Changes the capacity, dropping the oldest entries that no longer fit.
+    ///
+    /// 修改容量，并丢弃放不下的最旧记录。
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity.max(1);
+        self.trim();
+    }
+
+    /// Entries from oldest to newest.
+    ///
+    /// 从旧到新的记录。
+    pub fn entries(&self) -> &VecDeque<MortarDialogueHistoryEntry> {
+        &self.entries
+    }
+
+    /// Appends an entry, dropping the oldest one when full.
+    ///
+    /// 追加一条记录，写满时丢弃最旧的记录。
+    pub fn push(&mut self, entry: MortarDialogueHistoryEntry) {
+        self.entries.push_back(entry);
+        self.trim();
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    pub(crate) fn trim(&mut self) {
+        let excess = self.entries.len().saturating_sub(self.capacity);
+        self.entries.drain(..excess);
+    }
+}
+
+/// Appends the options the primary dialogue confirmed this frame, then the lines text targets
+/// started showing. Targets showing the same line add it once; the voices of a `parallel_text`
+/// line are added once each.
+pub(super) fn record_dialogue_history(
+    mut history: ResMut<MortarDialogueHistory>,
+    runtime: Res<MortarRuntime>,
+    targets: Query<&MortarDialogueText, (With<MortarTextTarget>, Changed<MortarDialogueText>)>,
+    mut confirmed: MessageReader<MortarChoiceResolved>,
+    mut recorded: Local<(LineCursor, Vec<Option<String>>)>,
+) {
+    let primary = runtime.primary_dialogue;
+    let choices: Vec<MortarDialogueHistoryEntry> = confirmed
+        .read()
+        .filter(|resolved| Some(resolved.entity.unwrap_or(Entity::PLACEHOLDER)) == primary)
+        .map(|resolved| MortarDialogueHistoryEntry {
+            mortar_path: resolved.mortar_path.clone(),
+            node: resolved.node.clone(),
+            body: Some(resolved.outcome.text.clone()),
+            choice: Some(resolved.outcome.index),
+            ..default()
+        })
+        .collect();
+    if !history.enabled {
+        return;
+    }
+    for entry in choices {
+        history.push(entry);
+    }
+    let Some(state) = runtime.primary_dialogue_state() else {
+        return;
+    };
+    let (cursor, voices) = &mut *recorded;
+    for text in &targets {
+        if text.body.is_empty() || text.cursor != state.cursor() {
+            continue;
+        }
+        if text.cursor != *cursor {
+            *cursor = text.cursor;
+            voices.clear();
+        }
+        if voices.contains(&text.voice) {
+            continue;
+        }
+        voices.push(text.voice.clone());
+        history.push(MortarDialogueHistoryEntry {
+            mortar_path: state.mortar_path.clone(),
+            node: state.current_node.clone(),
+            line_id: text.line_id.clone(),
+            header: text.header.clone(),
+            body: Some(text.body.clone()),
+            choice: None,
+        });
+    }
+}
@@ -0,0 +1,503 @@
+//! # choice_availability.rs
+//!
+//! # choice_availability.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Keeps the enabled/disabled state of on-screen choices in sync with the game. Presented
+//! choices are evaluated when they appear and re-evaluated only when a variable their conditions
+//! reference changes, or periodically when a condition depends on a bound function. Options that
+//! become disabled are recorded on the [`crate::DialogueState`] so selecting or confirming them is
+//! rejected, and a selection that became invalid is cleared. Options removed after a pick or
+//! hidden by `visible_if` are left out of the views, and a group with none left is moved past.
+//! Views follow the options' `ui.order` hints and carry their `ui` objects through for the UI.
+//!
+//! 让屏幕上选项的可用/禁用状态与游戏保持同步。选项出现时求值一次，之后只有在其条件引用的变量
+//! 发生变化，或条件依赖绑定函数而到达定期检查时间时才会重新求值。变为禁用的选项会记录到
+//! [`crate::DialogueState`] 上，从而拒绝对其选择或确认；已失效的选中状态会被清除。被选中后移除
+//! 或被 `visible_if` 隐藏的选项不会出现在视图中，选项全部消失的选项组会被直接跳过。视图遵循选项的
+//! `ui.order` 提示排序，并把它们的 `ui` 对象原样传给 UI。
+
+use bevy::asset::Assets;
+use bevy::prelude::*;
+use std::time::Duration;
+
+use crate::binder::CallContextGuard;
+use crate::debug::LOG_DIALOGUE;
+use crate::dialogue_state::{
+    option_details, page_count, page_usable, ui_hints, visible_if, visible_page,
+};
+use crate::runtime::SelectionTransition;
+use crate::{
+    MortarAsset, MortarCapabilities, MortarExperimentTag, MortarRegistry, MortarRuntime,
+    MortarUiHints, MortarVariableState, MortarVariableValue, evaluate_choice_condition_cached,
+};
+
+use super::experiments::ExperimentParams;
+use super::{MortarConditionCache, MortarDialogueVariables};
+
+/// Display state of one presented choice.
+///
+/// 单个已呈现选项的显示状态。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MortarChoiceView {
+    /// Declared index of the option, the one `SelectChoice` takes. For a navigation entry, the
+    /// first option of the page it leads to.
+    ///
+    /// 选项的声明索引，即 `SelectChoice` 使用的索引。对导航条目而言，是其所指向页面的第一个选项。
+    pub index: usize,
+    pub text: String,
+    pub enabled: bool,
+    pub kind: MortarChoiceViewKind,
+    /// The option's `id`, if it declares one.
+    ///
+    /// 选项的 `id`（若有声明）。
+    pub id: Option<String>,
+    /// The option's `icon`, for options drawn as an icon rather than text.
+    ///
+    /// 选项的 `icon`，用于以图标而非文本绘制的选项。
+    pub icon: Option<String>,
+    /// Other keys the option declares, such as a tooltip or a color.
+    ///
+    /// 选项声明的其他键，例如提示文本或颜色。
+    pub metadata: serde_json::Map<String, serde_json::Value>,
+    /// Experiment variant the option belongs to, see [`crate::MortarExperiments`].
+    ///
+    /// 该选项所属的实验变体，参见 [`crate::MortarExperiments`]。
+    pub experiment: Option<crate::MortarExperimentTag>,
+    /// The option's `ui` hints, verbatim; see [`MortarChoiceView::ui_hints`].
+    ///
+    /// 选项的 `ui` 提示（原样保留）；参见 [`MortarChoiceView::ui_hints`]。
+    pub ui: serde_json::Map<String, serde_json::Value>,
+}
+
+impl MortarChoiceView {
+    /// Typed access to [`MortarChoiceView::ui`].
+    ///
+    /// 对 [`MortarChoiceView::ui`] 的类型化访问。
+    pub fn ui_hints(&self) -> MortarUiHints<'_> {
+        MortarUiHints(&self.ui)
+    }
+}
+
+/// What a presented entry stands for.
+///
+/// 呈现条目所代表的内容。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MortarChoiceViewKind {
+    /// A declared option.
+    ///
+    /// 声明的选项。
+    #[default]
+    Choice,
+    /// Goes to the previous page; send `MortarCommand::ChoicePage { delta: -1 }`.
+    ///
+    /// 前往上一页；发送 `MortarCommand::ChoicePage { delta: -1 }`。
+    PrevPage,
+    /// Goes to the next page; send `MortarCommand::ChoicePage { delta: 1 }`.
+    ///
+    /// 前往下一页；发送 `MortarCommand::ChoicePage { delta: 1 }`。
+    NextPage,
+}
+
+/// Choices currently presented by the primary dialogue, with their enablement.
+///
+/// 主对话当前呈现的选项及其可用状态。
+#[derive(Resource, Debug, Clone, Default, PartialEq, Eq)]
+pub struct MortarChoicesPresented {
+    /// Dialogue entity the choices belong to.
+    ///
+    /// 选项所属的对话实体。
+    pub dialogue: Option<Entity>,
+    /// Options of the current page, with navigation entries around them when paginated.
+    ///
+    /// 当前页的选项；分页时前后带有导航条目。
+    pub views: Vec<MortarChoiceView>,
+    /// Shown page, from 0.
+    ///
+    /// 当前显示的页码，从 0 开始。
+    pub page: usize,
+    /// Number of pages; 1 without pagination.
+    ///
+    /// 总页数；未分页时为 1。
+    pub page_count: usize,
+    /// Token of the presented choice group; echo it in `SelectChoice` and `ConfirmChoice` so
+    /// input meant for this group is dropped once another group replaces it.
+    ///
+    /// 当前呈现的选项组的令牌；在 `SelectChoice` 与 `ConfirmChoice` 中回传它，使针对此组的输入在
+    /// 其他组取代它后被丢弃。
+    pub group_token: u64,
+    /// The group's `ui` hints, verbatim; see [`MortarChoicesPresented::group_ui_hints`].
+    ///
+    /// 选项组的 `ui` 提示（原样保留）；参见 [`MortarChoicesPresented::group_ui_hints`]。
+    pub group_ui: serde_json::Map<String, serde_json::Value>,
+}
+
+impl MortarChoicesPresented {
+    /// Typed access to [`MortarChoicesPresented::group_ui`].
+    ///
+    /// 对 [`MortarChoicesPresented::group_ui`] 的类型化访问。
+    pub fn group_ui_hints(&self) -> MortarUiHints<'_> {
+        MortarUiHints(&self.group_ui)
+    }
+}
+
+/// How often choices whose conditions call bound functions are re-checked.
+/// Variable-based conditions are re-checked only when a referenced variable changes.
+///
+/// 条件调用绑定函数的选项的重新检查间隔。基于变量的条件只会在所引用的变量变化时重新检查。
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MortarChoiceReevaluation {
+    /// `None` disables the periodic re-check.
+    ///
+    /// 为 `None` 时关闭定期检查。
+    pub function_interval: Option<Duration>,
+}
+
+impl Default for MortarChoiceReevaluation {
+    fn default() -> Self {
+        Self {
+            function_interval: Some(Duration::from_millis(250)),
+        }
+    }
+}
+
+/// Emitted when the selected choice is cleared because it became disabled.
+///
+/// 选中的选项因变为禁用而被清除时发出。
+#[derive(Message, Debug, Clone, PartialEq, Eq)]
+pub struct MortarChoiceDeselected {
+    pub entity: Option<Entity>,
+    pub index: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ChoiceKey {
+    dialogue: Entity,
+    path: String,
+    node: String,
+    choice_stack: Vec<usize>,
+    group_token: u64,
+    page: usize,
+    page_size: Option<usize>,
+}
+
+/// What the presented choices depend on, used to skip needless re-evaluation.
+#[derive(Default)]
+pub(super) struct ChoiceTracking {
+    key: Option<ChoiceKey>,
+    watched: Vec<(String, Option<MortarVariableValue>)>,
+    revision: u64,
+    uses_functions: bool,
+    since_function_check: Duration,
+}
+
+impl ChoiceTracking {
+    fn watched_changed(&self, variables: &MortarVariableState) -> bool {
+        self.watched
+            .iter()
+            .any(|(name, value)| variables.get(name) != value.as_ref())
+    }
+
+    fn watch(&mut self, condition: &mortar_compiler::Condition, variables: &MortarVariableState) {
+        let tokens = condition
+            .condition_type
+            .split_whitespace()
+            .chain(condition.args.iter().map(String::as_str));
+        for (position, token) in tokens.enumerate() {
+            let value = variables.get(token).cloned();
+            if position == 0 && value.is_none() {
+                self.uses_functions = true;
+            }
+            if (position == 0 || value.is_some())
+                && !self.watched.iter().any(|(name, _)| name == token)
+            {
+                self.watched.push((token.to_string(), value));
+            }
+        }
+    }
+}
+
+/// Views of every declared option, and the indices of options that are removed or hidden by
+/// their `visible_if`.
+fn evaluate_views(
+    runtime: &MortarRuntime,
+    (choices, option_values): (&[mortar_compiler::Choice], &[serde_json::Value]),
+    function_decls: &[mortar_compiler::Function],
+    variables: &MortarVariableState,
+    cache: &MortarConditionCache,
+    tracking: &mut ChoiceTracking,
+    mut is_removed: impl FnMut(usize) -> bool,
+) -> (Vec<MortarChoiceView>, Vec<usize>) {
+    tracking.watched.clear();
+    tracking.uses_functions = false;
+    tracking.since_function_check = Duration::ZERO;
+    tracking.revision = variables.revision();
+
+    let mut hidden = Vec::new();
+    let mut check = |condition: &mortar_compiler::Condition| {
+        tracking.watch(condition, variables);
+        evaluate_choice_condition_cached(
+            condition,
+            &runtime.functions,
+            function_decls,
+            variables,
+            cache,
+        )
+    };
+    let views = choices
+        .iter()
+        .enumerate()
+        .map(|(index, choice)| {
+            let option = option_values.get(index);
+            let visible = !is_removed(index)
+                && option
+                    .and_then(visible_if)
+                    .is_none_or(|condition| check(&condition));
+            if !visible {
+                hidden.push(index);
+            }
+            let enabled = choice.condition.as_ref().is_none_or(&mut check);
+            verbose_trace!(
+                Choices,
+                "Option {} {:?}: visible {}, enabled {}",
+                index,
+                choice.text,
+                visible,
+                enabled
+            );
+            let (id, icon, metadata) = option.map(option_details).unwrap_or_default();
+            MortarChoiceView {
+                index,
+                text: choice.text.clone(),
+                enabled,
+                kind: MortarChoiceViewKind::Choice,
+                id,
+                icon,
+                metadata,
+                experiment: option.and_then(MortarExperimentTag::from_item),
+                ui: option.map(ui_hints).unwrap_or_default(),
+            }
+        })
+        .collect();
+    (views, hidden)
+}
+
+/// Keeps the views of the shown page and adds navigation entries to the neighbouring pages.
+/// `views` are in presentation order, and pages are runs of their positions.
+fn paginate(
+    views: Vec<MortarChoiceView>,
+    requested: usize,
+    page_size: usize,
+    disabled: &[usize],
+    dialogue: Entity,
+) -> MortarChoicesPresented {
+    let len = views.len();
+    let indices: Vec<usize> = views.iter().map(|view| view.index).collect();
+    let is_disabled = |position: usize| disabled.contains(&indices[position]);
+    let page = visible_page(requested, len, page_size, is_disabled);
+    let page_count = page_count(len, page_size);
+    let usable = |page: &usize| page_usable(*page, len, page_size, is_disabled);
+    let prev = (0..page).rev().find(usable);
+    let next = (page + 1..page_count).find(usable);
+    let navigation = |page: usize, kind| MortarChoiceView {
+        index: indices[page * page_size],
+        text: String::new(),
+        enabled: true,
+        kind,
+        id: None,
+        icon: None,
+        metadata: serde_json::Map::new(),
+        experiment: None,
+        ui: serde_json::Map::new(),
+    };
+
+    let mut shown: Vec<_> = prev
+        .map(|prev| navigation(prev, MortarChoiceViewKind::PrevPage))
+        .into_iter()
+        .collect();
+    shown.extend(views.into_iter().skip(page * page_size).take(page_size));
+    shown.extend(next.map(|next| navigation(next, MortarChoiceViewKind::NextPage)));
+    MortarChoicesPresented {
+        dialogue: Some(dialogue),
+        views: shown,
+        page,
+        page_count,
+        group_token: 0,
+        group_ui: serde_json::Map::new(),
+    }
+}
+
+/// Moves past a group whose options are all removed or hidden, the way a `break` option does.
+fn fall_through(runtime: &mut MortarRuntime, dialogue: Entity) {
+    let Some(state) = runtime.get_dialogue_mut(dialogue) else {
+        return;
+    };
+    dev_info!(target: LOG_DIALOGUE, "Every option of the choice group is gone, continuing");
+    state.break_choices();
+    state.next_text();
+}
+
+/// Re-evaluates presented choices when something they depend on changed.
+///
+/// 当已呈现选项的依赖发生变化时重新求值。
+pub(super) fn refresh_presented_choices(
+    time: Res<Time>,
+    settings: Res<MortarChoiceReevaluation>,
+    mut runtime: ResMut<MortarRuntime>,
+    registry: Res<MortarRegistry>,
+    assets: Res<Assets<MortarAsset>>,
+    variables: Res<MortarDialogueVariables>,
+    mut presented: ResMut<MortarChoicesPresented>,
+    mut deselected: MessageWriter<MortarChoiceDeselected>,
+    mut tracking: Local<ChoiceTracking>,
+    mut experiments: ExperimentParams,
+    capabilities: Res<MortarCapabilities>,
+    condition_cache: Res<MortarConditionCache>,
+) {
+    let empty = MortarVariableState::new();
+    let variable_state = variables.state.as_ref().unwrap_or(&empty);
+
+    let views = {
+        let presenting = runtime.primary_dialogue.and_then(|dialogue| {
+            let state = runtime.active_dialogues.get(&dialogue)?;
+            let choices = state.get_choices()?;
+            state
+                .choices_presentable()
+                .then_some((dialogue, state, choices))
+        });
+        let Some((dialogue, state, choices)) = presenting else {
+            if tracking.key.take().is_some() {
+                *presented = MortarChoicesPresented::default();
+            }
+            return;
+        };
+
+        let key = ChoiceKey {
+            dialogue,
+            path: state.mortar_path.clone(),
+            node: state.current_node.clone(),
+            choice_stack: state.choice_stack.clone(),
+            group_token: state.group_token(),
+            page: state.choice_page,
+            page_size: state.choice_page_size(runtime.choice_pagination),
+        };
+        tracking.since_function_check += time.delta();
+        let rebuild = tracking.key.as_ref() != Some(&key)
+            || experiments.changed()
+            || capabilities.is_changed();
+        let variables_changed = tracking.revision != variable_state.revision()
+            && tracking.watched_changed(variable_state);
+        let functions_due = tracking.uses_functions
+            && settings
+                .function_interval
+                .is_some_and(|interval| tracking.since_function_check >= interval);
+        tracking.revision = variable_state.revision();
+        if !(rebuild || variables_changed || functions_due) {
+            return;
+        }
+
+        let function_decls = registry
+            .get(&state.mortar_path)
+            .and_then(|handle| assets.get(handle))
+            .map(|asset| asset.data.functions.as_slice())
+            .unwrap_or(&[]);
+        tracking.key = Some(key);
+        let _context = CallContextGuard::enter(runtime.call_context());
+        let option_values = state.current_option_values().map_or(&[][..], Vec::as_slice);
+        let views = evaluate_views(
+            &runtime,
+            (choices, option_values),
+            function_decls,
+            variable_state,
+            &condition_cache,
+            &mut tracking,
+            |index| {
+                let option = option_values.get(index);
+                let tag = option.and_then(MortarExperimentTag::from_item);
+                state.choice_removed(index, &runtime.removed_choices)
+                    || experiments.hidden_reason(tag.as_ref()).is_some()
+                    || option.is_some_and(|option| !runtime.capabilities().allows_item(option))
+            },
+        );
+        (views, state.choice_group_ui())
+    };
+    let ((mut views, hidden), group_ui) = views;
+    // Stable, so options without an `order` hint keep their declared order, and before
+    // pagination, which pages through presented positions.
+    //
+    // 稳定排序，使未设置 `order` 提示的选项保持声明顺序；并且发生在分页之前，分页按呈现位置进行。
+    views.sort_by_key(|view| view.ui_hints().order().unwrap_or(0));
+
+    let Some(key) = tracking.key.as_ref() else {
+        return;
+    };
+    let dialogue = key.dialogue;
+    if hidden.len() == views.len() && !views.is_empty() {
+        fall_through(&mut runtime, dialogue);
+        tracking.key = None;
+        *presented = MortarChoicesPresented::default();
+        return;
+    }
+    // Hidden options count as disabled, so they cannot be selected and pages holding only
+    // hidden options are skipped; they are then dropped from what the UI sees.
+    //
+    // 隐藏的选项按禁用处理，因此无法被选中，只含隐藏选项的页面也会被跳过；随后再从 UI 所见的
+    // 列表中移除。
+    let disabled: Vec<usize> = views
+        .iter()
+        .filter(|view| !view.enabled || hidden.contains(&view.index))
+        .map(|view| view.index)
+        .collect();
+    let mut next = match key.page_size {
+        Some(page_size) => paginate(views, key.page, page_size, &disabled, dialogue),
+        None => MortarChoicesPresented {
+            dialogue: Some(dialogue),
+            views,
+            page: 0,
+            page_count: 1,
+            group_token: 0,
+            group_ui: serde_json::Map::new(),
+        },
+    };
+    next.views
+        .retain(|view| view.kind != MortarChoiceViewKind::Choice || !hidden.contains(&view.index));
+    let next = MortarChoicesPresented {
+        group_token: key.group_token,
+        group_ui,
+        ..next
+    };
+    if *presented != next {
+        *presented = next;
+    }
+
+    let Some(state) = runtime.active_dialogues.get(&dialogue) else {
+        return;
+    };
+    let selection_lost = state
+        .selected_choice
+        .filter(|index| disabled.contains(index))
+        .filter(|_| {
+            !runtime
+                .selection_persistence
+                .keeps(SelectionTransition::Rebuild)
+        });
+    if state.disabled_choices == disabled && selection_lost.is_none() {
+        return;
+    }
+
+    let Some(state) = runtime.get_dialogue_mut(dialogue) else {
+        return;
+    };
+    state.disabled_choices = disabled;
+    if let Some(index) = selection_lost {
+        dev_info!(target: LOG_DIALOGUE, "Selected choice {} became disabled, clearing it", index);
+        state.selected_choice = None;
+        deselected.write(MortarChoiceDeselected {
+            entity: (dialogue != Entity::PLACEHOLDER).then_some(dialogue),
+            index,
+        });
+    }
+}
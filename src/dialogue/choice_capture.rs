@@ -0,0 +1,55 @@
+//! # choice_capture.rs
+//!
+//! # choice_capture.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Writes captured choices into the dialogue variables. It runs after the runtime handles this
+//! frame's confirms and before any text renders, so the line that follows a choice, even in the
+//! node it jumps to, already interpolates the pick. Each write bumps the variable revision like
+//! any other assignment, so conditions watching the variable are re-checked.
+//!
+//! 将捕获的选项写入对话变量。它在运行时处理完本帧的确认之后、任何文本显示之前运行，因此选项之后的
+//! 那一行（即使位于跳转到的节点中）已经能插值出所选内容。每次写入都像其他赋值一样递增变量修订号，
+//! 因此依赖该变量的条件会被重新检查。
+
+use bevy::prelude::*;
+
+use super::MortarDialogueVariables;
+use crate::{MortarChoiceCaptured, MortarVariableValue};
+
+pub(super) fn apply_choice_captures(
+    mut captured: MessageReader<MortarChoiceCaptured>,
+    mut variables: ResMut<MortarDialogueVariables>,
+) {
+    for capture in captured.read() {
+        let values = capture_writes(&capture.variable, &capture.value, capture.index);
+        // Before the first line initializes the variables, keep the values for then.
+        //
+        // 在第一行初始化变量之前，先保留这些值待初始化时写入。
+        match variables.state.as_mut() {
+            Some(state) => values
+                .into_iter()
+                .for_each(|(name, value)| state.set(&name, value)),
+            None => variables.restored.extend(values),
+        }
+    }
+}
+
+/// The variables a capture of option `index` writes: the captured value, and the index as
+/// `{variable}_index`.
+pub(crate) fn capture_writes(
+    variable: &str,
+    value: &MortarVariableValue,
+    index: usize,
+) -> [(String, MortarVariableValue); 2] {
+    [
+        (variable.to_owned(), value.clone()),
+        (
+            format!("{variable}_index"),
+            MortarVariableValue::Number(index as f64),
+        ),
+    ]
+}
@@ -0,0 +1,188 @@
+//! # components.rs
+//!
+//! # components.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! The components a game puts on its dialogue text entities, and the gameplay message that
+//! Mortar events and timelines send. Text targets receive the rendered line as
+//! [`MortarDialogueText`] and expose their reveal progress through [`MortarEventBinding`].
+//!
+//! 游戏挂在对话文本实体上的组件，以及 Mortar 事件与时间线发出的游戏消息。文本目标以
+//! [`MortarDialogueText`] 接收渲染后的行，并通过 [`MortarEventBinding`] 暴露其显示进度。
+
+use bevy::prelude::*;
+
+use super::InlineIcon;
+
+/// Marker for `Text` entities that should display Mortar dialogue output.
+///
+/// 标记需要显示 Mortar 对话文本的 UI 实体。
+#[derive(Component)]
+pub struct MortarTextTarget;
+
+/// Routes the voice of a `parallel_text` item whose `target` or `speaker` matches this name to
+/// the text target. Targets without a channel show all voices of such a line, one per row, and
+/// leave their events to the routed targets; other lines reach every target as before.
+///
+/// 将 `target` 或 `speaker` 与该名称相同的 `parallel_text` 声部路由到此文本目标。没有通道的目标会
+/// 逐行显示此类行的全部声部，并把事件交给被路由的目标处理；其他行仍照常发送到所有目标。
+#[derive(Component, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MortarTextChannel(pub String);
+
+/// Stores the current Mortar dialogue text so users can bind custom render effects.
+///
+/// 存储当前 Mortar 对话文本，便于绑定自定义渲染效果。
+#[derive(Component, Debug, Clone, Default, PartialEq)]
+pub struct MortarDialogueText {
+    /// Prefix header string (`[file / node]`).
+    ///
+    /// 前缀头字符串（`[文件 / 节点]`）。
+    pub header: String,
+    /// Body text processed from Mortar.
+    ///
+    /// Mortar 处理后的正文文本。
+    pub body: String,
+    /// Stable id of the displayed line ([`crate::TextData::line_id`]); a line group uses the id
+    /// of its first line.
+    ///
+    /// 当前显示行的稳定标识符（[`crate::TextData::line_id`]）；line 组使用其第一行的标识符。
+    pub line_id: String,
+    /// Position the line was rendered at; compare it to tell whether the line changed, see
+    /// [`crate::LineCursor`].
+    ///
+    /// 渲染该行时的位置；判断行是否变化时比较它，参见 [`crate::LineCursor`]。
+    pub cursor: crate::LineCursor,
+    /// Inline icons of the body, in order; each token was replaced by one placeholder char.
+    ///
+    /// 正文中的行内图标，按顺序排列；每个标记已被替换为一个占位字符。
+    pub icons: Vec<InlineIcon>,
+    /// Char indices where the reveal pauses until the next advance input, sorted; see
+    /// [`crate::PAUSE_TOKEN`].
+    ///
+    /// 逐字显示暂停并等待下一次推进输入的字符索引，已排序；参见 [`crate::PAUSE_TOKEN`]。
+    pub checkpoints: Vec<usize>,
+    /// The body as a screen reader should speak it; filled only while a
+    /// [`crate::MortarIconSpeechMap`] or [`crate::MortarSpeechFormat`] exists.
+    ///
+    /// 屏幕阅读器应朗读的正文；仅在存在 [`crate::MortarIconSpeechMap`] 或 [`crate::MortarSpeechFormat`] 时填写。
+    pub speakable: String,
+    /// Channel of the `parallel_text` voice shown, see [`MortarTextChannel`]; `None` otherwise.
+    ///
+    /// 所显示的 `parallel_text` 声部的通道，参见 [`MortarTextChannel`]；其他情况为 `None`。
+    pub voice: Option<String>,
+}
+
+impl MortarDialogueText {
+    /// Returns the concatenated header + body string.
+    ///
+    /// 返回拼接后的头部与正文文本。
+    pub fn full_text(&self) -> String {
+        format!("{}{}", self.header, self.body)
+    }
+}
+
+/// Written each time the primary dialogue shows a different line, whether or not any
+/// [`MortarTextTarget`] exists, for renderers that draw the line themselves: terminal panes,
+/// world-space text or a dialogue log. `header` and `body` are those an unchannelled target gets
+/// as [`MortarDialogueText`], so `header + body` is the shown text.
+///
+/// 主对话每次显示不同的行时写入，无论是否存在 [`MortarTextTarget`]，供自行绘制该行的渲染器使用：
+/// 终端面板、世界空间文本或对话记录。`header` 与 `body` 与没有通道的目标以 [`MortarDialogueText`]
+/// 得到的相同，因此 `header + body` 即显示的文本。
+#[derive(Message, Debug, Clone)]
+pub struct MortarTextChanged {
+    /// The primary dialogue showing the line.
+    ///
+    /// 显示该行的主对话。
+    pub dialogue: Option<Entity>,
+    pub mortar_path: String,
+    pub node: String,
+    pub text_index: usize,
+    pub header: String,
+    /// The interpolated body.
+    ///
+    /// 插值后的正文。
+    pub body: String,
+    /// Events of the line, indexed by char of `body`; a target would track them with a
+    /// [`crate::MortarEventTracker`].
+    ///
+    /// 该行的事件，按 `body` 的字符索引；目标会用 [`crate::MortarEventTracker`] 跟踪它们。
+    pub events: Vec<mortar_compiler::Event>,
+}
+
+/// Component that exposes the current playback index for Mortar events.
+///
+/// 用户可以将 `current_index` 绑定到任意系统（打字机、
+/// 音频时间线等），由 [`crate::MortarDialoguePlugin`] 自动触发事件。
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct MortarEventBinding {
+    /// Progress index used by [`crate::MortarEventTracker`].
+    ///
+    /// [`crate::MortarEventTracker`] 使用的进度索引。
+    pub current_index: f32,
+}
+
+/// Event emitted whenever Mortar timelines or text events ask the game to do something.
+///
+/// 由 Mortar 文本事件或时间线触发的游戏事件。
+#[derive(Message, Debug, Clone)]
+pub struct MortarGameEvent {
+    /// The logical source entity (usually the dialogue text). `None` for timeline-only events.
+    ///
+    /// 逻辑来源实体（通常是对话文本）；仅时间线事件时为 `None`。
+    pub source: Option<Entity>,
+    /// Event name defined inside Mortar (e.g. "set_animation").
+    ///
+    /// Mortar 中定义的事件名称（如 "set_animation"）。
+    pub name: String,
+    /// Raw argument list from Mortar.
+    ///
+    /// 来自 Mortar 的原始参数列表。
+    pub args: Vec<String>,
+    /// Entities named by `@name` arguments, in argument order; names that resolve to nothing
+    /// are left out. Handlers acting on someone should prefer these over `source`.
+    ///
+    /// 由 `@name` 参数指定的实体，按参数顺序排列；无法解析的名称会被略去。作用于某个对象的处理器
+    /// 应优先使用这些实体而非 `source`。
+    pub targets: Vec<Entity>,
+}
+
+impl MortarGameEvent {
+    /// An event with no targets, for games that write their own.
+    ///
+    /// 不带目标的事件，供游戏自行写入事件时使用。
+    pub fn new(source: Option<Entity>, name: impl Into<String>, args: Vec<String>) -> Self {
+        Self {
+            source,
+            name: name.into(),
+            args,
+            targets: Vec::new(),
+        }
+    }
+}
+
+/// Emitted when a line is shown, for analytics and other observers of dialogue progress.
+///
+/// 在显示一行时发出，供分析统计等关注对话进度的观察者使用。
+#[derive(Message, Debug, Clone, PartialEq)]
+pub struct MortarTextAdvanced {
+    /// The primary dialogue showing the line.
+    ///
+    /// 显示该行的主对话。
+    pub dialogue: Option<Entity>,
+    pub mortar_path: String,
+    pub node: String,
+    pub text_index: usize,
+    pub line_id: String,
+    /// Ids of the voices a `parallel_text` line shows, in order; empty for other lines.
+    ///
+    /// `parallel_text` 行所显示声部的标识符，按顺序排列；其他行为空。
+    pub line_ids: Vec<String>,
+    /// Experiment variant the line belongs to, see [`crate::MortarExperiments`].
+    ///
+    /// 该行所属的实验变体，参见 [`crate::MortarExperiments`]。
+    pub experiment: Option<crate::MortarExperimentTag>,
+}
@@ -10,10 +10,103 @@
 //! exclusive within one pass. It remembers the last serialized condition result so the negated or
 //! repeated branch can reuse that decision instead of re-evaluating independently.
 //!
+//! It also holds [`MortarConditionCache`], which memoizes the function calls of conditions for
+//! one frame. Line resolution, the choice list and its re-evaluation each check conditions, so a
+//! `has_backpack()` shared by a line and a choice ran several times a frame, which shows when the
+//! function is slow or counts its calls. Through [`crate::evaluate_if_condition_cached`] and
+//! [`crate::evaluate_choice_condition_cached`], a call runs once per frame for the same function,
+//! arguments, file and node, variable revision and registry generation; the plugin clears the
+//! cache in [`First`]. Functions marked with [`MortarFunctionRegistry::set_volatile`] always run.
+//!
 //! 提供了一个很小的缓存，用来保证同一轮 Mortar `if` / `else` 求值的互斥性。它会
 //! 记住最近一次序列化条件的结果，让取反或重复分支复用这次判断，而不是各自重新求值。
+//!
+//! 这里还有 [`MortarConditionCache`]，它在一帧内缓存条件中的函数调用结果。行解析、选项列表及其
+//! 重新求值都会检查条件，因此行与选项共用的 `has_backpack()` 过去每帧会执行多次，当函数较慢或会统计
+//! 调用次数时尤为明显。通过 [`crate::evaluate_if_condition_cached`] 与
+//! [`crate::evaluate_choice_condition_cached`]，相同的函数、参数、文件与节点、变量修订号和注册表
+//! 代数下，每帧只调用一次；插件在 [`First`] 中清空缓存。用
+//! [`MortarFunctionRegistry::set_volatile`] 标记的函数总会执行。
+
+use bevy::prelude::*;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::debug::LOG_EVAL;
+use crate::{MortarFunctionRegistry, MortarValue, MortarVariableState, evaluate_if_condition};
+
+/// Results of condition function calls made this frame, keyed by a hash of the call and the
+/// state it saw. Lookups take `&self`, so systems share it as a [`Res`].
+///
+/// 本帧内条件函数调用的结果，以调用及其所见状态的哈希为键。查询只需 `&self`，因此各系统以 [`Res`]
+/// 共享它。
+#[derive(Resource, Debug)]
+pub struct MortarConditionCache {
+    enabled: bool,
+    results: Mutex<HashMap<u64, MortarValue>>,
+}
+
+impl Default for MortarConditionCache {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            results: Mutex::default(),
+        }
+    }
+}
 
-use crate::{MortarFunctionRegistry, MortarVariableState, evaluate_if_condition};
+impl MortarConditionCache {
+    /// Turns memoization on or off; when off every condition calls its function. On by default.
+    ///
+    /// 开启或关闭缓存；关闭时每个条件都会调用其函数。默认开启。
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        self.clear();
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Forgets every result.
+    ///
+    /// 清除所有结果。
+    pub fn clear(&mut self) {
+        if let Ok(results) = self.results.get_mut() {
+            results.clear();
+        }
+    }
+
+    /// The result stored for `key`, or that of `call`, stored unless it is `None`. The lock is
+    /// not held while `call` runs, so the function may check conditions itself.
+    pub(crate) fn memoize(
+        &self,
+        key: u64,
+        call: impl FnOnce() -> Option<MortarValue>,
+    ) -> Option<MortarValue> {
+        if !self.enabled {
+            return call();
+        }
+        let cached = self
+            .results
+            .lock()
+            .ok()
+            .and_then(|results| results.get(&key).cloned());
+        if cached.is_some() {
+            return cached;
+        }
+        let value = call()?;
+        if let Ok(mut results) = self.results.lock() {
+            results.insert(key, value.clone());
+        }
+        Some(value)
+    }
+}
+
+/// Starts each frame with an empty [`MortarConditionCache`].
+pub(super) fn clear_condition_cache(mut cache: ResMut<MortarConditionCache>) {
+    cache.clear();
+}
 
 /// Cached result of a condition evaluation, used to ensure if/else mutual exclusivity.
 ///
@@ -64,6 +157,7 @@ fn try_cache_negated(
     }
     let result = !cache.result;
     dev_info!(
+        target: LOG_EVAL,
         "Condition cache hit (negated): cached={} → result={}",
         cache.result,
         result
@@ -80,6 +174,6 @@ fn try_cache_same(
     if cond_json != cache.json {
         return None;
     }
-    dev_info!("Condition cache hit (same): result={}", cache.result);
+    dev_info!(target: LOG_EVAL, "Condition cache hit (same): result={}", cache.result);
     Some(cache.result)
 }
@@ -0,0 +1,248 @@
+//! # display_animation.rs
+//!
+//! # display_animation.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Presentational animation of interpolated variables. Games register a [`MortarDisplayPolicy`]
+//! per variable in [`MortarDisplayAnimations`]; when a line interpolates a `{placeholder}` whose
+//! number changed since the previous line rendered, targets carrying [`MortarAnimatedDisplay`]
+//! roll the shown number from its old value to the new one. Only the target's `Text` animates:
+//! the variable, conditions, [`MortarDialogueText`](super::MortarDialogueText) and the line's
+//! events all see the final value from the first frame. A roll lives in [`MortarDisplayRoll`] and
+//! stops when the line changes.
+//!
+//! 插值变量的表现层动画。游戏在 [`MortarDisplayAnimations`] 中为每个变量注册
+//! [`MortarDisplayPolicy`]；当某行插值的 `{占位符}` 对应的数字自上一行渲染以来发生了变化时，带有
+//! [`MortarAnimatedDisplay`] 的目标会把显示的数字从旧值滚动到新值。只有目标的 `Text` 会产生动画：
+//! 变量、条件、[`MortarDialogueText`](super::MortarDialogueText) 以及该行的事件从第一帧起看到的都是
+//! 最终值。滚动过程保存在 [`MortarDisplayRoll`] 中，并在行变化时停止。
+
+use bevy::prelude::*;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::time::Duration;
+
+use super::{MortarIconSettings, icons};
+use crate::{DialogueState, LineCursor, MortarRuntime, MortarVariableState, MortarVariableValue};
+
+/// How a variable is displayed when its value changed since the previous line.
+///
+/// 变量的值自上一行以来发生变化时的显示方式。
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum MortarDisplayPolicy {
+    /// The new value shows at once.
+    ///
+    /// 立即显示新值。
+    #[default]
+    Instant,
+    /// A number rolls from its previous value to the new one over `duration`. Whole numbers
+    /// roll in whole steps.
+    ///
+    /// 数字在 `duration` 内从之前的值滚动到新值。整数以整数步长滚动。
+    NumberRoll {
+        duration: Duration,
+        easing: EaseFunction,
+    },
+}
+
+/// Display policies of interpolated variables, see [`MortarDisplayPolicy`].
+///
+/// 插值变量的显示策略，参见 [`MortarDisplayPolicy`]。
+#[derive(Resource, Debug, Default)]
+pub struct MortarDisplayAnimations {
+    policies: HashMap<String, MortarDisplayPolicy>,
+    /// Value of each rolling variable when the previous line rendered.
+    seen: HashMap<String, f64>,
+}
+
+impl MortarDisplayAnimations {
+    /// Sets how `variable` is displayed.
+    ///
+    /// 设置 `variable` 的显示方式。
+    pub fn set(&mut self, variable: impl Into<String>, policy: MortarDisplayPolicy) -> &mut Self {
+        self.policies.insert(variable.into(), policy);
+        self
+    }
+
+    /// How `variable` is displayed.
+    ///
+    /// `variable` 的显示方式。
+    pub fn policy(&self, variable: &str) -> MortarDisplayPolicy {
+        self.policies.get(variable).copied().unwrap_or_default()
+    }
+
+    /// The rolls of a line that just rendered, given its placeholders with the byte range of
+    /// their text, and notes the values of every rolling variable for the next line.
+    pub(super) fn plan(
+        &mut self,
+        variables: &MortarVariableState,
+        placeholders: &[(String, Range<usize>)],
+    ) -> Vec<RollSpan> {
+        let mut spans = Vec::new();
+        for (part, range) in placeholders {
+            let Some(name) = part
+                .strip_prefix('{')
+                .and_then(|part| part.strip_suffix('}'))
+            else {
+                continue;
+            };
+            let Some(MortarDisplayPolicy::NumberRoll { duration, easing }) =
+                self.policies.get(name).copied()
+            else {
+                continue;
+            };
+            if let (Some(&from), Some(&MortarVariableValue::Number(to))) =
+                (self.seen.get(name), variables.get(name))
+                && from != to
+            {
+                spans.push(RollSpan {
+                    range: range.clone(),
+                    from,
+                    to,
+                    duration,
+                    easing,
+                });
+            }
+        }
+        for name in self.policies.keys() {
+            if let Some(&MortarVariableValue::Number(value)) = variables.get(name) {
+                self.seen.insert(name.clone(), value);
+            }
+        }
+        spans
+    }
+}
+
+/// Opts a text target into the display animations of [`MortarDisplayAnimations`]. Meant for
+/// targets that show the whole line at once: a roll writes the target's `Text` every frame, so
+/// it would fight a typewriter or a reveal.
+///
+/// 让文本目标启用 [`MortarDisplayAnimations`] 中的显示动画。适用于一次显示整行的目标：滚动期间每帧
+/// 都会写入目标的 `Text`，因此会与打字机或逐字显示相冲突。
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct MortarAnimatedDisplay;
+
+/// One number rolling within a line.
+#[derive(Debug, Clone)]
+pub(super) struct RollSpan {
+    /// Bytes of the interpolated line the number occupies.
+    range: Range<usize>,
+    from: f64,
+    to: f64,
+    duration: Duration,
+    easing: EaseFunction,
+}
+
+impl RollSpan {
+    fn display(&self, elapsed: Duration) -> String {
+        let t = if self.duration.is_zero() {
+            1.0
+        } else {
+            (elapsed.as_secs_f64() / self.duration.as_secs_f64()).min(1.0)
+        };
+        let eased = f64::from(self.easing.sample_clamped(t as f32));
+        let value = self.from + (self.to - self.from) * eased;
+        let value = if t >= 1.0 {
+            self.to
+        } else if self.from.fract() == 0.0 && self.to.fract() == 0.0 {
+            value.round()
+        } else {
+            (value * 100.0).round() / 100.0
+        };
+        MortarVariableValue::Number(value).to_display_string()
+    }
+}
+
+/// The animated display of a line on a [`MortarAnimatedDisplay`] target while its numbers roll.
+/// It is removed once they settle or the line changes; the target's `MortarDialogueText` holds
+/// the final values throughout.
+///
+/// [`MortarAnimatedDisplay`] 目标上某行数字滚动期间的动画显示。数字停止滚动或行变化后即被移除；
+/// 目标的 `MortarDialogueText` 始终保存最终值。
+#[derive(Component, Debug, Clone)]
+pub struct MortarDisplayRoll {
+    /// The line as currently shown, header included.
+    ///
+    /// 当前显示的行，包含头部。
+    pub text: String,
+    cursor: LineCursor,
+    header: String,
+    /// The interpolated line with the final values.
+    interpolated: String,
+    spans: Vec<RollSpan>,
+    elapsed: Duration,
+}
+
+impl MortarDisplayRoll {
+    /// A roll of the line at `cursor`, or `None` when no number rolls.
+    pub(super) fn new(
+        cursor: LineCursor,
+        header: &str,
+        interpolated: &str,
+        spans: Vec<RollSpan>,
+    ) -> Option<Self> {
+        (!spans.is_empty()).then(|| Self {
+            text: String::new(),
+            cursor,
+            header: header.to_owned(),
+            interpolated: interpolated.to_owned(),
+            spans,
+            elapsed: Duration::ZERO,
+        })
+    }
+
+    /// Whether every number reached its final value.
+    pub fn settled(&self) -> bool {
+        self.spans.iter().all(|span| self.elapsed >= span.duration)
+    }
+
+    /// The interpolated line with each number at its current value.
+    fn compose(&self) -> String {
+        let mut out = String::with_capacity(self.interpolated.len());
+        let mut at = 0;
+        for span in &self.spans {
+            out.push_str(&self.interpolated[at..span.range.start]);
+            out.push_str(&span.display(self.elapsed));
+            at = span.range.end;
+        }
+        out.push_str(&self.interpolated[at..]);
+        out
+    }
+}
+
+/// Steps every roll and writes it into its target's `Text`. Rolls of a line that is no longer
+/// current are dropped; the new line already replaced the text.
+pub(super) fn animate_display_rolls(
+    mut commands: Commands,
+    time: Res<Time>,
+    runtime: Res<MortarRuntime>,
+    icon_settings: Res<MortarIconSettings>,
+    mut rolls: Query<(Entity, &mut Text, &mut MortarDisplayRoll)>,
+) {
+    let cursor = runtime.primary_dialogue_state().map(DialogueState::cursor);
+    for (entity, mut text, mut roll) in &mut rolls {
+        if cursor != Some(roll.cursor) {
+            commands.entity(entity).remove::<MortarDisplayRoll>();
+            continue;
+        }
+        roll.elapsed += time.delta();
+        let shown = icons::dialogue_text(
+            roll.header.clone(),
+            &roll.compose(),
+            "",
+            &[],
+            &icon_settings,
+        )
+        .full_text();
+        if text.0 != shown {
+            text.0.clone_from(&shown);
+        }
+        roll.text = shown;
+        if roll.settled() {
+            commands.entity(entity).remove::<MortarDisplayRoll>();
+        }
+    }
+}
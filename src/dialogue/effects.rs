@@ -0,0 +1,266 @@
+//! # effects.rs
+//!
+//! # effects.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Tracks "sticky" presentation effects fired by Mortar actions, such as `set_color` or
+//! `set_animation`, and reverts them automatically when the line, node, or conversation they
+//! belong to ends. Game code registers paired apply/revert handlers; actions without a pair keep
+//! behaving as plain [`MortarGameEvent`]s.
+//!
+//! 跟踪由 Mortar 动作触发的“粘性”表现效果（例如 `set_color`、`set_animation`），并在其所属的
+//! 行、节点或整段对话结束时自动撤销。游戏代码注册成对的应用/撤销处理函数；未注册的动作仍作为普通
+//! [`MortarGameEvent`] 处理。
+
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+use crate::MortarRuntime;
+use crate::debug::LOG_EVENTS;
+
+use super::MortarGameEvent;
+
+/// Handler invoked with the action's arguments (quotes and the `scope=` argument stripped).
+///
+/// 以动作参数（已去掉引号和 `scope=` 参数）调用的处理函数。
+pub type MortarEffectHandler = Box<dyn Fn(&mut Commands, &[String]) + Send + Sync>;
+
+/// How long a reversible effect stays applied.
+///
+/// Scripts can override the registered default with a trailing `scope=line`, `scope=node`
+/// or `scope=conversation` argument.
+///
+/// 可撤销效果的持续范围。
+///
+/// 脚本可以在参数末尾写 `scope=line`、`scope=node` 或 `scope=conversation` 来覆盖注册时的默认值。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MortarEffectScope {
+    /// Reverted when the dialogue advances to another line.
+    ///
+    /// 对话推进到下一行时撤销。
+    Line,
+    /// Reverted when the dialogue leaves the node.
+    ///
+    /// 对话离开当前节点时撤销。
+    Node,
+    /// Reverted when the dialogue finishes or is stopped.
+    ///
+    /// 对话结束或被停止时撤销。
+    #[default]
+    Conversation,
+}
+
+impl MortarEffectScope {
+    fn parse_arg(arg: &str) -> Option<Self> {
+        let value = arg
+            .strip_prefix("scope=")
+            .or_else(|| arg.strip_prefix("scope:"))?;
+        match value.trim().to_ascii_lowercase().as_str() {
+            "line" => Some(Self::Line),
+            "node" => Some(Self::Node),
+            "conversation" => Some(Self::Conversation),
+            _ => None,
+        }
+    }
+}
+
+struct EffectPair {
+    default_scope: MortarEffectScope,
+    apply: MortarEffectHandler,
+    revert: MortarEffectHandler,
+}
+
+/// An applied effect waiting to be reverted.
+///
+/// 已应用、等待撤销的效果。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MortarAppliedEffect {
+    pub name: String,
+    pub args: Vec<String>,
+    pub scope: MortarEffectScope,
+}
+
+/// Position of the primary dialogue used to detect when a scope ends.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct ScopePosition {
+    dialogue: Entity,
+    path: String,
+    node: String,
+    text_index: usize,
+}
+
+impl ScopePosition {
+    pub(super) fn of(runtime: &MortarRuntime) -> Option<Self> {
+        let dialogue = runtime.primary_dialogue?;
+        let state = runtime.active_dialogues.get(&dialogue)?;
+        Some(Self {
+            dialogue,
+            path: state.mortar_path.clone(),
+            node: state.current_node.clone(),
+            text_index: state.text_index,
+        })
+    }
+
+    /// Returns the widest scope that ended when moving from `self` to `next`.
+    pub(super) fn ended_scope(&self, next: Option<&Self>) -> Option<MortarEffectScope> {
+        let Some(next) = next else {
+            return Some(MortarEffectScope::Conversation);
+        };
+        if self.dialogue != next.dialogue {
+            return Some(MortarEffectScope::Conversation);
+        }
+        if self.path != next.path || self.node != next.node || next.text_index < self.text_index {
+            return Some(MortarEffectScope::Node);
+        }
+        (next.text_index != self.text_index).then_some(MortarEffectScope::Line)
+    }
+}
+
+/// Registry of reversible effects and the effects currently applied.
+///
+/// 可撤销效果的注册表，以及当前已应用的效果。
+#[derive(Resource, Default)]
+pub struct MortarReversibleEffects {
+    pairs: HashMap<String, EffectPair>,
+    applied: Vec<MortarAppliedEffect>,
+    position: Option<ScopePosition>,
+}
+
+impl MortarReversibleEffects {
+    /// Registers an effect that is reverted when the conversation ends.
+    ///
+    /// 注册一个在对话结束时撤销的效果。
+    pub fn on_effect<A, R>(&mut self, name: impl Into<String>, apply: A, revert: R) -> &mut Self
+    where
+        A: Fn(&mut Commands, &[String]) + Send + Sync + 'static,
+        R: Fn(&mut Commands, &[String]) + Send + Sync + 'static,
+    {
+        self.on_effect_scoped(name, MortarEffectScope::default(), apply, revert)
+    }
+
+    /// Registers an effect with an explicit default scope.
+    ///
+    /// 注册一个指定默认范围的效果。
+    pub fn on_effect_scoped<A, R>(
+        &mut self,
+        name: impl Into<String>,
+        default_scope: MortarEffectScope,
+        apply: A,
+        revert: R,
+    ) -> &mut Self
+    where
+        A: Fn(&mut Commands, &[String]) + Send + Sync + 'static,
+        R: Fn(&mut Commands, &[String]) + Send + Sync + 'static,
+    {
+        self.pairs.insert(
+            name.into(),
+            EffectPair {
+                default_scope,
+                apply: Box::new(apply),
+                revert: Box::new(revert),
+            },
+        );
+        self
+    }
+
+    /// Returns true if `name` has a registered apply/revert pair.
+    ///
+    /// `name` 已注册应用/撤销处理函数时返回 true。
+    pub fn is_registered(&self, name: &str) -> bool {
+        self.pairs.contains_key(name)
+    }
+
+    /// Effects applied and not yet reverted, in application order.
+    ///
+    /// 已应用但尚未撤销的效果，按应用顺序排列。
+    pub fn applied(&self) -> &[MortarAppliedEffect] {
+        &self.applied
+    }
+
+    /// Applies a fired action if it has a registered pair. Re-applying the same effect within
+    /// the same scope replaces its pending revert instead of stacking another one.
+    fn apply(&mut self, commands: &mut Commands, name: &str, raw_args: &[String], track: bool) {
+        let Some(pair) = self.pairs.get(name) else {
+            return;
+        };
+
+        let mut scope = pair.default_scope;
+        let mut args = Vec::with_capacity(raw_args.len());
+        for arg in raw_args.iter().map(|arg| arg.trim_matches('"')) {
+            match MortarEffectScope::parse_arg(arg) {
+                Some(explicit) => scope = explicit,
+                None => args.push(arg.to_string()),
+            }
+        }
+
+        (pair.apply)(commands, &args);
+        if !track {
+            return;
+        }
+
+        let effect = MortarAppliedEffect {
+            name: name.to_string(),
+            args,
+            scope,
+        };
+        match self
+            .applied
+            .iter_mut()
+            .find(|applied| applied.name == name && applied.scope == scope)
+        {
+            Some(existing) => *existing = effect,
+            None => self.applied.push(effect),
+        }
+    }
+
+    /// Reverts, in reverse order, every applied effect whose scope is at most `ended`.
+    fn revert_through(&mut self, commands: &mut Commands, ended: MortarEffectScope) {
+        let mut kept = Vec::with_capacity(self.applied.len());
+        for effect in std::mem::take(&mut self.applied).into_iter().rev() {
+            if effect.scope > ended {
+                kept.push(effect);
+                continue;
+            }
+            if let Some(pair) = self.pairs.get(&effect.name) {
+                dev_info!(
+                    target: LOG_EVENTS,
+                    "Reverting effect '{}' ({:?})",
+                    effect.name, effect.scope
+                );
+                (pair.revert)(commands, &effect.args);
+            }
+        }
+        kept.reverse();
+        self.applied = kept;
+    }
+}
+
+/// Reverts effects whose scope ended, then applies and records newly fired effects.
+///
+/// 撤销范围已结束的效果，然后应用并记录新触发的效果。
+pub(super) fn update_reversible_effects(
+    mut commands: Commands,
+    runtime: Res<MortarRuntime>,
+    mut effects: ResMut<MortarReversibleEffects>,
+    mut game_events: MessageReader<MortarGameEvent>,
+) {
+    let position = ScopePosition::of(&runtime);
+    if effects.position != position {
+        let ended = effects
+            .position
+            .as_ref()
+            .and_then(|previous| previous.ended_scope(position.as_ref()));
+        if let Some(ended) = ended {
+            effects.revert_through(&mut commands, ended);
+        }
+        effects.position = position;
+    }
+
+    let tracking = effects.position.is_some();
+    for event in game_events.read() {
+        effects.apply(&mut commands, &event.name, &event.args, tracking);
+    }
+}
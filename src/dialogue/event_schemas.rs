@@ -0,0 +1,323 @@
+//! # event_schemas.rs
+//!
+//! # event_schemas.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Checks the arguments of text events and `run` events when a line is collected, before any of
+//! them fires. A [`MortarEventSchemas`] resource maps action names to argument kinds or to a
+//! parser into a game type; with `validate_events_on_collect` set, each collected action is
+//! checked, failures are recorded on the [`crate::MortarEventTracker`] and in
+//! [`MortarEventDiagnostics`] with the node, line and character index, and
+//! [`MortarInvalidEventPolicy`] decides whether invalid events still fire. A typo such as
+//! `set_color(#12)` is then reported when its line shows, not mid-cutscene. Timeline steps are
+//! reported but always play, so the timeline keeps its timing.
+//!
+//! 在收集行时、任何事件触发之前检查文本事件与 `run` 事件的参数。[`MortarEventSchemas`] 资源把动作
+//! 名称映射到参数类型，或映射到解析为游戏类型的解析器；设置 `validate_events_on_collect` 后，每个
+//! 收集到的动作都会被检查，失败会连同节点、行与字符索引记录到 [`crate::MortarEventTracker`] 和
+//! [`MortarEventDiagnostics`] 中，并由 [`MortarInvalidEventPolicy`] 决定无效事件是否仍然触发。
+//! 这样 `set_color(#12)` 这类笔误会在其所在行显示时就被报告，而不是在过场动画中途才出错。时间线
+//! 步骤只报告而始终播放，以保持时间线的节奏。
+
+use bevy::prelude::*;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::binder::value_kind;
+use crate::debug::LOG_EVENTS;
+use crate::{MortarAsset, MortarEventTracker, MortarParamKind, MortarRuntime, MortarValue};
+
+type ArgParser = Arc<dyn Fn(&[String]) -> Result<(), String> + Send + Sync>;
+
+#[derive(Clone)]
+enum EventSchema {
+    Kinds(Vec<MortarParamKind>),
+    Parser(ArgParser),
+}
+
+/// Why the arguments of an event action do not match its schema.
+///
+/// 事件动作的参数与其模式不匹配的原因。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MortarEventArgError {
+    /// The action takes `expected` arguments but got `found`.
+    ///
+    /// 动作需要 `expected` 个参数，实际收到 `found` 个。
+    Arity { expected: usize, found: usize },
+    /// Argument `index` is of the wrong kind.
+    ///
+    /// 第 `index` 个参数类型不符。
+    Kind {
+        index: usize,
+        expected: MortarParamKind,
+        found: &'static str,
+    },
+    /// The action's parser refused the arguments.
+    ///
+    /// 动作的解析器拒绝了这些参数。
+    Rejected(String),
+}
+
+impl std::fmt::Display for MortarEventArgError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Arity { expected, found } => {
+                write!(f, "expected {expected} arguments, found {found}")
+            }
+            Self::Kind {
+                index,
+                expected,
+                found,
+            } => write!(
+                f,
+                "argument {index} should be a {}, found a {found}",
+                expected.as_str()
+            ),
+            Self::Rejected(reason) => f.write_str(reason),
+        }
+    }
+}
+
+/// What happens to an event whose arguments fail their schema.
+///
+/// 参数未通过模式检查的事件如何处理。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MortarInvalidEventPolicy {
+    /// Report the event and fire it anyway.
+    ///
+    /// 报告该事件，但仍然触发。
+    #[default]
+    FireAnyway,
+    /// Report the event and never fire it.
+    ///
+    /// 报告该事件，且不触发。
+    SuppressInvalid,
+}
+
+/// Argument schemas of event actions, checked when a line is collected. Insert it as a resource
+/// and set `validate_events_on_collect`; actions without a schema are not checked.
+///
+/// 事件动作的参数模式，在收集行时检查。将其作为资源插入并设置 `validate_events_on_collect`；
+/// 没有模式的动作不做检查。
+#[derive(Resource, Clone, Default)]
+pub struct MortarEventSchemas {
+    schemas: HashMap<String, EventSchema>,
+    pub validate_events_on_collect: bool,
+    pub invalid_policy: MortarInvalidEventPolicy,
+}
+
+impl MortarEventSchemas {
+    /// Expects `action` to take exactly `kinds`.
+    ///
+    /// 要求 `action` 的参数恰好为 `kinds`。
+    pub fn insert(
+        &mut self,
+        action: impl Into<String>,
+        kinds: impl Into<Vec<MortarParamKind>>,
+    ) -> &mut Self {
+        self.schemas
+            .insert(action.into(), EventSchema::Kinds(kinds.into()));
+        self
+    }
+
+    /// Checks `action` by parsing its arguments into a game type, e.g. `GiveItem::from_args`.
+    /// The parser gets the arguments as [`crate::MortarGameEvent`] carries them, quotes removed.
+    ///
+    /// 通过把参数解析为游戏类型来检查 `action`，例如 `GiveItem::from_args`。解析器收到的参数与
+    /// [`crate::MortarGameEvent`] 携带的一致，已去除引号。
+    pub fn insert_parser<T>(
+        &mut self,
+        action: impl Into<String>,
+        parse: impl Fn(&[String]) -> Result<T, String> + Send + Sync + 'static,
+    ) -> &mut Self {
+        let parser: ArgParser = Arc::new(move |args| parse(args).map(drop));
+        self.schemas
+            .insert(action.into(), EventSchema::Parser(parser));
+        self
+    }
+
+    /// Checks the raw arguments of `action` as the script wrote them.
+    ///
+    /// 按脚本书写的原始参数检查 `action`。
+    pub fn check(&self, action: &str, args: &[String]) -> Result<(), MortarEventArgError> {
+        match self.schemas.get(action) {
+            None => Ok(()),
+            Some(EventSchema::Kinds(kinds)) => {
+                if kinds.len() != args.len() {
+                    return Err(MortarEventArgError::Arity {
+                        expected: kinds.len(),
+                        found: args.len(),
+                    });
+                }
+                let mismatch =
+                    kinds
+                        .iter()
+                        .zip(args)
+                        .enumerate()
+                        .find_map(|(index, (kind, arg))| {
+                            let value = MortarValue::parse(arg);
+                            (!kind.accepts(&value)).then(|| MortarEventArgError::Kind {
+                                index,
+                                expected: *kind,
+                                found: value_kind(&value),
+                            })
+                        });
+                mismatch.map_or(Ok(()), Err)
+            }
+            Some(EventSchema::Parser(parse)) => {
+                let args: Vec<String> = args
+                    .iter()
+                    .map(|arg| arg.trim_matches('"').to_owned())
+                    .collect();
+                parse(&args).map_err(MortarEventArgError::Rejected)
+            }
+        }
+    }
+}
+
+/// One event action that failed its schema, with where it was collected.
+///
+/// 一个未通过模式检查的事件动作，以及它被收集的位置。
+#[derive(Debug, Clone, PartialEq)]
+pub struct MortarEventDiagnostic {
+    pub path: String,
+    pub node: String,
+    pub text_index: usize,
+    /// Character index the event fires at; `None` for events dispatched by `run`.
+    ///
+    /// 事件触发的字符索引；由 `run` 派发的事件为 `None`。
+    pub index: Option<f64>,
+    pub action: String,
+    pub args: Vec<String>,
+    pub error: MortarEventArgError,
+}
+
+/// Event actions that failed their schema, each reported once.
+///
+/// 未通过模式检查的事件动作，每个只报告一次。
+#[derive(Resource, Debug, Clone, Default)]
+pub struct MortarEventDiagnostics {
+    pub entries: Vec<MortarEventDiagnostic>,
+    /// Lines left with events unfired, see [`crate::MortarMandatoryEvents`].
+    ///
+    /// 带有未触发事件而被离开的行，参见 [`crate::MortarMandatoryEvents`]。
+    pub flushes: Vec<super::MortarEventFlush>,
+}
+
+impl MortarEventDiagnostics {
+    fn report(&mut self, diagnostic: MortarEventDiagnostic) {
+        if self.entries.contains(&diagnostic) {
+            return;
+        }
+        warn!(
+            target: LOG_EVENTS,
+            "Event '{}' on line {} of node '{}' in '{}' is invalid: {}",
+            diagnostic.action,
+            diagnostic.text_index,
+            diagnostic.node,
+            diagnostic.path,
+            diagnostic.error
+        );
+        self.entries.push(diagnostic);
+    }
+}
+
+/// Path, node and line index an event was collected from.
+type LineContext<'a> = (&'a str, &'a str, usize);
+
+/// Checks an action and reports it if invalid.
+fn check_action(
+    schemas: &MortarEventSchemas,
+    diagnostics: &mut MortarEventDiagnostics,
+    action: &mortar_compiler::Action,
+    (path, node, text_index): LineContext,
+    index: Option<f64>,
+) -> Option<MortarEventArgError> {
+    let error = schemas.check(&action.action_type, &action.args).err()?;
+    diagnostics.report(MortarEventDiagnostic {
+        path: path.to_owned(),
+        node: node.to_owned(),
+        text_index,
+        index,
+        action: action.action_type.clone(),
+        args: action.args.clone(),
+        error: error.clone(),
+    });
+    Some(error)
+}
+
+/// Checks the events of trackers inserted for a new line.
+pub(super) fn validate_collected_events(
+    schemas: Option<Res<MortarEventSchemas>>,
+    runtime: Res<MortarRuntime>,
+    mut diagnostics: ResMut<MortarEventDiagnostics>,
+    mut trackers: Query<&mut MortarEventTracker, Added<MortarEventTracker>>,
+) {
+    let Some(schemas) = schemas.filter(|schemas| schemas.validate_events_on_collect) else {
+        return;
+    };
+    let Some(state) = runtime.primary_dialogue_state() else {
+        return;
+    };
+    let context = (
+        state.mortar_path.as_str(),
+        state.current_node.as_str(),
+        state.text_index,
+    );
+    let suppress = schemas.invalid_policy == MortarInvalidEventPolicy::SuppressInvalid;
+    for mut tracker in &mut trackers {
+        let errors = tracker
+            .events()
+            .iter()
+            .map(|event| {
+                event.actions.iter().fold(None, |first, action| {
+                    let error = check_action(
+                        &schemas,
+                        &mut diagnostics,
+                        action,
+                        context,
+                        Some(event.index),
+                    );
+                    first.or(error)
+                })
+            })
+            .collect();
+        tracker.set_validation(errors, suppress);
+    }
+}
+
+/// Checks the event a `run` statement dispatches, or each event of the timeline it starts.
+/// Returns whether the run should play.
+pub(super) fn run_allowed(
+    schemas: Option<&MortarEventSchemas>,
+    diagnostics: &mut MortarEventDiagnostics,
+    asset: &MortarAsset,
+    name: &str,
+    context: LineContext,
+) -> bool {
+    let Some(schemas) = schemas.filter(|schemas| schemas.validate_events_on_collect) else {
+        return true;
+    };
+    if let Some(event) = asset.event_def(name) {
+        let invalid = check_action(schemas, diagnostics, &event.action, context, None).is_some();
+        return !invalid || schemas.invalid_policy == MortarInvalidEventPolicy::FireAnyway;
+    }
+    let steps = asset
+        .timeline_def(name)
+        .into_iter()
+        .flat_map(|timeline| &timeline.statements)
+        .filter(|statement| statement.stmt_type == "run");
+    for step in steps {
+        let event = step
+            .event_name
+            .as_deref()
+            .and_then(|name| asset.event_def(name));
+        if let Some(event) = event {
+            check_action(schemas, diagnostics, &event.action, context, None);
+        }
+    }
+    true
+}
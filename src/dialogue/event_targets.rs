@@ -0,0 +1,179 @@
+//! # event_targets.rs
+//!
+//! # event_targets.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Lets an event address entities other than its source. An unquoted argument written `@name`, e.g.
+//! `set_animation("wave", @blacksmith)`, names an entity: when the event is dispatched the name
+//! resolves through [`MortarSpeakerRegistry`] first, then through [`MortarSpeaker`] components, and
+//! the entity is added to [`MortarGameEvent::targets`]. The argument stays in `args` as written, so
+//! handlers reading positions keep working. A name that resolves to nothing adds no target; it is
+//! logged once and kept in [`MortarTargetDiagnostics`].
+//!
+//! 让事件指向来源以外的实体。不带引号、写作 `@name` 的参数会指定一个实体，例如
+//! `set_animation("wave", @blacksmith)`：事件派发时，该名称先通过 [`MortarSpeakerRegistry`]、再通过
+//! [`MortarSpeaker`] 组件解析，得到的实体会加入 [`MortarGameEvent::targets`]。该参数仍按原样保留在
+//! `args` 中，因此按位置读取参数的处理器不受影响。无法解析的名称不会产生目标；它只记录一次日志并
+//! 保存在 [`MortarTargetDiagnostics`] 中。
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use std::collections::{BTreeSet, HashMap};
+
+use super::MortarGameEvent;
+use crate::debug::LOG_EVENTS;
+
+/// Prefix of an argument that names an entity.
+const TARGET_PREFIX: char = '@';
+
+/// Names the entity as a speaker, for speakers not listed in [`MortarSpeakerRegistry`].
+///
+/// 将实体命名为说话者，用于未在 [`MortarSpeakerRegistry`] 中列出的说话者。
+#[derive(Component, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MortarSpeaker(pub String);
+
+/// Speaker names mapped to the entities that stand for them. Entries win over
+/// [`MortarSpeaker`] components of the same name.
+///
+/// 说话者名称到代表其实体的映射。条目优先于同名的 [`MortarSpeaker`] 组件。
+#[derive(Resource, Debug, Clone, Default)]
+pub struct MortarSpeakerRegistry {
+    entities: HashMap<String, Entity>,
+}
+
+impl MortarSpeakerRegistry {
+    /// Maps `speaker` to `entity`, returning the entity it replaced.
+    ///
+    /// 将 `speaker` 映射到 `entity`，返回被替换的实体。
+    pub fn register(&mut self, speaker: impl Into<String>, entity: Entity) -> Option<Entity> {
+        self.entities.insert(speaker.into(), entity)
+    }
+
+    pub fn unregister(&mut self, speaker: &str) -> Option<Entity> {
+        self.entities.remove(speaker)
+    }
+
+    pub fn get(&self, speaker: &str) -> Option<Entity> {
+        self.entities.get(speaker).copied()
+    }
+}
+
+/// Target names of `@name` arguments that resolved to no entity.
+///
+/// 未能解析为任何实体的 `@name` 参数目标名称。
+#[derive(Resource, Debug, Clone, Default)]
+pub struct MortarTargetDiagnostics {
+    pub unresolved: BTreeSet<String>,
+}
+
+/// An argument as the compiler writes it, split into its value and the entity it names. Only a
+/// bare `@name` token names an entity; quotes mark a string, so `"@intro.wav"` is a plain value.
+struct EventArg<'a> {
+    /// The argument without its string quotes.
+    value: &'a str,
+    target: Option<&'a str>,
+}
+
+impl<'a> EventArg<'a> {
+    fn parse(raw: &'a str) -> Self {
+        Self {
+            value: arg_value(raw),
+            target: target_name(raw),
+        }
+    }
+}
+
+/// The value an event handler receives for an argument, without its string quotes.
+pub(crate) fn arg_value(raw: &str) -> &str {
+    raw.trim().trim_matches('"')
+}
+
+/// The entity name of a bare `@name` argument; a quoted argument names nothing.
+pub(crate) fn target_name(raw: &str) -> Option<&str> {
+    raw.trim()
+        .strip_prefix(TARGET_PREFIX)
+        .filter(|name| !name.is_empty())
+}
+
+/// Resolves speaker names to entities.
+#[derive(SystemParam)]
+pub(super) struct SpeakerEntities<'w, 's> {
+    registry: Res<'w, MortarSpeakerRegistry>,
+    speakers: Query<'w, 's, (Entity, &'static MortarSpeaker)>,
+}
+
+impl SpeakerEntities<'_, '_> {
+    pub(super) fn get(&self, name: &str) -> Option<Entity> {
+        self.registry.get(name).or_else(|| {
+            self.speakers
+                .iter()
+                .find(|(_, speaker)| speaker.0 == name)
+                .map(|(entity, _)| entity)
+        })
+    }
+}
+
+/// Writes [`MortarGameEvent`]s with the targets their arguments name.
+#[derive(SystemParam)]
+pub(super) struct GameEventWriter<'w, 's> {
+    writer: MessageWriter<'w, MortarGameEvent>,
+    speakers: SpeakerEntities<'w, 's>,
+    diagnostics: ResMut<'w, MortarTargetDiagnostics>,
+}
+
+impl GameEventWriter<'_, '_> {
+    /// The event `name` with `args` as the compiler wrote them, its targets resolved.
+    pub(super) fn event(
+        &mut self,
+        source: Option<Entity>,
+        name: String,
+        args: Vec<String>,
+    ) -> MortarGameEvent {
+        let targets = self.resolve(&name, args.iter().filter_map(|arg| target_name(arg)));
+        MortarGameEvent {
+            source,
+            name,
+            args,
+            targets,
+        }
+    }
+
+    /// Writes the event `name` with its arguments unquoted, as `run` statements publish them.
+    /// Targets come from the arguments as written, so a quoted `"@name"` names nothing.
+    pub(super) fn write_unquoted(&mut self, source: Option<Entity>, name: String, raw: &[String]) {
+        let args: Vec<_> = raw.iter().map(|arg| EventArg::parse(arg)).collect();
+        let targets = self.resolve(&name, args.iter().filter_map(|arg| arg.target));
+        let args = args.iter().map(|arg| arg.value.to_owned()).collect();
+        self.send(MortarGameEvent {
+            source,
+            name,
+            args,
+            targets,
+        });
+    }
+
+    fn resolve<'a>(&mut self, name: &str, names: impl Iterator<Item = &'a str>) -> Vec<Entity> {
+        names
+            .filter_map(|target| {
+                let entity = self.speakers.get(target);
+                if entity.is_none() && self.diagnostics.unresolved.insert(target.to_owned()) {
+                    warn!(target: LOG_EVENTS, "Event '{name}' names '@{target}', which is no entity");
+                }
+                entity
+            })
+            .collect()
+    }
+
+    pub(super) fn write(&mut self, source: Option<Entity>, name: String, args: Vec<String>) {
+        let event = self.event(source, name, args);
+        self.send(event);
+    }
+
+    /// Writes an event made by [`Self::event`].
+    pub(super) fn send(&mut self, event: MortarGameEvent) {
+        self.writer.write(event);
+    }
+}
@@ -0,0 +1,193 @@
+//! # experiments.rs
+//!
+//! # experiments.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Narrative A/B experiments. Text items, lines and choice options may carry
+//! `"experiment": { "name": ..., "variant": ... }`; the [`MortarExperiments`] resource holds the
+//! variant this player is assigned to for each experiment. Content of another variant is treated
+//! like content whose condition failed: texts are skipped and options hidden, so the indices of
+//! the shown variant do not change. Content of an experiment with no assignment stays visible and
+//! is noted once in [`MortarExperimentDiagnostics`]. Each shown line is announced with a
+//! [`MortarTextAdvanced`] carrying its experiment tag, and choice views carry theirs, so analytics
+//! can segment by variant.
+//!
+//! 叙事 A/B 实验。文本项、line 与选项可以带有 `"experiment": { "name": ..., "variant": ... }`；
+//! [`MortarExperiments`] 资源保存该玩家在每个实验中被分配到的变体。属于其他变体的内容按条件
+//! 不成立的内容处理：文本被跳过、选项被隐藏，因此所显示变体的索引不会改变。所属实验尚未分配的
+//! 内容保持可见，并在 [`MortarExperimentDiagnostics`] 中记录一次。每一行显示时都会发出带有其
+//! 实验标签的 [`MortarTextAdvanced`]，选项视图也带有各自的标签，便于分析时按变体细分。
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap};
+
+use super::MortarTextAdvanced;
+use crate::debug::LOG_DIALOGUE;
+use crate::{DialogueState, MortarRuntime, TextData};
+
+/// The experiment and variant a piece of content belongs to.
+///
+/// 一段内容所属的实验与变体。
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MortarExperimentTag {
+    pub name: String,
+    pub variant: String,
+}
+
+impl MortarExperimentTag {
+    /// Reads the `experiment` key of a content item or an option.
+    pub(crate) fn from_item(item: &Value) -> Option<Self> {
+        let experiment = item.get("experiment")?;
+        let field = |key| experiment.get(key)?.as_str().map(str::to_owned);
+        Some(Self {
+            name: field("name")?,
+            variant: field("variant")?,
+        })
+    }
+}
+
+/// The variant this player sees in each experiment, set by the game from its own assignment.
+///
+/// 该玩家在每个实验中看到的变体，由游戏根据自己的分配系统设置。
+#[derive(Resource, Debug, Clone, Default, PartialEq, Eq)]
+pub struct MortarExperiments {
+    variants: HashMap<String, String>,
+}
+
+impl MortarExperiments {
+    /// Assigns `variant` of `experiment` to this player.
+    ///
+    /// 为该玩家分配 `experiment` 的 `variant` 变体。
+    pub fn assign(
+        &mut self,
+        experiment: impl Into<String>,
+        variant: impl Into<String>,
+    ) -> &mut Self {
+        self.variants.insert(experiment.into(), variant.into());
+        self
+    }
+
+    /// Assigns a variant picked by [`Self::hashed_variant`], for games without an assignment
+    /// system of their own. Does nothing when `variants` is empty.
+    ///
+    /// 分配由 [`Self::hashed_variant`] 选出的变体，供没有自己分配系统的游戏使用。`variants` 为空
+    /// 时不做任何事。
+    pub fn assign_hashed(
+        &mut self,
+        experiment: impl Into<String>,
+        player_id: &str,
+        variants: &[&str],
+    ) -> &mut Self {
+        let experiment = experiment.into();
+        if let Some(variant) = Self::hashed_variant(&experiment, player_id, variants) {
+            self.variants.insert(experiment, variant.to_owned());
+        }
+        self
+    }
+
+    /// Picks one of `variants` from a hash of the experiment name and the player id. The pick is
+    /// the same on every run and platform, and independent between experiments.
+    ///
+    /// 根据实验名与玩家标识的哈希从 `variants` 中选出一个。每次运行、每个平台上的结果都相同，且
+    /// 不同实验之间相互独立。
+    pub fn hashed_variant<'a>(
+        experiment: &str,
+        player_id: &str,
+        variants: &[&'a str],
+    ) -> Option<&'a str> {
+        // FNV-1a, which unlike the std hasher is specified and stable across releases.
+        //
+        // FNV-1a；与标准库的哈希器不同，它有明确规范，在各版本间保持稳定。
+        let bytes = experiment.bytes().chain([0]).chain(player_id.bytes());
+        let hash = bytes.fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        });
+        let len = u64::try_from(variants.len()).ok().filter(|len| *len > 0)?;
+        variants.get(usize::try_from(hash % len).ok()?).copied()
+    }
+
+    /// The variant assigned for `experiment`.
+    ///
+    /// 为 `experiment` 分配的变体。
+    pub fn variant(&self, experiment: &str) -> Option<&str> {
+        self.variants.get(experiment).map(String::as_str)
+    }
+}
+
+/// Experiments that content referred to without an assignment in [`MortarExperiments`].
+///
+/// 内容引用了、但 [`MortarExperiments`] 中没有分配的实验。
+#[derive(Resource, Debug, Clone, Default)]
+pub struct MortarExperimentDiagnostics {
+    pub unassigned: BTreeSet<String>,
+}
+
+/// What deciding experiment visibility touches.
+#[derive(SystemParam)]
+pub(super) struct ExperimentParams<'w> {
+    experiments: Res<'w, MortarExperiments>,
+    diagnostics: ResMut<'w, MortarExperimentDiagnostics>,
+    advanced: MessageWriter<'w, MortarTextAdvanced>,
+}
+
+impl ExperimentParams<'_> {
+    /// Whether the assignments changed since the system last ran.
+    pub(super) fn changed(&self) -> bool {
+        self.experiments.is_changed()
+    }
+
+    /// Why content tagged `tag` is hidden from this player, if it is.
+    pub(super) fn hidden_reason(&mut self, tag: Option<&MortarExperimentTag>) -> Option<String> {
+        let tag = tag?;
+        let Some(assigned) = self.experiments.variant(&tag.name) else {
+            if self.diagnostics.unassigned.insert(tag.name.clone()) {
+                warn!(
+                    target: LOG_DIALOGUE,
+                    "Experiment '{}' has no assigned variant, showing all of its content",
+                    tag.name
+                );
+            }
+            return None;
+        };
+        (assigned != tag.variant).then(|| {
+            format!(
+                "experiment '{}' variant '{}' is not the assigned '{}'",
+                tag.name, tag.variant, assigned
+            )
+        })
+    }
+
+    /// Keeps the lines of a group this player sees.
+    pub(super) fn shown_lines<'a>(&mut self, group: &'a [TextData]) -> Vec<&'a TextData> {
+        group
+            .iter()
+            .filter(|line| self.hidden_reason(line.experiment.as_ref()).is_none())
+            .collect()
+    }
+
+    /// Announces the line about to be shown.
+    pub(super) fn announce(
+        &mut self,
+        runtime: &MortarRuntime,
+        state: &DialogueState,
+        line_ids: Vec<String>,
+    ) {
+        let Some(text_data) = state.current_text_data() else {
+            return;
+        };
+        self.advanced.write(MortarTextAdvanced {
+            dialogue: runtime.primary_dialogue,
+            mortar_path: state.mortar_path.clone(),
+            node: state.current_node.clone(),
+            text_index: state.text_index,
+            line_id: text_data.line_id.clone(),
+            line_ids,
+            experiment: text_data.experiment.clone(),
+        });
+    }
+}
@@ -0,0 +1,229 @@
+//! # forced_line.rs
+//!
+//! # forced_line.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! [`MortarRuntime::force_show_line`], the rendering half of the direct drive calls in
+//! [`crate::MortarRuntime::force_set_position`]'s module. It positions the primary dialogue and
+//! runs the same steps as the text system on the spot: switch and condition checks, `else`
+//! resolution, interpolation, header, icons and parallel voices. The result is returned instead
+//! of being shown; the text targets are left alone. Header and icons use their default settings,
+//! and experiment tags are not consulted, so every variant shows as when none is assigned.
+//!
+//! [`MortarRuntime::force_show_line`] 是直接驱动调用中负责渲染的部分（参见
+//! [`crate::MortarRuntime::force_set_position`] 所在模块）。它定位主对话，并当场执行与文本系统相同的
+//! 步骤：switch 与条件检查、`else` 解析、插值、头部、图标与并行声部。结果会被返回而不会显示，文本目标
+//! 保持不变。头部与图标使用默认设置，且不参考实验标签，因此所有变体都会像未分配变体时一样显示。
+
+use bevy::asset::Assets;
+
+use super::line_explanation::describe_condition;
+use super::line_group::process_line_group;
+use super::text_events::collect_text_events;
+use super::{
+    MortarDialogueText, MortarDialogueVariables, MortarHeaderSettings, MortarIconSettings, header,
+    icons, parallel,
+};
+use crate::binder::CallContextGuard;
+use crate::eval::{FunctionDecls, interpolate};
+use crate::runtime::drive_file;
+use crate::{
+    MortarAsset, MortarDriveError, MortarRegistry, MortarRuntime, MortarVariableState,
+    evaluate_if_condition,
+};
+
+/// A line rendered by [`MortarRuntime::force_show_line`], as a text target without a channel
+/// would show it.
+///
+/// 由 [`MortarRuntime::force_show_line`] 渲染的行，与没有通道的文本目标所显示的一致。
+#[derive(Debug, Clone)]
+pub struct MortarRenderedLine {
+    pub mortar_path: String,
+    pub node: String,
+    /// Index of the line rendered, after clamping and `else` resolution.
+    ///
+    /// 渲染的行的索引（已经过限制与 `else` 解析）。
+    pub text_index: usize,
+    pub text: MortarDialogueText,
+    /// Events of the line, at the indices they were mapped to; empty for a parallel line.
+    ///
+    /// 该行的事件，位于其被映射到的索引处；并行行为空。
+    pub events: Vec<mortar_compiler::Event>,
+}
+
+impl MortarRuntime {
+    /// Moves the primary dialogue to line `index` of `node` in the file at `path`, starting it
+    /// when none is active, and renders the line at once. Statements before the line run against
+    /// `variables`. A line the text system would skip fails with
+    /// [`MortarDriveError::LineSkipped`], leaving the dialogue on it.
+    ///
+    /// 将主对话移动到 `path` 文件中 `node` 的第 `index` 行（没有活跃对话时会启动一个），并立即渲染该
+    /// 行。该行之前的语句会作用于 `variables`。文本系统会跳过的行以 [`MortarDriveError::LineSkipped`]
+    /// 失败，对话停留在该行。
+    pub fn force_show_line(
+        &mut self,
+        path: &str,
+        node: &str,
+        index: usize,
+        registry: &MortarRegistry,
+        assets: &Assets<MortarAsset>,
+        variables: &mut MortarDialogueVariables,
+    ) -> Result<MortarRenderedLine, MortarDriveError> {
+        let (path, asset_id, asset) = drive_file(registry, assets, path)?;
+        self.force_position(&path, node, index, asset)?;
+        let variables = variables.ensure_for(asset_id, &asset.data, self.warm_variables(&path));
+        let skipped = |reason: &str| MortarDriveError::LineSkipped {
+            reason: reason.to_owned(),
+        };
+
+        let state = self
+            .primary_dialogue_state()
+            .ok_or(MortarDriveError::NoDialogue)?;
+        let text_data = state
+            .current_text_data()
+            .ok_or_else(|| skipped("the node has no lines"))?;
+        let resolved = (text_data.parallel.is_empty() && !text_data.is_line).then(|| {
+            state
+                .resolve_text_at(state.text_index, &self.functions, variables)
+                .map(|(index, _)| index)
+        });
+        if let Some(Some(index)) = resolved
+            && index != state.text_index
+            && let Some(state) = self.primary_dialogue_state_mut()
+        {
+            state.enter_else_branch(index);
+            let cursor = state.cursor();
+            if !self.announce_direct_drive {
+                self.direct_drive.quiet_line = Some(cursor);
+            }
+        }
+
+        let state = self
+            .primary_dialogue_state()
+            .ok_or(MortarDriveError::NoDialogue)?;
+        let Some(text_data) = state.current_text_data() else {
+            return Err(skipped("the node has no lines"));
+        };
+        if state.is_text_hidden(state.text_index) {
+            return Err(skipped("its switch case was not picked"));
+        }
+        let _context = CallContextGuard::enter(self.call_context());
+        let func_decls = FunctionDecls::Asset(asset);
+        let (header_settings, icon_settings) = (
+            MortarHeaderSettings::default(),
+            MortarIconSettings::default(),
+        );
+        let resolve_header = |text: &crate::TextData, variables: &MortarVariableState| {
+            header::resolve_header(
+                &header_settings,
+                Some(&asset.metadata),
+                state,
+                text,
+                &self.functions,
+                func_decls,
+                variables,
+            )
+        };
+
+        let mut rendered = if !text_data.parallel.is_empty() {
+            let context = parallel::VoiceContext {
+                functions: &self.functions,
+                func_decls,
+                asset: Some(asset),
+                node_data: state.node_data(),
+                capabilities: self.capabilities(),
+                icon_settings: &icon_settings,
+            };
+            let voices = parallel::render_voices(
+                &text_data.parallel,
+                &context,
+                |_| None,
+                variables,
+                resolve_header,
+            );
+            if voices.is_empty() {
+                return Err(skipped("no voice is shown"));
+            }
+            parallel::RenderedLine::parallel(voices, &text_data.line_id, &icon_settings)
+        } else if text_data.is_line {
+            let group = state
+                .current_line_group()
+                .unwrap_or(&[])
+                .iter()
+                .filter(|line| self.capabilities().hidden_reason(&line.requires).is_none());
+            let processed = process_line_group(group, &self.functions, func_decls, variables)
+                .ok_or_else(|| skipped("no line in the group passed"))?;
+            let header = resolve_header(text_data, variables);
+            parallel::RenderedLine::Single {
+                text: icons::dialogue_text(
+                    header,
+                    &processed,
+                    &text_data.line_id,
+                    &[],
+                    &icon_settings,
+                ),
+                events: Vec::new(),
+            }
+        } else {
+            if let Some(reason) = self.capabilities().hidden_reason(&text_data.requires) {
+                return Err(skipped(&reason));
+            }
+            if resolved == Some(None) {
+                let reason = left_out_reason(text_data, &self.functions, variables);
+                return Err(skipped(&reason));
+            }
+            variables.execute_statements(&text_data.pre_statements);
+            let processed = interpolate(text_data, &self.functions, func_decls, variables);
+            if processed.is_empty() {
+                return Err(skipped("text is empty"));
+            }
+            let events = collect_text_events(
+                text_data,
+                variables,
+                Some(asset),
+                state.current_text_position(),
+                state.node_data(),
+            );
+            let header = resolve_header(text_data, variables);
+            parallel::RenderedLine::Single {
+                text: icons::dialogue_text(
+                    header,
+                    &processed,
+                    &text_data.line_id,
+                    &events,
+                    &icon_settings,
+                ),
+                events,
+            }
+        };
+        rendered.stamp(state.cursor());
+        let (text, events) = rendered.for_target(None);
+        let line = MortarRenderedLine {
+            mortar_path: state.mortar_path.clone(),
+            node: state.current_node.clone(),
+            text_index: state.text_index,
+            text: text.clone(),
+            events: events.to_vec(),
+        };
+        self.direct_drive.line_events.clone_from(&line.events);
+        Ok(line)
+    }
+}
+
+/// Why a regular text whose `if` / `else` block resolved to nothing is left out.
+fn left_out_reason(
+    text_data: &crate::TextData,
+    functions: &crate::MortarFunctionRegistry,
+    variables: &MortarVariableState,
+) -> String {
+    match &text_data.condition {
+        Some(condition) if !evaluate_if_condition(condition, functions, variables) => format!(
+            "condition {} -> false",
+            describe_condition(condition, variables)
+        ),
+        _ => "an earlier branch already ran".to_owned(),
+    }
+}
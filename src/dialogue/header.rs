@@ -0,0 +1,158 @@
+//! # header.rs
+//!
+//! # header.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Resolves the header shown above a line. A `header` key on the text item wins over a `header`
+//! key on its node, which wins over [`MortarHeaderSettings::format`]; an empty string means no
+//! header. `{file}` and `{node}` expand to the dialogue's position, and the remaining `{name}` or
+//! `{function(args)}` placeholders are interpolated like line text, so `— {location_name} —`
+//! works. The header never counts towards event indices, which follow the body alone. Targets
+//! whose header actually changes receive a [`MortarHeaderChanged`] message.
+//!
+//! 解析显示在行上方的头部。文本项上的 `header` 键优先于其节点上的 `header` 键，后者又优先于
+//! [`MortarHeaderSettings::format`]；空字符串表示不显示头部。`{file}` 与 `{node}` 会展开为对话当前
+//! 位置，其余的 `{name}` 或 `{function(args)}` 占位符会像行文本一样插值，因此可以写
+//! `— {location_name} —`。头部从不计入事件索引，事件索引只依据正文。头部确实发生变化的目标会收到
+//! [`MortarHeaderChanged`] 消息。
+
+use bevy::prelude::*;
+use mortar_compiler::StringPart;
+
+use crate::eval::{FunctionDecls, interpolate};
+use crate::{DialogueState, MortarFunctionRegistry, MortarMetadata, MortarVariableState, TextData};
+
+/// Default header format.
+///
+/// 默认的头部格式。
+#[derive(Resource, Debug, Clone, PartialEq, Eq)]
+pub struct MortarHeaderSettings {
+    /// Header used when neither the line nor its node declares one; empty for none.
+    ///
+    /// 行与节点都未声明头部时使用的头部；为空表示不显示。
+    pub format: String,
+    /// Placed between a non-empty header and the body.
+    ///
+    /// 放在非空头部与正文之间。
+    pub separator: String,
+}
+
+impl Default for MortarHeaderSettings {
+    fn default() -> Self {
+        Self {
+            format: "[{file} / {node}]".to_owned(),
+            separator: "\n\n".to_owned(),
+        }
+    }
+}
+
+/// Emitted when the header shown on a text target changes.
+///
+/// 当文本目标显示的头部发生变化时发出。
+#[derive(Message, Debug, Clone, PartialEq, Eq)]
+pub struct MortarHeaderChanged {
+    pub entity: Entity,
+    /// Resolved header, without the separator; empty when the header was removed.
+    ///
+    /// 解析后的头部（不含分隔符）；头部被移除时为空。
+    pub header: String,
+}
+
+/// Splits `template` into text and `{...}` parts for [`process_interpolated_text`].
+fn header_parts(template: &str) -> Vec<StringPart> {
+    let part = |part_type: &str, content: &str| StringPart {
+        part_type: part_type.to_owned(),
+        content: content.to_owned(),
+        function_name: None,
+        args: Vec::new(),
+        enum_type: None,
+        branches: None,
+    };
+    let mut parts = Vec::new();
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        let Some(close) = rest[open..].find('}').map(|close| open + close) else {
+            break;
+        };
+        if open > 0 {
+            parts.push(part("text", &rest[..open]));
+        }
+        let inner = rest[open + 1..close].trim();
+        let mut placeholder = part("placeholder", &rest[open..=close]);
+        if let Some((name, args)) = inner
+            .strip_suffix(')')
+            .and_then(|call| call.split_once('('))
+        {
+            placeholder.part_type = "expression".to_owned();
+            placeholder.function_name = Some(name.trim().to_owned());
+            placeholder.args = args
+                .split(',')
+                .map(str::trim)
+                .filter(|arg| !arg.is_empty())
+                .map(str::to_owned)
+                .collect();
+        }
+        parts.push(placeholder);
+        rest = &rest[close + 1..];
+    }
+    if !rest.is_empty() {
+        parts.push(part("text", rest));
+    }
+    parts
+}
+
+/// The header shown above `text_data`, separator included.
+pub(super) fn resolve_header(
+    settings: &MortarHeaderSettings,
+    metadata: Option<&MortarMetadata>,
+    state: &DialogueState,
+    text_data: &TextData,
+    functions: &MortarFunctionRegistry,
+    function_decls: FunctionDecls,
+    variable_state: &MortarVariableState,
+) -> String {
+    let template = text_data
+        .header
+        .as_deref()
+        .or_else(|| metadata.and_then(|metadata| metadata.node_header(&state.current_node)))
+        .unwrap_or(&settings.format);
+    if template.is_empty() {
+        return String::new();
+    }
+    let expanded = template
+        .replace("{file}", &state.mortar_path)
+        .replace("{node}", &state.current_node);
+    let header_data = TextData {
+        interpolated_parts: Some(header_parts(&expanded)),
+        value: expanded,
+        ..default()
+    };
+    let header = interpolate(&header_data, functions, function_decls, variable_state);
+    if header.is_empty() {
+        return header;
+    }
+    header + &settings.separator
+}
+
+/// Reports `entity` when its header goes from `previous` to `header`.
+pub(super) fn note_header_change(
+    writer: &mut MessageWriter<MortarHeaderChanged>,
+    settings: &MortarHeaderSettings,
+    entity: Entity,
+    previous: &str,
+    header: &str,
+) {
+    if previous == header {
+        return;
+    }
+    let header = header
+        .strip_suffix(settings.separator.as_str())
+        .unwrap_or(header);
+    writer.write(MortarHeaderChanged {
+        entity,
+        header: header.to_owned(),
+    });
+}
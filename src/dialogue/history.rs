@@ -0,0 +1,407 @@
+//! # history.rs
+//!
+//! # history.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Time-travel debugging for the primary dialogue. When [`MortarStateHistory`] is enabled, every
+//! frame that changed the runtime, the variables, or emitted a lifecycle or game event pushes a
+//! compact [`MortarStateRecord`] into a bounded ring buffer: where the dialogue was, the choice
+//! stack, whether runs were executing, the variable revision with the variables that changed, and
+//! the events of that frame. Records can be compared with [`MortarStateHistory::diff`] or printed
+//! with [`MortarStateHistory::dump_pretty`]. File paths, node names and event names are interned
+//! and evicted records are reused, so a full buffer stops allocating.
+//!
+//! 主对话的时间回溯调试。启用 [`MortarStateHistory`] 后，每个改变了运行时、变量，或发出了生命周期或
+//! 游戏事件的帧，都会向有界环形缓冲区推入一条紧凑的 [`MortarStateRecord`]：对话所处位置、选项栈、
+//! run 是否正在执行、变量修订号及发生变化的变量，以及该帧的事件。记录可以用
+//! [`MortarStateHistory::diff`] 比较，或用 [`MortarStateHistory::dump_pretty`] 打印。文件路径、
+//! 节点名与事件名会被驻留，被淘汰的记录会被复用，因此缓冲区写满后不再分配内存。
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt::Write as _;
+use std::sync::Arc;
+
+use super::{MortarDialogueVariables, MortarGameEvent, MortarRunsExecuting};
+use crate::{
+    MortarDialogueFinished, MortarDialogueStarted, MortarNodeEntered, MortarRuntime,
+    MortarVariableState, MortarVariableValue,
+};
+
+/// Records kept by a [`MortarStateHistory`] unless configured otherwise.
+///
+/// [`MortarStateHistory`] 默认保留的记录数量。
+pub const DEFAULT_STATE_HISTORY_CAPACITY: usize = 256;
+
+/// An event emitted during a recorded frame.
+///
+/// 被记录帧中发出的事件。
+#[derive(Debug, Clone, PartialEq)]
+pub enum MortarHistoryEvent {
+    DialogueStarted { node: Arc<str> },
+    NodeEntered { node: Arc<str>, entry_index: usize },
+    DialogueFinished { node: Arc<str> },
+    Game { name: Arc<str>, args: Vec<String> },
+}
+
+/// The primary dialogue as it stood at the end of one frame.
+///
+/// 某一帧结束时主对话的状态。
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MortarStateRecord {
+    /// Frame counted from when the history was enabled.
+    ///
+    /// 自启用历史记录起计算的帧序号。
+    pub frame: u64,
+    /// `None` when no dialogue was active.
+    ///
+    /// 没有活动对话时为 `None`。
+    pub mortar_path: Option<Arc<str>>,
+    pub node: Option<Arc<str>>,
+    pub text_index: usize,
+    /// Cursor of the line, see [`crate::LineCursor`]; the default one when no dialogue was
+    /// active.
+    ///
+    /// 该行的游标，参见 [`crate::LineCursor`]；没有活动对话时为默认游标。
+    pub cursor: crate::LineCursor,
+    pub choice_stack: Vec<usize>,
+    pub selected_choice: Option<usize>,
+    pub runs_executing: bool,
+    pub variable_revision: u64,
+    /// Variables whose value differs from the previous record.
+    ///
+    /// 与上一条记录相比值发生变化的变量。
+    pub variable_changes: Vec<(Arc<str>, MortarVariableValue)>,
+    pub events: Vec<MortarHistoryEvent>,
+}
+
+impl MortarStateRecord {
+    fn clear(&mut self) {
+        self.mortar_path = None;
+        self.node = None;
+        self.text_index = 0;
+        self.cursor = crate::LineCursor::default();
+        self.choice_stack.clear();
+        self.selected_choice = None;
+        self.runs_executing = false;
+        self.variable_revision = 0;
+        self.variable_changes.clear();
+        self.events.clear();
+    }
+
+    fn same_position(&self, other: &Self) -> bool {
+        self.mortar_path == other.mortar_path
+            && self.node == other.node
+            && self.cursor == other.cursor
+            && self.choice_stack == other.choice_stack
+            && self.selected_choice == other.selected_choice
+            && self.runs_executing == other.runs_executing
+            && self.variable_revision == other.variable_revision
+    }
+}
+
+impl std::fmt::Display for MortarHistoryEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::DialogueStarted { node } => write!(f, "+started({node})"),
+            Self::NodeEntered { node, entry_index } => write!(f, "+entered({node}@{entry_index})"),
+            Self::DialogueFinished { node } => write!(f, "+finished({node})"),
+            Self::Game { name, args } => write!(f, "+{name}({})", args.join(", ")),
+        }
+    }
+}
+
+impl std::fmt::Display for MortarStateRecord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#{:<5} ", self.frame)?;
+        match (&self.mortar_path, &self.node) {
+            (Some(path), Some(node)) => write!(f, "{path} / {node} @ {}", self.text_index)?,
+            _ => f.write_str("(idle)")?,
+        }
+        if !self.choice_stack.is_empty() {
+            write!(f, " choices={:?}", self.choice_stack)?;
+        }
+        if let Some(selected) = self.selected_choice {
+            write!(f, " selected={selected}")?;
+        }
+        if self.runs_executing {
+            f.write_str(" runs")?;
+        }
+        write!(f, " rev={}", self.variable_revision)?;
+        for (name, value) in &self.variable_changes {
+            write!(f, " {name}={}", value.to_display_string())?;
+        }
+        for event in &self.events {
+            write!(f, " {event}")?;
+        }
+        Ok(())
+    }
+}
+
+/// What changed between two records; each field is `(before, after)` and `None` when unchanged.
+///
+/// 两条记录之间的变化；每个字段为 `(之前, 之后)`，未变化时为 `None`。
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MortarStateDiff {
+    pub mortar_path: Option<(Option<Arc<str>>, Option<Arc<str>>)>,
+    pub node: Option<(Option<Arc<str>>, Option<Arc<str>>)>,
+    pub text_index: Option<(usize, usize)>,
+    pub choice_stack: Option<(Vec<usize>, Vec<usize>)>,
+    pub selected_choice: Option<(Option<usize>, Option<usize>)>,
+    pub runs_executing: Option<(bool, bool)>,
+    /// Latest value of every variable changed after the first record, up to the second.
+    ///
+    /// 第一条记录之后直至第二条记录期间变化的每个变量的最新值。
+    pub variables: Vec<(Arc<str>, MortarVariableValue)>,
+}
+
+impl MortarStateDiff {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+fn changed<T: Clone + PartialEq>(before: &T, after: &T) -> Option<(T, T)> {
+    (before != after).then(|| (before.clone(), after.clone()))
+}
+
+/// Opt-in ring buffer of recent runtime states.
+///
+/// 可选启用的近期运行时状态环形缓冲区。
+#[derive(Resource, Debug)]
+pub struct MortarStateHistory {
+    /// Off by default; recording costs a little every frame.
+    ///
+    /// 默认关闭；记录会在每帧带来少量开销。
+    pub enabled: bool,
+    capacity: usize,
+    records: VecDeque<MortarStateRecord>,
+    frame: u64,
+    strings: HashSet<Arc<str>>,
+    values: HashMap<Arc<str>, MortarVariableValue>,
+    scratch: MortarStateRecord,
+}
+
+impl Default for MortarStateHistory {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            capacity: DEFAULT_STATE_HISTORY_CAPACITY,
+            records: VecDeque::new(),
+            frame: 0,
+            strings: HashSet::new(),
+            values: HashMap::new(),
+            scratch: MortarStateRecord::default(),
+        }
+    }
+}
+
+impl MortarStateHistory {
+    /// An enabled history keeping the last `capacity` records.
+    ///
+    /// 保留最近 `capacity` 条记录的已启用历史。
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            enabled: true,
+            capacity: capacity.max(1),
+            ..Self::default()
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Changes the capacity, dropping the oldest records that no longer fit.
+    ///
+    /// 修改容量，并丢弃放不下的最旧记录。
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity.max(1);
+        let excess = self.records.len().saturating_sub(self.capacity);
+        self.records.drain(..excess);
+    }
+
+    /// Records from oldest to newest.
+    ///
+    /// 从旧到新的记录。
+    pub fn records(&self) -> &VecDeque<MortarStateRecord> {
+        &self.records
+    }
+
+    pub fn latest(&self) -> Option<&MortarStateRecord> {
+        self.records.back()
+    }
+
+    pub fn clear(&mut self) {
+        self.records.clear();
+        self.values.clear();
+    }
+
+    /// Compares the records at positions `a` and `b` of [`Self::records`].
+    ///
+    /// 比较 [`Self::records`] 中位置 `a` 与 `b` 的记录。
+    pub fn diff(&self, a: usize, b: usize) -> Option<MortarStateDiff> {
+        let before = self.records.get(a)?;
+        let after = self.records.get(b)?;
+        let mut variables: Vec<(Arc<str>, MortarVariableValue)> = Vec::new();
+        for record in self.records.range(a.min(b) + 1..=a.max(b)) {
+            for (name, value) in &record.variable_changes {
+                match variables.iter_mut().find(|(known, _)| known == name) {
+                    Some(entry) => entry.1 = value.clone(),
+                    None => variables.push((name.clone(), value.clone())),
+                }
+            }
+        }
+        Some(MortarStateDiff {
+            mortar_path: changed(&before.mortar_path, &after.mortar_path),
+            node: changed(&before.node, &after.node),
+            text_index: changed(&before.text_index, &after.text_index),
+            choice_stack: changed(&before.choice_stack, &after.choice_stack),
+            selected_choice: changed(&before.selected_choice, &after.selected_choice),
+            runs_executing: changed(&before.runs_executing, &after.runs_executing),
+            variables,
+        })
+    }
+
+    /// One line per record, oldest first.
+    ///
+    /// 每条记录一行，最旧的在前。
+    pub fn dump_pretty(&self) -> String {
+        let mut out = String::new();
+        for record in &self.records {
+            let _ = writeln!(out, "{record}");
+        }
+        out
+    }
+
+    fn intern(&mut self, value: &str) -> Arc<str> {
+        if let Some(interned) = self.strings.get(value) {
+            return interned.clone();
+        }
+        let interned: Arc<str> = Arc::from(value);
+        self.strings.insert(interned.clone());
+        interned
+    }
+
+    /// Collects the variables whose value moved since they were last seen.
+    fn diff_variables(
+        &mut self,
+        state: &MortarVariableState,
+        changes: &mut Vec<(Arc<str>, MortarVariableValue)>,
+    ) {
+        for (name, value) in state.values() {
+            if self.values.get(name) == Some(value) {
+                continue;
+            }
+            let name = self.intern(name);
+            self.values.insert(name.clone(), value.clone());
+            changes.push((name, value.clone()));
+        }
+    }
+
+    /// Reuses the oldest record once the buffer is full.
+    fn push(&mut self, record: &MortarStateRecord) {
+        let mut slot = if self.records.len() >= self.capacity {
+            self.records.pop_front().unwrap_or_default()
+        } else {
+            MortarStateRecord::default()
+        };
+        slot.clone_from(record);
+        self.records.push_back(slot);
+    }
+}
+
+#[derive(SystemParam)]
+pub(super) struct HistoryMessages<'w, 's> {
+    started: MessageReader<'w, 's, MortarDialogueStarted>,
+    entered: MessageReader<'w, 's, MortarNodeEntered>,
+    finished: MessageReader<'w, 's, MortarDialogueFinished>,
+    game_events: MessageReader<'w, 's, MortarGameEvent>,
+}
+
+impl HistoryMessages<'_, '_> {
+    fn clear(&mut self) {
+        self.started.clear();
+        self.entered.clear();
+        self.finished.clear();
+        self.game_events.clear();
+    }
+}
+
+/// Pushes a record for every frame that changed something.
+pub(super) fn record_state_history(
+    mut history: ResMut<MortarStateHistory>,
+    runtime: Res<MortarRuntime>,
+    runs: Res<MortarRunsExecuting>,
+    variables: Res<MortarDialogueVariables>,
+    mut messages: HistoryMessages,
+) {
+    if !history.enabled {
+        messages.clear();
+        return;
+    }
+    let history = history.as_mut();
+    history.frame += 1;
+    let mut record = std::mem::take(&mut history.scratch);
+    record.clear();
+    record.frame = history.frame;
+
+    if let Some(state) = runtime.primary_dialogue_state() {
+        record.mortar_path = Some(history.intern(&state.mortar_path));
+        record.node = Some(history.intern(&state.current_node));
+        record.text_index = state.text_index;
+        record.cursor = state.cursor();
+        record.choice_stack.extend_from_slice(&state.choice_stack);
+        record.selected_choice = state.selected_choice;
+    }
+    record.runs_executing = runs.executing;
+
+    if let Some(state) = &variables.state {
+        record.variable_revision = state.revision();
+        if variables.is_changed() {
+            history.diff_variables(state, &mut record.variable_changes);
+        }
+    }
+
+    for event in messages.started.read() {
+        let node = history.intern(&event.node);
+        record
+            .events
+            .push(MortarHistoryEvent::DialogueStarted { node });
+    }
+    for event in messages.entered.read() {
+        let node = history.intern(&event.node);
+        record.events.push(MortarHistoryEvent::NodeEntered {
+            node,
+            entry_index: event.entry_index,
+        });
+    }
+    for event in messages.finished.read() {
+        let node = history.intern(&event.node);
+        record
+            .events
+            .push(MortarHistoryEvent::DialogueFinished { node });
+    }
+    for event in messages.game_events.read() {
+        let name = history.intern(&event.name);
+        record.events.push(MortarHistoryEvent::Game {
+            name,
+            args: event.args.clone(),
+        });
+    }
+
+    let unchanged = record.variable_changes.is_empty()
+        && record.events.is_empty()
+        && history
+            .records
+            .back()
+            .is_some_and(|last| last.same_position(&record));
+    if (runtime.is_changed() || !record.events.is_empty()) && !unchanged {
+        history.push(&record);
+    }
+    history.scratch = record;
+}
@@ -0,0 +1,135 @@
+//! # icons.rs
+//!
+//! # icons.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Handles inline icon tokens such as `Press {icon:button_a} to jump`. Each token is replaced in
+//! the body by a single placeholder character, so it takes exactly one index unit for events and
+//! reveal counts, and is recorded as an [`InlineIcon`] on [`MortarDialogueText`] for the UI. With
+//! the `ui-icons` feature, [`MortarIconPlugin`] places image nodes over the placeholders.
+//!
+//! 处理诸如 `Press {icon:button_a} to jump` 的行内图标标记。每个标记在正文中被替换为单个占位字符，
+//! 因此在事件索引和逐字显示计数中恰好占一个单位，并作为 [`InlineIcon`] 记录到
+//! [`MortarDialogueText`] 上供 UI 使用。启用 `ui-icons` 功能后，[`MortarIconPlugin`]
+//! 会在占位字符处放置图片节点。
+
+use bevy::prelude::*;
+
+use super::MortarDialogueText;
+
+#[cfg(feature = "ui-icons")]
+mod render;
+
+#[cfg(feature = "ui-icons")]
+pub use render::{
+    MISSING_ICON_GLYPH, MortarIconAtlas, MortarIconDiagnostics, MortarIconPlugin, MortarInlineIcon,
+};
+
+const ICON_PREFIX: &str = "{icon:";
+
+/// Default placeholder: U+FFFC OBJECT REPLACEMENT CHARACTER.
+///
+/// 默认占位字符：U+FFFC 对象替换字符。
+pub const DEFAULT_ICON_PLACEHOLDER: char = '\u{FFFC}';
+
+/// An icon placed inside the body of a line.
+///
+/// 放置在行正文中的图标。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InlineIcon {
+    /// Index of the placeholder character in the body, counted in chars.
+    ///
+    /// 占位字符在正文中的索引，按字符计数。
+    pub char_index: usize,
+    /// Name from the `{icon:NAME}` token.
+    ///
+    /// `{icon:NAME}` 标记中的名称。
+    pub name: String,
+}
+
+/// Settings for inline icon tokens.
+///
+/// 行内图标标记的设置。
+#[derive(Resource, Debug, Clone, PartialEq, Eq)]
+pub struct MortarIconSettings {
+    /// Character that replaces each token in the body.
+    ///
+    /// 在正文中替换每个标记的字符。
+    pub placeholder: char,
+}
+
+impl Default for MortarIconSettings {
+    fn default() -> Self {
+        Self {
+            placeholder: DEFAULT_ICON_PLACEHOLDER,
+        }
+    }
+}
+
+/// Whether the name inside an interpolation placeholder, such as `icon:button_a`, is an icon.
+pub(crate) fn is_icon_token(name: &str) -> bool {
+    name.starts_with(&ICON_PREFIX[1..])
+}
+
+/// Replaces every `{icon:NAME}` token in `text` by `placeholder` and returns the icons found.
+/// Malformed tokens (empty name, whitespace, no closing brace) stay as written.
+///
+/// 将 `text` 中的每个 `{icon:NAME}` 标记替换为 `placeholder`，并返回找到的图标。
+/// 格式不正确的标记（名称为空、包含空白或缺少右花括号）保持原样。
+pub fn extract_inline_icons(text: &str, placeholder: char) -> (String, Vec<InlineIcon>) {
+    let mut body = String::with_capacity(text.len());
+    let mut icons = Vec::new();
+    let mut chars = 0;
+    let mut rest = text;
+    while let Some(start) = rest.find(ICON_PREFIX) {
+        let before = &rest[..start];
+        body.push_str(before);
+        chars += before.chars().count();
+
+        let after = &rest[start + ICON_PREFIX.len()..];
+        let name = after.find('}').map(|end| &after[..end]);
+        match name {
+            Some(name) if !name.is_empty() && !name.contains(char::is_whitespace) => {
+                icons.push(InlineIcon {
+                    char_index: chars,
+                    name: name.to_owned(),
+                });
+                body.push(placeholder);
+                chars += 1;
+                rest = &after[name.len() + 1..];
+            }
+            _ => {
+                body.push_str(ICON_PREFIX);
+                chars += ICON_PREFIX.chars().count();
+                rest = after;
+            }
+        }
+    }
+    body.push_str(rest);
+    (body, icons)
+}
+
+/// Builds the dialogue text of a processed line, extracting its icons and reveal checkpoints.
+pub(super) fn dialogue_text(
+    header: String,
+    processed: &str,
+    line_id: &str,
+    events: &[mortar_compiler::Event],
+    settings: &MortarIconSettings,
+) -> MortarDialogueText {
+    let (body, mut icons) = extract_inline_icons(processed, settings.placeholder);
+    let (body, checkpoints) = super::reveal::extract_checkpoints(&body, &mut icons, events);
+    MortarDialogueText {
+        header,
+        body,
+        line_id: line_id.to_owned(),
+        cursor: crate::LineCursor::default(),
+        icons,
+        checkpoints,
+        speakable: String::new(),
+        voice: None,
+    }
+}
@@ -0,0 +1,199 @@
+//! # render.rs
+//!
+//! # render.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Optional rendering helper for inline icons (feature `ui-icons`). For every UI
+//! [`MortarTextTarget`] it spawns one child per [`InlineIcon`](super::InlineIcon), an
+//! `ImageNode` taken from [`MortarIconAtlas`] or a warning glyph for unknown names, and keeps each
+//! child over its placeholder glyph after text layout. Icons stay hidden until their placeholder
+//! is revealed.
+//!
+//! 行内图标的可选渲染辅助（`ui-icons` 功能）。它为每个 UI [`MortarTextTarget`] 按
+//! [`InlineIcon`](super::InlineIcon) 生成一个子实体：取自 [`MortarIconAtlas`] 的 `ImageNode`，
+//! 未知名称则使用警告字形；并在文本布局之后让每个子实体保持在其占位字形的位置上。图标在其占位字符
+//! 显示之前保持隐藏。
+
+use bevy::prelude::*;
+use bevy::text::TextLayoutInfo;
+use bevy::ui::UiSystems;
+use std::collections::{BTreeSet, HashMap};
+
+use crate::debug::LOG_DIALOGUE;
+use crate::dialogue::{MortarDialogueSystemSet, MortarDialogueText, MortarTextTarget};
+
+/// Glyph shown for icon names missing from [`MortarIconAtlas`].
+///
+/// [`MortarIconAtlas`] 中不存在的图标名称所显示的字形。
+pub const MISSING_ICON_GLYPH: &str = "\u{26A0}";
+
+/// Maps icon names to images.
+///
+/// 图标名称到图片的映射。
+#[derive(Resource, Debug, Clone)]
+pub struct MortarIconAtlas {
+    pub icons: HashMap<String, Handle<Image>>,
+    /// Size of every icon in logical pixels.
+    ///
+    /// 每个图标的逻辑像素尺寸。
+    pub size: Vec2,
+}
+
+impl Default for MortarIconAtlas {
+    fn default() -> Self {
+        Self {
+            icons: HashMap::new(),
+            size: Vec2::splat(24.0),
+        }
+    }
+}
+
+impl MortarIconAtlas {
+    pub fn insert(&mut self, name: impl Into<String>, image: Handle<Image>) -> &mut Self {
+        self.icons.insert(name.into(), image);
+        self
+    }
+}
+
+/// Icon names that were used in dialogue but are missing from [`MortarIconAtlas`].
+///
+/// 在对话中使用但 [`MortarIconAtlas`] 中不存在的图标名称。
+#[derive(Resource, Debug, Clone, Default)]
+pub struct MortarIconDiagnostics {
+    pub unknown: BTreeSet<String>,
+}
+
+/// A child of a [`MortarTextTarget`] showing one inline icon.
+///
+/// [`MortarTextTarget`] 的子实体，显示一个行内图标。
+#[derive(Component, Reflect, Debug, Clone, PartialEq, Eq)]
+#[reflect(Component)]
+pub struct MortarInlineIcon {
+    /// Index of the placeholder in the body.
+    ///
+    /// 占位字符在正文中的索引。
+    pub char_index: usize,
+    pub name: String,
+}
+
+/// Spawns and positions inline icon images over UI dialogue text.
+///
+/// 在 UI 对话文本上生成并定位行内图标图片。
+pub struct MortarIconPlugin;
+
+impl Plugin for MortarIconPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MortarIconAtlas>()
+            .init_resource::<MortarIconDiagnostics>()
+            .add_systems(
+                Update,
+                spawn_inline_icons.after(MortarDialogueSystemSet::UpdateText),
+            )
+            .add_systems(
+                PostUpdate,
+                position_inline_icons.after(UiSystems::PostLayout),
+            );
+    }
+}
+
+/// Replaces the icon children of every target whose line changed.
+fn spawn_inline_icons(
+    mut commands: Commands,
+    targets: Query<
+        (Entity, &MortarDialogueText, Option<&Children>),
+        (With<MortarTextTarget>, Changed<MortarDialogueText>),
+    >,
+    existing: Query<(), With<MortarInlineIcon>>,
+    atlas: Res<MortarIconAtlas>,
+    mut diagnostics: ResMut<MortarIconDiagnostics>,
+) {
+    for (entity, dialogue_text, children) in &targets {
+        for child in children.into_iter().flatten() {
+            if existing.contains(*child) {
+                commands.entity(*child).despawn();
+            }
+        }
+        for icon in &dialogue_text.icons {
+            let marker = MortarInlineIcon {
+                char_index: icon.char_index,
+                name: icon.name.clone(),
+            };
+            let node = Node {
+                position_type: PositionType::Absolute,
+                width: Val::Px(atlas.size.x),
+                height: Val::Px(atlas.size.y),
+                ..default()
+            };
+            let mut child = commands.spawn((
+                marker,
+                node,
+                Visibility::Hidden,
+                ChildOf(entity),
+                crate::MortarInternal,
+            ));
+            if let Some(image) = atlas.icons.get(&icon.name) {
+                child.insert(ImageNode::new(image.clone()));
+                continue;
+            }
+            child.insert(Text::new(MISSING_ICON_GLYPH));
+            if diagnostics.unknown.insert(icon.name.clone()) {
+                warn!(
+                    target: LOG_DIALOGUE,
+                    "Unknown inline icon '{}' in line {}",
+                    icon.name,
+                    dialogue_text.line_id
+                );
+            }
+        }
+    }
+}
+
+/// Paragraph and byte offset inside it of the `char_index`-th char of `text`.
+fn glyph_key(text: &str, char_index: usize) -> Option<(usize, usize)> {
+    let (offset, _) = text.char_indices().nth(char_index)?;
+    let before = &text[..offset];
+    let paragraph = before.matches('\n').count();
+    let start = before.rfind('\n').map_or(0, |newline| newline + 1);
+    Some((paragraph, offset - start))
+}
+
+/// Moves each icon over its placeholder glyph, hiding icons whose placeholder is not shown.
+fn position_inline_icons(
+    targets: Query<
+        (&Text, &MortarDialogueText, &TextLayoutInfo, &Children),
+        With<MortarTextTarget>,
+    >,
+    mut icons: Query<(&MortarInlineIcon, &mut Node, &mut Visibility)>,
+    atlas: Res<MortarIconAtlas>,
+) {
+    for (text, dialogue_text, layout, children) in &targets {
+        let header_chars = dialogue_text.header.chars().count();
+        for child in children {
+            let Ok((icon, mut node, mut visibility)) = icons.get_mut(*child) else {
+                continue;
+            };
+            let glyph =
+                glyph_key(&text.0, header_chars + icon.char_index).and_then(|(paragraph, byte)| {
+                    layout
+                        .glyphs
+                        .iter()
+                        .find(|glyph| glyph.line_index == paragraph && glyph.byte_index == byte)
+                });
+            let Some(glyph) = glyph else {
+                visibility.set_if_neq(Visibility::Hidden);
+                continue;
+            };
+            let center = glyph.position / layout.scale_factor.max(f32::EPSILON);
+            let left = Val::Px(center.x - atlas.size.x / 2.0);
+            let top = Val::Px(center.y - atlas.size.y / 2.0);
+            if node.left != left || node.top != top {
+                node.left = left;
+                node.top = top;
+            }
+            visibility.set_if_neq(Visibility::Inherited);
+        }
+    }
+}
@@ -0,0 +1,222 @@
+//! # line_explanation.rs
+//!
+//! # line_explanation.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Explains why the current line reads the way it does, for debug overlays and console commands.
+//! The text system records what it already worked out while rendering the line: lines skipped on
+//! the way, the node's function overrides, the line's condition with the values of its operands,
+//! what each placeholder rendered to and where each event was mapped.
+//! [`MortarRuntime::explain_current_line`] formats that record; nothing is evaluated again, so
+//! bound functions are never called a second time.
+//!
+//! 解释当前行为何呈现为现在的样子，供调试覆盖层与控制台命令使用。文本系统会记录渲染该行时已经
+//! 得出的信息：途中被跳过的行、节点的函数覆盖、该行的条件及其操作数的值、每个占位符渲染出的
+//! 内容，以及每个事件被映射到的位置。[`MortarRuntime::explain_current_line`] 负责格式化这份记录；
+//! 不会重新求值，因此绑定函数不会被再次调用。
+
+use std::fmt;
+use std::fmt::Write;
+
+use bevy::log::debug;
+use mortar_compiler::Event;
+use mortar_compiler::IfCondition;
+
+use crate::MortarVariableState;
+use crate::debug::LOG_DIALOGUE;
+use crate::{DialogueState, MortarRuntime};
+
+/// What the text system worked out while rendering a line.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct LineExplanation {
+    path: String,
+    node: String,
+    text_index: usize,
+    line_id: String,
+    /// The node's function overrides, sorted by the overridden name.
+    overrides: Vec<(String, String)>,
+    /// Lines passed over before this one, with the reason.
+    pub(super) skipped: Vec<String>,
+    /// The line's condition, with operand values, and its result.
+    pub(super) condition: Option<(String, bool)>,
+    /// Each expression and placeholder with the text it rendered to.
+    pub(super) parts: Vec<(String, String)>,
+    /// Actions of each text event at the index it was mapped to.
+    pub(super) events: Vec<(f64, String)>,
+    pub(super) text: String,
+}
+
+impl LineExplanation {
+    pub(super) fn new(state: &DialogueState, line_id: &str) -> Self {
+        let mut overrides: Vec<_> = state
+            .overrides()
+            .iter()
+            .map(|(name, target)| (name.clone(), target.clone()))
+            .collect();
+        overrides.sort();
+        Self {
+            path: state.mortar_path.clone(),
+            node: state.current_node.clone(),
+            text_index: state.text_index,
+            line_id: line_id.to_owned(),
+            overrides,
+            ..Self::default()
+        }
+    }
+
+    pub(super) fn record_events(&mut self, events: &[Event]) {
+        self.events = events
+            .iter()
+            .map(|event| {
+                let actions: Vec<String> = event
+                    .actions
+                    .iter()
+                    .map(|action| format!("{}({})", action.action_type, action.args.join(", ")))
+                    .collect();
+                (event.index, actions.join(", "))
+            })
+            .collect();
+    }
+
+    fn describes(&self, state: &DialogueState) -> bool {
+        self.path == state.mortar_path
+            && self.node == state.current_node
+            && self.text_index == state.text_index
+    }
+}
+
+impl fmt::Display for LineExplanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Line {} of node '{}' in '{}'",
+            self.text_index, self.node, self.path
+        )?;
+        if !self.line_id.is_empty() {
+            write!(f, " ({})", self.line_id)?;
+        }
+        writeln!(f, ": {:?}", self.text)?;
+        for (name, target) in &self.overrides {
+            writeln!(f, "  override {name} -> {target}")?;
+        }
+        for skipped in &self.skipped {
+            writeln!(f, "  skipped {skipped}")?;
+        }
+        match &self.condition {
+            Some((condition, result)) => writeln!(
+                f,
+                "  condition {condition} -> {result}, {} branch shown",
+                if *result { "this" } else { "no" }
+            )?,
+            None => writeln!(f, "  no condition")?,
+        }
+        for (part, value) in &self.parts {
+            writeln!(f, "  {part} -> {value:?}")?;
+        }
+        for (index, actions) in &self.events {
+            writeln!(f, "  event at index {index}: {actions}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Notes a line skipped without being shown for [`MortarRuntime::explain_current_line`], and logs
+/// it if enabled in [`crate::MortarLogConfig`].
+pub(super) fn note_skipped_line(
+    config: &crate::MortarLogConfig,
+    state: &DialogueState,
+    reason: &str,
+    skipped: &mut Vec<String>,
+) {
+    skipped.push(format!("line {}: {}", state.text_index, reason));
+    if config.skipped_lines {
+        debug!(
+            target: LOG_DIALOGUE,
+            "Skipping line {} of node '{}': {}",
+            state.text_index, state.current_node, reason
+        );
+    }
+}
+
+/// Writes a line condition in script form, with the current value of each variable it reads.
+/// Function calls are written as calls; their results are not known without calling them again.
+pub(crate) fn describe_condition(
+    condition: &IfCondition,
+    variables: &MortarVariableState,
+) -> String {
+    let mut out = String::new();
+    write_condition(&mut out, condition, variables, false);
+    out
+}
+
+fn write_condition(
+    out: &mut String,
+    condition: &IfCondition,
+    variables: &MortarVariableState,
+    nested: bool,
+) {
+    let value = condition.value.as_deref().unwrap_or_default();
+    match condition.cond_type.as_str() {
+        "binary" => {
+            let (Some(left), Some(right)) = (&condition.left, &condition.right) else {
+                out.push('?');
+                return;
+            };
+            if nested {
+                out.push('(');
+            }
+            write_condition(out, left, variables, true);
+            let _ = write!(out, " {} ", condition.operator.as_deref().unwrap_or("?"));
+            write_condition(out, right, variables, true);
+            if nested {
+                out.push(')');
+            }
+        }
+        "unary" => {
+            out.push_str(condition.operator.as_deref().unwrap_or("?"));
+            if let Some(operand) = &condition.operand {
+                write_condition(out, operand, variables, true);
+            }
+        }
+        "func_call" => {
+            let name = condition
+                .operand
+                .as_ref()
+                .and_then(|operand| operand.value.as_deref())
+                .unwrap_or(value);
+            let args = condition
+                .right
+                .as_ref()
+                .and_then(|right| right.value.as_deref())
+                .unwrap_or_default();
+            let _ = write!(out, "{name}({args})");
+        }
+        "identifier" => match variables.get(value) {
+            Some(current) => {
+                let _ = write!(out, "{value} [= {}]", current.to_display_string());
+            }
+            None => out.push_str(value),
+        },
+        _ => out.push_str(value),
+    }
+}
+
+impl MortarRuntime {
+    /// A multi-line explanation of the primary dialogue's current line: lines skipped on the
+    /// way, its condition with operand values and result, what each placeholder rendered to and
+    /// where each event was mapped. `None` until the line has been rendered. Works without the
+    /// `verbose-debug` feature and never calls bound functions.
+    ///
+    /// 主对话当前行的多行说明：途中被跳过的行、其条件（含操作数的值）与结果、每个占位符渲染出的
+    /// 内容，以及每个事件被映射到的位置。该行被渲染之前返回 `None`。不依赖 `verbose-debug` 功能，
+    /// 也不会调用绑定函数。
+    pub fn explain_current_line(&self) -> Option<String> {
+        let explanation = self.line_explanation.as_ref()?;
+        explanation
+            .describes(self.primary_dialogue_state()?)
+            .then(|| explanation.to_string())
+    }
+}
@@ -0,0 +1,181 @@
+//! # line_group.rs
+//!
+//! # line_group.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Renders a group of consecutive `line` items into one body: each line is checked against its
+//! condition, runs its assignments and is interpolated, and the lines that produced text are
+//! joined with `\n`.
+//!
+//! 将一组连续的 `line` 项渲染为同一段正文：每一行先检查条件、执行赋值并完成插值，产生了文本的行再用
+//! `\n` 拼接起来。
+
+use crate::eval::{FunctionDecls, interpolate};
+use crate::{MortarVariableState, evaluate_if_condition};
+
+/// Processes a line group: evaluates conditions per-line, processes interpolation,
+/// and joins passing lines with `\n`. Returns `None` if all lines are empty/skipped.
+///
+/// 处理 line 组：逐行评估条件、处理插值，用 `\n` 拼接通过的行。
+pub(super) fn process_line_group<'a>(
+    group: impl IntoIterator<Item = &'a crate::TextData>,
+    functions: &crate::MortarFunctionRegistry,
+    func_decls: FunctionDecls,
+    variable_state: &mut MortarVariableState,
+) -> Option<String> {
+    let mut result_lines = Vec::new();
+    for line_data in group {
+        if let Some(condition) = &line_data.condition
+            && !evaluate_if_condition(condition, functions, variable_state)
+        {
+            continue;
+        }
+        variable_state.execute_statements(&line_data.pre_statements);
+        let line_text = interpolate(line_data, functions, func_decls, variable_state);
+        if !line_text.is_empty() {
+            result_lines.push(line_text);
+        }
+    }
+    if result_lines.is_empty() {
+        return None;
+    }
+    Some(result_lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{TextData, binder::MortarFunctionRegistry};
+
+    fn make_line(value: &str) -> TextData {
+        TextData {
+            value: value.to_string(),
+            interpolated_parts: None,
+            condition: None,
+            pre_statements: vec![],
+            events: None,
+            is_line: true,
+            line_id: String::new(),
+            header: None,
+            experiment: None,
+            requires: Vec::new(),
+            channel: None,
+            parallel: Vec::new(),
+        }
+    }
+
+    fn make_conditional_line(value: &str, cond: mortar_compiler::IfCondition) -> TextData {
+        TextData {
+            value: value.to_string(),
+            interpolated_parts: None,
+            condition: Some(cond),
+            pre_statements: vec![],
+            events: None,
+            is_line: true,
+            line_id: String::new(),
+            header: None,
+            experiment: None,
+            requires: Vec::new(),
+            channel: None,
+            parallel: Vec::new(),
+        }
+    }
+
+    fn true_condition() -> mortar_compiler::IfCondition {
+        // A variable set to "1" evaluates to truthy
+        mortar_compiler::IfCondition {
+            cond_type: "identifier".to_string(),
+            operator: None,
+            left: None,
+            right: None,
+            operand: None,
+            value: Some("truthy_var".to_string()),
+        }
+    }
+
+    fn false_condition() -> mortar_compiler::IfCondition {
+        // A variable not set evaluates to falsy
+        mortar_compiler::IfCondition {
+            cond_type: "identifier".to_string(),
+            operator: None,
+            left: None,
+            right: None,
+            operand: None,
+            value: Some("unset_var".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_process_line_group_basic() {
+        let group = vec![make_line("Line A"), make_line("Line B")];
+        let functions = MortarFunctionRegistry::new();
+        let func_decls = FunctionDecls::default();
+        let mut vs = MortarVariableState::default();
+
+        let result = process_line_group(&group, &functions, func_decls, &mut vs);
+        assert_eq!(result, Some("Line A\nLine B".to_string()));
+    }
+
+    #[test]
+    fn test_process_line_group_single_line() {
+        let group = vec![make_line("Only line")];
+        let functions = MortarFunctionRegistry::new();
+        let func_decls = FunctionDecls::default();
+        let mut vs = MortarVariableState::default();
+
+        let result = process_line_group(&group, &functions, func_decls, &mut vs);
+        assert_eq!(result, Some("Only line".to_string()));
+    }
+
+    #[test]
+    fn test_process_line_group_all_conditions_false() {
+        let group = vec![
+            make_conditional_line("Line A", false_condition()),
+            make_conditional_line("Line B", false_condition()),
+        ];
+        let functions = MortarFunctionRegistry::new();
+        let func_decls = FunctionDecls::default();
+        let mut vs = MortarVariableState::default();
+
+        let result = process_line_group(&group, &functions, func_decls, &mut vs);
+        assert_eq!(result, None, "All conditions false → None");
+    }
+
+    #[test]
+    fn test_process_line_group_mixed_conditions() {
+        let group = vec![
+            make_line("Always shown"),
+            make_conditional_line("True line", true_condition()),
+            make_conditional_line("False line", false_condition()),
+        ];
+        let functions = MortarFunctionRegistry::new();
+        let func_decls = FunctionDecls::default();
+        let mut vs = MortarVariableState::default();
+        vs.set("truthy_var", crate::MortarVariableValue::Boolean(true));
+
+        let result = process_line_group(&group, &functions, func_decls, &mut vs);
+        assert_eq!(
+            result,
+            Some("Always shown\nTrue line".to_string()),
+            "False line should be excluded"
+        );
+    }
+
+    #[test]
+    fn test_process_line_group_empty_lines_skipped() {
+        let group = vec![make_line(""), make_line("Non-empty")];
+        let functions = MortarFunctionRegistry::new();
+        let func_decls = FunctionDecls::default();
+        let mut vs = MortarVariableState::default();
+
+        let result = process_line_group(&group, &functions, func_decls, &mut vs);
+        assert_eq!(
+            result,
+            Some("Non-empty".to_string()),
+            "Empty lines should be excluded from join"
+        );
+    }
+}
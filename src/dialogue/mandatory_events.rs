@@ -0,0 +1,172 @@
+//! # mandatory_events.rs
+//!
+//! # mandatory_events.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Text events a line must not lose. A line's events fire as its reveal reaches them, and the
+//! ones it has not reached are dropped when the dialogue moves on. That is fine for cosmetic
+//! events but not for one such as `set_flag` that a later condition reads. An event is mandatory
+//! when its entry in the line's `events` sets `"mandatory": true` or one of its actions is named in
+//! [`MortarMandatoryEvents`]. When the dialogue leaves a line, before the next line is resolved,
+//! events the binding already reached fire as usual, then the unfired mandatory ones fire in index
+//! order with [`crate::MortarCallOrigin::ForcedOnAdvance`], and the rest are dropped. Each line left
+//! with unfired events is counted in [`crate::MortarEventDiagnostics::flushes`].
+//!
+//! 一行中不能丢失的文本事件。行中的事件会在逐字显示到达时触发，对话继续后未到达的事件会被丢弃。
+//! 这对装饰性事件没有问题，但像 `set_flag` 这样会被后续条件读取的事件就不行。若事件在该行 `events`
+//! 中的条目设置了 `"mandatory": true`，或其某个动作名称列在 [`MortarMandatoryEvents`] 中，该事件即为
+//! 必需事件。对话离开一行时，在解析下一行之前，绑定已经到达的事件照常触发，然后尚未触发的必需事件
+//! 按索引顺序以 [`crate::MortarCallOrigin::ForcedOnAdvance`] 触发，其余事件被丢弃。每个带有未触发
+//! 事件而被离开的行都会计入 [`crate::MortarEventDiagnostics::flushes`]。
+
+use bevy::prelude::*;
+use std::collections::HashSet;
+
+use super::event_targets::GameEventWriter;
+use super::{MortarEventBinding, MortarEventDiagnostics, MortarTextTarget};
+use crate::debug::LOG_EVENTS;
+use crate::{DialogueState, MortarEventTracker, MortarRuntime};
+
+/// Action names whose events are mandatory on every line. Defaults to `set_flag`.
+///
+/// 在每一行中都视为必需事件的动作名称。默认为 `set_flag`。
+#[derive(Resource, Debug, Clone)]
+pub struct MortarMandatoryEvents {
+    pub actions: HashSet<String>,
+}
+
+impl Default for MortarMandatoryEvents {
+    fn default() -> Self {
+        Self {
+            actions: HashSet::from(["set_flag".to_owned()]),
+        }
+    }
+}
+
+/// How many events of a line fired on leaving it and how many were dropped.
+///
+/// 离开一行时，该行有多少事件被强制触发、多少被丢弃。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MortarEventFlush {
+    pub path: String,
+    pub node: String,
+    pub text_index: usize,
+    pub forced: usize,
+    pub suppressed: usize,
+}
+
+/// An action as the script wrote it: its name and raw arguments.
+type RawAction = (String, Vec<String>);
+
+/// Which events of the line being written are mandatory, for the trackers of its targets.
+pub(super) struct MandatoryMarks<'a> {
+    settings: &'a MortarMandatoryEvents,
+    /// Actions of the events the line's content marks mandatory.
+    marked: Vec<Vec<RawAction>>,
+    line: (String, String, usize),
+}
+
+impl<'a> MandatoryMarks<'a> {
+    pub(super) fn new(settings: &'a MortarMandatoryEvents, state: &DialogueState) -> Self {
+        let marked = state
+            .text_source(state.text_index)
+            .and_then(|content| content.get("events")?.as_array())
+            .into_iter()
+            .flatten()
+            .filter(|event| event.get("mandatory").and_then(|value| value.as_bool()) == Some(true))
+            .filter_map(|event| {
+                let actions: Vec<mortar_compiler::Action> =
+                    serde_json::from_value(event.get("actions")?.clone()).ok()?;
+                Some(raw_actions(&actions))
+            })
+            .collect();
+        Self {
+            settings,
+            marked,
+            line: (
+                state.mortar_path.clone(),
+                state.current_node.clone(),
+                state.text_index,
+            ),
+        }
+    }
+
+    /// A tracker for `events`, with each event's mandatory mark.
+    pub(super) fn tracker(&self, events: &[mortar_compiler::Event]) -> MortarEventTracker {
+        let mandatory = events
+            .iter()
+            .map(|event| {
+                event
+                    .actions
+                    .iter()
+                    .any(|action| self.settings.actions.contains(&action.action_type))
+                    || self.marked.contains(&raw_actions(&event.actions))
+            })
+            .collect();
+        let (path, node, text_index) = &self.line;
+        MortarEventTracker::new(events.to_vec())
+            .with_mandatory(mandatory)
+            .on_line(path, node, *text_index)
+    }
+}
+
+fn raw_actions(actions: &[mortar_compiler::Action]) -> Vec<RawAction> {
+    actions
+        .iter()
+        .map(|action| (action.action_type.clone(), action.args.clone()))
+        .collect()
+}
+
+pub(super) type TrackerQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static mut MortarEventTracker,
+        Option<&'static MortarEventBinding>,
+    ),
+    With<MortarTextTarget>,
+>;
+
+/// Settles the events of the lines the text targets are leaving: those the binding reached fire,
+/// then the mandatory ones it did not, and the rest are dropped.
+pub(super) fn flush_left_lines(
+    trackers: &mut TrackerQuery,
+    runtime: &MortarRuntime,
+    game_events: &mut GameEventWriter,
+    diagnostics: &mut MortarEventDiagnostics,
+) {
+    for (entity, mut tracker, binding) in trackers {
+        let reached = binding.map_or(0.0, |binding| binding.current_index);
+        let mut actions = tracker.trigger_at_index(reached, runtime);
+        let left = tracker.fire_mandatory(runtime);
+        actions.extend(left.actions);
+        for action in actions {
+            game_events.write(Some(entity), action.action_name, action.args);
+        }
+        if left.forced + left.suppressed == 0 {
+            continue;
+        }
+        let Some((path, node, text_index)) = tracker.line() else {
+            continue;
+        };
+        debug!(
+            target: LOG_EVENTS,
+            "Left line {} of node '{}' with {} mandatory events forced and {} dropped",
+            text_index,
+            node,
+            left.forced,
+            left.suppressed
+        );
+        diagnostics.flushes.push(MortarEventFlush {
+            path: path.to_owned(),
+            node: node.to_owned(),
+            text_index,
+            forced: left.forced,
+            suppressed: left.suppressed,
+        });
+    }
+}
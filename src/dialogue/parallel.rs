@@ -0,0 +1,228 @@
+//! # parallel.rs
+//!
+//! # parallel.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Renders `parallel_text` items, where several speakers talk over each other. The item is one
+//! advance step, but each voice is checked, interpolated and given events on its own, then routed
+//! to the targets whose [`MortarTextChannel`] matches its `target` or `speaker`. Every routed target
+//! reveals and fires its voice independently, and their [`MortarLineStatus`] reports the group as a
+//! whole: complete only once every voice is.
+//!
+//! 渲染多个角色同时说话的 `parallel_text` 项。该项只占一次推进，但每个声部会各自检查条件、插值并
+//! 收集事件，再路由到 [`MortarTextChannel`] 与其 `target` 或 `speaker` 相同的目标上。被路由的目标
+//! 各自独立显示并触发其声部的事件，而它们的 [`MortarLineStatus`] 反映整组的状态：只有所有声部都
+//! 显示完毕才算完成。
+
+use bevy::prelude::*;
+use mortar_compiler::Event;
+
+#[cfg(feature = "ui")]
+use super::MortarLineStatus;
+use super::experiments::MortarExperimentTag;
+use super::text_events::collect_text_events;
+use super::{MortarDialogueText, MortarIconSettings, MortarTextChannel, icons};
+use crate::eval::{FunctionDecls, interpolate};
+use crate::{
+    LineCursor, MortarAsset, MortarCapabilities, MortarFunctionRegistry, MortarVariableState,
+    TextData, evaluate_if_condition,
+};
+
+/// A voice that passed its checks, ready to be routed.
+pub(super) struct RenderedVoice {
+    /// Header and body before icon extraction, for targets showing every voice.
+    full: String,
+    text: MortarDialogueText,
+    events: Vec<Event>,
+}
+
+/// Everything the voices of a parallel item are rendered with.
+pub(super) struct VoiceContext<'a> {
+    pub(super) functions: &'a MortarFunctionRegistry,
+    pub(super) func_decls: FunctionDecls<'a>,
+    pub(super) asset: Option<&'a MortarAsset>,
+    pub(super) node_data: &'a mortar_compiler::Node,
+    pub(super) capabilities: &'a MortarCapabilities,
+    pub(super) icon_settings: &'a MortarIconSettings,
+}
+
+/// Renders the voices this player sees, in order, each with the header `header` gives it;
+/// `experiment_hidden` says why an experiment hides a voice.
+pub(super) fn render_voices(
+    voices: &[TextData],
+    context: &VoiceContext,
+    mut experiment_hidden: impl FnMut(Option<&MortarExperimentTag>) -> Option<String>,
+    variable_state: &mut MortarVariableState,
+    header: impl Fn(&TextData, &MortarVariableState) -> String,
+) -> Vec<RenderedVoice> {
+    let mut rendered = Vec::new();
+    for voice in voices {
+        if experiment_hidden(voice.experiment.as_ref())
+            .or_else(|| context.capabilities.hidden_reason(&voice.requires))
+            .is_some()
+        {
+            continue;
+        }
+        if let Some(condition) = &voice.condition
+            && !evaluate_if_condition(condition, context.functions, variable_state)
+        {
+            continue;
+        }
+        variable_state.execute_statements(&voice.pre_statements);
+        let body = interpolate(voice, context.functions, context.func_decls, variable_state);
+        if body.is_empty() {
+            continue;
+        }
+        let events = collect_text_events(
+            voice,
+            variable_state,
+            context.asset,
+            None,
+            context.node_data,
+        );
+        let header = header(voice, variable_state);
+        let full = format!("{header}{body}");
+        let mut text = icons::dialogue_text(
+            header,
+            &body,
+            &voice.line_id,
+            &events,
+            context.icon_settings,
+        );
+        text.voice.clone_from(&voice.channel);
+        rendered.push(RenderedVoice { full, text, events });
+    }
+    rendered
+}
+
+/// Every voice with its header, one per row.
+pub(super) fn joined(voices: &[RenderedVoice]) -> String {
+    let rows: Vec<&str> = voices.iter().map(|voice| voice.full.as_str()).collect();
+    rows.join("\n")
+}
+
+/// The events of every voice, for the line explanation.
+pub(super) fn voice_events(voices: &[RenderedVoice]) -> Vec<Event> {
+    voices
+        .iter()
+        .flat_map(|voice| voice.events.iter().cloned())
+        .collect()
+}
+
+/// A rendered line and what each text target receives of it.
+pub(super) enum RenderedLine {
+    Single {
+        text: MortarDialogueText,
+        events: Vec<Event>,
+    },
+    Parallel {
+        voices: Vec<RenderedVoice>,
+        /// Every voice, one per row, for targets without a channel.
+        combined: MortarDialogueText,
+        /// Shown by channelled targets whose voice is hidden or absent.
+        silent: MortarDialogueText,
+    },
+}
+
+impl RenderedLine {
+    /// Joins `voices` for the targets without a channel; `line_id` identifies the group.
+    pub(super) fn parallel(
+        voices: Vec<RenderedVoice>,
+        line_id: &str,
+        icon_settings: &MortarIconSettings,
+    ) -> Self {
+        let combined =
+            icons::dialogue_text(String::new(), &joined(&voices), line_id, &[], icon_settings);
+        let silent = MortarDialogueText {
+            line_id: line_id.to_owned(),
+            ..default()
+        };
+        Self::Parallel {
+            voices,
+            combined,
+            silent,
+        }
+    }
+
+    /// Stamps every text of the line with the cursor it was rendered at.
+    pub(super) fn stamp(&mut self, cursor: LineCursor) {
+        match self {
+            Self::Single { text, .. } => text.cursor = cursor,
+            Self::Parallel {
+                voices,
+                combined,
+                silent,
+            } => {
+                let voices = voices.iter_mut().map(|voice| &mut voice.text);
+                for text in voices.chain([combined, silent]) {
+                    text.cursor = cursor;
+                }
+            }
+        }
+    }
+
+    /// Ids of the voices shown, empty for a single line.
+    pub(super) fn voice_line_ids(&self) -> Vec<String> {
+        match self {
+            Self::Single { .. } => Vec::new(),
+            Self::Parallel { voices, .. } => voices
+                .iter()
+                .map(|voice| voice.text.line_id.clone())
+                .collect(),
+        }
+    }
+
+    /// The text a target on `channel` shows, with the events its tracker fires.
+    pub(super) fn for_target(
+        &self,
+        channel: Option<&MortarTextChannel>,
+    ) -> (&MortarDialogueText, &[Event]) {
+        match (self, channel) {
+            (Self::Single { text, events }, _) => (text, events),
+            (Self::Parallel { combined, .. }, None) => (combined, &[]),
+            (Self::Parallel { voices, silent, .. }, Some(MortarTextChannel(channel))) => voices
+                .iter()
+                .find(|voice| voice.text.voice.as_ref() == Some(channel))
+                .map_or((silent, &[]), |voice| (&voice.text, &voice.events)),
+        }
+    }
+}
+
+/// Reveal progress of one target, settled after every target has been stepped.
+#[cfg(feature = "ui")]
+pub(super) struct TargetProgress<'a> {
+    pub(super) entity: Entity,
+    pub(super) status: Option<Mut<'a, MortarLineStatus>>,
+    pub(super) complete: bool,
+    pub(super) awaiting: bool,
+    pub(super) voice: bool,
+}
+
+/// Writes the progress of each target into its [`MortarLineStatus`]. The voices of a parallel
+/// line share theirs: complete once all are, and awaiting only while none is still revealing.
+#[cfg(feature = "ui")]
+pub(super) fn settle_progress(commands: &mut Commands, progress: Vec<TargetProgress>) {
+    let voices = progress.iter().filter(|target| target.voice);
+    let all_complete = voices.clone().all(|target| target.complete);
+    let all_held = voices
+        .clone()
+        .all(|target| target.complete || target.awaiting);
+    let any_awaiting = voices.clone().any(|target| target.awaiting);
+    for target in progress {
+        let (complete, awaiting) = if target.voice {
+            (all_complete, all_held && any_awaiting)
+        } else {
+            (target.complete, target.awaiting)
+        };
+        super::reveal::set_reveal_progress(
+            commands,
+            target.entity,
+            target.status,
+            complete,
+            awaiting,
+        );
+    }
+}
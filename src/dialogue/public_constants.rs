@@ -0,0 +1,69 @@
+//! # public_constants.rs
+//!
+//! # public_constants.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Prints the public constants of a mortar file the first time one of its dialogues becomes
+//! primary, so designers can see what a file exposes to the game.
+//!
+//! 在某个 mortar 文件的对话首次成为主对话时打印其公共常量，方便策划查看该文件向游戏公开了哪些内容。
+
+use bevy::asset::{AssetId, Assets};
+use bevy::prelude::*;
+use std::collections::HashSet;
+
+use crate::debug::LOG_DIALOGUE;
+use crate::{MortarAsset, MortarRegistry, MortarRuntime};
+
+/// Records which mortar assets already printed their public constants.
+///
+/// 记录哪些 mortar 资源已经打印过其公共常量。
+#[derive(Resource, Default)]
+pub struct LoggedConstants {
+    pub(crate) seen_assets: HashSet<AssetId<MortarAsset>>,
+}
+
+pub(super) fn log_public_constants_once(
+    runtime: Res<MortarRuntime>,
+    registry: Res<MortarRegistry>,
+    assets: Res<Assets<MortarAsset>>,
+    mut logged: ResMut<LoggedConstants>,
+) {
+    let Some(state) = runtime.primary_dialogue_state() else {
+        return;
+    };
+
+    let Some(handle) = registry.get(&state.mortar_path) else {
+        return;
+    };
+    if logged.seen_assets.contains(&handle.id()) {
+        return;
+    }
+    let Some(asset) = assets.get(handle) else {
+        return;
+    };
+
+    let public_consts: Vec<_> = asset.data.constants.iter().filter(|c| c.public).collect();
+    if public_consts.is_empty() {
+        logged.seen_assets.insert(handle.id());
+        return;
+    }
+
+    debug!(target: LOG_DIALOGUE, "Mortar public constants exposed by {}:", state.mortar_path);
+    for constant in public_consts {
+        let value_repr = match &constant.value {
+            serde_json::Value::String(s) => s.clone(),
+            _ => constant.value.to_string(),
+        };
+        debug!(
+            target: LOG_DIALOGUE,
+            "  {} ({}): {}",
+            constant.name, constant.const_type, value_repr
+        );
+    }
+
+    logged.seen_assets.insert(handle.id());
+}
@@ -0,0 +1,300 @@
+//! # reveal.rs
+//!
+//! # reveal.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Contains the built-in reveal driver and the seeking API for dialogue lines. The driver
+//! reveals the body of [`MortarDialogueText`] character by character and mirrors its position into
+//! [`MortarEventBinding`]. Seeking moves a line to any position, forward or backward, and updates
+//! the binding, the revealed text, [`MortarLineStatus`] and the event tracker within the same
+//! frame, so timeline editors can scrub dialogue. A frame that reveals several characters is
+//! stepped one character at a time (see [`MortarRevealStep`]), optionally capped per frame. The
+//! reveal holds at checkpoints until the next advance input (see [`PAUSE_TOKEN`]).
+//!
+//! 包含内置的逐字显示驱动以及对话行的定位（seek）接口。驱动会逐字显示 [`MortarDialogueText`]
+//! 的正文，并把当前位置同步到 [`MortarEventBinding`]。定位可以把一行移动到任意位置（向前或向后），
+//! 并在同一帧内更新绑定、已显示文本、[`MortarLineStatus`] 以及事件跟踪器，便于时间线编辑器拖动对话。
+//! 一帧内显示多个字符时会逐字符推进（见 [`MortarRevealStep`]），并可按帧限制字符数。显示会在检查点
+//! 处停住，直到下一次推进输入（见 [`PAUSE_TOKEN`]）。
+
+use bevy::prelude::*;
+
+use crate::MortarRuntime;
+
+mod checkpoints;
+#[cfg(feature = "ui")]
+mod driver;
+mod steps;
+
+pub(super) use checkpoints::extract_checkpoints;
+pub(crate) use checkpoints::is_pause_token;
+pub use checkpoints::{PAUSE_REVEAL_ACTION, PAUSE_TOKEN};
+#[cfg(feature = "ui")]
+pub(super) use driver::{advance_text_reveal, handle_line_seeks};
+pub use steps::MortarRevealStep;
+#[cfg(feature = "ui")]
+pub(super) use steps::RevealSteps;
+
+#[cfg(feature = "ui")]
+use super::MortarLineStatus;
+use super::{MortarDialogueText, MortarRevealPolicy, MortarRunsExecuting, MortarTextTarget};
+
+/// A position inside the current line.
+///
+/// 当前行内的一个位置。
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LinePosition {
+    /// Unicode scalar values from the start of the body.
+    ///
+    /// 从正文开头算起的 Unicode 标量值个数。
+    Chars(usize),
+    /// User-perceived characters. Combining marks, variation selectors, skin-tone modifiers and
+    /// zero-width-joiner sequences stay attached to their base character.
+    ///
+    /// 用户感知的字符。组合符号、变体选择符、肤色修饰符和零宽连接序列会附着在其基础字符上。
+    Graphemes(usize),
+    /// Fraction of the body, from 0.0 to 1.0.
+    ///
+    /// 正文的比例，取值 0.0 到 1.0。
+    Fraction(f32),
+    /// Time into the line, converted with the speed of the target's [`MortarTextReveal`].
+    ///
+    /// 行内时间，使用目标 [`MortarTextReveal`] 的速度换算。
+    Seconds(f32),
+}
+
+/// Built-in reveal driver. Add it to a [`MortarTextTarget`] to reveal each line progressively.
+///
+/// 内置的逐字显示驱动。将其添加到 [`MortarTextTarget`] 上即可逐步显示每一行。
+#[derive(Component, Debug, Clone, PartialEq)]
+pub struct MortarTextReveal {
+    pub chars_per_second: f32,
+    /// Whether the reveal advances on its own; seeking works either way.
+    ///
+    /// 是否自动推进；无论是否推进都可以定位。
+    pub playing: bool,
+    /// Most characters revealed in one frame; `None` reveals everything the elapsed time owes.
+    ///
+    /// 单帧内最多显示的字符数；为 `None` 时显示经过时间所对应的全部字符。
+    pub max_chars_per_frame: Option<usize>,
+    /// Whether characters held back by `max_chars_per_frame` are carried into the next frames, so
+    /// the reveal catches up after a slow frame. Without it the held-back time is dropped and the
+    /// reveal never outruns the cap.
+    ///
+    /// 被 `max_chars_per_frame` 限制而未显示的字符是否顺延到之后的帧，使显示在慢帧之后追上进度。
+    /// 关闭时被限制的时间会被丢弃，显示速度永远不会超过上限。
+    pub catch_up: bool,
+    revealed: f32,
+    carried: f32,
+    line: Option<String>,
+    /// Checkpoints of the line already released.
+    passed: usize,
+    /// Continue requests seen, see [`crate::AdvanceIntent::ContinueReveal`].
+    continued: u64,
+}
+
+impl MortarTextReveal {
+    pub fn new(chars_per_second: f32) -> Self {
+        Self {
+            chars_per_second,
+            playing: true,
+            max_chars_per_frame: None,
+            catch_up: true,
+            revealed: 0.0,
+            carried: 0.0,
+            line: None,
+            passed: 0,
+            continued: 0,
+        }
+    }
+
+    /// Number of characters currently revealed.
+    ///
+    /// 当前已显示的字符数。
+    pub fn revealed_chars(&self) -> usize {
+        self.revealed as usize
+    }
+
+    /// Whether part of `body` is still hidden, counting a line that has not started yet.
+    fn is_revealing(&self, body: &str) -> bool {
+        let shown = if self.line.as_deref() == Some(body) {
+            self.revealed_chars()
+        } else {
+            0
+        };
+        shown < body.chars().count()
+    }
+
+    /// Restarts from the first character when the body is a new line, returning whether it did.
+    #[cfg(feature = "ui")]
+    fn sync_line(&mut self, body: &str) -> bool {
+        if self.line.as_deref() == Some(body) {
+            return false;
+        }
+        self.line = Some(body.to_owned());
+        self.revealed = 0.0;
+        self.carried = 0.0;
+        self.passed = 0;
+        true
+    }
+
+    /// Moves the reveal forward by `delta` seconds, within the per-frame cap.
+    #[cfg(feature = "ui")]
+    fn advance(&mut self, delta: f32, len: usize) {
+        let target = (self.revealed + self.carried + self.chars_per_second * delta).min(len as f32);
+        let limit = self
+            .max_chars_per_frame
+            .map_or(len, |max| (self.revealed_chars() + max).min(len));
+        self.revealed = target.min(limit as f32);
+        self.carried = if self.catch_up {
+            target - self.revealed
+        } else {
+            0.0
+        };
+    }
+}
+
+impl Default for MortarTextReveal {
+    fn default() -> Self {
+        Self::new(30.0)
+    }
+}
+
+fn is_grapheme_extender(c: char) -> bool {
+    matches!(
+        c,
+        '\u{0300}'..='\u{036F}'
+            | '\u{1AB0}'..='\u{1AFF}'
+            | '\u{1DC0}'..='\u{1DFF}'
+            | '\u{200D}'
+            | '\u{20D0}'..='\u{20FF}'
+            | '\u{FE00}'..='\u{FE0F}'
+            | '\u{FE20}'..='\u{FE2F}'
+            | '\u{1F3FB}'..='\u{1F3FF}'
+    )
+}
+
+/// Converts a grapheme count into a char count.
+fn graphemes_to_chars(body: &str, graphemes: usize) -> usize {
+    let mut seen = 0;
+    let mut after_joiner = false;
+    for (index, c) in body.chars().enumerate() {
+        let starts_grapheme = !is_grapheme_extender(c) && !after_joiner;
+        after_joiner = c == '\u{200D}';
+        if starts_grapheme {
+            if seen == graphemes {
+                return index;
+            }
+            seen += 1;
+        }
+    }
+    body.chars().count()
+}
+
+impl LinePosition {
+    /// Resolves the position to a char index in `body`, clamped to its length.
+    /// `Seconds` needs a reveal speed and yields `None` without one.
+    ///
+    /// 将位置换算为 `body` 中的字符索引并截断到其长度。`Seconds` 需要显示速度，没有时返回 `None`。
+    pub fn to_chars(self, body: &str, chars_per_second: Option<f32>) -> Option<usize> {
+        let len = body.chars().count();
+        let chars = match self {
+            Self::Chars(chars) => chars,
+            Self::Graphemes(graphemes) => graphemes_to_chars(body, graphemes),
+            Self::Fraction(fraction) => (fraction.clamp(0.0, 1.0) * len as f32).round() as usize,
+            Self::Seconds(seconds) => (seconds.max(0.0) * chars_per_second?) as usize,
+        };
+        Some(chars.min(len))
+    }
+}
+
+#[cfg(feature = "ui")]
+pub(super) fn compose(dialogue_text: &MortarDialogueText, chars: usize) -> String {
+    let body: String = dialogue_text.body.chars().take(chars).collect();
+    format!("{}{}", dialogue_text.header, body)
+}
+
+#[cfg(feature = "ui")]
+pub(super) fn set_reveal_complete(
+    commands: &mut Commands,
+    entity: Entity,
+    status: Option<Mut<MortarLineStatus>>,
+    complete: bool,
+) {
+    set_reveal_progress(commands, entity, status, complete, false);
+}
+
+#[cfg(feature = "ui")]
+pub(super) fn set_reveal_progress(
+    commands: &mut Commands,
+    entity: Entity,
+    status: Option<Mut<MortarLineStatus>>,
+    complete: bool,
+    awaiting: bool,
+) {
+    match status {
+        Some(mut status) => {
+            if status.reveal_complete != complete {
+                status.reveal_complete = complete;
+            }
+            if status.awaiting_advance != awaiting {
+                status.awaiting_advance = awaiting;
+            }
+        }
+        None => {
+            commands.entity(entity).insert(MortarLineStatus {
+                reveal_complete: complete,
+                awaiting_advance: awaiting,
+                ..default()
+            });
+        }
+    }
+}
+
+/// Mirrors the run and reveal state into the runtime so [`crate::AdvanceIntent`] can see it.
+/// A pending request to finish the reveal is dropped once nothing is revealing any more.
+///
+/// 将 run 与逐字显示的状态同步到运行时，使 [`crate::AdvanceIntent`] 能够感知。
+/// 当不再有任何逐字显示时，丢弃尚未处理的补全请求。
+pub(super) fn sync_advance_gate(
+    runs_executing: Res<MortarRunsExecuting>,
+    reveals: Query<
+        (
+            &MortarDialogueText,
+            &MortarTextReveal,
+            Option<&MortarRevealPolicy>,
+        ),
+        With<MortarTextTarget>,
+    >,
+    #[cfg(feature = "typewriter")] typewriters: Query<
+        (
+            &MortarDialogueText,
+            &super::MortarTypewriterAdapter,
+            Option<&MortarRevealPolicy>,
+        ),
+        With<MortarTextTarget>,
+    >,
+    mut runtime: ResMut<MortarRuntime>,
+) {
+    let revealing = reveals.iter().any(|(dialogue_text, reveal, policy)| {
+        MortarRevealPolicy::is_gradual(policy) && reveal.is_revealing(&dialogue_text.body)
+    });
+    let awaiting = reveals.iter().any(|(dialogue_text, reveal, policy)| {
+        MortarRevealPolicy::is_gradual(policy) && reveal.is_awaiting(dialogue_text)
+    });
+    #[cfg(feature = "typewriter")]
+    let revealing = revealing
+        || typewriters.iter().any(|(dialogue_text, adapter, policy)| {
+            MortarRevealPolicy::is_gradual(policy) && adapter.is_revealing(&dialogue_text.body)
+        });
+    let gate = &mut runtime.bypass_change_detection().advance_gate;
+    gate.runs_executing = runs_executing.executing;
+    gate.revealing = revealing;
+    gate.awaiting = awaiting;
+    if !revealing {
+        gate.finish_reveal = false;
+    }
+}
@@ -0,0 +1,122 @@
+//! # checkpoints.rs
+//!
+//! # checkpoints.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Mid-line pauses for the built-in reveal. A `{pause}` token in the text, or a text event with
+//! the `__pause_reveal` action, marks a checkpoint: when [`MortarTextReveal`] reaches it the
+//! reveal holds and [`crate::MortarLineStatus::awaiting_advance`] is set, and the next advance
+//! input resumes the same line instead of moving to the next one. Tokens are removed from the
+//! body and take no index unit, like a placeholder that renders nothing, so event indices are
+//! unaffected. Finishing the reveal, e.g. by `NextText` while it plays, passes every checkpoint.
+//!
+//! 内置逐字显示的行内暂停。文本中的 `{pause}` 标记或带有 `__pause_reveal` 动作的文本事件会标出一个
+//! 检查点：[`MortarTextReveal`] 到达该处时会停住并设置 [`crate::MortarLineStatus::awaiting_advance`]，
+//! 下一次推进输入会继续显示同一行，而不是进入下一行。标记会从正文中移除且不占索引单位，与不渲染
+//! 任何内容的占位符相同，因此事件索引不受影响。补全显示（例如在播放时发送 `NextText`）会越过所有
+//! 检查点。
+
+use mortar_compiler::Event;
+
+use super::MortarTextReveal;
+use crate::dialogue::InlineIcon;
+use crate::dialogue::MortarDialogueText;
+
+/// Inline token that pauses the reveal until the next advance input.
+///
+/// 暂停逐字显示直到下一次推进输入的行内标记。
+pub const PAUSE_TOKEN: &str = "{pause}";
+
+/// Text event action that pauses the reveal at the event's index.
+///
+/// 在事件索引处暂停逐字显示的文本事件动作。
+pub const PAUSE_REVEAL_ACTION: &str = "__pause_reveal";
+
+/// Whether the name inside an interpolation placeholder is the pause token.
+pub(crate) fn is_pause_token(name: &str) -> bool {
+    name == &PAUSE_TOKEN[1..PAUSE_TOKEN.len() - 1]
+}
+
+/// Removes the pause tokens from `body`, moving `icons` back to match, and returns the body with
+/// the sorted checkpoints of both the tokens and the `__pause_reveal` events.
+pub(in crate::dialogue) fn extract_checkpoints(
+    body: &str,
+    icons: &mut [InlineIcon],
+    events: &[Event],
+) -> (String, Vec<usize>) {
+    let token_chars = PAUSE_TOKEN.chars().count();
+    let mut stripped = String::with_capacity(body.len());
+    let mut checkpoints = Vec::new();
+    let mut chars = 0;
+    let mut rest = body;
+    while let Some(start) = rest.find(PAUSE_TOKEN) {
+        let before = &rest[..start];
+        stripped.push_str(before);
+        chars += before.chars().count();
+        checkpoints.push(chars);
+        rest = &rest[start + PAUSE_TOKEN.len()..];
+    }
+    stripped.push_str(rest);
+    for icon in icons.iter_mut() {
+        // Token `n` started at `checkpoint + n * token_chars` in the original body.
+        //
+        // 第 `n` 个标记在原始正文中的起始位置为 `checkpoint + n * token_chars`。
+        let removed = checkpoints
+            .iter()
+            .enumerate()
+            .filter(|&(n, &at)| at + n * token_chars < icon.char_index)
+            .count();
+        icon.char_index -= removed * token_chars;
+    }
+    checkpoints.extend(
+        events
+            .iter()
+            .filter(|event| {
+                event
+                    .actions
+                    .iter()
+                    .any(|action| action.action_type == PAUSE_REVEAL_ACTION)
+            })
+            .map(|event| event.index.max(0.0).ceil() as usize),
+    );
+    checkpoints.sort_unstable();
+    checkpoints.dedup();
+    (stripped, checkpoints)
+}
+
+impl MortarTextReveal {
+    /// The checkpoint the reveal stops at next; one at the end of the line is no pause.
+    pub(super) fn next_checkpoint(&self, dialogue_text: &MortarDialogueText) -> Option<usize> {
+        let len = dialogue_text.body.chars().count();
+        dialogue_text
+            .checkpoints
+            .get(self.passed)
+            .copied()
+            .filter(|&checkpoint| checkpoint < len)
+    }
+
+    /// Whether the reveal holds at a checkpoint of the current line.
+    pub(super) fn is_awaiting(&self, dialogue_text: &MortarDialogueText) -> bool {
+        self.line.as_deref() == Some(dialogue_text.body.as_str())
+            && self
+                .next_checkpoint(dialogue_text)
+                .is_some_and(|checkpoint| self.revealed_chars() >= checkpoint)
+    }
+
+    /// Counts the checkpoints before `chars` as passed, after the reveal jumped there.
+    #[cfg(feature = "ui")]
+    pub(super) fn pass_checkpoints_before(
+        &mut self,
+        dialogue_text: &MortarDialogueText,
+        chars: usize,
+    ) {
+        self.passed = dialogue_text
+            .checkpoints
+            .iter()
+            .filter(|&&checkpoint| checkpoint < chars)
+            .count();
+    }
+}
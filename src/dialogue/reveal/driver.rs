@@ -0,0 +1,217 @@
+//! # driver.rs
+//!
+//! # driver.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Writes the reveal onto the `Text` of each target: the per-frame driver of [`MortarTextReveal`]
+//! and line seeking. Both need Bevy's text components, so they only exist with the `ui` feature.
+//!
+//! 将逐字显示写入每个目标的 `Text`：[`MortarTextReveal`] 的逐帧驱动以及行定位。两者都需要 Bevy 的
+//! 文本组件，因此只在启用 `ui` 特性时存在。
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+
+use super::super::event_targets::GameEventWriter;
+use super::super::parallel::{TargetProgress, settle_progress};
+use super::super::{
+    MortarDialogueText, MortarEventBinding, MortarLineStatus, MortarRevealPolicy,
+    MortarRunsExecuting, MortarTextTarget,
+};
+use super::{LinePosition, MortarTextReveal, RevealSteps, compose};
+use crate::debug::LOG_DIALOGUE;
+use crate::{MortarCommand, MortarEventTracker, MortarRuntime, MortarTrackerMode};
+
+type RevealQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static MortarDialogueText,
+        &'static mut Text,
+        &'static mut MortarTextReveal,
+        Option<&'static mut MortarEventBinding>,
+        Option<(
+            &'static mut MortarEventTracker,
+            Option<&'static MortarTrackerMode>,
+        )>,
+        Option<&'static mut MortarLineStatus>,
+        Option<&'static MortarRevealPolicy>,
+    ),
+    With<MortarTextTarget>,
+>;
+
+/// Advances the built-in reveal driver, one character at a time.
+///
+/// 逐字符推进内置的逐字显示驱动。
+pub(in crate::dialogue) fn advance_text_reveal(
+    mut commands: Commands,
+    time: Res<Time>,
+    runs_executing: Res<MortarRunsExecuting>,
+    mut steps: RevealSteps,
+    mut targets: RevealQuery,
+) {
+    if runs_executing.executing {
+        return;
+    }
+    let finish = steps.finish_reveal();
+    let requested = steps.continue_requests();
+    let mut progress = Vec::new();
+    for (entity, dialogue_text, mut text, mut reveal, binding, tracker, status, policy) in
+        &mut targets
+    {
+        if !MortarRevealPolicy::is_gradual(policy) {
+            continue;
+        }
+        if reveal.sync_line(&dialogue_text.body) {
+            reveal.continued = requested;
+        }
+        if reveal.continued != requested {
+            reveal.continued = requested;
+            if reveal.is_awaiting(dialogue_text) {
+                reveal.passed += 1;
+            }
+        }
+        let len = dialogue_text.body.chars().count();
+        let shown = reveal.revealed_chars();
+        if finish {
+            reveal.revealed = len as f32;
+            reveal.carried = 0.0;
+            reveal.pass_checkpoints_before(dialogue_text, len);
+        } else if reveal.playing && shown < len {
+            let stop = reveal.next_checkpoint(dialogue_text).unwrap_or(len);
+            reveal.advance(time.delta_secs(), stop);
+        }
+        let chars = reveal.revealed_chars();
+        steps.step(entity, &dialogue_text.body, shown, chars, tracker);
+        let composed = compose(dialogue_text, chars);
+        if text.0 != composed {
+            text.0 = composed;
+        }
+        if let Some(mut binding) = binding
+            && binding.current_index != chars as f32
+        {
+            binding.current_index = chars as f32;
+        }
+        progress.push(TargetProgress {
+            entity,
+            status,
+            complete: chars >= len,
+            awaiting: reveal.is_awaiting(dialogue_text),
+            voice: dialogue_text.voice.is_some(),
+        });
+    }
+    settle_progress(&mut commands, progress);
+}
+
+type SeekTargetQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static MortarDialogueText,
+        &'static mut Text,
+        Option<&'static mut MortarTextReveal>,
+        Option<&'static mut MortarEventBinding>,
+        Option<(
+            &'static mut MortarEventTracker,
+            Option<&'static MortarTrackerMode>,
+        )>,
+        Option<&'static mut MortarLineStatus>,
+        Option<&'static MortarRevealPolicy>,
+    ),
+    With<MortarTextTarget>,
+>;
+
+/// Everything a seek touches.
+#[derive(SystemParam)]
+pub(in crate::dialogue) struct SeekParams<'w, 's> {
+    commands: Commands<'w, 's>,
+    runtime: Res<'w, MortarRuntime>,
+    default_mode: Res<'w, MortarTrackerMode>,
+    targets: SeekTargetQuery<'w, 's>,
+    game_events: GameEventWriter<'w, 's>,
+}
+
+fn seek_targets(position: LinePosition, params: &mut SeekParams) {
+    let mut progress = Vec::new();
+    for (entity, dialogue_text, mut text, reveal, binding, tracker, status, policy) in
+        &mut params.targets
+    {
+        if !MortarRevealPolicy::is_gradual(policy) {
+            continue;
+        }
+        let speed = reveal.as_ref().map(|reveal| reveal.chars_per_second);
+        let Some(chars) = position.to_chars(&dialogue_text.body, speed) else {
+            warn!(
+                target: LOG_DIALOGUE,
+                "Cannot seek {:?} on {:?}: it has no MortarTextReveal speed",
+                position, entity
+            );
+            continue;
+        };
+
+        let mut awaiting = false;
+        if let Some(mut reveal) = reveal {
+            reveal.sync_line(&dialogue_text.body);
+            reveal.revealed = chars as f32;
+            reveal.carried = 0.0;
+            reveal.pass_checkpoints_before(dialogue_text, chars);
+            awaiting = reveal.is_awaiting(dialogue_text);
+            text.0 = compose(dialogue_text, chars);
+        }
+        if let Some(mut binding) = binding {
+            binding.current_index = chars as f32;
+        }
+        if let Some((mut tracker, mode)) = tracker {
+            let mode = mode.copied().unwrap_or(*params.default_mode);
+            let actions = tracker.scrub_to(chars as f32, mode, &params.runtime);
+            for action in actions {
+                params
+                    .game_events
+                    .write(Some(entity), action.action_name, action.args);
+            }
+        }
+        progress.push(TargetProgress {
+            entity,
+            status,
+            complete: chars >= dialogue_text.body.chars().count(),
+            awaiting,
+            voice: dialogue_text.voice.is_some(),
+        });
+    }
+    settle_progress(&mut params.commands, progress);
+}
+
+/// Applies [`MortarCommand::SeekLine`] requests.
+///
+/// 处理 [`MortarCommand::SeekLine`] 请求。
+pub(in crate::dialogue) fn handle_line_seeks(
+    mut events: MessageReader<MortarCommand>,
+    mut params: SeekParams,
+) {
+    for event in events.read() {
+        if let MortarCommand::SeekLine { position } = event {
+            seek_targets(*position, &mut params);
+        }
+    }
+}
+
+fn seek_line_system(In(position): In<LinePosition>, mut params: SeekParams) {
+    seek_targets(position, &mut params);
+}
+
+impl MortarRuntime {
+    /// Seeks every dialogue text target immediately, for exclusive systems that cannot wait for
+    /// a [`MortarCommand::SeekLine`] to be processed.
+    ///
+    /// 立即定位所有对话文本目标，供无法等待 [`MortarCommand::SeekLine`] 被处理的独占系统使用。
+    pub fn seek_line(world: &mut World, position: LinePosition) {
+        if let Err(err) = world.run_system_cached_with(seek_line_system, position) {
+            warn!(target: LOG_DIALOGUE, "Failed to seek dialogue line: {}", err);
+        }
+    }
+}
@@ -0,0 +1,120 @@
+//! # steps.rs
+//!
+//! # steps.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Splits a reveal that moves several characters in one frame into single-character steps. For
+//! each character the driver advances the event tracker to just past it and writes a
+//! [`MortarRevealStep::Char`] followed by a [`MortarRevealStep::Event`] for every action reached,
+//! so per-letter sounds and text events line up in positional order even at 30 FPS with a very
+//! fast reveal. Event callbacks run inside this loop, but the `Text` of the target is only
+//! written once the frame's last step is done, so they cannot observe a partial frame.
+//!
+//! 把一帧内前进多个字符的显示拆分为逐字符的步骤。对每个字符，驱动会把事件跟踪器推进到该字符
+//! 之后，先写出 [`MortarRevealStep::Char`]，再为每个到达的动作写出 [`MortarRevealStep::Event`]，
+//! 因此即使在 30 FPS 下以极快速度显示，逐字音效与文本事件也会按位置顺序排列。事件回调在此循环中
+//! 运行，但目标的 `Text` 只会在本帧最后一步完成后写入，因此回调无法观察到帧内的中间状态。
+
+#[cfg(feature = "ui")]
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+
+#[cfg(feature = "ui")]
+use super::super::event_targets::GameEventWriter;
+#[cfg(feature = "ui")]
+use crate::{MortarEventTracker, MortarRuntime, MortarTrackerMode};
+
+use super::super::MortarGameEvent;
+
+/// One step of a reveal, written in strict positional order. Within a frame every revealed
+/// character comes before the events it reaches, and after the events of earlier characters.
+/// Events are also written as [`MortarGameEvent`] as usual.
+///
+/// 显示过程中的一步，严格按位置顺序写出。同一帧内，每个显示的字符都位于它所到达的事件之前、
+/// 之前字符的事件之后。事件同样会照常以 [`MortarGameEvent`] 写出。
+#[derive(Message, Debug, Clone)]
+pub enum MortarRevealStep {
+    /// A character of the body became visible.
+    ///
+    /// 正文中的一个字符变为可见。
+    Char {
+        source: Entity,
+        /// Char index of the character in the body.
+        ///
+        /// 该字符在正文中的字符索引。
+        index: usize,
+        character: char,
+    },
+    /// A text event fired right after the preceding character.
+    ///
+    /// 紧随前一个字符触发的文本事件。
+    Event(MortarGameEvent),
+}
+
+#[cfg(feature = "ui")]
+type TrackerItem<'a> = Option<(Mut<'a, MortarEventTracker>, Option<&'a MortarTrackerMode>)>;
+
+/// What a reveal driver writes while stepping through characters.
+#[cfg(feature = "ui")]
+#[derive(SystemParam)]
+pub(in crate::dialogue) struct RevealSteps<'w, 's> {
+    runtime: Res<'w, MortarRuntime>,
+    default_mode: Res<'w, MortarTrackerMode>,
+    steps: MessageWriter<'w, MortarRevealStep>,
+    game_events: GameEventWriter<'w, 's>,
+}
+
+#[cfg(feature = "ui")]
+impl RevealSteps<'_, '_> {
+    /// Whether `NextText` asked to finish the reveal this frame.
+    pub(in crate::dialogue) fn finish_reveal(&self) -> bool {
+        self.runtime.advance_gate.finish_reveal
+    }
+
+    /// How many times the reveal was asked to continue past a checkpoint so far.
+    pub(in crate::dialogue) fn continue_requests(&self) -> u64 {
+        self.runtime.advance_gate.continue_requests
+    }
+
+    /// Reveals the characters of `body` from `from` up to `to` one at a time, firing the events
+    /// of `tracker` reached at `from` first and those reached by each character after it. Moving
+    /// backwards writes nothing; the tracker catches up in `trigger_bound_events`.
+    pub(in crate::dialogue) fn step(
+        &mut self,
+        source: Entity,
+        body: &str,
+        from: usize,
+        to: usize,
+        mut tracker: TrackerItem,
+    ) {
+        if to <= from {
+            return;
+        }
+        self.fire(source, from, &mut tracker);
+        for (index, character) in body.chars().enumerate().take(to).skip(from) {
+            self.steps.write(MortarRevealStep::Char {
+                source,
+                index,
+                character,
+            });
+            self.fire(source, index + 1, &mut tracker);
+        }
+    }
+
+    fn fire(&mut self, source: Entity, index: usize, tracker: &mut TrackerItem) {
+        let Some((tracker, mode)) = tracker.as_mut() else {
+            return;
+        };
+        let mode = mode.copied().unwrap_or(*self.default_mode);
+        for action in tracker.scrub_to(index as f32, mode, &self.runtime) {
+            let event = self
+                .game_events
+                .event(Some(source), action.action_name, action.args);
+            self.steps.write(MortarRevealStep::Event(event.clone()));
+            self.game_events.send(event);
+        }
+    }
+}
@@ -0,0 +1,161 @@
+//! # reveal_policy.rs
+//!
+//! # reveal_policy.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Lets each [`MortarTextTarget`] show the same line its own way, e.g. a main box revealing
+//! gradually next to a subtitle strip that shows the whole line at once. [`MortarRevealPolicy`]
+//! is applied per target: reveal drivers only animate `Gradual` targets, `Instant` targets keep
+//! the full body, and `Timed` targets clear themselves once the line has been readable for long
+//! enough. Event trackers only attach to `Gradual` targets unless
+//! [`MortarRevealPolicySettings`] says otherwise, so a line's events fire once.
+//!
+//! 让每个 [`MortarTextTarget`] 以各自的方式显示同一行，例如逐字显示的主对话框旁边放一条整行立即显示的
+//! 字幕条。[`MortarRevealPolicy`] 按目标生效：逐字显示驱动只推进 `Gradual` 目标，`Instant` 目标
+//! 始终显示完整正文，`Timed` 目标在整行可读足够长时间后自行清空。除非 [`MortarRevealPolicySettings`]
+//! 另有设置，事件跟踪器只会附加到 `Gradual` 目标上，因此一行的事件只触发一次。
+
+use bevy::prelude::*;
+
+#[cfg(feature = "ui")]
+use super::reveal::{compose, set_reveal_complete};
+#[cfg(feature = "ui")]
+use super::{MortarDialogueText, MortarEventBinding, MortarLineStatus, MortarTextTarget};
+
+/// Reading speed used by [`estimate_read_seconds`], a common subtitle guideline.
+///
+/// [`estimate_read_seconds`] 使用的阅读速度，取自常见的字幕规范。
+pub const READING_CHARS_PER_SECOND: f32 = 15.0;
+
+/// How a text target shows each line. Targets without the component are `Gradual`.
+///
+/// 文本目标显示每一行的方式。没有该组件的目标视为 `Gradual`。
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq)]
+pub enum MortarRevealPolicy {
+    /// Follows the reveal driver or typewriter on the target.
+    ///
+    /// 跟随目标上的逐字显示驱动或打字机。
+    #[default]
+    Gradual,
+    /// Always shows the full body.
+    ///
+    /// 始终显示完整正文。
+    Instant,
+    /// Shows the full body, then clears it after `seconds` plus the read-time estimate of the
+    /// line.
+    ///
+    /// 显示完整正文，并在 `seconds` 加上该行的阅读时间估计之后清空。
+    Timed { seconds: f32 },
+}
+
+impl MortarRevealPolicy {
+    /// Whether a target with this policy, or none, reveals gradually.
+    ///
+    /// 具有该策略（或没有策略）的目标是否逐字显示。
+    pub fn is_gradual(policy: Option<&Self>) -> bool {
+        policy.is_none_or(|policy| *policy == Self::Gradual)
+    }
+}
+
+/// Settings shared by all reveal policies.
+///
+/// 所有显示策略共用的设置。
+#[derive(Resource, Debug, Clone, Default, PartialEq, Eq)]
+pub struct MortarRevealPolicySettings {
+    /// Attach event trackers to `Instant` and `Timed` targets as well. Their events then fire
+    /// as soon as the line shows, once per such target.
+    ///
+    /// 同样为 `Instant` 与 `Timed` 目标附加事件跟踪器。其事件会在该行显示时立即触发，
+    /// 每个此类目标各触发一次。
+    pub track_events_on_all_targets: bool,
+}
+
+impl MortarRevealPolicySettings {
+    pub(super) fn tracks_events(&self, policy: Option<&MortarRevealPolicy>) -> bool {
+        self.track_events_on_all_targets || MortarRevealPolicy::is_gradual(policy)
+    }
+}
+
+/// Seconds a reader needs for `body` at [`READING_CHARS_PER_SECOND`].
+///
+/// 以 [`READING_CHARS_PER_SECOND`] 阅读 `body` 所需的秒数。
+pub fn estimate_read_seconds(body: &str) -> f32 {
+    body.chars().count() as f32 / READING_CHARS_PER_SECOND
+}
+
+/// Time a `Timed` target has shown its current line.
+#[cfg(feature = "ui")]
+#[derive(Component, Default)]
+pub(super) struct TimedLineClock {
+    elapsed: f32,
+    cleared: bool,
+}
+
+#[cfg(feature = "ui")]
+type PolicyQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        Ref<'static, MortarDialogueText>,
+        &'static MortarRevealPolicy,
+        &'static mut Text,
+        Option<&'static mut MortarEventBinding>,
+        Option<&'static mut MortarLineStatus>,
+        Option<&'static mut TimedLineClock>,
+    ),
+    With<MortarTextTarget>,
+>;
+
+/// Keeps `Instant` and `Timed` targets on the full body and clears `Timed` ones when they expire.
+#[cfg(feature = "ui")]
+pub(super) fn apply_reveal_policies(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut targets: PolicyQuery,
+) {
+    for (entity, dialogue_text, policy, mut text, binding, status, clock) in &mut targets {
+        let seconds = match *policy {
+            MortarRevealPolicy::Gradual => continue,
+            MortarRevealPolicy::Instant => None,
+            MortarRevealPolicy::Timed { seconds } => Some(seconds),
+        };
+        let len = dialogue_text.body.chars().count();
+        if let Some(mut binding) = binding
+            && binding.current_index != len as f32
+        {
+            binding.current_index = len as f32;
+        }
+        set_reveal_complete(&mut commands, entity, status, true);
+
+        let expired = match (seconds, clock) {
+            (None, _) => false,
+            (Some(_), None) => {
+                commands.entity(entity).insert(TimedLineClock::default());
+                false
+            }
+            (Some(_), Some(mut clock)) if dialogue_text.is_changed() => {
+                *clock = TimedLineClock::default();
+                false
+            }
+            (Some(_), Some(clock)) if clock.cleared => continue,
+            (Some(seconds), Some(mut clock)) => {
+                clock.elapsed += time.delta_secs();
+                clock.cleared =
+                    clock.elapsed >= seconds + estimate_read_seconds(&dialogue_text.body);
+                clock.cleared
+            }
+        };
+        if expired {
+            text.0.clear();
+        } else {
+            let full = compose(&dialogue_text, len);
+            if text.0 != full {
+                text.0 = full;
+            }
+        }
+    }
+}
@@ -13,38 +13,105 @@
 //! 负责执行 Mortar 对话内容里的 `run_event` 和 `run_timeline` 条目。它会安排带延迟
 //! 的 run，按正确时机分发游戏事件，并让对话运行时知道由 run 驱动的暂停何时开始和结束。
 
-use bevy::asset::Assets;
+use bevy::asset::{AssetId, Assets};
 use bevy::prelude::*;
 use std::time::Duration;
 
-use crate::{DialogueRunKind, MortarAsset, MortarRegistry, MortarRuntime};
+use crate::debug::LOG_RUNS;
+use crate::runtime::SignalWait;
+use crate::{DialogueRunKind, MortarAsset, MortarRegistry, MortarRuntime, MortarTrackerMode};
 
-use super::{MortarEventBinding, MortarGameEvent, MortarRunsExecuting, MortarTextTarget};
+mod chain;
+mod steps;
+#[cfg(feature = "ui")]
+mod text_behavior;
 
-/// Component that schedules pending run/timeline execution with timers.
+pub use chain::{DEFAULT_IMMEDIATE_STEPS_PER_FRAME, MortarTimelineSettings};
+use chain::{run_steps, spawn_steps};
+use steps::{RunStep, SIGNAL_STEP, timeline_step};
+#[cfg(feature = "ui")]
+pub(super) use text_behavior::{RunClearedText, restore_text_after_runs};
+
+use super::event_schemas::run_allowed;
+use super::event_targets::GameEventWriter;
+use super::{MortarEventBinding, MortarEventDiagnostics, MortarEventSchemas, MortarRunsExecuting};
+
+/// How `MortarTextTarget`s behave while `run` statements execute.
+///
+/// Insert as a resource to set the default for every target, or as a component on a
+/// [`MortarTextTarget`] entity to override it for that target only.
+///
+/// `run` 语句执行期间 `MortarTextTarget` 的表现方式。
+///
+/// 作为资源插入时设置所有目标的默认行为；作为组件插入到 [`MortarTextTarget`] 实体上时仅覆盖该目标。
+#[derive(Resource, Component, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RunTextBehavior {
+    /// Clear the text while runs execute and restore it if the line did not change afterwards.
+    ///
+    /// 执行期间清空文本；若执行结束后文本未变化则自动恢复。
+    #[default]
+    Clear,
+    /// Leave the text untouched.
+    ///
+    /// 保持文本不变。
+    Keep,
+    /// Keep the text but flag [`MortarLineStatus::blocked_by_runs`] so UIs can style it.
+    ///
+    /// 保留文本，但设置 [`MortarLineStatus::blocked_by_runs`]，便于 UI 调整样式。
+    Dim,
+}
+
+/// Per-target status of the displayed line.
+///
+/// 每个文本目标当前显示行的状态。
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MortarLineStatus {
+    /// True while `run` statements hold the dialogue under [`RunTextBehavior::Dim`].
+    ///
+    /// 在 [`RunTextBehavior::Dim`] 下，`run` 语句阻塞对话期间为 true。
+    pub blocked_by_runs: bool,
+    /// True once the whole line is revealed by [`crate::MortarTextReveal`] or a seek.
+    ///
+    /// 当整行已由 [`crate::MortarTextReveal`] 或定位显示完毕时为 true。
+    pub reveal_complete: bool,
+    /// True while [`crate::MortarTextReveal`] holds at a checkpoint of the line, waiting for an
+    /// advance input to continue it (see [`crate::PAUSE_TOKEN`]).
+    ///
+    /// 当 [`crate::MortarTextReveal`] 停在该行的检查点、等待推进输入以继续显示时为 true
+    /// （见 [`crate::PAUSE_TOKEN`]）。
+    pub awaiting_advance: bool,
+}
+
+/// Component that schedules pending run/timeline execution with timers or signal waits.
 ///
-/// 使用计时器安排待执行 run 或时间线的组件。
-#[derive(Component)]
+/// 使用计时器或信号等待安排待执行 run 或时间线的组件。
+#[derive(Component, Reflect)]
+#[reflect(Component)]
 pub(super) struct PendingRunExecution {
     timer: Timer,
-    remaining_runs: Vec<(String, Option<f64>, bool)>,
-    event_defs: Vec<mortar_compiler::EventDef>,
-    timeline_defs: Vec<mortar_compiler::TimelineDef>,
+    /// Signal the next step waits for; the timer is then its timeout, if any.
+    signal: Option<SignalWait>,
+    remaining_runs: Vec<RunStep>,
+    /// Asset whose events the steps name.
+    asset: AssetId<MortarAsset>,
 }
 
 pub(super) fn trigger_bound_events(
-    mut query: Query<(Entity, &MortarEventBinding, &mut crate::MortarEventTracker)>,
+    mut query: Query<(
+        Entity,
+        &MortarEventBinding,
+        &mut crate::MortarEventTracker,
+        Option<&MortarTrackerMode>,
+    )>,
     runtime: Res<MortarRuntime>,
-    mut writer: MessageWriter<MortarGameEvent>,
+    default_mode: Res<MortarTrackerMode>,
+    mut writer: GameEventWriter,
 ) {
-    for (entity, binding, mut tracker) in &mut query {
-        let actions = tracker.trigger_at_index(binding.current_index, &runtime);
+    for (entity, binding, mut tracker, mode) in &mut query {
+        let mode = mode.copied().unwrap_or(*default_mode);
+        let actions = tracker.scrub_to(binding.current_index, mode, &runtime);
         for action in actions {
-            writer.write(MortarGameEvent {
-                source: Some(entity),
-                name: action.action_name,
-                args: action.args,
-            });
+            writer.write(Some(entity), action.action_name, action.args);
         }
     }
 }
@@ -54,9 +121,15 @@ pub(super) fn process_run_statements_after_text(
     mut runtime: ResMut<MortarRuntime>,
     registry: Res<MortarRegistry>,
     assets: Res<Assets<MortarAsset>>,
-    mut text_query: Query<&mut Text, With<MortarTextTarget>>,
+    #[cfg(feature = "ui")] mut text_query: text_behavior::RunTextTargetQuery,
+    #[cfg(feature = "ui")] run_text_behavior: Res<RunTextBehavior>,
     mut runs_executing: ResMut<MortarRunsExecuting>,
-    mut game_events: MessageWriter<MortarGameEvent>,
+    mut game_events: GameEventWriter,
+    (schemas, mut diagnostics): (
+        Option<Res<MortarEventSchemas>>,
+        ResMut<MortarEventDiagnostics>,
+    ),
+    settings: Res<MortarTimelineSettings>,
 ) {
     if !runtime.is_changed() {
         return;
@@ -66,24 +139,35 @@ pub(super) fn process_run_statements_after_text(
         return;
     };
 
-    let Some(start_search_idx) = state.pending_run_position else {
-        return;
+    // Runs skipped by a mid-node entry play before the entry line, so no line of this node is
+    // on screen yet and the text behavior does not apply.
+    let entry_runs = state.take_entry_runs();
+    let is_entry = !entry_runs.is_empty();
+    let run_items = if is_entry {
+        entry_runs
+    } else {
+        let Some(start_search_idx) = state.pending_run_position else {
+            return;
+        };
+        if start_search_idx >= state.node_data().content.len() {
+            state.pending_run_position = None;
+            return;
+        }
+        state.collect_run_items_from(start_search_idx)
     };
-
-    if start_search_idx >= state.node_data().content.len() {
-        state.pending_run_position = None;
-        return;
-    }
-
-    let run_items = state.collect_run_items_from(start_search_idx);
     let mortar_path = state.mortar_path.clone();
+    let (node, text_index) = (state.current_node.clone(), state.text_index);
     let mut run_sequence = Vec::new();
     let mut content_indices_to_mark = Vec::new();
 
     for item in &run_items {
         match item.kind {
             DialogueRunKind::Event | DialogueRunKind::Timeline => {
-                run_sequence.push((item.name.clone(), None::<f64>, item.ignore_duration));
+                // A node override may point the run at another event or timeline.
+                //
+                // 节点覆盖可能让 run 指向另一个事件或时间线。
+                let name = state.overrides().get(&item.name).unwrap_or(&item.name);
+                run_sequence.push((name.clone(), None::<f64>, item.ignore_duration));
                 content_indices_to_mark.push(item.content_index);
             }
         }
@@ -103,31 +187,28 @@ pub(super) fn process_run_statements_after_text(
         return;
     };
 
-    let event_defs = &asset.data.events;
-    let timeline_defs = &asset.data.timelines;
-
-    let run_sequence_with_durations: Vec<(String, Option<f64>, bool)> = run_sequence
+    let asset = (handle.id(), asset);
+    let context = (mortar_path.as_str(), node.as_str(), text_index);
+    let run_sequence_with_durations: Vec<RunStep> = run_sequence
         .iter()
+        .filter(|(name, ..)| {
+            run_allowed(schemas.as_deref(), &mut diagnostics, asset.1, name, context)
+        })
         .map(|(name, _, ignore_duration)| {
-            let duration = event_defs
-                .iter()
-                .find(|e| e.name == *name)
-                .and_then(|e| e.duration);
+            let duration = asset.1.event_def(name).and_then(|e| e.duration);
             (name.clone(), duration, *ignore_duration)
         })
         .collect();
 
-    runs_executing.executing = true;
-
-    for mut text in &mut text_query {
-        **text = String::new();
-    }
+    runs_executing.executing = !run_sequence_with_durations.is_empty();
+    let signal_sequence = runtime.signals.sequence();
 
     if run_sequence_with_durations.len() > 1 {
-        let pending = start_timeline_execution(
+        let pending = spawn_steps(
             run_sequence_with_durations,
-            event_defs.to_vec(),
-            timeline_defs.to_vec(),
+            asset,
+            signal_sequence,
+            *settings,
             &mut commands,
             &mut game_events,
         );
@@ -137,8 +218,9 @@ pub(super) fn process_run_statements_after_text(
     } else if let Some((event_name, _, _)) = run_sequence_with_durations.first() {
         let timeline_running = execute_run_by_name(
             event_name,
-            event_defs,
-            timeline_defs,
+            asset,
+            signal_sequence,
+            *settings,
             &mut commands,
             &mut game_events,
         );
@@ -148,6 +230,15 @@ pub(super) fn process_run_statements_after_text(
         }
     }
 
+    // Runs that finished within this frame leave the line alone, so nothing downstream sees the
+    // text change and change back.
+    //
+    // 在本帧内即已结束的 run 不会改动当前行，因此下游不会观察到文本被改动又改回。
+    #[cfg(feature = "ui")]
+    if !is_entry && runs_executing.executing {
+        text_behavior::apply_run_text_behavior(&mut commands, &mut text_query, *run_text_behavior);
+    }
+
     if let Some(state) = runtime.primary_dialogue_state_mut() {
         for idx in content_indices_to_mark {
             state.mark_content_executed(idx);
@@ -156,80 +247,89 @@ pub(super) fn process_run_statements_after_text(
     }
 }
 
+/// Whether a pending execution may move on this frame, noting the signals that released it.
+fn pending_ready(
+    pending: &mut PendingRunExecution,
+    delta: Duration,
+    runtime: &MortarRuntime,
+    released: &mut Vec<String>,
+) -> bool {
+    let Some(wait) = pending.signal.as_ref() else {
+        pending.timer.tick(delta);
+        return pending.timer.just_finished();
+    };
+    if runtime.signals.releases(wait) {
+        released.push(wait.name.clone());
+        return true;
+    }
+    let Some(timeout) = wait.timeout else {
+        return false;
+    };
+    pending.timer.tick(delta);
+    if !pending.timer.just_finished() {
+        return false;
+    }
+    warn!(
+        target: LOG_RUNS,
+        "Signal '{}' did not arrive within {}s; continuing the timeline", wait.name, timeout
+    );
+    true
+}
+
 pub(super) fn process_pending_run_executions(
     mut commands: Commands,
     time: Res<Time>,
+    assets: Res<Assets<MortarAsset>>,
     mut query: Query<(Entity, &mut PendingRunExecution)>,
     mut runtime: ResMut<MortarRuntime>,
     mut runs_executing: ResMut<MortarRunsExecuting>,
-    mut game_events: MessageWriter<MortarGameEvent>,
+    mut game_events: GameEventWriter,
+    settings: Res<MortarTimelineSettings>,
 ) {
+    let mut released = Vec::new();
     for (entity, mut pending) in &mut query {
-        pending.timer.tick(time.delta());
-
-        if !pending.timer.just_finished() {
-            continue;
-        }
-
-        if pending.remaining_runs.is_empty() {
+        // Stopping the dialogue cancels the waits it was blocked on.
+        //
+        // 停止对话会取消其所阻塞的等待。
+        if pending.signal.is_some() && runtime.primary_dialogue.is_none() {
+            debug!(target: LOG_RUNS, "Dialogue stopped, cancelling a signal wait");
             commands.entity(entity).despawn();
             runs_executing.executing = false;
-            if runtime.has_active_dialogues() {
-                runtime.set_changed();
-            }
             continue;
         }
-
-        if let Some((event_name, _, _)) = pending.remaining_runs.first()
-            && event_name != "__WAIT__"
-            && let Some(event_def) = pending.event_defs.iter().find(|e| e.name == *event_name)
-        {
-            dispatch_game_event(&event_def.action, &mut game_events);
+        if !pending_ready(&mut pending, time.delta(), &runtime, &mut released) {
+            continue;
         }
 
-        if pending.remaining_runs.len() > 1 {
-            let remaining = pending.remaining_runs[1..].to_vec();
-            let next_event = &pending.remaining_runs[0];
-
-            let duration_secs = if next_event.0 == "__WAIT__" {
-                next_event.1.unwrap_or(0.0)
-            } else if next_event.2 {
-                0.0
-            } else {
-                pending
-                    .event_defs
-                    .iter()
-                    .find(|e| e.name == next_event.0)
-                    .and_then(|e| e.duration)
-                    .unwrap_or(0.0)
-            };
-
-            if duration_secs > 0.0 {
-                pending.remaining_runs = remaining;
-                pending
-                    .timer
-                    .set_duration(Duration::from_secs_f32(duration_secs as f32));
-                pending.timer.reset();
-            } else {
-                let event_defs = pending.event_defs.clone();
-                let timeline_defs = pending.timeline_defs.clone();
-                commands.entity(entity).despawn();
-                let _ = start_timeline_execution(
-                    remaining,
-                    event_defs,
-                    timeline_defs,
-                    &mut commands,
-                    &mut game_events,
-                );
-            }
-        } else {
+        let Some(asset) = assets.get(pending.asset) else {
+            warn!(target: LOG_RUNS, "Asset of a running timeline is gone, dropping the timeline");
             commands.entity(entity).despawn();
             runs_executing.executing = false;
-            if runtime.has_active_dialogues() {
-                runtime.set_changed();
+            continue;
+        };
+
+        let steps = std::mem::take(&mut pending.remaining_runs);
+        let sequence = runtime.signals.sequence();
+        match run_steps(
+            steps,
+            (pending.asset, asset),
+            sequence,
+            *settings,
+            &mut game_events,
+        ) {
+            Some(next) => *pending = next,
+            None => {
+                commands.entity(entity).despawn();
+                runs_executing.executing = false;
+                if runtime.has_active_dialogues() {
+                    runtime.set_changed();
+                }
             }
         }
     }
+    for name in released {
+        runtime.signals.consume(&name);
+    }
 }
 
 pub(super) fn clear_runs_executing_flag(
@@ -247,46 +347,55 @@ pub(super) fn clear_runs_executing_flag(
 
 fn execute_run_by_name(
     event_name: &str,
-    event_defs: &[mortar_compiler::EventDef],
-    timeline_defs: &[mortar_compiler::TimelineDef],
+    asset: (AssetId<MortarAsset>, &MortarAsset),
+    signal_sequence: u64,
+    settings: MortarTimelineSettings,
     commands: &mut Commands,
-    game_events: &mut MessageWriter<MortarGameEvent>,
+    game_events: &mut GameEventWriter,
 ) -> bool {
-    if let Some(event_def) = event_defs.iter().find(|e| e.name == event_name) {
+    if let Some(event_def) = asset.1.event_def(event_name) {
+        verbose_trace!(
+            Runs,
+            "Run '{}' dispatches event {:?}",
+            event_name,
+            event_def.action
+        );
         dispatch_game_event(&event_def.action, game_events);
         return false;
     }
 
-    let Some(timeline_def) = timeline_defs.iter().find(|t| t.name == event_name) else {
-        warn!("Run statement target not found: {}", event_name);
+    let Some(timeline_def) = asset.1.timeline_def(event_name) else {
+        warn!(target: LOG_RUNS, "Run statement target not found: {}", event_name);
         return false;
     };
 
-    let mut timeline_sequence = Vec::new();
-    for stmt in &timeline_def.statements {
-        match stmt.stmt_type.as_str() {
-            "run" => {
-                let Some(event_name) = &stmt.event_name else {
-                    continue;
-                };
-                let duration = stmt.duration.filter(|_| !stmt.ignore_duration);
-                timeline_sequence.push((event_name.clone(), duration, stmt.ignore_duration));
-            }
-            "wait" => {
-                let Some(duration) = stmt.duration else {
-                    continue;
-                };
-                timeline_sequence.push(("__WAIT__".to_string(), Some(duration), false));
-            }
-            _ => {}
-        }
+    let mut timeline_sequence: Vec<RunStep> = timeline_def
+        .statements
+        .iter()
+        .filter_map(timeline_step)
+        .collect();
+    // The wait of a step holds back the step after it, so a trailing signal wait needs one.
+    //
+    // 步骤的等待会推迟其后一个步骤，因此末尾的信号等待需要一个后续步骤。
+    if timeline_sequence
+        .last()
+        .is_some_and(|(name, _, _)| name.starts_with(SIGNAL_STEP))
+    {
+        timeline_sequence.push(("__WAIT__".to_string(), Some(0.0), false));
     }
 
+    verbose_trace!(
+        Runs,
+        "Run '{}' starts a timeline of {} steps",
+        event_name,
+        timeline_sequence.len()
+    );
     if !timeline_sequence.is_empty() {
-        let _ = start_timeline_execution(
+        let _ = spawn_steps(
             timeline_sequence,
-            event_defs.to_vec(),
-            timeline_defs.to_vec(),
+            asset,
+            signal_sequence,
+            settings,
             commands,
             game_events,
         );
@@ -295,72 +404,6 @@ fn execute_run_by_name(
     false
 }
 
-fn start_timeline_execution(
-    sequence: Vec<(String, Option<f64>, bool)>,
-    event_defs: Vec<mortar_compiler::EventDef>,
-    timeline_defs: Vec<mortar_compiler::TimelineDef>,
-    commands: &mut Commands,
-    game_events: &mut MessageWriter<MortarGameEvent>,
-) -> bool {
-    let mut spawned_async = false;
-
-    if let Some((first_event, first_duration, ignore_duration)) = sequence.first() {
-        if first_event != "__WAIT__"
-            && let Some(event_def) = event_defs.iter().find(|e| e.name == *first_event)
-        {
-            dispatch_game_event(&event_def.action, game_events);
-        }
-
-        if sequence.len() > 1 {
-            let remaining = sequence[1..].to_vec();
-            let duration_secs = if first_event == "__WAIT__" {
-                first_duration.unwrap_or(0.0)
-            } else if *ignore_duration {
-                0.0
-            } else {
-                event_defs
-                    .iter()
-                    .find(|e| e.name == *first_event)
-                    .and_then(|e| e.duration)
-                    .unwrap_or(0.0)
-            };
-
-            if duration_secs > 0.0 {
-                commands.spawn((PendingRunExecution {
-                    timer: Timer::from_seconds(duration_secs as f32, TimerMode::Once),
-                    remaining_runs: remaining,
-                    event_defs,
-                    timeline_defs,
-                },));
-                spawned_async = true;
-            } else {
-                spawned_async |= start_timeline_execution(
-                    remaining,
-                    event_defs,
-                    timeline_defs,
-                    commands,
-                    game_events,
-                );
-            }
-        }
-    }
-
-    spawned_async
-}
-
-fn dispatch_game_event(
-    action: &mortar_compiler::Action,
-    events: &mut MessageWriter<MortarGameEvent>,
-) {
-    let parsed_args: Vec<String> = action
-        .args
-        .iter()
-        .map(|arg| arg.trim_matches('"').to_string())
-        .collect();
-
-    events.write(MortarGameEvent {
-        source: None,
-        name: action.action_type.clone(),
-        args: parsed_args,
-    });
+fn dispatch_game_event(action: &mortar_compiler::Action, events: &mut GameEventWriter) {
+    events.write_unquoted(None, action.action_type.clone(), &action.args);
 }
@@ -0,0 +1,102 @@
+//! # chain.rs
+//!
+//! # chain.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Walks a run sequence from its next step: steps separated by nothing are dispatched in a loop
+//! until one has to wait, and the rest is handed to a [`PendingRunExecution`]. A per-frame budget
+//! of instantaneous steps ([`MortarTimelineSettings`]) carries a long chain of zero-duration steps
+//! over several frames instead of blocking one, in the same order and with runs still executing.
+//!
+//! 从下一步开始推进 run 序列：彼此之间没有间隔的步骤会在循环中依次分发，直到某一步需要等待，其余
+//! 步骤交给 [`PendingRunExecution`]。每帧的瞬时步骤预算（[`MortarTimelineSettings`]）会把一长串零
+//! 时长步骤分摊到多帧，而不是阻塞单帧；顺序保持不变，run 在此期间仍视为正在执行。
+
+use bevy::asset::AssetId;
+use bevy::prelude::*;
+
+use super::super::event_targets::GameEventWriter;
+use super::steps::{RunStep, StepDelay, step_delay};
+use super::{PendingRunExecution, dispatch_game_event};
+use crate::MortarAsset;
+
+/// Instantaneous steps one timeline dispatches per frame unless configured otherwise.
+///
+/// 默认情况下单个时间线每帧分发的瞬时步骤数。
+pub const DEFAULT_IMMEDIATE_STEPS_PER_FRAME: usize = 64;
+
+/// Settings for executing run sequences and timelines.
+///
+/// 执行 run 序列与时间线的设置。
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MortarTimelineSettings {
+    /// Most steps a timeline dispatches in one frame while they take no time; the rest continue
+    /// next frame. `None` dispatches the whole chain at once.
+    ///
+    /// 步骤不耗时时，单个时间线在一帧内最多分发的步骤数；其余步骤在下一帧继续。为 `None` 时一次
+    /// 分发整条链。
+    pub immediate_steps_per_frame: Option<usize>,
+}
+
+impl Default for MortarTimelineSettings {
+    fn default() -> Self {
+        Self {
+            immediate_steps_per_frame: Some(DEFAULT_IMMEDIATE_STEPS_PER_FRAME),
+        }
+    }
+}
+
+/// Dispatches `steps` from the first until one has to wait or the budget is spent, returning the
+/// execution that holds the rest, if any.
+pub(super) fn run_steps(
+    steps: Vec<RunStep>,
+    (asset_id, asset): (AssetId<MortarAsset>, &MortarAsset),
+    signal_sequence: u64,
+    settings: MortarTimelineSettings,
+    game_events: &mut GameEventWriter,
+) -> Option<PendingRunExecution> {
+    let budget = settings
+        .immediate_steps_per_frame
+        .map_or(usize::MAX, |steps| steps.max(1));
+    for (index, step) in steps.iter().enumerate() {
+        if let Some(event_def) = asset.event_def(&step.0) {
+            dispatch_game_event(&event_def.action, game_events);
+        }
+        let next = index + 1;
+        if next == steps.len() {
+            break;
+        }
+        let delay = step_delay(step, asset, signal_sequence);
+        if matches!(delay, StepDelay::Immediate) && next < budget {
+            continue;
+        }
+        let mut pending = PendingRunExecution {
+            timer: Timer::default(),
+            signal: None,
+            remaining_runs: Vec::new(),
+            asset: asset_id,
+        };
+        pending.wait(delay, steps[next..].to_vec());
+        return Some(pending);
+    }
+    None
+}
+
+/// Runs `steps` and spawns the execution that holds the rest, returning whether it did.
+pub(super) fn spawn_steps(
+    steps: Vec<RunStep>,
+    asset: (AssetId<MortarAsset>, &MortarAsset),
+    signal_sequence: u64,
+    settings: MortarTimelineSettings,
+    commands: &mut Commands,
+    game_events: &mut GameEventWriter,
+) -> bool {
+    let Some(pending) = run_steps(steps, asset, signal_sequence, settings, game_events) else {
+        return false;
+    };
+    commands.spawn((pending, crate::MortarInternal));
+    true
+}
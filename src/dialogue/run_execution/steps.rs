@@ -0,0 +1,110 @@
+//! # steps.rs
+//!
+//! # steps.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Steps of a run sequence and what separates each from the next: nothing, an event's duration,
+//! a `wait`, or a `wait_signal` (see [`crate::MortarRuntime::signal`]).
+//!
+//! run 序列的步骤，以及每一步与下一步之间的间隔：无间隔、事件的持续时间、`wait`，或
+//! `wait_signal`（参见 [`crate::MortarRuntime::signal`]）。
+
+use bevy::prelude::*;
+use std::time::Duration;
+
+use super::PendingRunExecution;
+use crate::MortarAsset;
+use crate::debug::LOG_RUNS;
+use crate::runtime::SignalWait;
+
+/// One step of a run sequence: name, duration and `ignore_duration`. A `__WAIT__` step only
+/// waits its duration; a [`SIGNAL_STEP`] step waits for a signal, its duration being the timeout
+/// and its flag whether the wait is latched.
+pub(super) type RunStep = (String, Option<f64>, bool);
+
+/// Name prefix of a step waiting for the signal named by the rest of the name.
+pub(super) const SIGNAL_STEP: &str = "__SIGNAL__:";
+
+/// What separates a step from the next one.
+pub(super) enum StepDelay {
+    Immediate,
+    Timer(f64),
+    Signal(SignalWait),
+}
+
+impl PendingRunExecution {
+    /// Holds `remaining_runs` back by `delay`, which is never [`StepDelay::Immediate`].
+    pub(super) fn wait(&mut self, delay: StepDelay, remaining_runs: Vec<RunStep>) {
+        let seconds = match delay {
+            StepDelay::Immediate => {
+                self.signal = None;
+                0.0
+            }
+            StepDelay::Timer(seconds) => {
+                self.signal = None;
+                seconds
+            }
+            StepDelay::Signal(wait) => {
+                let timeout = wait.timeout.unwrap_or(0.0);
+                self.signal = Some(wait);
+                timeout
+            }
+        };
+        self.remaining_runs = remaining_runs;
+        self.timer
+            .set_duration(Duration::from_secs_f32(seconds as f32));
+        self.timer.reset();
+    }
+}
+
+/// The delay after `step`, with signal waits beginning at `signal_sequence`.
+pub(super) fn step_delay(step: &RunStep, asset: &MortarAsset, signal_sequence: u64) -> StepDelay {
+    let (name, duration, flag) = step;
+    if let Some(signal) = name.strip_prefix(SIGNAL_STEP) {
+        return StepDelay::Signal(SignalWait {
+            name: signal.to_owned(),
+            since: signal_sequence,
+            latch: *flag,
+            timeout: duration.filter(|timeout| *timeout > 0.0),
+        });
+    }
+    let seconds = if name == "__WAIT__" {
+        duration.unwrap_or(0.0)
+    } else if *flag {
+        0.0
+    } else {
+        asset
+            .event_def(name)
+            .and_then(|e| e.duration)
+            .unwrap_or(0.0)
+    };
+    if seconds > 0.0 {
+        StepDelay::Timer(seconds)
+    } else {
+        StepDelay::Immediate
+    }
+}
+
+/// The run step of a timeline statement, if it has one.
+pub(super) fn timeline_step(stmt: &mortar_compiler::TimelineStmt) -> Option<RunStep> {
+    match stmt.stmt_type.as_str() {
+        "run" => {
+            let event_name = stmt.event_name.as_ref()?;
+            let duration = stmt.duration.filter(|_| !stmt.ignore_duration);
+            Some((event_name.clone(), duration, stmt.ignore_duration))
+        }
+        "wait" => Some(("__WAIT__".to_string(), Some(stmt.duration?), false)),
+        "wait_signal" => {
+            let Some(signal) = stmt.event_name.as_ref() else {
+                warn!(target: LOG_RUNS, "Timeline wait_signal without a signal name");
+                return None;
+            };
+            let latch = stmt.args.iter().any(|arg| arg.trim_matches('"') == "latch");
+            Some((format!("{SIGNAL_STEP}{signal}"), stmt.duration, latch))
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,111 @@
+//! # text_behavior.rs
+//!
+//! # text_behavior.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Applies [`RunTextBehavior`] to the `Text` of each target while `run` statements execute, and
+//! restores cleared text once they finish. It needs Bevy's text components, so it only exists with
+//! the `ui` feature.
+//!
+//! 在 `run` 语句执行期间将 [`RunTextBehavior`] 应用到每个目标的 `Text` 上，并在执行结束后恢复被
+//! 清空的文本。它需要 Bevy 的文本组件，因此只在启用 `ui` 特性时存在。
+
+use bevy::prelude::*;
+
+use super::super::{MortarDialogueText, MortarRunsExecuting, MortarTextTarget};
+use super::{MortarLineStatus, RunTextBehavior};
+
+/// Text cleared by [`RunTextBehavior::Clear`], kept so it can be restored after the runs.
+///
+/// 被 [`RunTextBehavior::Clear`] 清空的文本，保存下来以便执行结束后恢复。
+#[derive(Component)]
+pub(in crate::dialogue) struct RunClearedText(MortarDialogueText);
+
+pub(super) type RunTextTargetQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static mut Text,
+        Option<&'static mut MortarDialogueText>,
+        Option<&'static RunTextBehavior>,
+    ),
+    With<MortarTextTarget>,
+>;
+
+pub(super) fn apply_run_text_behavior(
+    commands: &mut Commands,
+    text_query: &mut RunTextTargetQuery,
+    default_behavior: RunTextBehavior,
+) {
+    for (entity, mut text, dialogue_text, behavior) in text_query {
+        match behavior.copied().unwrap_or(default_behavior) {
+            RunTextBehavior::Keep => {}
+            RunTextBehavior::Dim => {
+                commands
+                    .entity(entity)
+                    .entry::<MortarLineStatus>()
+                    .and_modify(|mut status| status.blocked_by_runs = true)
+                    .or_insert(MortarLineStatus {
+                        blocked_by_runs: true,
+                        ..default()
+                    });
+            }
+            RunTextBehavior::Clear => {
+                if let Some(mut dialogue_text) = dialogue_text {
+                    let previous = std::mem::take(&mut *dialogue_text);
+                    commands.entity(entity).insert(RunClearedText(previous));
+                }
+                **text = String::new();
+            }
+        }
+    }
+}
+
+/// Restores text cleared during runs and lifts the blocked flag once runs finish.
+/// New lines written afterwards by the text system replace the restored text.
+///
+/// run 执行结束后恢复被清空的文本并解除阻塞标记。之后文本系统写入的新行会覆盖恢复的文本。
+pub(in crate::dialogue) fn restore_text_after_runs(
+    mut commands: Commands,
+    runs_executing: Res<MortarRunsExecuting>,
+    mut cleared: Query<
+        (
+            Entity,
+            &mut Text,
+            Option<&mut MortarDialogueText>,
+            &RunClearedText,
+        ),
+        With<MortarTextTarget>,
+    >,
+    mut statuses: Query<&mut MortarLineStatus>,
+) {
+    if runs_executing.executing {
+        return;
+    }
+
+    for (entity, mut text, dialogue_text, RunClearedText(previous)) in &mut cleared {
+        let full_text = previous.full_text();
+        if text.0 != full_text {
+            text.0 = full_text;
+        }
+        match dialogue_text {
+            Some(mut dialogue_text) => {
+                dialogue_text.set_if_neq(previous.clone());
+            }
+            None => {
+                commands.entity(entity).insert(previous.clone());
+            }
+        }
+        commands.entity(entity).remove::<RunClearedText>();
+    }
+
+    for mut status in &mut statuses {
+        if status.blocked_by_runs {
+            status.blocked_by_runs = false;
+        }
+    }
+}
@@ -0,0 +1,65 @@
+//! # schedule.rs
+//!
+//! # schedule.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Which schedule the dialogue's timers run in. By default everything runs in `Update` and the
+//! timers advance by the frame time, so a timeline wait ends on whichever frame first crosses it.
+//! With [`MortarSchedule::FixedUpdate`] the run scheduler (timeline waits, event durations and
+//! signal-wait timeouts), the timed lines of [`crate::MortarRevealPolicy`] and the auto mode of
+//! [`crate::MortarAutoAdvance`] move to `FixedUpdate`, where Bevy's `Time` is `Time<Fixed>`: each
+//! tick advances them by exactly one step, so a wait of `d` seconds ends on tick `ceil(d / step)`
+//! after it starts, whatever the frame rate. The reveal driver follows its own setting, as a
+//! typewriter speed is usually tuned to the screen rather than to the simulation. Starting nodes,
+//! advancing and writing lines stay in `Update`, so a timer only starts ticking on the fixed tick
+//! after the frame that began it.
+//!
+//! The dialogue clock (the window focus policy of the `window` feature) gates these systems in
+//! both schedules: while it holds them, fixed ticks do not advance them either, and they pick up
+//! from where they were. The library has no choice timeouts or idle timers; choice re-checks of
+//! [`crate::MortarChoiceReevaluation`] and display rolls always run in `Update`.
+//!
+//! 对话计时器运行在哪个调度中。默认情况下一切都在 `Update` 中运行，计时器按帧时间推进，因此时间线
+//! 等待会在第一个越过它的帧结束。使用 [`MortarSchedule::FixedUpdate`] 时，run 调度器（时间线等待、
+//! 事件持续时间与信号等待超时）、[`crate::MortarRevealPolicy`] 的定时行以及
+//! [`crate::MortarAutoAdvance`] 的自动模式会移到 `FixedUpdate`，其中 Bevy 的 `Time` 即
+//! `Time<Fixed>`：每个刻恰好推进一个步长，因此持续 `d` 秒的等待会在开始后的第 `ceil(d / step)` 刻
+//! 结束，与帧率无关。显示驱动遵循其单独的设置，因为打字机速度通常按屏幕而非模拟来调整。开始节点、
+//! 推进与写入行仍在 `Update` 中进行，因此计时器要到开始它的那一帧之后的固定刻才开始计时。
+//!
+//! 对话时钟（`window` 特性的窗口焦点策略）在两个调度中都会控制这些系统：它暂停它们期间，固定刻也
+//! 不会推进它们，之后从原处继续。本库没有选项超时或空闲计时器；[`crate::MortarChoiceReevaluation`]
+//! 的选项重新检查与显示滚动始终在 `Update` 中运行。
+
+use bevy::ecs::intern::Interned;
+use bevy::ecs::schedule::ScheduleLabel;
+use bevy::prelude::*;
+
+/// The schedule a group of dialogue timers is added to.
+///
+/// 一组对话计时器所加入的调度。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MortarSchedule {
+    /// Timers advance by the frame time.
+    ///
+    /// 计时器按帧时间推进。
+    #[default]
+    Update,
+    /// Timers advance by the fixed step of `Time<Fixed>`, see
+    /// [`crate::MortarDialoguePlugin::schedule`].
+    ///
+    /// 计时器按 `Time<Fixed>` 的固定步长推进，参见 [`crate::MortarDialoguePlugin::schedule`]。
+    FixedUpdate,
+}
+
+impl MortarSchedule {
+    pub(super) fn label(self) -> Interned<dyn ScheduleLabel> {
+        match self {
+            Self::Update => Update.intern(),
+            Self::FixedUpdate => FixedUpdate.intern(),
+        }
+    }
+}
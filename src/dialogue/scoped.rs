@@ -0,0 +1,176 @@
+//! # scoped.rs
+//!
+//! # scoped.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Spawns short-lived presentation entities (emotes, speech-bubble tails, particles) that belong
+//! to the current line, node, or conversation of the primary dialogue, and despawns them together
+//! with their children once that scope ends. Each scope instance gets a fresh generation number, so
+//! an entity spawned late with the generation of a finished scope is removed instead of leaking into
+//! the next one.
+//!
+//! 生成属于主对话当前行、节点或整段对话的短生命周期表现实体（表情、气泡尾巴、粒子等），并在该范围
+//! 结束时连同子实体一起销毁。每个范围实例都有新的代数编号，因此带着已结束范围代数、延迟生成的实体会被
+//! 清理，而不会泄漏到下一个范围中。
+
+use bevy::ecs::system::{EntityCommands, SystemParam};
+use bevy::prelude::*;
+
+use crate::MortarRuntime;
+use crate::debug::LOG_DIALOGUE;
+
+use super::MortarEffectScope;
+use super::effects::ScopePosition;
+
+/// Ties an entity to one instance of a dialogue scope.
+///
+/// 将实体绑定到某个对话范围实例。
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MortarScoped {
+    pub scope: MortarEffectScope,
+    /// Generation of the scope instance the entity was spawned in.
+    ///
+    /// 实体生成时所在范围实例的代数。
+    pub generation: u64,
+}
+
+/// Current generation of each scope of the primary dialogue.
+///
+/// 主对话各个范围的当前代数。
+#[derive(Resource, Debug, Default)]
+pub struct MortarScopeGenerations {
+    line: u64,
+    node: u64,
+    conversation: u64,
+    next: u64,
+    position: Option<ScopePosition>,
+}
+
+impl MortarScopeGenerations {
+    /// Generation of the running instance of `scope`, or `None` while no dialogue is active.
+    ///
+    /// `scope` 当前运行实例的代数；没有活跃对话时返回 `None`。
+    pub fn current(&self, scope: MortarEffectScope) -> Option<u64> {
+        self.position.as_ref()?;
+        Some(match scope {
+            MortarEffectScope::Line => self.line,
+            MortarEffectScope::Node => self.node,
+            MortarEffectScope::Conversation => self.conversation,
+        })
+    }
+
+    fn slot(&mut self, scope: MortarEffectScope) -> &mut u64 {
+        match scope {
+            MortarEffectScope::Line => &mut self.line,
+            MortarEffectScope::Node => &mut self.node,
+            MortarEffectScope::Conversation => &mut self.conversation,
+        }
+    }
+
+    /// Returns true while the scope instance `scoped` belongs to is still running.
+    ///
+    /// `scoped` 所属的范围实例仍在运行时返回 true。
+    pub fn is_live(&self, scoped: &MortarScoped) -> bool {
+        self.current(scoped.scope) == Some(scoped.generation)
+    }
+
+    /// Starts new instances of every scope ended by moving to `position`.
+    fn sync(&mut self, position: Option<ScopePosition>) {
+        if self.position == position {
+            return;
+        }
+        let ended = match (&self.position, &position) {
+            (Some(previous), next) => previous.ended_scope(next.as_ref()),
+            (None, _) => Some(MortarEffectScope::Conversation),
+        };
+        let scopes = [
+            MortarEffectScope::Line,
+            MortarEffectScope::Node,
+            MortarEffectScope::Conversation,
+        ];
+        for scope in scopes.into_iter().filter(|scope| Some(*scope) <= ended) {
+            self.next += 1;
+            *self.slot(scope) = self.next;
+        }
+        self.position = position;
+    }
+}
+
+/// [`Commands`] wrapper that spawns entities tied to a scope of the primary dialogue.
+///
+/// Entities spawned while no dialogue is active are despawned on the next update.
+///
+/// 包装 [`Commands`]，用于生成绑定到主对话某个范围的实体。
+///
+/// 没有活跃对话时生成的实体会在下一次更新时被销毁。
+#[derive(SystemParam)]
+pub struct MortarScopedCommands<'w, 's> {
+    commands: Commands<'w, 's>,
+    generations: Res<'w, MortarScopeGenerations>,
+}
+
+impl<'w, 's> MortarScopedCommands<'w, 's> {
+    /// Spawns an entity that is despawned when the current line ends.
+    ///
+    /// 生成一个在当前行结束时销毁的实体。
+    pub fn spawn_for_line(&mut self, bundle: impl Bundle) -> EntityCommands<'_> {
+        self.spawn_scoped(MortarEffectScope::Line, bundle)
+    }
+
+    /// Spawns an entity that is despawned when the dialogue leaves the current node.
+    ///
+    /// 生成一个在对话离开当前节点时销毁的实体。
+    pub fn spawn_for_node(&mut self, bundle: impl Bundle) -> EntityCommands<'_> {
+        self.spawn_scoped(MortarEffectScope::Node, bundle)
+    }
+
+    /// Spawns an entity that is despawned when the dialogue finishes or is stopped.
+    ///
+    /// 生成一个在对话结束或被停止时销毁的实体。
+    pub fn spawn_for_conversation(&mut self, bundle: impl Bundle) -> EntityCommands<'_> {
+        self.spawn_scoped(MortarEffectScope::Conversation, bundle)
+    }
+
+    /// Spawns an entity tied to `scope`.
+    ///
+    /// 生成一个绑定到 `scope` 的实体。
+    pub fn spawn_scoped(
+        &mut self,
+        scope: MortarEffectScope,
+        bundle: impl Bundle,
+    ) -> EntityCommands<'_> {
+        let scoped = MortarScoped {
+            scope,
+            generation: self.generations.current(scope).unwrap_or_default(),
+        };
+        self.commands.spawn((bundle, scoped))
+    }
+
+    /// The wrapped commands.
+    ///
+    /// 被包装的命令队列。
+    pub fn commands(&mut self) -> &mut Commands<'w, 's> {
+        &mut self.commands
+    }
+}
+
+/// Advances scope generations and despawns scoped entities whose scope instance ended.
+///
+/// 推进范围代数，并销毁所属范围实例已结束的实体。
+pub(super) fn despawn_ended_scopes(
+    mut commands: Commands,
+    runtime: Res<MortarRuntime>,
+    mut generations: ResMut<MortarScopeGenerations>,
+    scoped: Query<(Entity, &MortarScoped)>,
+) {
+    generations.sync(ScopePosition::of(&runtime));
+    for (entity, scoped) in &scoped {
+        if !generations.is_live(scoped) {
+            dev_info!(target: LOG_DIALOGUE, "Despawning {:?} scoped to {:?}", entity, scoped.scope);
+            commands.entity(entity).despawn();
+        }
+    }
+}
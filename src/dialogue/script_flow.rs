@@ -0,0 +1,218 @@
+//! # script_flow.rs
+//!
+//! # script_flow.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Lets script events steer the dialogue. Event actions named `__next_text`, `__jump(node)`,
+//! `__select(index)`, `__confirm` and `__stop`, whether fired by a line's events or by a
+//! timeline, are turned into the matching [`MortarCommand`] for the primary dialogue and written
+//! through the normal queue, so the same ordering and guards apply as for player input. Each one
+//! is reported with a [`MortarScriptFlow`] message. They still reach the game as
+//! [`MortarGameEvent`]s.
+//!
+//! `__next_text` fired while the line is still revealing does not cut the line off: by default
+//! it finishes the reveal and then advances, or with [`MortarScriptAdvance::WaitForReveal`] it
+//! lets the reveal play out first. The request is dropped if the line changes in the meantime.
+//!
+//! 让脚本事件控制对话流程。名为 `__next_text`、`__jump(node)`、`__select(index)`、`__confirm` 与
+//! `__stop` 的事件动作，无论由行内事件还是时间线触发，都会被转换为主对话对应的 [`MortarCommand`]
+//! 并通过常规队列写入，因此与玩家输入适用相同的顺序与保护规则。每次转换都会通过
+//! [`MortarScriptFlow`] 消息报告。这些动作仍会作为 [`MortarGameEvent`] 送达游戏。
+//!
+//! 在行仍在逐字显示时触发的 `__next_text` 不会截断该行：默认会先补全显示再推进；若使用
+//! [`MortarScriptAdvance::WaitForReveal`]，则等待逐字显示自然播放完毕。若期间行已改变，该请求会被丢弃。
+
+use bevy::prelude::*;
+
+use super::MortarGameEvent;
+use crate::debug::LOG_DIALOGUE;
+use crate::{AdvanceIntent, DialogueState, MortarCommand, MortarRuntime};
+
+/// How a script `__next_text` treats a line that is still revealing.
+///
+/// 脚本中的 `__next_text` 如何对待仍在逐字显示的行。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MortarScriptAdvance {
+    /// Finish the reveal at once, then advance.
+    ///
+    /// 立即补全逐字显示，然后推进。
+    #[default]
+    FinishReveal,
+    /// Let the reveal play out, then advance.
+    ///
+    /// 等待逐字显示播放完毕，然后推进。
+    WaitForReveal,
+}
+
+/// Settings for script flow control.
+///
+/// 脚本流程控制的设置。
+#[derive(Resource, Debug, Clone, PartialEq, Eq)]
+pub struct MortarScriptFlowSettings {
+    /// Whether flow-control actions are applied; when `false` they are plain game events.
+    ///
+    /// 是否应用流程控制动作；为 `false` 时它们只是普通的游戏事件。
+    pub allow_script_flow_control: bool,
+    /// How `__next_text` treats a line that is still revealing.
+    ///
+    /// `__next_text` 如何对待仍在逐字显示的行。
+    pub advance: MortarScriptAdvance,
+}
+
+impl Default for MortarScriptFlowSettings {
+    fn default() -> Self {
+        Self {
+            allow_script_flow_control: true,
+            advance: MortarScriptAdvance::default(),
+        }
+    }
+}
+
+/// Emitted when a script action changes the dialogue flow, so observers can tell it apart from
+/// player input.
+///
+/// 当脚本动作改变对话流程时发出，便于观察者将其与玩家输入区分开。
+#[derive(Message, Debug, Clone)]
+pub struct MortarScriptFlow {
+    /// Source of the game event that carried the action.
+    ///
+    /// 携带该动作的游戏事件的来源。
+    pub source: Option<Entity>,
+    /// Action name, e.g. `__jump`.
+    ///
+    /// 动作名称，例如 `__jump`。
+    pub action: String,
+    /// Event written to the queue.
+    ///
+    /// 写入队列的事件。
+    pub event: MortarCommand,
+}
+
+/// A `__next_text` waiting for the reveal of its line.
+pub(super) struct PendingAdvance {
+    source: Option<Entity>,
+    line: (String, String, usize),
+    finish_requested: bool,
+}
+
+fn current_line(runtime: &MortarRuntime) -> Option<(String, String, usize)> {
+    let state = runtime.primary_dialogue_state()?;
+    Some((
+        state.mortar_path.clone(),
+        state.current_node.clone(),
+        state.text_index,
+    ))
+}
+
+/// Translates a flow-control action into the event it requests.
+fn flow_event(runtime: &MortarRuntime, event: &MortarGameEvent) -> Option<MortarCommand> {
+    let target = runtime.primary_dialogue;
+    let group = runtime
+        .primary_dialogue_state()
+        .map(DialogueState::group_token);
+    let arg = event.args.first().map(String::as_str);
+    match event.name.as_str() {
+        "__jump" => {
+            let (Some(node), Some(state)) = (arg, runtime.primary_dialogue_state()) else {
+                warn!(target: LOG_DIALOGUE, "Script __jump needs a node and an active dialogue");
+                return None;
+            };
+            Some(MortarCommand::StartNode {
+                path: state.mortar_path.clone(),
+                node: node.to_owned(),
+                target,
+                entry: None,
+            })
+        }
+        "__select" => {
+            let Some(index) = arg.and_then(|arg| arg.trim().parse().ok()) else {
+                warn!(target: LOG_DIALOGUE, "Script __select needs a choice index");
+                return None;
+            };
+            Some(MortarCommand::SelectChoice {
+                index,
+                target,
+                group,
+                source: None,
+            })
+        }
+        "__confirm" => Some(MortarCommand::ConfirmChoice { target, group }),
+        "__stop" => Some(MortarCommand::StopDialogue { target }),
+        _ => None,
+    }
+}
+
+/// Applies flow-control actions found among this frame's game events.
+pub(super) fn apply_script_flow_control(
+    settings: Res<MortarScriptFlowSettings>,
+    runtime: Res<MortarRuntime>,
+    mut game_events: MessageReader<MortarGameEvent>,
+    mut events: MessageWriter<MortarCommand>,
+    mut flows: MessageWriter<MortarScriptFlow>,
+    mut pending: Local<Option<PendingAdvance>>,
+) {
+    if !settings.allow_script_flow_control {
+        game_events.clear();
+        *pending = None;
+        return;
+    }
+    for game_event in game_events.read() {
+        if game_event.name == "__next_text" {
+            *pending = current_line(&runtime).map(|line| PendingAdvance {
+                source: game_event.source,
+                line,
+                finish_requested: false,
+            });
+            continue;
+        }
+        let Some(event) = flow_event(&runtime, game_event) else {
+            continue;
+        };
+        if matches!(
+            event,
+            MortarCommand::StartNode { .. } | MortarCommand::StopDialogue { .. }
+        ) {
+            *pending = None;
+        }
+        events.write(event.clone());
+        flows.write(MortarScriptFlow {
+            source: game_event.source,
+            action: game_event.name.clone(),
+            event,
+        });
+    }
+
+    let Some(advance) = pending.as_mut() else {
+        return;
+    };
+    if current_line(&runtime).as_ref() != Some(&advance.line) {
+        *pending = None;
+        return;
+    }
+    match runtime.advance_intent() {
+        AdvanceIntent::BlockedByRuns { .. } => return,
+        AdvanceIntent::RevealRemaining => {
+            if settings.advance == MortarScriptAdvance::FinishReveal && !advance.finish_requested {
+                advance.finish_requested = true;
+                events.write(MortarCommand::NextText {
+                    target: runtime.primary_dialogue,
+                });
+            }
+            return;
+        }
+        _ => {}
+    }
+    let event = MortarCommand::NextText {
+        target: runtime.primary_dialogue,
+    };
+    events.write(event.clone());
+    flows.write(MortarScriptFlow {
+        source: advance.source,
+        action: "__next_text".to_owned(),
+        event,
+    });
+    *pending = None;
+}
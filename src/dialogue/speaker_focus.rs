@@ -0,0 +1,175 @@
+//! # speaker_focus.rs
+//!
+//! # speaker_focus.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Turns who is speaking into data a camera rig can follow, without any camera math. Each shown
+//! line is classified from its text item: a `speaker` key makes it a line of that speaker, or a
+//! thought when the item's `tags` include `thought`; no speaker makes it narration; a
+//! `parallel_text` line focuses every voice of the item, by `speaker` or else `target`. Names
+//! resolve to entities through [`crate::MortarSpeakerRegistry`] first, then through
+//! [`crate::MortarSpeaker`] components. A [`MortarFocusHint`] is written only when the focus
+//! changes, and [`MortarSpeakerFocus`] keeps the latest one for polling until the dialogue ends.
+//! Both update in
+//! [`MortarDialogueSystemSet::UpdateText`](super::MortarDialogueSystemSet::UpdateText), so camera
+//! systems ordered after that set see them in the same frame.
+//!
+//! 将"谁在说话"转换为相机控制器可以跟随的数据，不包含任何相机运算。每个显示的行都根据其文本项分类：
+//! 带 `speaker` 键的是该说话者的台词，若该项的 `tags` 含有 `thought` 则为内心独白；没有说话者的是
+//! 旁白；`parallel_text` 行会聚焦该项的每个声部，按 `speaker`，否则按 `target`。名称先通过
+//! [`crate::MortarSpeakerRegistry`]、再通过 [`crate::MortarSpeaker`] 组件解析为实体。只有焦点变化时
+//! 才会写入 [`MortarFocusHint`]，[`MortarSpeakerFocus`] 则保存最新的提示供轮询，直到对话结束。
+//! 两者都在 [`MortarDialogueSystemSet::UpdateText`](super::MortarDialogueSystemSet::UpdateText) 中更新，
+//! 因此排在该集合之后的相机系统能在同一帧看到它们。
+
+use bevy::prelude::*;
+use serde_json::Value;
+
+use super::MortarTextChanged;
+use super::event_targets::SpeakerEntities;
+use crate::MortarRuntime;
+
+/// Text tag that marks a line as the speaker's thought.
+const THOUGHT_TAG: &str = "thought";
+
+/// One speaker a hint focuses on.
+///
+/// 提示所聚焦的一名说话者。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MortarFocusTarget {
+    pub speaker: String,
+    /// Entity of the speaker; `None` when the name is neither registered nor on a
+    /// [`crate::MortarSpeaker`].
+    ///
+    /// 说话者的实体；名称既未注册也不在任何 [`crate::MortarSpeaker`] 上时为 `None`。
+    pub entity: Option<Entity>,
+}
+
+/// How a line is spoken.
+///
+/// 行的说出方式。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MortarFocusKind {
+    /// Normal speech of the speaker.
+    ///
+    /// 说话者的普通台词。
+    Line,
+    /// The speaker's thought, from a `thought` tag on the text item.
+    ///
+    /// 说话者的内心独白，来自文本项上的 `thought` 标签。
+    Thought,
+    /// A line without a speaker.
+    ///
+    /// 没有说话者的行。
+    Narration,
+    /// Several speakers talking over each other in a `parallel_text` line, in voice order.
+    ///
+    /// `parallel_text` 行中同时说话的多名说话者，按声部顺序排列。
+    MultiFocus(Vec<MortarFocusTarget>),
+}
+
+/// Emitted when the focus of the primary dialogue changes to a new speaker or kind. For
+/// [`MortarFocusKind::MultiFocus`], `speaker` and `entity` are those of the first voice; for
+/// narration, `speaker` is empty.
+///
+/// 主对话的焦点变为新的说话者或方式时发出。对于 [`MortarFocusKind::MultiFocus`]，`speaker` 与
+/// `entity` 为第一个声部的值；对于旁白，`speaker` 为空。
+#[derive(Message, Debug, Clone, PartialEq, Eq)]
+pub struct MortarFocusHint {
+    pub speaker: String,
+    pub entity: Option<Entity>,
+    pub kind: MortarFocusKind,
+}
+
+/// The latest [`MortarFocusHint`], for camera controllers that poll; `None` without a dialogue.
+///
+/// 最新的 [`MortarFocusHint`]，供轮询式相机控制器使用；没有对话时为 `None`。
+#[derive(Resource, Debug, Clone, Default, PartialEq, Eq)]
+pub struct MortarSpeakerFocus {
+    pub current: Option<MortarFocusHint>,
+}
+
+/// The hint for the raw text item `item`, resolving names with `resolve`.
+fn focus_hint(item: &Value, resolve: impl Fn(&str) -> Option<Entity>) -> MortarFocusHint {
+    let name = |value: &Value, keys: &[&str]| {
+        keys.iter()
+            .find_map(|key| value.get(*key).and_then(Value::as_str))
+            .map(str::to_owned)
+    };
+    let target = |speaker: String| MortarFocusTarget {
+        entity: resolve(&speaker),
+        speaker,
+    };
+    let voices = item.get("texts").and_then(Value::as_array);
+    let (speakers, kind): (Vec<_>, _) = match voices {
+        Some(voices) if item.get("type").and_then(Value::as_str) == Some("parallel_text") => {
+            let targets: Vec<_> = voices
+                .iter()
+                .filter_map(|voice| name(voice, &["speaker", "target"]))
+                .map(target)
+                .collect();
+            (targets.clone(), MortarFocusKind::MultiFocus(targets))
+        }
+        _ => {
+            let thought = item
+                .get("tags")
+                .and_then(Value::as_array)
+                .is_some_and(|tags| tags.iter().any(|tag| tag.as_str() == Some(THOUGHT_TAG)));
+            let kind = if thought {
+                MortarFocusKind::Thought
+            } else {
+                MortarFocusKind::Line
+            };
+            (
+                name(item, &["speaker"]).map(target).into_iter().collect(),
+                kind,
+            )
+        }
+    };
+    match speakers.into_iter().next() {
+        Some(first) => MortarFocusHint {
+            speaker: first.speaker,
+            entity: first.entity,
+            kind,
+        },
+        None => MortarFocusHint {
+            speaker: String::new(),
+            entity: None,
+            kind: MortarFocusKind::Narration,
+        },
+    }
+}
+
+/// Classifies the lines shown this frame and announces those that move the focus.
+pub(super) fn update_speaker_focus(
+    runtime: Res<MortarRuntime>,
+    speakers: SpeakerEntities,
+    mut changes: MessageReader<MortarTextChanged>,
+    mut focus: ResMut<MortarSpeakerFocus>,
+    mut hints: MessageWriter<MortarFocusHint>,
+) {
+    let Some(state) = runtime.primary_dialogue_state() else {
+        changes.clear();
+        if focus.current.is_some() {
+            focus.current = None;
+        }
+        return;
+    };
+    let resolve = |name: &str| speakers.get(name);
+    for change in changes.read() {
+        if change.mortar_path != state.mortar_path || change.node != state.current_node {
+            continue;
+        }
+        let Some(item) = state.text_source(change.text_index) else {
+            continue;
+        };
+        let hint = focus_hint(item, resolve);
+        if focus.current.as_ref() != Some(&hint) {
+            hints.write(hint.clone());
+            focus.current = Some(hint);
+        }
+    }
+}
@@ -0,0 +1,206 @@
+//! # speech.rs
+//!
+//! # speech.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Speakable text for screen readers. Once a game inserts a [`MortarIconSpeechMap`] or a
+//! [`MortarSpeechFormat`], every displayed line gets [`MortarDialogueText::speakable`]: the body
+//! with inline icons spoken as words ("button A"), markup tags such as `<b>` or `<color=red>`
+//! removed, and then the game's formatter applied, e.g. to spell out numbers. A
+//! [`MortarSpeakableChanged`] message is written whenever a target's speakable text changes, and
+//! once per presented choice group with all its labels. Without either resource nothing runs.
+//!
+//! 供屏幕阅读器使用的可朗读文本。游戏插入 [`MortarIconSpeechMap`] 或 [`MortarSpeechFormat`] 后，
+//! 每一行显示的文本都会得到 [`MortarDialogueText::speakable`]：正文中的行内图标读作文字（如
+//! “button A”），`<b>`、`<color=red>` 等标记被移除，最后应用游戏的格式化器，例如把数字读出来。
+//! 目标的可朗读文本每次变化时都会写入 [`MortarSpeakableChanged`] 消息；每个呈现的选项组也会写入
+//! 一条包含全部选项标签的消息。两个资源都不存在时不会运行任何处理。
+
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+use super::{MortarChoiceViewKind, MortarChoicesPresented, MortarDialogueText};
+use crate::extract_inline_icons;
+
+/// Game-specific substitutions applied last to speakable text.
+///
+/// 最后应用于可朗读文本的游戏特定替换。
+pub trait MortarSpeechFormatter: Send + Sync + 'static {
+    /// Returns `text` as it should be spoken.
+    ///
+    /// 返回 `text` 应当被朗读的形式。
+    fn format(&self, text: &str) -> String;
+}
+
+impl<F: Fn(&str) -> String + Send + Sync + 'static> MortarSpeechFormatter for F {
+    fn format(&self, text: &str) -> String {
+        self(text)
+    }
+}
+
+/// The formatter used for speakable text.
+///
+/// 用于可朗读文本的格式化器。
+#[derive(Resource)]
+pub struct MortarSpeechFormat(Box<dyn MortarSpeechFormatter>);
+
+impl MortarSpeechFormat {
+    /// Wraps `formatter`, which may be a closure over the text.
+    ///
+    /// 包装 `formatter`，它也可以是接收文本的闭包。
+    pub fn new(formatter: impl MortarSpeechFormatter) -> Self {
+        Self(Box::new(formatter))
+    }
+}
+
+/// Words spoken for inline icons; an icon without an entry is spoken as its name with
+/// underscores read as spaces.
+///
+/// 行内图标对应的朗读文字；没有条目的图标会读出其名称，下划线按空格处理。
+#[derive(Resource, Debug, Clone, Default, PartialEq, Eq)]
+pub struct MortarIconSpeechMap {
+    pub words: HashMap<String, String>,
+}
+
+impl MortarIconSpeechMap {
+    /// Speaks icon `name` as `words`.
+    ///
+    /// 将图标 `name` 读作 `words`。
+    pub fn with(mut self, name: impl Into<String>, words: impl Into<String>) -> Self {
+        self.words.insert(name.into(), words.into());
+        self
+    }
+
+    fn speak(map: Option<&Self>, name: &str) -> String {
+        map.and_then(|map| map.words.get(name))
+            .cloned()
+            .unwrap_or_else(|| name.replace('_', " "))
+    }
+}
+
+/// Written when the speakable text of a target changes, or a choice group is presented. For
+/// choices, `entity` is the dialogue and `text` holds one label per line.
+///
+/// 目标的可朗读文本变化或呈现选项组时写入。对选项而言，`entity` 为对话实体，`text` 每行一个标签。
+#[derive(Message, Debug, Clone, PartialEq, Eq)]
+pub struct MortarSpeakableChanged {
+    pub entity: Entity,
+    pub text: String,
+}
+
+/// Whether the game asked for speakable text.
+pub(super) fn speech_enabled(
+    map: Option<Res<MortarIconSpeechMap>>,
+    format: Option<Res<MortarSpeechFormat>>,
+) -> bool {
+    map.is_some() || format.is_some()
+}
+
+/// Speakable form of `text`, whose icons are given separately.
+fn speakable(
+    text: &MortarDialogueText,
+    map: Option<&MortarIconSpeechMap>,
+    format: Option<&MortarSpeechFormat>,
+) -> String {
+    let mut icons = text.icons.iter().peekable();
+    let mut spoken = String::with_capacity(text.body.len());
+    for (index, ch) in text.body.chars().enumerate() {
+        match icons.next_if(|icon| icon.char_index == index) {
+            Some(icon) => spoken.push_str(&MortarIconSpeechMap::speak(map, &icon.name)),
+            None => spoken.push(ch),
+        }
+    }
+    let spoken = strip_markup(&spoken);
+    match format {
+        Some(format) => format.0.format(&spoken),
+        None => spoken,
+    }
+}
+
+/// Removes `<...>` tags, keeping the text between them. A `<` without a closing `>` stays.
+fn strip_markup(text: &str) -> String {
+    let mut plain = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(open) = rest.find('<') {
+        plain.push_str(&rest[..open]);
+        match rest[open..].find('>') {
+            Some(close) => rest = &rest[open + close + 1..],
+            None => {
+                rest = &rest[open..];
+                break;
+            }
+        }
+    }
+    plain.push_str(rest);
+    plain
+}
+
+/// Refreshes the speakable text of changed lines.
+pub(super) fn update_speakable_text(
+    map: Option<Res<MortarIconSpeechMap>>,
+    format: Option<Res<MortarSpeechFormat>>,
+    mut texts: Query<(Entity, &mut MortarDialogueText), Changed<MortarDialogueText>>,
+    mut changes: MessageWriter<MortarSpeakableChanged>,
+) {
+    for (entity, mut text) in &mut texts {
+        // A line cleared while runs execute is restored as it was, so it is not announced.
+        //
+        // 在 run 执行期间被清空的行之后会原样恢复，因此不播报。
+        if text.body.is_empty() {
+            continue;
+        }
+        let spoken = speakable(&text, map.as_deref(), format.as_deref());
+        if spoken != text.speakable {
+            // Consumers of `Changed<MortarDialogueText>` already saw this line.
+            //
+            // `Changed<MortarDialogueText>` 的使用者已经看到过这一行。
+            text.bypass_change_detection().speakable = spoken.clone();
+            changes.write(MortarSpeakableChanged {
+                entity,
+                text: spoken,
+            });
+        }
+    }
+}
+
+/// Announces the labels of a newly presented choice group.
+pub(super) fn announce_presented_choices(
+    map: Option<Res<MortarIconSpeechMap>>,
+    format: Option<Res<MortarSpeechFormat>>,
+    presented: Res<MortarChoicesPresented>,
+    mut changes: MessageWriter<MortarSpeakableChanged>,
+    mut previous: Local<String>,
+) {
+    if !presented.is_changed() {
+        return;
+    }
+    let labels: Vec<String> = presented
+        .views
+        .iter()
+        .filter(|view| view.kind == MortarChoiceViewKind::Choice)
+        .map(|view| {
+            if view.text.is_empty() {
+                let name = view.icon.as_deref().or(view.id.as_deref()).unwrap_or("");
+                return MortarIconSpeechMap::speak(map.as_deref(), name);
+            }
+            let (body, icons) = extract_inline_icons(&view.text, crate::DEFAULT_ICON_PLACEHOLDER);
+            let text = MortarDialogueText {
+                body,
+                icons,
+                ..default()
+            };
+            speakable(&text, map.as_deref(), format.as_deref())
+        })
+        .collect();
+    let text = labels.join("\n");
+    if text == *previous {
+        return;
+    }
+    previous.clone_from(&text);
+    if let Some(entity) = presented.dialogue.filter(|_| !text.is_empty()) {
+        changes.write(MortarSpeakableChanged { entity, text });
+    }
+}
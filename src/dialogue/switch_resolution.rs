@@ -0,0 +1,50 @@
+//! # switch_resolution.rs
+//!
+//! # switch_resolution.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Resolves the `switch` items the primary dialogue's cursor reaches, see
+//! [`crate::DialogueState::resolve_switches`]. It runs after choice captures are written and
+//! before any text renders, so a switch on a captured choice right after the group already sees
+//! the pick, and the first line shown is one of the picked case.
+//!
+//! 对主对话游标到达的 `switch` 项求值，参见 [`crate::DialogueState::resolve_switches`]。它在选项
+//! 捕获写入之后、任何文本显示之前运行，因此紧跟在选项组之后、以捕获的选项为键的 switch 已经能看到
+//! 所选内容，显示的第一行也来自被选中的分支。
+
+use bevy::asset::Assets;
+use bevy::prelude::*;
+
+use super::MortarDialogueVariables;
+use crate::{MortarAsset, MortarRegistry, MortarRuntime};
+
+pub(super) fn resolve_content_switches(
+    mut runtime: ResMut<MortarRuntime>,
+    registry: Res<MortarRegistry>,
+    assets: Res<Assets<MortarAsset>>,
+    mut variables: ResMut<MortarDialogueVariables>,
+) {
+    if !runtime.is_changed() {
+        return;
+    }
+    let Some(state) = runtime
+        .primary_dialogue_state()
+        .filter(|state| state.awaits_switch())
+    else {
+        return;
+    };
+    let Some(handle) = registry.get(&state.mortar_path) else {
+        return;
+    };
+    let Some(asset) = assets.get(handle) else {
+        return;
+    };
+    let warm = runtime.warm_variables(&state.mortar_path);
+    let variable_state = variables.ensure_for(handle.id(), &asset.data, warm);
+    if let Some(state) = runtime.primary_dialogue_state_mut() {
+        state.resolve_switches(variable_state);
+    }
+}
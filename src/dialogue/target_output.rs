@@ -0,0 +1,88 @@
+//! # target_output.rs
+//!
+//! # target_output.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Writes a rendered line onto one text target: its [`MortarDialogueText`], the event tracker and
+//! binding that fire the line's events, and the header change notification. The target's `Text`
+//! is written by the caller, since it only exists with the `ui` feature.
+//!
+//! 将渲染好的行写入单个文本目标：其 [`MortarDialogueText`]、触发该行事件的事件跟踪器与绑定，以及
+//! 头部变化通知。目标的 `Text` 仅在启用 `ui` 功能时存在，因此由调用方写入。
+
+use bevy::prelude::*;
+use mortar_compiler::Event;
+
+use super::mandatory_events::MandatoryMarks;
+use super::{
+    MortarDialogueText, MortarEventBinding, MortarHeaderChanged, MortarHeaderSettings,
+    MortarRevealPolicy, MortarRevealPolicySettings, header,
+};
+#[cfg(feature = "ui")]
+use super::{display_animation, run_execution};
+use crate::MortarEventTracker;
+
+/// What writing a line onto the targets touches besides the targets themselves.
+pub(super) struct TargetOutput<'a, 'w, 's> {
+    pub(super) commands: &'a mut Commands<'w, 's>,
+    pub(super) header_settings: &'a MortarHeaderSettings,
+    pub(super) header_changes: &'a mut MessageWriter<'w, MortarHeaderChanged>,
+    pub(super) policy_settings: &'a MortarRevealPolicySettings,
+    pub(super) mandatory: MandatoryMarks<'a>,
+}
+
+impl TargetOutput<'_, '_, '_> {
+    /// Shows `dialogue_text` on `entity`, tracking `events` when its policy allows.
+    pub(super) fn show(
+        &mut self,
+        entity: Entity,
+        current: Option<Mut<MortarDialogueText>>,
+        policy: Option<&MortarRevealPolicy>,
+        dialogue_text: &MortarDialogueText,
+        events: &[Event],
+    ) {
+        let mut target = self.commands.entity(entity);
+        target
+            .remove::<MortarEventTracker>()
+            .remove::<MortarEventBinding>();
+        #[cfg(feature = "ui")]
+        target
+            .remove::<run_execution::RunClearedText>()
+            .remove::<display_animation::MortarDisplayRoll>();
+        // Only one target per line should own the tracker, or its events fire twice.
+        //
+        // 每行只应有一个目标持有事件跟踪器，否则其事件会触发两次。
+        if !events.is_empty() && self.policy_settings.tracks_events(policy) {
+            target.insert((
+                self.mandatory.tracker(events),
+                MortarEventBinding::default(),
+            ));
+        }
+        let previous_header = current
+            .as_ref()
+            .map_or("", |current| current.header.as_str());
+        header::note_header_change(
+            self.header_changes,
+            self.header_settings,
+            entity,
+            previous_header,
+            &dialogue_text.header,
+        );
+        match current {
+            Some(mut current) => {
+                // The speakable text is derived later, from the new body.
+                //
+                // 可朗读文本稍后根据新正文得出。
+                let mut next = dialogue_text.clone();
+                next.speakable.clone_from(&current.speakable);
+                current.set_if_neq(next);
+            }
+            None => {
+                target.insert(dialogue_text.clone());
+            }
+        }
+    }
+}
@@ -15,7 +15,7 @@
 
 use std::collections::HashMap;
 
-use crate::{MortarVariableState, MortarVariableValue, TextData};
+use crate::{MortarAsset, MortarVariableState, TextData, TextPosition};
 
 fn build_interpolation_index_map(
     parts: &[mortar_compiler::StringPart],
@@ -54,6 +54,8 @@ fn build_interpolation_index_map(
             rendered_pos += branch_text.chars().count() as f64;
         } else if let Some(value) = variable_state.get(var_name) {
             rendered_pos += value.to_display_string().chars().count() as f64;
+        } else if let Some(value) = crate::runtime::capability_placeholder(var_name) {
+            rendered_pos += value.chars().count() as f64;
         }
     }
 
@@ -71,14 +73,21 @@ fn adjust_events_with_index_map(
         let mut adjusted_event = event.clone();
 
         if let Some(var_name) = &adjusted_event.index_variable
-            && let Some(MortarVariableValue::Number(n)) = variable_state.get(var_name)
+            && let Some(n) = variable_state.get_number(var_name)
         {
-            adjusted_event.index = *n;
+            adjusted_event.index = n;
         }
 
         if let Some(&rendered_index) = index_map.get(&(adjusted_event.index as usize)) {
             adjusted_event.index = rendered_index;
         }
+        verbose_trace!(
+            Events,
+            "Event {:?} declared at index {} mapped to {}",
+            event.actions,
+            event.index,
+            adjusted_event.index
+        );
 
         all_events.push(adjusted_event);
     }
@@ -87,16 +96,16 @@ fn adjust_events_with_index_map(
 pub fn collect_text_events(
     text_data: &TextData,
     variable_state: &MortarVariableState,
-    asset_data: Option<&mortar_compiler::MortaredData>,
-    current_text_content_idx: Option<usize>,
+    asset: Option<&MortarAsset>,
+    current_text_position: Option<TextPosition>,
     node_data: &mortar_compiler::Node,
 ) -> Vec<mortar_compiler::Event> {
     let mut all_events = Vec::new();
 
     if let Some(parts) = &text_data.interpolated_parts {
-        if let Some(asset_data) = asset_data {
+        if let Some(asset) = asset {
             let index_map =
-                build_interpolation_index_map(parts, variable_state, asset_data, &mut all_events);
+                build_interpolation_index_map(parts, variable_state, &asset.data, &mut all_events);
 
             if let Some(text_events) = &text_data.events {
                 adjust_events_with_index_map(
@@ -111,16 +120,23 @@ pub fn collect_text_events(
         all_events = text_events.clone();
         for event in &mut all_events {
             if let Some(var_name) = &event.index_variable
-                && let Some(MortarVariableValue::Number(n)) = variable_state.get(var_name)
+                && let Some(n) = variable_state.get_number(var_name)
             {
-                event.index = *n;
+                event.index = n;
             }
         }
     }
 
-    if let Some(asset_data) = asset_data
-        && let Some(content_idx) = current_text_content_idx
-        && let Some(prev_content) = content_idx
+    // A `run_event` with an index override attaches to the first text after it, not to the
+    // texts a `continuation` adds to that text's content item.
+    //
+    // 带索引覆盖的 `run_event` 只附加到其后的第一个文本，而不附加到 `continuation` 在该文本内容项
+    // 中追加的文本。
+    if let Some(asset) = asset
+        && let Some(position) = current_text_position
+        && position.sub_index == 0
+        && let Some(prev_content) = position
+            .content_index
             .checked_sub(1)
             .and_then(|idx| node_data.content.get(idx))
         && let Some("run_event") = prev_content.get("type").and_then(|v| v.as_str())
@@ -131,21 +147,13 @@ pub fn collect_text_events(
     {
         let index = if index_override.override_type == "variable" {
             variable_state
-                .get(&index_override.value)
-                .and_then(|v| match v {
-                    MortarVariableValue::Number(n) => Some(*n),
-                    _ => None,
-                })
+                .get_number(&index_override.value)
                 .unwrap_or(0.0)
         } else {
             index_override.value.parse::<f64>().unwrap_or(0.0)
         };
 
-        if let Some(event_def) = asset_data
-            .events
-            .iter()
-            .find(|event| event.name == event_name)
-        {
+        if let Some(event_def) = asset.event_def(event_name) {
             let text_event = mortar_compiler::Event {
                 index,
                 index_variable: None,
@@ -0,0 +1,534 @@
+//! # text_targets.rs
+//!
+//! # text_targets.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Renders the primary dialogue's current line onto every [`MortarTextTarget`]: it skips lines
+//! whose condition fails or whose switch case was not picked, interpolates the line, resolves its
+//! header, routes parallel voices and hands the result to
+//! [`target_output`](super::target_output). It also writes [`MortarTextChanged`] for renderers
+//! without targets. Only the targets' `Text` and the number rolls of display animations need the
+//! `ui` feature.
+//!
+//! 将主对话的当前行渲染到每个 [`MortarTextTarget`] 上：跳过条件不成立或所在 switch 分支未被选中的
+//! 行、插值、解析头部、路由并行声部，再把结果交给 [`target_output`](super::target_output)。它还会为
+//! 没有目标的渲染器写入 [`MortarTextChanged`]。只有目标的 `Text` 与显示动画的数字滚动需要 `ui`
+//! 功能。
+
+use bevy::asset::Assets;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+
+use super::event_targets::GameEventWriter;
+use super::line_explanation::{LineExplanation, describe_condition, note_skipped_line};
+use super::line_group::process_line_group;
+use super::mandatory_events::{self, MandatoryMarks};
+use super::text_events::collect_text_events;
+#[cfg(feature = "ui")]
+use super::{MortarAnimatedDisplay, MortarDisplayAnimations, MortarDisplayRoll};
+use super::{
+    MortarDialogueText, MortarDialogueVariables, MortarEventDiagnostics, MortarHeaderChanged,
+    MortarHeaderSettings, MortarIconSettings, MortarRevealPolicy, MortarRevealPolicySettings,
+    MortarRunsExecuting, MortarTextChanged, MortarTextChannel, MortarTextTarget, experiments,
+    header, icons, parallel, target_output,
+};
+use crate::binder::CallContextGuard;
+use crate::debug::LOG_DIALOGUE;
+use crate::eval::{FunctionDecls, interpolate_with};
+use crate::{
+    LineCursor, MortarAsset, MortarCapabilities, MortarCommand, MortarConditionCache,
+    MortarMandatoryEvents, MortarRegistry, MortarRuntime, MortarVariableState,
+    evaluate_if_condition_cached,
+};
+
+#[derive(SystemParam)]
+pub(super) struct TextUpdateParams<'w, 's> {
+    commands: Commands<'w, 's>,
+    runtime: ResMut<'w, MortarRuntime>,
+    registry: Res<'w, MortarRegistry>,
+    assets: Res<'w, Assets<MortarAsset>>,
+    texts: Query<'w, 's, TextTarget, With<MortarTextTarget>>,
+    variable_cache: ResMut<'w, MortarDialogueVariables>,
+    runs_executing: Res<'w, MortarRunsExecuting>,
+    events: MessageWriter<'w, MortarCommand>,
+    log_config: Res<'w, crate::MortarLogConfig>,
+    icon_settings: Res<'w, MortarIconSettings>,
+    policy_settings: Res<'w, MortarRevealPolicySettings>,
+    header_settings: Res<'w, MortarHeaderSettings>,
+    header_changes: MessageWriter<'w, MortarHeaderChanged>,
+    experiments: experiments::ExperimentParams<'w>,
+    capabilities: Res<'w, MortarCapabilities>,
+    trackers: mandatory_events::TrackerQuery<'w, 's>,
+    game_events: GameEventWriter<'w, 's>,
+    event_diagnostics: ResMut<'w, MortarEventDiagnostics>,
+    mandatory_events: Res<'w, MortarMandatoryEvents>,
+    #[cfg(feature = "ui")]
+    ui: UiTargets<'w, 's>,
+    text_changes: MessageWriter<'w, MortarTextChanged>,
+    condition_cache: Res<'w, MortarConditionCache>,
+}
+
+/// [`crate::MortarDialoguePlugin::write_text`].
+#[cfg(feature = "ui")]
+#[derive(Resource)]
+pub(super) struct WriteText(pub(super) bool);
+
+/// What only the `ui` feature writes: the targets' `Text` and the number rolls of
+/// [`MortarAnimatedDisplay`] targets.
+#[cfg(feature = "ui")]
+#[derive(SystemParam)]
+pub(super) struct UiTargets<'w, 's> {
+    write_text: Res<'w, WriteText>,
+    texts: Query<'w, 's, &'static mut Text, With<MortarTextTarget>>,
+    animated: Query<'w, 's, (), With<MortarAnimatedDisplay>>,
+    display_animations: ResMut<'w, MortarDisplayAnimations>,
+}
+
+#[cfg(feature = "ui")]
+impl UiTargets<'_, '_> {
+    fn show_waiting_text(&mut self) {
+        if !self.write_text.0 {
+            return;
+        }
+        for mut text in &mut self.texts {
+            if text.0 != WAITING_TEXT {
+                text.0 = WAITING_TEXT.to_owned();
+            }
+        }
+    }
+
+    /// Writes `dialogue_text` into the `Text` of `entity`, see
+    /// [`crate::MortarDialoguePlugin::write_text`].
+    fn write_text(&mut self, entity: Entity, dialogue_text: &MortarDialogueText) {
+        if !self.write_text.0 {
+            return;
+        }
+        let Ok(mut text) = self.texts.get_mut(entity) else {
+            return;
+        };
+        // Leave an identical line untouched so `Changed<Text>` consumers do not see a change when
+        // the runtime is only woken up.
+        //
+        // 内容相同的行保持不动，使 `Changed<Text>` 的使用者不会因运行时仅被唤醒而看到变化。
+        let full_text = dialogue_text.full_text();
+        if text.0 != full_text {
+            text.0 = full_text;
+        }
+    }
+}
+
+type TextTarget = (
+    Entity,
+    Option<&'static mut MortarDialogueText>,
+    Option<&'static MortarRevealPolicy>,
+    Option<&'static MortarTextChannel>,
+);
+
+/// Cursor of the last rendered line, with the function registry generation it saw while the
+/// conversation is still on its first node.
+type RenderedKey = (LineCursor, Option<u64>);
+
+/// Placeholder shown while no dialogue is active.
+#[cfg(feature = "ui")]
+const WAITING_TEXT: &str = "等待加载对话...";
+
+pub(super) fn update_mortar_text_targets(
+    mut asset_events: MessageReader<AssetEvent<MortarAsset>>,
+    params: TextUpdateParams,
+    mut last_key: Local<Option<RenderedKey>>,
+    mut skipped: Local<Vec<String>>,
+    mut last_sent: Local<Option<(LineCursor, String)>>,
+) {
+    let TextUpdateParams {
+        mut commands,
+        mut runtime,
+        registry,
+        assets,
+        mut texts,
+        mut variable_cache,
+        runs_executing,
+        mut events,
+        log_config,
+        icon_settings,
+        policy_settings,
+        header_settings,
+        mut header_changes,
+        mut experiments,
+        capabilities,
+        mut trackers,
+        mut game_events,
+        mut event_diagnostics,
+        mandatory_events,
+        #[cfg(feature = "ui")]
+        mut ui,
+        mut text_changes,
+        condition_cache,
+    } = params;
+
+    // Check if any Mortar asset has been modified (hot reloaded)
+    for event in asset_events.read() {
+        if let AssetEvent::Modified { id: _ } = event {
+            // If an asset changed, force a reload of variables
+            debug!(target: LOG_DIALOGUE, "Mortar asset modified, reloading variables...");
+            variable_cache.reset();
+            *last_key = None; // Also reset last_key to ensure text re-evaluation
+        }
+    }
+    // The visible line may depend on capabilities, so a change renders it again.
+    //
+    // 可见的行可能依赖能力，因此能力变化时会重新渲染。
+    if capabilities.is_changed() {
+        *last_key = None;
+    }
+
+    if runs_executing.executing {
+        return;
+    }
+
+    if !runtime.has_active_dialogues() {
+        variable_cache.reset();
+        #[cfg(feature = "ui")]
+        ui.show_waiting_text();
+        *last_key = None;
+        *last_sent = None;
+        skipped.clear();
+        return;
+    }
+
+    if !runtime.is_changed() {
+        return;
+    }
+
+    let Some(state) = runtime.primary_dialogue_state() else {
+        #[cfg(feature = "ui")]
+        ui.show_waiting_text();
+        *last_key = None;
+        return;
+    };
+
+    let asset = registry
+        .get(&state.mortar_path)
+        .and_then(|handle| Some((handle.id(), assets.get(handle)?)));
+
+    // Lines of the first node also keep the registry generation they were rendered with, so a
+    // function registered after the conversation started re-renders them; later lines never
+    // re-render for that.
+    //
+    // 第一个节点中的行还会记录渲染时的注册表版本，因此对话开始后才注册的函数会使其重新渲染；之后的
+    // 行不会因此重新渲染。
+    let generation = runtime.functions.generation();
+    if last_key.is_some_and(|(last, rendered)| {
+        last == state.cursor() && rendered.is_none_or(|g| g == generation)
+    }) {
+        return;
+    }
+    // A line forced by a direct drive call without announcement is left as it was: no targets
+    // change and nothing is reported.
+    //
+    // 由直接驱动调用强制显示且未要求通知的行保持原样：不更新任何目标，也不发出任何报告。
+    let quiet_line = runtime.direct_drive.quiet_line;
+    if quiet_line.is_some() && quiet_line != Some(state.cursor()) {
+        runtime.bypass_change_detection().direct_drive.quiet_line = None;
+    } else if quiet_line.is_some() {
+        *last_key = Some((state.cursor(), None));
+        return;
+    }
+    let Some(state) = runtime.primary_dialogue_state() else {
+        return;
+    };
+    // The line shown until now is being left; what it still owes fires before the new line is
+    // resolved, so a condition on a mandatory event's flag sees it.
+    //
+    // 正在离开此前显示的行；它尚未触发的事件会在解析新行之前触发，使依赖必需事件所设标志的条件能看到它。
+    if last_key.is_some_and(|(last, _)| last != state.cursor()) {
+        mandatory_events::flush_left_lines(
+            &mut trackers,
+            &runtime,
+            &mut game_events,
+            &mut event_diagnostics,
+        );
+    }
+    let Some(text_data) = state.current_text_data() else {
+        return;
+    };
+
+    let variable_state = if let Some((asset_id, asset)) = asset {
+        variable_cache.ensure_for(
+            asset_id,
+            &asset.data,
+            runtime.warm_variables(&state.mortar_path),
+        )
+    } else {
+        variable_cache
+            .state
+            .get_or_insert_with(MortarVariableState::new)
+    };
+
+    // A regular text goes through its `if` / `else` block; an `if` that fails moves the dialogue
+    // to its `else` run before anything renders.
+    //
+    // 常规 text 按其 `if` / `else` 块解析；不成立的 `if` 会在渲染前把对话移到其 `else` 段。
+    let resolved = (text_data.parallel.is_empty() && !text_data.is_line).then(|| {
+        let _context = CallContextGuard::enter(runtime.call_context());
+        state
+            .resolve_text_with(state.text_index, |condition| {
+                evaluate_if_condition_cached(
+                    condition,
+                    &runtime.functions,
+                    variable_state,
+                    &condition_cache,
+                )
+            })
+            .map(|(index, _)| index)
+    });
+    if let Some(Some(index)) = resolved
+        && index != state.text_index
+        && let Some(state) = runtime
+            .bypass_change_detection()
+            .primary_dialogue_state_mut()
+    {
+        state.enter_else_branch(index);
+    }
+    let Some(state) = runtime.primary_dialogue_state() else {
+        return;
+    };
+    let Some(text_data) = state.current_text_data() else {
+        return;
+    };
+    let cursor = state.cursor();
+    let first_node =
+        last_key.is_none_or(|(last, rendered)| rendered.is_some() && last.same_visit(&cursor));
+    let current_key = (cursor, first_node.then_some(generation));
+
+    let func_decls = asset
+        .map(|(_, asset)| FunctionDecls::Asset(asset))
+        .unwrap_or_default();
+    *last_key = Some(current_key);
+    let mut explanation = LineExplanation::new(state, &text_data.line_id);
+    let _context = CallContextGuard::enter(runtime.call_context());
+
+    let metadata = asset.map(|(_, asset)| &asset.metadata);
+    let resolve_header = |text: &crate::TextData, variables: &MortarVariableState| {
+        header::resolve_header(
+            &header_settings,
+            metadata,
+            state,
+            text,
+            &runtime.functions,
+            func_decls,
+            variables,
+        )
+    };
+
+    if state.is_text_hidden(state.text_index) {
+        let reason = "its switch case was not picked";
+        note_skipped_line(&log_config, state, reason, &mut skipped);
+        events.write(MortarCommand::skip_line());
+        return;
+    }
+
+    // Placeholders of a regular text with the bytes they rendered to, for display animations.
+    //
+    // 常规 text 中的占位符及其渲染结果所占的字节范围，供显示动画使用。
+    let mut placeholders = Vec::new();
+    // Line groups: collect all consecutive lines, evaluate conditions per-line,
+    // join passing lines with '\n'.
+    //
+    // Line 组：收集所有连续 line，逐行评估条件，用 '\n' 拼接通过的行。
+    let (processed_text, all_events, voices) = if !text_data.parallel.is_empty() {
+        let context = parallel::VoiceContext {
+            functions: &runtime.functions,
+            func_decls,
+            asset: asset.map(|(_, asset)| asset),
+            node_data: state.node_data(),
+            capabilities: runtime.capabilities(),
+            icon_settings: &icon_settings,
+        };
+        let voices = parallel::render_voices(
+            &text_data.parallel,
+            &context,
+            |tag| experiments.hidden_reason(tag),
+            variable_state,
+            resolve_header,
+        );
+        if voices.is_empty() {
+            note_skipped_line(&log_config, state, "no voice is shown", &mut skipped);
+            events.write(MortarCommand::skip_line());
+            return;
+        }
+        explanation.record_events(&parallel::voice_events(&voices));
+        (parallel::joined(&voices), Vec::new(), Some(voices))
+    } else if text_data.is_line {
+        let group = experiments
+            .shown_lines(state.current_line_group().unwrap_or(&[]))
+            .into_iter()
+            .filter(|line| {
+                runtime
+                    .capabilities()
+                    .hidden_reason(&line.requires)
+                    .is_none()
+            });
+        let Some(processed_text) =
+            process_line_group(group, &runtime.functions, func_decls, variable_state)
+        else {
+            note_skipped_line(
+                &log_config,
+                state,
+                "no line in the group passed",
+                &mut skipped,
+            );
+            events.write(MortarCommand::skip_line());
+            return;
+        };
+        (processed_text, Vec::new(), None)
+    } else {
+        // Regular text: handling (existing logic)
+        //
+        // 常规 text: 处理（现有逻辑）
+        let hidden = experiments.hidden_reason(text_data.experiment.as_ref());
+        if let Some(reason) =
+            hidden.or_else(|| runtime.capabilities().hidden_reason(&text_data.requires))
+        {
+            note_skipped_line(&log_config, state, &reason, &mut skipped);
+            events.write(MortarCommand::skip_line());
+            return;
+        }
+        if resolved == Some(None) {
+            let reason = match &text_data.condition {
+                Some(condition)
+                    if !evaluate_if_condition_cached(
+                        condition,
+                        &runtime.functions,
+                        variable_state,
+                        &condition_cache,
+                    ) =>
+                {
+                    format!(
+                        "condition {} -> false",
+                        describe_condition(condition, variable_state)
+                    )
+                }
+                _ => "an earlier branch already ran".to_owned(),
+            };
+            verbose_trace!(
+                Conditions,
+                "Line {} of node '{}' left out: {}",
+                state.text_index,
+                state.current_node,
+                reason
+            );
+            note_skipped_line(&log_config, state, &reason, &mut skipped);
+            events.write(MortarCommand::skip_line());
+            return;
+        }
+        if let Some(condition) = &text_data.condition {
+            let described = describe_condition(condition, variable_state);
+            verbose_trace!(
+                Conditions,
+                "Line {} of node '{}': condition {} -> true",
+                state.text_index,
+                state.current_node,
+                described
+            );
+            explanation.condition = Some((described, true));
+        }
+
+        variable_state.execute_statements(&text_data.pre_statements);
+
+        let processed_text = interpolate_with(
+            text_data,
+            &runtime.functions,
+            func_decls,
+            variable_state,
+            &mut |part, value, start| {
+                explanation.parts.push((part.to_owned(), value.to_owned()));
+                placeholders.push((part.to_owned(), start..start + value.len()));
+            },
+        );
+
+        if processed_text.is_empty() {
+            note_skipped_line(&log_config, state, "text is empty", &mut skipped);
+            events.write(MortarCommand::skip_line());
+            return;
+        }
+
+        let all_events = collect_text_events(
+            text_data,
+            variable_state,
+            asset.map(|(_, asset)| asset),
+            state.current_text_position(),
+            state.node_data(),
+        );
+        explanation.record_events(&all_events);
+        (processed_text, all_events, None)
+    };
+
+    let mut rendered = match voices {
+        Some(voices) => {
+            parallel::RenderedLine::parallel(voices, &text_data.line_id, &icon_settings)
+        }
+        None => parallel::RenderedLine::Single {
+            text: icons::dialogue_text(
+                resolve_header(text_data, variable_state),
+                &processed_text,
+                &text_data.line_id,
+                &all_events,
+                &icon_settings,
+            ),
+            events: all_events,
+        },
+    };
+    rendered.stamp(cursor);
+    // A line rendered again unchanged, as when the runtime is only woken up, is not announced.
+    //
+    // 内容未变而再次渲染的行（例如运行时仅被唤醒时）不会再次通知。
+    let (shown, shown_events) = rendered.for_target(None);
+    let sent = (cursor, shown.full_text());
+    if last_sent.as_ref() != Some(&sent) {
+        text_changes.write(MortarTextChanged {
+            dialogue: runtime.primary_dialogue,
+            mortar_path: state.mortar_path.clone(),
+            node: state.current_node.clone(),
+            text_index: state.text_index,
+            header: shown.header.clone(),
+            body: shown.body.clone(),
+            events: shown_events.to_vec(),
+        });
+        *last_sent = Some(sent);
+    }
+    let mandatory = MandatoryMarks::new(&mandatory_events, state);
+    experiments.announce(&runtime, state, rendered.voice_line_ids());
+    explanation.skipped = std::mem::take(&mut *skipped);
+    explanation.text.clone_from(&processed_text);
+    runtime.bypass_change_detection().line_explanation = Some(explanation);
+    #[cfg(feature = "ui")]
+    let roll = {
+        let spans = ui.display_animations.plan(variable_state, &placeholders);
+        match &rendered {
+            parallel::RenderedLine::Single { text, .. } => {
+                MortarDisplayRoll::new(cursor, &text.header, &processed_text, spans)
+            }
+            parallel::RenderedLine::Parallel { .. } => None,
+        }
+    };
+    let mut output = target_output::TargetOutput {
+        commands: &mut commands,
+        header_settings: &header_settings,
+        header_changes: &mut header_changes,
+        policy_settings: &policy_settings,
+        mandatory,
+    };
+    for (entity, current, policy, channel) in &mut texts {
+        let (dialogue_text, events) = rendered.for_target(channel);
+        #[cfg(feature = "ui")]
+        ui.write_text(entity, dialogue_text);
+        output.show(entity, current, policy, dialogue_text, events);
+        #[cfg(feature = "ui")]
+        if let Some(roll) = roll.as_ref().filter(|_| ui.animated.contains(entity)) {
+            output.commands.entity(entity).insert(roll.clone());
+        }
+    }
+}
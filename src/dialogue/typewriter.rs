@@ -0,0 +1,206 @@
+//! # typewriter.rs
+//!
+//! # typewriter.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Adapter for external typewriter components (feature `typewriter`), such as the one from
+//! `bevy_ecs_typewriter`. Implement [`MortarTypewriter`] for the component, add
+//! [`MortarTypewriterPlugin`] for it, and put a [`MortarTypewriterAdapter`] next to it on a
+//! [`MortarTextTarget`]. The library then feeds each new line body into the typewriter, composes
+//! the header and the typed characters into `Text`, mirrors the typed position into
+//! [`MortarEventBinding`], and fast-forwards the typewriter when `NextText` asks to finish the
+//! reveal.
+//!
+//! 外部打字机组件的适配器（`typewriter` 功能），例如 `bevy_ecs_typewriter` 提供的组件。为该组件实现
+//! [`MortarTypewriter`]，为其添加 [`MortarTypewriterPlugin`]，并在 [`MortarTextTarget`] 上同时放置
+//! [`MortarTypewriterAdapter`]。之后库会把每一行新正文送入打字机，把头部与已打出的字符组合写入
+//! `Text`，把打字位置同步到 [`MortarEventBinding`]，并在 `NextText` 请求补全显示时让打字机快进。
+
+use bevy::ecs::component::Mutable;
+use bevy::prelude::*;
+use std::marker::PhantomData;
+
+use crate::{MortarEventTracker, MortarRuntime, MortarTrackerMode};
+
+use super::reveal::{RevealSteps, compose, set_reveal_complete};
+use super::{
+    MortarDialogueSystemSet, MortarDialogueText, MortarEventBinding, MortarLineStatus,
+    MortarRevealPolicy, MortarTextTarget,
+};
+
+/// A typewriter component the adapter can drive.
+///
+/// 适配器可以驱动的打字机组件。
+pub trait MortarTypewriter: Component<Mutability = Mutable> {
+    /// Starts typing `text` from its first character, one character every `seconds_per_char`.
+    /// With `auto_play` off the typewriter should wait to be played by the game.
+    ///
+    /// 从第一个字符开始打出 `text`，每隔 `seconds_per_char` 秒一个字符。`auto_play` 关闭时，
+    /// 打字机应等待游戏自行开始播放。
+    fn start(&mut self, text: &str, seconds_per_char: f32, auto_play: bool);
+
+    /// Number of characters typed so far.
+    ///
+    /// 目前已打出的字符数。
+    fn typed_chars(&self) -> usize;
+
+    /// Shows the rest of the text at once.
+    ///
+    /// 立即显示剩余的全部文本。
+    fn fast_forward(&mut self);
+}
+
+/// Connects a [`MortarTypewriter`] on the same [`MortarTextTarget`] to the dialogue output.
+///
+/// 将同一 [`MortarTextTarget`] 上的 [`MortarTypewriter`] 与对话输出连接起来。
+#[derive(Component, Debug, Clone, PartialEq)]
+pub struct MortarTypewriterAdapter {
+    /// Seconds between typed characters.
+    ///
+    /// 打出相邻两个字符之间的秒数。
+    pub speed: f32,
+    /// Whether each new line starts typing on its own.
+    ///
+    /// 每一行新文本是否自动开始打字。
+    pub auto_play: bool,
+    line: Option<String>,
+    typed: usize,
+}
+
+impl MortarTypewriterAdapter {
+    pub fn new(speed: f32) -> Self {
+        Self {
+            speed,
+            auto_play: true,
+            line: None,
+            typed: 0,
+        }
+    }
+
+    /// Whether part of `body` is still untyped, counting a line that has not started yet.
+    pub(super) fn is_revealing(&self, body: &str) -> bool {
+        let typed = if self.line.as_deref() == Some(body) {
+            self.typed
+        } else {
+            0
+        };
+        typed < body.chars().count()
+    }
+}
+
+/// Adds the systems that drive the typewriter component `T` through [`MortarTypewriterAdapter`].
+///
+/// 添加通过 [`MortarTypewriterAdapter`] 驱动打字机组件 `T` 的系统。
+pub struct MortarTypewriterPlugin<T>(PhantomData<T>);
+
+impl<T> Default for MortarTypewriterPlugin<T> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T: MortarTypewriter> Plugin for MortarTypewriterPlugin<T> {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (feed_typewriters::<T>, present_typewriters::<T>)
+                .chain()
+                .in_set(MortarDialogueSystemSet::UpdateText)
+                .after(super::update_mortar_text_targets)
+                .before(super::reveal_policy::apply_reveal_policies),
+        );
+    }
+}
+
+type FeedQuery<'w, 's, T> = Query<
+    'w,
+    's,
+    (
+        &'static MortarDialogueText,
+        &'static mut T,
+        &'static mut MortarTypewriterAdapter,
+        Option<&'static MortarRevealPolicy>,
+    ),
+    With<MortarTextTarget>,
+>;
+
+/// Restarts the typewriter on each new line body and fast-forwards it on request.
+fn feed_typewriters<T: MortarTypewriter>(runtime: Res<MortarRuntime>, mut targets: FeedQuery<T>) {
+    for (dialogue_text, mut typewriter, mut adapter, policy) in &mut targets {
+        if !MortarRevealPolicy::is_gradual(policy) {
+            continue;
+        }
+        if adapter.line.as_deref() != Some(dialogue_text.body.as_str()) {
+            adapter.line = Some(dialogue_text.body.clone());
+            adapter.typed = 0;
+            typewriter.start(&dialogue_text.body, adapter.speed, adapter.auto_play);
+        }
+        if runtime.advance_gate.finish_reveal && adapter.is_revealing(&dialogue_text.body) {
+            typewriter.fast_forward();
+        }
+    }
+}
+
+type PresentQuery<'w, 's, T> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static MortarDialogueText,
+        &'static T,
+        &'static mut MortarTypewriterAdapter,
+        &'static mut Text,
+        Option<&'static mut MortarEventBinding>,
+        Option<(
+            &'static mut MortarEventTracker,
+            Option<&'static MortarTrackerMode>,
+        )>,
+        Option<&'static mut MortarLineStatus>,
+        Option<&'static MortarRevealPolicy>,
+    ),
+    With<MortarTextTarget>,
+>;
+
+/// Writes the typed part of the line into `Text`, the event binding and the line status,
+/// stepping through the characters typed since the last frame one at a time.
+fn present_typewriters<T: MortarTypewriter>(
+    mut commands: Commands,
+    mut steps: RevealSteps,
+    mut targets: PresentQuery<T>,
+) {
+    for (
+        entity,
+        dialogue_text,
+        typewriter,
+        mut adapter,
+        mut text,
+        binding,
+        tracker,
+        status,
+        policy,
+    ) in &mut targets
+    {
+        if !MortarRevealPolicy::is_gradual(policy) {
+            continue;
+        }
+        let len = dialogue_text.body.chars().count();
+        let typed = typewriter.typed_chars().min(len);
+        if adapter.typed != typed {
+            steps.step(entity, &dialogue_text.body, adapter.typed, typed, tracker);
+            adapter.typed = typed;
+        }
+        let composed = compose(dialogue_text, typed);
+        if text.0 != composed {
+            text.0 = composed;
+        }
+        if let Some(mut binding) = binding
+            && binding.current_index != typed as f32
+        {
+            binding.current_index = typed as f32;
+        }
+        set_reveal_complete(&mut commands, entity, status, typed >= len);
+    }
+}
@@ -0,0 +1,72 @@
+//! # variables.rs
+//!
+//! # variables.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Holds the variable state of the dialogue plugin, cached per loaded Mortar asset and seeded
+//! from saves or by the game before a conversation starts.
+//!
+//! 保存对话插件的变量状态：按已加载的 Mortar 资源缓存，并可在对话开始前由存档或游戏预设。
+
+use bevy::prelude::*;
+
+use crate::{MortarAsset, MortarVariableState};
+
+/// Resource that caches variable state for the currently loaded mortar file.
+/// The cache follows the asset rather than its path, so paths aliasing one asset share it.
+///
+/// 缓存当前 mortar 文件变量状态的资源。
+/// 缓存跟随资源而不是路径，因此指向同一资源的多个路径别名共享同一份状态。
+#[derive(Resource, Default)]
+pub struct MortarDialogueVariables {
+    pub state: Option<MortarVariableState>,
+    active_asset: Option<AssetId<MortarAsset>>,
+    /// Values restored from a save or seeded by the game, written over the state the next time
+    /// it is initialized.
+    pub(crate) restored: Vec<(String, crate::MortarVariableValue)>,
+}
+
+impl MortarDialogueVariables {
+    /// Seeds `name` for the next conversation: the value replaces the file's declared one when
+    /// its variables are initialized. Variables are dropped when no dialogue is active, so a game
+    /// that owns a value, such as the player's gold, seeds it each time a conversation starts.
+    ///
+    /// 为下一段对话预设 `name`：变量初始化时，该值会替换文件中声明的值。没有活动对话时变量会被
+    /// 丢弃，因此由游戏持有的值（例如玩家的金币）需要在每段对话开始时预设。
+    pub fn seed(&mut self, name: impl Into<String>, value: crate::MortarVariableValue) {
+        self.restored.push((name.into(), value));
+    }
+
+    pub(super) fn reset(&mut self) {
+        self.state = None;
+        self.active_asset = None;
+    }
+
+    pub(super) fn ensure_for(
+        &mut self,
+        asset_id: AssetId<MortarAsset>,
+        asset: &mortar_compiler::MortaredData,
+        warm: Option<&MortarVariableState>,
+    ) -> &mut MortarVariableState {
+        if self.active_asset != Some(asset_id) {
+            self.state = None;
+            self.active_asset = Some(asset_id);
+        }
+        let state = self.state.get_or_insert_with(|| {
+            warm.cloned().unwrap_or_else(|| {
+                MortarVariableState::from_variables(
+                    &asset.variables,
+                    &asset.constants,
+                    &asset.enums,
+                )
+            })
+        });
+        for (name, value) in self.restored.drain(..) {
+            state.set(&name, value);
+        }
+        state
+    }
+}
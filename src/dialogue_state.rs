@@ -6,20 +6,58 @@
 //!
 //! ## 模块概述
 //!
-//! Defines the in-memory dialogue state machine used by `bevy_mortar_bond`. It parses a
-//! Mortar node into text, choice, and run-oriented views, then stores the cursor, executed content
-//! markers, pending runs, and choice navigation data needed while a dialogue is active.
+//! Defines the in-memory dialogue state machine used by `bevy_mortar_bond`. It walks a
+//! Mortar node parsed into text, choice, and run-oriented views, shared as a [`ParsedNode`], and
+//! stores the cursor, executed content markers, pending runs, and choice navigation data needed
+//! while a dialogue is active. `switch` items are inlined case by case, and the cases a switch
+//! did not pick are hidden.
 //!
-//! 定义了 `bevy_mortar_bond` 使用的内存对话状态机。它会把 Mortar 节点拆成面向文本、
-//! 选项和 run 的视图，并保存对话进行中所需的游标、已执行内容标记、待执行 run 以及选项导航数据。
+//! 定义了 `bevy_mortar_bond` 使用的内存对话状态机。它遍历被拆成面向文本、选项和 run 的视图的
+//! Mortar 节点（以 [`ParsedNode`] 共享），并保存对话进行中所需的游标、已执行内容标记、待执行 run
+//! 以及选项导航数据。`switch` 项会按分支逐一内联，switch 未选中的分支会被隐藏。
 
 use bevy::prelude::*;
 use mortar_compiler::{Choice, Node};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::debug::LOG_DIALOGUE;
+use switch::{ContentGate, SwitchPick};
+
+mod capture;
+mod choice_mutation;
+mod choice_options;
+mod conditional;
+mod content_diagnostics;
+mod cursor;
+mod line_id;
+mod node_content;
+mod pagination;
+mod parsed_node;
+mod switch;
+mod ui_hints;
+
+pub use capture::{CaptureValue, ChoiceCapture};
+pub(crate) use choice_mutation::{RemovalScope, RemovedChoice, visible_if};
+pub(crate) use choice_options::option_details;
+pub use content_diagnostics::MortarDiagnostic;
+pub use cursor::LineCursor;
+#[cfg(feature = "tools")]
+pub(crate) use line_id::choice_line_id;
+pub use node_content::TextPosition;
+pub use pagination::ChoicePagination;
+pub(crate) use pagination::{page_count, page_usable, visible_page};
+pub use parsed_node::ParsedNode;
+#[cfg(test)]
+pub(crate) use parsed_node::parses;
+pub use ui_hints::MortarUiHints;
+pub(crate) use ui_hints::ui_hints;
 
 /// Text data extracted from content item
 ///
 /// 从内容项提取的文本数据
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct TextData {
     pub value: String,
     pub interpolated_parts: Option<Vec<mortar_compiler::StringPart>>,
@@ -28,6 +66,33 @@ pub struct TextData {
     pub events: Option<Vec<mortar_compiler::Event>>,
     /// When true, consecutive lines are joined with `\n` into a single display unit.
     pub is_line: bool,
+    /// Stable identifier that survives edits elsewhere in the file; see `line_id.rs` for the
+    /// algorithm. Left empty on hand-built items.
+    ///
+    /// 在文件其他位置编辑后仍保持不变的稳定标识符，算法见 `line_id.rs`。手动构建的文本项为空。
+    pub line_id: String,
+    /// Header override from the item's `header` key; empty for none.
+    ///
+    /// 来自文本项 `header` 键的头部覆盖；为空表示不显示头部。
+    pub header: Option<String>,
+    /// Experiment variant from the item's `experiment` key; see [`crate::MortarExperiments`].
+    ///
+    /// 来自文本项 `experiment` 键的实验变体；参见 [`crate::MortarExperiments`]。
+    pub experiment: Option<crate::MortarExperimentTag>,
+    /// Capabilities from the item's `requires` key; see [`crate::MortarCapabilities`].
+    ///
+    /// 来自文本项 `requires` 键的能力；参见 [`crate::MortarCapabilities`]。
+    pub requires: Vec<String>,
+    /// Channel a voice of a `parallel_text` item is routed to, from its `target` or `speaker`
+    /// key; see [`crate::MortarTextChannel`].
+    ///
+    /// `parallel_text` 项中某个声部被路由到的通道，来自其 `target` 或 `speaker` 键；参见
+    /// [`crate::MortarTextChannel`]。
+    pub channel: Option<String>,
+    /// Voices of a `parallel_text` item, shown together as one line; empty for other items.
+    ///
+    /// `parallel_text` 项的各个声部，作为同一行一起显示；其他文本项为空。
+    pub parallel: Vec<TextData>,
 }
 
 /// The state of a dialogue.
@@ -37,17 +102,91 @@ pub struct TextData {
 pub struct DialogueState {
     pub mortar_path: String,
     pub current_node: String,
+    /// Index of the current text. It repeats across nodes and visits; compare
+    /// [`Self::cursor`] to tell whether the line changed.
+    ///
+    /// 当前文本的索引。它会在不同节点与访问之间重复；判断行是否变化时请比较 [`Self::cursor`]。
     pub text_index: usize,
     pub selected_choice: Option<usize>,
     pub choice_stack: Vec<usize>,
     pub choices_broken: bool,
-    pub executed_content_indices: Vec<usize>,
+    /// Content indices whose `run` statements already executed.
+    ///
+    /// 其 `run` 语句已执行过的内容索引。
+    pub executed_content_indices: HashSet<usize>,
     pub pending_run_position: Option<usize>,
-    node_data: Node,
-    text_items: Vec<TextData>,
-    text_to_content_index: Vec<usize>,
-    choice_content_index: Option<usize>,
-    choices: Option<Vec<Choice>>,
+    /// Indices of the currently presented choices whose conditions are false.
+    ///
+    /// 当前呈现的选项中条件不成立的选项索引。
+    pub disabled_choices: Vec<usize>,
+    /// Requested page of the presented choices when they are paginated.
+    ///
+    /// 选项分页时当前请求的页码。
+    pub choice_page: usize,
+    /// Text index the node was entered at (0 unless entered mid-way).
+    ///
+    /// 进入节点时的文本索引（除非从中途进入，否则为 0）。
+    pub entry_index: usize,
+    entry_runs: Vec<DialogueRunItem>,
+    parsed: Arc<ParsedNode>,
+    tags: Vec<String>,
+    overrides: HashMap<String, String>,
+    group_token: u64,
+    visit: u64,
+    removed_choices: HashSet<RemovedChoice>,
+    switch_picks: Vec<SwitchPick>,
+    /// First text of the `else` run the dialogue jumped into, see `conditional.rs`.
+    else_branch: Option<usize>,
+}
+
+/// Source of choice group tokens, shared by every dialogue so a token is never reused.
+static NEXT_GROUP_TOKEN: AtomicU64 = AtomicU64::new(1);
+
+fn next_group_token() -> u64 {
+    NEXT_GROUP_TOKEN.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Where a node starts when it is entered part-way through.
+///
+/// 从节点中途进入时的起始位置。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MortarNodeEntry {
+    /// Text index of the first rendered line; clamped to the node's last line.
+    ///
+    /// 第一行显示文本的索引；超出范围时截断到节点最后一行。
+    pub index: usize,
+    /// Whether `run` statements before the entry line still execute. Skipped runs are
+    /// otherwise marked as executed.
+    ///
+    /// 入口行之前的 `run` 语句是否仍然执行；否则这些被跳过的 run 会被标记为已执行。
+    pub execute_skipped_runs: bool,
+}
+
+impl MortarNodeEntry {
+    pub fn at(index: usize) -> Self {
+        Self {
+            index,
+            execute_skipped_runs: false,
+        }
+    }
+
+    pub fn with_skipped_runs(mut self, execute: bool) -> Self {
+        self.execute_skipped_runs = execute;
+        self
+    }
+
+    /// Reads `entry_index` / `execute_skipped_runs` from a raw choice option.
+    fn from_choice_option(option: &serde_json::Value) -> Option<Self> {
+        let index = option.get("entry_index")?.as_u64()?;
+        let execute_skipped_runs = option
+            .get("execute_skipped_runs")
+            .and_then(|value| value.as_bool())
+            .unwrap_or(false);
+        Some(Self {
+            index: usize::try_from(index).unwrap_or(usize::MAX),
+            execute_skipped_runs,
+        })
+    }
 }
 
 /// Type of run content embedded in a node.
@@ -75,113 +214,50 @@ pub type DialogueRunDescriptor = (
     bool,
 );
 
-fn parse_node_content(
-    content_idx: usize,
-    content_value: &serde_json::Value,
-    text_items: &mut Vec<TextData>,
-    text_to_content_index: &mut Vec<usize>,
-    choice_content_index: &mut Option<usize>,
-    choices: &mut Option<Vec<Choice>>,
-) {
-    let Some(type_str) = content_value.get("type").and_then(|value| value.as_str()) else {
-        return;
+fn run_item_at(content_index: usize, content_value: &serde_json::Value) -> Option<DialogueRunItem> {
+    let type_str = content_value.get("type")?.as_str()?;
+    let kind = match type_str {
+        "run_event" if content_value.get("index_override").is_none() => DialogueRunKind::Event,
+        "run_timeline" => DialogueRunKind::Timeline,
+        _ => return None,
     };
-    match type_str {
-        "text" | "line" => {
-            let is_line = type_str == "line";
-            let value = content_value
-                .get("value")
-                .and_then(|value| value.as_str())
-                .unwrap_or("")
-                .to_string();
-            let interpolated_parts = content_value
-                .get("interpolated_parts")
-                .and_then(|value| serde_json::from_value(value.clone()).ok());
-            let condition = content_value
-                .get("condition")
-                .and_then(|value| serde_json::from_value(value.clone()).ok());
-            let pre_statements = content_value
-                .get("pre_statements")
-                .and_then(|value| serde_json::from_value(value.clone()).ok())
-                .unwrap_or_default();
-            let events = content_value
-                .get("events")
-                .and_then(|value| serde_json::from_value(value.clone()).ok());
-
-            text_items.push(TextData {
-                value,
-                interpolated_parts,
-                condition,
-                pre_statements,
-                events,
-                is_line,
-            });
-            text_to_content_index.push(content_idx);
-        }
-        "choice" => {
-            let Some(options_value) = content_value.get("options") else {
-                return;
-            };
-            let Ok(parsed_choices) = serde_json::from_value::<Vec<Choice>>(options_value.clone())
-                .inspect_err(|err| {
-                    warn!(
-                        "Failed to parse choice options at content index {}: {}",
-                        content_idx, err
-                    );
-                })
-            else {
-                return;
-            };
-            *choices = Some(parsed_choices);
-            *choice_content_index = Some(content_idx);
-        }
-        _ => {}
-    }
+    let name = content_value.get("name")?.as_str()?;
+    let ignore_duration = kind == DialogueRunKind::Event
+        && content_value
+            .get("ignore_duration")
+            .and_then(|value| value.as_bool())
+            .unwrap_or(false);
+    Some(DialogueRunItem {
+        content_index,
+        name: name.to_string(),
+        kind,
+        ignore_duration,
+    })
 }
 
 fn collect_consecutive_runs(
     content: &[serde_json::Value],
     start_index: usize,
-    executed: &[usize],
+    executed: &HashSet<usize>,
+    gate: impl Fn(usize) -> ContentGate,
 ) -> Vec<DialogueRunItem> {
     let mut runs = Vec::new();
     for (idx, content_value) in content.iter().enumerate().skip(start_index) {
         if executed.contains(&idx) {
             continue;
         }
+        match gate(idx) {
+            ContentGate::Shown => {}
+            ContentGate::Hidden => continue,
+            ContentGate::Pending => break,
+        }
         let Some(type_str) = content_value.get("type").and_then(|value| value.as_str()) else {
             break;
         };
-        match type_str {
-            "run_event" if content_value.get("index_override").is_some() => continue,
-            "run_event" => {
-                let Some(name) = content_value.get("name").and_then(|value| value.as_str()) else {
-                    continue;
-                };
-                let ignore_duration = content_value
-                    .get("ignore_duration")
-                    .and_then(|value| value.as_bool())
-                    .unwrap_or(false);
-                runs.push(DialogueRunItem {
-                    content_index: idx,
-                    name: name.to_string(),
-                    kind: DialogueRunKind::Event,
-                    ignore_duration,
-                });
-            }
-            "run_timeline" => {
-                let Some(name) = content_value.get("name").and_then(|value| value.as_str()) else {
-                    continue;
-                };
-                runs.push(DialogueRunItem {
-                    content_index: idx,
-                    name: name.to_string(),
-                    kind: DialogueRunKind::Timeline,
-                    ignore_duration: false,
-                });
-            }
-            _ => break,
+        if !matches!(type_str, "run_event" | "run_timeline") {
+            break;
         }
+        runs.extend(run_item_at(idx, content_value));
     }
     runs
 }
@@ -189,64 +265,59 @@ fn collect_consecutive_runs(
 fn collect_runs_at_position(
     content: &[serde_json::Value],
     content_position: usize,
-    executed: &[usize],
+    executed: &HashSet<usize>,
 ) -> Vec<DialogueRunDescriptor> {
-    let mut runs = Vec::new();
-    for (idx, content_value) in content.iter().enumerate() {
-        if idx != content_position || executed.contains(&idx) {
-            continue;
-        }
-        let Some(type_str) = content_value.get("type").and_then(|value| value.as_str()) else {
-            continue;
-        };
-        match type_str {
-            "run_event" => {
-                let Some(name) = content_value.get("name").and_then(|value| value.as_str()) else {
-                    continue;
-                };
-                let args = content_value
-                    .get("args")
-                    .and_then(|value| serde_json::from_value(value.clone()).ok())
-                    .unwrap_or_default();
-                let index_override = content_value
-                    .get("index_override")
-                    .and_then(|value| serde_json::from_value(value.clone()).ok());
-                let ignore_duration = content_value
-                    .get("ignore_duration")
-                    .and_then(|value| value.as_bool())
-                    .unwrap_or(false);
-                runs.push((idx, name.to_string(), args, index_override, ignore_duration));
-            }
-            "run_timeline" => {
-                let Some(name) = content_value.get("name").and_then(|value| value.as_str()) else {
-                    continue;
-                };
-                runs.push((idx, name.to_string(), vec![], None, false));
-            }
-            _ => {}
+    if executed.contains(&content_position) {
+        return Vec::new();
+    }
+    let Some(content_value) = content.get(content_position) else {
+        return Vec::new();
+    };
+    let Some(name) = content_value.get("name").and_then(|value| value.as_str()) else {
+        return Vec::new();
+    };
+    match content_value.get("type").and_then(|value| value.as_str()) {
+        Some("run_event") => {
+            let args = content_value
+                .get("args")
+                .and_then(|value| serde_json::from_value(value.clone()).ok())
+                .unwrap_or_default();
+            let index_override = content_value
+                .get("index_override")
+                .and_then(|value| serde_json::from_value(value.clone()).ok());
+            let ignore_duration = content_value
+                .get("ignore_duration")
+                .and_then(|value| value.as_bool())
+                .unwrap_or(false);
+            vec![(
+                content_position,
+                name.to_string(),
+                args,
+                index_override,
+                ignore_duration,
+            )]
         }
+        Some("run_timeline") => vec![(content_position, name.to_string(), vec![], None, false)],
+        _ => Vec::new(),
     }
-    runs
 }
 
 impl DialogueState {
+    /// Reads `node_data` into a new dialogue state. Nodes of a loaded file are read once and
+    /// shared through [`Self::from_parsed`] instead. Malformed item fields are read as absent and
+    /// reported by [`Self::diagnostics`].
+    ///
+    /// 将 `node_data` 读取为新的对话状态。已加载文件的节点只会读取一次，并通过
+    /// [`Self::from_parsed`] 共享。格式错误的项字段按缺失处理，并由 [`Self::diagnostics`] 报告。
     pub fn new(mortar_path: String, node_name: String, node_data: Node) -> Self {
-        let mut text_items = Vec::new();
-        let mut text_to_content_index = Vec::new();
-        let mut choice_content_index = None;
-        let mut choices = None;
-
-        for (content_idx, content_value) in node_data.content.iter().enumerate() {
-            parse_node_content(
-                content_idx,
-                content_value,
-                &mut text_items,
-                &mut text_to_content_index,
-                &mut choice_content_index,
-                &mut choices,
-            );
-        }
+        let parsed = ParsedNode::parse(&node_name, node_data);
+        Self::from_parsed(mortar_path, node_name, Arc::new(parsed))
+    }
 
+    /// A new dialogue state on a node read before, see [`crate::MortarAsset::parsed_node`].
+    ///
+    /// 基于已读取节点的新对话状态，参见 [`crate::MortarAsset::parsed_node`]。
+    pub fn from_parsed(mortar_path: String, node_name: String, parsed: Arc<ParsedNode>) -> Self {
         Self {
             mortar_path,
             current_node: node_name,
@@ -254,18 +325,133 @@ impl DialogueState {
             selected_choice: None,
             choice_stack: Vec::new(),
             choices_broken: false,
-            executed_content_indices: Vec::new(),
+            executed_content_indices: HashSet::new(),
             pending_run_position: None,
-            node_data,
-            text_items,
-            text_to_content_index,
-            choice_content_index,
-            choices,
+            disabled_choices: Vec::new(),
+            choice_page: 0,
+            entry_index: 0,
+            entry_runs: Vec::new(),
+            switch_picks: vec![SwitchPick::Pending; parsed.switches.len()],
+            parsed,
+            tags: Vec::new(),
+            overrides: HashMap::new(),
+            group_token: next_group_token(),
+            visit: cursor::next_visit(),
+            removed_choices: HashSet::new(),
+            else_branch: None,
         }
     }
 
+    /// Attaches the node's tags, see [`crate::MortarMetadata::node_tags`].
+    ///
+    /// 附加节点的标签，参见 [`crate::MortarMetadata::node_tags`]。
+    pub fn with_tags(mut self, tags: Vec<String>) -> Self {
+        self.tags = tags;
+        self
+    }
+
+    /// Attaches the node's function overrides, see [`crate::MortarMetadata::node_overrides`].
+    ///
+    /// 附加节点的函数覆盖，参见 [`crate::MortarMetadata::node_overrides`]。
+    pub fn with_overrides(mut self, overrides: HashMap<String, String>) -> Self {
+        self.overrides = overrides;
+        self
+    }
+
+    /// Function overrides of the current node; empty when it has none.
+    ///
+    /// 当前节点的函数覆盖；没有覆盖时为空。
+    pub fn overrides(&self) -> &HashMap<String, String> {
+        &self.overrides
+    }
+
+    /// Tags of the current node; empty when it has none.
+    ///
+    /// 当前节点的标签；没有标签时为空。
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    /// Whether the current node is tagged `tag`.
+    ///
+    /// 当前节点是否带有 `tag` 标签。
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|own| own == tag)
+    }
+
+    /// Positions the cursor at `entry.index` and settles the runs skipped on the way,
+    /// returning the index actually used.
+    ///
+    /// 将游标移动到 `entry.index` 并处理途中跳过的 run，返回实际使用的索引。
+    pub fn enter_at(&mut self, entry: MortarNodeEntry) -> usize {
+        let index = entry
+            .index
+            .min(self.parsed.text_items.len().saturating_sub(1));
+        if index != entry.index {
+            warn!(
+                target: LOG_DIALOGUE,
+                "Entry index {} is out of range for node '{}' ({} lines), clamping to {}",
+                entry.index,
+                self.current_node,
+                self.parsed.text_items.len(),
+                index
+            );
+        }
+        self.text_index = index;
+        self.entry_index = index;
+
+        let entry_position = self
+            .parsed
+            .text_positions
+            .get(index)
+            .copied()
+            .unwrap_or_default();
+        let skipped: Vec<DialogueRunItem> = self
+            .parsed
+            .node_data
+            .content
+            .iter()
+            .enumerate()
+            .take_while(|(idx, _)| TextPosition::item(*idx) < entry_position)
+            .filter(|(idx, _)| !self.executed_content_indices.contains(idx))
+            .filter(|(idx, _)| self.content_gate(*idx) == ContentGate::Shown)
+            .filter_map(|(idx, value)| run_item_at(idx, value))
+            .collect();
+        if entry.execute_skipped_runs {
+            self.entry_runs = skipped;
+        } else {
+            for item in skipped {
+                self.mark_content_executed(item.content_index);
+            }
+        }
+        index
+    }
+
+    /// Takes the skipped runs that must execute before the entry line renders.
+    ///
+    /// 取出必须在入口行显示之前执行的被跳过的 run。
+    pub fn take_entry_runs(&mut self) -> Vec<DialogueRunItem> {
+        std::mem::take(&mut self.entry_runs)
+    }
+
+    /// Entry position requested by the option at `index` of the current choice level.
+    ///
+    /// 当前选项层级中 `index` 处选项所请求的入口位置。
+    pub fn choice_entry(&self, index: usize) -> Option<MortarNodeEntry> {
+        let content = self
+            .parsed
+            .node_data
+            .content
+            .get(self.parsed.choice_content_index?)?;
+        let mut options = content.get("options")?;
+        for &level in &self.choice_stack {
+            options = options.get(level)?.get("choice")?;
+        }
+        MortarNodeEntry::from_choice_option(options.get(index)?)
+    }
+
     pub fn get_current_choices(&self) -> Option<&Vec<Choice>> {
-        let mut choices = self.choices.as_ref()?;
+        let mut choices = self.parsed.choices.as_ref()?;
         for &index in &self.choice_stack {
             let choice = choices.get(index)?;
             let nested = choice.choice.as_ref()?;
@@ -274,19 +460,51 @@ impl DialogueState {
         Some(choices)
     }
 
+    /// Token of the current choice group. Every node entry and every push, pop or clear of the
+    /// choice stack gets a new one, so selections can name the group they were made for.
+    ///
+    /// 当前选项组的令牌。每次进入节点以及每次压入、弹出或清空选项栈都会得到新令牌，
+    /// 因此选择可以标明其针对的选项组。
+    pub fn group_token(&self) -> u64 {
+        self.group_token
+    }
+
     pub fn push_choice(&mut self, index: usize) {
         self.choice_stack.push(index);
-        self.selected_choice = None;
+        self.reset_choice_group();
     }
 
     pub fn pop_choice(&mut self) -> Option<usize> {
-        self.selected_choice = None;
+        self.reset_choice_group();
         self.choice_stack.pop()
     }
 
     pub fn clear_choice_stack(&mut self) {
         self.choice_stack.clear();
+        self.reset_choice_group();
+    }
+
+    /// Dismisses the choice group as a `break` does: the choice stack and the selection are
+    /// cleared, and input aimed at the group is dropped from then on.
+    ///
+    /// 像 `break` 一样关闭选项组：清空选项栈与当前选择，此后针对该组的输入都会被丢弃。
+    pub fn break_choices(&mut self) {
+        self.clear_choice_stack();
+        self.choices_broken = true;
+    }
+
+    /// Whether the current choice group has been reached and not dismissed, so it can take input.
+    ///
+    /// 当前选项组是否已到达且未被关闭，从而可以接受输入。
+    pub fn choices_presentable(&self) -> bool {
+        self.get_choices().is_some() && !self.has_next_text_before_choice()
+    }
+
+    fn reset_choice_group(&mut self) {
         self.selected_choice = None;
+        self.disabled_choices.clear();
+        self.choice_page = 0;
+        self.group_token = next_group_token();
     }
 
     pub fn get_choices(&self) -> Option<&Vec<Choice>> {
@@ -295,73 +513,72 @@ impl DialogueState {
         }
 
         if self.choice_stack.is_empty() {
-            self.choices.as_ref()
+            self.parsed.choices.as_ref()
         } else {
             self.get_current_choices()
         }
     }
 
     pub fn current_text(&self) -> Option<&str> {
-        self.text_items
+        self.parsed
+            .text_items
             .get(self.text_index)
             .map(|text| text.value.as_str())
     }
 
     pub fn current_text_data(&self) -> Option<&TextData> {
-        self.text_items.get(self.text_index)
+        self.parsed.text_items.get(self.text_index)
     }
 
+    /// The current text resolved through its `if` / `else` block, see
+    /// [`Self::resolve_text_at`].
+    ///
+    /// 按 `if` / `else` 块解析后的当前文本，参见 [`Self::resolve_text_at`]。
     pub fn current_text_data_evaluated(
         &self,
         variable_state: &crate::MortarVariableState,
         functions: &crate::MortarFunctionRegistry,
     ) -> Option<&TextData> {
-        let text_data = self.text_items.get(self.text_index)?;
-
-        if text_data.condition.is_none() {
-            return Some(text_data);
-        }
-
-        if let Some(condition) = &text_data.condition {
-            return if crate::evaluate_if_condition(condition, functions, variable_state) {
-                Some(text_data)
-            } else {
-                None
-            };
-        }
-
-        Some(text_data)
+        self.resolve_text_at(self.text_index, functions, variable_state)
+            .map(|(_, text)| text)
     }
 
     fn line_group_end(&self) -> usize {
-        let Some(current) = self.text_items.get(self.text_index) else {
+        let Some(current) = self.parsed.text_items.get(self.text_index) else {
             return self.text_index + 1;
         };
         if !current.is_line {
             return self.text_index + 1;
         }
+        // A group ends where its switch case does.
+        //
+        // line 组在其所在的 switch 分支结束处结束。
+        let case =
+            |index: usize| self.switch_case_of(self.parsed.text_positions[index].content_index);
         let mut end = self.text_index + 1;
-        while end < self.text_items.len() && self.text_items[end].is_line {
+        while end < self.parsed.text_items.len()
+            && self.parsed.text_items[end].is_line
+            && case(end) == case(self.text_index)
+        {
             end += 1;
         }
         end
     }
 
     pub fn current_line_group(&self) -> Option<&[TextData]> {
-        self.text_items.get(self.text_index)?;
-        Some(&self.text_items[self.text_index..self.line_group_end()])
+        self.parsed.text_items.get(self.text_index)?;
+        Some(&self.parsed.text_items[self.text_index..self.line_group_end()])
     }
 
     pub fn has_next_text(&self) -> bool {
-        self.line_group_end() < self.text_items.len()
+        self.next_shown(self.line_group_end()) < self.parsed.text_items.len()
     }
 
     pub fn has_next_text_before_choice(&self) -> bool {
-        if let Some(choice_content_idx) = self.choice_content_index {
-            let next_idx = self.line_group_end();
-            if next_idx < self.text_items.len() {
-                let next_text_content_idx = self.text_to_content_index[next_idx];
-                next_text_content_idx < choice_content_idx
+        if let Some(choice_content_idx) = self.parsed.choice_content_index {
+            let next_idx = self.next_shown(self.line_group_end());
+            if next_idx < self.parsed.text_items.len() {
+                self.parsed.text_positions[next_idx] < TextPosition::item(choice_content_idx)
             } else {
                 false
             }
@@ -371,33 +588,41 @@ impl DialogueState {
     }
 
     pub fn next_text(&mut self) -> bool {
-        let end = self.line_group_end();
-        if end < self.text_items.len() {
-            self.text_index = end;
-            true
-        } else {
-            false
-        }
+        let Some(next) = self.next_text_index() else {
+            return false;
+        };
+        self.text_index = next;
+        true
+    }
+
+    /// Index of the line [`Self::next_text`] moves to, `None` when it would not move.
+    ///
+    /// [`Self::next_text`] 将移动到的行的索引；不会移动时为 `None`。
+    pub fn next_text_index(&self) -> Option<usize> {
+        let end = self.next_shown(self.line_group_end());
+        (end < self.parsed.text_items.len()).then_some(end)
     }
 
     pub fn reset(&mut self) {
         self.text_index = 0;
+        self.else_branch = None;
     }
 
     pub fn collect_run_items_from(&self, start_index: usize) -> Vec<DialogueRunItem> {
         collect_consecutive_runs(
-            &self.node_data.content,
+            &self.parsed.node_data.content,
             start_index,
             &self.executed_content_indices,
+            |idx| self.content_gate(idx),
         )
     }
 
     pub fn has_choices(&self) -> bool {
-        self.choices.is_some()
+        self.parsed.choices.is_some()
     }
 
     pub fn get_next_node(&self) -> Option<&str> {
-        self.node_data.next.as_deref()
+        self.parsed.node_data.next.as_deref()
     }
 
     pub fn get_runs_at_content_position(
@@ -405,32 +630,64 @@ impl DialogueState {
         content_position: usize,
     ) -> Vec<DialogueRunDescriptor> {
         collect_runs_at_position(
-            &self.node_data.content,
+            &self.parsed.node_data.content,
             content_position,
             &self.executed_content_indices,
         )
     }
 
     pub fn mark_content_executed(&mut self, content_index: usize) {
-        if !self.executed_content_indices.contains(&content_index) {
-            self.executed_content_indices.push(content_index);
-        }
+        self.executed_content_indices.insert(content_index);
     }
 
     pub fn node_data(&self) -> &Node {
-        &self.node_data
+        &self.parsed.node_data
+    }
+
+    /// The node this dialogue walks through, shared with the other dialogues on it.
+    ///
+    /// 此对话遍历的节点，与该节点上的其他对话共享。
+    pub fn parsed_node(&self) -> &Arc<ParsedNode> {
+        &self.parsed
+    }
+
+    /// Fields of the node's items that failed to deserialize, see [`ParsedNode::diagnostics`].
+    ///
+    /// 节点各项中无法反序列化的字段，参见 [`ParsedNode::diagnostics`]。
+    pub fn diagnostics(&self) -> &[MortarDiagnostic] {
+        self.parsed.diagnostics()
     }
 
     pub fn current_text_content_index(&self) -> Option<usize> {
-        self.text_to_content_index.get(self.text_index).copied()
+        self.current_text_position()
+            .map(|position| position.content_index)
     }
 
     pub fn line_group_last_content_index(&self) -> Option<usize> {
         let end = self.line_group_end();
-        self.text_to_content_index.get(end - 1).copied()
+        self.parsed
+            .text_positions
+            .get(end - 1)
+            .map(|position| position.content_index)
+    }
+
+    /// Content index the `run` statements after the current line group start at, or `None`
+    /// while the group's content item still has texts of its `continuation` to show.
+    ///
+    /// 当前 line 组之后的 `run` 语句起始的内容索引；若该组所在内容项的 `continuation` 中仍有文本
+    /// 待显示，则为 `None`。
+    pub fn run_position_after_line_group(&self) -> Option<usize> {
+        let end = self.line_group_end();
+        let last = self.parsed.text_positions.get(end - 1)?;
+        let continues = self
+            .parsed
+            .text_positions
+            .get(end)
+            .is_some_and(|next| next.content_index == last.content_index);
+        (!continues).then_some(last.content_index + 1)
     }
 
-    pub fn text_to_content_indices(&self) -> &[usize] {
-        &self.text_to_content_index
+    pub fn text_items(&self) -> &[TextData] {
+        &self.parsed.text_items
     }
 }
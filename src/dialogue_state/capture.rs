@@ -0,0 +1,95 @@
+//! # capture.rs
+//!
+//! # capture.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Stores which option was picked into a script variable, so text after a choice can say "So you
+//! chose the {last_choice} path". The variable comes from a `capture` field on the choice group
+//! (the `choice` content item or, for nested groups, the option that opens them; nested groups
+//! otherwise reuse their parent's) and falls back to [`ChoiceCapture::default_variable`]. The
+//! option's `id` (or its text, see [`CaptureValue`]) goes into the variable and its declared index
+//! into `{variable}_index`.
+//!
+//! 将被选中的选项存入脚本变量，使选项之后的文本可以写“原来你选择了 {last_choice} 这条路”。
+//! 变量名取自选项组上的 `capture` 字段（`choice` 内容项，或对嵌套组而言打开该组的选项；嵌套组
+//! 未设置时沿用父级的变量），否则回退到 [`ChoiceCapture::default_variable`]。选项的 `id`
+//! （或其文本，参见 [`CaptureValue`]）会写入该变量，其声明索引写入 `{variable}_index`。
+
+use super::DialogueState;
+use crate::MortarVariableValue;
+
+/// What a captured choice stores in its variable.
+///
+/// 被捕获的选项在变量中存储的内容。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CaptureValue {
+    /// The option's `id`, or its text when it has none.
+    ///
+    /// 选项的 `id`；没有 `id` 时为其文本。
+    #[default]
+    Id,
+    /// The option's text.
+    ///
+    /// 选项的文本。
+    Text,
+}
+
+/// Capture of choice groups without their own `capture` field.
+///
+/// 未设置自身 `capture` 字段的选项组的捕获方式。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChoiceCapture {
+    /// Variable every choice group captures into, e.g. `_last_choice`; `None` captures only
+    /// groups that name a variable.
+    ///
+    /// 所有选项组捕获到的变量，例如 `_last_choice`；为 `None` 时只捕获指定了变量的选项组。
+    pub default_variable: Option<String>,
+    pub value: CaptureValue,
+}
+
+impl DialogueState {
+    /// The variable and value that confirming option `index` of the current group captures.
+    ///
+    /// 确认当前选项组中第 `index` 个选项时捕获的变量与值。
+    pub fn choice_capture(
+        &self,
+        index: usize,
+        settings: &ChoiceCapture,
+    ) -> Option<(String, MortarVariableValue)> {
+        let group = self
+            .parsed
+            .node_data
+            .content
+            .get(self.parsed.choice_content_index?)?;
+        let mut variable = read_capture(group).or(settings.default_variable.as_deref());
+        let mut options = group.get("options")?;
+        for &level in &self.choice_stack {
+            let option = options.get(level)?;
+            variable = read_capture(option).or(variable);
+            options = option.get("choice")?;
+        }
+        let variable = variable?;
+        let option = options.get(index)?;
+        let text = option.get("text").and_then(serde_json::Value::as_str);
+        let id = option.get("id").and_then(serde_json::Value::as_str);
+        let value = match settings.value {
+            CaptureValue::Id => id.or(text),
+            CaptureValue::Text => text,
+        }
+        .unwrap_or_default();
+        Some((
+            variable.to_owned(),
+            MortarVariableValue::String(value.to_owned()),
+        ))
+    }
+}
+
+fn read_capture(value: &serde_json::Value) -> Option<&str> {
+    value
+        .get("capture")
+        .and_then(serde_json::Value::as_str)
+        .filter(|name| !name.is_empty())
+}
@@ -0,0 +1,93 @@
+//! # choice_mutation.rs
+//!
+//! # choice_mutation.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Options that leave or join their group while it is on screen. An option with
+//! `"remove_after_pick": true` is left out of later presentations of its group once picked, for
+//! the rest of the node visit or, with `"scope": "session"`, until
+//! [`crate::MortarRuntime::clear_removed_choices`]. An option with a `visible_if` condition is
+//! hidden while it is false, unlike `condition`, which only disables it. Declared indices of the
+//! remaining options never shift.
+//!
+//! 在屏幕上显示期间离开或加入其选项组的选项。带有 `"remove_after_pick": true` 的选项被选中后，
+//! 在该节点本次访问的剩余时间内（或在 `"scope": "session"` 时直到调用
+//! [`crate::MortarRuntime::clear_removed_choices`] 为止）不再出现在该组之后的呈现中。带有
+//! `visible_if` 条件的选项在条件为假时被隐藏，而 `condition` 只会禁用选项。其余选项的声明索引
+//! 始终不变。
+
+use mortar_compiler::Condition;
+use serde_json::Value;
+use std::collections::HashSet;
+
+use super::DialogueState;
+
+/// How long a picked `remove_after_pick` option stays removed.
+///
+/// 被选中的 `remove_after_pick` 选项保持移除的时长。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) enum RemovalScope {
+    /// Until the dialogue leaves the node.
+    #[default]
+    Visit,
+    /// Until the removals are cleared on the runtime.
+    Session,
+}
+
+/// An option removed from its group, keyed by where the group is declared.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct RemovedChoice {
+    path: String,
+    node: String,
+    group: usize,
+    choice_stack: Vec<usize>,
+    index: usize,
+}
+
+/// The `visible_if` condition of an option.
+pub(crate) fn visible_if(option: &Value) -> Option<Condition> {
+    serde_json::from_value(option.get("visible_if")?.clone()).ok()
+}
+
+impl DialogueState {
+    fn removal_key(&self, index: usize) -> Option<RemovedChoice> {
+        Some(RemovedChoice {
+            path: self.mortar_path.clone(),
+            node: self.current_node.clone(),
+            group: self.parsed.choice_content_index?,
+            choice_stack: self.choice_stack.clone(),
+            index,
+        })
+    }
+
+    /// The removal that picking option `index` of the current group causes, if any.
+    pub(crate) fn choice_removal(&self, index: usize) -> Option<(RemovalScope, RemovedChoice)> {
+        let option = self.current_option_values()?.get(index)?;
+        if !option
+            .get("remove_after_pick")
+            .and_then(Value::as_bool)
+            .unwrap_or(false)
+        {
+            return None;
+        }
+        let scope = match option.get("scope").and_then(Value::as_str) {
+            Some("session") => RemovalScope::Session,
+            _ => RemovalScope::Visit,
+        };
+        Some((scope, self.removal_key(index)?))
+    }
+
+    /// Records a removal scoped to this node visit.
+    pub(crate) fn remove_choice(&mut self, removed: RemovedChoice) {
+        self.removed_choices.insert(removed);
+    }
+
+    /// Whether option `index` of the current group was removed in this visit or in `session`.
+    pub(crate) fn choice_removed(&self, index: usize, session: &HashSet<RemovedChoice>) -> bool {
+        self.removal_key(index)
+            .is_some_and(|key| self.removed_choices.contains(&key) || session.contains(&key))
+    }
+}
@@ -0,0 +1,96 @@
+//! # choice_options.rs
+//!
+//! # choice_options.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Raw JSON of choice options, for what the compiler's `Choice` does not keep. Icon-only options
+//! may leave `text` out: it reads as an empty string, with a warning only when the option has no
+//! `id` or `icon` either. The `id`, the `icon` and any other keys of an option reach the UI
+//! through [`crate::MortarChoiceView`].
+//!
+//! 选项的原始 JSON，用于编译器的 `Choice` 未保留的内容。仅有图标的选项可以省略 `text`：此时按空字符串
+//! 处理，只有在选项也没有 `id` 或 `icon` 时才会警告。选项的 `id`、`icon` 以及其他任意键都会通过
+//! [`crate::MortarChoiceView`] 传给 UI。
+
+use bevy::prelude::*;
+use serde_json::{Map, Value};
+
+use super::DialogueState;
+use crate::debug::LOG_DIALOGUE;
+
+/// Option keys read by the compiler or the bond layer, left out of the metadata map.
+const KNOWN_KEYS: &[&str] = &[
+    "text",
+    "condition",
+    "next",
+    "action",
+    "choice",
+    "id",
+    "icon",
+    "capture",
+    "remove_after_pick",
+    "scope",
+    "visible_if",
+    "experiment",
+    "requires",
+    "ui",
+];
+
+/// Gives options without `text` an empty one, recursing into nested groups.
+pub(super) fn fill_missing_text(options: &mut Value, content_index: usize) {
+    let Some(options) = options.as_array_mut() else {
+        return;
+    };
+    for option in options.iter_mut().filter_map(Value::as_object_mut) {
+        let text = option.get("text").and_then(Value::as_str).unwrap_or("");
+        if text.is_empty() && !option.contains_key("id") && !option.contains_key("icon") {
+            warn!(
+                target: LOG_DIALOGUE,
+                "Choice option at content index {} has no text, id or icon", content_index
+            );
+        }
+        if !option.get("text").is_some_and(Value::is_string) {
+            option.insert("text".to_owned(), Value::String(String::new()));
+        }
+        if let Some(nested) = option.get_mut("choice") {
+            fill_missing_text(nested, content_index);
+        }
+    }
+}
+
+/// The `id`, `icon` and remaining keys of an option.
+pub(crate) fn option_details(
+    option: &Value,
+) -> (Option<String>, Option<String>, Map<String, Value>) {
+    let string = |key| option.get(key).and_then(Value::as_str).map(str::to_owned);
+    let metadata = option
+        .as_object()
+        .map(|object| {
+            object
+                .iter()
+                .filter(|(key, _)| !KNOWN_KEYS.contains(&key.as_str()))
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect()
+        })
+        .unwrap_or_default();
+    (string("id"), string("icon"), metadata)
+}
+
+impl DialogueState {
+    /// Raw JSON of the options in the current choice group.
+    pub(crate) fn current_option_values(&self) -> Option<&Vec<Value>> {
+        let group = self
+            .parsed
+            .node_data
+            .content
+            .get(self.parsed.choice_content_index?)?;
+        let mut options = group.get("options")?;
+        for &level in &self.choice_stack {
+            options = options.get(level)?.get("choice")?;
+        }
+        options.as_array()
+    }
+}
@@ -0,0 +1,142 @@
+//! # conditional.rs
+//!
+//! # conditional.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! `if` / `else` blocks over texts. The compiler flattens a block into a run of texts carrying
+//! the `if` condition `C`, followed by a run carrying `!C` for the `else` body; texts of nested
+//! blocks carry `C && …` or `!C && …`. Each `if` text checks `C` when reached; when the first one
+//! fails the dialogue continues at the first `else` text. Otherwise the `else` run is left out, even
+//! if the `if` body changed the variables `C` reads: it is only ever entered through its `if`, so
+//! the dialogue remembers which `else` run it jumped into.
+//!
+//! 作用于文本的 `if` / `else` 块。编译器会把一个块展开为一段带有 `if` 条件 `C` 的文本，随后是一段
+//! 带有 `!C` 的 `else` 体文本；嵌套块中的文本带有 `C && …` 或 `!C && …`。每条 `if` 文本在到达时检查
+//! `C`；第一条不成立时，对话从第一条 `else` 文本继续。否则会略过 `else` 段，即使 `if` 体修改了 `C`
+//! 读取的变量：`else` 段只能经由其 `if` 进入，因此对话会记住它跳入的是哪一段 `else`。
+
+use mortar_compiler::IfCondition;
+
+use super::{DialogueState, TextData};
+use crate::{MortarFunctionRegistry, MortarVariableState, evaluate_if_condition};
+
+fn same(a: &IfCondition, b: &IfCondition) -> bool {
+    let same_child = |a: &Option<Box<IfCondition>>, b: &Option<Box<IfCondition>>| match (a, b) {
+        (Some(a), Some(b)) => same(a, b),
+        (a, b) => a.is_none() && b.is_none(),
+    };
+    a.cond_type == b.cond_type
+        && a.operator == b.operator
+        && a.value == b.value
+        && same_child(&a.left, &b.left)
+        && same_child(&a.right, &b.right)
+        && same_child(&a.operand, &b.operand)
+}
+
+/// The condition `!C` negates, if it is a negation.
+fn negated(condition: &IfCondition) -> Option<&IfCondition> {
+    (condition.cond_type == "unary" && condition.operator.as_deref() == Some("!"))
+        .then_some(condition.operand.as_deref())
+        .flatten()
+}
+
+/// Whether `condition` belongs to the body of a block on `block`: it is `block` itself, or
+/// `block && …` from a block nested in it.
+fn inside(condition: &IfCondition, block: &IfCondition) -> bool {
+    same(condition, block)
+        || (condition.cond_type == "binary"
+            && condition.operator.as_deref() == Some("&&")
+            && condition
+                .left
+                .as_deref()
+                .is_some_and(|left| inside(left, block)))
+}
+
+impl DialogueState {
+    fn text_inside(&self, index: usize, block: &IfCondition) -> bool {
+        self.parsed
+            .text_items
+            .get(index)
+            .and_then(|text| text.condition.as_ref())
+            .is_some_and(|condition| inside(condition, block))
+    }
+
+    /// The first text of the `else` run paired with the `if` text at `index`.
+    fn paired_else(&self, index: usize, condition: &IfCondition) -> Option<usize> {
+        (index + 1..self.parsed.text_items.len())
+            .find(|&next| !self.text_inside(next, condition))
+            .filter(|&next| {
+                self.parsed.text_items[next]
+                    .condition
+                    .as_ref()
+                    .and_then(negated)
+                    .is_some_and(|operand| same(operand, condition))
+            })
+    }
+
+    /// The first text of the `else` run holding the text at `index`, when it is one.
+    fn else_run_start(&self, index: usize, condition: &IfCondition) -> Option<usize> {
+        let block = negated(condition)?;
+        let start = (0..index)
+            .rev()
+            .find(|&previous| !self.text_inside(previous, condition))
+            .map_or(0, |previous| previous + 1);
+        (start > 0 && self.text_inside(start - 1, block)).then_some(start)
+    }
+
+    /// Resolves the text at `index` through its `if` / `else` block: the text itself when it
+    /// has no condition or its condition holds, the first `else` text when it opens an `if` whose
+    /// condition fails, and `None` when it is left out. An `else` text is left out unless the
+    /// dialogue jumped into its run from the failed `if`. Returns the resolved text with its
+    /// index.
+    ///
+    /// 按 `if` / `else` 块解析 `index` 处的文本：没有条件或条件成立时为该文本本身；它开启的 `if` 条件
+    /// 不成立时为第一条 `else` 文本；被略过时为 `None`。除非对话是从不成立的 `if` 跳入其所在段，
+    /// 否则 `else` 文本都会被略过。返回解析出的文本及其索引。
+    pub fn resolve_text_at(
+        &self,
+        index: usize,
+        functions: &MortarFunctionRegistry,
+        variable_state: &MortarVariableState,
+    ) -> Option<(usize, &TextData)> {
+        self.resolve_text_with(index, |condition| {
+            evaluate_if_condition(condition, functions, variable_state)
+        })
+    }
+
+    /// [`Self::resolve_text_at`] checking conditions with `holds`.
+    pub(crate) fn resolve_text_with(
+        &self,
+        index: usize,
+        holds: impl Fn(&IfCondition) -> bool,
+    ) -> Option<(usize, &TextData)> {
+        let text = self.parsed.text_items.get(index)?;
+        if self.is_text_hidden(index) {
+            return None;
+        }
+        let Some(condition) = &text.condition else {
+            return Some((index, text));
+        };
+        if let Some(start) = self.else_run_start(index, condition) {
+            return (self.else_branch == Some(start)).then_some((index, text));
+        }
+        if holds(condition) {
+            return Some((index, text));
+        }
+        let opens_block = index == 0 || !self.text_inside(index - 1, condition);
+        let start = self
+            .paired_else(index, condition)
+            .filter(|&start| opens_block && !self.is_text_hidden(start))?;
+        Some((start, &self.parsed.text_items[start]))
+    }
+
+    /// Moves to `start`, the first text of an `else` run that [`Self::resolve_text_at`] resolved
+    /// a failed `if` to, so the rest of the run is shown.
+    pub(crate) fn enter_else_branch(&mut self, start: usize) {
+        self.text_index = start;
+        self.else_branch = Some(start);
+    }
+}
@@ -0,0 +1,88 @@
+//! # content_diagnostics.rs
+//!
+//! # content_diagnostics.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Reports the fields of content items that fail to deserialize while a node is read. Such a
+//! field used to be dropped without a word, so a text whose `condition` was malformed lost it and
+//! showed when it should not. The field is still read as absent, but each one now leaves a
+//! [`MortarDiagnostic`] on the [`ParsedNode`](super::ParsedNode), which the asset gathers in
+//! [`crate::MortarAsset::content_diagnostics`] and the loader logs once per load.
+//!
+//! 报告读取节点时无法反序列化的内容项字段。过去这样的字段会被悄悄丢弃，因此 `condition` 格式错误
+//! 的文本会失去条件，在不应显示时显示出来。该字段仍按缺失处理，但现在每个这样的字段都会在
+//! [`ParsedNode`](super::ParsedNode) 上留下一条 [`MortarDiagnostic`]，资源在
+//! [`crate::MortarAsset::content_diagnostics`] 中汇总它们，加载器在每次加载时记录一次。
+
+use std::fmt;
+
+/// A content item field that failed to deserialize and was read as absent.
+///
+/// 无法反序列化、因而按缺失处理的内容项字段。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MortarDiagnostic {
+    pub node: String,
+    /// Position of the item in the node's content.
+    ///
+    /// 该项在节点内容中的位置。
+    pub content_index: usize,
+    /// The field, prefixed by the entry holding it for `continuation` texts and voices, e.g.
+    /// `condition` or `texts[1].interpolated_parts`.
+    ///
+    /// 字段名；对于 `continuation` 文本与声部会带上所在条目的前缀，例如 `condition` 或
+    /// `texts[1].interpolated_parts`。
+    pub field: String,
+    /// The deserializer's error.
+    ///
+    /// 反序列化器给出的错误。
+    pub message: String,
+}
+
+impl fmt::Display for MortarDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "node '{}' item {}: malformed '{}': {}",
+            self.node, self.content_index, self.field, self.message
+        )
+    }
+}
+
+/// Reads the fields of one content item, reporting those that fail to deserialize.
+pub(super) struct ItemReader<'a> {
+    pub(super) node: &'a str,
+    pub(super) content_index: usize,
+    pub(super) diagnostics: &'a mut Vec<MortarDiagnostic>,
+}
+
+impl ItemReader<'_> {
+    /// `field` of `item`, where `item` sits at `prefix` within the content item, read with
+    /// `read` (usually `serde_json::from_value`); `None` when it is missing or malformed.
+    pub(super) fn field<T>(
+        &mut self,
+        item: &serde_json::Value,
+        prefix: &str,
+        field: &str,
+        read: fn(serde_json::Value) -> serde_json::Result<T>,
+    ) -> Option<T> {
+        let value = item.get(field)?.clone();
+        self.report(read(value), &format!("{prefix}{field}"))
+    }
+
+    /// The value of `result`, reporting its error as one of `field`.
+    pub(super) fn report<T>(&mut self, result: serde_json::Result<T>, field: &str) -> Option<T> {
+        result
+            .map_err(|err| {
+                self.diagnostics.push(MortarDiagnostic {
+                    node: self.node.to_owned(),
+                    content_index: self.content_index,
+                    field: field.to_owned(),
+                    message: err.to_string(),
+                });
+            })
+            .ok()
+    }
+}
@@ -0,0 +1,73 @@
+//! # cursor.rs
+//!
+//! # cursor.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! [`LineCursor`], the position of a dialogue as one comparable token. A raw `text_index` repeats
+//! across nodes and across visits of the same node, so a UI caching it can mistake a new line for
+//! the one it already shows. The cursor pairs the index with the node visit it belongs to; every
+//! activation of a node starts a new visit, so cursors of different visits never compare equal.
+//!
+//! [`LineCursor`]：将对话位置表示为一个可比较的令牌。原始的 `text_index` 在不同节点之间、以及同一
+//! 节点的多次访问之间都会重复，缓存它的 UI 可能会把新行误认为已显示的行。游标将索引与其所属的节点
+//! 访问配对；每次激活节点都会开始新的访问，因此不同访问的游标永远不会相等。
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use super::DialogueState;
+
+/// Source of node visits, shared by every dialogue so a visit is never reused.
+static NEXT_VISIT: AtomicU64 = AtomicU64::new(1);
+
+pub(super) fn next_visit() -> u64 {
+    NEXT_VISIT.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Where a dialogue stands: the node visit and the line within it. Compare cursors, not text
+/// indices, to tell whether the line changed. Cursors order by visit, later visits after earlier
+/// ones, then by line. The default cursor stands before every line.
+///
+/// 对话所处的位置：节点访问及其中的行。判断行是否变化时应比较游标，而不是文本索引。游标先按访问
+/// 排序（较晚的访问排在较早的之后），再按行排序。默认游标位于所有行之前。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LineCursor {
+    visit: u64,
+    text_index: usize,
+}
+
+impl LineCursor {
+    /// Text index of the line within its node.
+    ///
+    /// 该行在其节点中的文本索引。
+    pub fn text_index(&self) -> usize {
+        self.text_index
+    }
+
+    /// Whether both cursors belong to the same visit of a node.
+    ///
+    /// 两个游标是否属于同一次节点访问。
+    pub fn same_visit(&self, other: &Self) -> bool {
+        self.visit == other.visit
+    }
+}
+
+impl DialogueState {
+    /// The cursor of the current line, see [`LineCursor`].
+    ///
+    /// 当前行的游标，参见 [`LineCursor`]。
+    pub fn cursor(&self) -> LineCursor {
+        LineCursor {
+            visit: self.visit,
+            text_index: self.text_index,
+        }
+    }
+
+    /// Starts a new visit of the node, so a state parsed ahead of time orders after the nodes
+    /// entered before it.
+    pub(crate) fn begin_visit(&mut self) {
+        self.visit = next_visit();
+    }
+}
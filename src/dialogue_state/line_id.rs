@@ -0,0 +1,93 @@
+//! # line_id.rs
+//!
+//! # line_id.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Computes stable line identifiers for localization keys and analytics. A line keeps its id when
+//! other lines are inserted, removed or reordered around it, unlike its `text_index`.
+//!
+//! The algorithm is part of the public contract and must not change between releases:
+//!
+//! 1. A text item with a string `id` field uses that value verbatim.
+//! 2. Otherwise the id is the 64-bit FNV-1a hash of `node name`, `0x1F`, `raw text value`, `0x1F`,
+//!    `occurrence` (decimal), written as 16 lowercase hex digits. `occurrence` counts earlier
+//!    items of the same node with the same raw text, starting at 0, so repeated lines stay
+//!    distinct. The raw value is the text before interpolation.
+//! 3. The voices of a `parallel_text` item are text items too, counted right after the item.
+//! 4. A choice option with a string `id` uses it; otherwise the id is the same hash over
+//!    `node name`, `0x1F`, `choice`, `0x1F`, `option text`, `0x1F`, `occurrence`, where
+//!    `occurrence` counts earlier options of the node, nested ones included, with the same text.
+//!
+//! 为本地化键和统计分析计算稳定的行标识符。与 `text_index` 不同，在其周围插入、删除或重排其他行时，
+//! 行的标识符保持不变。
+//!
+//! 该算法属于公开约定，不得在版本之间改变：
+//!
+//! 1. 带有字符串 `id` 字段的文本项直接使用该值。
+//! 2. 否则标识符为 `节点名`、`0x1F`、`原始文本值`、`0x1F`、`出现次数`（十进制）的 64 位 FNV-1a
+//!    哈希，写作 16 位小写十六进制数字。`出现次数` 统计同一节点中原始文本相同的前序文本项，从 0
+//!    开始，因此重复的行也能区分。原始值指插值之前的文本。
+//! 3. `parallel_text` 项的各声部同样是文本项，紧接在该项之后计数。
+//! 4. 带有字符串 `id` 的选项直接使用该值；否则标识符为 `节点名`、`0x1F`、`choice`、`0x1F`、
+//!    `选项文本`、`0x1F`、`出现次数` 的同一哈希，`出现次数` 统计该节点中（含嵌套选项）文本相同的
+//!    前序选项。
+
+use std::collections::HashMap;
+
+use super::TextData;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+const SEPARATOR: u8 = 0x1F;
+
+fn fnv1a(parts: &[&[u8]]) -> u64 {
+    let mut hash = FNV_OFFSET_BASIS;
+    for (index, part) in parts.iter().enumerate() {
+        if index > 0 {
+            hash = (hash ^ u64::from(SEPARATOR)).wrapping_mul(FNV_PRIME);
+        }
+        for byte in *part {
+            hash = (hash ^ u64::from(*byte)).wrapping_mul(FNV_PRIME);
+        }
+    }
+    hash
+}
+
+/// Hashes one line of `node` whose raw text occurred `occurrence` times before it.
+fn content_line_id(node: &str, value: &str, occurrence: usize) -> String {
+    let occurrence = occurrence.to_string();
+    let hash = fnv1a(&[node.as_bytes(), value.as_bytes(), occurrence.as_bytes()]);
+    format!("{hash:016x}")
+}
+
+/// Hashes a choice option of `node` whose text occurred `occurrence` times before it.
+#[cfg(feature = "tools")]
+pub(crate) fn choice_line_id(node: &str, text: &str, occurrence: usize) -> String {
+    let occurrence = occurrence.to_string();
+    let hash = fnv1a(&[
+        node.as_bytes(),
+        b"choice",
+        text.as_bytes(),
+        occurrence.as_bytes(),
+    ]);
+    format!("{hash:016x}")
+}
+
+/// Fills the `line_id` of every item that has no explicit id, in node order.
+pub(super) fn assign_line_ids(node: &str, items: &mut [TextData]) {
+    assign_counted(node, items, &mut HashMap::new());
+}
+
+fn assign_counted(node: &str, items: &mut [TextData], occurrences: &mut HashMap<String, usize>) {
+    for item in items {
+        let occurrence = occurrences.entry(item.value.clone()).or_default();
+        if item.line_id.is_empty() {
+            item.line_id = content_line_id(node, &item.value, *occurrence);
+        }
+        *occurrence += 1;
+        assign_counted(node, &mut item.parallel, occurrences);
+    }
+}
@@ -0,0 +1,220 @@
+//! # node_content.rs
+//!
+//! # node_content.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Reads the content items of a node into the text items and the choice group a
+//! [`DialogueState`](super::DialogueState) walks through, with the keys the compiler's types do
+//! not carry: line ids, headers, experiment tags and required capabilities. A `parallel_text`
+//! item becomes one text item holding its voices, so it takes a single advance step.
+//!
+//! Each text item is mapped back to its content item through a [`TextPosition`]. A `continuation`
+//! array on a `text` or `line` item adds further text items at the same content index; they keep
+//! their declaration order through the position's sub index, so comparing positions never depends
+//! on which of the texts a lookup happens to find first.
+//!
+//! 将节点的内容项读取为 [`DialogueState`](super::DialogueState) 遍历的文本项与选项组，并读取编译器
+//! 类型未保留的键：行标识符、头部、实验标签与所需能力。`parallel_text` 项会成为一个包含其各声部的
+//! 文本项，因此只占一次推进。
+//!
+//! 每个文本项通过 [`TextPosition`] 映射回其内容项。`text` 或 `line` 项上的 `continuation` 数组会在
+//! 同一内容索引处追加文本项；它们按声明顺序记录在位置的子索引中，因此比较位置时不会取决于查找碰巧
+//! 先找到其中哪一个文本。
+
+use bevy::prelude::*;
+use mortar_compiler::Choice;
+
+use super::content_diagnostics::ItemReader;
+use super::{DialogueState, TextData, choice_options};
+
+/// Where a text item comes from: the content item holding it and its place among that item's
+/// texts. A content item holds one text at sub index 0 unless a `continuation` adds more, which
+/// are numbered in declaration order. Positions order by content index, then by sub index, which
+/// is the order the texts play in.
+///
+/// 文本项的来源：包含它的内容项，以及它在该内容项文本中的位置。内容项通常只在子索引 0 处包含一个
+/// 文本，除非 `continuation` 追加了更多文本，这些文本按声明顺序编号。位置先按内容索引排序，再按
+/// 子索引排序，即文本播放的顺序。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TextPosition {
+    pub content_index: usize,
+    pub sub_index: usize,
+}
+
+impl TextPosition {
+    /// The first text of content item `content_index`, and the position every run or choice
+    /// item at that index compares as.
+    ///
+    /// 内容项 `content_index` 的第一个文本，也是该索引处的 run 或选项项参与比较时使用的位置。
+    pub fn item(content_index: usize) -> Self {
+        Self {
+            content_index,
+            sub_index: 0,
+        }
+    }
+}
+
+pub(super) fn parse_node_content(
+    reader: &mut ItemReader,
+    content_value: &serde_json::Value,
+    text_items: &mut Vec<TextData>,
+    text_positions: &mut Vec<TextPosition>,
+    choice_content_index: &mut Option<usize>,
+    choices: &mut Option<Vec<Choice>>,
+) {
+    let Some(type_str) = content_value.get("type").and_then(|value| value.as_str()) else {
+        return;
+    };
+    match type_str {
+        "text" | "line" => {
+            let continuation = content_value
+                .get("continuation")
+                .and_then(|value| value.as_array());
+            let texts = std::iter::once(content_value).chain(continuation.into_iter().flatten());
+            for (sub_index, text) in texts.enumerate() {
+                let prefix = match sub_index {
+                    0 => String::new(),
+                    sub_index => format!("continuation[{}].", sub_index - 1),
+                };
+                text_items.push(parse_text(text, type_str == "line", reader, &prefix));
+                text_positions.push(TextPosition {
+                    content_index: reader.content_index,
+                    sub_index,
+                });
+            }
+        }
+        "parallel_text" => {
+            let parallel = content_value
+                .get("texts")
+                .and_then(|value| value.as_array())
+                .map(|voices| {
+                    voices
+                        .iter()
+                        .enumerate()
+                        .map(|(index, voice)| {
+                            let prefix = format!("texts[{index}].");
+                            let mut text = parse_text(voice, false, reader, &prefix);
+                            text.channel = ["target", "speaker"]
+                                .iter()
+                                .find_map(|key| voice.get(*key).and_then(|value| value.as_str()))
+                                .map(str::to_owned);
+                            text
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            text_items.push(TextData {
+                parallel,
+                ..parse_text(content_value, false, reader, "")
+            });
+            text_positions.push(TextPosition::item(reader.content_index));
+        }
+        "choice" => {
+            let Some(mut options_value) = content_value.get("options").cloned() else {
+                return;
+            };
+            choice_options::fill_missing_text(&mut options_value, reader.content_index);
+            let parsed = serde_json::from_value::<Vec<Choice>>(options_value);
+            let Some(parsed_choices) = reader.report(parsed, "options") else {
+                return;
+            };
+            *choices = Some(parsed_choices);
+            *choice_content_index = Some(reader.content_index);
+        }
+        "run_event" => {
+            // The run reads these again when it fires; reading them here reports them at load.
+            //
+            // run 触发时会再次读取这些字段；在此读取是为了在加载时报告它们。
+            let _: Option<Vec<String>> =
+                reader.field(content_value, "", "args", serde_json::from_value);
+            let _: Option<mortar_compiler::IndexOverride> =
+                reader.field(content_value, "", "index_override", serde_json::from_value);
+        }
+        _ => {}
+    }
+}
+
+impl DialogueState {
+    /// Position of the current text, see [`TextPosition`].
+    ///
+    /// 当前文本的位置，参见 [`TextPosition`]。
+    pub fn current_text_position(&self) -> Option<TextPosition> {
+        self.parsed.text_positions.get(self.text_index).copied()
+    }
+
+    /// Positions of the node's text items, by text index.
+    ///
+    /// 节点各文本项的位置，按文本索引排列。
+    pub fn text_positions(&self) -> &[TextPosition] {
+        &self.parsed.text_positions
+    }
+
+    /// The raw item the text at `index` was read from: its content item, or the entry of that
+    /// item's `continuation` it stands for.
+    ///
+    /// `index` 处文本读取自的原始项：其内容项，或它所对应的该内容项 `continuation` 条目。
+    pub fn text_source(&self, index: usize) -> Option<&serde_json::Value> {
+        let position = self.parsed.text_positions.get(index)?;
+        let item = self.parsed.node_data.content.get(position.content_index)?;
+        match position.sub_index {
+            0 => Some(item),
+            sub_index => item.get("continuation")?.as_array()?.get(sub_index - 1),
+        }
+    }
+}
+
+/// Reads a `text` or `line` item, or one voice of a `parallel_text` item.
+fn parse_text(
+    content_value: &serde_json::Value,
+    is_line: bool,
+    reader: &mut ItemReader,
+    prefix: &str,
+) -> TextData {
+    let value = content_value
+        .get("value")
+        .and_then(|value| value.as_str())
+        .unwrap_or("")
+        .to_string();
+    let interpolated_parts = reader.field(
+        content_value,
+        prefix,
+        "interpolated_parts",
+        serde_json::from_value,
+    );
+    let condition = reader.field(content_value, prefix, "condition", serde_json::from_value);
+    let pre_statements = reader
+        .field(
+            content_value,
+            prefix,
+            "pre_statements",
+            serde_json::from_value,
+        )
+        .unwrap_or_default();
+    let events = reader.field(content_value, prefix, "events", serde_json::from_value);
+    let line_id = content_value
+        .get("id")
+        .and_then(|value| value.as_str())
+        .unwrap_or_default()
+        .to_string();
+    let header = content_value
+        .get("header")
+        .and_then(|value| value.as_str())
+        .map(str::to_owned);
+    TextData {
+        value,
+        interpolated_parts,
+        condition,
+        pre_statements,
+        events,
+        is_line,
+        line_id,
+        header,
+        experiment: crate::MortarExperimentTag::from_item(content_value),
+        requires: crate::runtime::required_capabilities(content_value),
+        channel: None,
+        parallel: Vec::new(),
+    }
+}
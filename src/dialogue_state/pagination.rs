@@ -0,0 +1,145 @@
+//! # pagination.rs
+//!
+//! # pagination.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Splits long choice lists into pages. Pages are consecutive runs of presented positions, which
+//! follow declaration order unless options set a `ui.order` hint (see `ui_hints.rs`).
+//! `SelectChoice` keeps addressing the declared index and only the presented subset changes. The
+//! page size comes from a `page_size` field on the choice group (the `choice` content item or, for
+//! nested groups, the option that opens them) and falls back to [`ChoicePagination`]. Pages whose
+//! options are all disabled are skipped.
+//!
+//! 将较长的选项列表拆分为多页。每页是一段连续的呈现位置；除非选项设置了 `ui.order` 提示（参见
+//! `ui_hints.rs`），呈现位置即声明顺序。`SelectChoice` 始终使用声明索引，只有呈现的子集会变化。每页大小取自选项组上的 `page_size` 字段（`choice` 内容项，或对嵌套组而言
+//! 打开该组的选项），否则回退到 [`ChoicePagination`]。所有选项都被禁用的页面会被跳过。
+
+use super::DialogueState;
+
+/// Default pagination of choice groups without their own `page_size`.
+///
+/// 未设置自身 `page_size` 的选项组的默认分页方式。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChoicePagination {
+    /// Options per page; `0` disables pagination.
+    ///
+    /// 每页的选项数；为 `0` 时关闭分页。
+    pub page_size: usize,
+}
+
+/// Number of pages of `len` options.
+pub(crate) fn page_count(len: usize, page_size: usize) -> usize {
+    len.div_ceil(page_size).max(1)
+}
+
+/// The page shown for `requested`: itself when it has an enabled option, otherwise the nearest
+/// such page after it, then before it.
+pub(crate) fn visible_page(
+    requested: usize,
+    len: usize,
+    page_size: usize,
+    is_disabled: impl Fn(usize) -> bool,
+) -> usize {
+    let count = page_count(len, page_size);
+    let requested = requested.min(count - 1);
+    let usable = |page: &usize| page_usable(*page, len, page_size, &is_disabled);
+    (requested..count)
+        .find(usable)
+        .or_else(|| (0..requested).rev().find(usable))
+        .unwrap_or(requested)
+}
+
+/// Whether page `page` of `len` options has an enabled option; `is_disabled` takes a position.
+pub(crate) fn page_usable(
+    page: usize,
+    len: usize,
+    page_size: usize,
+    is_disabled: impl Fn(usize) -> bool,
+) -> bool {
+    (page * page_size..((page + 1) * page_size).min(len)).any(|index| !is_disabled(index))
+}
+
+impl DialogueState {
+    /// Page size of the presented choice group, or `default` when the group sets none.
+    ///
+    /// 当前呈现的选项组的每页大小；选项组未设置时使用 `default`。
+    pub fn choice_page_size(&self, default: Option<ChoicePagination>) -> Option<usize> {
+        let mut page_size = default.map(|pagination| pagination.page_size);
+        let content = self.parsed.choice_content_index.and_then(|index| {
+            let content = self.parsed.node_data.content.get(index)?;
+            page_size = read_page_size(content).or(page_size);
+            Some(content)
+        });
+        let mut options = content.and_then(|content| content.get("options"));
+        for &level in &self.choice_stack {
+            let option = options.and_then(|options| options.get(level));
+            page_size = option.and_then(read_page_size).or(page_size);
+            options = option.and_then(|option| option.get("choice"));
+        }
+        page_size.filter(|&size| size > 0)
+    }
+
+    /// Page currently shown for a group of `len` choices.
+    ///
+    /// 包含 `len` 个选项的选项组当前显示的页码。
+    pub fn visible_choice_page(&self, len: usize, page_size: usize) -> usize {
+        let order = self.choice_presentation_order();
+        visible_page(self.choice_page, len, page_size, |position| {
+            self.position_disabled(&order, position)
+        })
+    }
+
+    /// Whether the option presented at `position` of `order` is disabled.
+    fn position_disabled(&self, order: &[usize], position: usize) -> bool {
+        let index = order.get(position).copied().unwrap_or(position);
+        self.disabled_choices.contains(&index)
+    }
+
+    /// Turns `delta` pages, skipping pages without enabled options and stopping at the first or
+    /// last page. Returns whether the page changed.
+    ///
+    /// 翻动 `delta` 页，跳过没有可用选项的页面，并在首页或末页处停止。返回页码是否改变。
+    pub fn turn_choice_page(&mut self, delta: i32, page_size: usize) -> bool {
+        let Some(len) = self.get_choices().map(Vec::len) else {
+            return false;
+        };
+        let count = page_count(len, page_size);
+        let order = self.choice_presentation_order();
+        let is_disabled = |position| self.position_disabled(&order, position);
+        let usable = |page: usize| page_usable(page, len, page_size, is_disabled);
+        let start = self.visible_choice_page(len, page_size);
+        let mut page = start;
+        for _ in 0..delta.unsigned_abs() {
+            let next = if delta > 0 {
+                (page + 1..count).find(|&page| usable(page))
+            } else {
+                (0..page).rev().find(|&page| usable(page))
+            };
+            let Some(next) = next else {
+                break;
+            };
+            page = next;
+        }
+        self.choice_page = page;
+        page != start
+    }
+
+    /// Shows the page holding the declared choice `index`.
+    ///
+    /// 显示包含声明索引 `index` 的选项的页面。
+    pub fn show_choice_page_of(&mut self, index: usize, page_size: usize) {
+        let order = self.choice_presentation_order();
+        let position = order.iter().position(|&declared| declared == index);
+        self.choice_page = position.unwrap_or(index) / page_size;
+    }
+}
+
+fn read_page_size(value: &serde_json::Value) -> Option<usize> {
+    value
+        .get("page_size")
+        .and_then(serde_json::Value::as_u64)
+        .and_then(|size| usize::try_from(size).ok())
+}
@@ -0,0 +1,132 @@
+//! # parsed_node.rs
+//!
+//! # parsed_node.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! The part of a [`DialogueState`](super::DialogueState) that only depends on the node: its
+//! content with `switch` items inlined, the text items with their positions and line ids, and the
+//! choice group. Reading it deserializes every text, condition, statement, event and choice, so
+//! each [`crate::MortarAsset`] keeps one [`ParsedNode`] per node and every dialogue on that node
+//! shares it, see [`crate::MortarAsset::parsed_node`].
+//!
+//! [`DialogueState`](super::DialogueState) 中只取决于节点的部分：内联了 `switch` 项的内容、带位置与
+//! 行标识符的文本项，以及选项组。读取它需要反序列化每个文本、条件、语句、事件与选项，因此每个
+//! [`crate::MortarAsset`] 为每个节点保存一份 [`ParsedNode`]，该节点上的所有对话共享它，参见
+//! [`crate::MortarAsset::parsed_node`]。
+
+use mortar_compiler::{Choice, Node};
+
+use super::content_diagnostics::{ItemReader, MortarDiagnostic};
+use super::switch::{self, SwitchSpan};
+use super::{TextData, TextPosition, line_id, node_content};
+
+/// A node read into the items a dialogue walks through.
+///
+/// 读取为对话遍历项的节点。
+#[derive(Debug)]
+pub struct ParsedNode {
+    pub(super) node_data: Node,
+    pub(super) text_items: Vec<TextData>,
+    pub(super) text_positions: Vec<TextPosition>,
+    pub(super) choice_content_index: Option<usize>,
+    pub(super) choices: Option<Vec<Choice>>,
+    pub(super) switches: Vec<SwitchSpan>,
+    pub(super) diagnostics: Vec<MortarDiagnostic>,
+}
+
+impl ParsedNode {
+    /// Reads `node_data`; `node_name` names the node in warnings and line ids.
+    ///
+    /// 读取 `node_data`；`node_name` 用于警告与行标识符中的节点名称。
+    pub fn parse(node_name: &str, mut node_data: Node) -> Self {
+        #[cfg(test)]
+        parses::add();
+        let switches = switch::inline_switches(&mut node_data.content, node_name);
+        let mut text_items = Vec::new();
+        let mut text_positions = Vec::new();
+        let mut choice_content_index = None;
+        let mut choices = None;
+        let mut diagnostics = Vec::new();
+
+        for (content_index, content_value) in node_data.content.iter().enumerate() {
+            let mut reader = ItemReader {
+                node: node_name,
+                content_index,
+                diagnostics: &mut diagnostics,
+            };
+            node_content::parse_node_content(
+                &mut reader,
+                content_value,
+                &mut text_items,
+                &mut text_positions,
+                &mut choice_content_index,
+                &mut choices,
+            );
+        }
+        line_id::assign_line_ids(node_name, &mut text_items);
+
+        Self {
+            node_data,
+            text_items,
+            text_positions,
+            choice_content_index,
+            choices,
+            switches,
+            diagnostics,
+        }
+    }
+
+    /// The node's data, with its `switch` items inlined.
+    ///
+    /// 节点数据，其中的 `switch` 项已内联。
+    pub fn node_data(&self) -> &Node {
+        &self.node_data
+    }
+
+    pub fn text_items(&self) -> &[TextData] {
+        &self.text_items
+    }
+
+    /// Positions of the text items, by text index.
+    ///
+    /// 各文本项的位置，按文本索引排列。
+    pub fn text_positions(&self) -> &[TextPosition] {
+        &self.text_positions
+    }
+
+    /// The options of the node's choice group; `None` without one.
+    ///
+    /// 节点选项组的选项；没有选项组时为 `None`。
+    pub fn choices(&self) -> Option<&[Choice]> {
+        self.choices.as_deref()
+    }
+
+    /// Fields of the node's items that failed to deserialize and were read as absent.
+    ///
+    /// 节点各项中无法反序列化、因而按缺失处理的字段。
+    pub fn diagnostics(&self) -> &[MortarDiagnostic] {
+        &self.diagnostics
+    }
+}
+
+/// Counts the nodes parsed on this thread, so tests can tell a cached node from a parsed one.
+#[cfg(test)]
+pub(crate) mod parses {
+    use std::cell::Cell;
+
+    thread_local! {
+        static COUNT: Cell<usize> = const { Cell::new(0) };
+    }
+
+    pub(crate) fn add() {
+        COUNT.with(|cell| cell.set(cell.get() + 1));
+    }
+
+    /// Returns the count and resets it.
+    pub(crate) fn take() -> usize {
+        COUNT.with(|cell| cell.replace(0))
+    }
+}
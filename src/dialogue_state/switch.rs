@@ -0,0 +1,275 @@
+//! # switch.rs
+//!
+//! # switch.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! `switch` content items, which pick one group of content by the value of a variable:
+//! `{"type": "switch", "on": "_last_choice", "cases": {"a": [...], "b": [...]}, "default": [...]}`.
+//! Parsing inlines every case into the node's content, so texts and runs inside a case go through
+//! the usual machinery, and remembers where each case lies. A switch is resolved when the cursor
+//! reaches it: the variable's value picks the case with that key, else `default`, else nothing.
+//! From then on the other cases are hidden: advancing steps over their texts, they are left out
+//! of line groups, and their runs never execute. A case may hold one nested switch level; deeper
+//! switches are dropped with a warning.
+//!
+//! `switch` 内容项，根据变量的值选出一组内容：
+//! `{"type": "switch", "on": "_last_choice", "cases": {"a": [...], "b": [...]}, "default": [...]}`。
+//! 解析时会把每个分支内联到节点内容中，使分支内的文本与 run 走常规流程，并记录各分支所在的位置。
+//! 游标到达 switch 时才对其求值：变量的值选出键与之相同的分支，否则选 `default`，都没有则什么也不选。
+//! 此后其余分支会被隐藏：推进时跳过其中的文本，它们不会并入 line 组，其中的 run 也不会执行。分支内
+//! 可以再嵌套一层 switch；更深的 switch 会被丢弃并给出警告。
+
+use bevy::prelude::*;
+use serde_json::Value;
+use std::ops::Range;
+
+use super::DialogueState;
+use crate::MortarVariableState;
+use crate::debug::LOG_DIALOGUE;
+
+/// Switch levels a node may nest: a switch inside a case of another one.
+const MAX_SWITCH_DEPTH: usize = 1;
+
+/// Where one `switch` item landed in the inlined content.
+#[derive(Debug, Clone)]
+pub(super) struct SwitchSpan {
+    on: String,
+    cases: Vec<SwitchCase>,
+    content: Range<usize>,
+}
+
+/// One case of a switch; `key` is `None` for `default`.
+#[derive(Debug, Clone)]
+struct SwitchCase {
+    key: Option<String>,
+    content: Range<usize>,
+}
+
+/// The case a switch resolved to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(super) enum SwitchPick {
+    /// The cursor has not reached the switch yet.
+    #[default]
+    Pending,
+    Case(usize),
+    /// No case matched and there is no `default`.
+    Nothing,
+}
+
+/// Whether a content item takes part in the dialogue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum ContentGate {
+    Shown,
+    /// Inside a case that was not picked.
+    Hidden,
+    /// Inside a switch the cursor has not reached.
+    Pending,
+}
+
+/// Inlines the cases of every `switch` item in `content`, returning where each switch landed.
+/// Outer switches come before the switches nested in them.
+pub(super) fn inline_switches(content: &mut Vec<Value>, node: &str) -> Vec<SwitchSpan> {
+    let is_switch = |item: &Value| item.get("type").and_then(Value::as_str) == Some("switch");
+    if !content.iter().any(is_switch) {
+        return Vec::new();
+    }
+    let mut inlined = Vec::with_capacity(content.len());
+    let mut spans = Vec::new();
+    inline_into(std::mem::take(content), 0, node, &mut inlined, &mut spans);
+    *content = inlined;
+    spans
+}
+
+fn inline_into(
+    items: Vec<Value>,
+    depth: usize,
+    node: &str,
+    out: &mut Vec<Value>,
+    spans: &mut Vec<SwitchSpan>,
+) {
+    for item in items {
+        if item.get("type").and_then(Value::as_str) != Some("switch") {
+            out.push(item);
+            continue;
+        }
+        if depth > MAX_SWITCH_DEPTH {
+            warn!(
+                target: LOG_DIALOGUE,
+                "Node '{}' nests switches deeper than {} level; dropping the inner switch",
+                node,
+                MAX_SWITCH_DEPTH
+            );
+            continue;
+        }
+        let Some(on) = item.get("on").and_then(Value::as_str) else {
+            warn!(target: LOG_DIALOGUE, "Switch in node '{}' has no 'on' variable; dropping it", node);
+            continue;
+        };
+        let slot = spans.len();
+        let start = out.len();
+        spans.push(SwitchSpan {
+            on: on.to_owned(),
+            cases: Vec::new(),
+            content: start..start,
+        });
+        let keyed = item
+            .get("cases")
+            .and_then(Value::as_object)
+            .into_iter()
+            .flatten()
+            .map(|(key, case)| (Some(key.clone()), case));
+        let default = item.get("default").map(|case| (None, case));
+        let mut cases = Vec::new();
+        for (key, case) in keyed.chain(default) {
+            let case_start = out.len();
+            let case_items = case.as_array().cloned().unwrap_or_default();
+            inline_into(case_items, depth + 1, node, out, spans);
+            cases.push(SwitchCase {
+                key,
+                content: case_start..out.len(),
+            });
+        }
+        spans[slot].cases = cases;
+        spans[slot].content = start..out.len();
+    }
+}
+
+impl SwitchSpan {
+    /// The case `value` picks: the one keyed by it, else `default`.
+    fn pick(&self, value: Option<&str>) -> SwitchPick {
+        value
+            .and_then(|value| {
+                self.cases
+                    .iter()
+                    .position(|case| case.key.as_deref() == Some(value))
+            })
+            .or_else(|| self.cases.iter().position(|case| case.key.is_none()))
+            .map_or(SwitchPick::Nothing, SwitchPick::Case)
+    }
+}
+
+impl DialogueState {
+    /// Whether the content item at `content_index` takes part in the dialogue.
+    pub(super) fn content_gate(&self, content_index: usize) -> ContentGate {
+        for (span, pick) in self.parsed.switches.iter().zip(&self.switch_picks) {
+            if !span.content.contains(&content_index) {
+                continue;
+            }
+            match pick {
+                SwitchPick::Pending => return ContentGate::Pending,
+                SwitchPick::Nothing => return ContentGate::Hidden,
+                SwitchPick::Case(case) if !span.cases[*case].content.contains(&content_index) => {
+                    return ContentGate::Hidden;
+                }
+                SwitchPick::Case(_) => {}
+            }
+        }
+        ContentGate::Shown
+    }
+
+    /// The innermost switch case holding the content item at `content_index`, as (switch, case).
+    pub(super) fn switch_case_of(&self, content_index: usize) -> Option<(usize, usize)> {
+        self.parsed
+            .switches
+            .iter()
+            .enumerate()
+            .rev()
+            .find_map(|(slot, span)| {
+                let case = span
+                    .cases
+                    .iter()
+                    .position(|case| case.content.contains(&content_index))?;
+                Some((slot, case))
+            })
+    }
+
+    /// Whether the text at `index` sits in a switch case that was not picked.
+    ///
+    /// `index` 处的文本是否位于未被选中的 switch 分支中。
+    pub fn is_text_hidden(&self, index: usize) -> bool {
+        self.parsed
+            .text_positions
+            .get(index)
+            .is_some_and(|position| {
+                self.content_gate(position.content_index) == ContentGate::Hidden
+            })
+    }
+
+    /// The first text from `from` on that is not hidden, or the number of texts.
+    pub(super) fn next_shown(&self, from: usize) -> usize {
+        (from..self.parsed.text_items.len())
+            .find(|&index| !self.is_text_hidden(index))
+            .unwrap_or(self.parsed.text_items.len())
+    }
+
+    /// The first switch holding the current line that is still unresolved.
+    fn pending_switch(&self) -> Option<usize> {
+        let content = self.current_text_position()?.content_index;
+        self.parsed
+            .switches
+            .iter()
+            .zip(&self.switch_picks)
+            .position(|(span, pick)| {
+                *pick == SwitchPick::Pending && span.content.contains(&content)
+            })
+    }
+
+    /// Whether the cursor reached a switch that waits for [`Self::resolve_switches`].
+    ///
+    /// 游标是否已到达等待 [`Self::resolve_switches`] 求值的 switch。
+    pub fn awaits_switch(&self) -> bool {
+        self.pending_switch().is_some()
+    }
+
+    /// Resolves the switches the cursor reached against `variables` and moves the cursor to the
+    /// first text of the picked case. When nothing follows, the cursor stays on a hidden line,
+    /// which the text system skips. Returns whether a switch was resolved.
+    ///
+    /// 根据 `variables` 对游标已到达的 switch 求值，并将游标移到所选分支的第一条文本。之后没有
+    /// 任何内容时，游标停留在一条隐藏的行上，由文本系统跳过。返回是否有 switch 被求值。
+    pub fn resolve_switches(&mut self, variables: &MortarVariableState) -> bool {
+        let mut resolved = false;
+        while let Some(slot) = self.pending_switch() {
+            let span = &self.parsed.switches[slot];
+            let value = variables
+                .get(&span.on)
+                .map(crate::MortarVariableValue::to_display_string);
+            let pick = span.pick(value.as_deref());
+            match pick {
+                SwitchPick::Case(case) => {
+                    debug!(
+                        target: LOG_DIALOGUE,
+                        "Switch on '{}' in node '{}' picked {:?}",
+                        span.on,
+                        self.current_node,
+                        span.cases[case].key.as_deref().unwrap_or("default")
+                    );
+                    // Runs leading the case execute before its first text.
+                    //
+                    // 位于分支开头的 run 会在其第一条文本之前执行。
+                    let start = span.cases[case].content.start;
+                    self.pending_run_position.get_or_insert(start);
+                }
+                _ => {
+                    debug!(
+                        target: LOG_DIALOGUE,
+                        "Switch on '{}' in node '{}' matches no case for {:?}; skipping it",
+                        span.on,
+                        self.current_node,
+                        value
+                    );
+                }
+            }
+            self.switch_picks[slot] = pick;
+            resolved = true;
+            let next = self.next_shown(self.text_index);
+            if next < self.parsed.text_items.len() {
+                self.text_index = next;
+            }
+        }
+        resolved
+    }
+}
@@ -0,0 +1,115 @@
+//! # ui_hints.rs
+//!
+//! # ui_hints.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Presentation hints that scripts attach to choices for the UI. A choice group (the `choice`
+//! content item or, for nested groups, the option that opens them) and each option may carry a
+//! `ui` object, which is passed through verbatim. [`MortarUiHints`] reads a few well-known keys
+//! from it. The runtime itself only acts on `order`: options are stably sorted by it before
+//! pagination splits them into pages, so a page is a run of presented positions rather than of
+//! declared indices. Options without an `order` sort as `0`.
+//!
+//! 脚本为 UI 附加在选项上的呈现提示。选项组（`choice` 内容项，或对嵌套组而言打开该组的选项）以及
+//! 每个选项都可以携带一个 `ui` 对象，它会被原样传递。[`MortarUiHints`] 从中读取少数约定的键。运行时
+//! 本身只使用 `order`：选项在分页之前按它进行稳定排序，因此每页是一段连续的呈现位置，而非声明索引。
+//! 未设置 `order` 的选项按 `0` 排序。
+
+use serde_json::{Map, Value};
+
+use super::DialogueState;
+
+/// Typed view of a `ui` hint object.
+///
+/// `ui` 提示对象的类型化视图。
+#[derive(Debug, Clone, Copy)]
+pub struct MortarUiHints<'a>(pub &'a Map<String, Value>);
+
+impl MortarUiHints<'_> {
+    /// Number of columns to lay the group's options out in.
+    ///
+    /// 选项组中选项排布的列数。
+    pub fn columns(&self) -> Option<u8> {
+        self.0
+            .get("columns")
+            .and_then(Value::as_u64)
+            .and_then(|columns| u8::try_from(columns).ok())
+    }
+
+    /// Heading to group the option, or the group, under.
+    ///
+    /// 选项或选项组所归属的标题。
+    pub fn heading(&self) -> Option<String> {
+        self.0
+            .get("heading")
+            .and_then(Value::as_str)
+            .map(str::to_owned)
+    }
+
+    /// Sort key of the option among its group.
+    ///
+    /// 选项在其所在组中的排序键。
+    pub fn order(&self) -> Option<i32> {
+        self.0
+            .get("order")
+            .and_then(Value::as_i64)
+            .and_then(|order| i32::try_from(order).ok())
+    }
+}
+
+/// The `ui` object of a group or option, empty when it declares none.
+pub(crate) fn ui_hints(value: &Value) -> Map<String, Value> {
+    value
+        .get("ui")
+        .and_then(Value::as_object)
+        .cloned()
+        .unwrap_or_default()
+}
+
+/// Sort key of an option; options without an `order` hint sort as `0`.
+fn option_order(option: &Value) -> i32 {
+    option
+        .get("ui")
+        .and_then(Value::as_object)
+        .and_then(|ui| MortarUiHints(ui).order())
+        .unwrap_or(0)
+}
+
+impl DialogueState {
+    /// The `ui` hints of the presented choice group.
+    ///
+    /// 当前呈现的选项组的 `ui` 提示。
+    pub fn choice_group_ui(&self) -> Map<String, Value> {
+        let Some(content) = self
+            .parsed
+            .choice_content_index
+            .and_then(|index| self.parsed.node_data.content.get(index))
+        else {
+            return Map::new();
+        };
+        let mut group = content;
+        let mut options = content.get("options");
+        for &level in &self.choice_stack {
+            let Some(option) = options.and_then(|options| options.get(level)) else {
+                return Map::new();
+            };
+            group = option;
+            options = option.get("choice");
+        }
+        ui_hints(group)
+    }
+
+    /// Declared indices of the presented group's options in presentation order.
+    ///
+    /// 当前呈现的选项组中各选项的声明索引，按呈现顺序排列。
+    pub fn choice_presentation_order(&self) -> Vec<usize> {
+        let options = self.current_option_values().map_or(&[][..], Vec::as_slice);
+        let len = self.get_choices().map_or(options.len(), Vec::len);
+        let mut order: Vec<usize> = (0..len).collect();
+        order.sort_by_key(|&index| options.get(index).map_or(0, option_order));
+        order
+    }
+}
@@ -14,10 +14,16 @@
 //! 它是把解析后的 Mortar AST 片段转换成具体布尔值、具体值和最终对话字符串的语义桥接层。
 
 use bevy::prelude::*;
+use std::hash::{DefaultHasher, Hash, Hasher};
 
-use crate::binder::{MortarBoolean, MortarFunctionRegistry, MortarNumber, MortarString};
+use crate::MortarAsset;
+use crate::binder::{
+    MortarBoolean, MortarCallOrigin, MortarFunctionRegistry, MortarNumber, MortarString,
+    with_current_context,
+};
+use crate::debug::LOG_EVAL;
 use crate::variable_state::{MortarVariableState, MortarVariableValue};
-use crate::{MortarValue, TextData};
+use crate::{MortarConditionCache, MortarValue, TextData};
 
 /// Gets default return value based on type.
 ///
@@ -33,17 +39,42 @@ fn get_default_return_value(return_type: &str) -> String {
 
 /// Evaluates an IfCondition with support for function calls.
 ///
+/// This is the path for text and line conditions; choice conditions use [`evaluate_condition`].
+///
 /// 评估 IfCondition，支持函数调用。
+///
+/// 文本与 line 条件走此路径；选项条件使用 [`evaluate_condition`]。
 pub fn evaluate_if_condition(
     condition: &mortar_compiler::IfCondition,
     functions: &MortarFunctionRegistry,
     variable_state: &MortarVariableState,
+) -> bool {
+    if_condition(condition, functions, variable_state, None)
+}
+
+/// [`evaluate_if_condition`] with the function calls memoized in `cache`.
+///
+/// 把函数调用结果缓存在 `cache` 中的 [`evaluate_if_condition`]。
+pub fn evaluate_if_condition_cached(
+    condition: &mortar_compiler::IfCondition,
+    functions: &MortarFunctionRegistry,
+    variable_state: &MortarVariableState,
+    cache: &MortarConditionCache,
+) -> bool {
+    if_condition(condition, functions, variable_state, Some(cache))
+}
+
+fn if_condition(
+    condition: &mortar_compiler::IfCondition,
+    functions: &MortarFunctionRegistry,
+    variable_state: &MortarVariableState,
+    cache: Option<&MortarConditionCache>,
 ) -> bool {
     match condition.cond_type.as_str() {
         "func_call" => {
             let func_name = condition.operand.as_ref().and_then(|op| op.value.clone());
             let Some(func_name) = func_name else {
-                warn!("Function call condition missing function_name");
+                warn!(target: LOG_EVAL, "Function call condition missing function_name");
                 return false;
             };
             let args: Vec<MortarValue> = condition
@@ -53,13 +84,14 @@ pub fn evaluate_if_condition(
                 .map(|v| v.split_whitespace().map(MortarValue::parse).collect())
                 .unwrap_or_default();
 
-            if let Some(value) = functions.call(&func_name, &args) {
+            if let Some(value) = call_condition(functions, &func_name, &args, variable_state, cache)
+            {
                 value.is_truthy()
             } else {
-                warn!(
+                functions.warn_unbound(format_args!(
                     "Condition function '{}' not bound, defaulting to false",
                     func_name
-                );
+                ));
                 false
             }
         }
@@ -69,12 +101,12 @@ pub fn evaluate_if_condition(
 
             match condition.operator.as_deref() {
                 Some("&&") => {
-                    evaluate_if_condition(left, functions, variable_state)
-                        && evaluate_if_condition(right, functions, variable_state)
+                    if_condition(left, functions, variable_state, cache)
+                        && if_condition(right, functions, variable_state, cache)
                 }
                 Some("||") => {
-                    evaluate_if_condition(left, functions, variable_state)
-                        || evaluate_if_condition(right, functions, variable_state)
+                    if_condition(left, functions, variable_state, cache)
+                        || if_condition(right, functions, variable_state, cache)
                 }
                 _ => {
                     // For comparison operators, check if either operand is a func_call.
@@ -83,8 +115,10 @@ pub fn evaluate_if_condition(
                     // 对比较运算符，检查是否有 func_call 操作数。
                     // variable_state 无法解析 func_call，需在此处处理。
                     if left.cond_type == "func_call" || right.cond_type == "func_call" {
-                        let left_val = resolve_condition_value(left, functions, variable_state);
-                        let right_val = resolve_condition_value(right, functions, variable_state);
+                        let left_val =
+                            resolve_condition_value(left, functions, variable_state, cache);
+                        let right_val =
+                            resolve_condition_value(right, functions, variable_state, cache);
                         compare_mortar_values(&left_val, &right_val, condition.operator.as_deref())
                     } else {
                         variable_state.evaluate_condition(condition)
@@ -93,15 +127,16 @@ pub fn evaluate_if_condition(
             }
         }
         "unary" => {
-            let operand_result = evaluate_if_condition(
+            let operand_result = if_condition(
                 condition.operand.as_ref().unwrap().as_ref(),
                 functions,
                 variable_state,
+                cache,
             );
             match condition.operator.as_deref() {
                 Some("!") => !operand_result,
                 _ => {
-                    warn!("Unknown unary operator: {:?}", condition.operator);
+                    warn!(target: LOG_EVAL, "Unknown unary operator: {:?}", condition.operator);
                     false
                 }
             }
@@ -124,6 +159,7 @@ fn resolve_condition_value(
     cond: &mortar_compiler::IfCondition,
     functions: &MortarFunctionRegistry,
     variable_state: &MortarVariableState,
+    cache: Option<&MortarConditionCache>,
 ) -> MortarValue {
     match cond.cond_type.as_str() {
         "func_call" => {
@@ -135,7 +171,7 @@ fn resolve_condition_value(
                 .map(|v| v.split_whitespace().map(MortarValue::parse).collect())
                 .unwrap_or_default();
             func_name
-                .and_then(|name| functions.call(&name, &args))
+                .and_then(|name| call_condition(functions, &name, &args, variable_state, cache))
                 .unwrap_or(MortarValue::Void)
         }
         "identifier" => {
@@ -146,14 +182,10 @@ fn resolve_condition_value(
             if let Ok(n) = name.parse::<f64>() {
                 return MortarValue::Number(MortarNumber(n));
             }
-            match variable_state.get(name) {
-                Some(MortarVariableValue::Number(n)) => MortarValue::Number(MortarNumber(*n)),
-                Some(MortarVariableValue::String(s)) => {
-                    MortarValue::String(MortarString(s.clone()))
-                }
-                Some(MortarVariableValue::Boolean(b)) => MortarValue::Boolean(MortarBoolean(*b)),
-                None => MortarValue::Void,
-            }
+            variable_state
+                .get(name)
+                .map(variable_to_mortar_value)
+                .unwrap_or(MortarValue::Void)
         }
         "literal" => {
             let val = cond.value.as_deref().unwrap_or("0");
@@ -167,6 +199,29 @@ fn resolve_condition_value(
     }
 }
 
+/// Calls the condition function `name`, through `cache` unless the function is volatile. The key
+/// covers the file and node calling, whose scoped functions and overrides may differ, the
+/// variable revision and the registry generation.
+fn call_condition(
+    functions: &MortarFunctionRegistry,
+    name: &str,
+    args: &[MortarValue],
+    variable_state: &MortarVariableState,
+    cache: Option<&MortarConditionCache>,
+) -> Option<MortarValue> {
+    let call = || functions.call_from(MortarCallOrigin::Condition, name, args);
+    let Some(cache) = cache.filter(|_| !functions.is_volatile(name)) else {
+        return call();
+    };
+    let mut hasher = DefaultHasher::new();
+    (name, variable_state.revision(), functions.generation()).hash(&mut hasher);
+    for arg in args {
+        format!("{arg:?}").hash(&mut hasher);
+    }
+    with_current_context(|context| (&context.path, &context.node).hash(&mut hasher));
+    cache.memoize(hasher.finish(), call)
+}
+
 /// Compares two MortarValues with a given operator.
 ///
 /// 使用给定运算符比较两个 MortarValue。
@@ -196,14 +251,64 @@ fn compare_numbers(left: &MortarValue, right: &MortarValue, cmp: fn(f64, f64) ->
     }
 }
 
-/// Evaluates a condition by calling the bound function.
+/// Evaluates a choice condition ([`mortar_compiler::Condition`], the `when` clause of a choice).
+///
+/// Text and line conditions use [`mortar_compiler::IfCondition`] and go through
+/// [`evaluate_if_condition`]; choice conditions arrive as a flat `type` + `args` pair. When
+/// `condition_type` names a variable in `variable_state`, the condition is resolved against it:
+/// a bare variable is tested for truthiness, `[op, rhs]` args compare the variable with `rhs`,
+/// and a single arg is an equality check. Expressions such as `"gold >= 10"` stored in
+/// `condition_type` are split on whitespace the same way. Only when no variable matches is the
+/// bound function called.
 ///
-/// 通过调用绑定函数来评估条件。
+/// 评估选项条件（[`mortar_compiler::Condition`]，即选项的 `when` 子句）。
+///
+/// 文本与 line 的条件使用 [`mortar_compiler::IfCondition`]，由 [`evaluate_if_condition`] 处理；
+/// 选项条件则是扁平的 `type` + `args`。当 `condition_type` 是 `variable_state` 中的变量名时，
+/// 条件会基于该变量求值：单独的变量按真值判断，`[运算符, 右值]` 参数会与右值比较，
+/// 单个参数则视为相等判断。存放在 `condition_type` 中的 `"gold >= 10"` 这类表达式也会按空白拆分处理。
+/// 只有在没有匹配的变量时才会调用绑定函数。
 pub fn evaluate_condition(
+    condition: &mortar_compiler::Condition,
+    functions: &MortarFunctionRegistry,
+    function_decls: &[mortar_compiler::Function],
+    variable_state: &MortarVariableState,
+) -> bool {
+    choice_condition(condition, functions, function_decls, variable_state, None)
+}
+
+/// [`evaluate_condition`] with the function call memoized in `cache`. Named apart from
+/// [`crate::evaluate_condition_cached`], which keeps `if` / `else` branches exclusive.
+///
+/// 把函数调用结果缓存在 `cache` 中的 [`evaluate_condition`]。其名称有别于用于保证 `if` / `else`
+/// 分支互斥的 [`crate::evaluate_condition_cached`]。
+pub fn evaluate_choice_condition_cached(
+    condition: &mortar_compiler::Condition,
+    functions: &MortarFunctionRegistry,
+    function_decls: &[mortar_compiler::Function],
+    variable_state: &MortarVariableState,
+    cache: &MortarConditionCache,
+) -> bool {
+    choice_condition(
+        condition,
+        functions,
+        function_decls,
+        variable_state,
+        Some(cache),
+    )
+}
+
+fn choice_condition(
     condition: &mortar_compiler::Condition,
     functions: &MortarFunctionRegistry,
     _function_decls: &[mortar_compiler::Function],
+    variable_state: &MortarVariableState,
+    cache: Option<&MortarConditionCache>,
 ) -> bool {
+    if let Some(result) = evaluate_variable_condition(condition, variable_state) {
+        return result;
+    }
+
     // Parse arguments.
     //
     // 解析参数。
@@ -216,20 +321,98 @@ pub fn evaluate_condition(
     // Call the function.
     //
     // 调用函数。
-    if let Some(value) = functions.call(&condition.condition_type, &args) {
+    if let Some(value) = call_condition(
+        functions,
+        &condition.condition_type,
+        &args,
+        variable_state,
+        cache,
+    ) {
         value.is_truthy()
     } else {
         // Function not found - default to false.
         //
         // 未找到函数时默认返回 false。
-        warn!(
+        functions.warn_unbound(format_args!(
             "Condition function '{}' not bound, defaulting to false",
             condition.condition_type
-        );
+        ));
         false
     }
 }
 
+/// Resolves a choice condition against variables. Returns `None` when the condition does not
+/// reference a known variable, so the caller can fall back to the function registry.
+///
+/// 基于变量解析选项条件。若条件未引用已知变量则返回 `None`，由调用方回退到函数注册表。
+pub(crate) fn evaluate_variable_condition(
+    condition: &mortar_compiler::Condition,
+    variable_state: &MortarVariableState,
+) -> Option<bool> {
+    let tokens: Vec<&str> = condition
+        .condition_type
+        .split_whitespace()
+        .chain(condition.args.iter().map(String::as_str))
+        .collect();
+    let (name, rest) = tokens.split_first()?;
+    let left = variable_to_mortar_value(variable_state.get(name)?);
+
+    match rest {
+        [] => Some(left.is_truthy()),
+        [rhs] => Some(compare_mortar_values(
+            &left,
+            &resolve_token_value(rhs, variable_state),
+            Some("=="),
+        )),
+        [op, rhs] if matches!(*op, "==" | "!=" | ">" | "<" | ">=" | "<=") => Some(
+            compare_mortar_values(&left, &resolve_token_value(rhs, variable_state), Some(op)),
+        ),
+        _ => None,
+    }
+}
+
+/// Resolves a condition token as a variable reference, falling back to a literal.
+///
+/// 将条件片段解析为变量引用，失败时按字面量处理。
+fn resolve_token_value(token: &str, variable_state: &MortarVariableState) -> MortarValue {
+    variable_state
+        .get(token)
+        .map(variable_to_mortar_value)
+        .unwrap_or_else(|| MortarValue::parse(token))
+}
+
+fn variable_to_mortar_value(value: &MortarVariableValue) -> MortarValue {
+    match value {
+        MortarVariableValue::Number(n) => MortarValue::Number(MortarNumber(*n)),
+        MortarVariableValue::String(s) => MortarValue::String(MortarString(s.clone())),
+        MortarVariableValue::Boolean(b) => MortarValue::Boolean(MortarBoolean(*b)),
+    }
+}
+
+/// Where interpolation finds the declared return type of a function that is not bound.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum FunctionDecls<'a> {
+    /// A declaration list, searched in order.
+    List(&'a [mortar_compiler::Function]),
+    /// The declarations of an asset, through its lookup table.
+    Asset(&'a MortarAsset),
+}
+
+impl Default for FunctionDecls<'_> {
+    fn default() -> Self {
+        Self::List(&[])
+    }
+}
+
+impl<'a> FunctionDecls<'a> {
+    fn get(self, name: &str) -> Option<&'a mortar_compiler::Function> {
+        match self {
+            Self::List(decls) => decls.iter().find(|f| f.name == name),
+            Self::Asset(asset) => asset.function_decl(name),
+        }
+    }
+}
+
 /// Processes interpolated text by calling bound functions and resolving variables.
 ///
 /// 通过调用绑定函数和解析变量来处理插值文本。
@@ -238,6 +421,38 @@ pub fn process_interpolated_text(
     functions: &MortarFunctionRegistry,
     function_decls: &[mortar_compiler::Function],
     variable_state: &MortarVariableState,
+) -> String {
+    interpolate(
+        text_data,
+        functions,
+        FunctionDecls::List(function_decls),
+        variable_state,
+    )
+}
+
+pub(crate) fn interpolate(
+    text_data: &TextData,
+    functions: &MortarFunctionRegistry,
+    function_decls: FunctionDecls,
+    variable_state: &MortarVariableState,
+) -> String {
+    interpolate_with(
+        text_data,
+        functions,
+        function_decls,
+        variable_state,
+        &mut |_, _, _| {},
+    )
+}
+
+/// Like [`interpolate`], also handing each expression and placeholder with the text it rendered
+/// to, and the byte offset of that text in the result, to `rendered`.
+pub(crate) fn interpolate_with(
+    text_data: &TextData,
+    functions: &MortarFunctionRegistry,
+    function_decls: FunctionDecls,
+    variable_state: &MortarVariableState,
+    rendered: &mut dyn FnMut(&str, &str, usize),
 ) -> String {
     // If there are no interpolated parts, return the original text.
     //
@@ -248,6 +463,7 @@ pub fn process_interpolated_text(
 
     let mut result = String::new();
     for part in parts {
+        let start = result.len();
         match part.part_type.as_str() {
             "text" => {
                 result.push_str(&part.content);
@@ -263,20 +479,21 @@ pub fn process_interpolated_text(
                     .map(|arg| MortarValue::parse(arg))
                     .collect();
 
-                if let Some(value) = functions.call(func_name, &args) {
+                if let Some(value) =
+                    functions.call_from(MortarCallOrigin::Interpolation, func_name, &args)
+                {
                     result.push_str(&value.to_display_string());
                 } else {
                     let return_type = function_decls
-                        .iter()
-                        .find(|f| f.name == *func_name)
+                        .get(func_name)
                         .and_then(|f| f.return_type.as_deref())
                         .unwrap_or("void");
 
                     let default_value = get_default_return_value(return_type);
-                    warn!(
+                    functions.warn_unbound(format_args!(
                         "Function '{}' not bound, using default return value: {}",
                         func_name, default_value
-                    );
+                    ));
                     result.push_str(&default_value);
                 }
             }
@@ -296,11 +513,27 @@ pub fn process_interpolated_text(
                     //
                     // 尝试作为分支变量获取。
                     result.push_str(&branch_text);
+                } else if let Some(value) = crate::runtime::capability_placeholder(var_name) {
+                    // `{cap:key}` shows a capability of the player's setup.
+                    //
+                    // `{cap:key}` 显示玩家设备情况的某项能力。
+                    result.push_str(&value);
+                } else if crate::dialogue::is_icon_token(var_name)
+                    || crate::dialogue::is_pause_token(var_name)
+                {
+                    // Inline icons and reveal checkpoints are resolved by the dialogue layer.
+                    //
+                    // 行内图标与显示检查点由对话层处理。
+                    result.push_str(&part.content);
                 } else {
                     // Variable not found, keep placeholder.
                     //
                     // 未找到变量时保留占位符。
-                    warn!("Variable '{}' not found, keeping placeholder", var_name);
+                    warn!(
+                        target: LOG_EVAL,
+                        "Variable '{}' not found, keeping placeholder",
+                        var_name
+                    );
                     result.push_str(&part.content);
                 }
             }
@@ -311,6 +544,15 @@ pub fn process_interpolated_text(
                 result.push_str(&part.content);
             }
         }
+        if part.part_type != "text" {
+            verbose_trace!(
+                Interpolation,
+                "Interpolated {} -> {:?}",
+                part.content,
+                &result[start..]
+            );
+            rendered(&part.content, &result[start..], start);
+        }
     }
 
     result
@@ -15,14 +15,30 @@
 
 use bevy::prelude::*;
 
-/// The event system for Mortar.
-/// Events without a target entity operate on the primary dialogue.
+use crate::debug::LOG_EVENTS;
+
+/// The input message that drives Mortar dialogues; it was named `MortarEvent` before.
+/// Commands without a target entity operate on the primary dialogue.
+///
+/// 驱动 Mortar 对话的输入消息，旧名为 `MortarEvent`。
+/// 未指定目标实体的命令作用于主对话。
 #[derive(Message, Debug, Clone)]
-pub enum MortarEvent {
+pub enum MortarCommand {
     StartNode {
         path: String,
         node: String,
         target: Option<Entity>,
+        /// Starts part-way through the node instead of at its first line.
+        ///
+        /// 从节点中途而不是第一行开始。
+        entry: Option<crate::MortarNodeEntry>,
+    },
+    /// Parses and checks a node ahead of time so a later `StartNode` activates without parsing.
+    ///
+    /// 提前解析并检查节点，使之后的 `StartNode` 无需解析即可激活。
+    PrepareNode {
+        path: String,
+        node: String,
     },
     NextText {
         target: Option<Entity>,
@@ -30,21 +46,97 @@ pub enum MortarEvent {
     SelectChoice {
         index: usize,
         target: Option<Entity>,
+        /// Group token from [`crate::MortarChoicesPresented`]; the selection is dropped when
+        /// another group is presented by then. `None` always applies to the current group.
+        ///
+        /// 来自 [`crate::MortarChoicesPresented`] 的组令牌；若届时已呈现其他选项组，则丢弃此选择。
+        /// 为 `None` 时总是作用于当前组。
+        group: Option<u64>,
+        /// Input the selection came from, read under [`crate::ChoiceConfirmMode::PerSource`].
+        ///
+        /// 选择所来自的输入，在 [`crate::ChoiceConfirmMode::PerSource`] 下读取。
+        source: Option<crate::ChoiceInputSource>,
     },
     ConfirmChoice {
         target: Option<Entity>,
+        /// Group token, checked like [`MortarCommand::SelectChoice::group`].
+        ///
+        /// 组令牌，检查方式与 [`MortarCommand::SelectChoice::group`] 相同。
+        group: Option<u64>,
+    },
+    /// Turns the page of paginated choices by `delta`; the selection is left untouched.
+    ///
+    /// 将分页选项翻动 `delta` 页；不会改变已选中的选项。
+    ChoicePage {
+        delta: i32,
+        target: Option<Entity>,
     },
     StopDialogue {
         target: Option<Entity>,
     },
+    /// Jumps the active dialogue to `node` of the file it is playing, as a choice leading there
+    /// would: variables and bound functions carry over and no `MortarDialogueStarted` is sent.
+    /// Ignored with a warning when no dialogue is active or the file has no such node.
+    ///
+    /// 像通往 `node` 的选项一样，将活跃对话跳转到其正在播放的文件中的 `node`：变量与绑定函数都会
+    /// 保留，也不会发送 `MortarDialogueStarted`。没有活跃对话或文件中没有该节点时，会记录警告并忽略。
+    JumpToNode {
+        node: String,
+        target: Option<Entity>,
+    },
+    /// Releases every run waiting for the signal `name`, see [`crate::MortarRuntime::signal`].
+    ///
+    /// 释放所有等待信号 `name` 的 run，参见 [`crate::MortarRuntime::signal`]。
+    Signal {
+        name: String,
+    },
+    /// Moves the current line of every text target to `position`, handled by the dialogue plugin.
+    ///
+    /// 将所有文本目标的当前行移动到 `position`，由对话插件处理。
+    SeekLine {
+        position: crate::LinePosition,
+    },
+    /// Continues a reveal holding at a checkpoint, like `NextText` does; ignored otherwise.
+    ///
+    /// 继续显示停在检查点的行，与 `NextText` 的效果相同；其他情况下被忽略。
+    ContinueReveal,
+    /// Advances past a line that shows nothing. Written by the dialogue plugin, and counted
+    /// against [`crate::MortarRuntime::auto_advance_budget`].
+    ///
+    /// 越过不显示任何内容的行。由对话插件写入，并计入 [`crate::MortarRuntime::auto_advance_budget`]。
+    SkipLine {
+        target: Option<Entity>,
+    },
+    /// Lifts the halt of a dialogue stopped by [`crate::MortarErrorEvent`] and takes the step it
+    /// refused; ignored for dialogues that are not halted.
+    ///
+    /// 解除因 [`crate::MortarErrorEvent`] 而停止的对话的暂停，并执行先前被拒绝的步进；对未暂停的
+    /// 对话无效。
+    ResumeAfterError {
+        target: Option<Entity>,
+    },
 }
 
-impl MortarEvent {
+impl MortarCommand {
     pub fn start_node(path: impl Into<String>, node: impl Into<String>) -> Self {
         Self::StartNode {
             path: path.into(),
             node: node.into(),
             target: None,
+            entry: None,
+        }
+    }
+
+    pub fn start_node_at(
+        path: impl Into<String>,
+        node: impl Into<String>,
+        entry: crate::MortarNodeEntry,
+    ) -> Self {
+        Self::StartNode {
+            path: path.into(),
+            node: node.into(),
+            target: None,
+            entry: Some(entry),
         }
     }
 
@@ -57,6 +149,14 @@ impl MortarEvent {
             path: path.into(),
             node: node.into(),
             target: Some(entity),
+            entry: None,
+        }
+    }
+
+    pub fn prepare_node(path: impl Into<String>, node: impl Into<String>) -> Self {
+        Self::PrepareNode {
+            path: path.into(),
+            node: node.into(),
         }
     }
 
@@ -70,6 +170,100 @@ impl MortarEvent {
         }
     }
 
+    /// Selects option `index` of whatever group is current when the event is handled.
+    ///
+    /// 选中处理该事件时当前选项组中的第 `index` 个选项。
+    pub fn select_choice(index: usize) -> Self {
+        Self::SelectChoice {
+            index,
+            target: None,
+            group: None,
+            source: None,
+        }
+    }
+
+    /// Selects option `index` of the group with token `group`, if it is still presented.
+    ///
+    /// 若令牌为 `group` 的选项组仍在呈现，则选中其中第 `index` 个选项。
+    pub fn select_choice_in(group: u64, index: usize) -> Self {
+        Self::SelectChoice {
+            index,
+            target: None,
+            group: Some(group),
+            source: None,
+        }
+    }
+
+    /// Marks a `SelectChoice` as coming from `source`; other events are returned unchanged.
+    ///
+    /// 将 `SelectChoice` 标记为来自 `source`；其他事件原样返回。
+    pub fn from_source(mut self, source: crate::ChoiceInputSource) -> Self {
+        if let Self::SelectChoice { source: slot, .. } = &mut self {
+            *slot = Some(source);
+        }
+        self
+    }
+
+    pub fn confirm_choice() -> Self {
+        Self::ConfirmChoice {
+            target: None,
+            group: None,
+        }
+    }
+
+    /// Confirms the selection of the group with token `group`, if it is still presented.
+    ///
+    /// 若令牌为 `group` 的选项组仍在呈现，则确认其中的选择。
+    pub fn confirm_choice_in(group: u64) -> Self {
+        Self::ConfirmChoice {
+            target: None,
+            group: Some(group),
+        }
+    }
+
+    pub fn choice_page(delta: i32) -> Self {
+        Self::ChoicePage {
+            delta,
+            target: None,
+        }
+    }
+
+    pub fn continue_reveal() -> Self {
+        Self::ContinueReveal
+    }
+
+    pub fn signal(name: impl Into<String>) -> Self {
+        Self::Signal { name: name.into() }
+    }
+
+    pub fn skip_line() -> Self {
+        Self::SkipLine { target: None }
+    }
+
+    pub fn resume_after_error() -> Self {
+        Self::ResumeAfterError { target: None }
+    }
+
+    pub fn resume_after_error_for(entity: Entity) -> Self {
+        Self::ResumeAfterError {
+            target: Some(entity),
+        }
+    }
+
+    pub fn jump_to_node(node: impl Into<String>) -> Self {
+        Self::JumpToNode {
+            node: node.into(),
+            target: None,
+        }
+    }
+
+    pub fn jump_to_node_for(entity: Entity, node: impl Into<String>) -> Self {
+        Self::JumpToNode {
+            node: node.into(),
+            target: Some(entity),
+        }
+    }
+
     pub fn stop_dialogue() -> Self {
         Self::StopDialogue { target: None }
     }
@@ -81,28 +275,219 @@ impl MortarEvent {
     }
 }
 
+/// Event emitted when a dialogue starts on a controller that had no active dialogue.
+/// Jumps between nodes of a running dialogue do not emit it.
+///
+/// 当某个控制器在没有活跃对话时开始新对话时发出。运行中对话的节点跳转不会触发此事件。
+#[derive(Message, Debug, Clone)]
+pub struct MortarDialogueStarted {
+    pub entity: Option<Entity>,
+    pub mortar_path: String,
+    pub node: String,
+    /// Header of the started file, e.g. its title for a "Chapter 3 — The Harbor" banner.
+    ///
+    /// 所启动文件的头部信息，例如用于显示章节标题。
+    pub metadata: crate::MortarMetadata,
+}
+
+/// Why a start or jump request could not activate its node.
+///
+/// 开始或跳转请求无法激活其节点的原因。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MortarStartFailure {
+    /// The file declares no nodes at all.
+    ///
+    /// 文件没有声明任何节点。
+    NoNodes,
+    /// The file has no node with the requested name.
+    ///
+    /// 文件中没有所请求名称的节点。
+    NodeNotFound,
+    /// The file could not be read or compiled.
+    ///
+    /// 文件无法读取或编译。
+    LoadFailed,
+    /// Under [`crate::MortarStrictStart`], the latest analysis of the file found validation errors.
+    ///
+    /// 在 [`crate::MortarStrictStart`] 下，文件最近一次分析发现了校验错误。
+    InvalidAsset,
+}
+
+/// Event emitted when a file fails to load or cannot serve a start or jump request. The
+/// request is dropped instead of waiting.
+///
+/// 当文件加载失败或无法处理开始或跳转请求时发出。该请求会被丢弃，而不是继续等待。
+#[derive(Message, Debug, Clone, PartialEq, Eq)]
+pub struct MortarStartFailed {
+    pub entity: Option<Entity>,
+    pub mortar_path: String,
+    pub node: String,
+    pub reason: MortarStartFailure,
+}
+
+/// Changes in the life of the app and its Mortar files. Pausing in the background is reported
+/// with the `window` feature; reloads of registered files always are.
+///
+/// 应用及其 Mortar 文件生命周期中的变化。后台暂停在启用 `window` 特性时报告；已注册文件的重载总会报告。
+#[derive(Message, Debug, Clone, PartialEq, Eq)]
+pub enum MortarLifecycleEvent {
+    /// Dialogue paused because the app went into the background.
+    ///
+    /// 对话因应用进入后台而暂停。
+    BackgroundPaused,
+    /// Dialogue resumed after the app came back.
+    ///
+    /// 应用返回后对话恢复。
+    BackgroundResumed,
+    /// A registered file failed to load. When an earlier version had loaded, lookups keep using
+    /// it, see [`crate::MortarLastGood`]. `error` carries the compiler's errors as
+    /// `file:line:column: message`.
+    ///
+    /// 已注册的文件加载失败。若早先的版本已加载，查询会继续使用该版本，参见
+    /// [`crate::MortarLastGood`]。`error` 以 `file:line:column: message` 的形式携带编译器的错误。
+    ReloadFailed { path: String, error: String },
+    /// A new version of a registered file replaced the one in use.
+    ///
+    /// 已注册文件的新版本替换了正在使用的版本。
+    ReloadSucceeded { path: String },
+}
+
+/// Event emitted whenever a node becomes active, whether by a start or a jump.
+///
+/// 每当节点被激活（无论是开始还是跳转）时发出。
+#[derive(Message, Debug, Clone, PartialEq, Eq)]
+pub struct MortarNodeEntered {
+    pub entity: Option<Entity>,
+    pub mortar_path: String,
+    pub node: String,
+    /// Text index the node was entered at.
+    ///
+    /// 进入节点时的文本索引。
+    pub entry_index: usize,
+    /// Cursor of the entry line; a new visit, so it differs from every earlier cursor.
+    ///
+    /// 进入行的游标；属于一次新的访问，因此与之前的任何游标都不相同。
+    pub cursor: crate::LineCursor,
+    /// Tags of the entered node.
+    ///
+    /// 所进入节点的标签。
+    pub tags: Vec<String>,
+}
+
+/// Emitted when an option becomes the selection of its group. Under a one-step
+/// [`crate::ChoiceConfirmMode`] the confirm messages follow in the same frame.
+///
+/// 选项成为其选项组的选中项时发出。在一步的 [`crate::ChoiceConfirmMode`] 下，确认相关的消息会在
+/// 同一帧内紧随其后。
+#[derive(Message, Debug, Clone, PartialEq, Eq)]
+pub struct MortarChoiceSelected {
+    pub entity: Option<Entity>,
+    /// Declared index of the option in its group.
+    ///
+    /// 选项在其选项组中的声明索引。
+    pub index: usize,
+}
+
+/// Event emitted when confirming an option captures it into a script variable, before the
+/// choice jumps or the next line renders. The dialogue plugin writes `value` into `variable` and
+/// `index` into `{variable}_index`.
+///
+/// 确认选项并将其捕获到脚本变量时发出，发生在选项跳转或下一行显示之前。对话插件会把 `value` 写入
+/// `variable`，并把 `index` 写入 `{variable}_index`。
+#[derive(Message, Debug, Clone, PartialEq)]
+pub struct MortarChoiceCaptured {
+    pub entity: Option<Entity>,
+    pub variable: String,
+    pub value: crate::MortarVariableValue,
+    /// Declared index of the option in its group.
+    ///
+    /// 选项在其选项组中的声明索引。
+    pub index: usize,
+}
+
+/// Emitted when a confirm is applied, with the same [`crate::ConfirmOutcome`]
+/// [`crate::MortarRuntime::simulate_confirm`] returned for it beforehand.
+///
+/// 确认被应用时发出，携带的 [`crate::ConfirmOutcome`] 与事先 [`crate::MortarRuntime::simulate_confirm`]
+/// 返回的结果相同。
+#[derive(Message, Debug, Clone, PartialEq)]
+pub struct MortarChoiceResolved {
+    pub entity: Option<Entity>,
+    /// File and node of the confirmed choice group, before any jump it leads to.
+    ///
+    /// 被确认选项组所在的文件与节点，即其导致的任何跳转发生之前的位置。
+    pub mortar_path: String,
+    pub node: String,
+    pub outcome: crate::ConfirmOutcome,
+}
+
 /// Event emitted when a Mortar dialogue finishes naturally (not via StopDialogue).
 #[derive(Message, Debug, Clone)]
 pub struct MortarDialogueFinished {
     pub entity: Option<Entity>,
     pub mortar_path: String,
     pub node: String,
+    /// Cursor of the line the dialogue finished on.
+    ///
+    /// 对话结束时所在行的游标。
+    pub cursor: crate::LineCursor,
+}
+
+impl MortarDialogueFinished {
+    /// A finish notice with the default cursor, for games that write their own.
+    ///
+    /// 使用默认游标的结束通知，供游戏自行写入时使用。
+    pub fn new(
+        entity: Option<Entity>,
+        mortar_path: impl Into<String>,
+        node: impl Into<String>,
+    ) -> Self {
+        Self {
+            entity,
+            mortar_path: mortar_path.into(),
+            node: node.into(),
+            cursor: crate::LineCursor::default(),
+        }
+    }
 }
 
+/// Fires every event reached at `current_index` that has not fired yet, in ascending index order
+/// (declaration order among equal indices), so a cursor jumping over several events in one frame
+/// runs them as a slower reveal would have.
 fn fire_events(
     events: &[mortar_compiler::Event],
     fired_events: &mut Vec<usize>,
     current_index: f64,
     functions: &crate::MortarFunctionRegistry,
+    suppressed: impl Fn(usize) -> bool,
+    origin: crate::MortarCallOrigin,
 ) -> Vec<MortarEventAction> {
     let mut actions_to_process = Vec::new();
-    for (event_idx, event) in events.iter().enumerate() {
+    let mut order: Vec<_> = events.iter().enumerate().collect();
+    order.sort_by(|(_, a), (_, b)| a.index.total_cmp(&b.index));
+    for (event_idx, event) in order {
+        if suppressed(event_idx) {
+            continue;
+        }
         if current_index < event.index || fired_events.contains(&event_idx) {
+            verbose_trace!(
+                Events,
+                "Event {} at index {} not fired at {}: {}",
+                event_idx,
+                event.index,
+                current_index,
+                if current_index < event.index {
+                    "not reached"
+                } else {
+                    "already fired"
+                }
+            );
             continue;
         }
         fired_events.push(event_idx);
 
         debug!(
+            target: LOG_EVENTS,
             "Mortar event triggered at index {}: {:?}",
             event.index, event.actions
         );
@@ -114,13 +499,17 @@ fn fire_events(
                 .map(|arg| crate::MortarValue::parse(arg))
                 .collect();
 
-            if let Some(result) = functions.call(&action.action_type, &args) {
-                debug!(
+            if let Some(result) = functions.call_from(origin, &action.action_type, &args) {
+                trace!(
+                    target: LOG_EVENTS,
                     "Event function '{}' returned: {:?}",
                     action.action_type, result
                 );
             } else {
-                warn!("Event function '{}' not found", action.action_type);
+                functions.warn_unbound(format_args!(
+                    "Event function '{}' not found",
+                    action.action_type
+                ));
             }
 
             actions_to_process.push(MortarEventAction {
@@ -132,11 +521,47 @@ fn fire_events(
     actions_to_process
 }
 
+/// How a [`MortarEventTracker`] treats its index moving backwards.
+///
+/// Insert as a resource to set the default for every target, or as a component on a text target
+/// to override it for that target only.
+///
+/// [`MortarEventTracker`] 如何处理索引回退。
+///
+/// 作为资源插入时设置所有目标的默认行为；作为组件插入到文本目标上时仅覆盖该目标。
+#[derive(Resource, Component, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MortarTrackerMode {
+    /// Every event fires at most once per line.
+    ///
+    /// 每个事件在一行内最多触发一次。
+    #[default]
+    Once,
+    /// Events after the index re-arm when it moves back, so they fire again when reached.
+    ///
+    /// 索引回退时，位于其后的事件重新就绪，再次到达时会再次触发。
+    Window,
+}
+
 /// Component to track mortar text events and their firing state.
 #[derive(Component, Debug, Clone)]
 pub struct MortarEventTracker {
     events: Vec<mortar_compiler::Event>,
     fired_events: Vec<usize>,
+    /// Schema errors of each event, filled when [`crate::MortarEventSchemas`] checks them.
+    errors: Vec<Option<crate::MortarEventArgError>>,
+    suppress_invalid: bool,
+    /// Whether each event must fire even when its line is left before reaching it.
+    mandatory: Vec<bool>,
+    /// Path, node and text index of the line, when the dialogue plugin wrote it.
+    line: Option<(String, String, usize)>,
+}
+
+/// What [`MortarEventTracker::fire_mandatory`] did with the events a line left unfired.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ForcedEvents {
+    pub(crate) actions: Vec<MortarEventAction>,
+    pub(crate) forced: usize,
+    pub(crate) suppressed: usize,
 }
 
 impl MortarEventTracker {
@@ -144,22 +569,133 @@ impl MortarEventTracker {
         Self {
             events,
             fired_events: Vec::new(),
+            errors: Vec::new(),
+            suppress_invalid: false,
+            mandatory: Vec::new(),
+            line: None,
         }
     }
 
+    /// Marks the events that must fire even when their line is left before the reveal reaches
+    /// them, see [`crate::MortarMandatoryEvents`].
+    ///
+    /// 标记即使在逐字显示到达之前就离开所在行也必须触发的事件，参见 [`crate::MortarMandatoryEvents`]。
+    pub fn with_mandatory(mut self, mandatory: Vec<bool>) -> Self {
+        self.mandatory = mandatory;
+        self
+    }
+
+    pub(crate) fn on_line(mut self, path: &str, node: &str, text_index: usize) -> Self {
+        self.line = Some((path.to_owned(), node.to_owned(), text_index));
+        self
+    }
+
+    pub fn is_mandatory(&self, event: usize) -> bool {
+        self.mandatory.get(event).copied().unwrap_or(false)
+    }
+
+    pub(crate) fn line(&self) -> Option<(&str, &str, usize)> {
+        let (path, node, text_index) = self.line.as_ref()?;
+        Some((path, node, *text_index))
+    }
+
+    /// Fires the mandatory events not fired yet, in index order and with
+    /// [`crate::MortarCallOrigin::ForcedOnAdvance`], and drops the rest. Every event counts as
+    /// fired afterwards.
+    pub(crate) fn fire_mandatory(&mut self, runtime: &crate::MortarRuntime) -> ForcedEvents {
+        let _context = crate::binder::CallContextGuard::enter(runtime.call_context());
+        let unfired = self.events.len() - self.fired_events.len();
+        let before = self.fired_events.len();
+        let errors = &self.errors;
+        let mandatory = &self.mandatory;
+        let suppressed = |event: usize| {
+            !mandatory.get(event).copied().unwrap_or(false)
+                || (self.suppress_invalid && errors.get(event).is_some_and(Option::is_some))
+        };
+        let actions = fire_events(
+            &self.events,
+            &mut self.fired_events,
+            f64::INFINITY,
+            &runtime.functions,
+            suppressed,
+            crate::MortarCallOrigin::ForcedOnAdvance,
+        );
+        let forced = self.fired_events.len() - before;
+        self.fired_events = (0..self.events.len()).collect();
+        ForcedEvents {
+            actions,
+            forced,
+            suppressed: unfired - forced,
+        }
+    }
+
+    /// The events of the line, at the indices they were mapped to.
+    ///
+    /// 该行的事件，位于其被映射到的索引处。
+    pub fn events(&self) -> &[mortar_compiler::Event] {
+        &self.events
+    }
+
+    /// Why event `event` failed its schema; `None` when it passed or was not checked.
+    ///
+    /// 第 `event` 个事件未通过模式检查的原因；通过或未检查时为 `None`。
+    pub fn event_error(&self, event: usize) -> Option<&crate::MortarEventArgError> {
+        self.errors.get(event)?.as_ref()
+    }
+
+    /// Whether event `event` passed its schema or was not checked.
+    ///
+    /// 第 `event` 个事件是否通过了模式检查（或未被检查）。
+    pub fn is_valid(&self, event: usize) -> bool {
+        self.event_error(event).is_none()
+    }
+
+    pub(crate) fn set_validation(
+        &mut self,
+        errors: Vec<Option<crate::MortarEventArgError>>,
+        suppress_invalid: bool,
+    ) {
+        self.errors = errors;
+        self.suppress_invalid = suppress_invalid;
+    }
+
     pub fn trigger_at_index(
         &mut self,
         current_index: f32,
         runtime: &crate::MortarRuntime,
     ) -> Vec<MortarEventAction> {
+        let _context = crate::binder::CallContextGuard::enter(runtime.call_context());
+        let errors = &self.errors;
+        let suppressed =
+            |event: usize| self.suppress_invalid && errors.get(event).is_some_and(Option::is_some);
         fire_events(
             &self.events,
             &mut self.fired_events,
             current_index as f64,
             &runtime.functions,
+            suppressed,
+            crate::MortarCallOrigin::EventAction,
         )
     }
 
+    /// Moves the tracker to `current_index`, re-arming later events first in
+    /// [`MortarTrackerMode::Window`].
+    ///
+    /// 将跟踪器移动到 `current_index`；在 [`MortarTrackerMode::Window`] 下会先让其后的事件重新就绪。
+    pub fn scrub_to(
+        &mut self,
+        current_index: f32,
+        mode: MortarTrackerMode,
+        runtime: &crate::MortarRuntime,
+    ) -> Vec<MortarEventAction> {
+        if mode == MortarTrackerMode::Window {
+            let events = &self.events;
+            self.fired_events
+                .retain(|&event_idx| events[event_idx].index <= current_index as f64);
+        }
+        self.trigger_at_index(current_index, runtime)
+    }
+
     pub fn reset(&mut self) {
         self.fired_events.clear();
     }
@@ -174,7 +710,7 @@ impl MortarEventTracker {
 }
 
 /// An action triggered by a mortar event.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct MortarEventAction {
     pub action_name: String,
     pub args: Vec<String>,
@@ -0,0 +1,50 @@
+//! # internal.rs
+//!
+//! # internal.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Marks the entities the library spawns for its own bookkeeping: pending run executions, the
+//! audio players of `play_sound` and inline icon children. The library never spawns entities the
+//! game is expected to keep; every entity carrying [`MortarInternal`] is owned by the library,
+//! which despawns it when it is done. Editors, scene serializers and state-scoped cleanup can
+//! skip them with `Without<MortarInternal>`, or clear them with [`MortarInternal::despawn_all`].
+//! Entities spawned through [`crate::MortarScopedCommands`] hold the game's own bundle and are
+//! not marked. The components on internal entities are reflected, so a scene that does include
+//! them still serializes.
+//!
+//! 标记库为自身记账而生成的实体：挂起的 run 执行、`play_sound` 的音频播放器以及内联图标子实体。
+//! 库从不生成需要游戏保留的实体；所有带有 [`MortarInternal`] 的实体都归库所有，并在用完后由库
+//! 销毁。编辑器、场景序列化器与按状态作用域的清理可以用 `Without<MortarInternal>` 跳过它们，
+//! 或用 [`MortarInternal::despawn_all`] 清除它们。通过 [`crate::MortarScopedCommands`] 生成的
+//! 实体承载的是游戏自己的组件束，不会被标记。内部实体上的组件都支持反射，因此包含它们的场景
+//! 依然可以序列化。
+
+use bevy::prelude::*;
+
+/// Marker on every entity the library spawns and owns.
+///
+/// 库生成并拥有的每个实体上的标记。
+#[derive(Component, Reflect, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[reflect(Component, Default)]
+pub struct MortarInternal;
+
+impl MortarInternal {
+    /// Despawns every entity marked [`MortarInternal`], e.g. when leaving a game state, and
+    /// returns how many there were. Runs waiting on a despawned pending execution are dropped.
+    ///
+    /// 销毁所有带有 [`MortarInternal`] 标记的实体（例如离开某个游戏状态时），并返回其数量。
+    /// 等待被销毁的挂起执行的 run 会被丢弃。
+    pub fn despawn_all(world: &mut World) -> usize {
+        let entities: Vec<Entity> = world
+            .query_filtered::<Entity, With<MortarInternal>>()
+            .iter(world)
+            .collect();
+        entities
+            .into_iter()
+            .filter(|&entity| world.despawn(entity))
+            .count()
+    }
+}
@@ -29,6 +29,25 @@
 //! 1. 将 [`MortarEventTracker`] 添加到包含文本事件的实体
 //! 2. 使用当前进度索引调用 `trigger_at_index()`
 //! 3. 在游戏系统中处理返回的 [`MortarEventAction`]
+//!
+//! # Feature Flags
+//!
+//! - `ui` (default): writes lines onto [`MortarTextTarget`] entities, drives and seeks
+//!   [`MortarTextReveal`], and applies reveal policies and [`RunTextBehavior`]. Without it those
+//!   components and settings still exist but nothing acts on them.
+//! - `audio` (default): `MortarAudioSettings` and automatic playback of sound events.
+//! - `typewriter` and `ui-icons` turn on `ui`.
+//! - `window`: pauses dialogue while the app's windows are unfocused or minimized, as
+//!   `MortarFocusSettings` asks. Nothing pauses by default.
+//!
+//! # 特性开关
+//!
+//! - `ui`（默认）：把行写入 [`MortarTextTarget`] 实体，驱动并定位 [`MortarTextReveal`]，并应用
+//!   显示策略与 [`RunTextBehavior`]。关闭时这些组件与设置仍然存在，但不会有系统处理它们。
+//! - `audio`（默认）：`MortarAudioSettings` 以及声音事件的自动播放。
+//! - `typewriter` 与 `ui-icons` 会启用 `ui`。
+//! - `window`：按 `MortarFocusSettings` 的要求，在应用窗口失去焦点或被最小化时暂停对话。默认不暂停
+//!   任何内容。
 
 use bevy::prelude::*;
 #[cfg(test)]
@@ -36,76 +55,275 @@ use mortar_compiler::Node;
 
 #[macro_use]
 mod debug;
+#[cfg(feature = "animation")]
+mod animation;
 mod asset;
+#[cfg(feature = "audio")]
 mod audio;
 mod binder;
 mod dialogue;
 mod dialogue_state;
 mod eval;
 mod events;
+mod internal;
+mod preparation;
 mod runtime;
+#[cfg(feature = "save")]
+mod save;
 mod system;
+mod validation;
 mod variable_state;
 
 #[cfg(test)]
 mod tests;
 
-pub use asset::{MortarAsset, MortarAssetLoader};
+// Lets the derive macros, which name `bevy_mortar_bond`, be used in the crate's own tests.
+//
+// 使引用 `bevy_mortar_bond` 的派生宏可以在本 crate 自身的测试中使用。
+#[cfg(test)]
+extern crate self as bevy_mortar_bond;
+
+#[cfg(feature = "animation")]
+pub use animation::{
+    MortarAnimationDiagnostics, MortarAnimationEntry, MortarAnimationMap, MortarAnimationPlugin,
+    MortarAnimationRevert, MortarAnimationSettings,
+};
+#[cfg(feature = "tools")]
+pub use asset::{GraphFormat, StringsExport, StringsFormat};
+pub use asset::{
+    LoadError, MortarAsset, MortarAssetLoadStage, MortarAssetLoader, MortarLastGood,
+    MortarLoadStage, MortarMetadata, MortarOverlayShadow,
+};
+#[cfg(feature = "audio")]
 pub use audio::MortarAudioSettings;
 pub use bevy_mortar_bond_macros::{MortarFunctions, mortar_functions};
 pub use binder::{
-    MortarBoolean, MortarFunctionRegistry, MortarNumber, MortarString, MortarValue, MortarVoid,
+    CoercionPolicy, DEFAULT_MAX_CALL_DEPTH, MissingBinding, MissingBindingKind, MortarBoolean,
+    MortarCallContext, MortarCallError, MortarCallOrigin, MortarFnArgDoc, MortarFnDoc,
+    MortarFunction, MortarFunctionError, MortarFunctionManifest, MortarFunctionRegistry,
+    MortarFunctionSignature, MortarNumber, MortarParamKind, MortarScopedFunctions, MortarString,
+    MortarValue, MortarVoid, render_markdown,
 };
+pub use debug::{MortarDebugCategories, MortarLogConfig};
 pub use dialogue::{
-    CachedCondition, MortarDialoguePlugin, MortarDialogueSystemSet, MortarDialogueText,
-    MortarDialogueVariables, MortarEventBinding, MortarGameEvent, MortarRunsExecuting,
-    MortarTextTarget, evaluate_condition_cached,
+    CachedCondition, DEFAULT_DIALOGUE_HISTORY_CAPACITY, DEFAULT_ICON_PLACEHOLDER,
+    DEFAULT_IMMEDIATE_STEPS_PER_FRAME, DEFAULT_STATE_HISTORY_CAPACITY, InlineIcon, LinePosition,
+    MortarAppliedEffect, MortarChoiceDeselected, MortarChoiceReevaluation, MortarChoiceView,
+    MortarChoiceViewKind, MortarChoicesPresented, MortarConditionCache, MortarDialogueHistory,
+    MortarDialogueHistoryEntry, MortarDialoguePlugin, MortarDialogueSystemSet, MortarDialogueText,
+    MortarDialogueVariables, MortarEffectHandler, MortarEffectScope, MortarEventArgError,
+    MortarEventBinding, MortarEventDiagnostic, MortarEventDiagnostics, MortarEventFlush,
+    MortarEventSchemas, MortarExperimentDiagnostics, MortarExperimentTag, MortarExperiments,
+    MortarFocusHint, MortarFocusKind, MortarFocusTarget, MortarGameEvent, MortarHeaderChanged,
+    MortarHeaderSettings, MortarHistoryEvent, MortarIconSettings, MortarIconSpeechMap,
+    MortarInvalidEventPolicy, MortarLineStatus, MortarMandatoryEvents, MortarRevealPolicy,
+    MortarRevealPolicySettings, MortarRevealStep, MortarReversibleEffects, MortarRunsExecuting,
+    MortarSchedule, MortarScopeGenerations, MortarScoped, MortarScopedCommands,
+    MortarScriptAdvance, MortarScriptFlow, MortarScriptFlowSettings, MortarSpeakableChanged,
+    MortarSpeaker, MortarSpeakerFocus, MortarSpeakerRegistry, MortarSpeechFormat,
+    MortarSpeechFormatter, MortarStateDiff, MortarStateHistory, MortarStateRecord,
+    MortarTargetDiagnostics, MortarTextAdvanced, MortarTextChanged, MortarTextChannel,
+    MortarTextReveal, MortarTextTarget, MortarTimelineSettings, PAUSE_REVEAL_ACTION, PAUSE_TOKEN,
+    READING_CHARS_PER_SECOND, RunTextBehavior, estimate_read_seconds, evaluate_condition_cached,
+    extract_inline_icons,
 };
+#[cfg(feature = "ui-icons")]
+pub use dialogue::{
+    MISSING_ICON_GLYPH, MortarIconAtlas, MortarIconDiagnostics, MortarIconPlugin, MortarInlineIcon,
+};
+#[cfg(feature = "ui")]
+pub use dialogue::{
+    MortarAnimatedDisplay, MortarAutoAdvance, MortarDisplayAnimations, MortarDisplayPolicy,
+    MortarDisplayRoll, MortarRenderedLine,
+};
+#[cfg(feature = "window")]
+pub use dialogue::{
+    MortarBackgroundPause, MortarBackgroundWindows, MortarFocusPolicy, MortarFocusSettings,
+};
+#[cfg(feature = "typewriter")]
+pub use dialogue::{MortarTypewriter, MortarTypewriterAdapter, MortarTypewriterPlugin};
 pub use dialogue_state::{
-    DialogueRunDescriptor, DialogueRunItem, DialogueRunKind, DialogueState, TextData,
+    CaptureValue, ChoiceCapture, ChoicePagination, DialogueRunDescriptor, DialogueRunItem,
+    DialogueRunKind, DialogueState, LineCursor, MortarDiagnostic, MortarNodeEntry, MortarUiHints,
+    ParsedNode, TextData, TextPosition,
+};
+pub use eval::{
+    evaluate_choice_condition_cached, evaluate_condition, evaluate_if_condition,
+    evaluate_if_condition_cached, process_interpolated_text,
+};
+pub use events::{
+    MortarChoiceCaptured, MortarChoiceResolved, MortarChoiceSelected, MortarCommand,
+    MortarDialogueFinished, MortarDialogueStarted, MortarEventAction, MortarEventTracker,
+    MortarLifecycleEvent, MortarNodeEntered, MortarStartFailed, MortarStartFailure,
+    MortarTrackerMode,
+};
+pub use internal::MortarInternal;
+pub use preparation::{DEFAULT_PREPARED_TTL, MortarNodePrepared, PreparedDialogue};
+pub use runtime::{
+    AdvanceIntent, CAPABILITY_FUNCTION, CHANCE_FUNCTION, ChoiceConfirmMode, ChoiceInputSource,
+    ConfirmEffect, ConfirmOutcome, DEFAULT_AUTO_ADVANCE_BUDGET, DEFAULT_MAX_PREPARED,
+    DEFAULT_MAX_WARM_FILES, FocusContention, FocusRequest, INPUT_CAPABILITY, MortarAdvanceIntent,
+    MortarAvailability, MortarAvailabilityRule, MortarCapabilities, MortarDriveError,
+    MortarErrorEvent, MortarFocus, MortarFocusChanged, MortarHaltReason, MortarOverlayDiagnostics,
+    MortarRegistry, MortarRngState, MortarRuntime, MortarStartSuppressed, MortarStartSuppression,
+    MortarTrimPolicy, NODE_TAGGED_FUNCTION, PendingStatus, RANDOM_FUNCTION, SelectionPersistence,
+    SuppressedStartPolicy, UnfocusedBehavior,
+};
+#[cfg(feature = "save")]
+pub use save::{
+    MORTAR_SAVE_VERSION, MortarExportError, MortarHistoryExportPolicy, MortarHistoryFields,
+    MortarRestoreError, MortarSaveData, MortarSaveError, MortarSaveMigration, MortarSaveMigrations,
+    MortarSavedDialogue,
+};
+pub use validation::{
+    DEFAULT_MAX_TEXT_CHARS, MortarAnalysisComplete, MortarIssueKind, MortarIssueSeverity,
+    MortarLint, MortarLintConfig, MortarLintRule, MortarLinter, MortarStrictStart,
+    MortarValidationIssue, MortarValidationReport, lint_mortar_file, lint_mortared_data,
+    validate_mortar_file, validate_mortared_data,
+};
+pub use variable_state::{
+    MAX_TRANSACTION_DEPTH, MortarTransactionError, MortarVariableState, MortarVariableValue,
 };
-pub use eval::{evaluate_condition, evaluate_if_condition, process_interpolated_text};
-pub use events::{MortarDialogueFinished, MortarEvent, MortarEventAction, MortarEventTracker};
-pub use runtime::{MortarRegistry, MortarRuntime};
-pub use variable_state::{MortarVariableState, MortarVariableValue};
 
 /// Re-export mortar_compiler types for convenience.
 ///
 /// 为方便使用，重新导出 mortar_compiler 类型。
 pub use mortar_compiler::Event as MortarTextEvent;
 
-/// Convenient re-exports for common usage.
+/// Old name of [`MortarCommand`], the input message, kept for one release so downstream code
+/// still compiles. It was easy to confuse with [`MortarTextEvent`] and [`MortarGameEvent`].
+///
+/// 输入消息 [`MortarCommand`] 的旧名，保留一个版本以便下游代码仍能编译。它容易与
+/// [`MortarTextEvent`] 和 [`MortarGameEvent`] 混淆。
+#[deprecated(since = "0.5.0", note = "renamed to `MortarCommand`")]
+pub type MortarEvent = MortarCommand;
+
+/// What a typical integration needs in one import: the plugins, the command and notification
+/// messages, the text target components, the runtime resources, the value types and the binding
+/// macros. `use bevy_mortar_bond::prelude::*;` is enough to start, show and advance a dialogue and
+/// bind functions to it.
 ///
-/// 常用类型的便捷重导出。
+/// 典型集成所需的一次性导入：插件、命令与通知消息、文本目标组件、运行时资源、值类型以及绑定宏。
+/// `use bevy_mortar_bond::prelude::*;` 即可启动、显示并推进对话，并为其绑定函数。
 pub mod prelude {
+    #[cfg(feature = "audio")]
+    pub use crate::MortarAudioSettings;
     pub use crate::{
-        MortarAudioSettings, MortarDialoguePlugin, MortarDialogueSystemSet, MortarDialogueText,
-        MortarDialogueVariables, MortarEventBinding, MortarFunctionRegistry, MortarGameEvent,
-        MortarPlugin, MortarRunsExecuting, MortarTextTarget, MortarValue,
+        AdvanceIntent, ChoiceConfirmMode, ChoiceInputSource, DialogueState, MortarAsset,
+        MortarBoolean, MortarChoiceSelected, MortarChoiceView, MortarChoiceViewKind,
+        MortarChoicesPresented, MortarCommand, MortarDialogueFinished, MortarDialoguePlugin,
+        MortarDialogueStarted, MortarDialogueSystemSet, MortarDialogueText,
+        MortarDialogueVariables, MortarEventAction, MortarEventBinding, MortarEventTracker,
+        MortarFunctionRegistry, MortarFunctions, MortarGameEvent, MortarNumber, MortarPlugin,
+        MortarRegistry, MortarReversibleEffects, MortarRunsExecuting, MortarRuntime, MortarString,
+        MortarTextChanged, MortarTextEvent, MortarTextTarget, MortarValue, MortarVariableState,
+        MortarVariableValue, MortarVoid, mortar_functions,
     };
+    #[cfg(feature = "typewriter")]
+    pub use crate::{MortarTypewriter, MortarTypewriterAdapter, MortarTypewriterPlugin};
 }
 
 /// The main plugin for the mortar 'bond' (bind) system.
 ///
 /// Mortar "绑钉" （绑定）系统的主要插件。
-pub struct MortarPlugin;
+pub struct MortarPlugin {
+    /// Whether active dialogues are rebuilt from their file when it hot reloads, see
+    /// `system/hot_reload.rs`. On by default.
+    ///
+    /// 文件热重载时是否基于新文件重建活跃对话，参见 `system/hot_reload.rs`。默认开启。
+    pub hot_reload: bool,
+    /// Whether the last version of each file that loaded is kept and served again when a reload
+    /// of the file fails, see [`MortarLastGood`]. On by default; strict environments that would
+    /// rather see the failure turn it off.
+    ///
+    /// 是否保留每个文件最近一次成功加载的版本，并在文件重载失败时继续提供该版本，参见
+    /// [`MortarLastGood`]。默认开启；希望直接暴露失败的严格环境可将其关闭。
+    pub retain_last_good: bool,
+}
+
+impl Default for MortarPlugin {
+    fn default() -> Self {
+        Self {
+            hot_reload: true,
+            retain_last_good: true,
+        }
+    }
+}
 
 impl Plugin for MortarPlugin {
     fn build(&self, app: &mut App) {
+        let load_stages = asset::LoadStageQueue::default();
         app.init_asset::<MortarAsset>()
-            .init_asset_loader::<MortarAssetLoader>()
+            .register_asset_loader(MortarAssetLoader::reporting_to(load_stages.clone()))
+            .insert_resource(load_stages)
             .init_resource::<MortarRegistry>()
             .init_resource::<MortarRuntime>()
-            .add_message::<MortarEvent>()
+            .init_resource::<MortarLogConfig>()
+            .init_resource::<MortarDebugCategories>()
+            .init_resource::<MortarAdvanceIntent>()
+            .init_resource::<MortarFocus>()
+            .init_resource::<MortarLintConfig>()
+            .init_resource::<validation::AnalysisTasks>()
+            .init_resource::<MortarCapabilities>()
+            .init_resource::<MortarAvailability>()
+            .init_resource::<MortarOverlayDiagnostics>()
+            .insert_resource(MortarLastGood::new(self.retain_last_good))
+            .add_message::<MortarCommand>()
+            .add_message::<MortarDialogueStarted>()
             .add_message::<MortarDialogueFinished>()
+            .add_message::<MortarNodePrepared>()
+            .add_message::<MortarNodeEntered>()
+            .add_message::<MortarStartFailed>()
+            .add_message::<MortarStartSuppressed>()
+            .add_message::<MortarAssetLoadStage>()
+            .add_message::<MortarFunctionError>()
+            .add_message::<MortarChoiceSelected>()
+            .add_message::<MortarChoiceCaptured>()
+            .add_message::<MortarChoiceResolved>()
+            .add_message::<MortarAnalysisComplete>()
+            .add_message::<MortarErrorEvent>()
+            .add_message::<MortarFocusChanged>()
+            .add_message::<MortarLifecycleEvent>()
             .add_systems(
                 Update,
                 (
+                    debug::apply_log_config,
+                    debug::apply_debug_categories,
+                    runtime::merge_overlays,
+                    preparation::maintain_prepared_dialogues,
+                    runtime::sync_capabilities,
+                    runtime::refresh_custom_availability,
                     system::process_mortar_events_system,
+                    runtime::record_finished_dialogues,
+                    asset::drain_load_stages,
                     system::check_pending_start_system,
                     system::handle_pending_jump_system,
+                    runtime::update_advance_intent,
+                    runtime::sync_focus,
                 )
                     .chain(),
+            )
+            .add_systems(
+                Update,
+                (
+                    validation::analyze_loaded_assets.before(system::check_pending_start_system),
+                    binder::warn_unbound_on_load,
+                    asset::retain_last_good_versions
+                        .after(runtime::merge_overlays)
+                        .before(system::process_mortar_events_system),
+                    runtime::announce_direct_drive.before(system::process_mortar_events_system),
+                ),
+            )
+            .add_systems(PostUpdate, binder::emit_function_errors);
+        if self.hot_reload {
+            app.add_systems(
+                Update,
+                system::resync_reloaded_dialogues
+                    .after(runtime::merge_overlays)
+                    .before(system::process_mortar_events_system),
             );
+        }
+        #[cfg(feature = "save")]
+        app.init_resource::<MortarSaveMigrations>();
     }
 }
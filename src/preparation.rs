@@ -0,0 +1,304 @@
+//! # preparation.rs
+//!
+//! # preparation.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Lets games warm a conversation before the player triggers it. Preparing a node does
+//! everything a start would do except activating it: the node is parsed into a dialogue state, the
+//! initial variable state is built, the file is validated against the bound functions and the
+//! sounds it plays are requested from the asset server. A later start of the same node consumes the
+//! prepared entry instead of parsing again. Entries expire after a configurable TTL or when their
+//! asset is reloaded.
+//!
+//! 让游戏在玩家触发对话之前预热它。准备节点会完成开始对话时的全部工作，只是不激活：
+//! 解析节点得到对话状态、构建初始变量状态、根据已绑定函数校验文件，并向资源服务器请求其中
+//! 播放的音效。之后开始同一节点时会直接取用准备好的条目而不再解析。条目会在可配置的 TTL
+//! 到期或资源重新加载时失效。
+
+use bevy::asset::{AssetEvent, AssetId, AssetServer, Assets, UntypedHandle};
+use bevy::platform::time::Instant;
+use bevy::prelude::*;
+use std::time::Duration;
+
+use crate::debug::LOG_DIALOGUE;
+use crate::{
+    DialogueState, MortarAsset, MortarIssueSeverity, MortarRegistry, MortarRuntime,
+    MortarValidationIssue, MortarVariableState, validate_mortared_data,
+};
+
+/// Default lifetime of a prepared dialogue.
+///
+/// 已准备对话的默认存活时间。
+pub const DEFAULT_PREPARED_TTL: Duration = Duration::from_secs(30);
+
+/// A node parsed and checked ahead of its start.
+///
+/// 在开始之前已解析并检查完毕的节点。
+#[derive(Debug, Clone)]
+pub struct PreparedDialogue {
+    pub state: DialogueState,
+    /// Initial variable state of the file.
+    ///
+    /// 文件的初始变量状态。
+    pub variables: MortarVariableState,
+    /// Validation issues found while preparing.
+    ///
+    /// 准备期间发现的校验问题。
+    pub issues: Vec<MortarValidationIssue>,
+    /// `Time::elapsed` when the entry was prepared.
+    ///
+    /// 条目准备完成时的 `Time::elapsed`。
+    pub prepared_at: Duration,
+    warmed_assets: Vec<UntypedHandle>,
+}
+
+/// Emitted when a node finished preparing.
+///
+/// 节点准备完成时发出。
+#[derive(Message, Debug, Clone, PartialEq, Eq)]
+pub struct MortarNodePrepared {
+    pub path: String,
+    pub node: String,
+    /// Wall-clock time spent preparing.
+    ///
+    /// 准备所花费的实际时间。
+    pub duration: Duration,
+}
+
+impl MortarRuntime {
+    /// Prepares `node` of an already loaded asset so a later start activates without parsing.
+    /// `path` should be the registry's canonical key. Returns `None` when the node does not exist.
+    ///
+    /// 准备已加载资源中的 `node`，使之后的开始无需解析即可激活。`path` 应为注册表的规范化键。
+    /// 节点不存在时返回 `None`。
+    pub fn prepare(
+        &mut self,
+        path: &str,
+        node: &str,
+        asset: &MortarAsset,
+        asset_server: &AssetServer,
+        now: Duration,
+    ) -> Option<MortarNodePrepared> {
+        let started = Instant::now();
+        let Some(node_data) = crate::asset::find_node(&asset.data, node) else {
+            warn!(target: LOG_DIALOGUE, "Cannot prepare node '{}': not found in '{}'", node, path);
+            return None;
+        };
+        let state = self.parse_node(path, node, node_data, asset);
+
+        let variables = self
+            .warm_variables
+            .entry(path.to_owned())
+            .or_insert_with(|| {
+                MortarVariableState::from_variables(
+                    &asset.data.variables,
+                    &asset.data.constants,
+                    &asset.data.enums,
+                )
+            })
+            .clone();
+
+        let manifest = self.functions.export_manifest();
+        let issues = validate_mortared_data(&asset.data, Some(&manifest));
+        for issue in issues
+            .iter()
+            .filter(|issue| issue.severity == MortarIssueSeverity::Error)
+        {
+            warn!(target: LOG_DIALOGUE, "Preparing '{}' in '{}': {}", node, path, issue);
+        }
+
+        let warmed_assets = prewarm_assets(&state, asset_server);
+        self.prepared.insert(
+            (path.to_owned(), node.to_owned()),
+            PreparedDialogue {
+                state,
+                variables,
+                issues,
+                prepared_at: now,
+                warmed_assets,
+            },
+        );
+        let policy = self.trim_policy.clone();
+        self.trim(&policy);
+        dev_info!(target: LOG_DIALOGUE, "Prepared node: {} in {}", node, path);
+
+        Some(MortarNodePrepared {
+            path: path.to_owned(),
+            node: node.to_owned(),
+            duration: started.elapsed(),
+        })
+    }
+
+    /// Whether a non-expired preparation exists for the node.
+    ///
+    /// 该节点是否存在未过期的准备条目。
+    pub fn is_prepared(&self, path: &str, node: &str) -> bool {
+        self.prepared
+            .contains_key(&(path.to_owned(), node.to_owned()))
+    }
+
+    /// Number of times a node has been parsed into a dialogue state.
+    ///
+    /// 节点被解析为对话状态的次数。
+    pub fn node_parses(&self) -> u64 {
+        self.node_parses
+    }
+
+    /// Cached initial variable state of a prepared file.
+    ///
+    /// 已准备文件的缓存初始变量状态。
+    pub fn warm_variables(&self, path: &str) -> Option<&MortarVariableState> {
+        self.warm_variables.get(path)
+    }
+
+    pub(crate) fn parse_node(
+        &mut self,
+        path: &str,
+        node: &str,
+        node_data: &mortar_compiler::Node,
+        asset: &crate::MortarAsset,
+    ) -> DialogueState {
+        self.node_parses += 1;
+        let metadata = &asset.metadata;
+        DialogueState::from_parsed(path.to_owned(), node.to_owned(), asset.parsed(node_data))
+            .with_tags(metadata.node_tags(node).to_vec())
+            .with_overrides(metadata.overrides_of(node).cloned().unwrap_or_default())
+    }
+
+    /// Consumes the prepared state of a node, keeping its warmed assets alive for the dialogue.
+    pub(crate) fn take_prepared(&mut self, path: &str, node: &str) -> Option<DialogueState> {
+        let prepared = self.prepared.remove(&(path.to_owned(), node.to_owned()))?;
+        self.warmed_assets
+            .entry(path.to_owned())
+            .or_default()
+            .extend(prepared.warmed_assets);
+        Some(prepared.state)
+    }
+
+    fn forget_path(&mut self, path: &str) {
+        self.prepared
+            .retain(|(prepared_path, _), _| prepared_path != path);
+        self.warm_variables.remove(path);
+        self.warmed_assets.remove(path);
+    }
+}
+
+/// Requests the sounds a node plays so they are loaded before their line shows.
+#[cfg(feature = "audio")]
+fn prewarm_assets(state: &DialogueState, asset_server: &AssetServer) -> Vec<UntypedHandle> {
+    state
+        .text_items()
+        .iter()
+        .filter_map(|text| text.events.as_ref())
+        .flatten()
+        .flat_map(|event| &event.actions)
+        .filter(|action| action.action_type == "play_sound")
+        .filter_map(|action| action.args.first())
+        .map(|path| asset_server.load::<AudioSource>(path.clone()).untyped())
+        .collect()
+}
+
+/// Without the `audio` feature a node has no sounds to load ahead.
+#[cfg(not(feature = "audio"))]
+fn prewarm_assets(_state: &DialogueState, _asset_server: &AssetServer) -> Vec<UntypedHandle> {
+    Vec::new()
+}
+
+/// Handles a `PrepareNode` request, deferring it while the asset is still loading.
+pub(crate) fn handle_prepare_node(
+    path: &str,
+    node: &str,
+    now: Duration,
+    runtime: &mut MortarRuntime,
+    registry: &mut MortarRegistry,
+    assets: &Assets<MortarAsset>,
+    asset_server: &AssetServer,
+) -> Option<MortarNodePrepared> {
+    let handle = registry.get(path).cloned().unwrap_or_else(|| {
+        debug!(target: LOG_DIALOGUE, "Auto-loading mortar file for preparation: {}", path);
+        let handle = asset_server.load::<MortarAsset>(path.to_owned());
+        registry.register(path.to_owned(), handle.clone());
+        handle
+    });
+
+    let path = registry.canonical_key(path);
+    let path = path.as_str();
+    let Some(asset) = assets.get(&handle) else {
+        let key = (path.to_owned(), node.to_owned());
+        if !runtime.pending_prepares.contains(&key) {
+            runtime.pending_prepares.push(key);
+        }
+        return None;
+    };
+    runtime.prepare(path, node, asset, asset_server, now)
+}
+
+pub(crate) fn reloaded_paths(registry: &MortarRegistry, id: AssetId<MortarAsset>) -> Vec<String> {
+    registry
+        .paths()
+        .filter(|(_, handle)| handle.id() == id)
+        .map(|(path, _)| path.to_owned())
+        .collect()
+}
+
+/// Retries deferred preparations, drops entries that expired or whose asset was reloaded, and
+/// applies the runtime's trim policy.
+///
+/// 重试延后的准备请求，丢弃已过期或资源已重新加载的条目，并应用运行时的裁剪策略。
+pub(crate) fn maintain_prepared_dialogues(
+    time: Res<Time>,
+    mut runtime: ResMut<MortarRuntime>,
+    registry: Res<MortarRegistry>,
+    assets: Res<Assets<MortarAsset>>,
+    asset_server: Res<AssetServer>,
+    mut asset_events: MessageReader<AssetEvent<MortarAsset>>,
+    mut prepared_events: MessageWriter<MortarNodePrepared>,
+) {
+    for event in asset_events.read() {
+        let AssetEvent::Modified { id } = event else {
+            continue;
+        };
+        for path in reloaded_paths(&registry, *id) {
+            dev_info!(target: LOG_DIALOGUE, "Dropping preparations of reloaded '{}'", path);
+            runtime.forget_path(&path);
+        }
+    }
+
+    let now = time.elapsed();
+    if let Some(ttl) = runtime.prepared_ttl {
+        runtime
+            .prepared
+            .retain(|_, prepared| now.saturating_sub(prepared.prepared_at) < ttl);
+    }
+    let policy = runtime.trim_policy.clone();
+    runtime.trim(&policy);
+
+    let pending = std::mem::take(&mut runtime.pending_prepares);
+    for (path, node) in pending {
+        let Some(asset) = registry.get(&path).and_then(|handle| assets.get(handle)) else {
+            runtime.pending_prepares.push((path, node));
+            continue;
+        };
+        if let Some(prepared) = runtime.prepare(&path, &node, asset, &asset_server, now) {
+            prepared_events.write(prepared);
+        }
+    }
+
+    let MortarRuntime {
+        active_dialogues,
+        prepared,
+        warmed_assets,
+        ..
+    } = &mut *runtime;
+    warmed_assets.retain(|path, _| {
+        active_dialogues
+            .values()
+            .any(|state| &state.mortar_path == path)
+            || prepared
+                .keys()
+                .any(|(prepared_path, _)| prepared_path == path)
+    });
+}
@@ -13,30 +13,224 @@
 //! 定义了支撑 Mortar 运行时的长生命周期资源。它负责记录已注册的 Mortar 资源、活跃的
 //! 对话控制器、待开始或待跳转请求，以及求值 Mortar 逻辑时会用到的函数绑定注册表。
 
+use bevy::asset::UntypedHandle;
 use bevy::prelude::*;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+use crate::debug::LOG_ASSET;
+
+mod advance;
+mod availability;
+mod capabilities;
+mod confirm;
+mod confirm_mode;
+mod direct_drive;
+mod focus;
+mod loop_guard;
+mod overlays;
+mod pending;
+mod rng;
+mod selection_persistence;
+mod signals;
+mod tags;
+mod trim;
+
+pub(crate) use advance::{AdvanceGate, update_advance_intent};
+pub use advance::{AdvanceIntent, MortarAdvanceIntent};
+pub use availability::{
+    MortarAvailability, MortarAvailabilityRule, MortarStartSuppressed, MortarStartSuppression,
+    SuppressedStartPolicy,
+};
+pub(crate) use availability::{record_finished_dialogues, refresh_custom_availability};
+pub use capabilities::{CAPABILITY_FUNCTION, MortarCapabilities};
+pub(crate) use capabilities::{capability_placeholder, required_capabilities, sync_capabilities};
+pub(crate) use confirm::resolve_confirm;
+pub use confirm::{ConfirmEffect, ConfirmOutcome};
+pub use confirm_mode::{ChoiceConfirmMode, ChoiceInputSource, INPUT_CAPABILITY};
+pub use direct_drive::MortarDriveError;
+#[cfg(feature = "ui")]
+pub(crate) use direct_drive::drive_file;
+pub(crate) use direct_drive::{DirectDrive, announce_direct_drive};
+pub(crate) use focus::sync_focus;
+pub use focus::{
+    FocusContention, FocusRequest, MortarFocus, MortarFocusChanged, UnfocusedBehavior,
+};
+pub(crate) use loop_guard::AutoAdvanceTrail;
+pub use loop_guard::{DEFAULT_AUTO_ADVANCE_BUDGET, MortarErrorEvent, MortarHaltReason};
+pub use overlays::MortarOverlayDiagnostics;
+pub(crate) use overlays::merge_overlays;
+pub(crate) use pending::PendingLoad;
+pub use pending::PendingStatus;
+pub use rng::{CHANCE_FUNCTION, MortarRngState, RANDOM_FUNCTION};
+pub use selection_persistence::SelectionPersistence;
+pub(crate) use selection_persistence::SelectionTransition;
+pub(crate) use signals::{SignalBoard, SignalWait};
+pub use tags::NODE_TAGGED_FUNCTION;
+pub use trim::{DEFAULT_MAX_PREPARED, DEFAULT_MAX_WARM_FILES, MortarTrimPolicy};
 
 /// A global registry for Mortar assets, managing multiple mortar files.
 ///
+/// Paths are stored under a canonical key (see [`MortarRegistry::canonical_key`]), so
+/// `./pub.mortar` and `pub.mortar` name the same entry.
+///
 /// 全局 Mortar 资源注册表，管理多个 mortar 文件。
+///
+/// 路径以规范化键存储（见 [`MortarRegistry::canonical_key`]），因此 `./pub.mortar` 与
+/// `pub.mortar` 指向同一条目。
 #[derive(Resource, Default)]
 pub struct MortarRegistry {
     assets: HashMap<String, Handle<crate::MortarAsset>>,
+    raw_paths: HashMap<String, String>,
+    case_insensitive: bool,
+    noted_aliases: HashSet<AssetId<crate::MortarAsset>>,
+    /// Overlays by canonical base path, see [`MortarRegistry::register_overlay`].
+    overlays: HashMap<String, overlays::OverlayStack>,
 }
 
 impl MortarRegistry {
+    /// Creates a registry whose keys ignore case, for projects whose assets live on
+    /// case-insensitive file systems. Insert it before adding the plugin.
+    ///
+    /// 创建键不区分大小写的注册表，适用于资源位于不区分大小写文件系统上的项目。需在添加插件前插入。
+    pub fn with_case_insensitive_keys() -> Self {
+        Self {
+            case_insensitive: true,
+            ..default()
+        }
+    }
+
+    /// Normalizes a path the way the registry stores it: backslashes become `/`, `.` segments,
+    /// leading `./` and repeated separators are dropped, and case is folded when enabled.
+    ///
+    /// 按注册表的存储方式规范化路径：反斜杠转为 `/`，去掉 `.` 段、开头的 `./` 和重复的分隔符，
+    /// 启用时还会统一为小写。
+    pub fn canonical_key(&self, path: &str) -> String {
+        let unified = path.replace('\\', "/");
+        let key = unified
+            .split('/')
+            .filter(|segment| !segment.is_empty() && *segment != ".")
+            .collect::<Vec<_>>()
+            .join("/");
+        let key = if unified.starts_with('/') {
+            format!("/{key}")
+        } else {
+            key
+        };
+        if self.case_insensitive {
+            key.to_lowercase()
+        } else {
+            key
+        }
+    }
+
     /// Registers a mortar asset, using its path as an identifier.
+    /// Registering a path whose canonical key is already taken by a different spelling warns
+    /// and replaces the previous handle.
     ///
     /// 注册一个 mortar 资源，使用路径名作为标识符。
+    /// 若规范化键已被另一种写法占用，会发出警告并替换原有句柄。
     pub fn register(&mut self, path: impl Into<String>, handle: Handle<crate::MortarAsset>) {
-        self.assets.insert(path.into(), handle);
+        let raw = path.into();
+        let key = self.canonical_key(&raw);
+        if let Some(previous) = self.raw_paths.get(&key)
+            && *previous != raw
+        {
+            warn!(
+                target: LOG_ASSET,
+                "Mortar path '{}' and '{}' both normalize to '{}'; the later registration replaces the earlier one",
+                previous, raw, key
+            );
+        }
+        self.raw_paths.insert(key.clone(), raw);
+        self.invalidate_overlays(&key);
+        self.assets.insert(key, handle.clone());
+
+        let aliases = self.aliases_of(&handle);
+        if aliases.len() > 1 && !self.noted_aliases.contains(&handle.id()) {
+            info!(
+                target: LOG_ASSET,
+                "Mortar paths {:?} refer to the same asset; dialogue state is shared between them",
+                aliases
+            );
+            self.noted_aliases.insert(handle.id());
+        }
+    }
+
+    /// Lists, sorted, every registered path whose handle points at the same asset as `handle`.
+    ///
+    /// 按顺序列出所有句柄与 `handle` 指向同一资源的已注册路径。
+    pub fn aliases_of(&self, handle: &Handle<crate::MortarAsset>) -> Vec<&str> {
+        let mut aliases: Vec<_> = self
+            .assets
+            .iter()
+            .filter(|(_, registered)| registered.id() == handle.id())
+            .map(|(path, _)| path.as_str())
+            .collect();
+        aliases.sort_unstable();
+        aliases
     }
 
-    /// Gets the handle for a registered asset.
+    /// Gets the handle for a registered asset; for a path with overlays, the handle of the merged
+    /// content.
     ///
-    /// 获取已注册的资源句柄。
+    /// 获取已注册的资源句柄；对于带覆盖层的路径，返回合并后内容的句柄。
     pub fn get(&self, path: &str) -> Option<&Handle<crate::MortarAsset>> {
-        self.assets.get(path)
+        let key = self.canonical_key(path);
+        self.merged(&key).or_else(|| self.assets.get(&key))
+    }
+
+    pub(crate) fn paths(&self) -> impl Iterator<Item = (&str, &Handle<crate::MortarAsset>)> {
+        self.assets
+            .iter()
+            .map(|(path, handle)| (path.as_str(), self.merged(path).unwrap_or(handle)))
+    }
+
+    /// Exports the strings of every loaded file, see [`crate::MortarAsset::export_strings`],
+    /// ordered by path and with a leading `file` column.
+    ///
+    /// 导出所有已加载文件的字符串（参见 [`crate::MortarAsset::export_strings`]），按路径排序，并在
+    /// 最前面加上 `file` 列。
+    #[cfg(feature = "tools")]
+    pub fn export_all_strings(
+        &self,
+        assets: &Assets<crate::MortarAsset>,
+        export: impl Into<crate::StringsExport>,
+    ) -> String {
+        let mut files: Vec<_> = self
+            .paths()
+            .filter_map(|(path, handle)| Some((path, assets.get(handle)?)))
+            .collect();
+        files.sort_by_key(|(path, _)| *path);
+        let rows: Vec<_> = files
+            .into_iter()
+            .flat_map(|(path, asset)| {
+                crate::asset::string_rows(&asset.data, &asset.metadata)
+                    .into_iter()
+                    .map(move |row| (Some(path), row))
+            })
+            .collect();
+        crate::asset::render_rows(&rows, export.into())
+    }
+
+    /// Finds the loaded asset whose metadata title equals `title`, returning its path and handle.
+    /// When several files share a title, the one with the smallest path wins.
+    ///
+    /// 查找元数据标题等于 `title` 的已加载资源，返回其路径与句柄。多个文件标题相同时返回路径最小的那个。
+    pub fn find_by_title(
+        &self,
+        title: &str,
+        assets: &Assets<crate::MortarAsset>,
+    ) -> Option<(&str, &Handle<crate::MortarAsset>)> {
+        self.assets
+            .iter()
+            .filter(|(_, handle)| {
+                assets
+                    .get(*handle)
+                    .is_some_and(|asset| asset.metadata.title.as_deref() == Some(title))
+            })
+            .min_by(|(a, _), (b, _)| a.cmp(b))
+            .map(|(path, handle)| (path.as_str(), handle))
     }
 }
 
@@ -50,13 +244,65 @@ pub struct MortarRuntime {
     /// Active dialogue states keyed by controller entity.
     pub active_dialogues: HashMap<Entity, crate::dialogue_state::DialogueState>,
     /// The "primary" dialogue entity - receives input by default.
+    /// It holds focus: it is the only dialogue presented and taking user input, see
+    /// [`MortarRuntime::request_focus`].
     pub primary_dialogue: Option<Entity>,
+    /// What a focus request does while another dialogue holds focus.
+    pub focus_contention: FocusContention,
+    /// Dialogues waiting for focus, next first.
+    pub(crate) focus_queue: Vec<Entity>,
+    /// Dialogues that hold still while unfocused, see [`UnfocusedBehavior::Pause`].
+    pub(crate) paused_when_unfocused: HashSet<Entity>,
     /// Pending start requests keyed by controller entity (path, node).
     pub pending_starts: HashMap<Entity, (String, String)>,
     /// Pending jump requests keyed by controller entity (path, node).
     pub pending_jumps: HashMap<Entity, (String, String)>,
+    /// Entry positions of pending starts and jumps, keyed by controller entity.
+    pub pending_entries: HashMap<Entity, crate::MortarNodeEntry>,
+    /// Load status of pending starts, see [`MortarRuntime::pending_start_status`].
+    pub(crate) pending_loads: HashMap<Entity, PendingLoad>,
+    /// Latest stage of every file still loading, keyed by canonical path.
+    pub(crate) load_stages: HashMap<String, crate::MortarLoadStage>,
     /// The function registry for calling Mortar functions.
     pub functions: crate::MortarFunctionRegistry,
+    /// Nodes prepared ahead of their start, keyed by (path, node).
+    pub prepared: HashMap<(String, String), crate::PreparedDialogue>,
+    /// How long prepared nodes stay valid. `None` keeps them until their asset reloads.
+    pub prepared_ttl: Option<Duration>,
+    /// Caps on the runtime's caches, applied every frame.
+    pub trim_policy: MortarTrimPolicy,
+    /// Pagination of choice groups that set no `page_size` of their own. `None` shows all options.
+    pub choice_pagination: Option<crate::ChoicePagination>,
+    /// Capture of choice groups that set no `capture` of their own.
+    pub choice_capture: crate::ChoiceCapture,
+    /// Whether selecting a choice also confirms it.
+    pub choice_confirm: ChoiceConfirmMode,
+    /// How long a choice selection lives before its group is confirmed.
+    pub selection_persistence: SelectionPersistence,
+    /// Whether start requests refuse files whose load-time analysis found validation errors.
+    pub strict_start: crate::MortarStrictStart,
+    /// Whether the direct drive calls, such as [`MortarRuntime::force_set_position`], write the
+    /// lifecycle messages a start request would. Off by default.
+    pub announce_direct_drive: bool,
+    /// Automatic steps a dialogue may take without user input before it halts. `None` never
+    /// halts.
+    pub auto_advance_budget: Option<usize>,
+    /// Automatic steps of each dialogue since its last user input.
+    pub(crate) auto_advance: HashMap<Entity, AutoAdvanceTrail>,
+    /// Options picked with `"remove_after_pick"` and `"scope": "session"`.
+    pub(crate) removed_choices: HashSet<crate::dialogue_state::RemovedChoice>,
+    /// Preparation requests waiting for their asset to load.
+    pub(crate) pending_prepares: Vec<(String, String)>,
+    pub(crate) warm_variables: HashMap<String, crate::MortarVariableState>,
+    pub(crate) warmed_assets: HashMap<String, Vec<UntypedHandle>>,
+    pub(crate) node_parses: u64,
+    pub(crate) advance_gate: AdvanceGate,
+    pub(crate) signals: SignalBoard,
+    pub(crate) rng: rng::SharedRng,
+    pub(crate) capabilities: MortarCapabilities,
+    /// What the text system worked out for the last line it rendered.
+    pub(crate) line_explanation: Option<crate::dialogue::LineExplanation>,
+    pub(crate) direct_drive: DirectDrive,
 }
 
 impl MortarRuntime {
@@ -91,6 +337,37 @@ impl MortarRuntime {
         self.primary_dialogue_state_mut()
     }
 
+    /// Call context of the primary dialogue's current line, passed to bound functions.
+    ///
+    /// 主对话当前行的调用上下文，会传给绑定函数。
+    pub fn call_context(&self) -> crate::MortarCallContext {
+        let Some(state) = self.primary_dialogue_state() else {
+            return crate::MortarCallContext::unknown();
+        };
+        crate::MortarCallContext {
+            source_entity: self
+                .primary_dialogue
+                .and_then(crate::system::entity_to_option),
+            tags: state.tags().to_vec(),
+            overrides: state.overrides().clone(),
+            capabilities: self.capabilities.clone(),
+            ..crate::MortarCallContext::at(
+                state.mortar_path.as_str(),
+                state.current_node.as_str(),
+                state.text_index,
+            )
+        }
+    }
+
+    /// The functions dialogues of `path` call: bindings scoped to the path, then the global
+    /// ones. See [`crate::MortarFunctionRegistry::register_scoped`].
+    ///
+    /// `path` 的对话所调用的函数：先是该路径的作用域绑定，然后是全局绑定。参见
+    /// [`crate::MortarFunctionRegistry::register_scoped`]。
+    pub fn functions_for(&self, path: impl Into<String>) -> crate::MortarScopedFunctions<'_> {
+        self.functions.scoped(path)
+    }
+
     pub fn has_active_dialogues(&self) -> bool {
         !self.active_dialogues.is_empty()
     }
@@ -98,16 +375,54 @@ impl MortarRuntime {
     pub fn active_dialogue_count(&self) -> usize {
         self.active_dialogues.len()
     }
+
+    /// Brings back every option removed for the session by `"remove_after_pick"`.
+    ///
+    /// 恢复所有因 `"remove_after_pick"` 而在会话范围内被移除的选项。
+    pub fn clear_removed_choices(&mut self) {
+        self.removed_choices.clear();
+    }
 }
 
 impl Default for MortarRuntime {
     fn default() -> Self {
+        let rng = rng::SharedRng::default();
+        let mut functions = tags::builtin_functions();
+        rng::register_random_functions(&mut functions, &rng);
         Self {
             active_dialogues: HashMap::new(),
             primary_dialogue: None,
+            focus_contention: FocusContention::default(),
+            focus_queue: Vec::new(),
+            paused_when_unfocused: HashSet::new(),
             pending_starts: HashMap::new(),
             pending_jumps: HashMap::new(),
-            functions: crate::MortarFunctionRegistry::new(),
+            pending_entries: HashMap::new(),
+            pending_loads: HashMap::new(),
+            load_stages: HashMap::new(),
+            functions,
+            prepared: HashMap::new(),
+            prepared_ttl: Some(crate::preparation::DEFAULT_PREPARED_TTL),
+            trim_policy: MortarTrimPolicy::default(),
+            choice_pagination: None,
+            choice_capture: crate::ChoiceCapture::default(),
+            choice_confirm: ChoiceConfirmMode::default(),
+            selection_persistence: SelectionPersistence::default(),
+            strict_start: crate::MortarStrictStart::default(),
+            announce_direct_drive: false,
+            auto_advance_budget: Some(DEFAULT_AUTO_ADVANCE_BUDGET),
+            auto_advance: HashMap::new(),
+            removed_choices: HashSet::new(),
+            pending_prepares: Vec::new(),
+            warm_variables: HashMap::new(),
+            warmed_assets: HashMap::new(),
+            node_parses: 0,
+            advance_gate: AdvanceGate::default(),
+            signals: SignalBoard::default(),
+            rng,
+            capabilities: MortarCapabilities::default(),
+            line_explanation: None,
+            direct_drive: DirectDrive::default(),
         }
     }
 }
@@ -0,0 +1,190 @@
+//! # advance.rs
+//!
+//! # advance.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Answers "what would advancing do right now?" for input prompts. [`AdvanceIntent`] is computed
+//! from the runtime every time it is asked, and `NextText` handling branches on the same value,
+//! so a prompt can never promise something the runtime will not do. The dialogue plugin mirrors
+//! the run and reveal state into the runtime, and [`MortarAdvanceIntent`] republishes the answer
+//! for the primary dialogue each frame for UIs that react to change detection.
+//!
+//! 为输入提示回答“现在推进会发生什么”。[`AdvanceIntent`] 每次查询时都由运行时实时计算，
+//! `NextText` 的处理也依据同一个值分支，因此提示永远不会承诺运行时不会做的事。对话插件会把 run
+//! 和逐字显示的状态同步到运行时，[`MortarAdvanceIntent`] 则每帧重新发布主对话的结果，
+//! 供依赖变更检测的 UI 使用。
+
+use bevy::prelude::*;
+
+use crate::{DialogueState, MortarCommand, MortarRuntime};
+
+/// What an advance request would do for a dialogue.
+///
+/// 推进请求对某个对话会产生的效果。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdvanceIntent {
+    /// The line is still being revealed; advancing reveals the rest of it.
+    ///
+    /// 当前行仍在逐字显示；推进会显示剩余部分。
+    RevealRemaining,
+    /// The reveal holds at a checkpoint of the line; advancing continues it to the next
+    /// checkpoint or the end of the line.
+    ///
+    /// 逐字显示停在该行的检查点；推进会继续显示到下一个检查点或行尾。
+    ContinueReveal,
+    /// Advancing moves to the next line, possibly in the node that follows.
+    ///
+    /// 推进会进入下一行，可能位于后续节点中。
+    NextLine,
+    /// A choice is selected; advancing confirms it with [`MortarCommand::ConfirmChoice`].
+    ///
+    /// 已选中某个选项；推进会通过 [`MortarCommand::ConfirmChoice`] 确认它。
+    ConfirmChoice { index: usize },
+    /// Choices are shown but none is selected, so advancing does nothing. Where
+    /// [`MortarRuntime::selection_confirms`] holds, selecting an option confirms it as well.
+    ///
+    /// 正在显示选项但尚未选中，推进不会产生效果。在 [`MortarRuntime::selection_confirms`] 成立时，
+    /// 选中选项也会同时确认它。
+    NeedsSelection,
+    /// `run` statements are executing. Advance requests are ignored unless `skippable`.
+    ///
+    /// `run` 语句正在执行。除非 `skippable` 为 true，否则推进请求会被忽略。
+    BlockedByRuns { skippable: bool },
+    /// This is the last line; advancing ends the dialogue.
+    ///
+    /// 这是最后一行；推进会结束对话。
+    WouldFinishDialogue,
+    /// There is no dialogue to advance, or it is halted for `reason` until it is resumed.
+    ///
+    /// 没有可推进的对话，或对话因 `reason` 而暂停，直到被恢复。
+    Nothing {
+        reason: Option<crate::MortarHaltReason>,
+    },
+}
+
+impl Default for AdvanceIntent {
+    fn default() -> Self {
+        Self::Nothing { reason: None }
+    }
+}
+
+impl AdvanceIntent {
+    /// The message that carries out this advance for the primary dialogue, if any.
+    ///
+    /// 对主对话执行此次推进所需发送的消息（如有）。
+    pub fn event(self) -> Option<MortarCommand> {
+        match self {
+            Self::RevealRemaining
+            | Self::ContinueReveal
+            | Self::NextLine
+            | Self::WouldFinishDialogue => Some(MortarCommand::next_text()),
+            Self::ConfirmChoice { .. } => Some(MortarCommand::confirm_choice()),
+            Self::NeedsSelection | Self::BlockedByRuns { .. } | Self::Nothing { .. } => None,
+        }
+    }
+}
+
+/// The [`AdvanceIntent`] of the primary dialogue, refreshed every frame by [`crate::MortarPlugin`].
+/// It only counts as changed when the intent does.
+///
+/// 主对话的 [`AdvanceIntent`]，由 [`crate::MortarPlugin`] 每帧刷新。仅在意图变化时才标记为已更改。
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MortarAdvanceIntent {
+    pub intent: AdvanceIntent,
+}
+
+/// Presentation state that gates advancing, mirrored from the dialogue plugin.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct AdvanceGate {
+    pub(crate) runs_executing: bool,
+    pub(crate) revealing: bool,
+    /// Set by `NextText` when it lands on [`AdvanceIntent::RevealRemaining`], cleared once no
+    /// target is revealing.
+    pub(crate) finish_reveal: bool,
+    /// Whether a text target holds at a reveal checkpoint.
+    pub(crate) awaiting: bool,
+    /// Requests to continue past a checkpoint so far; each reveal driver remembers the last one
+    /// it saw.
+    pub(crate) continue_requests: u64,
+}
+
+impl AdvanceGate {
+    /// Asks the reveals holding at a checkpoint to continue.
+    pub(crate) fn request_continue(&mut self) {
+        self.continue_requests = self.continue_requests.wrapping_add(1);
+    }
+}
+
+/// Classifies the dialogue flow once presentation no longer holds the line.
+fn flow_intent(state: &DialogueState) -> AdvanceIntent {
+    if state.has_next_text() {
+        return AdvanceIntent::NextLine;
+    }
+    if state.has_choices() && !state.choices_broken {
+        return match state.selected_choice {
+            Some(index) if !state.disabled_choices.contains(&index) => {
+                AdvanceIntent::ConfirmChoice { index }
+            }
+            _ => AdvanceIntent::NeedsSelection,
+        };
+    }
+    match state.get_next_node() {
+        Some(next) if next != "return" => AdvanceIntent::NextLine,
+        _ => AdvanceIntent::WouldFinishDialogue,
+    }
+}
+
+impl MortarRuntime {
+    /// What advancing the primary dialogue would do right now.
+    ///
+    /// 当前推进主对话会产生的效果。
+    pub fn advance_intent(&self) -> AdvanceIntent {
+        self.primary_dialogue
+            .map_or(AdvanceIntent::default(), |entity| {
+                self.advance_intent_for(entity)
+            })
+    }
+
+    /// What advancing the dialogue of `entity` would do right now.
+    ///
+    /// 当前推进 `entity` 的对话会产生的效果。
+    pub fn advance_intent_for(&self, entity: Entity) -> AdvanceIntent {
+        let Some(state) = self.active_dialogues.get(&entity) else {
+            return AdvanceIntent::default();
+        };
+        if let Some(intent) = self.halted_intent(entity) {
+            return intent;
+        }
+        if self.pending_jumps.contains_key(&entity) {
+            return AdvanceIntent::default();
+        }
+        // Runs and text targets only ever present the primary dialogue.
+        //
+        // run 与文本目标只会呈现主对话。
+        if self.primary_dialogue == Some(entity) {
+            if self.advance_gate.runs_executing {
+                return AdvanceIntent::BlockedByRuns { skippable: false };
+            }
+            if self.advance_gate.awaiting {
+                return AdvanceIntent::ContinueReveal;
+            }
+            if self.advance_gate.revealing {
+                return AdvanceIntent::RevealRemaining;
+            }
+        }
+        flow_intent(state)
+    }
+}
+
+/// Republishes the primary dialogue's intent into [`MortarAdvanceIntent`].
+pub(crate) fn update_advance_intent(
+    runtime: Res<MortarRuntime>,
+    mut intent: ResMut<MortarAdvanceIntent>,
+) {
+    intent.set_if_neq(MortarAdvanceIntent {
+        intent: runtime.advance_intent(),
+    });
+}
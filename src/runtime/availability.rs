@@ -0,0 +1,264 @@
+//! # availability.rs
+//!
+//! # availability.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Gating of conversation starts. Game code registers rules per (path, node) in
+//! [`MortarAvailability`]: a cooldown measured from the last time the node's dialogue finished, a
+//! cap on starts per session, and custom checks against the world. The start handling consults
+//! the rules before a start activates a node on an idle controller; jumps within a running
+//! conversation are never gated. A blocked start writes [`MortarStartSuppressed`], and
+//! [`SuppressedStartPolicy`] decides whether the request is dropped or kept waiting until the
+//! rules let it through. Custom checks need the world, so they run once a frame ahead of the
+//! start handling and the gate reads their latest verdict.
+//!
+//! 对话开始的准入控制。游戏代码在 [`MortarAvailability`] 中按 (路径, 节点) 注册规则：从该节点的
+//! 对话上次结束时开始计算的冷却时间、每个会话内的开始次数上限，以及针对世界的自定义检查。开始请求
+//! 在空闲控制器上激活节点之前会参考这些规则；正在进行的对话内部的跳转从不受限。被拦下的开始请求会
+//! 写出 [`MortarStartSuppressed`]，并由 [`SuppressedStartPolicy`] 决定丢弃该请求，还是让它一直等待
+//! 到规则放行。自定义检查需要访问世界，因此它们每帧在开始处理之前运行一次，准入时读取最新的结果。
+
+use bevy::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+use crate::MortarDialogueFinished;
+
+/// A check of whether a conversation may start, given read access to the world.
+type CustomCheck = Box<dyn Fn(&World) -> bool + Send + Sync>;
+
+/// What happens to a start request the rules block.
+///
+/// 被规则拦下的开始请求的处理方式。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SuppressedStartPolicy {
+    /// The request is dropped.
+    ///
+    /// 丢弃该请求。
+    #[default]
+    Discard,
+    /// The request stays pending and starts once the rules allow it. It is reported once.
+    ///
+    /// 请求保持等待，规则允许时再开始。只报告一次。
+    Retry,
+}
+
+/// Why a start request was suppressed.
+///
+/// 开始请求被拦下的原因。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MortarStartSuppression {
+    /// The node's dialogue finished less than its cooldown ago.
+    ///
+    /// 该节点的对话结束至今尚未超过其冷却时间。
+    Cooldown { remaining: Duration },
+    /// The node already started `max` times this session.
+    ///
+    /// 该节点在本会话中已开始过 `max` 次。
+    SessionLimit { max: u32 },
+    /// A custom check of the rule failed.
+    ///
+    /// 规则的某个自定义检查未通过。
+    Unavailable,
+}
+
+/// Event emitted instead of starting a node whose availability rules block it.
+///
+/// 当节点的准入规则阻止其开始时，代替开始而发出的事件。
+#[derive(Message, Debug, Clone, PartialEq, Eq)]
+pub struct MortarStartSuppressed {
+    pub entity: Option<Entity>,
+    pub mortar_path: String,
+    pub node: String,
+    pub reason: MortarStartSuppression,
+}
+
+/// The availability rule of one node, see [`MortarAvailability::rule`].
+///
+/// 单个节点的准入规则，参见 [`MortarAvailability::rule`]。
+#[derive(Default)]
+pub struct MortarAvailabilityRule {
+    cooldown: Option<Duration>,
+    max_per_session: Option<u32>,
+    custom: Vec<CustomCheck>,
+    /// Whether a custom check failed when they last ran.
+    custom_blocked: bool,
+}
+
+impl MortarAvailabilityRule {
+    /// Blocks starts until `cooldown` has passed since the node's dialogue last finished.
+    ///
+    /// 在该节点的对话上次结束后经过 `cooldown` 之前，阻止其开始。
+    pub fn cooldown(&mut self, cooldown: Duration) -> &mut Self {
+        self.cooldown = Some(cooldown);
+        self
+    }
+
+    /// Blocks starts once the node started `max` times this session.
+    ///
+    /// 该节点在本会话中开始满 `max` 次后，阻止其开始。
+    pub fn max_per_session(&mut self, max: u32) -> &mut Self {
+        self.max_per_session = Some(max);
+        self
+    }
+
+    /// Blocks starts while `check` returns false. Checks run once a frame, before starts are
+    /// handled.
+    ///
+    /// 当 `check` 返回 false 时阻止开始。检查每帧在处理开始请求之前运行一次。
+    pub fn custom(&mut self, check: impl Fn(&World) -> bool + Send + Sync + 'static) -> &mut Self {
+        self.custom.push(Box::new(check));
+        self
+    }
+}
+
+/// Availability rules of conversation starts, and the history they are checked against.
+///
+/// 对话开始的准入规则，以及用于检查这些规则的历史记录。
+#[derive(Resource, Default)]
+pub struct MortarAvailability {
+    /// What happens to a start request the rules block.
+    pub policy: SuppressedStartPolicy,
+    rules: HashMap<(String, String), MortarAvailabilityRule>,
+    /// When the dialogue of each (path, node) last finished, by the virtual clock.
+    finished_at: HashMap<(String, String), Duration>,
+    /// Starts of each (path, node) this session.
+    starts: HashMap<(String, String), u32>,
+    /// The (path, node) each running conversation started at, keyed by controller entity.
+    running: HashMap<Entity, (String, String)>,
+    /// Controllers whose retried start was already reported.
+    reported: HashSet<Entity>,
+}
+
+impl MortarAvailability {
+    /// The rule of `node` in `path`, created empty on first use. The path is matched by its
+    /// canonical key, see [`crate::MortarRegistry::canonical_key`].
+    ///
+    /// `path` 中 `node` 的规则，首次使用时创建空规则。路径按其规范化键匹配，参见
+    /// [`crate::MortarRegistry::canonical_key`]。
+    pub fn rule(
+        &mut self,
+        path: impl Into<String>,
+        node: impl Into<String>,
+    ) -> &mut MortarAvailabilityRule {
+        self.rules.entry((path.into(), node.into())).or_default()
+    }
+
+    /// Drops the rule of `node` in `path`, returning whether there was one.
+    ///
+    /// 移除 `path` 中 `node` 的规则，并返回之前是否存在。
+    pub fn remove_rule(&mut self, path: &str, node: &str) -> bool {
+        self.rules
+            .remove(&(path.to_owned(), node.to_owned()))
+            .is_some()
+    }
+
+    /// Starts a new session: start counts and cooldowns begin again.
+    ///
+    /// 开始新的会话：开始次数与冷却时间重新计算。
+    pub fn reset_session(&mut self) {
+        self.finished_at.clear();
+        self.starts.clear();
+    }
+
+    /// How many times `node` in `path` started this session.
+    ///
+    /// `path` 中的 `node` 在本会话中开始的次数。
+    pub fn starts_this_session(
+        &self,
+        registry: &crate::MortarRegistry,
+        path: &str,
+        node: &str,
+    ) -> u32 {
+        let key = (registry.canonical_key(path), node.to_owned());
+        self.starts.get(&key).copied().unwrap_or_default()
+    }
+
+    fn rule_for(
+        &self,
+        registry: &crate::MortarRegistry,
+        path: &str,
+        node: &str,
+    ) -> Option<&MortarAvailabilityRule> {
+        self.rules
+            .iter()
+            .find(|((rule_path, rule_node), _)| {
+                rule_node == node && registry.canonical_key(rule_path) == path
+            })
+            .map(|(_, rule)| rule)
+    }
+
+    /// Why `node` in the canonical `path` may not start at `now`, if it may not.
+    pub(crate) fn check(
+        &self,
+        registry: &crate::MortarRegistry,
+        path: &str,
+        node: &str,
+        now: Duration,
+    ) -> Option<MortarStartSuppression> {
+        let rule = self.rule_for(registry, path, node)?;
+        let key = (path.to_owned(), node.to_owned());
+        if let (Some(cooldown), Some(finished)) = (rule.cooldown, self.finished_at.get(&key)) {
+            let elapsed = now.saturating_sub(*finished);
+            if elapsed < cooldown {
+                let remaining = cooldown - elapsed;
+                return Some(MortarStartSuppression::Cooldown { remaining });
+            }
+        }
+        if let Some(max) = rule.max_per_session
+            && self.starts.get(&key).copied().unwrap_or_default() >= max
+        {
+            return Some(MortarStartSuppression::SessionLimit { max });
+        }
+        rule.custom_blocked
+            .then_some(MortarStartSuppression::Unavailable)
+    }
+
+    /// Counts a start of `node` in the canonical `path`.
+    pub(crate) fn note_started(&mut self, entity: Entity, path: &str, node: &str) {
+        self.reported.remove(&entity);
+        let key = (path.to_owned(), node.to_owned());
+        *self.starts.entry(key.clone()).or_default() += 1;
+        self.running.insert(entity, key);
+    }
+
+    /// Whether the suppression of a start on `entity` should be reported. A fresh request is
+    /// always reported; a retried one only the first time.
+    pub(crate) fn should_report(&mut self, entity: Entity, fresh: bool) -> bool {
+        if fresh {
+            self.reported.remove(&entity);
+        }
+        self.policy == SuppressedStartPolicy::Discard || self.reported.insert(entity)
+    }
+}
+
+/// Runs the custom checks of every rule and keeps their verdicts for the start handling.
+pub(crate) fn refresh_custom_availability(world: &mut World) {
+    world.resource_scope(|world, mut availability: Mut<MortarAvailability>| {
+        for rule in availability.rules.values_mut() {
+            if !rule.custom.is_empty() {
+                rule.custom_blocked = !rule.custom.iter().all(|check| check(world));
+            }
+        }
+    });
+}
+
+/// Notes when dialogues finished, for the cooldowns. A conversation that jumped on finishes both
+/// the node it ended in and the node it started at.
+pub(crate) fn record_finished_dialogues(
+    mut finished: MessageReader<MortarDialogueFinished>,
+    mut availability: ResMut<MortarAvailability>,
+    time: Res<Time>,
+) {
+    for message in finished.read() {
+        let entity = message.entity.unwrap_or(Entity::PLACEHOLDER);
+        if let Some(started) = availability.running.remove(&entity) {
+            availability.finished_at.insert(started, time.elapsed());
+        }
+        let key = (message.mortar_path.clone(), message.node.clone());
+        availability.finished_at.insert(key, time.elapsed());
+    }
+}
@@ -0,0 +1,169 @@
+//! # capabilities.rs
+//!
+//! # capabilities.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Platform and accessibility flags for adaptive content. The game fills [`MortarCapabilities`]
+//! at startup and updates it whenever the player's setup changes, e.g. when a controller is
+//! plugged in. Scripts see it three ways: the built-in [`CAPABILITY_FUNCTION`] in conditions,
+//! `{cap:key}` placeholders in text, and `requires: [...]` arrays on texts, lines and choice
+//! options, which hide the item like a failed condition while a listed capability is missing.
+//! The plugin mirrors the resource into the runtime, and a change re-renders the current line and
+//! rebuilds the presented choices.
+//!
+//! 用于自适应内容的平台与无障碍标志。游戏在启动时填写 [`MortarCapabilities`]，并在玩家的设备
+//! 情况变化时（例如插入手柄）更新它。脚本可以通过三种方式读取：条件中的内置函数
+//! [`CAPABILITY_FUNCTION`]、文本中的 `{cap:key}` 占位符，以及文本、line 与选项上的
+//! `requires: [...]` 数组——只要其中列出的能力缺失，该项就会像条件不成立一样被隐藏。插件会把该资源
+//! 同步到运行时，能力变化时会重新渲染当前行并重建已呈现的选项。
+
+use bevy::prelude::*;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::MortarRuntime;
+use crate::binder::with_current_context;
+use crate::{MortarCallContext, MortarValue};
+
+/// Built-in script function `cap("name")`, true when [`MortarCapabilities::has`] is. Scripts
+/// declare it like any bound function: `fn cap(name: String) -> Boolean`.
+///
+/// 内置脚本函数 `cap("name")`，当 [`MortarCapabilities::has`] 为真时为真。脚本像其他绑定函数一样
+/// 声明它：`fn cap(name: String) -> Boolean`。
+pub const CAPABILITY_FUNCTION: &str = "cap";
+
+/// Prefix of the placeholders that show a capability, as in `{cap:input}`.
+const PLACEHOLDER_PREFIX: &str = "cap:";
+
+/// What the player's platform and settings support: plain flags such as `reduced_motion`, and
+/// keyed values such as `input` → `gamepad`.
+///
+/// 玩家的平台与设置所支持的能力：诸如 `reduced_motion` 的普通标志，以及诸如 `input` → `gamepad`
+/// 的键值。
+#[derive(Resource, Debug, Clone, Default, PartialEq, Eq)]
+pub struct MortarCapabilities {
+    flags: BTreeSet<String>,
+    values: BTreeMap<String, String>,
+}
+
+impl MortarCapabilities {
+    /// Sets the flag `flag`.
+    ///
+    /// 设置标志 `flag`。
+    pub fn insert(&mut self, flag: impl Into<String>) -> &mut Self {
+        self.flags.insert(flag.into());
+        self
+    }
+
+    /// Clears the flag `flag`, returning whether it was set.
+    ///
+    /// 清除标志 `flag`，并返回它之前是否已设置。
+    pub fn remove(&mut self, flag: &str) -> bool {
+        self.flags.remove(flag)
+    }
+
+    /// Sets `key` to `value`, replacing the previous value.
+    ///
+    /// 将 `key` 设为 `value`，替换之前的值。
+    pub fn set(&mut self, key: impl Into<String>, value: impl Into<String>) -> &mut Self {
+        self.values.insert(key.into(), value.into());
+        self
+    }
+
+    /// Clears `key`, returning its value.
+    ///
+    /// 清除 `key`，并返回其值。
+    pub fn unset(&mut self, key: &str) -> Option<String> {
+        self.values.remove(key)
+    }
+
+    /// The value of `key`.
+    ///
+    /// `key` 的值。
+    pub fn value(&self, key: &str) -> Option<&str> {
+        self.values.get(key).map(String::as_str)
+    }
+
+    /// Whether `name` is a set flag or the value of some key, so `"gamepad"` holds while
+    /// `input` is `gamepad`.
+    ///
+    /// `name` 是否为已设置的标志或某个键的值；例如 `input` 为 `gamepad` 时 `"gamepad"` 成立。
+    pub fn has(&self, name: &str) -> bool {
+        self.flags.contains(name) || self.values.values().any(|value| value == name)
+    }
+
+    /// Why an item requiring `requires` is hidden, if a capability is missing.
+    pub(crate) fn hidden_reason(&self, requires: &[String]) -> Option<String> {
+        let missing = requires.iter().find(|name| !self.has(name))?;
+        Some(format!("capability '{missing}' is missing"))
+    }
+
+    /// Whether the `requires` array of a content item or an option is met.
+    pub(crate) fn allows_item(&self, item: &Value) -> bool {
+        required_capabilities(item)
+            .iter()
+            .all(|name| self.has(name))
+    }
+
+    /// Text for the placeholder named `name`: the value of the key, `true` for a set flag, and
+    /// empty otherwise. `None` when `name` is not a capability placeholder.
+    fn placeholder(&self, name: &str) -> Option<String> {
+        let key = name.strip_prefix(PLACEHOLDER_PREFIX)?;
+        Some(match self.value(key) {
+            Some(value) => value.to_owned(),
+            None if self.flags.contains(key) => true.to_string(),
+            None => String::new(),
+        })
+    }
+}
+
+/// Reads the `requires` array of a content item or an option.
+pub(crate) fn required_capabilities(item: &Value) -> Vec<String> {
+    item.get("requires")
+        .and_then(Value::as_array)
+        .map(|names| {
+            names
+                .iter()
+                .filter_map(Value::as_str)
+                .map(str::to_owned)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Resolves a `{cap:key}` placeholder against the capabilities of the installed call context.
+pub(crate) fn capability_placeholder(name: &str) -> Option<String> {
+    if !name.starts_with(PLACEHOLDER_PREFIX) {
+        return None;
+    }
+    with_current_context(|context| context.capabilities.placeholder(name))
+}
+
+pub(super) fn has_capability(context: &MortarCallContext, args: &[MortarValue]) -> MortarValue {
+    let Some(name) = args.first() else {
+        return false.into();
+    };
+    context.capabilities.has(&name.to_display_string()).into()
+}
+
+impl MortarRuntime {
+    /// The capabilities scripts currently see, mirrored from [`MortarCapabilities`].
+    ///
+    /// 脚本当前看到的能力，由 [`MortarCapabilities`] 同步而来。
+    pub fn capabilities(&self) -> &MortarCapabilities {
+        &self.capabilities
+    }
+}
+
+/// Mirrors [`MortarCapabilities`] into the runtime when the game changes it.
+pub(crate) fn sync_capabilities(
+    capabilities: Res<MortarCapabilities>,
+    mut runtime: ResMut<MortarRuntime>,
+) {
+    if capabilities.is_changed() && runtime.capabilities != *capabilities {
+        runtime.capabilities = capabilities.clone();
+    }
+}
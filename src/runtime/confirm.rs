@@ -0,0 +1,375 @@
+//! # confirm.rs
+//!
+//! # confirm.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Answers "what would confirming this option do?" for UIs that preview the outcome before the
+//! player commits, e.g. to show "ends conversation" or to preload the next node. The
+//! [`ConfirmOutcome`] comes from the same resolution `ConfirmChoice` handling applies, and the real
+//! confirm publishes it in [`crate::MortarChoiceResolved`], so a preview always matches what
+//! happens next against unchanged state. The resolution writes the capture inside a variable
+//! transaction and rolls it back, so watchers never see it, and describes the runs the confirm
+//! dispatches and the events they write instead of dispatching them. Bound functions are never
+//! called: those the confirm leads to calling are listed in
+//! [`ConfirmOutcome::unsimulated_calls`], and whatever game state they change is not simulated.
+//!
+//! 为需要在玩家确认前预览结果的 UI 回答“确认这个选项会发生什么”，例如显示“结束对话”或预加载下一
+//! 个节点。[`ConfirmOutcome`] 来自 `ConfirmChoice` 处理时所应用的同一次结算，真正的确认会在
+//! [`crate::MortarChoiceResolved`] 中发布它，因此在状态未变时，预览总是与随后发生的事一致。结算会在
+//! 变量事务中写入捕获并将其回滚，因此观察者永远看不到它；确认所分发的 run 及其写出的事件只会被描述，
+//! 而不会被分发。绑定函数从不会被调用：确认将导致调用的函数会列在
+//! [`ConfirmOutcome::unsimulated_calls`] 中，它们对游戏状态的改动不会被模拟。
+
+use bevy::asset::Assets;
+use bevy::prelude::*;
+
+use crate::dialogue::{arg_value, capture_writes};
+use crate::eval::evaluate_variable_condition;
+use crate::{
+    ChoiceCapture, DialogueState, MortarAsset, MortarDialogueVariables, MortarEventAction,
+    MortarNodeEntry, MortarRegistry, MortarRuntime, MortarVariableState, MortarVariableValue,
+    TextData,
+};
+
+/// Where confirming a choice takes the dialogue.
+///
+/// 确认选项后对话的去向。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfirmEffect {
+    /// The dialogue finishes, through `return`, an unknown action or an option with no `next`.
+    ///
+    /// 对话结束：通过 `return`、未知动作或没有 `next` 的选项。
+    EndDialogue,
+    /// The group closes and the dialogue continues with the line after it, or leaves the node as
+    /// at its end when there is none.
+    ///
+    /// 选项组关闭，对话继续显示其后的那一行；若没有后续行，则像到达节点末尾一样离开节点。
+    Break,
+    /// The option's nested choices are presented.
+    ///
+    /// 显示该选项的嵌套选项。
+    EnterNested,
+    /// The dialogue jumps to `node`, entering it at `entry` when the option names one.
+    ///
+    /// 对话跳转到 `node`；若选项指定了入口，则从 `entry` 进入。
+    Jump {
+        node: String,
+        entry: Option<MortarNodeEntry>,
+    },
+    /// The confirm is rejected and changes nothing: the group is not presented, a jump is still
+    /// pending, or the option is disabled or out of range.
+    ///
+    /// 确认被拒绝，不做任何改动：选项组未显示、仍有待处理的跳转，或选项被禁用或索引越界。
+    Rejected,
+}
+
+/// Everything confirming an option does to a dialogue.
+///
+/// 确认选项对对话产生的全部效果。
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfirmOutcome {
+    /// Declared index of the option in its group.
+    ///
+    /// 选项在其选项组中的声明索引。
+    pub index: usize,
+    pub text: String,
+    pub effect: ConfirmEffect,
+    /// The variable and value written by the capture, see [`crate::MortarChoiceCaptured`].
+    ///
+    /// 捕获写入的变量与值，参见 [`crate::MortarChoiceCaptured`]。
+    pub capture: Option<(String, MortarVariableValue)>,
+    /// Whether the option is `remove_after_pick` and disappears from its group.
+    ///
+    /// 该选项是否为 `remove_after_pick`，确认后会从选项组中消失。
+    pub removed_after_pick: bool,
+    /// Runs the confirm dispatches before the next line renders, under the names they execute
+    /// as: the runs a jump's entry skipped over when it executes them.
+    ///
+    /// 确认在下一行显示之前分发的 run，使用其实际执行时的名称：即跳转入口执行被跳过的 run 时
+    /// 的那些 run。
+    pub runs: Vec<String>,
+    /// Game events the [`Self::runs`] write, in order, with the arguments a
+    /// [`crate::MortarGameEvent`] carries.
+    ///
+    /// [`Self::runs`] 写出的游戏事件，按顺序排列，参数与 [`crate::MortarGameEvent`] 携带的一致。
+    pub events: Vec<MortarEventAction>,
+    /// Bound functions the confirm leads to calling, which the simulation does not call: those in
+    /// the condition and placeholders of the line it lands on, or in the conditions of the nested
+    /// options it presents. What they do to the game state is not simulated.
+    ///
+    /// 确认将导致调用、但模拟不会调用的绑定函数：落点行的条件与占位符中的函数，或所显示的嵌套选项
+    /// 的条件中的函数。它们对游戏状态的改动不会被模拟。
+    pub unsimulated_calls: Vec<String>,
+}
+
+impl ConfirmOutcome {
+    fn rejected(index: usize, text: Option<&str>) -> Self {
+        Self {
+            index,
+            text: text.unwrap_or_default().to_owned(),
+            effect: ConfirmEffect::Rejected,
+            capture: None,
+            removed_after_pick: false,
+            runs: Vec::new(),
+            events: Vec::new(),
+            unsimulated_calls: Vec::new(),
+        }
+    }
+}
+
+/// Resolves confirming option `index` of the current group, without touching the state. The
+/// capture is written to `variables` inside a transaction that is rolled back before returning.
+pub(crate) fn resolve_confirm(
+    state: &DialogueState,
+    index: usize,
+    capture: &ChoiceCapture,
+    asset: Option<&MortarAsset>,
+    variables: Option<&mut MortarVariableState>,
+) -> Option<ConfirmOutcome> {
+    let choice = state.get_choices()?.get(index)?;
+    let effect = match (&choice.action, &choice.choice, &choice.next) {
+        (Some(action), _, _) if action == "break" => ConfirmEffect::Break,
+        (Some(_), _, _) => ConfirmEffect::EndDialogue,
+        (None, Some(_), _) => ConfirmEffect::EnterNested,
+        (None, None, Some(next)) if next != "return" => ConfirmEffect::Jump {
+            node: next.clone(),
+            entry: state.choice_entry(index),
+        },
+        (None, None, _) => ConfirmEffect::EndDialogue,
+    };
+    let mut outcome = ConfirmOutcome {
+        index,
+        text: choice.text.clone(),
+        effect,
+        capture: state.choice_capture(index, capture),
+        removed_after_pick: state.choice_removal(index).is_some(),
+        runs: Vec::new(),
+        events: Vec::new(),
+        unsimulated_calls: Vec::new(),
+    };
+
+    let mut calls = Vec::new();
+    match &outcome.effect {
+        ConfirmEffect::Break => {
+            if let Some(line) = state
+                .next_text_index()
+                .and_then(|next| state.text_items().get(next))
+            {
+                line_calls(line, &mut calls);
+            }
+        }
+        ConfirmEffect::Jump { node, entry } => {
+            if let Some(asset) = asset
+                && let Some(mut destination) = destination(state, node, *entry, asset)
+            {
+                outcome.runs = destination
+                    .take_entry_runs()
+                    .into_iter()
+                    .map(|item| {
+                        let name = destination.overrides().get(&item.name);
+                        name.unwrap_or(&item.name).clone()
+                    })
+                    .collect();
+                outcome.events = run_events(&outcome.runs, asset);
+                if let Some(line) = destination.current_text_data() {
+                    line_calls(line, &mut calls);
+                }
+            }
+        }
+        ConfirmEffect::EnterNested => {
+            let nested = choice.choice.iter().flatten();
+            let conditions: Vec<_> = nested
+                .filter_map(|option| option.condition.as_ref())
+                .collect();
+            nested_calls(&conditions, &outcome, variables, &mut calls);
+        }
+        ConfirmEffect::EndDialogue | ConfirmEffect::Rejected => {}
+    }
+    outcome.unsimulated_calls = calls;
+    Some(outcome)
+}
+
+/// The node a jump lands in, positioned at `entry` as activating it would.
+fn destination(
+    state: &DialogueState,
+    node: &str,
+    entry: Option<MortarNodeEntry>,
+    asset: &MortarAsset,
+) -> Option<DialogueState> {
+    let overrides = asset
+        .metadata
+        .overrides_of(node)
+        .cloned()
+        .unwrap_or_default();
+    let mut destination = DialogueState::from_parsed(
+        state.mortar_path.clone(),
+        node.to_owned(),
+        asset.parsed_node(node)?,
+    )
+    .with_overrides(overrides);
+    if let Some(entry) = entry {
+        destination.enter_at(entry);
+    }
+    Some(destination)
+}
+
+/// Events dispatching `runs` writes. A single run may name a timeline, whose `run` steps are
+/// dispatched in turn; in a sequence of several runs only events are dispatched.
+fn run_events(runs: &[String], asset: &MortarAsset) -> Vec<MortarEventAction> {
+    let steps: Vec<&str> = match runs {
+        [run] if asset.event_def(run).is_none() => asset
+            .timeline_def(run)
+            .into_iter()
+            .flat_map(|timeline| &timeline.statements)
+            .filter(|statement| statement.stmt_type == "run")
+            .filter_map(|statement| statement.event_name.as_deref())
+            .collect(),
+        runs => runs.iter().map(String::as_str).collect(),
+    };
+    steps
+        .into_iter()
+        .filter_map(|step| asset.event_def(step))
+        .map(|event| MortarEventAction {
+            action_name: event.action.action_type.clone(),
+            args: event
+                .action
+                .args
+                .iter()
+                .map(|arg| arg_value(arg).to_owned())
+                .collect(),
+        })
+        .collect()
+}
+
+/// Notes the functions the conditions of nested options call. A condition naming a variable,
+/// including the one the capture writes, reads it instead, so the capture is previewed first.
+fn nested_calls(
+    conditions: &[&mortar_compiler::Condition],
+    outcome: &ConfirmOutcome,
+    variables: Option<&mut MortarVariableState>,
+    calls: &mut Vec<String>,
+) {
+    let mut note = |variables: Option<&MortarVariableState>| {
+        for condition in conditions {
+            if variables
+                .is_none_or(|variables| evaluate_variable_condition(condition, variables).is_none())
+            {
+                note_call(calls, &condition.condition_type);
+            }
+        }
+    };
+    let Some(variables) = variables else {
+        note(None);
+        return;
+    };
+    let previewed = variables.preview(|variables| {
+        if let Some((variable, value)) = &outcome.capture {
+            for (name, value) in capture_writes(variable, value, outcome.index) {
+                variables.set(&name, value);
+            }
+        }
+        note(Some(variables));
+    });
+    if previewed.is_err() {
+        note(Some(variables));
+    }
+}
+
+/// Notes the functions rendering `line` calls: in its condition and its placeholders.
+fn line_calls(line: &TextData, calls: &mut Vec<String>) {
+    if let Some(condition) = &line.condition {
+        condition_calls(condition, calls);
+    }
+    for part in line.interpolated_parts.iter().flatten() {
+        if part.part_type == "expression"
+            && let Some(name) = &part.function_name
+        {
+            note_call(calls, name);
+        }
+    }
+}
+
+fn condition_calls(condition: &mortar_compiler::IfCondition, calls: &mut Vec<String>) {
+    if condition.cond_type == "func_call"
+        && let Some(name) = condition.operand.as_ref().and_then(|op| op.value.as_ref())
+    {
+        note_call(calls, name);
+    }
+    for part in [&condition.left, &condition.right, &condition.operand]
+        .into_iter()
+        .flatten()
+    {
+        condition_calls(part, calls);
+    }
+}
+
+fn note_call(calls: &mut Vec<String>, name: &str) {
+    if !calls.iter().any(|call| call == name) {
+        calls.push(name.to_owned());
+    }
+}
+
+impl MortarRuntime {
+    /// What confirming option `index` of the primary dialogue would do, without doing it. Nothing
+    /// outside the outcome changes: the capture is rolled back in `variables` before returning,
+    /// runs and their events are only described, and no bound function is called. A confirm that
+    /// would be rejected resolves to [`ConfirmEffect::Rejected`].
+    ///
+    /// 确认主对话的第 `index` 个选项会产生的效果，但不实际执行。结果之外的一切都不会改变：捕获会在
+    /// 返回前于 `variables` 中回滚，run 及其事件只会被描述，也不会调用任何绑定函数。会被拒绝的确认
+    /// 结算为 [`ConfirmEffect::Rejected`]。
+    pub fn simulate_confirm(
+        &mut self,
+        index: usize,
+        registry: &MortarRegistry,
+        assets: &Assets<MortarAsset>,
+        variables: &mut MortarDialogueVariables,
+    ) -> ConfirmOutcome {
+        match self.primary_dialogue {
+            Some(entity) => self.simulate_confirm_for(entity, index, registry, assets, variables),
+            None => ConfirmOutcome::rejected(index, None),
+        }
+    }
+
+    /// What confirming option `index` of the dialogue of `entity` would do, without doing it, see
+    /// [`Self::simulate_confirm`].
+    ///
+    /// 确认 `entity` 对话的第 `index` 个选项会产生的效果，但不实际执行，参见
+    /// [`Self::simulate_confirm`]。
+    pub fn simulate_confirm_for(
+        &mut self,
+        entity: Entity,
+        index: usize,
+        registry: &MortarRegistry,
+        assets: &Assets<MortarAsset>,
+        variables: &mut MortarDialogueVariables,
+    ) -> ConfirmOutcome {
+        let Some(state) = self.active_dialogues.get(&entity) else {
+            return ConfirmOutcome::rejected(index, None);
+        };
+        let text = state
+            .get_choices()
+            .and_then(|choices| choices.get(index))
+            .map(|choice| choice.text.as_str());
+        if self.pending_jumps.contains_key(&entity)
+            || !state.choices_presentable()
+            || state.disabled_choices.contains(&index)
+        {
+            return ConfirmOutcome::rejected(index, text);
+        }
+        let asset = registry
+            .get(&state.mortar_path)
+            .and_then(|handle| assets.get(handle));
+        resolve_confirm(
+            state,
+            index,
+            &self.choice_capture,
+            asset,
+            variables.state.as_mut(),
+        )
+        .unwrap_or_else(|| ConfirmOutcome::rejected(index, text))
+    }
+}
@@ -0,0 +1,93 @@
+//! # confirm_mode.rs
+//!
+//! # confirm_mode.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Decides whether selecting a choice also confirms it. Gamepad and keyboard players move a
+//! selection and then confirm it, while a mouse click is expected to pick the option outright.
+//! [`ChoiceConfirmMode`] makes that explicit. A one-step selection still passes through the
+//! selected state: `SelectChoice` handling marks the option and announces it, then confirms it
+//! right away, so lifecycle messages arrive in the same order as with two steps, only within one
+//! frame.
+//!
+//! 决定选中选项时是否同时确认它。手柄与键盘玩家先移动选中项再确认，而鼠标点击则应直接选定选项。
+//! [`ChoiceConfirmMode`] 将这一点明确化。一步选择仍会经过选中状态：处理 `SelectChoice` 时先标记并
+//! 宣布该选项，再立即确认它，因此生命周期消息的顺序与两步模式相同，只是发生在同一帧内。
+
+use super::{MortarCapabilities, MortarRuntime};
+
+/// Capability key whose value names the active input device, see [`ChoiceInputSource::detect`].
+///
+/// 其值表示当前输入设备的能力键，参见 [`ChoiceInputSource::detect`]。
+pub const INPUT_CAPABILITY: &str = "input";
+
+/// How a `SelectChoice` relates to confirming the option.
+///
+/// `SelectChoice` 与确认选项之间的关系。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ChoiceConfirmMode {
+    /// Selecting only marks the option; a `ConfirmChoice` commits it.
+    ///
+    /// 选中只会标记选项；由 `ConfirmChoice` 提交。
+    #[default]
+    TwoStep,
+    /// Selecting an enabled option confirms it in the same frame.
+    ///
+    /// 选中可用选项时在同一帧内确认它。
+    OneStep,
+    /// One step for pointer input and two steps for navigation input. The source comes from the
+    /// `SelectChoice` event, or from [`ChoiceInputSource::detect`] when the event names none.
+    ///
+    /// 指针输入为一步，导航输入为两步。输入来源取自 `SelectChoice` 事件；事件未指定时由
+    /// [`ChoiceInputSource::detect`] 判断。
+    PerSource,
+}
+
+/// The kind of input a selection came from.
+///
+/// 选择所来自的输入类型。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChoiceInputSource {
+    /// Mouse or touch: the option is picked where it is pressed.
+    ///
+    /// 鼠标或触摸：在按下的位置直接选定选项。
+    Pointer,
+    /// Gamepad or keyboard: a selection moves between options before it is confirmed.
+    ///
+    /// 手柄或键盘：选中项先在选项之间移动，然后再确认。
+    Navigation,
+}
+
+impl ChoiceInputSource {
+    /// The source implied by the [`INPUT_CAPABILITY`] value: `mouse`, `touch` and `pointer` are
+    /// [`Self::Pointer`], anything else or no value is [`Self::Navigation`].
+    ///
+    /// 由 [`INPUT_CAPABILITY`] 的值推断输入来源：`mouse`、`touch` 与 `pointer` 为
+    /// [`Self::Pointer`]，其他值或未设置时为 [`Self::Navigation`]。
+    pub fn detect(capabilities: &MortarCapabilities) -> Self {
+        match capabilities.value(INPUT_CAPABILITY) {
+            Some("mouse" | "touch" | "pointer") => Self::Pointer,
+            _ => Self::Navigation,
+        }
+    }
+}
+
+impl MortarRuntime {
+    /// Whether a selection from `source` also confirms the option under
+    /// [`MortarRuntime::choice_confirm`].
+    ///
+    /// 在 [`MortarRuntime::choice_confirm`] 下，来自 `source` 的选择是否同时确认该选项。
+    pub fn selection_confirms(&self, source: Option<ChoiceInputSource>) -> bool {
+        match self.choice_confirm {
+            ChoiceConfirmMode::TwoStep => false,
+            ChoiceConfirmMode::OneStep => true,
+            ChoiceConfirmMode::PerSource => {
+                source.unwrap_or_else(|| ChoiceInputSource::detect(&self.capabilities))
+                    == ChoiceInputSource::Pointer
+            }
+        }
+    }
+}
@@ -0,0 +1,245 @@
+//! # direct_drive.rs
+//!
+//! # direct_drive.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! A synchronous command surface for tools that drive dialogue as a library, such as a cutscene
+//! editor scrubbing while the app is paused. The calls act on the runtime immediately instead of
+//! going through [`crate::MortarCommand`] messages and frame boundaries:
+//! [`MortarRuntime::force_set_position`] moves the primary dialogue to a line with the same
+//! checks a start request makes, `MortarRuntime::force_show_line` (feature `ui`) also renders the
+//! line and returns it, and [`MortarRuntime::fire_line_events`] fires a chosen part of that line's
+//! events into a sink the caller owns. By default they bypass lifecycle messages: no
+//! [`MortarDialogueStarted`] or [`MortarNodeEntered`] is written, and the forced line is shown on
+//! the text targets without a `MortarTextChanged`. Turning on
+//! [`MortarRuntime::announce_direct_drive`] writes them on the next frame as the message path
+//! would.
+//!
+//! 面向把对话当作库来驱动的工具（例如在应用暂停时拖动时间轴的过场动画编辑器）的同步命令接口。这些
+//! 调用会立即作用于运行时，而不经过 [`crate::MortarCommand`] 消息与帧边界：
+//! [`MortarRuntime::force_set_position`] 以开始请求相同的检查把主对话移动到某一行，
+//! `MortarRuntime::force_show_line`（`ui` 功能）还会渲染该行并返回结果，
+//! [`MortarRuntime::fire_line_events`] 则把该行事件中选定的部分触发到调用者持有的接收器中。默认情况下
+//! 它们绕过生命周期消息：不会写入 [`MortarDialogueStarted`] 或 [`MortarNodeEntered`]，强制显示的行会
+//! 显示在文本目标上但不发出 `MortarTextChanged`。开启 [`MortarRuntime::announce_direct_drive`] 后，
+//! 这些消息会像消息路径一样在下一帧写入。
+
+use bevy::asset::Assets;
+use bevy::prelude::*;
+use std::fmt;
+use std::ops::RangeBounds;
+
+use crate::asset::find_node;
+use crate::validation::StartGate;
+use crate::{
+    LineCursor, MortarAsset, MortarDialogueStarted, MortarEventAction, MortarEventTracker,
+    MortarNodeEntered, MortarNodeEntry, MortarRegistry, MortarRuntime, MortarStartFailure,
+};
+
+/// Why a direct drive call could not act.
+///
+/// 直接驱动调用无法执行的原因。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MortarDriveError {
+    /// There is no primary dialogue to move.
+    ///
+    /// 没有可移动的主对话。
+    NoDialogue,
+    /// The file is not registered.
+    ///
+    /// 文件未注册。
+    UnknownFile { mortar_path: String },
+    /// The file is registered but has not loaded.
+    ///
+    /// 文件已注册但尚未加载。
+    NotLoaded { mortar_path: String },
+    /// Under [`crate::MortarStrictStart::Wait`], the file's analysis has not completed.
+    ///
+    /// 在 [`crate::MortarStrictStart::Wait`] 下，文件的分析尚未完成。
+    Analyzing { mortar_path: String },
+    /// The file cannot serve the node, as a start request would fail.
+    ///
+    /// 文件无法提供该节点，与开始请求失败的情形相同。
+    Refused {
+        mortar_path: String,
+        node: String,
+        reason: MortarStartFailure,
+    },
+    /// The line is positioned but would be skipped, for the given reason.
+    ///
+    /// 已定位到该行，但它会因给定原因被跳过。
+    LineSkipped { reason: String },
+}
+
+impl fmt::Display for MortarDriveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoDialogue => write!(f, "no primary dialogue"),
+            Self::UnknownFile { mortar_path } => write!(f, "'{mortar_path}' is not registered"),
+            Self::NotLoaded { mortar_path } => write!(f, "'{mortar_path}' has not loaded"),
+            Self::Analyzing { mortar_path } => {
+                write!(f, "'{mortar_path}' is waiting for its analysis")
+            }
+            Self::Refused {
+                mortar_path,
+                node,
+                reason,
+            } => write!(f, "'{mortar_path}' cannot start node '{node}': {reason:?}"),
+            Self::LineSkipped { reason } => write!(f, "the line is skipped: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for MortarDriveError {}
+
+/// Bookkeeping of the direct drive calls.
+#[derive(Debug, Default)]
+pub(crate) struct DirectDrive {
+    /// Line forced without an announcement; the text system shows it silently.
+    pub(crate) quiet_line: Option<LineCursor>,
+    /// Events of the line `force_show_line` rendered last.
+    pub(crate) line_events: Vec<mortar_compiler::Event>,
+    /// Lifecycle messages owed under [`MortarRuntime::announce_direct_drive`].
+    announcements: Vec<(Option<MortarDialogueStarted>, MortarNodeEntered)>,
+}
+
+impl MortarRuntime {
+    /// Moves the primary dialogue to line `index` of `node` in its file, clamping the index as a
+    /// start entry does, and returns the index used. Runs skipped on the way count as executed.
+    ///
+    /// 将主对话移动到其文件中 `node` 的第 `index` 行，并像开始请求的入口一样限制索引，返回实际使用的
+    /// 索引。途中跳过的 run 视为已执行。
+    pub fn force_set_position(
+        &mut self,
+        node: &str,
+        index: usize,
+        registry: &MortarRegistry,
+        assets: &Assets<MortarAsset>,
+    ) -> Result<usize, MortarDriveError> {
+        let path = self
+            .primary_dialogue_state()
+            .map(|state| state.mortar_path.clone())
+            .ok_or(MortarDriveError::NoDialogue)?;
+        let (path, _, asset) = drive_file(registry, assets, &path)?;
+        self.force_position(&path, node, index, asset)
+    }
+
+    /// Positions the primary dialogue in the file at `path`, starting one on the resource-based
+    /// controller when none is active.
+    pub(crate) fn force_position(
+        &mut self,
+        path: &str,
+        node: &str,
+        index: usize,
+        asset: &MortarAsset,
+    ) -> Result<usize, MortarDriveError> {
+        let refused = |reason| MortarDriveError::Refused {
+            mortar_path: path.to_owned(),
+            node: node.to_owned(),
+            reason,
+        };
+        match self.strict_start.gate(asset) {
+            StartGate::Open => {}
+            StartGate::Wait => {
+                return Err(MortarDriveError::Analyzing {
+                    mortar_path: path.to_owned(),
+                });
+            }
+            StartGate::Refuse => return Err(refused(MortarStartFailure::InvalidAsset)),
+        }
+        let Some(node_data) = find_node(&asset.data, node) else {
+            return Err(refused(if asset.data.nodes.is_empty() {
+                MortarStartFailure::NoNodes
+            } else {
+                MortarStartFailure::NodeNotFound
+            }));
+        };
+
+        let entity = self.primary_dialogue.unwrap_or(Entity::PLACEHOLDER);
+        let same_node = self
+            .active_dialogues
+            .get(&entity)
+            .is_some_and(|state| state.mortar_path == path && state.current_node == node);
+        let state = match self.active_dialogues.get(&entity) {
+            Some(state) if same_node => state.clone(),
+            _ => self.parse_node(path, node, node_data, asset),
+        };
+        let entry = Some(MortarNodeEntry::at(index));
+        let activation = crate::system::activate_dialogue(self, entity, state, entry, asset);
+        let state = &self.active_dialogues[&entity];
+        let (used, cursor) = (state.text_index, state.cursor());
+        self.direct_drive.line_events.clear();
+        if self.announce_direct_drive {
+            self.direct_drive.quiet_line = None;
+            let (started, entered) = activation.into_messages();
+            self.direct_drive.announcements.push((started, entered));
+        } else {
+            self.direct_drive.quiet_line = Some(cursor);
+        }
+        Ok(used)
+    }
+
+    /// Fires the events of the line `MortarRuntime::force_show_line` rendered last whose index
+    /// falls in `range`, in index order, calling their bound functions and handing their actions
+    /// to `sink` instead of the world's messages. Returns how many actions were handed over.
+    ///
+    /// 触发 `MortarRuntime::force_show_line` 最近渲染的行中索引落在 `range` 内的事件，按索引顺序
+    /// 调用其绑定的函数，并把动作交给 `sink` 而不是写入世界消息。返回交出的动作数量。
+    pub fn fire_line_events(
+        &self,
+        range: impl RangeBounds<f64>,
+        sink: &mut impl Extend<MortarEventAction>,
+    ) -> usize {
+        let chosen: Vec<_> = self
+            .direct_drive
+            .line_events
+            .iter()
+            .filter(|event| range.contains(&event.index))
+            .cloned()
+            .collect();
+        let actions = MortarEventTracker::new(chosen).trigger_at_index(f32::INFINITY, self);
+        let fired = actions.len();
+        sink.extend(actions);
+        fired
+    }
+}
+
+/// Resolves a registered, loaded file by its canonical path.
+pub(crate) fn drive_file<'a>(
+    registry: &MortarRegistry,
+    assets: &'a Assets<MortarAsset>,
+    path: &str,
+) -> Result<(String, AssetId<MortarAsset>, &'a MortarAsset), MortarDriveError> {
+    let path = registry.canonical_key(path);
+    let handle = registry
+        .get(&path)
+        .ok_or_else(|| MortarDriveError::UnknownFile {
+            mortar_path: path.clone(),
+        })?;
+    let asset = assets
+        .get(handle)
+        .ok_or_else(|| MortarDriveError::NotLoaded {
+            mortar_path: path.clone(),
+        })?;
+    Ok((path, handle.id(), asset))
+}
+
+/// Writes the lifecycle messages owed by direct drive calls.
+pub(crate) fn announce_direct_drive(
+    mut runtime: ResMut<MortarRuntime>,
+    mut started: MessageWriter<MortarDialogueStarted>,
+    mut entered: MessageWriter<MortarNodeEntered>,
+) {
+    if runtime.direct_drive.announcements.is_empty() {
+        return;
+    }
+    for (start, entry) in std::mem::take(&mut runtime.direct_drive.announcements) {
+        if let Some(start) = start {
+            started.write(start);
+        }
+        entered.write(entry);
+    }
+}
@@ -0,0 +1,244 @@
+//! # focus.rs
+//!
+//! # focus.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Arbitration between concurrent dialogues over the one dialogue box. Text targets, runs, voice
+//! and choice UI only ever present the primary dialogue, so the primary dialogue is the focused
+//! one: [`MortarRuntime::request_focus`] hands it over under the [`FocusContention`] policy and
+//! [`MortarRuntime::release_focus`] passes it to the next queued dialogue. User input (advancing,
+//! selecting and confirming) aimed at an unfocused dialogue is ignored; such a dialogue still
+//! takes automatic steps unless [`UnfocusedBehavior::Pause`] holds it where it is. Starting the
+//! resource-based dialogue always takes focus, and a dialogue that ends gives it back.
+//! [`MortarFocus`] mirrors the state every frame and [`MortarFocusChanged`] reports handoffs.
+//!
+//! 多个并发对话之间对同一个对话框的仲裁。文本目标、run、语音与选项 UI 只会呈现主对话，因此主对话
+//! 就是拥有焦点的对话：[`MortarRuntime::request_focus`] 按 [`FocusContention`] 策略移交焦点，
+//! [`MortarRuntime::release_focus`] 把焦点交给队列中的下一个对话。针对无焦点对话的用户输入（推进、
+//! 选择与确认）会被忽略；除非 [`UnfocusedBehavior::Pause`] 让它停在原处，这样的对话仍会执行自动步骤。
+//! 启动基于资源的对话总会获得焦点，结束的对话会交还焦点。[`MortarFocus`] 每帧同步该状态，
+//! [`MortarFocusChanged`] 报告焦点的移交。
+
+use bevy::prelude::*;
+
+use super::MortarRuntime;
+use crate::debug::LOG_DIALOGUE;
+
+/// What a focus request does while another dialogue holds focus.
+///
+/// 另一个对话持有焦点时，焦点请求的处理方式。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FocusContention {
+    /// The request takes focus; the previous holder waits first in the queue and gets focus back
+    /// when it is released.
+    ///
+    /// 请求方夺取焦点；原持有者排在队列最前，焦点被释放时重新获得焦点。
+    #[default]
+    Steal,
+    /// The request waits in the queue until focus is released.
+    ///
+    /// 请求方在队列中等待，直到焦点被释放。
+    Queue,
+    /// The request is refused and the dialogue stays unfocused.
+    ///
+    /// 请求被拒绝，对话保持无焦点。
+    Reject,
+}
+
+/// How an unfocused dialogue behaves.
+///
+/// 无焦点对话的行为。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UnfocusedBehavior {
+    /// Keeps taking automatic steps (skipped lines, node jumps, resuming after an error); only
+    /// user input is ignored.
+    ///
+    /// 继续执行自动步骤（跳过的行、节点跳转、出错后恢复）；只忽略用户输入。
+    #[default]
+    KeepNonBlocking,
+    /// Holds still until it is focused again.
+    ///
+    /// 保持不动，直到再次获得焦点。
+    Pause,
+}
+
+/// Result of [`MortarRuntime::request_focus`].
+///
+/// [`MortarRuntime::request_focus`] 的结果。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FocusRequest {
+    Granted,
+    Queued,
+    Rejected,
+}
+
+/// The focused dialogue and the ones waiting for focus, republished from the runtime every
+/// frame. Dialogues are named by controller entity, `Entity::PLACEHOLDER` for the resource-based
+/// one.
+///
+/// 拥有焦点的对话以及等待焦点的对话，每帧从运行时重新发布。对话以控制器实体表示，基于资源的对话为
+/// `Entity::PLACEHOLDER`。
+#[derive(Resource, Debug, Clone, Default, PartialEq, Eq)]
+pub struct MortarFocus {
+    holder: Option<Entity>,
+    queued: Vec<Entity>,
+}
+
+impl MortarFocus {
+    pub fn holder(&self) -> Option<Entity> {
+        self.holder
+    }
+
+    /// Dialogues waiting for focus, next first.
+    ///
+    /// 等待焦点的对话，下一个排在最前。
+    pub fn queued(&self) -> &[Entity] {
+        &self.queued
+    }
+
+    pub fn is_focused(&self, entity: Entity) -> bool {
+        self.holder == Some(entity)
+    }
+}
+
+/// Sent when focus moves between dialogues, including to or from none. Handoffs within one frame
+/// are reported as one.
+///
+/// 焦点在对话之间移动时发出，包括移入或移出“无焦点”。同一帧内的多次移交会合并为一次报告。
+#[derive(Message, Debug, Clone, PartialEq, Eq)]
+pub struct MortarFocusChanged {
+    pub previous: Option<Entity>,
+    pub current: Option<Entity>,
+}
+
+impl MortarRuntime {
+    /// Asks for focus for the active dialogue of `entity`, settling contention with
+    /// [`Self::focus_contention`].
+    ///
+    /// 为 `entity` 的活跃对话请求焦点，并按 [`Self::focus_contention`] 处理争用。
+    pub fn request_focus(&mut self, entity: Entity) -> FocusRequest {
+        if !self.active_dialogues.contains_key(&entity) {
+            return FocusRequest::Rejected;
+        }
+        let holder = self
+            .primary_dialogue
+            .filter(|holder| self.active_dialogues.contains_key(holder));
+        match holder {
+            Some(holder) if holder == entity => return FocusRequest::Granted,
+            Some(holder) => match self.focus_contention {
+                FocusContention::Steal => {
+                    self.focus_queue.retain(|queued| *queued != holder);
+                    self.focus_queue.insert(0, holder);
+                }
+                FocusContention::Queue => {
+                    if !self.focus_queue.contains(&entity) {
+                        self.focus_queue.push(entity);
+                    }
+                    return FocusRequest::Queued;
+                }
+                FocusContention::Reject => return FocusRequest::Rejected,
+            },
+            None => {}
+        }
+        self.focus_queue.retain(|queued| *queued != entity);
+        self.primary_dialogue = Some(entity);
+        debug!(target: LOG_DIALOGUE, "Dialogue {:?} took focus", entity);
+        FocusRequest::Granted
+    }
+
+    /// Passes focus to the next queued dialogue still active, or to none. Returns the new holder.
+    ///
+    /// 把焦点交给队列中下一个仍然活跃的对话，没有时则不交给任何对话。返回新的持有者。
+    pub fn release_focus(&mut self) -> Option<Entity> {
+        self.primary_dialogue = None;
+        while !self.focus_queue.is_empty() {
+            let next = self.focus_queue.remove(0);
+            if self.active_dialogues.contains_key(&next) {
+                self.primary_dialogue = Some(next);
+                break;
+            }
+        }
+        debug!(target: LOG_DIALOGUE, "Focus passed to {:?}", self.primary_dialogue);
+        self.primary_dialogue
+    }
+
+    /// Sets how the dialogue of `entity` behaves while unfocused. The setting lasts until the
+    /// dialogue ends.
+    ///
+    /// 设置 `entity` 的对话在无焦点时的行为。该设置持续到对话结束。
+    pub fn set_unfocused_behavior(&mut self, entity: Entity, behavior: UnfocusedBehavior) {
+        match behavior {
+            UnfocusedBehavior::KeepNonBlocking => self.paused_when_unfocused.remove(&entity),
+            UnfocusedBehavior::Pause => self.paused_when_unfocused.insert(entity),
+        };
+    }
+
+    pub fn unfocused_behavior(&self, entity: Entity) -> UnfocusedBehavior {
+        if self.paused_when_unfocused.contains(&entity) {
+            UnfocusedBehavior::Pause
+        } else {
+            UnfocusedBehavior::KeepNonBlocking
+        }
+    }
+
+    /// Whether `entity` is active but another dialogue holds focus.
+    pub(crate) fn lacks_focus(&self, entity: Entity) -> bool {
+        self.primary_dialogue.is_some_and(|holder| holder != entity)
+            && self.active_dialogues.contains_key(&entity)
+    }
+
+    /// Whether the dialogue of `entity` holds still because it is unfocused and paused.
+    pub(crate) fn held_by_focus(&self, entity: Entity) -> bool {
+        self.lacks_focus(entity) && self.paused_when_unfocused.contains(&entity)
+    }
+
+    /// Settles focus for a dialogue just installed. A new resource-based dialogue always takes
+    /// focus, a new instance requests it, and a dialogue moving to another node only takes it when
+    /// nobody holds it.
+    pub(crate) fn focus_on_activation(&mut self, entity: Entity, started: bool) {
+        if started && entity == Entity::PLACEHOLDER {
+            let contention = std::mem::take(&mut self.focus_contention);
+            self.request_focus(entity);
+            self.focus_contention = contention;
+        } else if started
+            || self
+                .primary_dialogue
+                .is_none_or(|holder| !self.active_dialogues.contains_key(&holder))
+        {
+            self.request_focus(entity);
+        }
+    }
+
+    /// Forgets the focus of a dialogue that ended, passing focus on when it held it.
+    pub(crate) fn drop_focus(&mut self, entity: Entity) {
+        self.focus_queue.retain(|queued| *queued != entity);
+        self.paused_when_unfocused.remove(&entity);
+        if self.primary_dialogue == Some(entity) {
+            self.release_focus();
+        }
+    }
+}
+
+/// Republishes focus into [`MortarFocus`] and reports handoffs.
+pub(crate) fn sync_focus(
+    runtime: Res<MortarRuntime>,
+    mut focus: ResMut<MortarFocus>,
+    mut changes: MessageWriter<MortarFocusChanged>,
+) {
+    let holder = runtime
+        .primary_dialogue
+        .filter(|holder| runtime.active_dialogues.contains_key(holder));
+    if focus.holder != holder {
+        changes.write(MortarFocusChanged {
+            previous: focus.holder,
+            current: holder,
+        });
+    }
+    focus.set_if_neq(MortarFocus {
+        holder,
+        queued: runtime.focus_queue.clone(),
+    });
+}
@@ -0,0 +1,187 @@
+//! # loop_guard.rs
+//!
+//! # loop_guard.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Protection against scripts that advance on their own forever, such as a node whose every line
+//! fails its condition and whose `next` leads back to it. Lines the text system skips advance
+//! with [`MortarCommand::SkipLine`] instead of `NextText`; each of them, and each jump a node makes
+//! to the one after it, counts as an automatic step of its dialogue. Any user input that moves the
+//! dialogue starts the count over. Once a dialogue takes more than
+//! [`MortarRuntime::auto_advance_budget`] automatic steps in a row, it halts:
+//! [`MortarErrorEvent::AutoAdvanceLoopDetected`] reports the last hops, the advance intent reads
+//! [`AdvanceIntent::Nothing`] with [`MortarHaltReason::AutoAdvanceLoop`], and only
+//! [`MortarCommand::ResumeAfterError`], a new start or a stop moves it again.
+//!
+//! 防止脚本无休止地自行推进，例如某节点的每一行条件都不成立，且其 `next` 又指回自身。文本系统跳过
+//! 的行通过 [`MortarCommand::SkipLine`] 而非 `NextText` 推进；每次跳过，以及节点向其后续节点的每次
+//! 跳转，都计为该对话的一次自动步进。任何推动对话的用户输入都会使计数重新开始。一旦对话连续自动步进
+//! 超过 [`MortarRuntime::auto_advance_budget`] 次，它就会暂停：
+//! [`MortarErrorEvent::AutoAdvanceLoopDetected`] 报告最近的跳转，推进意图变为带有
+//! [`MortarHaltReason::AutoAdvanceLoop`] 的 [`AdvanceIntent::Nothing`]，此后只有
+//! [`MortarCommand::ResumeAfterError`]、新的开始请求或停止请求才能使其继续。
+
+use bevy::prelude::*;
+use std::collections::VecDeque;
+
+use crate::debug::LOG_DIALOGUE;
+use crate::{AdvanceIntent, MortarRuntime};
+
+/// Automatic steps a dialogue may take in a row before it halts, unless configured otherwise.
+///
+/// 未另行配置时，对话在暂停前可连续进行的自动步进次数。
+pub const DEFAULT_AUTO_ADVANCE_BUDGET: usize = 100;
+
+/// Hops kept for the diagnosis of a detected loop.
+const TRAIL_LEN: usize = 16;
+
+/// Why a dialogue stopped advancing until it is resumed.
+///
+/// 对话在恢复之前停止推进的原因。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MortarHaltReason {
+    /// The dialogue exceeded its automatic step budget, see [`MortarErrorEvent`].
+    ///
+    /// 对话超出了自动步进预算，参见 [`MortarErrorEvent`]。
+    AutoAdvanceLoop,
+}
+
+/// Errors the runtime detects in running scripts.
+///
+/// 运行时在执行脚本时检测到的错误。
+#[derive(Message, Debug, Clone, PartialEq, Eq)]
+pub enum MortarErrorEvent {
+    /// A dialogue advanced on its own more than [`MortarRuntime::auto_advance_budget`] times in a
+    /// row and was halted.
+    ///
+    /// 对话连续自行推进超过 [`MortarRuntime::auto_advance_budget`] 次，已被暂停。
+    AutoAdvanceLoopDetected {
+        entity: Option<Entity>,
+        mortar_path: String,
+        /// Node the dialogue halted in.
+        ///
+        /// 对话暂停时所在的节点。
+        node: String,
+        /// Automatic steps taken since the last user input.
+        ///
+        /// 自上次用户输入以来的自动步进次数。
+        steps: usize,
+        /// The last hops as (node, text index), oldest first.
+        ///
+        /// 最近的跳转，格式为 (节点, 文本索引)，按从旧到新排列。
+        trail: Vec<(String, usize)>,
+    },
+}
+
+/// Automatic steps of one dialogue since its last user input.
+#[derive(Debug, Default)]
+pub(crate) struct AutoAdvanceTrail {
+    steps: usize,
+    hops: VecDeque<(String, usize)>,
+    halted: bool,
+    /// Set while the start request of an automatic jump is on its way.
+    jumping: bool,
+}
+
+impl MortarRuntime {
+    /// Why the dialogue of `entity` is halted, if it is.
+    ///
+    /// `entity` 的对话暂停的原因（如已暂停）。
+    pub fn halt_reason(&self, entity: Entity) -> Option<MortarHaltReason> {
+        self.auto_advance
+            .get(&entity)
+            .is_some_and(|trail| trail.halted)
+            .then_some(MortarHaltReason::AutoAdvanceLoop)
+    }
+
+    /// Forgets the automatic steps of `entity`, lifting a halt.
+    pub(crate) fn reset_auto_advance(&mut self, entity: Entity) {
+        self.auto_advance.remove(&entity);
+    }
+
+    /// Handles a start request for `entity`: the one an automatic jump sent keeps counting, any
+    /// other starts over.
+    pub(crate) fn note_start_request(&mut self, entity: Entity) {
+        match self.auto_advance.get_mut(&entity) {
+            Some(trail) if trail.jumping => trail.jumping = false,
+            _ => self.reset_auto_advance(entity),
+        }
+    }
+
+    /// Counts skipping the current line of `entity` as an automatic step, see
+    /// [`MortarRuntime::record_auto_step`].
+    pub(crate) fn record_auto_skip(&mut self, entity: Entity) -> (bool, Option<MortarErrorEvent>) {
+        let Some(state) = self.active_dialogues.get(&entity) else {
+            return (false, None);
+        };
+        let hop = (state.current_node.clone(), state.text_index);
+        self.record_auto_step(entity, hop)
+    }
+
+    /// Counts the jump of `entity` to `node` as an automatic step, see
+    /// [`MortarRuntime::record_auto_step`].
+    pub(crate) fn record_auto_jump(
+        &mut self,
+        entity: Entity,
+        node: &str,
+    ) -> (bool, Option<MortarErrorEvent>) {
+        let outcome = self.record_auto_step(entity, (node.to_owned(), 0));
+        if outcome.0 {
+            self.auto_advance.entry(entity).or_default().jumping = true;
+        }
+        outcome
+    }
+
+    /// Counts an automatic step of `entity` landing on `hop`. Returns whether the step may
+    /// proceed, and the error to report when this step exhausted the budget.
+    fn record_auto_step(
+        &mut self,
+        entity: Entity,
+        hop: (String, usize),
+    ) -> (bool, Option<MortarErrorEvent>) {
+        let Some(state) = self.active_dialogues.get(&entity) else {
+            return (true, None);
+        };
+        let (mortar_path, node) = (state.mortar_path.clone(), state.current_node.clone());
+        let budget = self.auto_advance_budget;
+        let trail = self.auto_advance.entry(entity).or_default();
+        if trail.halted {
+            return (false, None);
+        }
+        trail.steps += 1;
+        if trail.hops.len() == TRAIL_LEN {
+            trail.hops.pop_front();
+        }
+        trail.hops.push_back(hop);
+        if budget.is_none_or(|budget| trail.steps <= budget) {
+            return (true, None);
+        }
+        trail.halted = true;
+        warn!(
+            target: LOG_DIALOGUE,
+            "Halting entity {:?} in node '{}': {} automatic steps without user input",
+            entity,
+            node,
+            trail.steps
+        );
+        let error = MortarErrorEvent::AutoAdvanceLoopDetected {
+            entity: crate::system::entity_to_option(entity),
+            mortar_path,
+            node,
+            steps: trail.steps,
+            trail: trail.hops.iter().cloned().collect(),
+        };
+        (false, Some(error))
+    }
+
+    /// The intent of a halted dialogue, if `entity` is halted.
+    pub(crate) fn halted_intent(&self, entity: Entity) -> Option<AdvanceIntent> {
+        self.halt_reason(entity)
+            .map(|reason| AdvanceIntent::Nothing {
+                reason: Some(reason),
+            })
+    }
+}
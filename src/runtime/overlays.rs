@@ -0,0 +1,181 @@
+//! # overlays.rs
+//!
+//! # overlays.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Overlay files for DLC and mods, layered on a registered base file without modifying it. Each
+//! base path with overlays owns a merged asset under a fixed handle, which
+//! [`MortarRegistry::get`] returns in place of the base, so starts, jumps, preparation and the
+//! load-time analysis all see the merged content. The merge runs once every layer has loaded and
+//! again whenever one of them reloads; until then the merged handle has no asset and starts on
+//! the path wait. See [`MortarRegistry::register_overlay`] for how layers merge.
+//!
+//! 用于 DLC 与模组的覆盖层文件，叠加在已注册的基础文件之上而不修改它。每个带覆盖层的基础路径都拥有
+//! 一个固定句柄下的合并资源，[`MortarRegistry::get`] 会返回它而不是基础文件，因此开始、跳转、预先
+//! 准备以及加载时分析看到的都是合并后的内容。所有层加载完成后执行合并，任一层重新加载时再次合并；
+//! 在此之前合并句柄下没有资源，该路径上的开始请求会等待。各层的合并方式参见
+//! [`MortarRegistry::register_overlay`]。
+
+use bevy::asset::uuid::Uuid;
+use bevy::prelude::*;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use super::MortarRegistry;
+use crate::debug::LOG_ASSET;
+use crate::{MortarAsset, MortarOverlayShadow};
+
+/// High bits of the ids of merged assets; the low bits count them.
+const MERGED_ID_PREFIX: u128 = 0x6d6f_7274_6172_6f76_0000_0000_0000_0000;
+
+/// Source of merged asset ids, so every base path gets its own.
+static NEXT_MERGED: AtomicU64 = AtomicU64::new(1);
+
+/// The overlays of one base path and the asset they merge into.
+pub(super) struct OverlayStack {
+    /// Overlays with their priority, in the order they apply.
+    layers: Vec<(Handle<MortarAsset>, i32)>,
+    merged: Handle<MortarAsset>,
+    /// Whether a layer changed since the last merge.
+    stale: bool,
+}
+
+/// Definitions shadowed by the last merge of each base path, see [`MortarOverlayShadow`].
+///
+/// 每个基础路径最近一次合并中被遮蔽的定义，参见 [`MortarOverlayShadow`]。
+#[derive(Resource, Debug, Default)]
+pub struct MortarOverlayDiagnostics {
+    shadows: HashMap<String, Vec<MortarOverlayShadow>>,
+}
+
+impl MortarOverlayDiagnostics {
+    /// Definitions shadowed when the overlays of `base_path` last merged.
+    ///
+    /// `base_path` 的覆盖层最近一次合并时被遮蔽的定义。
+    pub fn shadows(&self, registry: &MortarRegistry, base_path: &str) -> &[MortarOverlayShadow] {
+        self.shadows
+            .get(&registry.canonical_key(base_path))
+            .map_or(&[], Vec::as_slice)
+    }
+}
+
+impl MortarRegistry {
+    /// Layers `overlay` on the file registered at `base_path`. Overlays apply from the lowest
+    /// `priority` to the highest, in registration order on ties, and lookups of `base_path`
+    /// return the merged content from then on. An overlay's nodes replace the base's nodes of
+    /// the same name or are added; a node marked `"patch": true` is spliced into the existing
+    /// node instead, its content items placed by `replace_index: N` or `insert_after: N` hints
+    /// against the node as it stood before that overlay, or appended. Other definitions replace
+    /// or add by name.
+    ///
+    /// 将 `overlay` 叠加到注册在 `base_path` 的文件上。覆盖层按 `priority` 从低到高应用，优先级相同
+    /// 时按注册顺序；此后对 `base_path` 的查找都会返回合并后的内容。覆盖层的节点会替换基础文件中的
+    /// 同名节点或作为新节点加入；标记为 `"patch": true` 的节点则拼接进已有节点，其内容项依据
+    /// `replace_index: N` 或 `insert_after: N` 提示（针对该覆盖层应用之前的节点）放置，没有提示的追加
+    /// 到末尾。其他定义按名称替换或加入。
+    pub fn register_overlay(
+        &mut self,
+        base_path: impl Into<String>,
+        overlay: Handle<MortarAsset>,
+        priority: i32,
+    ) {
+        let key = self.canonical_key(&base_path.into());
+        let stack = self.overlays.entry(key).or_insert_with(|| OverlayStack {
+            layers: Vec::new(),
+            merged: Handle::from(Uuid::from_u128(
+                MERGED_ID_PREFIX | u128::from(NEXT_MERGED.fetch_add(1, Ordering::Relaxed)),
+            )),
+            stale: true,
+        });
+        stack.layers.push((overlay, priority));
+        stack.layers.sort_by_key(|(_, priority)| *priority);
+        stack.stale = true;
+    }
+
+    /// Drops every overlay of `base_path`, returning whether there were any.
+    ///
+    /// 移除 `base_path` 的所有覆盖层，并返回之前是否存在。
+    pub fn remove_overlays(&mut self, base_path: &str) -> bool {
+        self.overlays
+            .remove(&self.canonical_key(base_path))
+            .is_some()
+    }
+
+    /// The merged handle of `key` when it has overlays and a base.
+    pub(super) fn merged(&self, key: &str) -> Option<&Handle<MortarAsset>> {
+        self.overlays
+            .get(key)
+            .filter(|_| self.assets.contains_key(key))
+            .map(|stack| &stack.merged)
+    }
+
+    /// Marks the overlays of `key` for another merge.
+    pub(super) fn invalidate_overlays(&mut self, key: &str) {
+        if let Some(stack) = self.overlays.get_mut(key) {
+            stack.stale = true;
+        }
+    }
+}
+
+/// Merges the overlays of every base path whose layers changed, once all of them are loaded.
+pub(crate) fn merge_overlays(
+    mut registry: ResMut<MortarRegistry>,
+    mut assets: ResMut<Assets<MortarAsset>>,
+    mut asset_events: MessageReader<AssetEvent<MortarAsset>>,
+    mut diagnostics: ResMut<MortarOverlayDiagnostics>,
+) {
+    let registry = registry.as_mut();
+    for event in asset_events.read() {
+        let AssetEvent::Modified { id } = event else {
+            continue;
+        };
+        for (key, stack) in &mut registry.overlays {
+            let base = registry.assets.get(key).map(Handle::id);
+            if base == Some(*id) || stack.layers.iter().any(|(layer, _)| layer.id() == *id) {
+                stack.stale = true;
+            }
+        }
+    }
+
+    for (key, stack) in &mut registry.overlays {
+        if !stack.stale {
+            continue;
+        }
+        let Some(base) = registry
+            .assets
+            .get(key)
+            .and_then(|handle| assets.get(handle))
+        else {
+            continue;
+        };
+        let layers: Option<Vec<_>> = stack
+            .layers
+            .iter()
+            .map(|(layer, _)| Some((layer.id(), assets.get(layer)?)))
+            .collect();
+        let Some(layers) = layers else {
+            continue;
+        };
+        let (merged, shadows) = crate::asset::merge_layers(base, &layers);
+        for shadow in &shadows {
+            warn!(
+                target: LOG_ASSET,
+                "Overlay {:?} replaces {} '{}' of overlay {:?} in '{}'",
+                shadow.by,
+                shadow.kind,
+                shadow.name,
+                shadow.shadowed,
+                key
+            );
+        }
+        diagnostics.shadows.insert(key.clone(), shadows);
+        if let Err(error) = assets.insert(stack.merged.id(), merged) {
+            warn!(target: LOG_ASSET, "Could not store the merged '{}': {}", key, error);
+            continue;
+        }
+        stack.stale = false;
+    }
+}
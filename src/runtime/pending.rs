@@ -0,0 +1,129 @@
+//! # pending.rs
+//!
+//! # pending.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Status of start requests waiting for their file to load, so a game can show
+//! "Loading conversation… (compiling)" instead of nothing. A status appears when a start has to
+//! wait, follows the stages the loader reports, counts the time waited, and disappears when the
+//! node activates. A file that fails to load leaves a [`MortarLoadStage::Failed`] status until the
+//! controller starts or stops another dialogue.
+//!
+//! 等待文件加载的开始请求的状态，让游戏可以显示“正在加载对话……（编译中）”而不是一片空白。状态在
+//! 开始请求需要等待时出现，跟随加载器报告的阶段，累计等待时间，并在节点激活时消失。加载失败的
+//! 文件会留下 [`MortarLoadStage::Failed`] 状态，直到该控制器开始或停止另一段对话。
+
+use bevy::prelude::Entity;
+use std::time::Duration;
+
+use super::MortarRuntime;
+use crate::MortarLoadStage;
+
+/// Where a start request waiting for its file stands.
+///
+/// 等待文件加载的开始请求的当前状况。
+#[derive(Debug, Clone, PartialEq)]
+pub struct PendingStatus {
+    pub path: String,
+    pub node: String,
+    /// Seconds since the request started waiting.
+    ///
+    /// 请求开始等待以来经过的秒数。
+    pub waited_secs: f32,
+    pub stage: MortarLoadStage,
+}
+
+/// A pending status with the time its wait began, set on the first frame it is checked.
+#[derive(Debug, Clone)]
+pub(crate) struct PendingLoad {
+    pub(crate) status: PendingStatus,
+    since: Option<Duration>,
+}
+
+impl MortarRuntime {
+    /// Status of the start that has waited longest for its file, `None` when nothing waits.
+    ///
+    /// 等待文件加载最久的开始请求的状态；没有请求在等待时为 `None`。
+    pub fn pending_start_status(&self) -> Option<PendingStatus> {
+        self.pending_loads
+            .values()
+            .max_by(|a, b| a.status.waited_secs.total_cmp(&b.status.waited_secs))
+            .map(|load| load.status.clone())
+    }
+
+    /// Status of the start waiting on `target`, or on no controller for `None`.
+    ///
+    /// `target` 上等待的开始请求的状态；为 `None` 时指未指定控制器的请求。
+    pub fn pending_start_status_for(&self, target: Option<Entity>) -> Option<PendingStatus> {
+        self.pending_loads
+            .get(&target.unwrap_or(Entity::PLACEHOLDER))
+            .map(|load| load.status.clone())
+    }
+
+    /// Starts the status of a request that waits for `path`.
+    pub(crate) fn begin_pending_load(&mut self, entity: Entity, path: &str, node: &str) {
+        let stage = self
+            .load_stages
+            .get(path)
+            .copied()
+            .unwrap_or(MortarLoadStage::Queued);
+        let status = PendingStatus {
+            path: path.to_owned(),
+            node: node.to_owned(),
+            waited_secs: 0.0,
+            stage,
+        };
+        self.pending_loads.insert(
+            entity,
+            PendingLoad {
+                status,
+                since: None,
+            },
+        );
+    }
+
+    /// Keeps a start of `path` waiting for the analysis of its loaded file, see
+    /// [`crate::MortarStrictStart::Wait`].
+    pub(crate) fn wait_for_analysis(&mut self, entity: Entity, path: &str, node: &str) {
+        self.pending_starts
+            .insert(entity, (path.to_owned(), node.to_owned()));
+        if !self.pending_loads.contains_key(&entity) {
+            self.begin_pending_load(entity, path, node);
+        }
+        if let Some(load) = self.pending_loads.get_mut(&entity) {
+            load.status.stage = MortarLoadStage::Analyzing;
+        }
+    }
+
+    /// Records the stage a load of `path` reached, for the requests waiting on it.
+    pub(crate) fn note_load_stage(&mut self, path: String, stage: MortarLoadStage) {
+        for load in self.pending_loads.values_mut() {
+            if load.status.path == path && !load.status.stage.is_terminal() {
+                load.status.stage = stage;
+            }
+        }
+        if stage.is_terminal() {
+            self.load_stages.remove(&path);
+        } else {
+            self.load_stages.insert(path, stage);
+        }
+    }
+
+    /// Updates how long `entity` has waited, `elapsed` being the current time.
+    pub(crate) fn tick_pending_load(&mut self, entity: Entity, elapsed: Duration) {
+        if let Some(load) = self.pending_loads.get_mut(&entity) {
+            let since = *load.since.get_or_insert(elapsed);
+            load.status.waited_secs = elapsed.saturating_sub(since).as_secs_f32();
+        }
+    }
+
+    /// Marks the wait of `entity` as failed; the status stays until its next start or stop.
+    pub(crate) fn fail_pending_load(&mut self, entity: Entity) {
+        if let Some(load) = self.pending_loads.get_mut(&entity) {
+            load.status.stage = MortarLoadStage::Failed;
+        }
+    }
+}
@@ -0,0 +1,234 @@
+//! # rng.rs
+//!
+//! # rng.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Random streams for scripts. The built-in [`RANDOM_FUNCTION`] and [`CHANCE_FUNCTION`] draw
+//! from the stream of the conversation that calls them, so replaying a conversation from a save
+//! rolls the same numbers as the original session. Each conversation is seeded when it starts,
+//! from the global seed ([`MortarRuntime::set_rng_seed`]), its file, its node and how many
+//! conversations started before it. Calls from outside the dialogue pipeline draw from the global
+//! stream. A stream is a seed and a position, see [`MortarRngState`]; the state reported by
+//! [`MortarRuntime::conversation_rng_state`] sits at the start of the shown line, so a restore
+//! that shows the line again draws the same rolls for it.
+//!
+//! 供脚本使用的随机数流。内置的 [`RANDOM_FUNCTION`] 与 [`CHANCE_FUNCTION`] 从调用它们的对话的流中
+//! 取值，因此从存档重放对话时会掷出与原始会话相同的数字。每段对话在开始时播种，种子由全局种子
+//! （[`MortarRuntime::set_rng_seed`]）、文件、节点以及此前开始过的对话数量得出。对话流程之外的调用
+//! 使用全局流。流由种子与位置组成，参见 [`MortarRngState`]；[`MortarRuntime::conversation_rng_state`]
+//! 报告的状态位于当前显示行的开头，因此再次显示该行的恢复会为它掷出相同的结果。
+
+use bevy::prelude::*;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use super::MortarRuntime;
+use crate::{MortarCallContext, MortarFunctionRegistry, MortarValue};
+
+/// Built-in script function `random()`, a number in `[0, 1)`.
+///
+/// 内置脚本函数 `random()`，返回 `[0, 1)` 内的数。
+pub const RANDOM_FUNCTION: &str = "random";
+
+/// Built-in script function `chance(p)`, true with probability `p`.
+///
+/// 内置脚本函数 `chance(p)`，以概率 `p` 为真。
+pub const CHANCE_FUNCTION: &str = "chance";
+
+const GOLDEN_GAMMA: u64 = 0x9E37_79B9_7F4A_7C15;
+
+/// Position in a random stream. Draw `n` depends only on the seed and `n`, so a saved state
+/// resumes the stream exactly.
+///
+/// 随机数流中的位置。第 `n` 次取值只取决于种子与 `n`，因此保存的状态可以精确地恢复该流。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "save", derive(serde::Serialize, serde::Deserialize))]
+pub struct MortarRngState {
+    pub seed: u64,
+    /// Number of values drawn so far.
+    ///
+    /// 已取值的次数。
+    pub position: u64,
+}
+
+impl MortarRngState {
+    /// The start of the stream seeded with `seed`.
+    ///
+    /// 以 `seed` 播种的流的起点。
+    pub fn new(seed: u64) -> Self {
+        Self { seed, position: 0 }
+    }
+
+    /// Draws the next 64 random bits.
+    ///
+    /// 取出接下来的 64 个随机位。
+    pub fn next_u64(&mut self) -> u64 {
+        self.position += 1;
+        mix(self
+            .seed
+            .wrapping_add(self.position.wrapping_mul(GOLDEN_GAMMA)))
+    }
+
+    /// Draws a number in `[0, 1)`.
+    ///
+    /// 取出 `[0, 1)` 内的数。
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// SplitMix64 finalizer.
+fn mix(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Stable hash of the parts a conversation seed is derived from.
+fn derive_seed(seed: u64, path: &str, node: &str, counter: u64) -> u64 {
+    let mut hash = 0xCBF2_9CE4_8422_2325_u64;
+    for byte in path.bytes().chain([0]).chain(node.bytes()) {
+        hash = (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01B3);
+    }
+    mix(seed ^ mix(hash) ^ counter.wrapping_mul(GOLDEN_GAMMA))
+}
+
+/// A conversation's stream and where its shown line began drawing.
+#[derive(Debug)]
+struct ConversationStream {
+    state: MortarRngState,
+    line: Option<(String, usize)>,
+    line_start: u64,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct RngStreams {
+    global: MortarRngState,
+    conversations: HashMap<Entity, ConversationStream>,
+    started: u64,
+    queued: Option<MortarRngState>,
+}
+
+impl RngStreams {
+    fn draw(&mut self, context: &MortarCallContext) -> f64 {
+        let stream = context
+            .node
+            .as_ref()
+            .zip(context.text_index)
+            .and_then(|line| {
+                let entity = context.source_entity.unwrap_or(Entity::PLACEHOLDER);
+                Some((line, self.conversations.get_mut(&entity)?))
+            });
+        let Some(((node, text_index), stream)) = stream else {
+            return self.global.next_f64();
+        };
+        let line = (node.clone(), text_index);
+        if stream.line.as_ref() != Some(&line) {
+            stream.line = Some(line);
+            stream.line_start = stream.state.position;
+        }
+        stream.state.next_f64()
+    }
+}
+
+/// Streams shared between the runtime and the built-in functions it registers.
+pub(crate) type SharedRng = Arc<Mutex<RngStreams>>;
+
+fn lock(rng: &SharedRng) -> MutexGuard<'_, RngStreams> {
+    rng.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Registers the random built-ins, drawing from `rng`.
+pub(super) fn register_random_functions(functions: &mut MortarFunctionRegistry, rng: &SharedRng) {
+    let streams = rng.clone();
+    functions.register_with_context_arity(RANDOM_FUNCTION, 0, move |context, _| {
+        lock(&streams).draw(context).into()
+    });
+    let streams = rng.clone();
+    functions.register_with_context_arity(CHANCE_FUNCTION, 1, move |context, args| {
+        let probability = match args.first() {
+            Some(MortarValue::Number(number)) => number.0,
+            _ => 0.0,
+        };
+        (lock(&streams).draw(context) < probability).into()
+    });
+    // Each call is a fresh draw, so conditions never share one.
+    //
+    // 每次调用都是一次新的抽取，因此条件之间从不共用结果。
+    functions.set_volatile(RANDOM_FUNCTION, true);
+    functions.set_volatile(CHANCE_FUNCTION, true);
+}
+
+impl MortarRuntime {
+    /// Reseeds the global stream; conversations started afterwards derive their seeds from it.
+    ///
+    /// 重新为全局流播种；此后开始的对话会据此得出各自的种子。
+    pub fn set_rng_seed(&mut self, seed: u64) {
+        lock(&self.rng).global = MortarRngState::new(seed);
+    }
+
+    /// The global seed.
+    ///
+    /// 全局种子。
+    pub fn rng_seed(&self) -> u64 {
+        lock(&self.rng).global.seed
+    }
+
+    /// State of the primary conversation's stream at the start of its shown line, for saves,
+    /// recorders and replayers.
+    ///
+    /// 主对话的流在当前显示行开头时的状态，供存档、录制与重放使用。
+    pub fn conversation_rng_state(&self) -> Option<MortarRngState> {
+        let state = self.primary_dialogue_state()?;
+        let streams = lock(&self.rng);
+        let stream = streams.conversations.get(&self.primary_dialogue?)?;
+        let shown = (state.current_node.clone(), state.text_index);
+        let position = if stream.line.as_ref() == Some(&shown) {
+            stream.line_start
+        } else {
+            stream.state.position
+        };
+        Some(MortarRngState {
+            position,
+            ..stream.state
+        })
+    }
+
+    /// Makes the next conversation to start use `state` instead of a derived seed, as when
+    /// resuming a save.
+    ///
+    /// 让下一段开始的对话使用 `state`，而不是推导出的种子，例如在恢复存档时。
+    pub fn queue_conversation_rng(&mut self, state: MortarRngState) {
+        lock(&self.rng).queued = Some(state);
+    }
+
+    pub(crate) fn begin_conversation_rng(&mut self, entity: Entity, path: &str, node: &str) {
+        let mut streams = lock(&self.rng);
+        let counter = streams.started;
+        streams.started += 1;
+        let state = streams.queued.take().unwrap_or_else(|| {
+            MortarRngState::new(derive_seed(streams.global.seed, path, node, counter))
+        });
+        streams.conversations.insert(
+            entity,
+            ConversationStream {
+                state,
+                line: None,
+                line_start: state.position,
+            },
+        );
+    }
+
+    pub(crate) fn end_conversation_rng(&mut self, entity: Option<Entity>) {
+        let mut streams = lock(&self.rng);
+        match entity {
+            Some(entity) => {
+                streams.conversations.remove(&entity);
+            }
+            None => streams.conversations.clear(),
+        }
+    }
+}
@@ -0,0 +1,117 @@
+//! # selection_persistence.rs
+//!
+//! # selection_persistence.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! How long a choice selection lives before its group is confirmed. A UI may select an option
+//! while lines still play before the group, or keep a selection while the presented group
+//! changes under it; whether that intent survives is [`SelectionPersistence`], decided in one
+//! place for every transition. A selection never outlives its group: pushing, popping or clearing
+//! the choice stack gives the group a new token and drops it under every policy, and a kept
+//! selection is only ever a selection. Presenting the group never confirms it, even under
+//! [`crate::ChoiceConfirmMode::OneStep`].
+//!
+//! 选项组被确认之前，选中状态能存活多久。UI 可能在选项组之前的行仍在播放时就选中某个选项，
+//! 或者在已呈现的选项组发生变化时保留选中；这份意图是否保留由 [`SelectionPersistence`] 决定，
+//! 并在同一处处理所有状态转换。选中状态永远不会比其选项组存活得更久：压入、弹出或清空选项栈都会
+//! 为选项组分配新令牌，在任何策略下都会丢弃选中；被保留的选中也始终只是选中。呈现选项组永远不会
+//! 确认它，即使在 [`crate::ChoiceConfirmMode::OneStep`] 下也是如此。
+
+use bevy::prelude::*;
+
+use super::MortarRuntime;
+
+/// Whether a choice selection survives the dialogue advancing before its group presents, and
+/// the presented group being rebuilt.
+///
+/// 选项选中状态能否在其选项组呈现之前的对话推进中，以及已呈现选项组被重建时保留下来。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SelectionPersistence {
+    /// Selecting is only accepted while the group is presented, and advancing to another line
+    /// clears the selection. The safest policy: no selection can carry into a group the player
+    /// has not seen.
+    ///
+    /// 只在选项组已呈现时接受选中，推进到其他行会清除选中。最安全的策略：任何选中都不会带入玩家
+    /// 尚未看到的选项组。
+    #[default]
+    ClearOnAdvance,
+    /// A selection made while lines still play before the group is remembered and shown as
+    /// selected once the group presents. A selected option that becomes disabled or hidden
+    /// is deselected.
+    ///
+    /// 在选项组之前的行仍在播放时做出的选中会被记住，并在选项组呈现时显示为已选中。被选中的选项
+    /// 变为禁用或隐藏时会取消选中。
+    KeepUntilGroupPresents,
+    /// As [`Self::KeepUntilGroupPresents`], and a selected option that becomes disabled or hidden
+    /// stays selected; it cannot be confirmed until it is enabled again.
+    ///
+    /// 与 [`Self::KeepUntilGroupPresents`] 相同，并且被选中的选项变为禁用或隐藏时仍保持选中；
+    /// 在重新可用之前无法确认它。
+    KeepAcrossGroupRebuilds,
+}
+
+/// A moment a selection may be dropped at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SelectionTransition {
+    /// An option is selected while lines still play before its group.
+    Preselect,
+    /// The dialogue advances to another line of the node.
+    Advance,
+    /// The presented group is rebuilt and the selected option became disabled or hidden.
+    Rebuild,
+}
+
+impl SelectionPersistence {
+    /// Whether the selection survives `transition`.
+    pub(crate) fn keeps(self, transition: SelectionTransition) -> bool {
+        match self {
+            Self::ClearOnAdvance => false,
+            Self::KeepUntilGroupPresents => transition != SelectionTransition::Rebuild,
+            Self::KeepAcrossGroupRebuilds => true,
+        }
+    }
+}
+
+impl MortarRuntime {
+    /// Remembers option `index` of the group of `entity` when the group is only waiting for the
+    /// lines before it and the policy keeps preselections. Returns whether it was kept.
+    pub(crate) fn keep_preselection(&mut self, entity: Entity, index: usize) -> bool {
+        if !self
+            .selection_persistence
+            .keeps(SelectionTransition::Preselect)
+            || self.pending_jumps.contains_key(&entity)
+        {
+            return false;
+        }
+        let Some(state) = self.active_dialogues.get_mut(&entity) else {
+            return false;
+        };
+        let waiting = state
+            .get_choices()
+            .is_some_and(|choices| index < choices.len())
+            && state.has_next_text_before_choice();
+        if waiting {
+            state.selected_choice = Some(index);
+        }
+        waiting
+    }
+
+    /// Drops the selection of `entity` unless the policy keeps it through `transition`,
+    /// returning the dropped index.
+    pub(crate) fn settle_selection(
+        &mut self,
+        entity: Entity,
+        transition: SelectionTransition,
+    ) -> Option<usize> {
+        if self.selection_persistence.keeps(transition) {
+            return None;
+        }
+        self.active_dialogues
+            .get_mut(&entity)?
+            .selected_choice
+            .take()
+    }
+}
@@ -0,0 +1,76 @@
+//! # signals.rs
+//!
+//! # signals.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Game-to-script signals for waits whose length is not known in advance. A timeline step
+//! `{"type": "wait_signal", "event_name": "door_opened"}` holds the runs (and so the dialogue)
+//! until the game sends [`crate::MortarCommand::Signal`] or calls [`MortarRuntime::signal`] with
+//! that name. Every wait on the name releases on one signal. A signal that arrives while nothing
+//! waits is dropped, unless the next wait on the name is latched (`"args": ["latch"]`), in which
+//! case it releases that wait at once. An optional `duration` is the timeout after which the
+//! timeline moves on anyway. Stopping the dialogue cancels pending waits.
+//!
+//! 供时长无法预知的等待使用的游戏到脚本的信号。时间线步骤
+//! `{"type": "wait_signal", "event_name": "door_opened"}` 会挂起 run（从而挂起对话），直到游戏以该
+//! 名称发送 [`crate::MortarCommand::Signal`] 或调用 [`MortarRuntime::signal`]。同名的所有等待会被同一个
+//! 信号一起释放。没有等待时到达的信号会被丢弃，除非该名称的下一个等待是锁存的
+//! （`"args": ["latch"]`），此时它会立即释放那个等待。可选的 `duration` 为超时时间，超时后时间线
+//! 照常继续。停止对话会取消所有挂起的等待。
+
+use bevy::reflect::Reflect;
+use std::collections::HashMap;
+
+use super::MortarRuntime;
+
+/// A timeline step waiting for a signal.
+#[derive(Debug, Clone, PartialEq, Reflect)]
+pub(crate) struct SignalWait {
+    pub(crate) name: String,
+    /// Signal sequence number when the wait began; only later signals release an unlatched wait.
+    pub(crate) since: u64,
+    pub(crate) latch: bool,
+    pub(crate) timeout: Option<f64>,
+}
+
+/// Signals received from the game, with the sequence number of the latest one per name.
+#[derive(Debug, Default)]
+pub(crate) struct SignalBoard {
+    sequence: u64,
+    received: HashMap<String, u64>,
+}
+
+impl SignalBoard {
+    pub(crate) fn sequence(&self) -> u64 {
+        self.sequence
+    }
+
+    pub(crate) fn releases(&self, wait: &SignalWait) -> bool {
+        self.received
+            .get(&wait.name)
+            .is_some_and(|&at| wait.latch || at > wait.since)
+    }
+
+    /// Forgets a signal once it released its waits, so it cannot release later ones.
+    pub(crate) fn consume(&mut self, name: &str) {
+        self.received.remove(name);
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.received.clear();
+    }
+}
+
+impl MortarRuntime {
+    /// Releases every run waiting for the signal `name`.
+    ///
+    /// 释放所有等待信号 `name` 的 run。
+    pub fn signal(&mut self, name: impl Into<String>) {
+        self.signals.sequence += 1;
+        let sequence = self.signals.sequence;
+        self.signals.received.insert(name.into(), sequence);
+    }
+}
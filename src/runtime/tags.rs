@@ -0,0 +1,68 @@
+//! # tags.rs
+//!
+//! # tags.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Node tags for gameplay systems. A `.mortared` node may carry a `tags` array, which is copied
+//! into its [`crate::DialogueState`] when the node is parsed and reported by
+//! [`crate::MortarNodeEntered`]. Systems ask [`MortarRuntime::active_has_tag`] whether the primary
+//! dialogue sits in, say, a `combat_locked` node, and scripts branch on the same question through
+//! the built-in [`NODE_TAGGED_FUNCTION`], which a fresh runtime registers. Missing or untagged
+//! nodes simply have no tags.
+//!
+//! 供游戏系统使用的节点标签。`.mortared` 节点可以带有 `tags` 数组，节点被解析时它会被复制到对应的
+//! [`crate::DialogueState`]，并由 [`crate::MortarNodeEntered`] 报告。系统可以通过
+//! [`MortarRuntime::active_has_tag`] 询问主对话是否位于例如 `combat_locked` 的节点中，脚本则通过
+//! 新建运行时注册的内置函数 [`NODE_TAGGED_FUNCTION`] 进行同样的判断。不存在或没有标签的节点
+//! 只是没有标签。
+
+use super::MortarRuntime;
+use crate::{MortarCallContext, MortarFunctionRegistry, MortarValue};
+
+/// Built-in script function `node_tagged("tag")`, true when the node being run is tagged `tag`.
+/// Scripts declare it like any bound function: `fn node_tagged(tag: String) -> Boolean`.
+///
+/// 内置脚本函数 `node_tagged("tag")`，当正在运行的节点带有 `tag` 标签时为真。脚本像其他绑定函数
+/// 一样声明它：`fn node_tagged(tag: String) -> Boolean`。
+pub const NODE_TAGGED_FUNCTION: &str = "node_tagged";
+
+impl MortarRuntime {
+    /// Tags of the primary dialogue's node; empty when idle.
+    ///
+    /// 主对话所在节点的标签；空闲时为空。
+    pub fn active_tags(&self) -> &[String] {
+        self.primary_dialogue_state()
+            .map_or(&[], |state| state.tags())
+    }
+
+    /// Whether the primary dialogue's node is tagged `tag`.
+    ///
+    /// 主对话所在节点是否带有 `tag` 标签。
+    pub fn active_has_tag(&self, tag: &str) -> bool {
+        self.primary_dialogue_state()
+            .is_some_and(|state| state.has_tag(tag))
+    }
+}
+
+fn node_tagged(context: &MortarCallContext, args: &[MortarValue]) -> MortarValue {
+    let Some(tag) = args.first() else {
+        return false.into();
+    };
+    let tag = tag.to_display_string();
+    context.tags.contains(&tag).into()
+}
+
+/// The registry a fresh runtime starts with.
+pub(super) fn builtin_functions() -> MortarFunctionRegistry {
+    let mut functions = MortarFunctionRegistry::new();
+    functions.register_with_context_arity(NODE_TAGGED_FUNCTION, 1, node_tagged);
+    functions.register_with_context_arity(
+        super::CAPABILITY_FUNCTION,
+        1,
+        super::capabilities::has_capability,
+    );
+    functions
+}
@@ -0,0 +1,137 @@
+//! # trim.rs
+//!
+//! # trim.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Keeps the runtime's caches bounded over a long play session. [`MortarTrimPolicy`] caps the
+//! prepared nodes and the cached initial variable states, and is applied every frame; games can
+//! also call [`MortarRuntime::trim`] with a stricter policy, e.g. on a level change.
+//!
+//! Collections that persist on purpose and are not trimmed:
+//!
+//! - [`crate::MortarRegistry`] and [`crate::MortarFunctionRegistry`]: owned by the game, one entry
+//!   per registered path or bound function.
+//! - `LoggedConstants` and the registry's noted aliases: one entry per loaded asset, so the log
+//!   stays quiet after the first time.
+//! - Per-conversation state (`active_dialogues`, pending starts, jumps and entries): removed when
+//!   the conversation stops or finishes.
+//!
+//! 让运行时缓存在长时间游玩中保持有界。[`MortarTrimPolicy`] 限制已准备节点和缓存的初始变量状态的
+//! 数量，并在每帧应用；游戏也可以用更严格的策略调用 [`MortarRuntime::trim`]，例如在切换关卡时。
+//!
+//! 有意保留且不会被裁剪的集合：
+//!
+//! - [`crate::MortarRegistry`] 与 [`crate::MortarFunctionRegistry`]：由游戏持有，每个注册路径或
+//!   绑定函数一条。
+//! - `LoggedConstants` 与注册表中已提示的别名：每个已加载资源一条，使日志只在首次出现时输出。
+//! - 每段对话的状态（`active_dialogues`、待处理的开始、跳转与入口）：对话停止或结束时移除。
+
+use super::MortarRuntime;
+
+/// Default cap on prepared nodes.
+///
+/// 已准备节点数量的默认上限。
+pub const DEFAULT_MAX_PREPARED: usize = 64;
+
+/// Default cap on cached initial variable states.
+///
+/// 缓存初始变量状态数量的默认上限。
+pub const DEFAULT_MAX_WARM_FILES: usize = 16;
+
+/// Limits for the runtime's caches. `None` leaves a cache unbounded.
+///
+/// 运行时缓存的限制。`None` 表示该缓存不设上限。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MortarTrimPolicy {
+    /// Most prepared nodes kept; the oldest preparations are dropped first.
+    ///
+    /// 最多保留的已准备节点数；最早准备的条目最先被丢弃。
+    pub max_prepared: Option<usize>,
+    /// Most files whose initial variable state stays cached. Only files without an active
+    /// conversation or preparation are dropped.
+    ///
+    /// 最多缓存初始变量状态的文件数。只会丢弃没有活跃对话或准备条目的文件。
+    pub max_warm_files: Option<usize>,
+    /// Whether stopping a conversation releases the spare capacity of the runtime's maps.
+    ///
+    /// 停止对话时是否释放运行时各映射中多余的容量。
+    pub shrink_on_stop: bool,
+}
+
+impl Default for MortarTrimPolicy {
+    fn default() -> Self {
+        Self {
+            max_prepared: Some(DEFAULT_MAX_PREPARED),
+            max_warm_files: Some(DEFAULT_MAX_WARM_FILES),
+            shrink_on_stop: false,
+        }
+    }
+}
+
+impl MortarRuntime {
+    /// Drops cached entries over the limits of `policy`.
+    ///
+    /// 丢弃超出 `policy` 限制的缓存条目。
+    pub fn trim(&mut self, policy: &MortarTrimPolicy) {
+        if let Some(max) = policy.max_prepared
+            && self.prepared.len() > max
+        {
+            let mut by_age: Vec<_> = self
+                .prepared
+                .iter()
+                .map(|(key, prepared)| (prepared.prepared_at, key.clone()))
+                .collect();
+            by_age.sort();
+            let excess = by_age.len() - max;
+            for (_, key) in by_age.into_iter().take(excess) {
+                self.prepared.remove(&key);
+            }
+        }
+
+        if let Some(max) = policy.max_warm_files
+            && self.warm_variables.len() > max
+        {
+            let mut idle: Vec<String> = self
+                .warm_variables
+                .keys()
+                .filter(|path| !self.uses_path(path))
+                .cloned()
+                .collect();
+            idle.sort();
+            let excess = self.warm_variables.len() - max;
+            for path in idle.into_iter().take(excess) {
+                self.warm_variables.remove(&path);
+            }
+        }
+    }
+
+    /// Releases the spare capacity of the per-conversation maps and caches.
+    ///
+    /// 释放每段对话的映射与缓存中多余的容量。
+    pub fn shrink_to_fit(&mut self) {
+        self.active_dialogues.shrink_to_fit();
+        self.pending_starts.shrink_to_fit();
+        self.pending_jumps.shrink_to_fit();
+        self.pending_entries.shrink_to_fit();
+        self.pending_loads.shrink_to_fit();
+        self.load_stages.shrink_to_fit();
+        self.prepared.shrink_to_fit();
+        self.pending_prepares.shrink_to_fit();
+        self.warm_variables.shrink_to_fit();
+        self.warmed_assets.shrink_to_fit();
+    }
+
+    /// Whether an active conversation or a preparation still reads from `path`.
+    fn uses_path(&self, path: &str) -> bool {
+        self.active_dialogues
+            .values()
+            .any(|state| state.mortar_path == path)
+            || self
+                .prepared
+                .keys()
+                .any(|(prepared_path, _)| prepared_path == path)
+    }
+}
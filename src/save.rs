@@ -0,0 +1,292 @@
+//! # save.rs
+//!
+//! # save.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Opt-in save format (feature `save`). [`MortarSaveData`] bundles the dialogue position and the
+//! variable values under a `version` number. Saves are encoded as UTF-8 JSON: the format is
+//! stable, self-describing, and already the tree that migrations work on. Loading first reads the
+//! bytes into a [`serde_json::Value`], runs the upgraders registered in [`MortarSaveMigrations`]
+//! until the tree reaches [`MORTAR_SAVE_VERSION`], and only then decodes the typed struct. Data
+//! written by a newer version is rejected with [`MortarSaveError::NewerVersion`]. Game save
+//! structs can also be moved in and out of the variables directly, see
+//! [`MortarDialogueVariables::import_from`]. Saves can also be taken and restored on the runtime in
+//! place, see [`MortarRuntime::snapshot`] and [`MortarRuntime::restore`]. The dialogue backlog is
+//! not captured; a blob exported within its own size budget can be embedded, see
+//! [`crate::MortarDialogueHistory::export`].
+//!
+//! 可选的存档格式（`save` 功能）。[`MortarSaveData`] 把对话位置和变量值打包在一个 `version`
+//! 版本号之下。存档以 UTF-8 JSON 编码：该格式稳定、自描述，并且本身就是迁移所操作的树结构。
+//! 读取时先把字节解析为 [`serde_json::Value`]，依次运行 [`MortarSaveMigrations`] 中注册的升级函数，
+//! 直到树达到 [`MORTAR_SAVE_VERSION`]，最后才解码为强类型结构。由更新版本写出的数据会以
+//! [`MortarSaveError::NewerVersion`] 拒绝。游戏自己的存档结构体也可以直接导入或导出变量，参见
+//! [`MortarDialogueVariables::import_from`]。也可以直接在运行时上生成与恢复存档，参见
+//! [`MortarRuntime::snapshot`] 与 [`MortarRuntime::restore`]。对话回顾记录不会被捕获；可以嵌入在其
+//! 自身大小预算内导出的数据，参见 [`crate::MortarDialogueHistory::export`]。
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+mod bridge;
+mod history_export;
+mod snapshot;
+
+pub use bridge::MortarExportError;
+pub use history_export::{MortarHistoryExportPolicy, MortarHistoryFields};
+pub use snapshot::MortarRestoreError;
+
+use crate::{
+    MortarCommand, MortarDialogueVariables, MortarNodeEntry, MortarRngState, MortarRuntime,
+    MortarVariableValue,
+};
+
+/// Version written by [`MortarSaveData::to_bytes`].
+///
+/// [`MortarSaveData::to_bytes`] 写出的版本号。
+pub const MORTAR_SAVE_VERSION: u32 = 1;
+
+/// Upgrades a save tree from the given version to the next one.
+///
+/// 把存档树从给定版本升级到下一个版本。
+pub type MortarSaveMigration = fn(u32, &mut serde_json::Value) -> Result<(), String>;
+
+/// Where the primary dialogue stood when the save was taken.
+///
+/// 存档时主对话所处的位置。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MortarSavedDialogue {
+    pub mortar_path: String,
+    pub node: String,
+    pub text_index: usize,
+    /// Nested options entered in the node's choice, outermost first.
+    ///
+    /// 节点选项中已进入的嵌套选项，从最外层开始。
+    #[serde(default)]
+    pub choice_stack: Vec<usize>,
+    #[serde(default)]
+    pub selected_choice: Option<usize>,
+    /// Whether a `break` dismissed the node's choice.
+    ///
+    /// 节点的选项是否已被 `break` 关闭。
+    #[serde(default)]
+    pub choices_broken: bool,
+    /// Content indices whose `run` statements already executed, in ascending order.
+    ///
+    /// `run` 语句已执行的内容索引，按升序排列。
+    #[serde(default)]
+    pub executed_content_indices: Vec<usize>,
+}
+
+/// A versioned snapshot of dialogue progress.
+///
+/// 带版本号的对话进度快照。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MortarSaveData {
+    pub version: u32,
+    pub dialogue: Option<MortarSavedDialogue>,
+    pub variables: BTreeMap<String, MortarVariableValue>,
+    /// Random stream of the saved conversation, at the start of the saved line.
+    ///
+    /// 存档对话的随机数流，位于存档所在行的开头。
+    #[serde(default)]
+    pub rng: Option<MortarRngState>,
+    /// Dialogue backlog exported within its own budget, see [`Self::with_history`]. Saves
+    /// carry none unless one is embedded.
+    ///
+    /// 在其自身预算内导出的对话回顾记录，参见 [`Self::with_history`]。除非嵌入，存档不包含它。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub history: Option<serde_json::Value>,
+}
+
+/// Why save data could not be loaded.
+///
+/// 存档数据无法读取的原因。
+#[derive(Debug)]
+pub enum MortarSaveError {
+    /// The bytes are not valid save JSON, or the migrated tree does not match the format.
+    ///
+    /// 字节不是有效的存档 JSON，或迁移后的树与格式不符。
+    Format(serde_json::Error),
+    /// The data has no numeric `version` field.
+    ///
+    /// 数据缺少数值类型的 `version` 字段。
+    MissingVersion,
+    /// The data was written by a newer version than this build supports.
+    ///
+    /// 数据由比当前构建更新的版本写出。
+    NewerVersion { found: u32, supported: u32 },
+    /// No upgrader is registered for an older version.
+    ///
+    /// 没有为某个旧版本注册升级函数。
+    MissingMigration { from: u32 },
+    /// A registered upgrader failed.
+    ///
+    /// 已注册的升级函数执行失败。
+    Migration { from: u32, message: String },
+}
+
+impl std::fmt::Display for MortarSaveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Format(err) => write!(f, "malformed save data: {err}"),
+            Self::MissingVersion => write!(f, "save data has no version"),
+            Self::NewerVersion { found, supported } => write!(
+                f,
+                "save data version {found} is newer than the supported version {supported}"
+            ),
+            Self::MissingMigration { from } => {
+                write!(f, "no migration registered for save version {from}")
+            }
+            Self::Migration { from, message } => {
+                write!(f, "migrating save version {from} failed: {message}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for MortarSaveError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Format(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl MortarSaveData {
+    /// Captures the primary dialogue position and the cached variable values.
+    ///
+    /// 记录主对话的位置和缓存的变量值。
+    pub fn capture(runtime: &MortarRuntime, variables: &MortarDialogueVariables) -> Self {
+        let dialogue = runtime.primary_dialogue_state().map(|state| {
+            let mut executed: Vec<usize> = state.executed_content_indices.iter().copied().collect();
+            executed.sort_unstable();
+            MortarSavedDialogue {
+                mortar_path: state.mortar_path.clone(),
+                node: state.current_node.clone(),
+                text_index: state.text_index,
+                choice_stack: state.choice_stack.clone(),
+                selected_choice: state.selected_choice,
+                choices_broken: state.choices_broken,
+                executed_content_indices: executed,
+            }
+        });
+        let variables = variables
+            .state
+            .iter()
+            .flat_map(|state| state.values())
+            .map(|(name, value)| (name.to_owned(), value.clone()))
+            .collect();
+        Self {
+            version: MORTAR_SAVE_VERSION,
+            dialogue,
+            variables,
+            rng: runtime.conversation_rng_state(),
+            history: None,
+        }
+    }
+
+    /// Encodes the save as UTF-8 JSON.
+    ///
+    /// 将存档编码为 UTF-8 JSON。
+    pub fn to_bytes(&self) -> Vec<u8> {
+        serde_json::to_vec(self).expect("save data has only string keys and always serializes")
+    }
+
+    /// Decodes a save, upgrading older versions with `migrations`.
+    ///
+    /// 解码存档，并使用 `migrations` 升级旧版本。
+    pub fn from_bytes(
+        bytes: &[u8],
+        migrations: &MortarSaveMigrations,
+    ) -> Result<Self, MortarSaveError> {
+        let mut tree: serde_json::Value =
+            serde_json::from_slice(bytes).map_err(MortarSaveError::Format)?;
+        let version = tree
+            .get("version")
+            .and_then(serde_json::Value::as_u64)
+            .and_then(|version| u32::try_from(version).ok())
+            .ok_or(MortarSaveError::MissingVersion)?;
+        if version > MORTAR_SAVE_VERSION {
+            return Err(MortarSaveError::NewerVersion {
+                found: version,
+                supported: MORTAR_SAVE_VERSION,
+            });
+        }
+        migrations.upgrade(version, &mut tree)?;
+        serde_json::from_value(tree).map_err(MortarSaveError::Format)
+    }
+
+    /// Queues the saved variable values and returns the event that resumes the saved line.
+    /// The values are written over the variable state before the resumed line renders. Call
+    /// [`Self::restore_rng`] as well for the resumed conversation to roll as it did.
+    ///
+    /// 排队恢复存档中的变量值，并返回恢复到存档所在行的事件。变量值会在恢复的行显示之前写入变量状态。
+    /// 若要让恢复的对话掷出与原先相同的结果，还需调用 [`Self::restore_rng`]。
+    pub fn restore(&self, variables: &mut MortarDialogueVariables) -> Option<MortarCommand> {
+        self.queue_variables(variables);
+        self.dialogue.as_ref().map(|dialogue| {
+            MortarCommand::start_node_at(
+                &dialogue.mortar_path,
+                &dialogue.node,
+                MortarNodeEntry::at(dialogue.text_index),
+            )
+        })
+    }
+
+    fn queue_variables(&self, variables: &mut MortarDialogueVariables) {
+        variables.restored = self
+            .variables
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect();
+    }
+
+    /// Hands the saved random stream to the conversation that [`Self::restore`] resumes.
+    ///
+    /// 将存档中的随机数流交给 [`Self::restore`] 所恢复的对话。
+    pub fn restore_rng(&self, runtime: &mut MortarRuntime) {
+        if let Some(state) = self.rng {
+            runtime.queue_conversation_rng(state);
+        }
+    }
+}
+
+/// Upgraders for older save versions, keyed by the version they upgrade from.
+///
+/// 旧存档版本的升级函数，以其升级起始版本为键。
+#[derive(Resource, Default)]
+pub struct MortarSaveMigrations {
+    upgraders: HashMap<u32, MortarSaveMigration>,
+}
+
+impl MortarSaveMigrations {
+    /// Registers the upgrader that turns a `from_version` tree into a `from_version + 1` tree.
+    ///
+    /// 注册把 `from_version` 版本的树升级为 `from_version + 1` 版本的升级函数。
+    pub fn register(&mut self, from_version: u32, upgrade: MortarSaveMigration) -> &mut Self {
+        self.upgraders.insert(from_version, upgrade);
+        self
+    }
+
+    fn upgrade(&self, from: u32, tree: &mut serde_json::Value) -> Result<(), MortarSaveError> {
+        for version in from..MORTAR_SAVE_VERSION {
+            let upgrade = self
+                .upgraders
+                .get(&version)
+                .ok_or(MortarSaveError::MissingMigration { from: version })?;
+            upgrade(version, tree).map_err(|message| MortarSaveError::Migration {
+                from: version,
+                message,
+            })?;
+            if let Some(fields) = tree.as_object_mut() {
+                fields.insert("version".to_owned(), (version + 1).into());
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,351 @@
+//! # bridge.rs
+//!
+//! # bridge.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Moves whole game structs in and out of [`MortarDialogueVariables`] through serde. Importing
+//! serializes the struct to JSON and seeds one variable per leaf: nested fields become dotted
+//! names under an optional prefix, so `player.stats.hp` holds `PlayerData::stats.hp` imported
+//! under `"player"`. Names follow serde, so `#[serde(rename)]` renames the variable too. Strings,
+//! numbers and booleans map directly; `null` leaves are left unset and arrays are skipped with a
+//! warning. Exporting rebuilds the tree from the variables under the prefix and deserializes it,
+//! naming the dotted path of any field that is missing or holds the wrong type.
+//!
+//! 通过 serde 在 [`MortarDialogueVariables`] 与整个游戏结构体之间搬运数据。导入时把结构体序列化为
+//! JSON，并为每个叶子预设一个变量：嵌套字段在可选前缀下变成以点分隔的名称，因此以 `"player"` 为前缀
+//! 导入时，`PlayerData::stats.hp` 存放在 `player.stats.hp` 中。名称遵循 serde，因此 `#[serde(rename)]`
+//! 同样会重命名变量。字符串、数字和布尔值直接对应；`null` 叶子不会被设置，数组会被跳过并给出警告。
+//! 导出时根据前缀下的变量重建树并反序列化，若字段缺失或类型不符，会给出该字段以点分隔的路径。
+
+use bevy::log::{debug, warn};
+use serde::de::{self, DeserializeOwned, DeserializeSeed, IntoDeserializer, MapAccess, Visitor};
+use serde::{Serialize, forward_to_deserialize_any};
+use std::collections::{BTreeMap, btree_map};
+use std::fmt;
+
+use crate::debug::LOG_DIALOGUE;
+use crate::{MortarDialogueVariables, MortarVariableValue};
+
+/// Why variables could not be exported into a struct.
+///
+/// 变量无法导出到结构体的原因。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MortarExportError {
+    /// No variable holds the field at this dotted path.
+    ///
+    /// 没有变量存放该点分路径上的字段。
+    MissingField { field: String },
+    /// The variable at this dotted path does not fit the field's type.
+    ///
+    /// 该点分路径上的变量与字段类型不符。
+    MistypedField { field: String, message: String },
+}
+
+impl fmt::Display for MortarExportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingField { field } => write!(f, "no variable holds field '{field}'"),
+            Self::MistypedField { field, message } => write!(f, "field '{field}': {message}"),
+        }
+    }
+}
+
+impl std::error::Error for MortarExportError {}
+
+impl MortarDialogueVariables {
+    /// Seeds one variable per leaf of `value`, named by its dotted path under `prefix`. Like
+    /// [`Self::seed`], the values apply when the variables are next initialized, so a game
+    /// imports its save struct each time a conversation starts.
+    ///
+    /// 为 `value` 的每个叶子预设一个变量，名称为其在 `prefix` 下的点分路径。与 [`Self::seed`] 相同，
+    /// 这些值会在变量下次初始化时生效，因此游戏需要在每段对话开始时导入其存档结构体。
+    pub fn import_from<T: Serialize>(&mut self, value: &T, prefix: Option<&str>) {
+        let tree = match serde_json::to_value(value) {
+            Ok(tree) => tree,
+            Err(err) => {
+                warn!(target: LOG_DIALOGUE, "Cannot import variables: {}", err);
+                return;
+            }
+        };
+        let mut leaves = Vec::new();
+        flatten(prefix.map(str::to_owned), &tree, &mut leaves);
+        for (name, value) in leaves {
+            self.seed(name, value);
+        }
+    }
+
+    /// Rebuilds a `T` from the variables under `prefix`, seeded values included.
+    ///
+    /// 根据 `prefix` 下的变量（包括已预设的值）重建 `T`。
+    pub fn export_into<T: DeserializeOwned>(
+        &self,
+        prefix: Option<&str>,
+    ) -> Result<T, MortarExportError> {
+        let mut values: BTreeMap<&str, &MortarVariableValue> =
+            self.state.iter().flat_map(|state| state.values()).collect();
+        values.extend(
+            self.restored
+                .iter()
+                .map(|(name, value)| (name.as_str(), value)),
+        );
+        let tree = Tree::under(&values, prefix);
+        let deserializer = TreeDeserializer {
+            tree: &tree,
+            path: prefix.unwrap_or_default().to_owned(),
+        };
+        T::deserialize(deserializer).map_err(PathError::into_export_error)
+    }
+}
+
+fn join(path: &str, field: &str) -> String {
+    if path.is_empty() {
+        field.to_owned()
+    } else {
+        format!("{path}.{field}")
+    }
+}
+
+/// Collects the variable leaves of `value`, named by their dotted path.
+fn flatten(
+    name: Option<String>,
+    value: &serde_json::Value,
+    leaves: &mut Vec<(String, MortarVariableValue)>,
+) {
+    match value {
+        serde_json::Value::Object(fields) => {
+            for (field, child) in fields {
+                let path = join(name.as_deref().unwrap_or_default(), field);
+                flatten(Some(path), child, leaves);
+            }
+        }
+        serde_json::Value::Null => {
+            debug!(target: LOG_DIALOGUE, "Leaving {:?} unset: the value is null", name);
+        }
+        serde_json::Value::Array(_) => {
+            warn!(target: LOG_DIALOGUE, "Skipping {:?}: arrays have no variable form", name);
+        }
+        leaf => match (name, MortarVariableValue::from_json(leaf)) {
+            (Some(name), Some(value)) => leaves.push((name, value)),
+            (name, _) => {
+                warn!(target: LOG_DIALOGUE, "Skipping {:?}: it needs a prefix to name it", name);
+            }
+        },
+    }
+}
+
+/// Variables arranged by the segments of their dotted names.
+enum Tree<'a> {
+    Leaf(&'a MortarVariableValue),
+    Fields(BTreeMap<&'a str, Tree<'a>>),
+}
+
+impl<'a> Tree<'a> {
+    /// The variables named `prefix` or under `prefix.`; a field wins over a value of the same
+    /// name.
+    fn under(values: &BTreeMap<&'a str, &'a MortarVariableValue>, prefix: Option<&str>) -> Self {
+        let mut root = Tree::Fields(BTreeMap::new());
+        for (&name, &value) in values {
+            if prefix == Some(name) {
+                root = Tree::Leaf(value);
+                continue;
+            }
+            let path = match prefix {
+                None => Some(name),
+                Some(prefix) => name
+                    .strip_prefix(prefix)
+                    .and_then(|rest| rest.strip_prefix('.')),
+            };
+            if let Some(path) = path {
+                root.insert(path, value);
+            }
+        }
+        root
+    }
+
+    fn insert(&mut self, path: &'a str, value: &'a MortarVariableValue) {
+        if let Tree::Leaf(_) = self {
+            *self = Tree::Fields(BTreeMap::new());
+        }
+        let Tree::Fields(fields) = self else {
+            return;
+        };
+        match path.split_once('.') {
+            None => {
+                fields.entry(path).or_insert_with(|| Tree::Leaf(value));
+            }
+            Some((head, rest)) => fields
+                .entry(head)
+                .or_insert_with(|| Tree::Fields(BTreeMap::new()))
+                .insert(rest, value),
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Failure {
+    Missing(&'static str),
+    Mistyped(String),
+}
+
+/// A deserialization error, tagged with the dotted path it happened at by the innermost
+/// deserializer it passes through.
+#[derive(Debug)]
+struct PathError {
+    failure: Failure,
+    path: Option<String>,
+}
+
+impl PathError {
+    fn at(mut self, path: &str) -> Self {
+        if self.path.is_none() {
+            self.path = Some(match self.failure {
+                Failure::Missing(field) => join(path, field),
+                Failure::Mistyped(_) => path.to_owned(),
+            });
+        }
+        self
+    }
+
+    fn into_export_error(self) -> MortarExportError {
+        let field = self.path.unwrap_or_default();
+        match self.failure {
+            Failure::Missing(_) => MortarExportError::MissingField { field },
+            Failure::Mistyped(message) => MortarExportError::MistypedField { field, message },
+        }
+    }
+}
+
+impl fmt::Display for PathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.failure {
+            Failure::Missing(field) => write!(f, "missing field `{field}`"),
+            Failure::Mistyped(message) => f.write_str(message),
+        }
+    }
+}
+
+impl std::error::Error for PathError {}
+
+impl de::Error for PathError {
+    fn custom<T: fmt::Display>(message: T) -> Self {
+        Self {
+            failure: Failure::Mistyped(message.to_string()),
+            path: None,
+        }
+    }
+
+    fn missing_field(field: &'static str) -> Self {
+        Self {
+            failure: Failure::Missing(field),
+            path: None,
+        }
+    }
+}
+
+struct TreeDeserializer<'a> {
+    tree: &'a Tree<'a>,
+    path: String,
+}
+
+/// Hands a variable to `visitor`; whole numbers go as integers so integer fields accept them.
+fn visit_value<'de, V: Visitor<'de>>(
+    value: &MortarVariableValue,
+    visitor: V,
+) -> Result<V::Value, PathError> {
+    match value {
+        MortarVariableValue::String(text) => visitor.visit_str(text),
+        MortarVariableValue::Boolean(flag) => visitor.visit_bool(*flag),
+        MortarVariableValue::Number(number) if number.fract() != 0.0 => visitor.visit_f64(*number),
+        MortarVariableValue::Number(number) if *number >= 0.0 && *number <= u64::MAX as f64 => {
+            visitor.visit_u64(*number as u64)
+        }
+        MortarVariableValue::Number(number) if *number >= i64::MIN as f64 => {
+            visitor.visit_i64(*number as i64)
+        }
+        MortarVariableValue::Number(number) => visitor.visit_f64(*number),
+    }
+}
+
+impl<'de> de::Deserializer<'de> for TreeDeserializer<'_> {
+    type Error = PathError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, PathError> {
+        let result = match self.tree {
+            Tree::Leaf(value) => visit_value(value, visitor),
+            Tree::Fields(fields) => visitor.visit_map(FieldAccess {
+                fields: fields.iter(),
+                value: None,
+                path: &self.path,
+            }),
+        };
+        result.map_err(|err| err.at(&self.path))
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, PathError> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, PathError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, PathError> {
+        // Unit variants are stored as their name.
+        //
+        // 单元变体以其名称存储。
+        let Tree::Leaf(MortarVariableValue::String(name)) = self.tree else {
+            return self.deserialize_any(visitor);
+        };
+        visitor
+            .visit_enum(name.as_str().into_deserializer())
+            .map_err(|err: PathError| err.at(&self.path))
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf unit
+        unit_struct seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+struct FieldAccess<'a> {
+    fields: btree_map::Iter<'a, &'a str, Tree<'a>>,
+    value: Option<(&'a str, &'a Tree<'a>)>,
+    path: &'a str,
+}
+
+impl<'de> MapAccess<'de> for FieldAccess<'_> {
+    type Error = PathError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, PathError> {
+        let Some((&name, tree)) = self.fields.next() else {
+            return Ok(None);
+        };
+        self.value = Some((name, tree));
+        seed.deserialize(name.into_deserializer()).map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, PathError> {
+        let Some((name, tree)) = self.value.take() else {
+            return Err(de::Error::custom("value requested before its field"));
+        };
+        seed.deserialize(TreeDeserializer {
+            tree,
+            path: join(self.path, name),
+        })
+    }
+}
@@ -0,0 +1,219 @@
+//! # history_export.rs
+//!
+//! # history_export.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Partial persistence of the dialogue backlog for saves with a size cap. Exporting walks
+//! [`MortarDialogueHistory`] from the newest entry back, encoding each one and adding its exact
+//! encoded size to the total, and stops at the first entry that would break the
+//! [`MortarHistoryExportPolicy`] budget, so the oldest lines are the ones left out. The compact
+//! [`MortarHistoryFields::LineIds`] form drops headers and bodies and keeps where each line came
+//! from; importing rebuilds those bodies from the loaded files and leaves a placeholder entry with
+//! no body when the line cannot be found. Confirmed options have no line id to rebuild from, so
+//! they keep their text in either form. The blob is UTF-8 JSON and can travel inside
+//! [`MortarSaveData`] or on its own.
+//!
+//! 为有大小上限的存档部分保存对话回顾记录。导出时从最新的记录向前遍历 [`MortarDialogueHistory`]，
+//! 对每条记录编码并把其确切的编码大小计入总量，遇到第一条会超出 [`MortarHistoryExportPolicy`] 预算的
+//! 记录即停止，因此被舍弃的是最旧的行。紧凑的 [`MortarHistoryFields::LineIds`] 形式会丢弃头部与正文，
+//! 只保留每行的来源；导入时根据已加载的文件重建这些正文，找不到对应行时保留一条没有正文的占位记录。
+//! 确认的选项没有可用于重建的行标识符，因此两种形式下都会保留其文本。
+//! 导出数据为 UTF-8 JSON，可以放在 [`MortarSaveData`] 中，也可以单独保存。
+
+use bevy::asset::Assets;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::{MortarSaveData, MortarSaveError};
+use crate::{
+    DialogueState, MortarAsset, MortarDialogueHistory, MortarDialogueHistoryEntry, MortarRegistry,
+};
+
+/// Bytes of the export around its entries: `{"entries":[` and `]}`.
+const ENVELOPE_BYTES: usize = 14;
+
+/// Which fields of each entry an export keeps.
+///
+/// 导出时保留每条记录的哪些字段。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MortarHistoryFields {
+    /// Every field, bodies as shown.
+    ///
+    /// 全部字段，正文与显示时一致。
+    #[default]
+    Full,
+    /// Only the file, node and line id of each entry; bodies are rebuilt on import from the
+    /// script text, before interpolation.
+    ///
+    /// 只保留每条记录的文件、节点与行标识符；导入时根据脚本文本（插值之前）重建正文。
+    LineIds,
+}
+
+/// Limits of a history export. Entries beyond them are dropped oldest first.
+///
+/// 历史导出的限制。超出限制的记录从最旧的开始丢弃。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MortarHistoryExportPolicy {
+    pub max_entries: Option<usize>,
+    /// Cap on the encoded size of the whole export, in bytes.
+    ///
+    /// 整个导出内容编码后的大小上限，以字节为单位。
+    pub max_bytes: Option<usize>,
+    pub fields: MortarHistoryFields,
+}
+
+#[derive(Serialize)]
+struct EncodedEntry<'a> {
+    path: &'a str,
+    node: &'a str,
+    line_id: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    header: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    body: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    choice: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct DecodedEntry {
+    path: String,
+    node: String,
+    line_id: String,
+    #[serde(default)]
+    header: Option<String>,
+    #[serde(default)]
+    body: Option<String>,
+    #[serde(default)]
+    choice: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct DecodedHistory {
+    entries: Vec<DecodedEntry>,
+}
+
+impl MortarDialogueHistory {
+    /// Encodes the newest entries that fit `policy` as UTF-8 JSON. The result never exceeds
+    /// `max_bytes`, unless even an empty export would.
+    ///
+    /// 将符合 `policy` 的最新记录编码为 UTF-8 JSON。结果不会超过 `max_bytes`，除非连空导出都放不下。
+    pub fn export(&self, policy: &MortarHistoryExportPolicy) -> Vec<u8> {
+        let full = policy.fields == MortarHistoryFields::Full;
+        let mut size = ENVELOPE_BYTES;
+        let mut kept = Vec::new();
+        for entry in self
+            .entries
+            .iter()
+            .rev()
+            .take(policy.max_entries.unwrap_or(usize::MAX))
+        {
+            let encoded = serde_json::to_vec(&EncodedEntry {
+                path: &entry.mortar_path,
+                node: &entry.node,
+                line_id: &entry.line_id,
+                header: full.then_some(entry.header.as_str()),
+                body: entry.body.as_deref().filter(|_| full || entry.is_choice()),
+                choice: entry.choice,
+            })
+            .expect("history entries have only string fields and always serialize");
+            let added = encoded.len() + usize::from(!kept.is_empty());
+            if policy.max_bytes.is_some_and(|max| size + added > max) {
+                break;
+            }
+            size += added;
+            kept.push(encoded);
+        }
+
+        let mut bytes = Vec::with_capacity(size);
+        bytes.extend_from_slice(br#"{"entries":["#);
+        for (index, encoded) in kept.iter().rev().enumerate() {
+            if index > 0 {
+                bytes.push(b',');
+            }
+            bytes.extend_from_slice(encoded);
+        }
+        bytes.extend_from_slice(b"]}");
+        bytes
+    }
+
+    /// Replaces the entries with an export, rebuilding missing bodies from the files in
+    /// `registry`. Entries whose line cannot be found keep no body. Returns the number of entries
+    /// left as placeholders.
+    ///
+    /// 用导出内容替换现有记录，并根据 `registry` 中的文件重建缺失的正文。找不到对应行的记录不含正文。
+    /// 返回作为占位保留的记录数量。
+    pub fn import(
+        &mut self,
+        bytes: &[u8],
+        registry: &MortarRegistry,
+        assets: &Assets<MortarAsset>,
+    ) -> Result<usize, MortarSaveError> {
+        let decoded: DecodedHistory =
+            serde_json::from_slice(bytes).map_err(MortarSaveError::Format)?;
+        let mut nodes: HashMap<(String, String), Option<DialogueState>> = HashMap::new();
+        let mut placeholders = 0;
+        self.entries.clear();
+        for entry in decoded.entries {
+            let body = entry.body.or_else(|| {
+                nodes
+                    .entry((entry.path.clone(), entry.node.clone()))
+                    .or_insert_with(|| parse_node(&entry.path, &entry.node, registry, assets))
+                    .as_ref()?
+                    .text_items()
+                    .iter()
+                    .find(|item| item.line_id == entry.line_id)
+                    .map(|item| item.value.clone())
+            });
+            placeholders += usize::from(body.is_none());
+            self.entries.push_back(MortarDialogueHistoryEntry {
+                mortar_path: entry.path,
+                node: entry.node,
+                line_id: entry.line_id,
+                header: entry.header.unwrap_or_default(),
+                body,
+                choice: entry.choice,
+            });
+        }
+        self.trim();
+        Ok(placeholders)
+    }
+}
+
+/// The lines of `node` in the file registered at `path`, if it is loaded.
+fn parse_node(
+    path: &str,
+    node: &str,
+    registry: &MortarRegistry,
+    assets: &Assets<MortarAsset>,
+) -> Option<DialogueState> {
+    let asset = assets.get(registry.get(path)?)?;
+    let parsed = asset.parsed_node(node)?;
+    Some(DialogueState::from_parsed(
+        path.to_owned(),
+        node.to_owned(),
+        parsed,
+    ))
+}
+
+impl MortarSaveData {
+    /// Embeds a history blob from [`MortarDialogueHistory::export`].
+    ///
+    /// 嵌入由 [`MortarDialogueHistory::export`] 生成的历史数据。
+    pub fn with_history(mut self, blob: &[u8]) -> Result<Self, MortarSaveError> {
+        self.history = Some(serde_json::from_slice(blob).map_err(MortarSaveError::Format)?);
+        Ok(self)
+    }
+
+    /// The embedded history blob, for [`MortarDialogueHistory::import`].
+    ///
+    /// 嵌入的历史数据，供 [`MortarDialogueHistory::import`] 使用。
+    pub fn history_blob(&self) -> Option<Vec<u8>> {
+        self.history.as_ref().map(|history| {
+            serde_json::to_vec(history).expect("a parsed history blob always serializes")
+        })
+    }
+}
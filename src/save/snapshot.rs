@@ -0,0 +1,171 @@
+//! # snapshot.rs
+//!
+//! # snapshot.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Taking and restoring saves directly on the runtime. [`MortarRuntime::snapshot`] captures the
+//! primary dialogue with its choice stack, selection and executed runs. [`MortarRuntime::restore`]
+//! rebuilds the dialogue state from the loaded asset in place, without going through a start
+//! request: the node's parsed lines and choices come from the current file, never from the save.
+//! The node must still exist; the line index is clamped to the node, and saved choice levels,
+//! selections and content indices that the file no longer has are dropped with a warning.
+//!
+//! 直接在运行时上生成与恢复存档。[`MortarRuntime::snapshot`] 记录主对话及其选项栈、当前选择和已执行
+//! 的 run。[`MortarRuntime::restore`] 不经过开始请求，直接基于已加载的资源重建对话状态：节点解析后的
+//! 行与选项来自当前文件，而不是存档。节点必须仍然存在；行索引会限制在节点范围内，文件中已不存在的
+//! 选项层级、选择与内容索引会被丢弃并给出警告。
+
+use bevy::asset::Assets;
+use bevy::log::warn;
+use bevy::prelude::Entity;
+use std::fmt;
+
+use super::{MortarSaveData, MortarSavedDialogue};
+use crate::asset::find_node;
+use crate::debug::LOG_DIALOGUE;
+use crate::{
+    DialogueState, MortarAsset, MortarDialogueVariables, MortarNodeEntry, MortarRegistry,
+    MortarRuntime,
+};
+
+/// Why a save could not be restored into the runtime.
+///
+/// 存档无法恢复到运行时的原因。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MortarRestoreError {
+    /// The save holds no dialogue position.
+    ///
+    /// 存档不包含对话位置。
+    NoDialogue,
+    /// The saved file is not registered.
+    ///
+    /// 存档所在的文件未注册。
+    UnknownFile { mortar_path: String },
+    /// The saved file is registered but has not loaded.
+    ///
+    /// 存档所在的文件已注册但尚未加载。
+    NotLoaded { mortar_path: String },
+    /// The saved node is gone from the file.
+    ///
+    /// 存档所在的节点已从文件中移除。
+    NodeNotFound { mortar_path: String, node: String },
+}
+
+impl fmt::Display for MortarRestoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoDialogue => write!(f, "save holds no dialogue"),
+            Self::UnknownFile { mortar_path } => write!(f, "'{mortar_path}' is not registered"),
+            Self::NotLoaded { mortar_path } => write!(f, "'{mortar_path}' has not loaded"),
+            Self::NodeNotFound { mortar_path, node } => {
+                write!(f, "node '{node}' is gone from '{mortar_path}'")
+            }
+        }
+    }
+}
+
+impl std::error::Error for MortarRestoreError {}
+
+impl MortarRuntime {
+    /// Captures the primary dialogue and the cached variable values; `None` without a dialogue.
+    ///
+    /// 记录主对话与缓存的变量值；没有对话时返回 `None`。
+    pub fn snapshot(&self, variables: &MortarDialogueVariables) -> Option<MortarSaveData> {
+        self.primary_dialogue_state()?;
+        Some(MortarSaveData::capture(self, variables))
+    }
+
+    /// Rebuilds the saved dialogue from its loaded file as the primary dialogue, replacing the one
+    /// running, and queues the saved variable values and random stream.
+    ///
+    /// 基于已加载的文件将存档中的对话重建为主对话并替换正在运行的对话，同时排队恢复存档中的变量值
+    /// 与随机数流。
+    pub fn restore(
+        &mut self,
+        save: &MortarSaveData,
+        assets: &Assets<MortarAsset>,
+        registry: &MortarRegistry,
+        variables: &mut MortarDialogueVariables,
+    ) -> Result<(), MortarRestoreError> {
+        let saved = save
+            .dialogue
+            .as_ref()
+            .ok_or(MortarRestoreError::NoDialogue)?;
+        let path = registry.canonical_key(&saved.mortar_path);
+        let handle = registry
+            .get(&path)
+            .ok_or_else(|| MortarRestoreError::UnknownFile {
+                mortar_path: path.clone(),
+            })?;
+        let asset = assets
+            .get(handle)
+            .ok_or_else(|| MortarRestoreError::NotLoaded {
+                mortar_path: path.clone(),
+            })?;
+        let node_data = find_node(&asset.data, &saved.node).ok_or_else(|| {
+            MortarRestoreError::NodeNotFound {
+                mortar_path: path.clone(),
+                node: saved.node.clone(),
+            }
+        })?;
+
+        let mut state = self.parse_node(&path, &saved.node, node_data, asset);
+        state.begin_visit();
+        state.enter_at(MortarNodeEntry::at(saved.text_index));
+        apply_saved_progress(&mut state, saved);
+
+        let entity = Entity::PLACEHOLDER;
+        save.queue_variables(variables);
+        self.end_conversation_rng(Some(entity));
+        save.restore_rng(self);
+        self.begin_conversation_rng(entity, &path, &saved.node);
+        let started = !self.active_dialogues.contains_key(&entity);
+        self.active_dialogues.insert(entity, state);
+        self.focus_on_activation(entity, started);
+        self.pending_starts.remove(&entity);
+        self.pending_entries.remove(&entity);
+        self.pending_loads.remove(&entity);
+        Ok(())
+    }
+}
+
+/// Applies the saved choice and run progress that still fit the node.
+fn apply_saved_progress(state: &mut DialogueState, saved: &MortarSavedDialogue) {
+    for &level in &saved.choice_stack {
+        let nested = state
+            .get_current_choices()
+            .and_then(|choices| choices.get(level))
+            .is_some_and(|choice| choice.choice.is_some());
+        if !nested {
+            warn!(
+                target: LOG_DIALOGUE,
+                "Saved choice level {} is gone from node '{}'; restoring the outer level",
+                level,
+                saved.node
+            );
+            break;
+        }
+        state.push_choice(level);
+    }
+    state.choices_broken = saved.choices_broken;
+    let options = state.get_choices().map_or(0, Vec::len);
+    state.selected_choice = saved.selected_choice.filter(|&index| index < options);
+    let content = state.node_data().content.len();
+    let (kept, dropped): (Vec<usize>, Vec<usize>) = saved
+        .executed_content_indices
+        .iter()
+        .copied()
+        .partition(|&index| index < content);
+    if !dropped.is_empty() {
+        warn!(
+            target: LOG_DIALOGUE,
+            "Executed content {:?} is gone from node '{}'",
+            dropped,
+            saved.node
+        );
+    }
+    state.executed_content_indices.extend(kept);
+}
@@ -13,61 +13,56 @@
 //! 包含 Mortar 运行时的核心事件处理系统。它会响应开始、推进、选择和停止请求，
 //! 维护活跃对话映射的一致性，并在对话自然结束时发出完成事件。
 
+use crate::debug::LOG_DIALOGUE;
+use crate::dialogue_state::RemovalScope;
+use crate::preparation::handle_prepare_node;
+use crate::runtime::{SelectionTransition, resolve_confirm};
 use crate::{
-    DialogueState, MortarAsset, MortarDialogueFinished, MortarEvent, MortarRegistry, MortarRuntime,
+    AdvanceIntent, ChoiceInputSource, ConfirmEffect, DialogueState, MortarAsset,
+    MortarChoiceCaptured, MortarChoiceResolved, MortarChoiceSelected, MortarCommand,
+    MortarDialogueFinished, MortarDialogueVariables, MortarErrorEvent, MortarNodePrepared,
+    MortarRegistry, MortarRuntime, MortarVariableState,
 };
 use bevy::asset::{AssetServer, Assets};
-use bevy::log::{info, warn};
-use bevy::prelude::{Entity, MessageReader, MessageWriter, Res, ResMut};
+use bevy::ecs::system::SystemParam;
+use bevy::log::{debug, warn};
+use bevy::prelude::{DetectChangesMut, Entity, MessageReader, MessageWriter, Res, ResMut, Time};
 
-fn entity_to_option(entity: Entity) -> Option<Entity> {
+mod hot_reload;
+mod node_start;
+
+pub(crate) use hot_reload::resync_reloaded_dialogues;
+use node_start::{ActivationWriters, AvailabilityGate, handle_jump_to_node, handle_start_node};
+pub(crate) use node_start::{
+    activate_dialogue, check_pending_start_system, handle_pending_jump_system,
+};
+
+pub(crate) fn entity_to_option(entity: Entity) -> Option<Entity> {
     (entity != Entity::PLACEHOLDER).then_some(entity)
 }
 
-fn remove_entity_dialogue(runtime: &mut MortarRuntime, entity: Entity) {
-    runtime.active_dialogues.remove(&entity);
-    if runtime.primary_dialogue == Some(entity) {
-        runtime.primary_dialogue = None;
-    }
+/// Writers for the messages advancing, selecting and confirming write.
+#[derive(SystemParam)]
+pub(crate) struct ConfirmWriters<'w> {
+    selected: MessageWriter<'w, MortarChoiceSelected>,
+    finished: MessageWriter<'w, MortarDialogueFinished>,
+    captured: MessageWriter<'w, MortarChoiceCaptured>,
+    resolved: MessageWriter<'w, MortarChoiceResolved>,
 }
 
-fn handle_start_node(
-    path: &str,
-    node: &str,
-    target: Option<Entity>,
-    runtime: &mut MortarRuntime,
-    registry: &mut MortarRegistry,
-    assets: &Assets<MortarAsset>,
-    asset_server: &AssetServer,
-) {
-    let handle = if let Some(h) = registry.get(path) {
-        h.clone()
-    } else {
-        info!("Auto-loading mortar file: {}", path);
-        let handle = asset_server.load::<MortarAsset>(path.to_owned());
-        registry.register(path.to_owned(), handle.clone());
-        handle
-    };
-
-    let Some(asset) = assets.get(&handle) else {
-        dev_info!("Asset '{}' not loaded yet, waiting...", path);
-        let entity = target.unwrap_or(Entity::PLACEHOLDER);
-        runtime
-            .pending_starts
-            .insert(entity, (path.to_owned(), node.to_owned()));
-        return;
-    };
-    let Some(node_data) = asset.data.nodes.iter().find(|n| n.name == node) else {
-        warn!("Node '{}' not found in '{}'", node, path);
-        return;
-    };
-    let state = DialogueState::new(path.to_owned(), node.to_owned(), node_data.clone());
+/// What resolving a confirm reads besides the runtime, the same resources
+/// [`MortarRuntime::simulate_confirm`] is given.
+struct ConfirmScope<'a> {
+    registry: &'a MortarRegistry,
+    assets: &'a Assets<MortarAsset>,
+    variables: Option<&'a mut MortarVariableState>,
+}
 
-    let entity = target.unwrap_or(Entity::PLACEHOLDER);
-    runtime.active_dialogues.insert(entity, state);
-    runtime.primary_dialogue = Some(entity);
-    runtime.pending_starts.remove(&entity);
-    dev_info!("Started node: {} in {} for entity {:?}", node, path, entity);
+fn remove_entity_dialogue(runtime: &mut MortarRuntime, entity: Entity) {
+    runtime.active_dialogues.remove(&entity);
+    runtime.end_conversation_rng(Some(entity));
+    runtime.reset_auto_advance(entity);
+    runtime.drop_focus(entity);
 }
 
 fn handle_next_text(
@@ -79,214 +74,403 @@ fn handle_next_text(
         return;
     };
 
-    let (should_continue, has_choices, choices_broken, next_node_info, mortar_path, current_node) = {
+    let intent = runtime.advance_intent_for(entity);
+    match intent {
+        AdvanceIntent::Nothing { .. } => return,
+        AdvanceIntent::BlockedByRuns { .. } => {
+            dev_info!(target: LOG_DIALOGUE, "Runs are executing, ignoring NextText");
+            return;
+        }
+        AdvanceIntent::RevealRemaining => {
+            dev_info!(target: LOG_DIALOGUE, "Line still revealing, finishing the reveal");
+            runtime.advance_gate.finish_reveal = true;
+            return;
+        }
+        AdvanceIntent::ContinueReveal => {
+            dev_info!(target: LOG_DIALOGUE, "Reveal paused at a checkpoint, continuing it");
+            runtime.advance_gate.request_continue();
+            return;
+        }
+        _ => {}
+    }
+
+    {
         let Some(state) = runtime.active_dialogues.get_mut(&entity) else {
             return;
         };
-        state.pending_run_position = state
-            .line_group_last_content_index()
-            .map(|content_idx| content_idx + 1);
+        state.pending_run_position = state.run_position_after_line_group();
 
         if state.next_text() {
-            (true, false, false, None, String::new(), String::new())
-        } else {
-            dev_info!("Reached end of node: {}", state.current_node);
-            let has_choices = state.has_choices();
-            let choices_broken = state.choices_broken;
-            let next_node = state.get_next_node().map(|s| s.to_string());
-            let path = state.mortar_path.clone();
-            let node = state.current_node.clone();
-            (false, has_choices, choices_broken, next_node, path, node)
+            runtime.settle_selection(entity, SelectionTransition::Advance);
+            return;
         }
-    };
-
-    if should_continue {
-        return;
+        dev_info!(target: LOG_DIALOGUE, "Reached end of node: {}", state.current_node);
     }
 
-    if has_choices && !choices_broken {
-        dev_info!("Node has choices, waiting for user selection");
+    if matches!(
+        intent,
+        AdvanceIntent::NeedsSelection | AdvanceIntent::ConfirmChoice { .. }
+    ) {
+        dev_info!(target: LOG_DIALOGUE, "Node has choices, waiting for user selection");
         return;
     }
+    leave_node(runtime, entity, finished_events);
+}
 
-    let Some(next_node) = next_node_info else {
-        dev_info!("Node ended without next or choices for entity {:?}", entity);
-        remove_entity_dialogue(runtime, entity);
-        finished_events.write(MortarDialogueFinished {
-            entity: entity_to_option(entity),
-            mortar_path,
-            node: current_node,
-        });
+/// Leaves a node that ran out of lines: jumps to its `next` node, or finishes the dialogue when
+/// it has none or returns.
+fn leave_node(
+    runtime: &mut MortarRuntime,
+    entity: Entity,
+    finished_events: &mut MessageWriter<MortarDialogueFinished>,
+) {
+    let Some(state) = runtime.active_dialogues.get(&entity) else {
         return;
     };
-
-    if next_node == "return" {
+    let mortar_path = state.mortar_path.clone();
+    let Some(next_node) = state
+        .get_next_node()
+        .filter(|next| *next != "return")
+        .map(str::to_owned)
+    else {
         dev_info!(
-            "Return instruction, stopping dialogue for entity {:?}",
+            target: LOG_DIALOGUE,
+            "Node ended without next or choices, or returned, for entity {:?}",
             entity
         );
-        remove_entity_dialogue(runtime, entity);
-        finished_events.write(MortarDialogueFinished {
+        let finished = MortarDialogueFinished {
             entity: entity_to_option(entity),
             mortar_path,
-            node: current_node,
-        });
-    } else {
-        dev_info!("Auto-jumping to next node: {}", next_node);
-        runtime
-            .pending_jumps
-            .insert(entity, (mortar_path, next_node));
+            node: state.current_node.clone(),
+            cursor: state.cursor(),
+        };
+        remove_entity_dialogue(runtime, entity);
+        finished_events.write(finished);
+        return;
+    };
+
+    dev_info!(target: LOG_DIALOGUE, "Auto-jumping to next node: {}", next_node);
+    runtime
+        .pending_jumps
+        .insert(entity, (mortar_path, next_node));
+}
+
+/// Lets user input through unless its dialogue is halted or another dialogue holds focus. Input
+/// that goes through starts the count of automatic steps over.
+fn accept_user_input(runtime: &mut MortarRuntime, target: Option<Entity>, input: &str) -> bool {
+    let Some(entity) = target.or(runtime.primary_dialogue) else {
+        return true;
+    };
+    if runtime.lacks_focus(entity) {
+        debug!(
+            target: LOG_DIALOGUE,
+            "Ignoring {} for entity {:?}; another dialogue holds focus", input, entity
+        );
+        return false;
+    }
+    if runtime.halt_reason(entity).is_some() {
+        warn!(
+            target: LOG_DIALOGUE,
+            "Ignoring {} for entity {:?}; it is halted until ResumeAfterError", input, entity
+        );
+        return false;
+    }
+    runtime.reset_auto_advance(entity);
+    true
+}
+
+/// Advances past a line that shows nothing, unless the dialogue ran out of automatic steps.
+fn handle_skip_line(
+    target: Option<Entity>,
+    runtime: &mut MortarRuntime,
+    finished_events: &mut MessageWriter<MortarDialogueFinished>,
+    errors: &mut MessageWriter<MortarErrorEvent>,
+) {
+    let Some(entity) = target
+        .or(runtime.primary_dialogue)
+        .filter(|entity| !runtime.held_by_focus(*entity))
+    else {
+        return;
+    };
+    match runtime.record_auto_skip(entity) {
+        (true, _) => handle_next_text(Some(entity), runtime, finished_events),
+        (false, Some(error)) => {
+            errors.write(error);
+        }
+        (false, None) => {}
+    }
+}
+
+/// Lifts the halt of a dialogue and takes the step it refused.
+fn handle_resume_after_error(
+    target: Option<Entity>,
+    runtime: &mut MortarRuntime,
+    finished_events: &mut MessageWriter<MortarDialogueFinished>,
+) {
+    let Some(entity) = target
+        .or(runtime.primary_dialogue)
+        .filter(|entity| !runtime.held_by_focus(*entity))
+    else {
+        return;
+    };
+    if runtime.halt_reason(entity).is_none() {
+        return;
     }
+    dev_info!(target: LOG_DIALOGUE, "Resuming halted dialogue for entity {:?}", entity);
+    runtime.reset_auto_advance(entity);
+    handle_next_text(Some(entity), runtime, finished_events);
+}
+
+/// Whether an input stamped with `group` was meant for a choice group that is no longer current.
+fn stale_group(state: &DialogueState, group: Option<u64>, input: &str) -> bool {
+    let Some(group) = group.filter(|group| *group != state.group_token()) else {
+        return false;
+    };
+    warn!(
+        target: LOG_DIALOGUE,
+        "Dropping {} for choice group {}; the current group is {}",
+        input,
+        group,
+        state.group_token()
+    );
+    true
+}
+
+/// Drops choice input for a group that cannot take it: not reached yet, dismissed by a `break`,
+/// or left by a confirmed jump still waiting to apply. A stale selection is cleared, so buffered
+/// input cannot resurrect the group; one kept for a group not reached yet stays.
+fn unpresentable_group(runtime: &mut MortarRuntime, entity: Entity, input: &str) -> bool {
+    let leaving = runtime.pending_jumps.contains_key(&entity);
+    let keeps = runtime
+        .selection_persistence
+        .keeps(SelectionTransition::Preselect);
+    let Some(state) = runtime.active_dialogues.get_mut(&entity) else {
+        return false;
+    };
+    if !leaving && state.choices_presentable() {
+        return false;
+    }
+    if leaving || !keeps || !state.has_next_text_before_choice() {
+        state.selected_choice = None;
+    }
+    warn!(
+        target: LOG_DIALOGUE,
+        "Dropping {} for entity {:?}; its choice group is not presented", input, entity
+    );
+    true
 }
 
-fn handle_select_choice(index: usize, target: Option<Entity>, runtime: &mut MortarRuntime) {
+fn handle_select_choice(
+    index: usize,
+    target: Option<Entity>,
+    group: Option<u64>,
+    source: Option<ChoiceInputSource>,
+    runtime: &mut MortarRuntime,
+    scope: ConfirmScope,
+    writers: &mut ConfirmWriters,
+) {
     let Some(entity) = target.or(runtime.primary_dialogue) else {
-        warn!("No active dialogue to select choice from");
+        warn!(target: LOG_DIALOGUE, "No active dialogue to select choice from");
+        return;
+    };
+    let Some(state) = runtime.active_dialogues.get_mut(&entity) else {
+        warn!(target: LOG_DIALOGUE, "No active dialogue for entity {:?}", entity);
         return;
     };
+    if stale_group(state, group, "SelectChoice") {
+        return;
+    }
+    if runtime.keep_preselection(entity, index) {
+        dev_info!(target: LOG_DIALOGUE, "Choice {} kept until its group presents", index);
+        writers.selected.write(MortarChoiceSelected {
+            entity: entity_to_option(entity),
+            index,
+        });
+        return;
+    }
+    if unpresentable_group(runtime, entity, "SelectChoice") {
+        return;
+    }
     let Some(state) = runtime.active_dialogues.get_mut(&entity) else {
-        warn!("No active dialogue for entity {:?}", entity);
         return;
     };
     let Some(choices) = state.get_choices() else {
-        warn!("No choices available in current node");
+        warn!(target: LOG_DIALOGUE, "No choices available in current node");
         return;
     };
     if index >= choices.len() {
-        warn!("Invalid choice index: {}", index);
+        warn!(target: LOG_DIALOGUE, "Invalid choice index: {}", index);
+        return;
+    }
+    if state.disabled_choices.contains(&index) {
+        warn!(target: LOG_DIALOGUE, "Choice {} is disabled and cannot be selected", index);
         return;
     }
 
     dev_info!(
+        target: LOG_DIALOGUE,
         "Choice marked as selected: {} - {}",
         index,
         choices[index].text
     );
     state.selected_choice = Some(index);
+    if let Some(page_size) = state.choice_page_size(runtime.choice_pagination) {
+        state.show_choice_page_of(index, page_size);
+    }
+    writers.selected.write(MortarChoiceSelected {
+        entity: entity_to_option(entity),
+        index,
+    });
+    if runtime.selection_confirms(source) {
+        handle_confirm_choice(Some(entity), group, runtime, scope, writers);
+    }
 }
 
-fn handle_choice_action(
-    action: &str,
-    entity: Entity,
-    runtime: &mut MortarRuntime,
-    finished_events: &mut MessageWriter<MortarDialogueFinished>,
-    mortar_path: &str,
-    current_node: &str,
-) {
-    match action {
-        "return" => {
-            dev_info!("Choice action is return, stopping dialogue");
-            remove_entity_dialogue(runtime, entity);
-            finished_events.write(MortarDialogueFinished {
-                entity: entity_to_option(entity),
-                mortar_path: mortar_path.to_owned(),
-                node: current_node.to_owned(),
-            });
-        }
-        "break" => {
-            dev_info!("Choice action is break, continuing to next text");
-            let Some(state) = runtime.active_dialogues.get_mut(&entity) else {
-                return;
-            };
-            state.clear_choice_stack();
-            state.choices_broken = true;
-            state.next_text();
-        }
-        _ => {
-            dev_info!("Unknown choice action: {}", action);
-            remove_entity_dialogue(runtime, entity);
-            finished_events.write(MortarDialogueFinished {
-                entity: entity_to_option(entity),
-                mortar_path: mortar_path.to_owned(),
-                node: current_node.to_owned(),
-            });
-        }
+fn handle_choice_page(delta: i32, target: Option<Entity>, runtime: &mut MortarRuntime) {
+    let default = runtime.choice_pagination;
+    let Some(state) = target
+        .or(runtime.primary_dialogue)
+        .and_then(|entity| runtime.active_dialogues.get_mut(&entity))
+    else {
+        warn!(target: LOG_DIALOGUE, "No active dialogue to turn the choice page of");
+        return;
+    };
+    let Some(page_size) = state.choice_page_size(default) else {
+        debug!(target: LOG_DIALOGUE, "Choices are not paginated, ignoring page turn");
+        return;
+    };
+    if state.turn_choice_page(delta, page_size) {
+        dev_info!(target: LOG_DIALOGUE, "Choice page turned to {}", state.choice_page);
     }
 }
 
 fn handle_confirm_choice(
     target: Option<Entity>,
+    group: Option<u64>,
     runtime: &mut MortarRuntime,
-    finished_events: &mut MessageWriter<MortarDialogueFinished>,
+    scope: ConfirmScope,
+    writers: &mut ConfirmWriters,
 ) {
     let Some(entity) = target.or(runtime.primary_dialogue) else {
-        warn!("No active dialogue to confirm choice from");
+        warn!(target: LOG_DIALOGUE, "No active dialogue to confirm choice from");
         return;
     };
 
-    let (choice_index, choices_clone, mortar_path, current_node) = {
+    if unpresentable_group(runtime, entity, "ConfirmChoice") {
+        return;
+    }
+    let (outcome, mortar_path, current_node, cursor, removal) = {
         let Some(state) = runtime.active_dialogues.get(&entity) else {
-            warn!("No active dialogue for entity {:?}", entity);
+            warn!(target: LOG_DIALOGUE, "No active dialogue for entity {:?}", entity);
             return;
         };
+        if stale_group(state, group, "ConfirmChoice") {
+            return;
+        }
         let Some(choice_index) = state.selected_choice else {
-            warn!("No choice selected to confirm");
+            warn!(target: LOG_DIALOGUE, "No choice selected to confirm");
             return;
         };
-        let Some(choices) = state.get_choices() else {
-            warn!("No choices available in current node");
+        if state.disabled_choices.contains(&choice_index) {
+            warn!(target: LOG_DIALOGUE, "Choice {} is disabled, confirm rejected", choice_index);
+            return;
+        }
+        // The same resolution `simulate_confirm` previews, so the preview cannot diverge.
+        //
+        // 与 `simulate_confirm` 预览时相同的结算，因此预览不会与实际结果不一致。
+        let asset = scope
+            .registry
+            .get(&state.mortar_path)
+            .and_then(|handle| scope.assets.get(handle));
+        let capture = &runtime.choice_capture;
+        let Some(outcome) = resolve_confirm(state, choice_index, capture, asset, scope.variables)
+        else {
+            warn!(target: LOG_DIALOGUE, "Invalid choice index: {}", choice_index);
             return;
         };
         (
-            choice_index,
-            choices.clone(),
+            outcome,
             state.mortar_path.clone(),
             state.current_node.clone(),
+            state.cursor(),
+            state.choice_removal(choice_index),
         )
     };
 
-    let Some(choice) = choices_clone.get(choice_index) else {
-        warn!("Invalid choice index: {}", choice_index);
-        return;
-    };
-
-    dev_info!("Choice confirmed: {} - {}", choice_index, choice.text);
-
-    if let Some(action) = &choice.action {
-        handle_choice_action(
-            action,
-            entity,
-            runtime,
-            finished_events,
-            &mortar_path,
-            &current_node,
-        );
-        return;
+    dev_info!(target: LOG_DIALOGUE, "Choice confirmed: {} - {}", outcome.index, outcome.text);
+    if let Some((variable, value)) = outcome.capture.clone() {
+        writers.captured.write(MortarChoiceCaptured {
+            entity: entity_to_option(entity),
+            variable,
+            value,
+            index: outcome.index,
+        });
     }
-
-    if choice.choice.is_some() {
-        dev_info!("Choice has nested choices, entering nested level");
-        if let Some(state) = runtime.active_dialogues.get_mut(&entity) {
-            state.push_choice(choice_index);
+    match removal {
+        Some((RemovalScope::Visit, removed)) => {
+            if let Some(state) = runtime.active_dialogues.get_mut(&entity) {
+                state.remove_choice(removed);
+            }
         }
-        return;
+        Some((RemovalScope::Session, removed)) => {
+            runtime.removed_choices.insert(removed);
+        }
+        None => {}
     }
 
-    let Some(next_node) = &choice.next else {
-        dev_info!("Choice has no next node or action, stopping dialogue");
-        remove_entity_dialogue(runtime, entity);
-        finished_events.write(MortarDialogueFinished {
-            entity: entity_to_option(entity),
-            mortar_path,
-            node: current_node,
-        });
-        return;
-    };
-
-    if next_node == "return" {
-        dev_info!("Choice leads to return, stopping dialogue");
-        remove_entity_dialogue(runtime, entity);
-        finished_events.write(MortarDialogueFinished {
-            entity: entity_to_option(entity),
-            mortar_path,
-            node: current_node,
-        });
-    } else {
-        dev_info!("Choice leads to node: {}", next_node);
-        runtime
-            .pending_jumps
-            .insert(entity, (mortar_path, next_node.clone()));
+    match &outcome.effect {
+        ConfirmEffect::EndDialogue => {
+            dev_info!(target: LOG_DIALOGUE, "Choice ends the dialogue");
+            remove_entity_dialogue(runtime, entity);
+            writers.finished.write(MortarDialogueFinished {
+                entity: entity_to_option(entity),
+                mortar_path: mortar_path.clone(),
+                node: current_node.clone(),
+                cursor,
+            });
+        }
+        ConfirmEffect::Break => {
+            dev_info!(target: LOG_DIALOGUE, "Choice action is break, continuing to next text");
+            // Breaking gives the group a new token and marks it broken, so selections and
+            // confirms still queued for it are dropped. With no line after the group the node is
+            // left at once, as advancing past its last line would.
+            //
+            // 关闭选项组会为其分配新令牌并标记为已关闭，因此仍排队针对它的选择与确认都会被丢弃。
+            // 选项组之后没有行时立即离开节点，与推进越过最后一行时相同。
+            let continues = runtime.active_dialogues.get_mut(&entity).map(|state| {
+                state.break_choices();
+                state.next_text()
+            });
+            if continues == Some(false) {
+                leave_node(runtime, entity, &mut writers.finished);
+            }
+        }
+        ConfirmEffect::EnterNested => {
+            dev_info!(target: LOG_DIALOGUE, "Choice has nested choices, entering nested level");
+            if let Some(state) = runtime.active_dialogues.get_mut(&entity) {
+                state.push_choice(outcome.index);
+            }
+        }
+        ConfirmEffect::Jump { node, entry } => {
+            dev_info!(target: LOG_DIALOGUE, "Choice leads to node: {}", node);
+            runtime
+                .pending_jumps
+                .insert(entity, (mortar_path.clone(), node.clone()));
+            if let Some(entry) = entry {
+                runtime.pending_entries.insert(entity, *entry);
+            }
+        }
+        // Only previews resolve to a rejection; the checks above turn the confirm down first.
+        //
+        // 只有预览会结算为拒绝；上面的检查会先拒绝该确认。
+        ConfirmEffect::Rejected => {}
     }
+    writers.resolved.write(MortarChoiceResolved {
+        entity: entity_to_option(entity),
+        mortar_path,
+        node: current_node,
+        outcome,
+    });
 }
 
 fn handle_stop_dialogue(target: Option<Entity>, runtime: &mut MortarRuntime) {
@@ -294,17 +478,32 @@ fn handle_stop_dialogue(target: Option<Entity>, runtime: &mut MortarRuntime) {
         runtime.active_dialogues.clear();
         runtime.pending_starts.clear();
         runtime.pending_jumps.clear();
+        runtime.pending_entries.clear();
+        runtime.pending_loads.clear();
+        runtime.signals.clear();
+        runtime.auto_advance.clear();
+        runtime.end_conversation_rng(None);
         runtime.primary_dialogue = None;
-        dev_info!("All dialogues stopped");
+        runtime.focus_queue.clear();
+        runtime.paused_when_unfocused.clear();
+        if runtime.trim_policy.shrink_on_stop {
+            runtime.shrink_to_fit();
+        }
+        dev_info!(target: LOG_DIALOGUE, "All dialogues stopped");
         return;
     };
     runtime.active_dialogues.remove(&entity);
+    runtime.end_conversation_rng(Some(entity));
     runtime.pending_starts.remove(&entity);
     runtime.pending_jumps.remove(&entity);
-    if runtime.primary_dialogue == Some(entity) {
-        runtime.primary_dialogue = None;
+    runtime.pending_entries.remove(&entity);
+    runtime.pending_loads.remove(&entity);
+    runtime.reset_auto_advance(entity);
+    runtime.drop_focus(entity);
+    if runtime.trim_policy.shrink_on_stop {
+        runtime.shrink_to_fit();
     }
-    dev_info!("Dialogue stopped for entity {:?}", entity);
+    dev_info!(target: LOG_DIALOGUE, "Dialogue stopped for entity {:?}", entity);
 }
 
 /// Processes Mortar events.
@@ -312,103 +511,120 @@ fn handle_stop_dialogue(target: Option<Entity>, runtime: &mut MortarRuntime) {
 ///
 /// 处理 Mortar 事件。
 /// 现在支持多控制器架构和可选的目标实体。
-pub fn process_mortar_events_system(
-    mut events: MessageReader<MortarEvent>,
+pub(crate) fn process_mortar_events_system(
+    mut events: MessageReader<MortarCommand>,
     mut runtime: ResMut<MortarRuntime>,
     mut registry: ResMut<MortarRegistry>,
     assets: Res<Assets<MortarAsset>>,
     asset_server: Res<AssetServer>,
-    mut finished_events: MessageWriter<MortarDialogueFinished>,
+    mut variables: Option<ResMut<MortarDialogueVariables>>,
+    mut confirm_writers: ConfirmWriters,
+    mut activation_writers: ActivationWriters,
+    mut availability: AvailabilityGate,
+    mut prepared_events: MessageWriter<MortarNodePrepared>,
+    mut errors: MessageWriter<MortarErrorEvent>,
+    time: Res<Time>,
 ) {
     for event in events.read() {
+        // Resolving a confirm only writes the capture inside a transaction it rolls back, so the
+        // variables are left unchanged.
+        //
+        // 结算确认只会在随后回滚的事务中写入捕获，因此变量保持不变。
+        let scope = ConfirmScope {
+            registry: &registry,
+            assets: &assets,
+            variables: variables
+                .as_mut()
+                .and_then(|variables| variables.bypass_change_detection().state.as_mut()),
+        };
         match event {
-            MortarEvent::StartNode { path, node, target } => handle_start_node(
+            MortarCommand::StartNode {
                 path,
                 node,
+                target,
+                entry,
+            } => {
+                handle_start_node(
+                    path,
+                    node,
+                    *target,
+                    *entry,
+                    &mut runtime,
+                    &mut registry,
+                    &assets,
+                    &asset_server,
+                    &mut activation_writers,
+                    &mut availability,
+                );
+            }
+            MortarCommand::PrepareNode { path, node } => {
+                let prepared = handle_prepare_node(
+                    path,
+                    node,
+                    time.elapsed(),
+                    &mut runtime,
+                    &mut registry,
+                    &assets,
+                    &asset_server,
+                );
+                if let Some(prepared) = prepared {
+                    prepared_events.write(prepared);
+                }
+            }
+            MortarCommand::NextText { target } => {
+                if accept_user_input(&mut runtime, *target, "NextText") {
+                    handle_next_text(*target, &mut runtime, &mut confirm_writers.finished)
+                }
+            }
+            MortarCommand::SkipLine { target } => handle_skip_line(
                 *target,
                 &mut runtime,
-                &mut registry,
-                &assets,
-                &asset_server,
+                &mut confirm_writers.finished,
+                &mut errors,
             ),
-            MortarEvent::NextText { target } => {
-                handle_next_text(*target, &mut runtime, &mut finished_events)
+            MortarCommand::ResumeAfterError { target } => {
+                handle_resume_after_error(*target, &mut runtime, &mut confirm_writers.finished)
+            }
+            MortarCommand::SelectChoice {
+                index,
+                target,
+                group,
+                source,
+            } => {
+                if accept_user_input(&mut runtime, *target, "SelectChoice") {
+                    handle_select_choice(
+                        *index,
+                        *target,
+                        *group,
+                        *source,
+                        &mut runtime,
+                        scope,
+                        &mut confirm_writers,
+                    );
+                }
             }
-            MortarEvent::SelectChoice { index, target } => {
-                handle_select_choice(*index, *target, &mut runtime)
+            MortarCommand::ConfirmChoice { target, group } => {
+                if accept_user_input(&mut runtime, *target, "ConfirmChoice") {
+                    handle_confirm_choice(
+                        *target,
+                        *group,
+                        &mut runtime,
+                        scope,
+                        &mut confirm_writers,
+                    )
+                }
             }
-            MortarEvent::ConfirmChoice { target } => {
-                handle_confirm_choice(*target, &mut runtime, &mut finished_events)
+            MortarCommand::ChoicePage { delta, target } => {
+                handle_choice_page(*delta, *target, &mut runtime)
             }
-            MortarEvent::StopDialogue { target } => handle_stop_dialogue(*target, &mut runtime),
+            MortarCommand::StopDialogue { target } => handle_stop_dialogue(*target, &mut runtime),
+            MortarCommand::JumpToNode { node, target } => {
+                handle_jump_to_node(node, *target, &mut runtime, &registry, &assets)
+            }
+            MortarCommand::Signal { name } => runtime.signal(name.as_str()),
+            MortarCommand::ContinueReveal => runtime.advance_gate.request_continue(),
+            // Seeking acts on text targets and is handled by the dialogue plugin.
+            MortarCommand::SeekLine { .. } => {}
         }
     }
 }
-
-/// Checks for and starts pending nodes.
-///
-/// 检查并启动等待中的节点。
-pub fn check_pending_start_system(
-    mut runtime: ResMut<MortarRuntime>,
-    registry: Res<MortarRegistry>,
-    assets: Res<Assets<MortarAsset>>,
-) {
-    // Collect entities to process (avoid borrowing issues)
-    let pending: Vec<(Entity, String, String)> = runtime
-        .pending_starts
-        .iter()
-        .map(|(e, (p, n))| (*e, p.clone(), n.clone()))
-        .collect();
-
-    for (entity, path, node) in pending {
-        let Some(handle) = registry.get(&path) else {
-            continue;
-        };
-        let Some(asset) = assets.get(handle) else {
-            continue;
-        };
-        let Some(node_data) = asset.data.nodes.iter().find(|n| n.name == node) else {
-            continue;
-        };
-
-        let state = DialogueState::new(path.clone(), node.clone(), node_data.clone());
-        runtime.active_dialogues.insert(entity, state);
-        runtime.primary_dialogue = Some(entity);
-        runtime.pending_starts.remove(&entity);
-        dev_info!(
-            "Started pending node: {} in {} for entity {:?}",
-            node,
-            path,
-            entity
-        );
-    }
-}
-
-/// Handles pending jumps to other nodes.
-///
-/// 处理等待中的节点跳转。
-pub fn handle_pending_jump_system(
-    mut runtime: ResMut<MortarRuntime>,
-    mut event_writer: MessageWriter<MortarEvent>,
-) {
-    // Collect pending jumps to process
-    let jumps: Vec<(Entity, String, String)> = runtime
-        .pending_jumps
-        .drain()
-        .map(|(e, (p, n))| (e, p, n))
-        .collect();
-
-    for (entity, path, node) in jumps {
-        dev_info!(
-            "Processing pending jump to: {} in {} for entity {:?}",
-            node,
-            path,
-            entity
-        );
-        event_writer.write(MortarEvent::StartNode {
-            path,
-            node,
-            target: Some(entity),
-        });
-    }
-}
@@ -0,0 +1,96 @@
+//! # hot_reload.rs
+//!
+//! # hot_reload.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Brings active dialogues up to date when their file hot reloads. A dialogue whose node changed
+//! is rebuilt from the new node data on the same node, keeping its line when the node still has it
+//! and otherwise moving to the node's last line; `run` statements before that line are treated as
+//! already executed. A dialogue whose node was removed from the file is stopped. Enabled by
+//! `MortarPlugin::hot_reload`.
+//!
+//! 在对话所属文件热重载时更新活跃对话。节点发生变化的对话会基于新的节点数据在同一节点上重建：
+//! 节点仍有当前行时保持在该行，否则移到节点的最后一行；该行之前的 `run` 语句视为已执行。节点已从
+//! 文件中移除的对话会被停止。由 `MortarPlugin::hot_reload` 启用。
+
+use bevy::asset::{AssetEvent, Assets};
+use bevy::log::warn;
+use bevy::prelude::{Entity, MessageReader, Res, ResMut};
+
+use super::handle_stop_dialogue;
+use crate::asset::find_node;
+use crate::debug::LOG_DIALOGUE;
+use crate::preparation::reloaded_paths;
+use crate::{MortarAsset, MortarNodeEntry, MortarRegistry, MortarRuntime};
+
+/// Rebuilds or stops the active dialogues of every reloaded file.
+pub(crate) fn resync_reloaded_dialogues(
+    mut runtime: ResMut<MortarRuntime>,
+    registry: Res<MortarRegistry>,
+    assets: Res<Assets<MortarAsset>>,
+    mut asset_events: MessageReader<AssetEvent<MortarAsset>>,
+) {
+    for event in asset_events.read() {
+        let AssetEvent::Modified { id } = event else {
+            continue;
+        };
+        let Some(asset) = assets.get(*id) else {
+            continue;
+        };
+        for path in reloaded_paths(&registry, *id) {
+            let dialogues: Vec<Entity> = runtime
+                .active_dialogues
+                .iter()
+                .filter(|(_, state)| state.mortar_path == path)
+                .map(|(entity, _)| *entity)
+                .collect();
+            for entity in dialogues {
+                resync_dialogue(&mut runtime, entity, &path, asset);
+            }
+        }
+    }
+}
+
+fn resync_dialogue(runtime: &mut MortarRuntime, entity: Entity, path: &str, asset: &MortarAsset) {
+    let Some(state) = runtime.active_dialogues.get(&entity) else {
+        return;
+    };
+    let node = state.current_node.clone();
+    let Some(node_data) = find_node(&asset.data, &node) else {
+        warn!(
+            target: LOG_DIALOGUE,
+            "Node '{}' is gone from reloaded '{}'; stopping the dialogue of entity {:?}",
+            node,
+            path,
+            entity
+        );
+        handle_stop_dialogue(Some(entity), runtime);
+        return;
+    };
+    let mut rebuilt = runtime.parse_node(path, &node, node_data, asset);
+    let state = &runtime.active_dialogues[&entity];
+    if rebuilt.node_data().content == state.node_data().content
+        && rebuilt.node_data().next == state.node_data().next
+    {
+        return;
+    }
+    let index = state
+        .text_index
+        .min(rebuilt.text_items().len().saturating_sub(1));
+    if index != state.text_index {
+        warn!(
+            target: LOG_DIALOGUE,
+            "Line {} of node '{}' is gone from reloaded '{}'; moving to line {}",
+            state.text_index,
+            node,
+            path,
+            index
+        );
+    }
+    rebuilt.enter_at(MortarNodeEntry::at(index));
+    dev_info!(target: LOG_DIALOGUE, "Reloaded node '{}' in '{}' for entity {:?}", node, path, entity);
+    runtime.active_dialogues.insert(entity, rebuilt);
+}
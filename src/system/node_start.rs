@@ -0,0 +1,482 @@
+//! # node_start.rs
+//!
+//! # node_start.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Turns start and jump requests into active dialogue states. It resolves the target asset,
+//! reuses a prepared node when one is available, positions the cursor at the requested entry line,
+//! and reports lifecycle messages for the activated node. A loaded file without the requested
+//! node fails the request with [`MortarStartFailed`]; when several nodes share the name, the first
+//! declaration is used and a warning is logged. Requests waiting for their file keep a load status
+//! up to date, and fail the same way when the file cannot be loaded. Under
+//! [`crate::MortarStrictStart`], a start also consults the file's load-time analysis. A start on
+//! an idle controller must also pass the [`MortarAvailability`] rules of its node.
+//!
+//! 把开始与跳转请求转换为活跃的对话状态。它会解析目标资源、在有已准备节点时直接复用、
+//! 将游标放到请求的入口行，并为被激活的节点发出生命周期消息。已加载的文件中没有所请求的节点时，
+//! 请求以 [`MortarStartFailed`] 失败；若多个节点同名，则使用第一个声明并记录警告。等待文件的请求
+//! 会持续更新其加载状态，文件无法加载时也以同样方式失败。在 [`crate::MortarStrictStart`] 下，开始请求
+//! 还会参考文件的加载时分析结果。空闲控制器上的开始请求还必须通过其节点的 [`MortarAvailability`] 规则。
+
+use crate::asset::{find_node, node_declarations};
+use crate::debug::LOG_DIALOGUE;
+use crate::validation::StartGate;
+use crate::{
+    DialogueState, MortarAsset, MortarAvailability, MortarCommand, MortarDialogueStarted,
+    MortarErrorEvent, MortarNodeEntered, MortarNodeEntry, MortarRegistry, MortarRuntime,
+    MortarStartFailed, MortarStartFailure, MortarStartSuppressed, SuppressedStartPolicy,
+};
+use bevy::asset::{AssetServer, Assets};
+use bevy::ecs::system::SystemParam;
+use bevy::log::{debug, warn};
+use bevy::prelude::{Entity, MessageWriter, Res, ResMut, Time};
+use mortar_compiler::Node;
+
+use super::entity_to_option;
+
+/// Lifecycle messages produced when a node becomes active.
+pub(crate) struct Activation {
+    started: Option<MortarDialogueStarted>,
+    entered: MortarNodeEntered,
+}
+
+/// Writers for the lifecycle messages of node activation.
+#[derive(SystemParam)]
+pub(crate) struct ActivationWriters<'w> {
+    started: MessageWriter<'w, MortarDialogueStarted>,
+    entered: MessageWriter<'w, MortarNodeEntered>,
+    failed: MessageWriter<'w, MortarStartFailed>,
+    suppressed: MessageWriter<'w, MortarStartSuppressed>,
+}
+
+impl Activation {
+    pub(crate) fn into_messages(self) -> (Option<MortarDialogueStarted>, MortarNodeEntered) {
+        (self.started, self.entered)
+    }
+}
+
+impl ActivationWriters<'_> {
+    pub(super) fn write(&mut self, activation: Activation) {
+        if let Some(started) = activation.started {
+            self.started.write(started);
+        }
+        self.entered.write(activation.entered);
+    }
+}
+
+/// The availability rules of starts, with the clock their cooldowns run on.
+#[derive(SystemParam)]
+pub(crate) struct AvailabilityGate<'w> {
+    availability: ResMut<'w, MortarAvailability>,
+    time: Res<'w, Time>,
+}
+
+impl AvailabilityGate<'_> {
+    /// Whether a start of `node` on `entity` may activate. A controller that is idle must pass
+    /// the rules; a blocked start is kept pending or dropped as the policy says, and reported
+    /// unless a retry was already reported.
+    fn admits(
+        &mut self,
+        runtime: &mut MortarRuntime,
+        registry: &MortarRegistry,
+        entity: Entity,
+        path: &str,
+        node: &str,
+        fresh: bool,
+        suppressed: &mut MessageWriter<MortarStartSuppressed>,
+    ) -> bool {
+        if runtime.active_dialogues.contains_key(&entity) {
+            return true;
+        }
+        let now = self.time.elapsed();
+        let Some(reason) = self.availability.check(registry, path, node, now) else {
+            return true;
+        };
+        runtime.pending_loads.remove(&entity);
+        if self.availability.policy == SuppressedStartPolicy::Retry {
+            runtime
+                .pending_starts
+                .insert(entity, (path.to_owned(), node.to_owned()));
+        } else {
+            runtime.pending_starts.remove(&entity);
+            runtime.pending_entries.remove(&entity);
+        }
+        if self.availability.should_report(entity, fresh) {
+            debug!(target: LOG_DIALOGUE, "Suppressed start of '{}' in '{}': {:?}", node, path, reason);
+            suppressed.write(MortarStartSuppressed {
+                entity: entity_to_option(entity),
+                mortar_path: path.to_owned(),
+                node: node.to_owned(),
+                reason,
+            });
+        }
+        false
+    }
+
+    /// Counts the start of a conversation that `activation` began.
+    fn note(&mut self, entity: Entity, activation: &Activation) {
+        if let Some(started) = &activation.started {
+            self.availability
+                .note_started(entity, &started.mortar_path, &started.node);
+        }
+    }
+}
+
+/// Drops the pending request of `entity` and reports why `node` cannot start.
+fn refuse_start(
+    runtime: &mut MortarRuntime,
+    entity: Entity,
+    path: &str,
+    node: &str,
+    reason: MortarStartFailure,
+) -> MortarStartFailed {
+    warn!(target: LOG_DIALOGUE, "Cannot start '{}' in '{}': {:?}", node, path, reason);
+    runtime.pending_starts.remove(&entity);
+    runtime.pending_entries.remove(&entity);
+    runtime.pending_loads.remove(&entity);
+    MortarStartFailed {
+        entity: entity_to_option(entity),
+        mortar_path: path.to_owned(),
+        node: node.to_owned(),
+        reason,
+    }
+}
+
+/// Finds `node` in a loaded file, dropping the pending request and reporting a failure when the
+/// file cannot serve it. `Ok(None)` means the start waits for the file's analysis.
+fn resolve_node<'a>(
+    runtime: &mut MortarRuntime,
+    entity: Entity,
+    path: &str,
+    node: &str,
+    asset: &'a MortarAsset,
+) -> Result<Option<&'a Node>, MortarStartFailed> {
+    match runtime.strict_start.gate(asset) {
+        StartGate::Open => {}
+        StartGate::Wait => {
+            runtime.wait_for_analysis(entity, path, node);
+            return Ok(None);
+        }
+        StartGate::Refuse => {
+            let reason = MortarStartFailure::InvalidAsset;
+            return Err(refuse_start(runtime, entity, path, node, reason));
+        }
+    }
+    let Some(node_data) = find_node(&asset.data, node) else {
+        let reason = if asset.data.nodes.is_empty() {
+            MortarStartFailure::NoNodes
+        } else {
+            MortarStartFailure::NodeNotFound
+        };
+        return Err(refuse_start(runtime, entity, path, node, reason));
+    };
+    let declarations = node_declarations(&asset.data, node).len();
+    if declarations > 1 {
+        warn!(
+            target: LOG_DIALOGUE,
+            "Node '{}' is declared {} times in '{}'; using the first declaration",
+            node,
+            declarations,
+            path
+        );
+    }
+    Ok(Some(node_data))
+}
+
+/// Installs a dialogue state at its entry line. The started message is only produced when the
+/// controller was idle.
+pub(crate) fn activate_dialogue(
+    runtime: &mut MortarRuntime,
+    entity: Entity,
+    mut state: DialogueState,
+    entry: Option<MortarNodeEntry>,
+    asset: &MortarAsset,
+) -> Activation {
+    state.begin_visit();
+    if let Some(entry) = entry {
+        state.enter_at(entry);
+    }
+    let started =
+        (!runtime.active_dialogues.contains_key(&entity)).then(|| MortarDialogueStarted {
+            entity: entity_to_option(entity),
+            mortar_path: state.mortar_path.clone(),
+            node: state.current_node.clone(),
+            metadata: asset.metadata.clone(),
+        });
+    let entered = MortarNodeEntered {
+        entity: entity_to_option(entity),
+        mortar_path: state.mortar_path.clone(),
+        node: state.current_node.clone(),
+        entry_index: state.entry_index,
+        cursor: state.cursor(),
+        tags: state.tags().to_vec(),
+    };
+    if let Some(started) = &started {
+        runtime.begin_conversation_rng(entity, &started.mortar_path, &started.node);
+    }
+    runtime.active_dialogues.insert(entity, state);
+    runtime.focus_on_activation(entity, started.is_some());
+    runtime.pending_starts.remove(&entity);
+    runtime.pending_entries.remove(&entity);
+    runtime.pending_loads.remove(&entity);
+    Activation { started, entered }
+}
+
+pub(super) fn handle_start_node(
+    path: &str,
+    node: &str,
+    target: Option<Entity>,
+    entry: Option<MortarNodeEntry>,
+    runtime: &mut MortarRuntime,
+    registry: &mut MortarRegistry,
+    assets: &Assets<MortarAsset>,
+    asset_server: &AssetServer,
+    writers: &mut ActivationWriters,
+    gate: &mut AvailabilityGate,
+) {
+    let handle = if let Some(h) = registry.get(path) {
+        h.clone()
+    } else {
+        debug!(target: LOG_DIALOGUE, "Auto-loading mortar file: {}", path);
+        let handle = asset_server.load::<MortarAsset>(path.to_owned());
+        registry.register(path.to_owned(), handle.clone());
+        handle
+    };
+    // Dialogue state and pending requests always use the canonical path.
+    let path = registry.canonical_key(path);
+    let path = path.as_str();
+
+    let entity = target.unwrap_or(Entity::PLACEHOLDER);
+    runtime.pending_loads.remove(&entity);
+    runtime.note_start_request(entity);
+    let Some(asset) = assets.get(&handle) else {
+        dev_info!(target: LOG_DIALOGUE, "Asset '{}' not loaded yet, waiting...", path);
+        runtime
+            .pending_starts
+            .insert(entity, (path.to_owned(), node.to_owned()));
+        runtime.begin_pending_load(entity, path, node);
+        if let Some(entry) = entry {
+            runtime.pending_entries.insert(entity, entry);
+        }
+        return;
+    };
+    let node_data = match resolve_node(runtime, entity, path, node, asset) {
+        Ok(Some(node_data)) => node_data,
+        Ok(None) => {
+            if let Some(entry) = entry {
+                runtime.pending_entries.insert(entity, entry);
+            }
+            return;
+        }
+        Err(failed) => {
+            writers.failed.write(failed);
+            return;
+        }
+    };
+    if let Some(entry) = entry {
+        runtime.pending_entries.insert(entity, entry);
+    }
+    if !gate.admits(
+        runtime,
+        registry,
+        entity,
+        path,
+        node,
+        true,
+        &mut writers.suppressed,
+    ) {
+        return;
+    }
+    let state = runtime
+        .take_prepared(path, node)
+        .unwrap_or_else(|| runtime.parse_node(path, node, node_data, asset));
+
+    dev_info!(target: LOG_DIALOGUE, "Started node: {} in {} for entity {:?}", node, path, entity);
+    let activation = activate_dialogue(runtime, entity, state, entry, asset);
+    gate.note(entity, &activation);
+    writers.write(activation);
+}
+
+/// Drops a pending start whose file failed to load, leaving a failed status behind.
+fn fail_pending_start(
+    runtime: &mut MortarRuntime,
+    entity: Entity,
+    path: &str,
+    node: &str,
+) -> MortarStartFailed {
+    warn!(target: LOG_DIALOGUE, "Cannot start '{}' in '{}': the file failed to load", node, path);
+    runtime.pending_starts.remove(&entity);
+    runtime.pending_entries.remove(&entity);
+    runtime.fail_pending_load(entity);
+    MortarStartFailed {
+        entity: entity_to_option(entity),
+        mortar_path: path.to_owned(),
+        node: node.to_owned(),
+        reason: MortarStartFailure::LoadFailed,
+    }
+}
+
+/// Checks for and starts pending nodes, updating the status of those still waiting.
+///
+/// 检查并启动等待中的节点，并更新仍在等待的节点的状态。
+pub(crate) fn check_pending_start_system(
+    mut runtime: ResMut<MortarRuntime>,
+    registry: Res<MortarRegistry>,
+    assets: Res<Assets<MortarAsset>>,
+    asset_server: Res<AssetServer>,
+    time: Res<Time>,
+    mut writers: ActivationWriters,
+    mut gate: AvailabilityGate,
+) {
+    // Collect entities to process (avoid borrowing issues)
+    let pending: Vec<(Entity, String, String)> = runtime
+        .pending_starts
+        .iter()
+        .map(|(e, (p, n))| (*e, p.clone(), n.clone()))
+        .collect();
+
+    for (entity, path, node) in pending {
+        let Some(handle) = registry.get(&path) else {
+            continue;
+        };
+        let Some(asset) = assets.get(handle) else {
+            runtime.tick_pending_load(entity, time.elapsed());
+            if asset_server.load_state(handle).is_failed() {
+                let failed = fail_pending_start(&mut runtime, entity, &path, &node);
+                writers.failed.write(failed);
+            }
+            continue;
+        };
+        let node_data = match resolve_node(&mut runtime, entity, &path, &node, asset) {
+            Ok(Some(node_data)) => node_data,
+            Ok(None) => {
+                runtime.tick_pending_load(entity, time.elapsed());
+                continue;
+            }
+            Err(failed) => {
+                writers.failed.write(failed);
+                continue;
+            }
+        };
+        let fresh = runtime.pending_loads.contains_key(&entity);
+        let suppressed = &mut writers.suppressed;
+        if !gate.admits(
+            &mut runtime,
+            &registry,
+            entity,
+            &path,
+            &node,
+            fresh,
+            suppressed,
+        ) {
+            continue;
+        }
+
+        let state = runtime
+            .take_prepared(&path, &node)
+            .unwrap_or_else(|| runtime.parse_node(&path, &node, node_data, asset));
+        let entry = runtime.pending_entries.get(&entity).copied();
+        let activation = activate_dialogue(&mut runtime, entity, state, entry, asset);
+        gate.note(entity, &activation);
+        writers.write(activation);
+        dev_info!(
+            target: LOG_DIALOGUE,
+            "Started pending node: {} in {} for entity {:?}",
+            node,
+            path,
+            entity
+        );
+    }
+}
+
+/// Queues a jump of the active dialogue of `target` to `node` of the file it is playing, so it
+/// goes through [`handle_pending_jump_system`] like a jump chosen in the script.
+///
+/// 将 `target` 的活动对话跳转到其正在播放文件中的 `node` 加入队列，使其像脚本中选择的跳转一样经过
+/// [`handle_pending_jump_system`]。
+pub(super) fn handle_jump_to_node(
+    node: &str,
+    target: Option<Entity>,
+    runtime: &mut MortarRuntime,
+    registry: &MortarRegistry,
+    assets: &Assets<MortarAsset>,
+) {
+    let Some((entity, state)) = target
+        .or(runtime.primary_dialogue)
+        .and_then(|entity| Some((entity, runtime.active_dialogues.get(&entity)?)))
+    else {
+        warn!(target: LOG_DIALOGUE, "No active dialogue to jump to node '{}' in", node);
+        return;
+    };
+    let path = state.mortar_path.clone();
+    let exists = registry
+        .get(&path)
+        .and_then(|handle| assets.get(handle))
+        .is_some_and(|asset| asset.has_node(node));
+    if !exists {
+        warn!(
+            target: LOG_DIALOGUE,
+            "Cannot jump to node '{}': '{}' has no such node", node, path
+        );
+        return;
+    }
+    dev_info!(
+        target: LOG_DIALOGUE,
+        "Jumping to node '{}' in '{}' for entity {:?}",
+        node,
+        path,
+        entity
+    );
+    runtime.pending_entries.remove(&entity);
+    runtime
+        .pending_jumps
+        .insert(entity, (path, node.to_owned()));
+}
+
+/// Handles pending jumps to other nodes.
+///
+/// 处理等待中的节点跳转。
+pub(crate) fn handle_pending_jump_system(
+    mut runtime: ResMut<MortarRuntime>,
+    mut event_writer: MessageWriter<MortarCommand>,
+    mut errors: MessageWriter<MortarErrorEvent>,
+) {
+    // Collect pending jumps to process; unfocused paused dialogues keep theirs
+    let ready: Vec<Entity> = runtime
+        .pending_jumps
+        .keys()
+        .filter(|entity| !runtime.held_by_focus(**entity))
+        .copied()
+        .collect();
+    let jumps: Vec<(Entity, String, String)> = ready
+        .into_iter()
+        .filter_map(|e| runtime.pending_jumps.remove(&e).map(|(p, n)| (e, p, n)))
+        .collect();
+
+    for (entity, path, node) in jumps {
+        dev_info!(
+            target: LOG_DIALOGUE,
+            "Processing pending jump to: {} in {} for entity {:?}",
+            node,
+            path,
+            entity
+        );
+        let (proceed, error) = runtime.record_auto_jump(entity, &node);
+        if let Some(error) = error {
+            errors.write(error);
+        }
+        if !proceed {
+            continue;
+        }
+        let entry = runtime.pending_entries.remove(&entity);
+        event_writer.write(MortarCommand::StartNode {
+            path,
+            node,
+            target: Some(entity),
+            entry,
+        });
+    }
+}
@@ -7,5 +7,251 @@ mod core_tests;
 
 #[cfg(test)]
 mod line_group_tests;
+#[cfg(all(test, feature = "ui"))]
+mod parallel_text_tests;
 
+#[cfg(all(test, feature = "ui"))]
+mod run_text_behavior_tests;
+
+#[cfg(test)]
+mod reversible_effects_tests;
+
+#[cfg(all(test, feature = "ui"))]
+mod choice_reevaluation_tests;
+
+#[cfg(test)]
+mod choice_pagination_tests;
+
+#[cfg(test)]
+mod metadata_tests;
+
+#[cfg(test)]
+mod preparation_tests;
+
+#[cfg(test)]
+mod memory_tests;
+
+#[cfg(all(test, feature = "ui"))]
+mod node_entry_tests;
+
+#[cfg(all(test, feature = "ui"))]
+mod line_seek_tests;
+
+#[cfg(test)]
+mod registry_tests;
+
+#[cfg(test)]
+mod reentrancy_tests;
+
+#[cfg(test)]
+mod scoped_entity_tests;
+
+#[cfg(test)]
+mod log_target_tests;
+
+#[cfg(all(test, feature = "ui"))]
+mod alias_tests;
+
+#[cfg(all(test, feature = "ui"))]
+mod advance_intent_tests;
+
+#[cfg(all(test, feature = "ui"))]
+mod line_id_tests;
+#[cfg(test)]
+mod load_progress_tests;
+
+#[cfg(all(test, feature = "ui"))]
+mod icon_tests;
+
+#[cfg(all(test, feature = "ui"))]
+mod call_context_tests;
+
+#[cfg(all(test, feature = "ui"))]
+mod coercion_tests;
+
+#[cfg(all(test, feature = "ui"))]
+mod reveal_policy_tests;
+
+#[cfg(test)]
+mod validation_tests;
+
+#[cfg(test)]
+mod lint_tests;
+
+#[cfg(test)]
+mod malformed_node_tests;
+
+#[cfg(all(test, feature = "ui"))]
+mod script_flow_tests;
+
+#[cfg(all(test, feature = "ui"))]
+mod state_history_tests;
+
+#[cfg(all(test, feature = "ui"))]
+mod text_coalescing_tests;
+
+#[cfg(all(test, feature = "ui"))]
+mod header_tests;
+
+#[cfg(all(test, feature = "ui"))]
+mod node_tag_tests;
+
+#[cfg(test)]
+mod choice_group_token_tests;
+
+#[cfg(all(test, feature = "ui"))]
+mod choice_capture_tests;
+#[cfg(test)]
+mod choice_metadata_tests;
+#[cfg(test)]
+mod content_diagnostics_tests;
+#[cfg(test)]
+mod definition_lookup_tests;
+#[cfg(test)]
+mod fixed_schedule_tests;
+#[cfg(all(test, feature = "ui"))]
+mod late_registration_tests;
+#[cfg(test)]
+mod parsed_node_tests;
+#[cfg(all(test, feature = "ui"))]
+mod rng_stream_tests;
+#[cfg(all(test, feature = "ui"))]
+mod shop_example_tests;
+#[cfg(test)]
+mod signal_wait_tests;
+#[cfg(all(test, feature = "ui"))]
+mod speech_tests;
+#[cfg(test)]
+mod thread_safety_tests;
+#[cfg(test)]
+mod timeline_chain_tests;
+
+#[cfg(feature = "tools")]
+mod graph_export_tests;
+
+#[cfg(feature = "tools")]
+mod strings_export_tests;
+
+#[cfg(feature = "save")]
+mod save_snapshot_tests;
+#[cfg(feature = "save")]
+mod save_tests;
+
+#[cfg(feature = "typewriter")]
+mod typewriter_tests;
+
+#[cfg(feature = "animation")]
+mod animation_tests;
+
+#[cfg(all(test, feature = "ui"))]
+mod capability_tests;
+#[cfg(test)]
+mod choice_confirm_guard_tests;
+#[cfg(all(test, feature = "ui"))]
+mod choice_mutation_tests;
+#[cfg(all(test, feature = "ui"))]
+mod event_schema_tests;
+#[cfg(all(test, feature = "ui"))]
+mod experiment_tests;
 mod fuzz_tests;
+#[cfg(test)]
+mod internal_entity_tests;
+#[cfg(all(test, feature = "ui"))]
+mod line_explanation_tests;
+#[cfg(all(test, feature = "ui"))]
+mod preview_watch_tests;
+#[cfg(all(test, feature = "ui"))]
+mod reveal_catch_up_tests;
+#[cfg(all(test, feature = "ui"))]
+mod reveal_checkpoint_tests;
+#[cfg(test)]
+mod simulate_confirm_tests;
+
+#[cfg(test)]
+mod headless_tests;
+
+#[cfg(test)]
+mod choice_confirm_mode_tests;
+
+#[cfg(test)]
+mod analysis_tests;
+
+#[cfg(test)]
+mod choice_ui_hints_tests;
+
+#[cfg(all(test, feature = "ui"))]
+mod auto_advance_guard_tests;
+
+#[cfg(all(test, feature = "save"))]
+mod variable_bridge_tests;
+
+#[cfg(test)]
+mod start_availability_tests;
+
+#[cfg(all(test, feature = "ui"))]
+mod switch_content_tests;
+
+#[cfg(test)]
+mod line_cursor_tests;
+
+#[cfg(all(test, feature = "ui"))]
+mod display_animation_tests;
+
+#[cfg(test)]
+mod overlay_tests;
+
+#[cfg(all(test, feature = "ui"))]
+mod scoped_function_tests;
+
+#[cfg(all(test, feature = "ui"))]
+mod selection_persistence_tests;
+
+#[cfg(test)]
+mod jump_to_node_tests;
+
+#[cfg(all(test, feature = "save", feature = "ui"))]
+mod history_export_tests;
+
+#[cfg(all(test, feature = "ui"))]
+mod conditional_text_tests;
+
+#[cfg(all(test, feature = "ui"))]
+mod choice_finish_tests;
+
+#[cfg(all(test, feature = "ui"))]
+mod focus_tests;
+
+#[cfg(all(test, feature = "ui"))]
+mod auto_advance_tests;
+#[cfg(all(test, feature = "ui", feature = "window"))]
+mod background_focus_tests;
+#[cfg(all(test, feature = "ui"))]
+mod backlog_choice_tests;
+#[cfg(all(test, feature = "ui"))]
+mod condition_memo_tests;
+#[cfg(all(test, feature = "ui"))]
+mod content_index_tests;
+#[cfg(all(test, feature = "ui"))]
+mod direct_drive_tests;
+#[cfg(all(test, feature = "ui"))]
+mod event_targets_tests;
+#[cfg(test)]
+mod function_docs_tests;
+#[cfg(test)]
+mod function_instance_tests;
+#[cfg(test)]
+mod function_registry_tests;
+#[cfg(all(test, feature = "ui"))]
+mod hot_reload_tests;
+#[cfg(test)]
+mod last_good_tests;
+#[cfg(all(test, feature = "ui"))]
+mod mandatory_event_tests;
+#[cfg(all(test, feature = "ui"))]
+mod node_override_tests;
+#[cfg(all(test, feature = "ui"))]
+mod prelude_tests;
+#[cfg(all(test, feature = "ui"))]
+mod speaker_focus_tests;
+#[cfg(all(test, feature = "ui"))]
+mod text_changed_tests;
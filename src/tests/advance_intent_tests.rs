@@ -0,0 +1,167 @@
+//! Drives a dialogue through every [`AdvanceIntent`] and checks that `NextText` does what the
+//! intent announced: runs block it, a revealing line is finished instead of skipped, and the
+//! [`MortarAdvanceIntent`] resource follows the runtime.
+//!
+//! 驱动一段对话经历每一种 [`AdvanceIntent`]，并检查 `NextText` 的行为与意图一致：run 会阻止推进，
+//! 正在逐字显示的行会被补全而不是跳过，[`MortarAdvanceIntent`] 资源与运行时保持一致。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use bevy::time::TimeUpdateStrategy;
+use mortar_compiler::Deserializer;
+use std::time::Duration;
+
+const PATH: &str = "advance.mortar";
+
+fn advance_asset() -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [
+            {
+                "name": "Start",
+                "content": [
+                    { "type": "text", "value": "One" },
+                    { "type": "run_event", "name": "flash" },
+                    { "type": "run_event", "name": "shake" },
+                    { "type": "text", "value": "Two" },
+                    { "type": "choice", "options": [
+                        { "text": "Stop", "next": "return" },
+                        { "text": "Go on", "next": "Next" }
+                    ] }
+                ]
+            },
+            { "name": "Next", "content": [{ "type": "text", "value": "Last" }] }
+        ],
+        "functions": [],
+        "events": [
+            { "name": "flash", "action": { "type": "flash" }, "duration": 1.0 },
+            { "name": "shake", "action": { "type": "shake" } }
+        ]
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+fn setup_app() -> (App, Entity) {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+    ))
+    .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+        30,
+    )));
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(advance_asset());
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+    let target = app
+        .world_mut()
+        .spawn((Text::new(""), MortarTextTarget))
+        .id();
+    (app, target)
+}
+
+fn send(app: &mut App, event: MortarCommand) {
+    app.world_mut().write_message(event);
+    for _ in 0..3 {
+        app.update();
+    }
+}
+
+/// Returns the intent, checking that the published resource agrees with the runtime.
+fn intent(app: &App) -> AdvanceIntent {
+    let computed = app.world().resource::<MortarRuntime>().advance_intent();
+    assert_eq!(
+        app.world().resource::<MortarAdvanceIntent>().intent,
+        computed
+    );
+    computed
+}
+
+fn current_text(app: &App) -> Option<String> {
+    let runtime = app.world().resource::<MortarRuntime>();
+    Some(runtime.primary_dialogue_state()?.current_text()?.to_owned())
+}
+
+#[test]
+fn test_intent_follows_dialogue_flow() {
+    let (mut app, _) = setup_app();
+    app.update();
+    assert_eq!(intent(&app), AdvanceIntent::Nothing { reason: None });
+    assert!(intent(&app).event().is_none());
+
+    send(&mut app, MortarCommand::start_node(PATH, "Start"));
+    assert_eq!(intent(&app), AdvanceIntent::NextLine);
+
+    send(&mut app, MortarCommand::next_text());
+    assert_eq!(current_text(&app).as_deref(), Some("Two"));
+    assert_eq!(
+        intent(&app),
+        AdvanceIntent::BlockedByRuns { skippable: false }
+    );
+    send(&mut app, MortarCommand::next_text());
+    assert_eq!(current_text(&app).as_deref(), Some("Two"));
+
+    for _ in 0..40 {
+        app.update();
+    }
+    assert_eq!(intent(&app), AdvanceIntent::NeedsSelection);
+
+    send(
+        &mut app,
+        MortarCommand::SelectChoice {
+            index: 1,
+            target: None,
+            group: None,
+            source: None,
+        },
+    );
+    assert_eq!(intent(&app), AdvanceIntent::ConfirmChoice { index: 1 });
+    let confirm = intent(&app)
+        .event()
+        .expect("confirming should send an event");
+    assert!(matches!(
+        confirm,
+        MortarCommand::ConfirmChoice {
+            target: None,
+            group: None,
+        }
+    ));
+
+    send(&mut app, confirm);
+    assert_eq!(current_text(&app).as_deref(), Some("Last"));
+    assert_eq!(intent(&app), AdvanceIntent::WouldFinishDialogue);
+
+    send(&mut app, MortarCommand::next_text());
+    assert_eq!(intent(&app), AdvanceIntent::Nothing { reason: None });
+    assert!(
+        !app.world()
+            .resource::<MortarRuntime>()
+            .has_active_dialogues()
+    );
+}
+
+#[test]
+fn test_next_text_finishes_reveal_first() {
+    let (mut app, target) = setup_app();
+    app.world_mut()
+        .entity_mut(target)
+        .insert(MortarTextReveal::new(1.0));
+
+    send(&mut app, MortarCommand::start_node(PATH, "Start"));
+    assert_eq!(intent(&app), AdvanceIntent::RevealRemaining);
+
+    send(&mut app, MortarCommand::next_text());
+    assert_eq!(current_text(&app).as_deref(), Some("One"));
+    assert_eq!(
+        app.world().get::<Text>(target).unwrap().0,
+        "[advance.mortar / Start]\n\nOne"
+    );
+    assert_eq!(intent(&app), AdvanceIntent::NextLine);
+}
@@ -0,0 +1,95 @@
+//! Covers one asset registered under two paths: the aliases are reported by the registry, and
+//! moving between them keeps the variable state and logs the public constants only once.
+//!
+//! 覆盖同一资源注册在两个路径下的情况：注册表能报告这些别名，在别名之间切换时保留变量状态，
+//! 并且公共常量只打印一次。
+
+use crate::dialogue::LoggedConstants;
+use crate::*;
+use bevy::asset::AssetPlugin;
+use mortar_compiler::Deserializer;
+
+fn aliased_asset() -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [
+            { "name": "Intro", "content": [{ "type": "text", "value": "Welcome" }] },
+            { "name": "Outro", "content": [{ "type": "text", "value": "Farewell" }] }
+        ],
+        "functions": [],
+        "variables": [{ "name": "gold", "type": "Number", "value": 20 }],
+        "constants": [{ "name": "CHAPTER", "type": "Number", "value": 0, "public": true }]
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+fn start(app: &mut App, path: &str, node: &str) {
+    app.world_mut()
+        .write_message(MortarCommand::start_node(path, node));
+    for _ in 0..3 {
+        app.update();
+    }
+}
+
+#[test]
+fn test_aliases_share_variables_and_constant_log() {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+    ));
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(aliased_asset());
+    {
+        let mut registry = app.world_mut().resource_mut::<MortarRegistry>();
+        registry.register("intro.mortar", handle.clone());
+        registry.register("./chapter0.mortar", handle.clone());
+        assert_eq!(
+            registry.aliases_of(&handle),
+            ["chapter0.mortar", "intro.mortar"]
+        );
+    }
+    app.world_mut().spawn((Text::new(""), MortarTextTarget));
+
+    start(&mut app, "intro.mortar", "Intro");
+    app.world_mut()
+        .resource_mut::<MortarDialogueVariables>()
+        .state
+        .as_mut()
+        .expect("variables should be initialized")
+        .set("gold", MortarVariableValue::Number(99.0));
+
+    start(&mut app, "chapter0.mortar", "Outro");
+    let world = app.world();
+    let state = world
+        .resource::<MortarRuntime>()
+        .primary_dialogue_state()
+        .expect("dialogue should be active");
+    assert_eq!(state.mortar_path, "chapter0.mortar");
+    assert_eq!(state.current_text(), Some("Farewell"));
+    assert_eq!(
+        world
+            .resource::<MortarDialogueVariables>()
+            .state
+            .as_ref()
+            .and_then(|variables| variables.get("gold")),
+        Some(&MortarVariableValue::Number(99.0))
+    );
+    assert_eq!(world.resource::<LoggedConstants>().seen_assets.len(), 1);
+}
+
+#[test]
+fn test_distinct_assets_are_not_aliases() {
+    let mut assets = Assets::<MortarAsset>::default();
+    let first = assets.add(aliased_asset());
+    let second = assets.add(aliased_asset());
+    let mut registry = MortarRegistry::default();
+    registry.register("intro.mortar", first.clone());
+    registry.register("chapter0.mortar", second);
+    assert_eq!(registry.aliases_of(&first), ["intro.mortar"]);
+}
@@ -0,0 +1,189 @@
+//! Covers the load-time analysis: with a slowed analysis function the results never arrive on the
+//! frame the analysis starts, a newer save supersedes the analysis of the version it replaced, and
+//! strict starts wait for the analysis or proceed optimistically before refusing invalid files.
+//!
+//! 覆盖加载时分析：使用被放慢的分析函数时，结果绝不会在分析开始的那一帧到达；较新的保存会取代其
+//! 所替换版本的分析；严格启动会等待分析或乐观地先行开始，之后再拒绝无效文件。
+
+use crate::validation::{AnalysisPass, AnalysisTasks, Findings};
+use crate::*;
+use bevy::asset::AssetPlugin;
+use mortar_compiler::{Deserializer, MortaredData};
+use std::time::Duration;
+
+const PATH: &str = "master.mortar";
+const SLOWDOWN: Duration = Duration::from_millis(30);
+
+fn slow_analysis(pass: &AnalysisPass, data: &MortaredData) -> Findings {
+    std::thread::sleep(SLOWDOWN);
+    pass.run(data)
+}
+
+/// Analyses completed so far.
+#[derive(Resource, Default)]
+struct Completed(Vec<MortarAnalysisComplete>);
+
+fn record(mut completed: ResMut<Completed>, mut reader: MessageReader<MortarAnalysisComplete>) {
+    completed.0.extend(reader.read().cloned());
+}
+
+fn asset(next: Option<&str>) -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [{
+            "name": "Start",
+            "content": [{ "type": "text", "value": "The ledger is open" }],
+            "next": next
+        }],
+        "functions": []
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+fn setup_app(strict_start: MortarStrictStart, asset: MortarAsset) -> (App, Handle<MortarAsset>) {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+    ))
+    .insert_resource(MortarLintConfig {
+        lint_on_load: true,
+        validate_on_load: true,
+        ..MortarLintConfig::default()
+    })
+    .init_resource::<Completed>()
+    .add_systems(Last, record);
+    app.world_mut().resource_mut::<AnalysisTasks>().analyze = slow_analysis;
+    app.world_mut().resource_mut::<MortarRuntime>().strict_start = strict_start;
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(asset);
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle.clone());
+    (app, handle)
+}
+
+fn completed(app: &App) -> &[MortarAnalysisComplete] {
+    &app.world().resource::<Completed>().0
+}
+
+/// Updates until `count` analyses have completed, giving the task threads time between frames.
+fn update_until_completed(app: &mut App, count: usize) {
+    for _ in 0..1000 {
+        if completed(app).len() >= count {
+            return;
+        }
+        app.update();
+        std::thread::sleep(Duration::from_millis(2));
+    }
+    panic!("analysis did not complete");
+}
+
+fn current_node(app: &App) -> Option<String> {
+    app.world()
+        .resource::<MortarRuntime>()
+        .primary_dialogue_state()
+        .map(|state| state.current_node.clone())
+}
+
+#[test]
+fn test_analysis_does_not_complete_on_the_frame_it_starts() {
+    let (mut app, handle) = setup_app(MortarStrictStart::Off, asset(None));
+    app.update();
+    assert!(completed(&app).is_empty());
+    let assets = app.world().resource::<Assets<MortarAsset>>();
+    assert!(!assets.get(&handle).unwrap().is_analyzed());
+
+    update_until_completed(&mut app, 1);
+    let done = &completed(&app)[0];
+    assert_eq!(done.asset, handle.id());
+    assert!(done.errors.is_empty());
+    assert!(done.duration >= SLOWDOWN);
+    let assets = app.world().resource::<Assets<MortarAsset>>();
+    assert!(assets.get(&handle).unwrap().is_analyzed());
+}
+
+#[test]
+fn test_newer_save_supersedes_the_analysis_in_flight() {
+    let (mut app, handle) = setup_app(MortarStrictStart::Off, asset(None));
+    app.update();
+    app.world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .insert(&handle, asset(Some("Nowhere")))
+        .unwrap();
+    update_until_completed(&mut app, 1);
+    for _ in 0..5 {
+        app.update();
+    }
+
+    let completed = completed(&app);
+    assert_eq!(completed.len(), 1, "the replaced version is never reported");
+    assert_eq!(
+        completed[0].errors[0].kind,
+        MortarIssueKind::BrokenNodeReference
+    );
+    let assets = app.world().resource::<Assets<MortarAsset>>();
+    let asset = assets.get(&handle).unwrap();
+    assert!(asset.has_validation_errors());
+    assert_eq!(asset.validation_issues(), completed[0].errors.as_slice());
+}
+
+#[test]
+fn test_strict_wait_holds_the_start_until_analysis_completes() {
+    let (mut app, _) = setup_app(MortarStrictStart::Wait, asset(None));
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, "Start"));
+    app.update();
+    assert_eq!(current_node(&app), None);
+    let status = app
+        .world()
+        .resource::<MortarRuntime>()
+        .pending_start_status()
+        .expect("the start should wait");
+    assert_eq!(status.stage, MortarLoadStage::Analyzing);
+
+    update_until_completed(&mut app, 1);
+    app.update();
+    assert_eq!(current_node(&app).as_deref(), Some("Start"));
+}
+
+#[test]
+fn test_strict_wait_refuses_an_invalid_asset() {
+    let (mut app, _) = setup_app(MortarStrictStart::Wait, asset(Some("Nowhere")));
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, "Start"));
+    update_until_completed(&mut app, 1);
+    app.update();
+
+    assert_eq!(current_node(&app), None);
+    let failures: Vec<_> = app
+        .world_mut()
+        .resource_mut::<Messages<MortarStartFailed>>()
+        .drain()
+        .collect();
+    assert_eq!(failures.len(), 1);
+    assert_eq!(failures[0].reason, MortarStartFailure::InvalidAsset);
+}
+
+#[test]
+fn test_strict_optimistic_starts_before_analysis_then_refuses() {
+    let (mut app, _) = setup_app(MortarStrictStart::Optimistic, asset(Some("Nowhere")));
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, "Start"));
+    app.update();
+    assert_eq!(current_node(&app).as_deref(), Some("Start"));
+
+    update_until_completed(&mut app, 1);
+    app.world_mut()
+        .write_message(MortarCommand::stop_dialogue());
+    app.update();
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, "Start"));
+    app.update();
+    assert_eq!(current_node(&app), None);
+}
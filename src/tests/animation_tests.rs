@@ -0,0 +1,135 @@
+//! Covers the animation bridge on a minimal animation graph: a mapped name plays through
+//! [`AnimationTransitions`] with the entry's repeat mode, an unmapped name leaves the rig alone and
+//! is reported, a timed animation reverts to idle, and a rig can be picked by name.
+//!
+//! 在最小的动画图上覆盖动画桥接：已映射的名称会以条目的重复模式通过 [`AnimationTransitions`] 播放，
+//! 未映射的名称不会影响骨架并会被报告，定时动画会回到待机动画，并且可以按名称选择骨架。
+
+use crate::*;
+use bevy::animation::graph::{AnimationGraph, AnimationNodeIndex};
+use bevy::animation::transition::AnimationTransitions;
+use bevy::animation::{AnimationPlayer, RepeatAnimation};
+use bevy::time::TimeUpdateStrategy;
+use std::time::Duration;
+
+struct Nodes {
+    idle: AnimationNodeIndex,
+    wave: AnimationNodeIndex,
+}
+
+fn graph_nodes() -> Nodes {
+    let mut graph = AnimationGraph::new();
+    let root = graph.root;
+    Nodes {
+        idle: graph.add_clip(Handle::default(), 1.0, root),
+        wave: graph.add_clip(Handle::default(), 1.0, root),
+    }
+}
+
+fn setup_app() -> (App, Nodes) {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, MortarAnimationPlugin))
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+            100,
+        )));
+    (app, graph_nodes())
+}
+
+fn spawn_rig(app: &mut App, name: &str, nodes: &Nodes) -> Entity {
+    let map = MortarAnimationMap::new()
+        .with(
+            "idle",
+            MortarAnimationEntry::new(nodes.idle).with_repeat(RepeatAnimation::Forever),
+        )
+        .with(
+            "wave",
+            MortarAnimationEntry::new(nodes.wave).with_transition(Duration::from_millis(200)),
+        )
+        .with_revert_to("idle");
+    app.world_mut()
+        .spawn((
+            Name::new(name.to_owned()),
+            AnimationPlayer::default(),
+            AnimationTransitions::new(),
+            map,
+        ))
+        .id()
+}
+
+fn fire(app: &mut App, args: &[&str]) {
+    app.world_mut().write_message(MortarGameEvent::new(
+        None,
+        "set_animation",
+        args.iter().map(ToString::to_string).collect(),
+    ));
+    app.update();
+}
+
+fn main_animation(app: &App, rig: Entity) -> Option<AnimationNodeIndex> {
+    app.world()
+        .get::<AnimationTransitions>(rig)
+        .unwrap()
+        .get_main_animation()
+}
+
+#[test]
+fn test_mapped_name_plays_through_transitions() {
+    let (mut app, nodes) = setup_app();
+    let rig = spawn_rig(&mut app, "Mira", &nodes);
+    fire(&mut app, &["idle"]);
+    assert_eq!(main_animation(&app, rig), Some(nodes.idle));
+    let player = app.world().get::<AnimationPlayer>(rig).unwrap();
+    let idle = player.animation(nodes.idle).unwrap();
+    assert_eq!(idle.repeat_mode(), RepeatAnimation::Forever);
+
+    fire(&mut app, &["wave"]);
+    assert_eq!(main_animation(&app, rig), Some(nodes.wave));
+    assert!(
+        app.world()
+            .get::<AnimationPlayer>(rig)
+            .unwrap()
+            .is_playing_animation(nodes.wave)
+    );
+    assert!(app.world().get::<MortarAnimationRevert>(rig).is_none());
+}
+
+#[test]
+fn test_unmapped_name_is_reported() {
+    let (mut app, nodes) = setup_app();
+    let rig = spawn_rig(&mut app, "Mira", &nodes);
+    fire(&mut app, &["idle"]);
+    fire(&mut app, &["dance"]);
+
+    assert_eq!(main_animation(&app, rig), Some(nodes.idle));
+    let diagnostics = app.world().resource::<MortarAnimationDiagnostics>();
+    assert!(diagnostics.unmapped.contains("dance"));
+}
+
+#[test]
+fn test_timed_animation_reverts_to_idle() {
+    let (mut app, nodes) = setup_app();
+    let rig = spawn_rig(&mut app, "Mira", &nodes);
+    fire(&mut app, &["wave", "", "0.3"]);
+    assert_eq!(main_animation(&app, rig), Some(nodes.wave));
+    assert!(app.world().get::<MortarAnimationRevert>(rig).is_some());
+
+    for _ in 0..5 {
+        app.update();
+    }
+    assert_eq!(main_animation(&app, rig), Some(nodes.idle));
+    assert!(app.world().get::<MortarAnimationRevert>(rig).is_none());
+}
+
+#[test]
+fn test_second_arg_picks_rig_by_name() {
+    let (mut app, nodes) = setup_app();
+    let mira = spawn_rig(&mut app, "Mira", &nodes);
+    let toma = spawn_rig(&mut app, "Toma", &nodes);
+    fire(&mut app, &["wave"]);
+    assert_eq!(main_animation(&app, mira), None);
+    assert_eq!(main_animation(&app, toma), None);
+
+    fire(&mut app, &["wave", "Toma"]);
+    assert_eq!(main_animation(&app, mira), None);
+    assert_eq!(main_animation(&app, toma), Some(nodes.wave));
+}
@@ -0,0 +1,168 @@
+//! Covers the auto-advance loop guard: a node whose lines all fail their condition and whose
+//! `next` leads back to it halts once the automatic step budget runs out, the error names the
+//! looping node, user input is ignored while halted, and resuming, starting or stopping recovers.
+//!
+//! 覆盖自动推进循环防护：节点中的行条件全部不成立、且其 `next` 又指回自身时，自动步进预算耗尽后
+//! 对话会暂停，错误会指出循环的节点，暂停期间用户输入会被忽略，而恢复、开始或停止请求可以使其恢复。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use mortar_compiler::Deserializer;
+
+const PATH: &str = "loop.mortar";
+const BUDGET: usize = 10;
+
+fn loop_asset() -> MortarAsset {
+    let hidden = |value: &str| {
+        serde_json::json!({
+            "type": "text",
+            "value": value,
+            "condition": { "type": "identifier", "value": "met" }
+        })
+    };
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [
+            { "name": "Loop", "content": [hidden("Hello again"), hidden("Still here")], "next": "Loop" },
+            { "name": "Safe", "content": [{ "type": "text", "value": "Out of the loop" }] }
+        ],
+        "functions": [],
+        "variables": [{ "name": "met", "type": "Boolean", "value": false }]
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+/// Errors reported so far.
+#[derive(Resource, Default)]
+struct Reported(Vec<MortarErrorEvent>);
+
+fn record(mut reported: ResMut<Reported>, mut reader: MessageReader<MortarErrorEvent>) {
+    reported.0.extend(reader.read().cloned());
+}
+
+fn setup_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+    ))
+    .init_resource::<Reported>()
+    .add_systems(Last, record);
+    app.world_mut()
+        .resource_mut::<MortarRuntime>()
+        .auto_advance_budget = Some(BUDGET);
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(loop_asset());
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+    app.world_mut().spawn((Text::new(""), MortarTextTarget));
+    app
+}
+
+fn reported(app: &App) -> &[MortarErrorEvent] {
+    &app.world().resource::<Reported>().0
+}
+
+/// Updates until `count` errors have been reported.
+fn update_until_reported(app: &mut App, count: usize) {
+    for _ in 0..(BUDGET * 10) {
+        if reported(app).len() >= count {
+            return;
+        }
+        app.update();
+    }
+    panic!("the loop was not detected");
+}
+
+fn runtime(app: &App) -> &MortarRuntime {
+    app.world().resource::<MortarRuntime>()
+}
+
+fn position(app: &App) -> Option<(String, usize)> {
+    runtime(app)
+        .primary_dialogue_state()
+        .map(|state| (state.current_node.clone(), state.text_index))
+}
+
+/// Starts the looping node and updates until it halts.
+fn halt(app: &mut App) {
+    let count = reported(app).len() + 1;
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, "Loop"));
+    update_until_reported(app, count);
+}
+
+#[test]
+fn test_self_loop_halts_and_names_the_node() {
+    let mut app = setup_app();
+    halt(&mut app);
+
+    let MortarErrorEvent::AutoAdvanceLoopDetected {
+        node, steps, trail, ..
+    } = &reported(&app)[0];
+    assert_eq!(node, "Loop");
+    assert_eq!(*steps, BUDGET + 1);
+    assert!(!trail.is_empty());
+    assert!(trail.iter().all(|(node, _)| node == "Loop"));
+    assert!(trail.iter().any(|(_, index)| *index == 0));
+    assert!(trail.iter().any(|(_, index)| *index == 1));
+    assert_eq!(
+        runtime(&app).advance_intent(),
+        AdvanceIntent::Nothing {
+            reason: Some(MortarHaltReason::AutoAdvanceLoop)
+        }
+    );
+
+    // Nothing moves while halted, user input included.
+    //
+    // 暂停期间一切都不会推进，包括用户输入。
+    let before = position(&app);
+    app.world_mut().write_message(MortarCommand::next_text());
+    for _ in 0..5 {
+        app.update();
+    }
+    assert_eq!(position(&app), before);
+    assert_eq!(reported(&app).len(), 1);
+}
+
+#[test]
+fn test_resume_after_error_runs_until_the_budget_runs_out_again() {
+    let mut app = setup_app();
+    halt(&mut app);
+    app.world_mut()
+        .write_message(MortarCommand::resume_after_error());
+    update_until_reported(&mut app, 2);
+    assert_eq!(
+        position(&app).map(|(node, _)| node).as_deref(),
+        Some("Loop")
+    );
+}
+
+#[test]
+fn test_stop_and_start_lift_the_halt() {
+    let mut app = setup_app();
+    halt(&mut app);
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, "Safe"));
+    app.update();
+    assert_eq!(
+        runtime(&app).advance_intent(),
+        AdvanceIntent::WouldFinishDialogue
+    );
+
+    halt(&mut app);
+    app.world_mut()
+        .write_message(MortarCommand::stop_dialogue());
+    app.update();
+    assert!(!runtime(&app).has_active_dialogues());
+    assert_eq!(
+        runtime(&app).advance_intent(),
+        AdvanceIntent::Nothing { reason: None }
+    );
+}
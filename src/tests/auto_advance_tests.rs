@@ -0,0 +1,166 @@
+//! Covers the auto mode: a fully shown line advances once its delay, base plus per character,
+//! has passed and not before, the clock holds while a timeline blocks the dialogue, and a choice
+//! group is never confirmed, even with an option selected.
+//!
+//! 覆盖自动模式：完整显示的行在其延迟（基础延迟加每字符延迟）过去后才会推进；时间线阻塞对话期间
+//! 计时暂停；选项组永远不会被确认，即使已选中某个选项。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use bevy::time::TimeUpdateStrategy;
+use mortar_compiler::Deserializer;
+use std::time::Duration;
+
+const PATH: &str = "ferry.mortar";
+
+fn ferry_asset() -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [
+            {
+                "name": "Dock",
+                "content": [
+                    { "type": "text", "value": "Fog." },
+                    { "type": "text", "value": "The ferry is late." },
+                    {
+                        "type": "choice",
+                        "options": [{ "text": "Wait", "next": "Pier" }, { "text": "Leave" }]
+                    }
+                ]
+            },
+            {
+                "name": "Pier",
+                "content": [
+                    { "type": "text", "value": "A horn." },
+                    { "type": "run_event", "name": "Arrival" },
+                    { "type": "text", "value": "It docks." }
+                ]
+            }
+        ],
+        "functions": [],
+        "events": [{ "name": "moor", "action": { "type": "moor" } }],
+        "timelines": [{ "name": "Arrival", "statements": [
+            { "type": "run", "event_name": "moor" },
+            { "type": "wait_signal", "event_name": "moored" }
+        ] }]
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+/// An app stepping 0.1s per frame, with the auto mode on.
+fn setup_app(node: &str, delay_seconds: f32, per_char_seconds: f32) -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+    ))
+    .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+        100,
+    )))
+    .insert_resource(MortarAutoAdvance {
+        enabled: true,
+        delay_seconds,
+        per_char_seconds,
+    });
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(ferry_asset());
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+    app.world_mut().spawn((Text::new(""), MortarTextTarget));
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, node));
+    app
+}
+
+fn current_text(app: &App) -> Option<String> {
+    let runtime = app.world().resource::<MortarRuntime>();
+    runtime
+        .primary_dialogue_state()?
+        .current_text()
+        .map(str::to_owned)
+}
+
+/// Runs frames until the current text is `text`, returning how many it took.
+fn frames_until(app: &mut App, text: &str, limit: usize) -> Option<usize> {
+    (1..=limit).find(|_| {
+        app.update();
+        current_text(app).as_deref() == Some(text)
+    })
+}
+
+#[test]
+fn test_lines_advance_after_their_delay() {
+    let mut app = setup_app("Dock", 0.45, 0.0);
+    let shown = frames_until(&mut app, "Fog.", 5).expect("the dialogue starts");
+    assert_eq!(shown, 1);
+    let waited = frames_until(&mut app, "The ferry is late.", 20).expect("the line advances");
+    assert!((5..=7).contains(&waited), "advanced after {waited} frames");
+
+    // With 0.1s for each character, "Fog." holds 0.4s longer.
+    //
+    // 每字符 0.1 秒时，"Fog." 多停留 0.4 秒。
+    let mut app = setup_app("Dock", 0.45, 0.1);
+    frames_until(&mut app, "Fog.", 5).expect("the dialogue starts");
+    let waited = frames_until(&mut app, "The ferry is late.", 30).expect("the line advances");
+    assert!((9..=11).contains(&waited), "advanced after {waited} frames");
+}
+
+#[test]
+fn test_choices_are_never_confirmed() {
+    let mut app = setup_app("Dock", 0.2, 0.0);
+    frames_until(&mut app, "The ferry is late.", 20).expect("the line advances");
+    for _ in 0..10 {
+        app.update();
+    }
+    app.world_mut()
+        .write_message(MortarCommand::select_choice(0));
+    for _ in 0..30 {
+        app.update();
+    }
+    let runtime = app.world().resource::<MortarRuntime>();
+    let state = runtime.primary_dialogue_state().expect("still active");
+    assert_eq!(state.current_node, "Dock");
+    assert_eq!(state.selected_choice, Some(0));
+    assert_eq!(
+        runtime.advance_intent(),
+        AdvanceIntent::ConfirmChoice { index: 0 }
+    );
+}
+
+#[test]
+fn test_clock_holds_while_runs_execute() {
+    let mut app = setup_app("Pier", 0.3, 0.0);
+    frames_until(&mut app, "It docks.", 20).expect("the timeline starts");
+    for _ in 0..30 {
+        app.update();
+    }
+    assert!(app.world().resource::<MortarRunsExecuting>().executing);
+    assert!(
+        app.world()
+            .resource::<MortarRuntime>()
+            .primary_dialogue_state()
+            .is_some(),
+        "the dialogue waits for the timeline"
+    );
+
+    app.world_mut()
+        .write_message(MortarCommand::signal("moored"));
+    let mut frames = 0;
+    while app
+        .world()
+        .resource::<MortarRuntime>()
+        .primary_dialogue_state()
+        .is_some()
+    {
+        assert!(frames < 20, "the dialogue never finished");
+        app.update();
+        frames += 1;
+    }
+    assert!(frames >= 3, "finished after {frames} frames");
+}
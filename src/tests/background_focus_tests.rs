@@ -0,0 +1,260 @@
+//! Covers pausing dialogue in the background: nothing pauses under the default policy, both
+//! pausing policies hold the reveal and timeline waits without catching up on return, several
+//! windows pause once all of them (or any, when configured) are away, a minimized window counts as
+//! away, and the pause is reported as lifecycle events.
+//!
+//! 覆盖后台时暂停对话：默认策略下不暂停任何内容；两种暂停策略都会停住逐字显示与时间线等待，返回时
+//! 不补追进度；多个窗口全部离开（或按配置任一离开）时才暂停；最小化的窗口视为离开；暂停以生命周期
+//! 事件报告。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use bevy::time::TimeUpdateStrategy;
+use bevy::window::{WindowFocused, WindowOccluded};
+use mortar_compiler::Deserializer;
+use std::time::Duration;
+
+const PATH: &str = "attic.mortar";
+
+#[derive(Resource, Default)]
+struct Recorded {
+    lifecycle: Vec<MortarLifecycleEvent>,
+    fired: Vec<String>,
+}
+
+fn record(
+    mut recorded: ResMut<Recorded>,
+    mut lifecycle: MessageReader<MortarLifecycleEvent>,
+    mut events: MessageReader<MortarGameEvent>,
+) {
+    recorded.lifecycle.extend(lifecycle.read().cloned());
+    recorded
+        .fired
+        .extend(events.read().map(|event| event.name.clone()));
+}
+
+fn attic_asset() -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [{
+            "name": "Attic",
+            "content": [
+                { "type": "text", "value": "Dust drifts through a thin beam of light." },
+                { "type": "run_event", "name": "Creak" },
+                { "type": "text", "value": "Something moved." }
+            ]
+        }],
+        "functions": [],
+        "events": [
+            { "name": "step", "action": { "type": "floor_creaks" } },
+            { "name": "fall", "action": { "type": "box_falls" } }
+        ],
+        "timelines": [{ "name": "Creak", "statements": [
+            { "type": "run", "event_name": "step" },
+            { "type": "wait", "duration": 1.0 },
+            { "type": "run", "event_name": "fall" }
+        ] }]
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+fn run(app: &mut App, frames: usize) {
+    for _ in 0..frames {
+        app.update();
+    }
+}
+
+/// Starts the attic under `settings` at 100ms per frame, with the target revealing 10 characters
+/// per second when `reveal` is set.
+fn setup_app(settings: MortarFocusSettings, reveal: bool) -> (App, Entity) {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+    ))
+    .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+        100,
+    )))
+    .insert_resource(settings)
+    .init_resource::<Recorded>()
+    .add_systems(Last, record);
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(attic_asset());
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+    let target = app
+        .world_mut()
+        .spawn((Text::new(""), MortarTextTarget))
+        .id();
+    if reveal {
+        app.world_mut()
+            .entity_mut(target)
+            .insert(MortarTextReveal::new(10.0));
+    }
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, "Attic"));
+    run(&mut app, 3);
+    (app, target)
+}
+
+fn settings(policy: MortarFocusPolicy) -> MortarFocusSettings {
+    MortarFocusSettings {
+        policy,
+        ..default()
+    }
+}
+
+fn focus(app: &mut App, window: Entity, focused: bool) {
+    app.world_mut()
+        .write_message(WindowFocused { window, focused });
+}
+
+fn revealed(app: &App, target: Entity) -> usize {
+    app.world()
+        .get::<MortarTextReveal>(target)
+        .unwrap()
+        .revealed_chars()
+}
+
+fn lifecycle(app: &App) -> &[MortarLifecycleEvent] {
+    &app.world().resource::<Recorded>().lifecycle
+}
+
+/// How many characters the reveal shows over `frames` frames.
+fn reveal_progress(app: &mut App, target: Entity, frames: usize) -> usize {
+    let before = revealed(app, target);
+    run(app, frames);
+    revealed(app, target) - before
+}
+
+#[test]
+fn test_default_policy_keeps_revealing_in_the_background() {
+    let (mut app, target) = setup_app(MortarFocusSettings::default(), true);
+    let window = app.world_mut().spawn_empty().id();
+    focus(&mut app, window, false);
+    run(&mut app, 1);
+
+    assert!(reveal_progress(&mut app, target, 5) > 0);
+    assert!(!app.world().resource::<MortarBackgroundPause>().is_paused());
+    assert!(lifecycle(&app).is_empty());
+}
+
+#[test]
+fn test_pausing_policies_hold_the_reveal_without_catching_up() {
+    for policy in [
+        MortarFocusPolicy::PauseAll,
+        MortarFocusPolicy::PauseBlockingOnly,
+    ] {
+        let (mut app, target) = setup_app(settings(policy), true);
+        let window = app.world_mut().spawn_empty().id();
+        focus(&mut app, window, false);
+        run(&mut app, 1);
+
+        assert_eq!(reveal_progress(&mut app, target, 5), 0);
+        assert_eq!(
+            app.world().resource::<MortarBackgroundPause>().paused_by(),
+            Some(policy)
+        );
+        assert_eq!(lifecycle(&app), [MortarLifecycleEvent::BackgroundPaused]);
+
+        // One character per frame, nothing owed for the paused frames.
+        //
+        // 每帧一个字符，暂停的帧不会补上。
+        focus(&mut app, window, true);
+        run(&mut app, 1);
+        let progress = reveal_progress(&mut app, target, 5);
+        assert!((1..=5).contains(&progress), "{progress}");
+        assert_eq!(
+            lifecycle(&app),
+            [
+                MortarLifecycleEvent::BackgroundPaused,
+                MortarLifecycleEvent::BackgroundResumed
+            ]
+        );
+    }
+}
+
+#[test]
+fn test_timeline_wait_stands_still_in_the_background() {
+    let (mut app, _) = setup_app(settings(MortarFocusPolicy::PauseBlockingOnly), false);
+    let window = app.world_mut().spawn_empty().id();
+    app.world_mut().write_message(MortarCommand::next_text());
+    run(&mut app, 2);
+    assert_eq!(app.world().resource::<Recorded>().fired, ["floor_creaks"]);
+
+    focus(&mut app, window, false);
+    run(&mut app, 30);
+    assert_eq!(app.world().resource::<Recorded>().fired, ["floor_creaks"]);
+
+    // The wait picks up with the time it had left.
+    //
+    // 等待从剩余的时间继续。
+    focus(&mut app, window, true);
+    run(&mut app, 3);
+    assert_eq!(app.world().resource::<Recorded>().fired, ["floor_creaks"]);
+    run(&mut app, 10);
+    assert_eq!(
+        app.world().resource::<Recorded>().fired,
+        ["floor_creaks", "box_falls"]
+    );
+}
+
+#[test]
+fn test_several_windows_pause_when_all_or_any_are_away() {
+    for (background_when, paused_with_one_away) in [
+        (MortarBackgroundWindows::AllUnfocused, false),
+        (MortarBackgroundWindows::AnyUnfocused, true),
+    ] {
+        let (mut app, _) = setup_app(
+            MortarFocusSettings {
+                policy: MortarFocusPolicy::PauseAll,
+                background_when,
+            },
+            true,
+        );
+        let main = app.world_mut().spawn_empty().id();
+        let map = app.world_mut().spawn_empty().id();
+        focus(&mut app, main, true);
+        focus(&mut app, map, false);
+        run(&mut app, 1);
+        let paused = |app: &App| app.world().resource::<MortarBackgroundPause>().is_paused();
+        assert_eq!(paused(&app), paused_with_one_away, "{background_when:?}");
+
+        focus(&mut app, main, false);
+        run(&mut app, 1);
+        assert!(paused(&app), "{background_when:?}");
+    }
+}
+
+#[test]
+fn test_minimized_window_counts_as_away() {
+    let (mut app, target) = setup_app(settings(MortarFocusPolicy::PauseAll), true);
+    let window = app.world_mut().spawn_empty().id();
+    focus(&mut app, window, true);
+    app.world_mut().write_message(WindowOccluded {
+        window,
+        occluded: true,
+    });
+    run(&mut app, 1);
+    assert_eq!(reveal_progress(&mut app, target, 5), 0);
+
+    app.world_mut().write_message(WindowOccluded {
+        window,
+        occluded: false,
+    });
+    run(&mut app, 1);
+    assert!(reveal_progress(&mut app, target, 5) > 0);
+    assert_eq!(
+        lifecycle(&app),
+        [
+            MortarLifecycleEvent::BackgroundPaused,
+            MortarLifecycleEvent::BackgroundResumed
+        ]
+    );
+}
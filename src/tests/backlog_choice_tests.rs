@@ -0,0 +1,133 @@
+//! Covers confirmed options in the dialogue backlog: a play-through of a line, a skipped
+//! conditional line, a choice and the jump it leads to records the interpolated lines and the
+//! option in order under their own nodes, leaves the skipped line out, and a small capacity keeps
+//! only the newest entries until `clear` empties it.
+//!
+//! 覆盖对话回顾记录中的已确认选项：依次经过一行、一条被跳过的条件行、一次选择及其导致的跳转后，
+//! 会按顺序在各自的节点下记录插值后的行与选项，并略过被跳过的行；较小的容量只保留最新的记录，直到
+//! `clear` 将其清空。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use mortar_compiler::Deserializer;
+
+const PATH: &str = "gate.mortar";
+
+fn gate_asset() -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [
+            {
+                "name": "Gate",
+                "content": [
+                    {
+                        "type": "text",
+                        "value": "The gate is {state}.",
+                        "interpolated_parts": [
+                            { "type": "text", "content": "The gate is " },
+                            { "type": "placeholder", "content": "{state}" },
+                            { "type": "text", "content": "." }
+                        ]
+                    },
+                    {
+                        "type": "text",
+                        "value": "You have a key.",
+                        "condition": { "type": "identifier", "value": "has_key" }
+                    },
+                    { "type": "text", "value": "Who goes there?" },
+                    {
+                        "type": "choice",
+                        "options": [{ "text": "A friend", "next": "Yard" }, { "text": "Nobody" }]
+                    }
+                ]
+            },
+            { "name": "Yard", "content": [{ "type": "text", "value": "Welcome in." }] }
+        ],
+        "functions": [],
+        "variables": [
+            { "name": "state", "type": "String", "value": "shut" },
+            { "name": "has_key", "type": "Boolean", "value": false }
+        ]
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+/// Plays the gate through its choice into the yard, with `history` as the backlog.
+fn play_gate(history: MortarDialogueHistory) -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+    ))
+    .insert_resource(history);
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(gate_asset());
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+    app.world_mut().spawn((Text::new(""), MortarTextTarget));
+
+    for command in [
+        MortarCommand::start_node(PATH, "Gate"),
+        MortarCommand::next_text(),
+        MortarCommand::select_choice(0),
+        MortarCommand::confirm_choice(),
+    ] {
+        app.world_mut().write_message(command);
+        // The skipped line takes a frame of its own before the next one shows.
+        //
+        // 被跳过的行需要单独占用一帧，下一行才会显示。
+        for _ in 0..6 {
+            app.update();
+        }
+    }
+    app
+}
+
+fn entries(app: &App) -> Vec<(&str, &str, Option<usize>)> {
+    let history = app.world().resource::<MortarDialogueHistory>();
+    history
+        .entries()
+        .iter()
+        .map(|entry| {
+            let body = entry.body.as_deref().unwrap_or_default();
+            (entry.node.as_str(), body, entry.choice)
+        })
+        .collect()
+}
+
+#[test]
+fn test_lines_and_confirmed_options_are_recorded_in_order() {
+    let app = play_gate(MortarDialogueHistory::default());
+    assert_eq!(
+        entries(&app),
+        [
+            ("Gate", "The gate is shut.", None),
+            ("Gate", "Who goes there?", None),
+            ("Gate", "A friend", Some(0)),
+            ("Yard", "Welcome in.", None),
+        ]
+    );
+    let history = app.world().resource::<MortarDialogueHistory>();
+    assert!(history.entries()[2].is_choice());
+    assert_eq!(history.entries()[2].mortar_path, PATH);
+}
+
+#[test]
+fn test_capacity_keeps_the_newest_entries_until_cleared() {
+    let mut app = play_gate(MortarDialogueHistory::new(2));
+    assert_eq!(
+        entries(&app),
+        [("Gate", "A friend", Some(0)), ("Yard", "Welcome in.", None)]
+    );
+
+    app.world_mut()
+        .resource_mut::<MortarDialogueHistory>()
+        .clear();
+    assert!(entries(&app).is_empty());
+}
@@ -0,0 +1,166 @@
+//! Covers the call context handed to bound functions: a context-aware function interpolated into
+//! a line sees the node it renders in, functions declared with `#[mortar_functions]` receive it
+//! through a leading `&MortarCallContext` parameter, and plain registry calls see an unknown
+//! context.
+//!
+//! 覆盖传给绑定函数的调用上下文：插值到行中的上下文感知函数能看到它所渲染的节点，通过
+//! `#[mortar_functions]` 声明的函数通过开头的 `&MortarCallContext` 参数接收上下文，而直接的注册表
+//! 调用看到的是未知上下文。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use mortar_compiler::Deserializer;
+
+const PATH: &str = "context.mortar";
+
+#[derive(MortarFunctions)]
+struct ContextFunctions;
+
+#[mortar_functions]
+impl ContextFunctions {
+    fn greeting(context: &MortarCallContext, name: MortarString) -> String {
+        format!(
+            "{} from {}",
+            name.as_str(),
+            context.node.as_deref().unwrap_or("nowhere")
+        )
+    }
+}
+
+fn node_name(context: &MortarCallContext, _args: &[MortarValue]) -> MortarValue {
+    context.node.clone().unwrap_or_default().into()
+}
+
+fn interpolated_line(function: &str, args: &[&str]) -> serde_json::Value {
+    serde_json::json!({
+        "type": "text",
+        "value": format!("Welcome to {{{function}}}"),
+        "interpolated_parts": [
+            { "type": "text", "content": "Welcome to " },
+            {
+                "type": "expression",
+                "content": format!("{{{function}}}"),
+                "function_name": function,
+                "args": args
+            }
+        ]
+    })
+}
+
+fn context_asset() -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [
+            { "name": "Start", "content": [interpolated_line("node_name", &[])], "next": "Harbor" },
+            {
+                "name": "Harbor",
+                "content": [
+                    interpolated_line("node_name", &[]),
+                    interpolated_line("greeting", &["\"Mira\""])
+                ]
+            }
+        ],
+        "functions": [
+            { "name": "node_name", "params": [], "return_type": "String" },
+            {
+                "name": "greeting",
+                "params": [{ "name": "name", "type": "String" }],
+                "return_type": "String"
+            }
+        ]
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+fn setup_app() -> (App, Entity) {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+    ));
+    {
+        let mut runtime = app.world_mut().resource_mut::<MortarRuntime>();
+        runtime
+            .functions
+            .register_with_context("node_name", node_name);
+        ContextFunctions::bind_functions(&mut runtime.functions);
+    }
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(context_asset());
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+    let target = app
+        .world_mut()
+        .spawn((Text::new(""), MortarTextTarget))
+        .id();
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, "Start"));
+    for _ in 0..3 {
+        app.update();
+    }
+    (app, target)
+}
+
+fn body(app: &App, target: Entity) -> String {
+    app.world()
+        .get::<MortarDialogueText>(target)
+        .unwrap()
+        .body
+        .clone()
+}
+
+fn advance(app: &mut App) {
+    app.world_mut().write_message(MortarCommand::next_text());
+    for _ in 0..3 {
+        app.update();
+    }
+}
+
+#[test]
+fn test_interpolated_function_sees_rendering_node() {
+    let (mut app, target) = setup_app();
+    assert_eq!(body(&app, target), "Welcome to Start");
+
+    advance(&mut app);
+    assert_eq!(body(&app, target), "Welcome to Harbor");
+}
+
+#[test]
+fn test_macro_passes_context_before_script_args() {
+    let (mut app, target) = setup_app();
+    advance(&mut app);
+    advance(&mut app);
+    assert_eq!(body(&app, target), "Welcome to Mira from Harbor");
+
+    let manifest = app
+        .world()
+        .resource::<MortarRuntime>()
+        .functions
+        .export_manifest();
+    assert_eq!(manifest.get("greeting").unwrap().arity, Some(1));
+}
+
+#[test]
+fn test_plain_call_sees_unknown_context() {
+    let mut functions = MortarFunctionRegistry::new();
+    functions.register_with_context("node_name", node_name);
+    functions.register_with_context("origin", |context, _| {
+        (context == &MortarCallContext::unknown()).into()
+    });
+
+    let value = functions.call("node_name", &[]).unwrap();
+    assert_eq!(value.to_display_string(), "");
+    assert!(functions.call("origin", &[]).unwrap().is_truthy());
+
+    let context = MortarCallContext::at(PATH, "Harbor", 2);
+    let value = functions
+        .call_with_context(&context, "node_name", &[])
+        .unwrap();
+    assert_eq!(value.to_display_string(), "Harbor");
+}
@@ -0,0 +1,140 @@
+//! Covers adaptive content: a `{cap:input}` placeholder renders the current value, a line with
+//! `requires: ["gamepad"]` shows only while the capability is present, `cap()` answers from the
+//! runtime mirror, and changing [`MortarCapabilities`] mid-line re-renders the current line.
+//!
+//! 覆盖自适应内容：`{cap:input}` 占位符显示当前值，带 `requires: ["gamepad"]` 的行只在该能力存在时
+//! 显示，`cap()` 依据运行时中的同步副本作答，并且在一行显示期间修改 [`MortarCapabilities`] 会重新
+//! 渲染当前行。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use mortar_compiler::Deserializer;
+
+const PATH: &str = "controls.mortar";
+
+fn controls_asset() -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [{
+            "name": "Start",
+            "content": [
+                {
+                    "type": "text",
+                    "value": "Press {cap:input} to jump",
+                    "interpolated_parts": [
+                        { "type": "text", "content": "Press " },
+                        { "type": "placeholder", "content": "{cap:input}" },
+                        { "type": "text", "content": " to jump" }
+                    ]
+                },
+                { "type": "text", "value": "Controller tip", "requires": ["gamepad"] },
+                { "type": "text", "value": "End" }
+            ]
+        }],
+        "functions": []
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+fn setup_app(capabilities: MortarCapabilities) -> (App, Entity) {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+    ))
+    .insert_resource(capabilities);
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(controls_asset());
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+    let target = app
+        .world_mut()
+        .spawn((Text::new(""), MortarTextTarget))
+        .id();
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, "Start"));
+    for _ in 0..3 {
+        app.update();
+    }
+    (app, target)
+}
+
+fn input(value: &str) -> MortarCapabilities {
+    let mut capabilities = MortarCapabilities::default();
+    capabilities.set("input", value);
+    capabilities
+}
+
+fn body(app: &App, target: Entity) -> String {
+    app.world()
+        .get::<MortarDialogueText>(target)
+        .unwrap()
+        .body
+        .clone()
+}
+
+fn advance(app: &mut App) {
+    app.world_mut().write_message(MortarCommand::next_text());
+    for _ in 0..3 {
+        app.update();
+    }
+}
+
+#[test]
+fn test_gamepad_shows_placeholder_and_gated_line() {
+    let (mut app, target) = setup_app(input("gamepad"));
+
+    assert_eq!(body(&app, target), "Press gamepad to jump");
+    let runtime = app.world().resource::<MortarRuntime>();
+    let cap = runtime.functions.call_with_context(
+        &runtime.call_context(),
+        CAPABILITY_FUNCTION,
+        &["gamepad".into()],
+    );
+    assert_eq!(cap.map(|value| value.is_truthy()), Some(true));
+
+    advance(&mut app);
+    assert_eq!(body(&app, target), "Controller tip");
+}
+
+#[test]
+fn test_keyboard_skips_gated_line() {
+    let (mut app, target) = setup_app(input("keyboard"));
+
+    assert_eq!(body(&app, target), "Press keyboard to jump");
+    assert!(
+        !app.world()
+            .resource::<MortarRuntime>()
+            .capabilities()
+            .has("gamepad")
+    );
+
+    advance(&mut app);
+    assert_eq!(body(&app, target), "End");
+}
+
+#[test]
+fn test_capability_change_rerenders_current_line() {
+    let (mut app, target) = setup_app(input("keyboard"));
+    assert_eq!(body(&app, target), "Press keyboard to jump");
+
+    app.world_mut()
+        .resource_mut::<MortarCapabilities>()
+        .set("input", "gamepad");
+    app.update();
+    app.update();
+    assert_eq!(body(&app, target), "Press gamepad to jump");
+    assert_eq!(
+        app.world().resource::<MortarRuntime>().capabilities(),
+        &input("gamepad")
+    );
+
+    advance(&mut app);
+    assert_eq!(body(&app, target), "Controller tip");
+}
@@ -0,0 +1,143 @@
+//! Covers choice capture: confirming an option writes its `id` and declared index into the
+//! group's `capture` variable before the next line renders, groups without one fall back to the
+//! runtime's default variable, and a nested group can capture into its own variable.
+//!
+//! 覆盖选项捕获：确认选项时，会在下一行显示之前把它的 `id` 与声明索引写入选项组的 `capture`
+//! 变量；没有该字段的选项组回退到运行时的默认变量；嵌套组可以捕获到自己的变量中。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use mortar_compiler::Deserializer;
+
+const PATH: &str = "crossroads.mortar";
+
+fn crossroads_asset() -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [
+            {
+                "name": "Crossroads",
+                "content": [
+                    { "type": "text", "value": "Which way?" },
+                    { "type": "choice", "capture": "path", "options": [
+                        { "id": "north", "text": "Take the hill road", "next": "After" },
+                        { "id": "south", "text": "Follow the river", "next": "After" }
+                    ] }
+                ]
+            },
+            {
+                "name": "After",
+                "content": [{
+                    "type": "text",
+                    "value": "So you chose the {path} path ({path_index}).",
+                    "interpolated_parts": [
+                        { "type": "text", "content": "So you chose the " },
+                        { "type": "placeholder", "content": "{path}" },
+                        { "type": "text", "content": " path (" },
+                        { "type": "placeholder", "content": "{path_index}" },
+                        { "type": "text", "content": ")." }
+                    ]
+                }]
+            },
+            {
+                "name": "Tavern",
+                "content": [
+                    { "type": "text", "value": "What will it be?" },
+                    { "type": "choice", "options": [
+                        { "text": "Order a drink", "capture": "drink", "choice": [
+                            { "text": "Ale", "next": "Served" },
+                            { "id": "cider", "text": "Cider", "next": "Served" }
+                        ] },
+                        { "text": "Leave", "next": "return" }
+                    ] }
+                ]
+            },
+            { "name": "Served", "content": [{ "type": "text", "value": "Here you go." }] }
+        ],
+        "functions": []
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+fn setup_app(node: &str, capture: ChoiceCapture) -> (App, Entity) {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+    ));
+    app.world_mut()
+        .resource_mut::<MortarRuntime>()
+        .choice_capture = capture;
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(crossroads_asset());
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+    let target = app
+        .world_mut()
+        .spawn((Text::new(""), MortarTextTarget))
+        .id();
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, node));
+    for _ in 0..3 {
+        app.update();
+    }
+    (app, target)
+}
+
+fn pick(app: &mut App, index: usize) {
+    app.world_mut()
+        .write_message(MortarCommand::select_choice(index));
+    app.world_mut()
+        .write_message(MortarCommand::confirm_choice());
+    for _ in 0..3 {
+        app.update();
+    }
+}
+
+fn variable(app: &App, name: &str) -> Option<String> {
+    app.world()
+        .resource::<MortarDialogueVariables>()
+        .state
+        .as_ref()?
+        .get(name)
+        .map(MortarVariableValue::to_display_string)
+}
+
+#[test]
+fn test_following_line_interpolates_captured_id() {
+    let (mut app, target) = setup_app("Crossroads", ChoiceCapture::default());
+    pick(&mut app, 1);
+
+    let text = app.world().get::<MortarDialogueText>(target).unwrap();
+    assert_eq!(text.body, "So you chose the south path (1).");
+    assert_eq!(variable(&app, "path").as_deref(), Some("south"));
+}
+
+#[test]
+fn test_default_variable_and_nested_group_capture() {
+    let capture = ChoiceCapture {
+        default_variable: Some("_last_choice".to_owned()),
+        value: CaptureValue::Text,
+    };
+    let (mut app, _) = setup_app("Tavern", capture);
+    pick(&mut app, 0);
+    assert_eq!(
+        variable(&app, "_last_choice").as_deref(),
+        Some("Order a drink")
+    );
+    assert_eq!(variable(&app, "_last_choice_index").as_deref(), Some("0"));
+
+    pick(&mut app, 1);
+    assert_eq!(variable(&app, "drink").as_deref(), Some("Cider"));
+    assert_eq!(variable(&app, "drink_index").as_deref(), Some("1"));
+    assert_eq!(
+        variable(&app, "_last_choice").as_deref(),
+        Some("Order a drink")
+    );
+}
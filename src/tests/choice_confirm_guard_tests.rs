@@ -0,0 +1,171 @@
+//! Covers the confirm path after a choice group is resolved: a duplicate `ConfirmChoice` after a
+//! `break` or a jump leaves the dialogue where it is, input queued in the same frame as the
+//! confirm cannot resolve the group twice, and a group cannot be selected before it is reached.
+//!
+//! 覆盖选项组结束之后的确认路径：`break` 或跳转之后重复的 `ConfirmChoice` 不会改变对话位置，与确认
+//! 同一帧排队的输入无法让选项组被再次结算，尚未到达的选项组也无法被选择。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use mortar_compiler::Deserializer;
+
+const PATH: &str = "crossroads.mortar";
+
+#[derive(Resource, Default)]
+struct Captured(usize);
+
+fn count_captured(mut captured: ResMut<Captured>, mut reader: MessageReader<MortarChoiceCaptured>) {
+    captured.0 += reader.read().count();
+}
+
+fn crossroads_asset() -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [
+            {
+                "name": "Start",
+                "content": [
+                    { "type": "text", "value": "A fork in the road" },
+                    { "type": "text", "value": "Which way?" },
+                    { "type": "choice", "capture": "way", "options": [
+                        { "text": "Stay", "action": "break" },
+                        { "text": "Go", "next": "Town" }
+                    ] },
+                    { "type": "text", "value": "You stay" },
+                    { "type": "text", "value": "Still here" }
+                ]
+            },
+            {
+                "name": "Town",
+                "content": [
+                    { "type": "text", "value": "The town" },
+                    { "type": "text", "value": "The square" }
+                ]
+            }
+        ],
+        "functions": []
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+fn setup_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+    ))
+    .init_resource::<Captured>()
+    .add_systems(Last, count_captured);
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(crossroads_asset());
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+    app.world_mut().spawn((Text::new(""), MortarTextTarget));
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, "Start"));
+    run(&mut app, 3);
+    app
+}
+
+fn run(app: &mut App, frames: usize) {
+    for _ in 0..frames {
+        app.update();
+    }
+}
+
+fn send(app: &mut App, events: impl IntoIterator<Item = MortarCommand>) {
+    for event in events {
+        app.world_mut().write_message(event);
+    }
+    run(app, 3);
+}
+
+fn position(app: &App) -> (String, usize, Option<usize>) {
+    let state = app
+        .world()
+        .resource::<MortarRuntime>()
+        .primary_dialogue_state()
+        .expect("the dialogue is active");
+    (
+        state.current_node.clone(),
+        state.text_index,
+        state.selected_choice,
+    )
+}
+
+fn reach_choices(app: &mut App) {
+    send(app, [MortarCommand::next_text()]);
+    assert!(
+        !app.world()
+            .resource::<MortarChoicesPresented>()
+            .views
+            .is_empty()
+    );
+}
+
+#[test]
+fn test_duplicate_confirm_after_break_changes_nothing() {
+    let mut app = setup_app();
+    reach_choices(&mut app);
+    send(
+        &mut app,
+        [
+            MortarCommand::select_choice(0),
+            MortarCommand::confirm_choice(),
+        ],
+    );
+    let after_break = position(&app);
+    assert_eq!(after_break, ("Start".to_owned(), 2, None));
+
+    send(&mut app, [MortarCommand::confirm_choice()]);
+    assert_eq!(position(&app), after_break);
+    send(
+        &mut app,
+        [
+            MortarCommand::select_choice(1),
+            MortarCommand::confirm_choice(),
+        ],
+    );
+    assert_eq!(position(&app), after_break);
+    assert_eq!(app.world().resource::<Captured>().0, 1);
+}
+
+#[test]
+fn test_duplicate_confirm_after_jump_changes_nothing() {
+    let mut app = setup_app();
+    reach_choices(&mut app);
+    send(
+        &mut app,
+        [
+            MortarCommand::select_choice(1),
+            MortarCommand::confirm_choice(),
+            MortarCommand::select_choice(1),
+            MortarCommand::confirm_choice(),
+        ],
+    );
+    let after_jump = position(&app);
+    assert_eq!(after_jump, ("Town".to_owned(), 0, None));
+    assert_eq!(app.world().resource::<Captured>().0, 1);
+
+    send(&mut app, [MortarCommand::confirm_choice()]);
+    assert_eq!(position(&app), after_jump);
+}
+
+#[test]
+fn test_group_cannot_be_selected_before_it_is_reached() {
+    let mut app = setup_app();
+    send(
+        &mut app,
+        [
+            MortarCommand::select_choice(1),
+            MortarCommand::confirm_choice(),
+        ],
+    );
+    assert_eq!(position(&app), ("Start".to_owned(), 0, None));
+}
@@ -0,0 +1,215 @@
+//! Covers `ChoiceConfirmMode`: a two-step selection waits for its confirm while a one-step one
+//! confirms within the same frame, yet both publish the same selected, captured and resolved
+//! messages in the same order and leave the dialogue in the same place; under `PerSource` a
+//! pointer selection confirms, a navigation one waits, and a selection without a source follows
+//! the `input` capability.
+//!
+//! 覆盖 `ChoiceConfirmMode`：两步选择会等待确认，而一步选择在同一帧内完成确认，但两者会以相同顺序
+//! 发布相同的选中、捕获与结算消息，并让对话停在同一位置；在 `PerSource` 下，指针选择会确认，导航
+//! 选择会等待，未指定来源的选择则依据 `input` 能力决定。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use mortar_compiler::Deserializer;
+
+const PATH: &str = "gate.mortar";
+
+/// Lifecycle messages with the frame they were read in.
+#[derive(Resource, Default)]
+struct Lifecycle {
+    frame: u32,
+    seen: Vec<(u32, &'static str, usize)>,
+}
+
+fn count_frame(mut lifecycle: ResMut<Lifecycle>) {
+    lifecycle.frame += 1;
+}
+
+fn record(
+    mut lifecycle: ResMut<Lifecycle>,
+    mut selected: MessageReader<MortarChoiceSelected>,
+    mut captured: MessageReader<MortarChoiceCaptured>,
+    mut resolved: MessageReader<MortarChoiceResolved>,
+) {
+    let frame = lifecycle.frame;
+    let selected: Vec<_> = selected
+        .read()
+        .map(|m| (frame, "selected", m.index))
+        .collect();
+    let captured: Vec<_> = captured
+        .read()
+        .map(|m| (frame, "captured", m.index))
+        .collect();
+    let resolved: Vec<_> = resolved
+        .read()
+        .map(|m| (frame, "resolved", m.outcome.index))
+        .collect();
+    lifecycle.seen.extend(selected);
+    lifecycle.seen.extend(captured);
+    lifecycle.seen.extend(resolved);
+}
+
+fn gate_asset() -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [
+            {
+                "name": "Start",
+                "content": [
+                    { "type": "text", "value": "The gate is shut" },
+                    { "type": "choice", "capture": "pick", "options": [
+                        { "text": "Knock", "next": "Inside" },
+                        { "text": "Leave", "action": "return" }
+                    ] }
+                ]
+            },
+            { "name": "Inside", "content": [{ "type": "text", "value": "Come in" }] }
+        ],
+        "functions": []
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+fn setup_app(mode: ChoiceConfirmMode) -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+    ))
+    .init_resource::<Lifecycle>()
+    .add_systems(First, count_frame)
+    .add_systems(Last, record);
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(gate_asset());
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+    app.world_mut()
+        .resource_mut::<MortarRuntime>()
+        .choice_confirm = mode;
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, "Start"));
+    for _ in 0..3 {
+        app.update();
+    }
+    app
+}
+
+fn send(app: &mut App, event: MortarCommand) {
+    app.world_mut().write_message(event);
+    app.update();
+}
+
+fn current_node(app: &App) -> Option<String> {
+    app.world()
+        .resource::<MortarRuntime>()
+        .primary_dialogue_state()
+        .map(|state| state.current_node.clone())
+}
+
+fn lifecycle(app: &App) -> Vec<(u32, &'static str, usize)> {
+    app.world().resource::<Lifecycle>().seen.clone()
+}
+
+fn kinds(seen: &[(u32, &'static str, usize)]) -> Vec<(&'static str, usize)> {
+    seen.iter().map(|&(_, kind, index)| (kind, index)).collect()
+}
+
+fn settle(app: &mut App) {
+    for _ in 0..3 {
+        app.update();
+    }
+}
+
+#[test]
+fn test_one_step_matches_two_step_within_one_frame() {
+    let mut two_step = setup_app(ChoiceConfirmMode::TwoStep);
+    send(&mut two_step, MortarCommand::select_choice(0));
+    assert_eq!(
+        two_step
+            .world()
+            .resource::<MortarRuntime>()
+            .advance_intent(),
+        AdvanceIntent::ConfirmChoice { index: 0 }
+    );
+    send(&mut two_step, MortarCommand::confirm_choice());
+    settle(&mut two_step);
+
+    let mut one_step = setup_app(ChoiceConfirmMode::OneStep);
+    assert!(
+        one_step
+            .world()
+            .resource::<MortarRuntime>()
+            .selection_confirms(None)
+    );
+    send(&mut one_step, MortarCommand::select_choice(0));
+    settle(&mut one_step);
+
+    let two = lifecycle(&two_step);
+    let one = lifecycle(&one_step);
+    let expected = [("selected", 0), ("captured", 0), ("resolved", 0)];
+    assert_eq!(kinds(&two), expected);
+    assert_eq!(kinds(&one), expected);
+    assert!(two[0].0 < two[1].0, "two steps confirm on a later frame");
+    assert!(one.iter().all(|&(frame, ..)| frame == one[0].0));
+
+    assert_eq!(current_node(&two_step).as_deref(), Some("Inside"));
+    assert_eq!(current_node(&one_step), current_node(&two_step));
+}
+
+#[test]
+fn test_per_source_confirms_pointer_and_waits_for_navigation() {
+    let mut app = setup_app(ChoiceConfirmMode::PerSource);
+    send(
+        &mut app,
+        MortarCommand::select_choice(1).from_source(ChoiceInputSource::Navigation),
+    );
+    assert_eq!(kinds(&lifecycle(&app)), [("selected", 1)]);
+    assert_eq!(
+        app.world()
+            .resource::<MortarRuntime>()
+            .primary_dialogue_state()
+            .and_then(|state| state.selected_choice),
+        Some(1)
+    );
+
+    send(
+        &mut app,
+        MortarCommand::select_choice(0).from_source(ChoiceInputSource::Pointer),
+    );
+    settle(&mut app);
+    assert_eq!(
+        kinds(&lifecycle(&app)),
+        [
+            ("selected", 1),
+            ("selected", 0),
+            ("captured", 0),
+            ("resolved", 0)
+        ]
+    );
+    assert_eq!(current_node(&app).as_deref(), Some("Inside"));
+}
+
+#[test]
+fn test_per_source_without_a_source_follows_the_input_capability() {
+    let mut app = setup_app(ChoiceConfirmMode::PerSource);
+    let runtime = app.world().resource::<MortarRuntime>();
+    assert!(!runtime.selection_confirms(None));
+
+    app.world_mut()
+        .resource_mut::<MortarCapabilities>()
+        .set(INPUT_CAPABILITY, "mouse");
+    app.update();
+    let runtime = app.world().resource::<MortarRuntime>();
+    assert!(runtime.selection_confirms(None));
+    assert!(!runtime.selection_confirms(Some(ChoiceInputSource::Navigation)));
+
+    send(&mut app, MortarCommand::select_choice(0));
+    settle(&mut app);
+    assert_eq!(current_node(&app).as_deref(), Some("Inside"));
+}
@@ -0,0 +1,152 @@
+//! Covers where confirming a choice leaves the dialogue, decided without a follow-up `NextText`:
+//! `return` finishes it, `break` shows the line after the group when there is one and otherwise
+//! leaves the node like its end would (following `next` or finishing), and a plain `next` jumps.
+//!
+//! 覆盖确认选项后对话的去向，无需额外发送 `NextText`：`return` 结束对话；`break` 在选项组之后有行
+//! 时显示该行，否则像到达节点末尾一样离开节点（跟随 `next` 或结束）；普通的 `next` 会跳转。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use mortar_compiler::Deserializer;
+
+const PATH: &str = "harbor.mortar";
+
+#[derive(Resource, Default)]
+struct Finished(Vec<MortarDialogueFinished>);
+
+fn record(mut finished: ResMut<Finished>, mut messages: MessageReader<MortarDialogueFinished>) {
+    finished.0.extend(messages.read().cloned());
+}
+
+fn harbor_asset() -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [
+            {
+                "name": "Gate",
+                "content": [
+                    { "type": "text", "value": "Who goes there?" },
+                    { "type": "choice", "options": [
+                        { "text": "Leave", "action": "return" },
+                        { "text": "Wait", "action": "break" },
+                        { "text": "Enter", "next": "Hall" }
+                    ] },
+                    { "type": "text", "value": "You wait." }
+                ]
+            },
+            {
+                "name": "Dock",
+                "content": [
+                    { "type": "text", "value": "The ship is leaving." },
+                    { "type": "choice", "options": [{ "text": "Stay", "action": "break" }] }
+                ]
+            },
+            {
+                "name": "Pier",
+                "content": [
+                    { "type": "text", "value": "Boats bob." },
+                    { "type": "choice", "options": [{ "text": "Stay", "action": "break" }] }
+                ],
+                "next": "Hall"
+            },
+            { "name": "Hall", "content": [{ "type": "text", "value": "A hall." }] }
+        ],
+        "functions": []
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+fn run(app: &mut App) {
+    for _ in 0..3 {
+        app.update();
+    }
+}
+
+/// Starts `node` and confirms option `index` of its group.
+fn confirm_in(node: &str, index: usize) -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+    ))
+    .init_resource::<Finished>()
+    .add_systems(Last, record);
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(harbor_asset());
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+    app.world_mut().spawn((Text::new(""), MortarTextTarget));
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, node));
+    run(&mut app);
+    app.world_mut()
+        .write_message(MortarCommand::select_choice(index));
+    app.world_mut()
+        .write_message(MortarCommand::confirm_choice());
+    run(&mut app);
+    app
+}
+
+fn finished_nodes(app: &App) -> Vec<&str> {
+    app.world()
+        .resource::<Finished>()
+        .0
+        .iter()
+        .map(|finished| finished.node.as_str())
+        .collect()
+}
+
+fn current_text(app: &App) -> Option<(String, String)> {
+    let state = app
+        .world()
+        .resource::<MortarRuntime>()
+        .primary_dialogue_state()?;
+    Some((state.current_node.clone(), state.current_text()?.to_owned()))
+}
+
+#[test]
+fn test_return_finishes_the_dialogue() {
+    let app = confirm_in("Gate", 0);
+    assert_eq!(finished_nodes(&app), ["Gate"]);
+    assert_eq!(current_text(&app), None);
+}
+
+#[test]
+fn test_break_with_trailing_text_shows_it() {
+    let app = confirm_in("Gate", 1);
+    assert!(finished_nodes(&app).is_empty());
+    assert_eq!(
+        current_text(&app),
+        Some(("Gate".to_owned(), "You wait.".to_owned()))
+    );
+}
+
+#[test]
+fn test_break_without_trailing_text_leaves_the_node() {
+    let app = confirm_in("Dock", 0);
+    assert_eq!(finished_nodes(&app), ["Dock"]);
+    assert_eq!(current_text(&app), None);
+
+    let app = confirm_in("Pier", 0);
+    assert!(finished_nodes(&app).is_empty());
+    assert_eq!(
+        current_text(&app),
+        Some(("Hall".to_owned(), "A hall.".to_owned()))
+    );
+}
+
+#[test]
+fn test_next_jumps_to_its_node() {
+    let app = confirm_in("Gate", 2);
+    assert!(finished_nodes(&app).is_empty());
+    assert_eq!(
+        current_text(&app),
+        Some(("Hall".to_owned(), "A hall.".to_owned()))
+    );
+}
@@ -0,0 +1,127 @@
+//! Covers choice group tokens: confirming an option with a nested group moves to a new token, a
+//! selection stamped with the parent group's token and queued behind the confirm is dropped instead
+//! of landing on the child group, and tokenless selections keep applying to the current group.
+//!
+//! 覆盖选项组令牌：确认带有嵌套组的选项后会切换到新令牌；带有父级组令牌、排在确认之后的选择会被
+//! 丢弃，而不会落到子级组上；不带令牌的选择仍作用于当前组。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use mortar_compiler::Deserializer;
+
+const PATH: &str = "tavern.mortar";
+
+fn tavern_asset() -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [
+            {
+                "name": "Start",
+                "content": [
+                    { "type": "text", "value": "What will it be?" },
+                    { "type": "choice", "options": [
+                        { "text": "Order a drink", "choice": [
+                            { "text": "Ale", "next": "Ale" },
+                            { "text": "Cider", "next": "Cider" }
+                        ] },
+                        { "text": "Leave", "next": "return" }
+                    ] }
+                ]
+            },
+            { "name": "Ale", "content": [{ "type": "text", "value": "One ale." }] },
+            { "name": "Cider", "content": [{ "type": "text", "value": "One cider." }] }
+        ],
+        "functions": []
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+fn setup_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+    ));
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(tavern_asset());
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+    app.world_mut().spawn((Text::new(""), MortarTextTarget));
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, "Start"));
+    for _ in 0..3 {
+        app.update();
+    }
+    app
+}
+
+fn primary_state(app: &App) -> &DialogueState {
+    app.world()
+        .resource::<MortarRuntime>()
+        .primary_dialogue_state()
+        .expect("dialogue should still be active")
+}
+
+fn presented_token(app: &App) -> u64 {
+    app.world().resource::<MortarChoicesPresented>().group_token
+}
+
+#[test]
+fn test_stale_parent_selection_is_dropped_in_child_group() {
+    let mut app = setup_app();
+    let parent = presented_token(&app);
+    assert_eq!(parent, primary_state(&app).group_token());
+
+    // The player confirms "Order a drink" and keeps mashing: a second selection for the parent
+    // group's "Leave" is queued behind the confirm, in the same frame.
+    //
+    // 玩家确认“点一杯酒”后继续连按：针对父级组“离开”的第二次选择在同一帧中排在确认之后。
+    app.world_mut()
+        .write_message(MortarCommand::select_choice_in(parent, 0));
+    app.world_mut()
+        .write_message(MortarCommand::confirm_choice_in(parent));
+    app.world_mut()
+        .write_message(MortarCommand::select_choice_in(parent, 1));
+    app.world_mut()
+        .write_message(MortarCommand::confirm_choice_in(parent));
+    app.update();
+
+    let state = primary_state(&app);
+    assert_eq!(state.choice_stack, vec![0]);
+    assert_ne!(state.group_token(), parent);
+    assert_eq!(
+        state.selected_choice, None,
+        "stale selection must not apply"
+    );
+    let child = state.group_token();
+    app.update();
+    assert_eq!(presented_token(&app), child);
+}
+
+#[test]
+fn test_tokenless_selection_applies_to_current_group() {
+    let mut app = setup_app();
+    app.world_mut()
+        .write_message(MortarCommand::select_choice(0));
+    app.world_mut()
+        .write_message(MortarCommand::confirm_choice());
+    app.world_mut()
+        .write_message(MortarCommand::select_choice(1));
+    app.update();
+    assert_eq!(primary_state(&app).selected_choice, Some(1));
+
+    app.update();
+    let child = presented_token(&app);
+    app.world_mut()
+        .write_message(MortarCommand::confirm_choice_in(child));
+    for _ in 0..3 {
+        app.update();
+    }
+    assert_eq!(primary_state(&app).current_node, "Cider");
+}
@@ -0,0 +1,101 @@
+//! Covers choice options without text: an icon-only option parses next to a text option instead
+//! of failing the group, both views carry their `id`, `icon` and extra keys, and only an option
+//! with neither text, id nor icon is warned about.
+//!
+//! 覆盖没有文本的选项：仅有图标的选项可以与文本选项一同解析，而不会导致整个选项组失败；两个视图都带有
+//! 各自的 `id`、`icon` 与额外键；只有既无文本、也无 id 与图标的选项才会产生警告。
+
+use super::log_target_tests::capture;
+use crate::*;
+use bevy::asset::AssetPlugin;
+use bevy::log::Level;
+use mortar_compiler::Deserializer;
+
+const PATH: &str = "duel.mortar";
+
+fn duel_json(extra_option: Option<serde_json::Value>) -> serde_json::Value {
+    let mut options = vec![
+        serde_json::json!({ "text": "Talk it out", "next": "End" }),
+        serde_json::json!({
+            "id": "sword", "icon": "icons/sword.png", "tooltip": "Draw your blade", "next": "End"
+        }),
+    ];
+    options.extend(extra_option);
+    serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [
+            {
+                "name": "Duel",
+                "content": [
+                    { "type": "text", "value": "Well?" },
+                    { "type": "choice", "options": options }
+                ]
+            },
+            { "name": "End", "content": [{ "type": "text", "value": "Done." }] }
+        ],
+        "functions": []
+    })
+}
+
+fn warnings(json: &serde_json::Value) -> Vec<String> {
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    let node = data.nodes[0].clone();
+    capture(|| {
+        let state = DialogueState::new(PATH.to_owned(), "Duel".to_owned(), node);
+        assert!(state.get_choices().is_some(), "options should parse");
+    })
+    .into_iter()
+    .filter(|(_, level, _)| *level == Level::WARN)
+    .map(|(_, _, message)| message)
+    .collect()
+}
+
+#[test]
+fn test_icon_only_option_is_presented_with_metadata() {
+    let json = duel_json(None);
+    assert!(warnings(&json).is_empty());
+
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+    ));
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(MortarAsset::new(data));
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+    app.world_mut().spawn((Text::new(""), MortarTextTarget));
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, "Duel"));
+    for _ in 0..4 {
+        app.update();
+    }
+
+    let views = &app.world().resource::<MortarChoicesPresented>().views;
+    assert_eq!(views.len(), 2);
+    assert_eq!(views[0].text, "Talk it out");
+    assert_eq!(
+        (views[0].id.as_deref(), views[0].icon.as_deref()),
+        (None, None)
+    );
+    assert!(views[0].metadata.is_empty());
+    assert_eq!(views[1].text, "");
+    assert_eq!(views[1].id.as_deref(), Some("sword"));
+    assert_eq!(views[1].icon.as_deref(), Some("icons/sword.png"));
+    assert_eq!(views[1].metadata.len(), 1);
+    assert_eq!(views[1].metadata["tooltip"], "Draw your blade");
+}
+
+#[test]
+fn test_bare_option_is_warned_about() {
+    let json = duel_json(Some(serde_json::json!({ "next": "End" })));
+    let warnings = warnings(&json);
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].contains("no text, id or icon"));
+}
@@ -0,0 +1,174 @@
+//! Covers options that leave or join a presented group: a session-scoped `remove_after_pick`
+//! option stays gone when the hub is entered again while a visit-scoped one comes back, a
+//! `visible_if` option appears when its flag flips on screen, and a group with nothing left
+//! continues to the text after it.
+//!
+//! 覆盖离开或加入已呈现选项组的选项：会话范围的 `remove_after_pick` 选项在再次进入中心节点时
+//! 仍然消失，而访问范围的选项会重新出现；`visible_if` 选项在其标志于显示期间翻转时出现；
+//! 选项全部消失的选项组会继续到其后的文本。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use mortar_compiler::Deserializer;
+
+const PATH: &str = "hub.mortar";
+
+fn hub_asset() -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [
+            {
+                "name": "Hub",
+                "content": [
+                    { "type": "text", "value": "Ask away." },
+                    { "type": "choice", "options": [
+                        {
+                            "text": "Ask about the murder",
+                            "remove_after_pick": true,
+                            "scope": "session",
+                            "next": "Murder"
+                        },
+                        { "text": "Ask about the weather", "remove_after_pick": true, "next": "Weather" },
+                        {
+                            "text": "Ask about the alibi",
+                            "visible_if": { "type": "knows_alibi", "args": [] },
+                            "next": "Hub"
+                        },
+                        { "text": "Leave" }
+                    ] }
+                ]
+            },
+            { "name": "Murder", "content": [{ "type": "text", "value": "Dreadful." }], "next": "Hub" },
+            { "name": "Weather", "content": [{ "type": "text", "value": "Rain." }], "next": "Hub" },
+            {
+                "name": "Once",
+                "content": [
+                    { "type": "text", "value": "One question." },
+                    { "type": "choice", "options": [
+                        { "text": "Why?", "remove_after_pick": true, "scope": "session", "next": "Once" }
+                    ] },
+                    { "type": "text", "value": "Nothing left to ask." }
+                ]
+            }
+        ],
+        "functions": [],
+        "variables": [{ "name": "knows_alibi", "type": "Boolean", "value": false }]
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+fn setup_app(node: &str) -> (App, Entity) {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+    ));
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(hub_asset());
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+    let target = app
+        .world_mut()
+        .spawn((Text::new(""), MortarTextTarget))
+        .id();
+    send(&mut app, MortarCommand::start_node(PATH, node));
+    send(&mut app, MortarCommand::next_text());
+    (app, target)
+}
+
+fn send(app: &mut App, event: MortarCommand) {
+    app.world_mut().write_message(event);
+    for _ in 0..3 {
+        app.update();
+    }
+}
+
+fn pick(app: &mut App, index: usize) {
+    send(app, MortarCommand::select_choice(index));
+    send(app, MortarCommand::confirm_choice());
+}
+
+fn presented(app: &App) -> Vec<(usize, String)> {
+    app.world()
+        .resource::<MortarChoicesPresented>()
+        .views
+        .iter()
+        .map(|view| (view.index, view.text.clone()))
+        .collect()
+}
+
+fn indices(app: &App) -> Vec<usize> {
+    presented(app).into_iter().map(|(index, _)| index).collect()
+}
+
+#[test]
+fn test_removed_option_stays_gone_when_hub_is_entered_again() {
+    let (mut app, _) = setup_app("Hub");
+    assert_eq!(indices(&app), vec![0, 1, 3]);
+
+    pick(&mut app, 0);
+    send(&mut app, MortarCommand::next_text());
+    send(&mut app, MortarCommand::next_text());
+    assert_eq!(indices(&app), vec![1, 3]);
+    assert_eq!(presented(&app)[0].1, "Ask about the weather");
+
+    // A removal scoped to the visit ends with it: the weather question is back after the loop.
+    //
+    // 访问范围的移除随访问结束：循环回来后天气问题重新出现。
+    pick(&mut app, 1);
+    send(&mut app, MortarCommand::next_text());
+    send(&mut app, MortarCommand::next_text());
+    assert_eq!(indices(&app), vec![1, 3]);
+
+    send(&mut app, MortarCommand::select_choice(0));
+    assert_eq!(
+        app.world()
+            .resource::<MortarRuntime>()
+            .primary_dialogue_state()
+            .unwrap()
+            .selected_choice,
+        None
+    );
+
+    app.world_mut()
+        .resource_mut::<MortarRuntime>()
+        .clear_removed_choices();
+    send(&mut app, MortarCommand::stop_dialogue());
+    send(&mut app, MortarCommand::start_node(PATH, "Hub"));
+    send(&mut app, MortarCommand::next_text());
+    assert_eq!(indices(&app), vec![0, 1, 3]);
+}
+
+#[test]
+fn test_visible_if_option_appears_when_flag_flips() {
+    let (mut app, _) = setup_app("Hub");
+    assert_eq!(indices(&app), vec![0, 1, 3]);
+
+    app.world_mut()
+        .resource_mut::<MortarDialogueVariables>()
+        .state
+        .as_mut()
+        .expect("variables initialized by the text system")
+        .set("knows_alibi", MortarVariableValue::Boolean(true));
+    app.update();
+    assert_eq!(indices(&app), vec![0, 1, 2, 3]);
+    assert_eq!(presented(&app)[2].1, "Ask about the alibi");
+}
+
+#[test]
+fn test_group_with_nothing_left_continues_past_it() {
+    let (mut app, target) = setup_app("Once");
+    assert_eq!(indices(&app), vec![0]);
+
+    pick(&mut app, 0);
+    send(&mut app, MortarCommand::next_text());
+    let body = &app.world().get::<MortarDialogueText>(target).unwrap().body;
+    assert_eq!(body, "Nothing left to ask.");
+    assert!(presented(&app).is_empty());
+}
@@ -0,0 +1,176 @@
+//! Pages a twelve-option shop through `ChoicePage` and checks that the presented subset, the
+//! navigation entries and the page counters follow, while `SelectChoice` keeps addressing the
+//! declared index. Pages with only disabled options are skipped.
+//!
+//! 通过 `ChoicePage` 翻阅一个有十二个选项的商店，检查呈现的子集、导航条目与页码计数随之变化，
+//! 同时 `SelectChoice` 始终使用声明索引。只包含禁用选项的页面会被跳过。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use mortar_compiler::Deserializer;
+
+const PATH: &str = "shop.mortar";
+
+/// Twelve options; the 11th leads to `Eleven`. Options 5-9 need 100 gold when `locked`.
+fn shop_asset(locked: bool, page_size: Option<usize>) -> MortarAsset {
+    let options: Vec<_> = (0..12)
+        .map(|index| {
+            let mut option = serde_json::json!({
+                "text": format!("Item {}", index + 1),
+                "next": if index == 10 { "Eleven" } else { "return" }
+            });
+            if locked && (5..10).contains(&index) {
+                option["condition"] = serde_json::json!({ "type": "gold", "args": [">=", "100"] });
+            }
+            option
+        })
+        .collect();
+    let mut choice = serde_json::json!({ "type": "choice", "options": options });
+    if let Some(page_size) = page_size {
+        choice["page_size"] = page_size.into();
+    }
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [
+            { "name": "Start", "content": [{ "type": "text", "value": "Buy?" }, choice] },
+            { "name": "Eleven", "content": [{ "type": "text", "value": "Sold." }] }
+        ],
+        "functions": [],
+        "variables": [{ "name": "gold", "type": "Number", "value": 20 }]
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+fn setup_app(asset: MortarAsset, pagination: Option<ChoicePagination>) -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+    ));
+    app.world_mut()
+        .resource_mut::<MortarRuntime>()
+        .choice_pagination = pagination;
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(asset);
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+    app.world_mut().spawn((Text::new(""), MortarTextTarget));
+    send(&mut app, MortarCommand::start_node(PATH, "Start"));
+    app
+}
+
+fn send(app: &mut App, event: MortarCommand) {
+    app.world_mut().write_message(event);
+    for _ in 0..3 {
+        app.update();
+    }
+}
+
+/// Shown page, page count and the presented entries as (kind, declared index).
+fn presented(app: &App) -> (usize, usize, Vec<(MortarChoiceViewKind, usize)>) {
+    let presented = app.world().resource::<MortarChoicesPresented>();
+    let views = presented
+        .views
+        .iter()
+        .map(|view| (view.kind, view.index))
+        .collect();
+    (presented.page, presented.page_count, views)
+}
+
+fn choices(range: std::ops::Range<usize>) -> Vec<(MortarChoiceViewKind, usize)> {
+    range
+        .map(|index| (MortarChoiceViewKind::Choice, index))
+        .collect()
+}
+
+#[test]
+fn test_select_and_confirm_on_third_page() {
+    let pagination = ChoicePagination { page_size: 5 };
+    let mut app = setup_app(shop_asset(false, None), Some(pagination));
+    let mut first = choices(0..5);
+    first.push((MortarChoiceViewKind::NextPage, 5));
+    assert_eq!(presented(&app), (0, 3, first));
+
+    send(&mut app, MortarCommand::choice_page(1));
+    send(&mut app, MortarCommand::choice_page(1));
+    let mut third = vec![(MortarChoiceViewKind::PrevPage, 5)];
+    third.extend(choices(10..12));
+    assert_eq!(presented(&app), (2, 3, third));
+
+    send(
+        &mut app,
+        MortarCommand::SelectChoice {
+            index: 10,
+            target: None,
+            group: None,
+            source: None,
+        },
+    );
+    let runtime = app.world().resource::<MortarRuntime>();
+    assert_eq!(
+        runtime.primary_dialogue_state().unwrap().selected_choice,
+        Some(10)
+    );
+    send(
+        &mut app,
+        MortarCommand::ConfirmChoice {
+            target: None,
+            group: None,
+        },
+    );
+    let state = app
+        .world()
+        .resource::<MortarRuntime>()
+        .primary_dialogue_state()
+        .cloned()
+        .unwrap();
+    assert_eq!(state.current_node, "Eleven");
+}
+
+#[test]
+fn test_pages_of_disabled_options_are_skipped() {
+    let pagination = ChoicePagination { page_size: 5 };
+    let mut app = setup_app(shop_asset(true, None), Some(pagination));
+    let mut first = choices(0..5);
+    first.push((MortarChoiceViewKind::NextPage, 10));
+    assert_eq!(presented(&app), (0, 3, first));
+
+    send(&mut app, MortarCommand::choice_page(1));
+    assert_eq!(presented(&app).0, 2);
+    send(&mut app, MortarCommand::choice_page(-1));
+    assert_eq!(presented(&app).0, 0);
+    send(&mut app, MortarCommand::choice_page(-1));
+    assert_eq!(presented(&app).0, 0);
+}
+
+#[test]
+fn test_group_page_size_and_selection_restore_page() {
+    let mut app = setup_app(shop_asset(false, Some(4)), None);
+    assert_eq!(presented(&app).1, 3);
+
+    send(
+        &mut app,
+        MortarCommand::SelectChoice {
+            index: 9,
+            target: None,
+            group: None,
+            source: None,
+        },
+    );
+    let mut third = vec![(MortarChoiceViewKind::PrevPage, 4)];
+    third.extend(choices(8..12));
+    assert_eq!(presented(&app), (2, 3, third));
+}
+
+#[test]
+fn test_unpaginated_choices_show_every_option() {
+    let mut app = setup_app(shop_asset(false, None), None);
+    send(&mut app, MortarCommand::choice_page(1));
+    assert_eq!(presented(&app), (0, 1, choices(0..12)));
+}
@@ -0,0 +1,161 @@
+//! Exercises re-evaluation of presented choices. A guard offers a bribe that needs gold;
+//! spending the gold while the choice is on screen must disable the option, drop the selection
+//! and make the runtime refuse to confirm it.
+//!
+//! 验证已呈现选项的重新求值。守卫提供一个需要金币的贿赂选项；在选项显示期间花掉金币后，
+//! 该选项必须被禁用、选中状态被清除，并且运行时拒绝确认它。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use mortar_compiler::Deserializer;
+
+const PATH: &str = "guard.mortar";
+
+fn guard_asset() -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [
+            {
+                "name": "Start",
+                "content": [
+                    { "type": "text", "value": "Halt!" },
+                    { "type": "choice", "options": [
+                        {
+                            "text": "Bribe the guard (10g)",
+                            "condition": { "type": "gold", "args": [">=", "10"] },
+                            "next": "Bribed"
+                        },
+                        { "text": "Leave", "next": "return" }
+                    ] }
+                ]
+            },
+            { "name": "Bribed", "content": [{ "type": "text", "value": "Go on." }] }
+        ],
+        "functions": [],
+        "variables": [{ "name": "gold", "type": "Number", "value": 20 }]
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+fn setup_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+    ));
+
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(guard_asset());
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+    app.world_mut().spawn((Text::new(""), MortarTextTarget));
+
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, "Start"));
+    for _ in 0..3 {
+        app.update();
+    }
+    app
+}
+
+fn set_gold(app: &mut App, gold: f64) {
+    app.world_mut()
+        .resource_mut::<MortarDialogueVariables>()
+        .state
+        .as_mut()
+        .expect("variables initialized by the text system")
+        .set("gold", MortarVariableValue::Number(gold));
+    app.update();
+}
+
+fn enabled_flags(app: &App) -> Vec<bool> {
+    app.world()
+        .resource::<MortarChoicesPresented>()
+        .views
+        .iter()
+        .map(|view| view.enabled)
+        .collect()
+}
+
+fn primary_state(app: &App) -> &DialogueState {
+    app.world()
+        .resource::<MortarRuntime>()
+        .primary_dialogue_state()
+        .expect("dialogue should still be active")
+}
+
+#[test]
+fn test_choices_presented_with_initial_enablement() {
+    let app = setup_app();
+    assert_eq!(enabled_flags(&app), vec![true, true]);
+    assert_eq!(
+        app.world().resource::<MortarChoicesPresented>().views[0].text,
+        "Bribe the guard (10g)"
+    );
+}
+
+#[test]
+fn test_disabling_selected_choice_deselects_and_rejects_confirm() {
+    let mut app = setup_app();
+
+    app.world_mut().write_message(MortarCommand::SelectChoice {
+        index: 0,
+        target: None,
+        group: None,
+        source: None,
+    });
+    app.update();
+    assert_eq!(primary_state(&app).selected_choice, Some(0));
+
+    set_gold(&mut app, 5.0);
+    assert_eq!(enabled_flags(&app), vec![false, true]);
+    assert_eq!(primary_state(&app).selected_choice, None);
+    let deselected: Vec<_> = app
+        .world()
+        .resource::<Messages<MortarChoiceDeselected>>()
+        .iter_current_update_messages()
+        .cloned()
+        .collect();
+    assert_eq!(
+        deselected,
+        vec![MortarChoiceDeselected {
+            entity: None,
+            index: 0
+        }]
+    );
+
+    // Selecting the disabled option is refused.
+    app.world_mut().write_message(MortarCommand::SelectChoice {
+        index: 0,
+        target: None,
+        group: None,
+        source: None,
+    });
+    app.update();
+    assert_eq!(primary_state(&app).selected_choice, None);
+
+    // A stale selection (e.g. from a UI that missed the update) cannot be confirmed.
+    app.world_mut()
+        .resource_mut::<MortarRuntime>()
+        .primary_dialogue_state_mut()
+        .unwrap()
+        .selected_choice = Some(0);
+    app.world_mut().write_message(MortarCommand::ConfirmChoice {
+        target: None,
+        group: None,
+    });
+    for _ in 0..3 {
+        app.update();
+    }
+    assert_eq!(primary_state(&app).current_node, "Start");
+
+    // Earning the gold back re-enables the option.
+    set_gold(&mut app, 15.0);
+    assert_eq!(enabled_flags(&app), vec![true, true]);
+}
@@ -0,0 +1,155 @@
+//! Covers `ui` hints on choices: the group's and each option's `ui` objects reach
+//! `MortarChoicesPresented` verbatim with their typed accessors, and `order` hints sort the
+//! options before pagination pages through them, so pages, navigation entries and selection all
+//! follow the presented order while `SelectChoice` keeps taking declared indices.
+//!
+//! 覆盖选项上的 `ui` 提示：选项组与每个选项的 `ui` 对象原样传递到 `MortarChoicesPresented`，并提供
+//! 类型化访问；`order` 提示会在分页之前对选项排序，因此页面、导航条目与选中都遵循呈现顺序，而
+//! `SelectChoice` 仍使用声明索引。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use mortar_compiler::Deserializer;
+
+const PATH: &str = "tavern.mortar";
+
+fn tavern_asset(group: serde_json::Value) -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [{
+            "name": "Start",
+            "content": [{ "type": "text", "value": "What will it be?" }, group]
+        }],
+        "functions": []
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+fn setup_app(group: serde_json::Value) -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+    ));
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(tavern_asset(group));
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, "Start"));
+    for _ in 0..3 {
+        app.update();
+    }
+    app
+}
+
+fn send(app: &mut App, event: MortarCommand) {
+    app.world_mut().write_message(event);
+    for _ in 0..2 {
+        app.update();
+    }
+}
+
+fn presented(app: &App) -> &MortarChoicesPresented {
+    app.world().resource::<MortarChoicesPresented>()
+}
+
+/// `(kind, declared index, text)` of every presented entry.
+fn entries(app: &App) -> Vec<(MortarChoiceViewKind, usize, String)> {
+    presented(app)
+        .views
+        .iter()
+        .map(|view| (view.kind, view.index, view.text.clone()))
+        .collect()
+}
+
+fn option(text: &str, order: i32) -> serde_json::Value {
+    serde_json::json!({ "text": text, "ui": { "order": order } })
+}
+
+#[test]
+fn test_hints_survive_from_json_to_the_presented_group() {
+    let app = setup_app(serde_json::json!({
+        "type": "choice",
+        "ui": { "columns": 2, "theme": "tavern" },
+        "options": [
+            { "text": "Rumors", "tooltip": "Ask around",
+              "ui": { "heading": "Ask about…", "badge": { "color": "gold" } } },
+            { "text": "Leave" }
+        ]
+    }));
+    let presented = presented(&app);
+    assert_eq!(
+        presented.group_ui,
+        serde_json::json!({ "columns": 2, "theme": "tavern" })
+            .as_object()
+            .cloned()
+            .unwrap()
+    );
+    assert_eq!(presented.group_ui_hints().columns(), Some(2));
+    assert_eq!(presented.group_ui_hints().heading(), None);
+
+    let rumors = &presented.views[0];
+    assert_eq!(
+        serde_json::Value::Object(rumors.ui.clone()),
+        serde_json::json!({ "heading": "Ask about…", "badge": { "color": "gold" } })
+    );
+    assert_eq!(rumors.ui_hints().heading().as_deref(), Some("Ask about…"));
+    assert_eq!(rumors.ui_hints().order(), None);
+    assert!(rumors.metadata.contains_key("tooltip"));
+    assert!(!rumors.metadata.contains_key("ui"));
+    assert!(presented.views[1].ui.is_empty());
+}
+
+#[test]
+fn test_order_sorts_stably_before_pagination() {
+    let mut app = setup_app(serde_json::json!({
+        "type": "choice",
+        "page_size": 2,
+        "options": [
+            option("Leave", 9),
+            option("Rumors", 1),
+            option("Drink", 5),
+            option("Owner", 1),
+            option("Roads", 0)
+        ]
+    }));
+    use MortarChoiceViewKind::{Choice, NextPage, PrevPage};
+    let entry = |kind, index: usize, text: &str| (kind, index, text.to_owned());
+    assert_eq!(
+        entries(&app),
+        [
+            entry(Choice, 4, "Roads"),
+            entry(Choice, 1, "Rumors"),
+            entry(NextPage, 3, ""),
+        ]
+    );
+
+    send(&mut app, MortarCommand::choice_page(1));
+    assert_eq!(presented(&app).page, 1);
+    assert_eq!(
+        entries(&app),
+        [
+            entry(PrevPage, 4, ""),
+            entry(Choice, 3, "Owner"),
+            entry(Choice, 2, "Drink"),
+            entry(NextPage, 0, ""),
+        ]
+    );
+
+    // Selecting a declared index shows the page it is presented on.
+    //
+    // 选中某个声明索引时，会显示它所呈现的那一页。
+    send(&mut app, MortarCommand::select_choice(0));
+    assert_eq!(presented(&app).page, 2);
+    assert_eq!(
+        entries(&app),
+        [entry(PrevPage, 3, ""), entry(Choice, 0, "Leave")]
+    );
+}
@@ -0,0 +1,202 @@
+//! Covers argument coercion: the same mismatched argument is converted for a lenient function and
+//! refused for a strict one, policies come from the registry default, per-function overrides or
+//! `#[mortar(strict)]`, and a refused call from a dialogue line renders nothing and is reported as
+//! a [`MortarFunctionError`].
+//!
+//! 覆盖参数转换：同一个类型不符的参数对宽松函数会被转换，对严格函数会被拒绝；策略来自注册表默认值、
+//! 按函数覆盖或 `#[mortar(strict)]`；对话行中被拒绝的调用不渲染任何内容，并作为
+//! [`MortarFunctionError`] 报告。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use mortar_compiler::Deserializer;
+
+const PATH: &str = "coercion.mortar";
+
+#[derive(MortarFunctions)]
+struct CoercionFunctions;
+
+#[mortar_functions]
+impl CoercionFunctions {
+    fn double(amount: MortarNumber) -> f64 {
+        amount.as_f64() * 2.0
+    }
+
+    #[mortar(strict)]
+    fn double_strict(amount: MortarNumber) -> f64 {
+        amount.as_f64() * 2.0
+    }
+
+    fn shout(word: MortarString, loud: MortarBoolean) -> String {
+        if loud.as_bool() {
+            word.as_str().to_uppercase()
+        } else {
+            word.as_str().to_owned()
+        }
+    }
+}
+
+fn registry() -> MortarFunctionRegistry {
+    let mut registry = MortarFunctionRegistry::new();
+    CoercionFunctions::register(&mut registry);
+    registry
+}
+
+fn number(value: Option<MortarValue>) -> Option<f64> {
+    value
+        .and_then(|value| value.as_number())
+        .map(|n| n.as_f64())
+}
+
+#[test]
+fn test_same_bad_argument_lenient_and_strict() {
+    let registry = registry();
+    let bad = [MortarValue::from("3")];
+    assert_eq!(registry.policy("double"), CoercionPolicy::Lenient);
+    assert_eq!(registry.policy("double_strict"), CoercionPolicy::Strict);
+
+    assert_eq!(number(registry.call("double", &bad)), Some(6.0));
+    assert_eq!(
+        registry.try_call("double_strict", &bad).unwrap_err(),
+        MortarCallError::Coercion {
+            function: "double_strict".to_owned(),
+            index: 0,
+            expected: MortarParamKind::Number,
+            found: "string",
+        }
+    );
+    assert!(matches!(
+        registry.call("double_strict", &bad),
+        Some(MortarValue::Void)
+    ));
+    let errors = registry.take_errors();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].function, "double_strict");
+
+    // Well-typed arguments pass under either policy.
+    //
+    // 类型正确的参数在两种策略下都能通过。
+    let good = [MortarValue::from(4.0)];
+    assert_eq!(number(registry.call("double_strict", &good)), Some(8.0));
+}
+
+#[test]
+fn test_lenient_conversions_and_defaults() {
+    let registry = registry();
+    let shouted = registry.call("shout", &[MortarValue::from(7.0), MortarValue::from(1.0)]);
+    assert_eq!(shouted.unwrap().to_display_string(), "7");
+    let shouted = registry.call(
+        "shout",
+        &[MortarValue::from("hey"), MortarValue::from("true")],
+    );
+    assert_eq!(shouted.unwrap().to_display_string(), "HEY");
+    // No conversion for 2 or a missing argument: the parameter's default is used.
+    //
+    // 2 与缺失的参数没有转换：使用该参数的默认值。
+    let shouted = registry.call("shout", &[MortarValue::from("hey"), MortarValue::from(2.0)]);
+    assert_eq!(shouted.unwrap().to_display_string(), "hey");
+    assert_eq!(number(registry.call("double", &[])), Some(0.0));
+    assert!(registry.take_errors().is_empty());
+}
+
+#[test]
+fn test_registry_default_and_overrides() {
+    let mut registry = registry();
+    registry.set_default_policy(CoercionPolicy::Strict);
+    registry.set_policy("double_strict", CoercionPolicy::Lenient);
+    let bad = [MortarValue::from("3")];
+    assert!(matches!(
+        registry.call("double", &bad),
+        Some(MortarValue::Void)
+    ));
+    assert_eq!(number(registry.call("double_strict", &bad)), Some(6.0));
+
+    // Functions registered without parameter kinds are never coerced.
+    //
+    // 未声明参数类型的函数不会进行转换。
+    registry.register("raw", |args| {
+        args.first().cloned().unwrap_or(MortarValue::Void)
+    });
+    let raw = registry.call("raw", &bad).unwrap();
+    assert_eq!(raw.as_string().map(MortarString::as_str), Some("3"));
+}
+
+fn coercion_asset() -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [{
+            "name": "Start",
+            "content": [{
+                "type": "text",
+                "value": "Gold: {double(\"3\")} / {double_strict(\"3\")}",
+                "interpolated_parts": [
+                    { "type": "text", "content": "Gold: " },
+                    {
+                        "type": "expression",
+                        "content": "{double(\"3\")}",
+                        "function_name": "double",
+                        "args": ["\"3\""]
+                    },
+                    { "type": "text", "content": " / " },
+                    {
+                        "type": "expression",
+                        "content": "{double_strict(\"3\")}",
+                        "function_name": "double_strict",
+                        "args": ["\"3\""]
+                    }
+                ]
+            }]
+        }],
+        "functions": []
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+#[derive(Resource, Default)]
+struct FunctionErrors(Vec<MortarFunctionError>);
+
+fn record_errors(
+    mut events: MessageReader<MortarFunctionError>,
+    mut errors: ResMut<FunctionErrors>,
+) {
+    errors.0.extend(events.read().cloned());
+}
+
+#[test]
+fn test_refused_call_in_line_is_reported() {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+    ))
+    .init_resource::<FunctionErrors>()
+    .add_systems(Last, record_errors);
+    CoercionFunctions::register(&mut app.world_mut().resource_mut::<MortarRuntime>().functions);
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(coercion_asset());
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+    let target = app
+        .world_mut()
+        .spawn((Text::new(""), MortarTextTarget))
+        .id();
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, "Start"));
+    for _ in 0..3 {
+        app.update();
+    }
+
+    let text = app.world().get::<MortarDialogueText>(target).unwrap();
+    assert_eq!(text.body, "Gold: 6 / ");
+    let errors = &app.world().resource::<FunctionErrors>().0;
+    assert!(!errors.is_empty());
+    assert!(errors.iter().all(|error| error.function == "double_strict"));
+    assert_eq!(errors[0].context.origin, MortarCallOrigin::Interpolation);
+    assert_eq!(errors[0].context.node.as_deref(), Some("Start"));
+}
@@ -0,0 +1,113 @@
+//! Covers the per-frame memoization of condition function calls: a counting `has_backpack`
+//! checked by a line's condition and by a choice's condition in the same frame runs once with
+//! `MortarConditionCache` and twice with it disabled, and a function marked volatile runs for
+//! every check.
+//!
+//! 覆盖条件函数调用的每帧缓存：在同一帧中被某行条件和某选项条件检查的计数函数 `has_backpack`，
+//! 启用 `MortarConditionCache` 时只执行一次，禁用时执行两次；标记为易变的函数每次检查都会执行。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use bevy::time::TimeUpdateStrategy;
+use mortar_compiler::Deserializer;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+const PATH: &str = "camp.mortar";
+
+fn camp_asset() -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [{
+            "name": "Camp",
+            "content": [
+                {
+                    "type": "text",
+                    "value": "Your pack is ready.",
+                    "condition": {
+                        "type": "func_call",
+                        "operand": { "type": "identifier", "value": "has_backpack" }
+                    }
+                },
+                { "type": "choice", "options": [
+                    {
+                        "text": "Set out",
+                        "condition": { "type": "has_backpack", "args": [] }
+                    },
+                    { "text": "Rest" }
+                ] }
+            ]
+        }],
+        "functions": []
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+/// Calls of `has_backpack` in the frame after another function is bound while the choices are
+/// shown. Choices calling functions are re-checked every 0.25s, and frames last 0.3s, so that
+/// frame checks the choice; the new registry generation re-renders the first node's line, so it
+/// checks the line's condition too.
+fn calls_in_shared_frame(cache_enabled: bool, volatile: bool) -> usize {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+    ))
+    .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+        300,
+    )));
+    app.world_mut()
+        .resource_mut::<MortarConditionCache>()
+        .set_enabled(cache_enabled);
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counter = calls.clone();
+    {
+        let mut runtime = app.world_mut().resource_mut::<MortarRuntime>();
+        runtime.functions.register("has_backpack", move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            MortarValue::Boolean(MortarBoolean(true))
+        });
+        runtime.functions.set_volatile("has_backpack", volatile);
+    }
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(camp_asset());
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+    app.world_mut().spawn((Text::new(""), MortarTextTarget));
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, "Camp"));
+    for _ in 0..3 {
+        app.update();
+    }
+    assert_eq!(
+        app.world().resource::<MortarChoicesPresented>().views.len(),
+        2,
+        "the choices are shown"
+    );
+
+    app.world_mut()
+        .resource_mut::<MortarRuntime>()
+        .functions
+        .register("light_fire", |_| MortarValue::Void);
+    let before = calls.load(Ordering::SeqCst);
+    app.update();
+    calls.load(Ordering::SeqCst) - before
+}
+
+#[test]
+fn test_line_and_choice_share_one_call() {
+    assert_eq!(calls_in_shared_frame(true, false), 1);
+    assert_eq!(calls_in_shared_frame(false, false), 2);
+}
+
+#[test]
+fn test_volatile_functions_bypass_the_cache() {
+    assert_eq!(calls_in_shared_frame(true, true), 2);
+}
@@ -0,0 +1,165 @@
+//! Covers `if` / `else` blocks over texts: a passing `if` shows its body and leaves the `else`
+//! out, a failing one resolves to its `else` run, and one without an `else` is left out. In a
+//! running dialogue the `else` run plays in full once entered, and stays out after its `if` ran
+//! even when the `if` body flipped the condition.
+//!
+//! 覆盖作用于文本的 `if` / `else` 块：成立的 `if` 显示其主体并略过 `else`，不成立的 `if` 解析到其
+//! `else` 段，没有 `else` 的 `if` 则被略过。在运行中的对话里，进入后的 `else` 段会完整播放；`if`
+//! 执行后即使其主体翻转了条件，`else` 段也会被略过。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use mortar_compiler::{Deserializer, Node};
+use serde_json::{Value, json};
+
+const PATH: &str = "door.mortar";
+
+fn has_key() -> Value {
+    json!({ "type": "identifier", "value": "has_key" })
+}
+
+fn lacks_key() -> Value {
+    json!({ "type": "unary", "operator": "!", "operand": has_key() })
+}
+
+fn door_content() -> Vec<Value> {
+    vec![
+        json!({ "type": "text", "value": "A door." }),
+        json!({
+            "type": "text",
+            "value": "You unlock it.",
+            "condition": has_key(),
+            "pre_statements": [
+                { "type": "assignment", "var_name": "has_key", "value": "false" }
+            ]
+        }),
+        json!({ "type": "text", "value": "It swings open.", "condition": has_key() }),
+        json!({ "type": "text", "value": "It's locked.", "condition": lacks_key() }),
+        json!({ "type": "text", "value": "Find the key.", "condition": lacks_key() }),
+        json!({ "type": "text", "value": "Bye." }),
+    ]
+}
+
+fn state_for(content: Vec<Value>) -> DialogueState {
+    let node = Node {
+        name: "Door".to_string(),
+        content,
+        branches: None,
+        variables: vec![],
+        next: None,
+    };
+    DialogueState::new(PATH.to_string(), "Door".to_string(), node)
+}
+
+fn variables(has_key: bool) -> MortarVariableState {
+    let mut variables = MortarVariableState::new();
+    variables.set("has_key", MortarVariableValue::Boolean(has_key));
+    variables
+}
+
+fn resolved(state: &DialogueState, index: usize, has_key: bool) -> Option<(usize, &str)> {
+    state
+        .resolve_text_at(index, &MortarFunctionRegistry::new(), &variables(has_key))
+        .map(|(index, text)| (index, text.value.as_str()))
+}
+
+#[test]
+fn test_passing_if_shows_its_body_and_leaves_else_out() {
+    let state = state_for(door_content());
+    assert_eq!(resolved(&state, 0, true), Some((0, "A door.")));
+    assert_eq!(resolved(&state, 1, true), Some((1, "You unlock it.")));
+    assert_eq!(resolved(&state, 2, true), Some((2, "It swings open.")));
+    assert_eq!(resolved(&state, 3, true), None);
+    assert_eq!(resolved(&state, 4, false), None);
+}
+
+#[test]
+fn test_failing_if_resolves_to_its_else() {
+    let state = state_for(door_content());
+    assert_eq!(resolved(&state, 1, false), Some((3, "It's locked.")));
+    assert_eq!(resolved(&state, 2, false), None);
+    let evaluated = state
+        .current_text_data_evaluated(&variables(false), &MortarFunctionRegistry::new())
+        .map(|text| text.value.as_str());
+    assert_eq!(evaluated, Some("A door."));
+}
+
+#[test]
+fn test_failing_if_without_else_is_left_out() {
+    let state = state_for(vec![
+        json!({ "type": "text", "value": "A glint.", "condition": has_key() }),
+        json!({ "type": "text", "value": "Bye." }),
+    ]);
+    assert_eq!(resolved(&state, 0, false), None);
+    assert_eq!(resolved(&state, 0, true), Some((0, "A glint.")));
+    assert!(
+        state
+            .current_text_data_evaluated(&variables(false), &MortarFunctionRegistry::new())
+            .is_none()
+    );
+}
+
+/// Plays the door node with `has_key` set and returns the lines it showed.
+fn play_door(has_key: bool) -> Vec<String> {
+    let json = json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [{ "name": "Door", "content": door_content() }],
+        "functions": [],
+        "variables": [{ "name": "has_key", "type": "Boolean", "value": has_key }]
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+    ));
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(MortarAsset::new(data));
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+    app.world_mut().spawn((Text::new(""), MortarTextTarget));
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, "Door"));
+    // Skipped lines take a frame each, so every press waits for the next line to be recorded.
+    //
+    // 每跳过一行需要一帧，因此每次按键都要等到下一行被记录。
+    let mut shown = 0;
+    for _ in 0..8 {
+        for _ in 0..20 {
+            app.update();
+            if shown_lines(&app).len() > shown {
+                break;
+            }
+        }
+        shown = shown_lines(&app).len();
+        app.world_mut().write_message(MortarCommand::next_text());
+    }
+    shown_lines(&app)
+}
+
+fn shown_lines(app: &App) -> Vec<String> {
+    app.world()
+        .resource::<MortarDialogueHistory>()
+        .entries()
+        .iter()
+        .filter_map(|entry| entry.body.clone())
+        .collect()
+}
+
+#[test]
+fn test_dialogue_plays_one_branch_of_the_block() {
+    assert_eq!(
+        play_door(true),
+        ["A door.", "You unlock it.", "Bye."].map(String::from)
+    );
+    assert_eq!(
+        play_door(false),
+        ["A door.", "It's locked.", "Find the key.", "Bye."].map(String::from)
+    );
+}
@@ -0,0 +1,113 @@
+//! Covers the diagnostics of malformed content items: a `condition` or `options` field that does
+//! not deserialize is reported with its node, item and field instead of vanishing, fields of
+//! `continuation` texts and voices name the entry holding them, and the same diagnostics reach a
+//! dialogue state built from the raw node.
+//!
+//! 覆盖格式错误内容项的诊断：无法反序列化的 `condition` 或 `options` 字段会连同其节点、项与字段一起
+//! 报告，而不是悄悄消失；`continuation` 文本与声部的字段会带上所在条目；由原始节点构建的对话状态
+//! 也能得到相同的诊断。
+
+use crate::*;
+use mortar_compiler::Deserializer;
+
+fn asset(content: serde_json::Value) -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [
+            { "name": "Vault", "content": content },
+            { "name": "Hall", "content": [{ "type": "text", "value": "Quiet." }] }
+        ],
+        "functions": [],
+        "variables": [{ "name": "has_key", "type": "Boolean", "value": false }]
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+#[test]
+fn test_malformed_condition_is_reported() {
+    let asset = asset(serde_json::json!([
+        { "type": "text", "value": "The vault is sealed." },
+        {
+            "type": "text",
+            "value": "The key turns.",
+            "condition": { "type": 7, "value": "has_key" }
+        }
+    ]));
+    let diagnostics = asset.content_diagnostics();
+    assert_eq!(diagnostics.len(), 1);
+    let diagnostic = &diagnostics[0];
+    assert_eq!(diagnostic.node, "Vault");
+    assert_eq!(diagnostic.content_index, 1);
+    assert_eq!(diagnostic.field, "condition");
+    assert!(!diagnostic.message.is_empty());
+    assert!(
+        diagnostic
+            .to_string()
+            .starts_with("node 'Vault' item 1: malformed 'condition': ")
+    );
+
+    let state = DialogueState::new(
+        "vault.mortar".to_owned(),
+        "Vault".to_owned(),
+        asset.data.nodes[0].clone(),
+    );
+    assert_eq!(state.diagnostics(), diagnostics.as_slice());
+    assert!(state.text_items()[1].condition.is_none());
+    assert!(
+        asset
+            .parsed_node("Hall")
+            .expect("the node exists")
+            .diagnostics()
+            .is_empty()
+    );
+}
+
+#[test]
+fn test_malformed_options_are_reported() {
+    let asset = asset(serde_json::json!([
+        { "type": "text", "value": "Which lock?" },
+        {
+            "type": "choice",
+            "options": [{ "text": "Left", "next": 3 }, { "text": "Right" }]
+        }
+    ]));
+    let diagnostics = asset.content_diagnostics();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].content_index, 1);
+    assert_eq!(diagnostics[0].field, "options");
+    let parsed = asset.parsed_node("Vault").expect("the node exists");
+    assert!(parsed.choices().is_none());
+}
+
+#[test]
+fn test_nested_fields_name_their_entry() {
+    let asset = asset(serde_json::json!([
+        {
+            "type": "text",
+            "value": "Tumblers click.",
+            "continuation": [{ "type": "text", "value": "Then stop.", "events": "soon" }]
+        },
+        {
+            "type": "parallel_text",
+            "texts": [
+                { "value": "Left.", "target": "left" },
+                { "value": "Right.", "target": "right", "interpolated_parts": 4 }
+            ]
+        },
+        { "type": "run_event", "name": "Alarm", "args": [1, 2] }
+    ]));
+    let fields: Vec<(usize, String)> = asset
+        .content_diagnostics()
+        .into_iter()
+        .map(|diagnostic| (diagnostic.content_index, diagnostic.field))
+        .collect();
+    assert_eq!(
+        fields,
+        [
+            (0, "continuation[0].events".to_owned()),
+            (1, "texts[1].interpolated_parts".to_owned()),
+            (2, "args".to_owned()),
+        ]
+    );
+}
@@ -0,0 +1,190 @@
+//! Covers texts sharing one content index through a `continuation`: they keep declaration order
+//! as `(content_index, sub_index)` positions, the choice after them waits for the last of them,
+//! a `run_event` index override attaches to the first only, the `run` after the item waits until
+//! the continuation was shown, and validation warns about the shared index.
+//!
+//! 覆盖通过 `continuation` 共享同一内容索引的文本：它们以 `(content_index, sub_index)` 位置保持声明
+//! 顺序；其后的选项会等待其中最后一个文本；`run_event` 的索引覆盖只附加到第一个文本；内容项之后的
+//! `run` 会等到续接文本显示后才执行；校验会对共享索引发出警告。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use mortar_compiler::Deserializer;
+
+const PATH: &str = "echo.mortar";
+
+#[derive(Resource, Default)]
+struct Seen {
+    changes: Vec<MortarTextChanged>,
+    fired: Vec<String>,
+}
+
+fn record(
+    mut seen: ResMut<Seen>,
+    mut changes: MessageReader<MortarTextChanged>,
+    mut fired: MessageReader<MortarGameEvent>,
+) {
+    seen.changes.extend(changes.read().cloned());
+    let names: Vec<_> = fired.read().map(|event| event.name.clone()).collect();
+    seen.fired.extend(names);
+}
+
+fn echo_node() -> serde_json::Value {
+    serde_json::json!({
+        "name": "Echo",
+        "content": [
+            {
+                "type": "run_event",
+                "name": "Chime",
+                "index_override": { "type": "value", "value": "2" }
+            },
+            {
+                "type": "text",
+                "value": "Hello.",
+                "continuation": [{
+                    "type": "text",
+                    "value": "Hello again.",
+                    "events": [{ "index": 0, "actions": [{ "type": "echo" }] }]
+                }]
+            },
+            { "type": "run_event", "name": "Bell" },
+            { "type": "choice", "options": [{ "text": "Leave" }] }
+        ]
+    })
+}
+
+fn echo_asset() -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [echo_node()],
+        "functions": [],
+        "events": [
+            { "name": "Chime", "action": { "type": "chime" } },
+            { "name": "Bell", "action": { "type": "bell" } }
+        ]
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+fn echo_state() -> DialogueState {
+    let node = serde_json::from_value(echo_node()).expect("node should deserialize");
+    DialogueState::new(PATH.to_string(), "Echo".to_string(), node)
+}
+
+fn setup_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+    ))
+    .init_resource::<Seen>()
+    .add_systems(Last, record);
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(echo_asset());
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+    app
+}
+
+fn send(app: &mut App, command: MortarCommand) {
+    app.world_mut().write_message(command);
+    for _ in 0..3 {
+        app.update();
+    }
+}
+
+fn action_types(change: &MortarTextChanged) -> Vec<&str> {
+    change
+        .events
+        .iter()
+        .flat_map(|event| &event.actions)
+        .map(|action| action.action_type.as_str())
+        .collect()
+}
+
+#[test]
+fn test_shared_index_texts_keep_declaration_order() {
+    let mut state = echo_state();
+    assert_eq!(
+        state.text_positions(),
+        [
+            TextPosition::item(1),
+            TextPosition {
+                content_index: 1,
+                sub_index: 1
+            }
+        ]
+    );
+    let values: Vec<&str> = state
+        .text_items()
+        .iter()
+        .map(|text| text.value.as_str())
+        .collect();
+    assert_eq!(values, ["Hello.", "Hello again."]);
+    assert_eq!(
+        state.text_source(1).and_then(|raw| raw.get("value")),
+        Some(&serde_json::json!("Hello again."))
+    );
+
+    // The choice waits for the continuation, and so does the run after the item.
+    //
+    // 选项会等待续接文本，内容项之后的 run 同样如此。
+    assert!(state.has_next_text_before_choice());
+    assert!(!state.choices_presentable());
+    assert_eq!(state.run_position_after_line_group(), None);
+
+    assert!(state.next_text());
+    assert_eq!(state.current_text_content_index(), Some(1));
+    assert!(!state.has_next_text_before_choice());
+    assert!(state.choices_presentable());
+    assert_eq!(state.run_position_after_line_group(), Some(2));
+}
+
+#[test]
+fn test_runs_attach_around_shared_index_texts() {
+    let mut app = setup_app();
+    send(&mut app, MortarCommand::start_node(PATH, "Echo"));
+    let seen = app.world().resource::<Seen>();
+    assert_eq!(seen.changes.len(), 1);
+    assert_eq!(seen.changes[0].body, "Hello.");
+    assert_eq!(action_types(&seen.changes[0]), ["chime"]);
+
+    send(&mut app, MortarCommand::next_text());
+    let seen = app.world().resource::<Seen>();
+    assert_eq!(seen.changes.len(), 2);
+    assert_eq!(seen.changes[1].body, "Hello again.");
+    assert_eq!(action_types(&seen.changes[1]), ["echo"]);
+    assert!(!seen.fired.iter().any(|name| name == "bell"));
+
+    send(&mut app, MortarCommand::next_text());
+    assert!(
+        app.world()
+            .resource::<Seen>()
+            .fired
+            .iter()
+            .any(|name| name == "bell")
+    );
+    let runtime = app.world().resource::<MortarRuntime>();
+    let state = runtime
+        .primary_dialogue_state()
+        .expect("dialogue is active");
+    assert!(state.choices_presentable());
+}
+
+#[test]
+fn test_shared_content_index_is_a_validation_warning() {
+    let issues = validate_mortared_data(&echo_asset().data, None);
+    let shared: Vec<_> = issues
+        .iter()
+        .filter(|issue| issue.kind == MortarIssueKind::SharedContentIndex)
+        .collect();
+    assert_eq!(shared.len(), 1);
+    assert_eq!(shared[0].severity, MortarIssueSeverity::Warning);
+    assert_eq!(shared[0].node.as_deref(), Some("Echo"));
+}
@@ -104,3 +104,41 @@ fn test_event_with_index_variable() {
 
     assert_eq!(event.index_variable, Some("custom_time".to_string()));
 }
+
+#[test]
+fn test_event_tracker_fires_skipped_events_in_index_order() {
+    let event = |index: f64, action: &str| mortar_compiler::Event {
+        index,
+        index_variable: None,
+        actions: vec![mortar_compiler::Action {
+            action_type: action.to_string(),
+            args: vec![],
+        }],
+    };
+    let events = vec![
+        event(10.0, "play_sound"),
+        event(5.0, "set_color"),
+        event(10.0, "shake"),
+        event(0.0, "fade_in"),
+    ];
+
+    let called = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let mut runtime = MortarRuntime::default();
+    for name in ["play_sound", "set_color", "shake", "fade_in"] {
+        let called = called.clone();
+        runtime.functions.register(name, move |_| {
+            called.lock().unwrap().push(name);
+            MortarValue::Void
+        });
+    }
+
+    let mut tracker = MortarEventTracker::new(events);
+    let actions = tracker.trigger_at_index(100.0, &runtime);
+    let names: Vec<_> = actions
+        .iter()
+        .map(|action| action.action_name.as_str())
+        .collect();
+    let expected = ["fade_in", "set_color", "play_sound", "shake"];
+    assert_eq!(names, expected);
+    assert_eq!(*called.lock().unwrap(), expected);
+}
@@ -54,7 +54,12 @@ fn test_evaluate_condition_with_function() {
         args: vec![],
     };
 
-    let result = evaluate_condition(&condition, &functions, &function_decls);
+    let result = evaluate_condition(
+        &condition,
+        &functions,
+        &function_decls,
+        &MortarVariableState::new(),
+    );
     assert!(!result);
 }
 
@@ -70,6 +75,73 @@ fn test_evaluate_condition_with_registered_function() {
         args: vec![],
     };
 
-    let result = evaluate_condition(&condition, &functions, &function_decls);
+    let result = evaluate_condition(
+        &condition,
+        &functions,
+        &function_decls,
+        &MortarVariableState::new(),
+    );
     assert!(result);
 }
+
+#[test]
+fn test_evaluate_condition_prefers_variable_over_function() {
+    let mut functions = MortarFunctionRegistry::new();
+    functions.register("has_key", |_args| {
+        MortarValue::Boolean(MortarBoolean(false))
+    });
+
+    let mut vars = MortarVariableState::new();
+    vars.set("has_key", MortarVariableValue::Boolean(true));
+
+    let condition = mortar_compiler::Condition {
+        condition_type: "has_key".to_string(),
+        args: vec![],
+    };
+
+    assert!(evaluate_condition(&condition, &functions, &[], &vars));
+}
+
+#[test]
+fn test_variable_choice_condition_tracks_variable_changes() {
+    use serde_json::json;
+
+    let node = Node {
+        name: "Shop".to_string(),
+        content: vec![
+            json!({ "type": "text", "value": "What do you want?" }),
+            json!({
+                "type": "choice",
+                "options": [
+                    { "text": "Buy sword", "condition": { "type": "gold", "args": [">=", "10"] }, "next": "Sword" },
+                    { "text": "Haggle", "condition": { "type": "gold < price" }, "next": "Haggle" }
+                ]
+            }),
+        ],
+        branches: None,
+        variables: vec![],
+        next: None,
+    };
+    let state = DialogueState::new("shop.mortar".to_string(), "Shop".to_string(), node);
+    let functions = MortarFunctionRegistry::new();
+    let mut vars = MortarVariableState::new();
+    vars.set("gold", MortarVariableValue::Number(5.0));
+    vars.set("price", MortarVariableValue::Number(10.0));
+
+    let enabled = |vars: &MortarVariableState| -> Vec<bool> {
+        state
+            .get_choices()
+            .unwrap()
+            .iter()
+            .map(|choice| {
+                let cond = choice.condition.as_ref().unwrap();
+                evaluate_condition(cond, &functions, &[], vars)
+            })
+            .collect()
+    };
+
+    assert_eq!(enabled(&vars), vec![false, true]);
+
+    vars.execute_assignment("gold", "12");
+    assert_eq!(enabled(&vars), vec![true, false]);
+}
@@ -17,6 +17,12 @@ fn test_process_interpolated_text_no_interpolation() {
         events: None,
         pre_statements: vec![],
         is_line: false,
+        line_id: String::new(),
+        header: None,
+        experiment: None,
+        requires: Vec::new(),
+        channel: None,
+        parallel: Vec::new(),
     };
 
     let functions = MortarFunctionRegistry::new();
@@ -31,6 +37,12 @@ fn test_process_interpolated_text_no_interpolation() {
 fn test_process_interpolated_text_with_variables() {
     let text_data = TextData {
         is_line: false,
+        line_id: String::new(),
+        header: None,
+        experiment: None,
+        requires: Vec::new(),
+        channel: None,
+        parallel: Vec::new(),
         value: "Hello {name}!".to_string(),
         interpolated_parts: Some(vec![
             mortar_compiler::StringPart {
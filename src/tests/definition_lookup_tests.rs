@@ -0,0 +1,93 @@
+//! Covers the name lookup tables of an asset: function, event and timeline lookups return the same
+//! definitions as a linear search, duplicates included, tables follow edits after a reindex, and on
+//! a large generated file they make far fewer name comparisons than scanning.
+//!
+//! 覆盖资源的名称查找表：函数、事件与时间线的查找结果与线性查找相同（包括重名的情况）；重建索引后
+//! 查找表会反映修改；在生成的大型文件上，其名称比较次数远少于逐个扫描。
+
+use crate::asset::comparisons;
+use crate::*;
+use mortar_compiler::Deserializer;
+
+fn asset(functions: usize, events: usize) -> MortarAsset {
+    let functions: Vec<_> = (0..functions)
+        .map(|i| serde_json::json!({ "name": format!("f{i}"), "params": [], "return_type": "Number" }))
+        .collect();
+    let events: Vec<_> = (0..events)
+        .map(|i| serde_json::json!({ "name": format!("e{i}"), "action": { "type": format!("act{i}") } }))
+        .collect();
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [{ "name": "Start", "content": [{ "type": "text", "value": "Hi" }] }],
+        "functions": functions,
+        "events": events,
+        "timelines": [
+            { "name": "intro", "statements": [{ "type": "run", "event_name": "e0" }] },
+            { "name": "intro", "statements": [] }
+        ]
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+#[test]
+fn test_lookups_match_linear_search() {
+    let mut asset = asset(4, 4);
+    // A duplicate name resolves to its first declaration, as `find` does.
+    //
+    // 重名时解析为第一个声明，与 `find` 一致。
+    let mut duplicate = asset.data.events[1].clone();
+    duplicate.action.action_type = "shadowed".to_owned();
+    asset.data.events.push(duplicate);
+    asset.reindex();
+
+    for name in ["f0", "f3", "e1", "missing"] {
+        let linear = asset.data.functions.iter().find(|f| f.name == name);
+        assert!(std::ptr::eq(
+            asset.function_decl(name).map_or(std::ptr::null(), |f| f),
+            linear.map_or(std::ptr::null(), |f| f)
+        ));
+        let linear = asset.data.events.iter().find(|e| e.name == name);
+        assert!(std::ptr::eq(
+            asset.event_def(name).map_or(std::ptr::null(), |e| e),
+            linear.map_or(std::ptr::null(), |e| e)
+        ));
+    }
+    assert_eq!(asset.event_def("e1").unwrap().action.action_type, "act1");
+    assert_eq!(asset.timeline_def("intro").unwrap().statements.len(), 1);
+
+    let mut late = asset.data.events[0].clone();
+    late.name = "late".to_owned();
+    asset.data.events.push(late);
+    assert!(
+        asset.event_def("late").is_none(),
+        "tables follow edits only after a reindex"
+    );
+    asset.reindex();
+    assert!(asset.event_def("late").is_some());
+}
+
+#[test]
+fn test_tables_cut_comparisons_on_large_asset() {
+    let asset = asset(300, 300);
+    let names: Vec<String> = (0..300).map(|i| format!("e{i}")).collect();
+
+    let mut linear = 0;
+    for name in &names {
+        let found = asset.data.events.iter().find(|event| {
+            linear += 1;
+            event.name == *name
+        });
+        assert!(found.is_some());
+    }
+
+    comparisons::take();
+    for name in &names {
+        assert!(asset.event_def(name).is_some());
+    }
+    let indexed = comparisons::take();
+
+    assert_eq!(linear, 300 * 301 / 2);
+    assert_eq!(indexed, 300);
+    assert!(indexed * 100 < linear);
+}
@@ -0,0 +1,245 @@
+//! Covers the direct drive calls on the runtime: `force_show_line` renders what the text system
+//! would announce for the same line, leaves the text targets alone and writes no messages unless
+//! `announce_direct_drive` is on, `fire_line_events` hands a chosen range of the line's events to a
+//! sink, and `force_set_position` clamps the line and refuses what a start request would.
+//!
+//! 覆盖运行时上的直接驱动调用：`force_show_line` 渲染的结果与文本系统对同一行的通知一致，除非开启
+//! `announce_direct_drive`，否则不更新文本目标也不写入任何消息；`fire_line_events` 把该行事件中选定
+//! 范围的部分交给接收器；`force_set_position` 会限制行索引，并拒绝开始请求也会拒绝的情况。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use mortar_compiler::Deserializer;
+
+const PATH: &str = "well.mortar";
+
+#[derive(Resource, Default)]
+struct Recorded {
+    changes: Vec<MortarTextChanged>,
+    started: usize,
+    entered: Vec<MortarNodeEntered>,
+    game_events: usize,
+}
+
+fn record(
+    mut recorded: ResMut<Recorded>,
+    mut changes: MessageReader<MortarTextChanged>,
+    mut started: MessageReader<MortarDialogueStarted>,
+    mut entered: MessageReader<MortarNodeEntered>,
+    mut game_events: MessageReader<MortarGameEvent>,
+) {
+    recorded.changes.extend(changes.read().cloned());
+    recorded.started += started.read().count();
+    recorded.entered.extend(entered.read().cloned());
+    recorded.game_events += game_events.read().count();
+}
+
+fn well_asset() -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [{
+            "name": "Well",
+            "content": [
+                {
+                    "type": "text",
+                    "value": "You toss {coins} coins.",
+                    "interpolated_parts": [
+                        { "type": "text", "content": "You toss " },
+                        { "type": "placeholder", "content": "{coins}" },
+                        { "type": "text", "content": " coins." }
+                    ],
+                    "events": [
+                        { "index": 4, "actions": [{ "type": "splash" }] },
+                        { "index": 9, "actions": [{ "type": "ripple", "args": ["2"] }] }
+                    ]
+                },
+                { "type": "text", "value": "Nothing happens." }
+            ]
+        }],
+        "functions": [],
+        "variables": [{ "name": "coins", "type": "Number", "value": 3 }]
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+fn setup_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+    ))
+    .init_resource::<Recorded>()
+    .add_systems(Last, record);
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(well_asset());
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+    app.world_mut().spawn((Text::new(""), MortarTextTarget));
+    app.update();
+    app
+}
+
+fn send(app: &mut App, command: MortarCommand) {
+    app.world_mut().write_message(command);
+    for _ in 0..3 {
+        app.update();
+    }
+}
+
+fn force_show(
+    app: &mut App,
+    path: &str,
+    index: usize,
+) -> Result<MortarRenderedLine, MortarDriveError> {
+    app.world_mut()
+        .resource_scope(|world, mut runtime: Mut<MortarRuntime>| {
+            world.resource_scope(|world, mut variables: Mut<MortarDialogueVariables>| {
+                let assets = world.resource::<Assets<MortarAsset>>();
+                let registry = world.resource::<MortarRegistry>();
+                runtime.force_show_line(path, "Well", index, registry, assets, &mut variables)
+            })
+        })
+}
+
+fn force_position(app: &mut App, node: &str, index: usize) -> Result<usize, MortarDriveError> {
+    app.world_mut()
+        .resource_scope(|world, mut runtime: Mut<MortarRuntime>| {
+            let assets = world.resource::<Assets<MortarAsset>>();
+            let registry = world.resource::<MortarRegistry>();
+            runtime.force_set_position(node, index, registry, assets)
+        })
+}
+
+fn recorded(app: &App) -> &Recorded {
+    app.world().resource::<Recorded>()
+}
+
+#[test]
+fn test_forced_lines_match_the_normal_pipeline() {
+    let mut played = setup_app();
+    send(&mut played, MortarCommand::start_node(PATH, "Well"));
+    send(&mut played, MortarCommand::next_text());
+    let expected = &recorded(&played).changes;
+    assert_eq!(expected.len(), 2);
+
+    let mut app = setup_app();
+    for (index, change) in expected.iter().enumerate() {
+        let line = force_show(&mut app, PATH, index).expect("the line is shown");
+        assert_eq!(line.mortar_path, change.mortar_path);
+        assert_eq!(line.node, change.node);
+        assert_eq!(line.text_index, change.text_index);
+        assert_eq!(line.text.header, change.header);
+        assert_eq!(line.text.body, change.body);
+        let indices = |events: &[mortar_compiler::Event]| -> Vec<f64> {
+            events.iter().map(|event| event.index).collect()
+        };
+        assert_eq!(indices(&line.events), indices(&change.events));
+    }
+
+    // Nothing reaches the world, and the targets keep what they showed.
+    //
+    // 没有任何消息进入世界，目标保持原先的显示。
+    for _ in 0..3 {
+        app.update();
+    }
+    let recorded = recorded(&app);
+    assert!(recorded.changes.is_empty());
+    assert_eq!(recorded.started, 0);
+    assert!(recorded.entered.is_empty());
+    assert_eq!(recorded.game_events, 0);
+    let mut shown = app.world_mut().query::<&MortarDialogueText>();
+    assert_eq!(shown.iter(app.world()).count(), 0);
+    let runtime = app.world().resource::<MortarRuntime>();
+    let state = runtime
+        .primary_dialogue_state()
+        .expect("the line is active");
+    assert_eq!(state.text_index, 1);
+}
+
+#[test]
+fn test_fire_line_events_hands_a_range_to_the_sink() {
+    let mut app = setup_app();
+    let line = force_show(&mut app, PATH, 0).expect("the line is shown");
+    assert_eq!(line.events.len(), 2);
+    let split = (line.events[0].index + line.events[1].index) / 2.0;
+
+    let runtime = app.world().resource::<MortarRuntime>();
+    let mut sink = Vec::new();
+    assert_eq!(runtime.fire_line_events(..split, &mut sink), 1);
+    assert_eq!(runtime.fire_line_events(split.., &mut sink), 1);
+    let names: Vec<&str> = sink
+        .iter()
+        .map(|action| action.action_name.as_str())
+        .collect();
+    assert_eq!(names, ["splash", "ripple"]);
+    assert_eq!(sink[1].args, ["2"]);
+    assert_eq!(runtime.fire_line_events(.., &mut sink), 2);
+
+    for _ in 0..3 {
+        app.update();
+    }
+    assert_eq!(recorded(&app).game_events, 0);
+}
+
+#[test]
+fn test_announced_drive_writes_lifecycle_messages() {
+    let mut app = setup_app();
+    app.world_mut()
+        .resource_mut::<MortarRuntime>()
+        .announce_direct_drive = true;
+    force_show(&mut app, PATH, 0).expect("the line is shown");
+    for _ in 0..3 {
+        app.update();
+    }
+    assert_eq!(recorded(&app).started, 1);
+    assert_eq!(recorded(&app).entered.len(), 1);
+    let bodies: Vec<&str> = recorded(&app)
+        .changes
+        .iter()
+        .map(|change| change.body.as_str())
+        .collect();
+    assert_eq!(bodies, ["You toss 3 coins."]);
+
+    force_position(&mut app, "Well", 1).expect("the node exists");
+    for _ in 0..3 {
+        app.update();
+    }
+    let recorded = recorded(&app);
+    assert_eq!(recorded.started, 1);
+    assert_eq!(recorded.entered.len(), 2);
+    assert_eq!(recorded.entered[1].entry_index, 1);
+    assert_eq!(recorded.changes.len(), 2);
+}
+
+#[test]
+fn test_force_set_position_clamps_and_refuses() {
+    let mut app = setup_app();
+    assert_eq!(
+        force_position(&mut app, "Well", 0),
+        Err(MortarDriveError::NoDialogue)
+    );
+    assert!(matches!(
+        force_show(&mut app, "cave.mortar", 0),
+        Err(MortarDriveError::UnknownFile { .. })
+    ));
+
+    send(&mut app, MortarCommand::start_node(PATH, "Well"));
+    assert_eq!(force_position(&mut app, "Well", 99), Ok(1));
+    assert_eq!(
+        force_position(&mut app, "Cave", 0),
+        Err(MortarDriveError::Refused {
+            mortar_path: PATH.to_owned(),
+            node: "Cave".to_owned(),
+            reason: MortarStartFailure::NodeNotFound,
+        })
+    );
+    let runtime = app.world().resource::<MortarRuntime>();
+    let state = runtime.primary_dialogue_state().expect("still active");
+    assert_eq!(state.text_index, 1);
+}
@@ -0,0 +1,139 @@
+//! Covers display animations: after `gold` changes between lines, an opted-in target rolls the
+//! interpolated number from the old value to the new one over the policy's duration while
+//! [`MortarDialogueText`] keeps the final value and changes only once, no game event fires, and a
+//! target that did not opt in shows the final value at once.
+//!
+//! 覆盖显示动画：`gold` 在两行之间变化后，启用动画的目标会在策略规定的时长内把插值数字从旧值滚动到
+//! 新值，期间 [`MortarDialogueText`] 始终保存最终值且只变化一次，不会触发任何游戏事件；未启用动画的
+//! 目标则立即显示最终值。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use bevy::time::TimeUpdateStrategy;
+use mortar_compiler::Deserializer;
+use std::time::Duration;
+
+const PATH: &str = "purse.mortar";
+
+fn purse_asset() -> MortarAsset {
+    let gold_line = |before: &str| {
+        serde_json::json!({
+            "type": "text",
+            "value": format!("{before}{{gold}} gold."),
+            "interpolated_parts": [
+                { "type": "text", "content": before },
+                { "type": "placeholder", "content": "{gold}" },
+                { "type": "text", "content": " gold." }
+            ]
+        })
+    };
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [{
+            "name": "Purse",
+            "content": [gold_line("You have "), gold_line("Now you have ")]
+        }],
+        "functions": [],
+        "variables": [{ "name": "gold", "type": "Number", "value": 120 }]
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+/// Changes of the animated target's dialogue text and game events seen so far.
+#[derive(Resource, Default)]
+struct Observed {
+    text_changes: usize,
+    game_events: usize,
+}
+
+fn observe(
+    mut observed: ResMut<Observed>,
+    texts: Query<(), (Changed<MortarDialogueText>, With<MortarAnimatedDisplay>)>,
+    mut events: MessageReader<MortarGameEvent>,
+) {
+    observed.text_changes += texts.iter().count();
+    observed.game_events += events.read().count();
+}
+
+fn shown(app: &App, target: Entity) -> String {
+    app.world().get::<Text>(target).unwrap().0.clone()
+}
+
+#[test]
+fn test_changed_number_rolls_on_opted_in_targets() {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+    ))
+    .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+        100,
+    )))
+    .insert_resource(MortarHeaderSettings {
+        format: String::new(),
+        ..default()
+    })
+    .init_resource::<Observed>()
+    .add_systems(Last, observe);
+    app.world_mut()
+        .resource_mut::<MortarDisplayAnimations>()
+        .set(
+            "gold",
+            MortarDisplayPolicy::NumberRoll {
+                duration: Duration::from_millis(500),
+                easing: EaseFunction::Linear,
+            },
+        );
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(purse_asset());
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+    let animated = app
+        .world_mut()
+        .spawn((Text::new(""), MortarTextTarget, MortarAnimatedDisplay))
+        .id();
+    let plain = app
+        .world_mut()
+        .spawn((Text::new(""), MortarTextTarget))
+        .id();
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, "Purse"));
+    for _ in 0..3 {
+        app.update();
+    }
+    assert_eq!(shown(&app, animated), "You have 120 gold.");
+
+    app.world_mut()
+        .resource_mut::<MortarDialogueVariables>()
+        .state
+        .as_mut()
+        .expect("variables should be loaded")
+        .set("gold", MortarVariableValue::Number(470.0));
+    app.world_mut().resource_mut::<Observed>().text_changes = 0;
+    app.world_mut().write_message(MortarCommand::next_text());
+
+    for expected in ["190", "260", "330", "400"] {
+        app.update();
+        assert_eq!(
+            shown(&app, animated),
+            format!("Now you have {expected} gold.")
+        );
+        assert_eq!(shown(&app, plain), "Now you have 470 gold.");
+        let text = app.world().get::<MortarDialogueText>(animated).unwrap();
+        assert_eq!(text.body, "Now you have 470 gold.");
+        assert!(app.world().get::<MortarDisplayRoll>(animated).is_some());
+    }
+    app.update();
+    assert_eq!(shown(&app, animated), "Now you have 470 gold.");
+    assert!(app.world().get::<MortarDisplayRoll>(animated).is_none());
+
+    let observed = app.world().resource::<Observed>();
+    assert_eq!(observed.text_changes, 1);
+    assert_eq!(observed.game_events, 0);
+}
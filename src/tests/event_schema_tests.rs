@@ -0,0 +1,169 @@
+//! Covers eager event validation: a line with one valid and one schema-violating event reports
+//! the bad one with its node, line and character index, only the valid one fires under
+//! `SuppressInvalid`, both fire under `FireAnyway`, and a suppressed `run` event is never sent.
+//!
+//! 覆盖事件的提前校验：一行中有一个有效事件与一个违反模式的事件时，违规事件会连同其节点、行与
+//! 字符索引被报告；在 `SuppressInvalid` 下只有有效事件触发，在 `FireAnyway` 下两者都会触发；
+//! 被抑制的 `run` 事件永远不会发出。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use mortar_compiler::Deserializer;
+
+const PATH: &str = "bounce.mortar";
+
+#[derive(Resource, Default)]
+struct Fired(Vec<(String, Vec<String>)>);
+
+fn record_fired(mut fired: ResMut<Fired>, mut events: MessageReader<MortarGameEvent>) {
+    let events: Vec<_> = events
+        .read()
+        .map(|event| (event.name.clone(), event.args.clone()))
+        .collect();
+    fired.0.extend(events);
+}
+
+fn bounce_asset() -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [
+            {
+                "name": "Start",
+                "content": [{
+                    "type": "text",
+                    "value": "Boing boing",
+                    "events": [
+                        { "index": 1, "actions": [{ "type": "bounce", "args": ["2"] }] },
+                        { "index": 6, "actions": [{ "type": "bounce", "args": ["\"high\""] }] }
+                    ]
+                }]
+            },
+            {
+                "name": "Runs",
+                "content": [
+                    { "type": "text", "value": "Jump" },
+                    { "type": "run_event", "name": "BadBounce" },
+                    { "type": "text", "value": "Landed" }
+                ]
+            }
+        ],
+        "functions": [],
+        "events": [{ "name": "BadBounce", "action": { "type": "bounce", "args": ["1", "2"] } }]
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+fn setup_app(policy: MortarInvalidEventPolicy, node: &str) -> (App, Entity) {
+    let mut schemas = MortarEventSchemas::default();
+    schemas.validate_events_on_collect = true;
+    schemas.invalid_policy = policy;
+    schemas.insert("bounce", [MortarParamKind::Number]);
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+    ))
+    .insert_resource(schemas)
+    .init_resource::<Fired>()
+    .add_systems(Last, record_fired);
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(bounce_asset());
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+    let target = app
+        .world_mut()
+        .spawn((Text::new(""), MortarTextTarget))
+        .id();
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, node));
+    for _ in 0..3 {
+        app.update();
+    }
+    (app, target)
+}
+
+/// Moves the event binding past every event of the line.
+fn reveal_all(app: &mut App, target: Entity) {
+    app.world_mut()
+        .get_mut::<MortarEventBinding>(target)
+        .expect("the line has events")
+        .current_index = 20.0;
+    app.update();
+}
+
+fn fired(app: &App) -> Vec<(String, Vec<String>)> {
+    app.world().resource::<Fired>().0.clone()
+}
+
+#[test]
+fn test_invalid_event_is_reported_and_suppressed() {
+    let (mut app, target) = setup_app(MortarInvalidEventPolicy::SuppressInvalid, "Start");
+
+    let entries = &app.world().resource::<MortarEventDiagnostics>().entries;
+    assert_eq!(entries.len(), 1);
+    let entry = &entries[0];
+    assert_eq!(entry.path, PATH);
+    assert_eq!(entry.node, "Start");
+    assert_eq!(entry.text_index, 0);
+    assert_eq!(entry.index, Some(6.0));
+    assert_eq!(entry.action, "bounce");
+    assert_eq!(
+        entry.error,
+        MortarEventArgError::Kind {
+            index: 0,
+            expected: MortarParamKind::Number,
+            found: "string",
+        }
+    );
+    let tracker = app.world().get::<MortarEventTracker>(target).unwrap();
+    assert!(tracker.is_valid(0));
+    assert!(!tracker.is_valid(1));
+
+    reveal_all(&mut app, target);
+    assert_eq!(
+        fired(&app),
+        vec![("bounce".to_owned(), vec!["2".to_owned()])]
+    );
+}
+
+#[test]
+fn test_fire_anyway_fires_invalid_event() {
+    let (mut app, target) = setup_app(MortarInvalidEventPolicy::FireAnyway, "Start");
+    assert_eq!(
+        app.world()
+            .resource::<MortarEventDiagnostics>()
+            .entries
+            .len(),
+        1
+    );
+
+    reveal_all(&mut app, target);
+    assert_eq!(fired(&app).len(), 2);
+}
+
+#[test]
+fn test_invalid_run_event_is_suppressed() {
+    let (mut app, _) = setup_app(MortarInvalidEventPolicy::SuppressInvalid, "Runs");
+    app.world_mut().write_message(MortarCommand::next_text());
+    for _ in 0..3 {
+        app.update();
+    }
+
+    let entries = &app.world().resource::<MortarEventDiagnostics>().entries;
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].index, None);
+    assert_eq!(
+        entries[0].error,
+        MortarEventArgError::Arity {
+            expected: 1,
+            found: 2
+        }
+    );
+    assert!(fired(&app).is_empty());
+}
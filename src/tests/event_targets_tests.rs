@@ -0,0 +1,130 @@
+//! Covers named event targets: an `@hero` argument of a text event resolves to the entity
+//! registered for `hero` and lands in `MortarGameEvent::targets` while `args` keep it as written,
+//! a `MortarSpeaker` component is found when the registry has no entry, and an unknown `@ghost`
+//! adds no target and leaves one entry in `MortarTargetDiagnostics`. A quoted `"@intro.wav"` is a
+//! plain string and names nothing.
+//!
+//! 覆盖具名事件目标：文本事件的 `@hero` 参数会解析为为 `hero` 注册的实体并进入
+//! `MortarGameEvent::targets`，而 `args` 仍按原样保留该参数；注册表中没有条目时会查找
+//! `MortarSpeaker` 组件；未知的 `@ghost` 不会产生目标，并在 `MortarTargetDiagnostics` 中留下一条记录。
+//! 带引号的 `"@intro.wav"` 只是普通字符串，不指定任何实体。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use mortar_compiler::Deserializer;
+
+const PATH: &str = "forge.mortar";
+
+#[derive(Resource, Default)]
+struct Recorded(Vec<MortarGameEvent>);
+
+fn record(mut recorded: ResMut<Recorded>, mut events: MessageReader<MortarGameEvent>) {
+    recorded.0.extend(events.read().cloned());
+}
+
+fn forge_asset() -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [{
+            "name": "Forge",
+            "content": [
+                {
+                    "type": "text",
+                    "value": "Welcome.",
+                    "events": [{ "index": 0, "actions": [
+                        { "type": "set_animation", "args": ["\"wave\"", "@hero"] },
+                        { "type": "nod", "args": ["@smith"] },
+                        { "type": "play_sound", "args": ["\"@intro.wav\""] }
+                    ] }]
+                },
+                { "type": "run_event", "name": "Haunt" },
+                { "type": "run_event", "name": "Chime" },
+                { "type": "text", "value": "Brr." }
+            ]
+        }],
+        "functions": [],
+        "events": [
+            { "name": "Haunt", "action": { "type": "shiver", "args": ["@ghost", "@ghost"] } },
+            { "name": "Chime", "action": { "type": "ring", "args": ["\"@bell\""] } }
+        ]
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+fn run_forge() -> (App, Entity, Entity) {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+    ))
+    .init_resource::<Recorded>()
+    .add_systems(PostUpdate, record);
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(forge_asset());
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+    let hero = app.world_mut().spawn_empty().id();
+    app.world_mut()
+        .resource_mut::<MortarSpeakerRegistry>()
+        .register("hero", hero);
+    let smith = app.world_mut().spawn(MortarSpeaker("smith".into())).id();
+    app.world_mut().spawn((Text::new(""), MortarTextTarget));
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, "Forge"));
+    for _ in 0..10 {
+        app.update();
+    }
+    app.world_mut().write_message(MortarCommand::next_text());
+    for _ in 0..10 {
+        app.update();
+    }
+    (app, hero, smith)
+}
+
+fn recorded(app: &App, name: &str) -> MortarGameEvent {
+    let events = &app.world().resource::<Recorded>().0;
+    let matching: Vec<_> = events.iter().filter(|event| event.name == name).collect();
+    assert_eq!(matching.len(), 1, "{name} fired once among {events:?}");
+    matching[0].clone()
+}
+
+#[test]
+fn test_named_targets_resolve() {
+    let (app, hero, smith) = run_forge();
+    let wave = recorded(&app, "set_animation");
+    assert_eq!(wave.targets, [hero]);
+    assert_eq!(wave.args[1], "@hero");
+    assert!(wave.source.is_some());
+    assert_eq!(recorded(&app, "nod").targets, [smith]);
+}
+
+#[test]
+fn test_unknown_target_is_reported() {
+    let (app, ..) = run_forge();
+    let shiver = recorded(&app, "shiver");
+    assert!(shiver.targets.is_empty());
+    assert_eq!(shiver.args, ["@ghost", "@ghost"]);
+    let diagnostics = app.world().resource::<MortarTargetDiagnostics>();
+    assert_eq!(diagnostics.unresolved.len(), 1);
+    assert!(diagnostics.unresolved.contains("ghost"));
+}
+
+#[test]
+fn test_quoted_at_argument_is_a_plain_string() {
+    let (app, ..) = run_forge();
+    let sound = recorded(&app, "play_sound");
+    assert!(sound.targets.is_empty());
+    assert_eq!(sound.args, ["\"@intro.wav\""]);
+    let ring = recorded(&app, "ring");
+    assert!(ring.targets.is_empty());
+    assert_eq!(ring.args, ["@bell"]);
+    let diagnostics = app.world().resource::<MortarTargetDiagnostics>();
+    assert!(!diagnostics.unresolved.contains("intro.wav"));
+    assert!(!diagnostics.unresolved.contains("bell"));
+}
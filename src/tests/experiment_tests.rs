@@ -0,0 +1,165 @@
+//! Covers narrative experiments: of two variants of a line only the assigned one renders and its
+//! tag reaches `MortarTextAdvanced`, an option of another variant is hidden without shifting the
+//! indices of the rest, an unassigned experiment shows all its content with a diagnostics note,
+//! and the hash fallback is stable.
+//!
+//! 覆盖叙事实验：一行的两个变体中只有被分配的那个会渲染，且其标签会出现在 `MortarTextAdvanced`
+//! 中；属于其他变体的选项被隐藏，其余选项的索引不变；未分配的实验会显示其全部内容并记录诊断；
+//! 哈希回退的结果稳定。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use mortar_compiler::Deserializer;
+
+const PATH: &str = "greeting.mortar";
+
+#[derive(Resource, Default)]
+struct Advanced(Vec<MortarTextAdvanced>);
+
+fn record_advanced(mut advanced: ResMut<Advanced>, mut reader: MessageReader<MortarTextAdvanced>) {
+    advanced.0.extend(reader.read().cloned());
+}
+
+fn variant(name: &str) -> serde_json::Value {
+    serde_json::json!({ "name": "greeting", "variant": name })
+}
+
+fn greeting_asset() -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [{
+            "name": "Start",
+            "content": [
+                { "type": "text", "value": "Hello there!", "experiment": variant("a") },
+                { "type": "text", "value": "Hey, you!", "experiment": variant("b") },
+                {
+                    "type": "choice",
+                    "options": [
+                        { "text": "Wave", "experiment": variant("a") },
+                        { "text": "Nod" },
+                        { "text": "Shout", "experiment": variant("b") }
+                    ]
+                }
+            ]
+        }],
+        "functions": []
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+fn setup_app(experiments: MortarExperiments) -> (App, Entity) {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+    ))
+    .insert_resource(experiments)
+    .init_resource::<Advanced>()
+    .add_systems(Last, record_advanced);
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(greeting_asset());
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+    let target = app
+        .world_mut()
+        .spawn((Text::new(""), MortarTextTarget))
+        .id();
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, "Start"));
+    for _ in 0..4 {
+        app.update();
+    }
+    (app, target)
+}
+
+fn body(app: &App, target: Entity) -> String {
+    app.world()
+        .get::<MortarDialogueText>(target)
+        .unwrap()
+        .body
+        .clone()
+}
+
+fn presented(app: &mut App) -> Vec<(usize, String)> {
+    app.world_mut().write_message(MortarCommand::next_text());
+    for _ in 0..3 {
+        app.update();
+    }
+    let presented = app.world().resource::<MortarChoicesPresented>();
+    presented
+        .views
+        .iter()
+        .map(|view| (view.index, view.text.clone()))
+        .collect()
+}
+
+#[test]
+fn test_only_assigned_variant_renders_and_is_tagged() {
+    let mut experiments = MortarExperiments::default();
+    experiments.assign("greeting", "b");
+    let (mut app, target) = setup_app(experiments);
+
+    assert_eq!(body(&app, target), "Hey, you!");
+    let advanced = &app.world().resource::<Advanced>().0;
+    assert_eq!(advanced.len(), 1);
+    assert_eq!(advanced[0].text_index, 1);
+    assert_eq!(
+        advanced[0].experiment,
+        Some(MortarExperimentTag {
+            name: "greeting".to_owned(),
+            variant: "b".to_owned(),
+        })
+    );
+
+    assert_eq!(
+        presented(&mut app),
+        [(1, "Nod".to_owned()), (2, "Shout".to_owned())]
+    );
+}
+
+#[test]
+fn test_unassigned_experiment_shows_everything() {
+    let (mut app, target) = setup_app(MortarExperiments::default());
+
+    assert_eq!(body(&app, target), "Hello there!");
+    assert!(
+        app.world()
+            .resource::<MortarExperimentDiagnostics>()
+            .unassigned
+            .contains("greeting")
+    );
+    app.world_mut().write_message(MortarCommand::next_text());
+    for _ in 0..3 {
+        app.update();
+    }
+    assert_eq!(body(&app, target), "Hey, you!");
+    assert_eq!(presented(&mut app).len(), 3);
+}
+
+#[test]
+fn test_hashed_variant_is_stable() {
+    let variants = ["a", "b"];
+    let first = MortarExperiments::hashed_variant("greeting", "player-42", &variants);
+    assert!(first.is_some());
+    assert_eq!(
+        MortarExperiments::hashed_variant("greeting", "player-42", &variants),
+        first
+    );
+    assert_eq!(
+        MortarExperiments::hashed_variant("greeting", "player-42", &[]),
+        None
+    );
+
+    let picks: std::collections::HashSet<_> = (0..64)
+        .filter_map(|id| {
+            MortarExperiments::hashed_variant("greeting", &format!("player-{id}"), &variants)
+        })
+        .collect();
+    assert_eq!(picks.len(), 2);
+}
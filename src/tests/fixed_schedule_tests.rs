@@ -0,0 +1,159 @@
+//! Covers running the dialogue timers in `FixedUpdate`: a timeline wait of 0.5s ends exactly
+//! `ceil(0.5 / step)` fixed ticks after it starts, however many ticks each frame runs, and the
+//! system sets of the plugin can be ordered against in `FixedUpdate`.
+//!
+//! 覆盖在 `FixedUpdate` 中运行对话计时器：0.5 秒的时间线等待在开始后恰好 `ceil(0.5 / step)` 个
+//! 固定刻结束，与每帧运行多少刻无关；并且可以在 `FixedUpdate` 中相对插件的系统集合排序。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use bevy::time::TimeUpdateStrategy;
+use mortar_compiler::Deserializer;
+use std::collections::HashMap;
+use std::time::Duration;
+
+const PATH: &str = "gate.mortar";
+const WAIT: f64 = 0.5;
+
+#[derive(Resource, Default)]
+struct Ticks(u32);
+
+/// The tick on which each game event was first seen.
+#[derive(Resource, Default)]
+struct FiredAt(HashMap<String, u32>);
+
+fn count_ticks(mut ticks: ResMut<Ticks>) {
+    ticks.0 += 1;
+}
+
+fn record_fired(
+    mut events: MessageReader<MortarGameEvent>,
+    ticks: Res<Ticks>,
+    mut fired: ResMut<FiredAt>,
+) {
+    for event in events.read() {
+        fired.0.entry(event.name.clone()).or_insert(ticks.0);
+    }
+}
+
+fn gate_asset() -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [{
+            "name": "Gate",
+            "content": [
+                { "type": "text", "value": "The gate creaks." },
+                { "type": "run_event", "name": "Opening" },
+                { "type": "text", "value": "It is open." }
+            ]
+        }],
+        "functions": [],
+        "events": [
+            { "name": "lift", "action": { "type": "lift_bar" } },
+            { "name": "swing", "action": { "type": "swing_open" } }
+        ],
+        "timelines": [{ "name": "Opening", "statements": [
+            { "type": "run", "event_name": "lift" },
+            { "type": "wait", "duration": WAIT },
+            { "type": "run", "event_name": "swing" }
+        ] }]
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+fn setup_app(step: Duration, frame: Duration) -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin {
+            schedule: MortarSchedule::FixedUpdate,
+            ..default()
+        },
+    ))
+    .insert_resource(Time::<Fixed>::from_duration(step))
+    .insert_resource(TimeUpdateStrategy::ManualDuration(frame))
+    .init_resource::<Ticks>()
+    .init_resource::<FiredAt>()
+    .add_systems(
+        FixedUpdate,
+        (
+            count_ticks.before(MortarDialogueSystemSet::Timers),
+            record_fired.after(MortarDialogueSystemSet::Timers),
+        ),
+    )
+    .add_systems(PostUpdate, record_fired);
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(gate_asset());
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, "Gate"));
+    for _ in 0..3 {
+        app.update();
+    }
+    app.world_mut().write_message(MortarCommand::next_text());
+    app
+}
+
+/// Runs frames until the timeline ends and returns how many fixed ticks the wait took.
+fn ticks_waited(step: Duration, frame: Duration) -> u32 {
+    let mut app = setup_app(step, frame);
+    for _ in 0..200 {
+        app.update();
+        let fired = &app.world().resource::<FiredAt>().0;
+        if let (Some(lift), Some(swing)) = (fired.get("lift_bar"), fired.get("swing_open")) {
+            return swing - lift;
+        }
+    }
+    panic!("the timeline never finished");
+}
+
+#[test]
+fn test_fixed_waits_take_whole_ticks() {
+    for step in [Duration::from_micros(15_625), Duration::from_millis(150)] {
+        let expected = (WAIT / step.as_secs_f64()).ceil() as u32;
+        for frame in [
+            Duration::from_millis(10),
+            step,
+            Duration::from_millis(40),
+            Duration::from_millis(70),
+        ] {
+            assert_eq!(
+                ticks_waited(step, frame),
+                expected,
+                "step {step:?}, frame {frame:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_waits_hold_the_dialogue_until_the_last_tick() {
+    let step = Duration::from_micros(15_625);
+    let mut app = setup_app(step, step);
+    let lift = loop {
+        app.update();
+        if let Some(tick) = app.world().resource::<FiredAt>().0.get("lift_bar") {
+            break *tick;
+        }
+    };
+    while app.world().resource::<Ticks>().0 < lift + 31 {
+        app.update();
+    }
+    assert!(app.world().resource::<MortarRunsExecuting>().executing);
+    assert!(
+        !app.world()
+            .resource::<FiredAt>()
+            .0
+            .contains_key("swing_open")
+    );
+
+    app.update();
+    assert_eq!(app.world().resource::<FiredAt>().0["swing_open"], lift + 32);
+}
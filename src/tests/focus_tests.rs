@@ -0,0 +1,194 @@
+//! Covers focus between two dialogue instances: only the focused one is presented and takes user
+//! input, a new instance steals, queues behind or is refused focus by the contention policy, focus
+//! passes on when released or when its holder ends, and a paused unfocused dialogue keeps its jump
+//! until it is focused.
+//!
+//! 覆盖两个对话实例之间的焦点：只有拥有焦点的实例会被呈现并接受用户输入；新实例按争用策略夺取焦点、
+//! 排队等待或被拒绝；焦点在释放或持有者结束时移交；暂停的无焦点对话会保留其跳转直到获得焦点。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use mortar_compiler::Deserializer;
+
+const PATH: &str = "plaza.mortar";
+
+#[derive(Resource, Default)]
+struct Handoffs(Vec<MortarFocusChanged>);
+
+fn record(mut handoffs: ResMut<Handoffs>, mut messages: MessageReader<MortarFocusChanged>) {
+    handoffs.0.extend(messages.read().cloned());
+}
+
+fn plaza_asset() -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [
+            { "name": "Baker", "content": [
+                { "type": "text", "value": "Fresh bread!" },
+                { "type": "text", "value": "Two coins." }
+            ] },
+            { "name": "Guard", "content": [
+                { "type": "text", "value": "Halt." },
+                { "type": "text", "value": "Move along." }
+            ] },
+            { "name": "Gate", "content": [{ "type": "text", "value": "The gate opens." }] }
+        ],
+        "functions": []
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+fn run(app: &mut App) {
+    for _ in 0..3 {
+        app.update();
+    }
+}
+
+fn send(app: &mut App, event: MortarCommand) {
+    app.world_mut().write_message(event);
+    run(app);
+}
+
+/// Starts the baker, then the guard under `contention`.
+fn setup_app(contention: FocusContention) -> (App, Entity, Entity) {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+    ))
+    .init_resource::<Handoffs>()
+    .add_systems(Last, record);
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(plaza_asset());
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+    app.world_mut()
+        .resource_mut::<MortarRuntime>()
+        .focus_contention = contention;
+    app.world_mut().spawn((Text::new(""), MortarTextTarget));
+    let baker = app.world_mut().spawn_empty().id();
+    let guard = app.world_mut().spawn_empty().id();
+    send(
+        &mut app,
+        MortarCommand::start_node_for(baker, PATH, "Baker"),
+    );
+    send(
+        &mut app,
+        MortarCommand::start_node_for(guard, PATH, "Guard"),
+    );
+    (app, baker, guard)
+}
+
+fn rendered_text(app: &mut App) -> String {
+    let mut query = app
+        .world_mut()
+        .query_filtered::<&MortarDialogueText, With<MortarTextTarget>>();
+    query.single(app.world()).unwrap().body.clone()
+}
+
+fn text_index(app: &App, entity: Entity) -> usize {
+    app.world()
+        .resource::<MortarRuntime>()
+        .get_dialogue(entity)
+        .expect("dialogue should be active")
+        .text_index
+}
+
+fn focus(app: &App) -> &MortarFocus {
+    app.world().resource::<MortarFocus>()
+}
+
+#[test]
+fn test_only_the_focused_dialogue_is_presented_and_advanced() {
+    let (mut app, baker, guard) = setup_app(FocusContention::Steal);
+    assert!(focus(&app).is_focused(guard));
+    assert_eq!(focus(&app).queued(), [baker]);
+    assert_eq!(rendered_text(&mut app), "Halt.");
+
+    send(&mut app, MortarCommand::next_text_for(baker));
+    assert_eq!(text_index(&app, baker), 0);
+    send(&mut app, MortarCommand::next_text());
+    assert_eq!(text_index(&app, guard), 1);
+    assert_eq!(rendered_text(&mut app), "Move along.");
+
+    let next = app
+        .world_mut()
+        .resource_mut::<MortarRuntime>()
+        .release_focus();
+    assert_eq!(next, Some(baker));
+    send(&mut app, MortarCommand::next_text_for(guard));
+    assert_eq!(text_index(&app, guard), 1);
+    assert_eq!(rendered_text(&mut app), "Fresh bread!");
+
+    let handoffs = &app.world().resource::<Handoffs>().0;
+    assert_eq!(
+        handoffs,
+        &[
+            MortarFocusChanged {
+                previous: None,
+                current: Some(baker),
+            },
+            MortarFocusChanged {
+                previous: Some(baker),
+                current: Some(guard),
+            },
+            MortarFocusChanged {
+                previous: Some(guard),
+                current: Some(baker),
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_queued_dialogue_is_focused_when_the_holder_ends() {
+    let (mut app, baker, guard) = setup_app(FocusContention::Queue);
+    assert!(focus(&app).is_focused(baker));
+    assert_eq!(focus(&app).queued(), [guard]);
+    assert_eq!(rendered_text(&mut app), "Fresh bread!");
+
+    send(&mut app, MortarCommand::stop_dialogue_for(baker));
+    assert!(focus(&app).is_focused(guard));
+    assert!(focus(&app).queued().is_empty());
+    assert_eq!(rendered_text(&mut app), "Halt.");
+}
+
+#[test]
+fn test_rejected_dialogue_stays_unfocused() {
+    let (mut app, baker, guard) = setup_app(FocusContention::Reject);
+    assert!(focus(&app).is_focused(baker));
+    assert!(focus(&app).queued().is_empty());
+    let request = app
+        .world_mut()
+        .resource_mut::<MortarRuntime>()
+        .request_focus(guard);
+    assert_eq!(request, FocusRequest::Rejected);
+
+    send(&mut app, MortarCommand::stop_dialogue_for(baker));
+    assert_eq!(focus(&app).holder(), None);
+}
+
+#[test]
+fn test_paused_dialogue_keeps_its_jump_until_focused() {
+    let (mut app, baker, _) = setup_app(FocusContention::Steal);
+    app.world_mut()
+        .resource_mut::<MortarRuntime>()
+        .set_unfocused_behavior(baker, UnfocusedBehavior::Pause);
+    send(&mut app, MortarCommand::jump_to_node_for(baker, "Gate"));
+    let runtime = app.world().resource::<MortarRuntime>();
+    assert!(runtime.pending_jumps.contains_key(&baker));
+    assert_eq!(runtime.get_dialogue(baker).unwrap().current_node, "Baker");
+
+    app.world_mut()
+        .resource_mut::<MortarRuntime>()
+        .release_focus();
+    run(&mut app);
+    let runtime = app.world().resource::<MortarRuntime>();
+    assert_eq!(runtime.get_dialogue(baker).unwrap().current_node, "Gate");
+}
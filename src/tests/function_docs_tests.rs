@@ -0,0 +1,109 @@
+//! Covers the function docs `#[mortar_functions]` generates: documented and undocumented methods
+//! both get a manifest entry with their argument names, Mortar types and return type, the call
+//! context is not listed as an argument, binding installs the docs on the registry, and the
+//! Markdown reference groups them by source struct.
+//!
+//! 覆盖 `#[mortar_functions]` 生成的函数文档：有文档与无文档的方法都会得到包含参数名称、Mortar 类型
+//! 与返回类型的清单条目；调用上下文不会作为参数列出；绑定会把文档安装到注册表上；Markdown 参考按
+//! 来源结构体分组。
+
+use crate::*;
+use std::borrow::Cow;
+
+#[derive(MortarFunctions)]
+struct Shop;
+
+#[mortar_functions]
+impl Shop {
+    /// Whether the player carries `item`.
+    ///
+    /// Counts the backpack only.
+    fn has_item(item: MortarString) -> bool {
+        item.as_str() == "lamp"
+    }
+
+    fn buy(_context: &MortarCallContext, item: MortarString, count: MortarNumber) {
+        let _ = (item, count);
+    }
+}
+
+#[derive(MortarFunctions)]
+struct Weather;
+
+#[mortar_functions]
+impl Weather {
+    /// Hours of daylight left.
+    fn daylight() -> f64 {
+        6.0
+    }
+}
+
+fn arg(name: &'static str, ty: &'static str) -> MortarFnArgDoc {
+    MortarFnArgDoc {
+        name: Cow::Borrowed(name),
+        ty: Cow::Borrowed(ty),
+    }
+}
+
+#[test]
+fn test_documented_and_undocumented_methods_get_entries() {
+    let docs = Shop::MORTAR_FUNCTION_DOCS;
+    assert_eq!(docs.len(), 2);
+
+    assert_eq!(docs[0].source, "Shop");
+    assert_eq!(docs[0].name, "has_item");
+    assert_eq!(docs[0].args.as_ref(), [arg("item", "String")]);
+    assert_eq!(docs[0].returns, "Boolean");
+    assert_eq!(
+        docs[0].description,
+        "Whether the player carries `item`.\n\nCounts the backpack only."
+    );
+
+    assert_eq!(docs[1].name, "buy");
+    assert_eq!(
+        docs[1].args.as_ref(),
+        [arg("item", "String"), arg("count", "Number")]
+    );
+    assert_eq!(docs[1].returns, "Void");
+    assert_eq!(docs[1].description, "");
+    assert_eq!(docs[1].signature(), "buy(item: String, count: Number)");
+}
+
+#[test]
+fn test_binding_installs_the_docs() {
+    let mut functions = MortarFunctionRegistry::new();
+    Shop::register(&mut functions);
+    Weather::register(&mut functions);
+    let names: Vec<&str> = functions
+        .docs()
+        .iter()
+        .map(|doc| doc.name.as_ref())
+        .collect();
+    assert_eq!(names, ["has_item", "buy", "daylight"]);
+
+    // Binding again replaces the entries instead of repeating them.
+    //
+    // 再次绑定会替换条目而不是重复添加。
+    Shop::register(&mut functions);
+    assert_eq!(functions.docs().len(), 3);
+
+    assert!(functions.unregister("buy"));
+    assert!(functions.docs().iter().all(|doc| doc.name != "buy"));
+}
+
+#[test]
+fn test_markdown_groups_functions_by_source() {
+    let mut docs = Shop::MORTAR_FUNCTION_DOCS.to_vec();
+    docs.extend_from_slice(Weather::MORTAR_FUNCTION_DOCS);
+    assert_eq!(
+        render_markdown(&docs),
+        "# Mortar functions\n\
+         \n## Shop\n\
+         \n### `has_item(item: String) -> Boolean`\n\
+         \nWhether the player carries `item`.\n\nCounts the backpack only.\n\
+         \n### `buy(item: String, count: Number)`\n\
+         \n## Weather\n\
+         \n### `daylight() -> Number`\n\
+         \nHours of daylight left.\n"
+    );
+}
@@ -0,0 +1,104 @@
+//! Covers binding `&self` and `&mut self` methods through `#[mortar_functions]`: a void method
+//! changes the shared instance and a later call sees it, the game reads the same state through its
+//! own handle, methods may take the call context, and `bind_functions` alone binds only the
+//! associated functions.
+//!
+//! 覆盖通过 `#[mortar_functions]` 绑定 `&self` 与 `&mut self` 方法：无返回值的方法修改共享实例，之后
+//! 的调用能看到修改；游戏通过自己持有的句柄读取同一状态；方法可以接收调用上下文；单独的
+//! `bind_functions` 只绑定关联函数。
+
+use crate::*;
+use std::sync::{Arc, Mutex};
+
+#[derive(MortarFunctions, Default)]
+struct Inventory {
+    items: Vec<String>,
+    last_node: Option<String>,
+}
+
+#[mortar_functions]
+impl Inventory {
+    /// Whether the backpack holds `item`.
+    fn has_item(&self, item: MortarString) -> bool {
+        self.items.iter().any(|held| held == item.as_str())
+    }
+
+    fn give(&mut self, item: MortarString) {
+        self.items.push(item.as_str().to_owned());
+    }
+
+    fn note_node(&mut self, context: &MortarCallContext) {
+        self.last_node = context.node.clone();
+    }
+
+    fn capacity() -> f64 {
+        8.0
+    }
+}
+
+fn call(functions: &MortarFunctionRegistry, name: &str, args: &[MortarValue]) -> MortarValue {
+    functions
+        .call(name, args)
+        .unwrap_or_else(|| panic!("`{name}` should be bound"))
+}
+
+#[test]
+fn test_void_method_changes_state_a_later_call_sees() {
+    let inventory = Arc::new(Mutex::new(Inventory::default()));
+    let mut functions = MortarFunctionRegistry::new();
+    Inventory::bind_functions_with(&mut functions, Arc::clone(&inventory));
+
+    let lamp = [MortarValue::from("lamp")];
+    assert_eq!(
+        call(&functions, "has_item", &lamp).as_bool(),
+        Some(false.into())
+    );
+    assert!(matches!(call(&functions, "give", &lamp), MortarValue::Void));
+    assert_eq!(
+        call(&functions, "has_item", &lamp).as_bool(),
+        Some(true.into())
+    );
+    assert_eq!(inventory.lock().unwrap().items, ["lamp"]);
+
+    // The game's handle and the script see the same instance.
+    //
+    // 游戏持有的句柄与脚本看到的是同一个实例。
+    inventory.lock().unwrap().items.clear();
+    assert_eq!(
+        call(&functions, "has_item", &lamp).as_bool(),
+        Some(false.into())
+    );
+    assert_eq!(
+        call(&functions, "capacity", &[]).as_number(),
+        Some(MortarNumber(8.0))
+    );
+}
+
+#[test]
+fn test_method_receives_the_call_context() {
+    let inventory = Arc::new(Mutex::new(Inventory::default()));
+    let mut functions = MortarFunctionRegistry::new();
+    Inventory::bind_functions_with(&mut functions, Arc::clone(&inventory));
+
+    let context = MortarCallContext::at("shop.mortar", "Shop", 0);
+    functions.call_with_context(&context, "note_node", &[]);
+    assert_eq!(inventory.lock().unwrap().last_node.as_deref(), Some("Shop"));
+}
+
+#[test]
+fn test_bind_functions_skips_methods_with_a_receiver() {
+    let mut functions = MortarFunctionRegistry::new();
+    Inventory::register(&mut functions);
+    assert!(functions.contains("capacity"));
+    assert!(!functions.contains("has_item"));
+    let documented: Vec<&str> = functions
+        .docs()
+        .iter()
+        .map(|doc| doc.name.as_ref())
+        .collect();
+    assert_eq!(documented, ["capacity"]);
+
+    Inventory::bind_functions_with(&mut functions, Arc::default());
+    assert!(functions.contains("has_item"));
+    assert_eq!(functions.docs().len(), 4);
+}
@@ -0,0 +1,130 @@
+//! Covers introspecting the function registry: registering and unregistering round trips through
+//! `contains` and `names`, an unregistered function is no longer callable, a plain `register` drops
+//! the arity an earlier `register_with_arity` recorded, and validation against a script's
+//! declarations lists declared functions without a binding and bindings without a declaration.
+//!
+//! 覆盖函数注册表的内省：注册与注销通过 `contains` 与 `names` 往返；已注销的函数不能再被调用；
+//! 普通的 `register` 会丢弃先前 `register_with_arity` 记录的参数个数；针对脚本声明的校验会列出
+//! 没有绑定的已声明函数以及没有声明的绑定。
+
+use crate::*;
+use mortar_compiler::Deserializer;
+
+fn declarations() -> Vec<mortar_compiler::Function> {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [],
+        "functions": [
+            { "name": "has_item", "params": [{ "name": "item", "type": "String" }], "return": "Boolean" },
+            { "name": "play_sound", "params": [{ "name": "path", "type": "String" }] },
+            { "name": "gold", "params": [], "return": "Number" }
+        ]
+    });
+    Deserializer::from_json(&json.to_string())
+        .expect("fixture should deserialize")
+        .functions
+}
+
+fn sorted_names(functions: &MortarFunctionRegistry) -> Vec<&str> {
+    let mut names: Vec<&str> = functions.names().collect();
+    names.sort_unstable();
+    names
+}
+
+#[test]
+fn test_register_unregister_round_trip() {
+    let mut functions = MortarFunctionRegistry::new();
+    functions.register("gold", |_| MortarValue::from(3.0));
+    functions.register_with_arity("roll", 2, |_| MortarValue::from(4.0));
+    assert!(functions.contains("gold"));
+    assert_eq!(sorted_names(&functions), ["gold", "roll"]);
+
+    let generation = functions.generation();
+    assert!(functions.unregister("roll"));
+    assert!(!functions.unregister("roll"));
+    assert!(!functions.contains("roll"));
+    assert_eq!(functions.generation(), generation + 1);
+    assert_eq!(sorted_names(&functions), ["gold"]);
+    assert!(functions.call("roll", &[]).is_none());
+    assert!(functions.export_manifest().get("roll").is_none());
+
+    functions.register("roll", |_| MortarValue::from(6.0));
+    assert_eq!(
+        functions
+            .call("roll", &[])
+            .and_then(|value| value.as_number()),
+        Some(MortarNumber(6.0))
+    );
+}
+
+#[test]
+fn test_plain_register_drops_the_old_arity() {
+    let mut functions = MortarFunctionRegistry::new();
+    functions.register_with_arity("roll", 2, |_| MortarValue::from(4.0));
+    assert_eq!(
+        functions.export_manifest().get("roll").unwrap().arity,
+        Some(2)
+    );
+    functions.register("roll", |_| MortarValue::from(6.0));
+    assert_eq!(functions.export_manifest().get("roll").unwrap().arity, None);
+}
+
+#[test]
+fn test_scoped_bindings_are_not_global() {
+    let mut functions = MortarFunctionRegistry::new();
+    functions.register_scoped("inn.mortar", "gold", |_| MortarValue::from(1.0));
+    assert!(!functions.contains("gold"));
+    assert!(functions.names().next().is_none());
+    assert!(!functions.unregister("gold"));
+    assert!(functions.scoped("inn.mortar").contains("gold"));
+}
+
+#[test]
+fn test_validation_against_declarations() {
+    let mut functions = MortarFunctionRegistry::new();
+    functions.register("gold", |_| MortarValue::from(3.0));
+    functions.register_with_arity("roll", 2, |_| MortarValue::from(4.0));
+    functions.register("debug_dump", |_| MortarValue::Void);
+
+    let missing = functions.validate_against(&declarations());
+    let summary: Vec<(&str, &str, MissingBindingKind)> = missing
+        .iter()
+        .map(|missing| {
+            (
+                missing.name.as_str(),
+                missing.signature.as_str(),
+                missing.kind,
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            (
+                "has_item",
+                "has_item(item: String) -> Boolean",
+                MissingBindingKind::Unbound
+            ),
+            (
+                "play_sound",
+                "play_sound(path: String)",
+                MissingBindingKind::Unbound
+            ),
+            (
+                "debug_dump",
+                "debug_dump(..)",
+                MissingBindingKind::Undeclared
+            ),
+            ("roll", "roll(_, _)", MissingBindingKind::Undeclared),
+        ]
+    );
+}
+
+#[test]
+fn test_fully_bound_declarations_validate_clean() {
+    let mut functions = MortarFunctionRegistry::new();
+    for decl in declarations() {
+        functions.register(decl.name, |_| MortarValue::Void);
+    }
+    assert!(functions.validate_against(&declarations()).is_empty());
+}
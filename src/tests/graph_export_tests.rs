@@ -0,0 +1,116 @@
+//! Checks the conversation-flow graph export against the bundled `basic.mortared`
+//! fixture. The assertions pin down specific edges, labels, and terminal shapes so
+//! documentation graphs stay stable while the exporter evolves.
+//!
+//! 使用自带的 `basic.mortared` fixture 检查对话流程图导出。断言固定了具体的边、
+//! 标签和结束状态形状，保证导出器演进时文档用图依旧稳定。
+
+use crate::{GraphFormat, MortarAsset};
+use mortar_compiler::Deserializer;
+
+fn basic_asset() -> MortarAsset {
+    let data = Deserializer::from_json(include_str!("../../assets/basic.mortared"))
+        .expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+fn node_id(asset: &MortarAsset, name: &str) -> String {
+    let idx = asset
+        .data
+        .nodes
+        .iter()
+        .position(|node| node.name == name)
+        .expect("node exists in fixture");
+    format!("n{idx}")
+}
+
+#[test]
+fn test_export_dot_contains_edges_and_labels() {
+    let asset = basic_asset();
+    let dot = asset.export_graph(GraphFormat::Dot);
+
+    let start = node_id(&asset, "Start");
+    let choice_point = node_id(&asset, "ChoicePoint");
+    let forest = node_id(&asset, "ForestScene");
+    let town = node_id(&asset, "TownScene");
+    let apple = node_id(&asset, "EatApple");
+
+    assert!(dot.starts_with("digraph mortar {"));
+    assert!(dot.contains(&format!("{start} -> {choice_point};")));
+    assert!(dot.contains(&format!("{choice_point} -> {forest} [label=\"探索森林\"];")));
+    assert!(
+        dot.contains(&format!(
+            "{choice_point} -> {town} [label=\"留在城里\", style=dashed, color=gray50];"
+        )),
+        "conditional choices should be dashed: {dot}"
+    );
+    assert!(dot.contains(&format!(
+        "{choice_point} -> {apple} [label=\"吃点什么 / 苹果\"];"
+    )));
+    assert!(dot.contains(&format!(
+        "{choice_point} -> terminal [label=\"别朝我叭叭了！！\"];"
+    )));
+    assert!(dot.contains("terminal [label=\"END\", shape=doublecircle];"));
+    assert!(dot.contains("texts: 3"));
+}
+
+#[test]
+fn test_export_mermaid_contains_edges_and_labels() {
+    let asset = basic_asset();
+    let mermaid = asset.export_graph(GraphFormat::Mermaid);
+
+    let choice_point = node_id(&asset, "ChoicePoint");
+    let inventory = node_id(&asset, "Inventory");
+
+    assert!(mermaid.starts_with("flowchart TD\n"));
+    assert!(mermaid.contains(&format!("{choice_point} -.->|\"查看背包\"| {inventory}")));
+    assert!(mermaid.contains("terminal((\"END\"))"));
+    assert!(mermaid.contains("ChoicePoint<br/>texts: 2, choices"));
+}
+
+#[test]
+fn test_export_marks_external_targets_and_escapes_labels() {
+    let mut asset = basic_asset();
+    let start = asset
+        .data
+        .nodes
+        .iter_mut()
+        .find(|node| node.name == "Start")
+        .expect("Start node exists");
+    start.next = Some("OtherFile\"Node".to_string());
+
+    let dot = asset.export_graph(GraphFormat::Dot);
+    assert!(dot.contains("x0 [label=\"OtherFile\\\"Node\", shape=box, style=dashed];"));
+
+    let mermaid = asset.export_graph(GraphFormat::Mermaid);
+    assert!(mermaid.contains("x0[/\"OtherFile#quot;Node\"/]"));
+}
+
+#[test]
+fn test_export_keeps_long_external_targets_with_a_shared_prefix_apart() {
+    let mut asset = basic_asset();
+    let prefix = "chapter_two_harbor_district_night_market";
+    let targets = [
+        ("Start", format!("{prefix}_fishmonger")),
+        ("ForestScene", format!("{prefix}_lantern_seller")),
+        ("TownScene", format!("{prefix}_fishmonger")),
+    ];
+    for (name, target) in &targets {
+        let node = asset
+            .data
+            .nodes
+            .iter_mut()
+            .find(|node| node.name == *name)
+            .expect("node exists in fixture");
+        node.next = Some(target.clone());
+    }
+    let start = node_id(&asset, "Start");
+    let forest = node_id(&asset, "ForestScene");
+    let town = node_id(&asset, "TownScene");
+
+    let dot = asset.export_graph(GraphFormat::Dot);
+    assert!(dot.contains(&format!("{start} -> x0;")));
+    assert!(dot.contains(&format!("{forest} -> x1;")));
+    assert!(dot.contains(&format!("{town} -> x0;")), "{dot}");
+    assert!(!dot.contains("x2 ["));
+}
@@ -0,0 +1,143 @@
+//! Covers header resolution: a line's `header` beats its node's, which beats
+//! [`MortarHeaderSettings`], an empty header removes it, placeholders in headers are interpolated,
+//! and [`MortarHeaderChanged`] fires only when the shown header really changes.
+//!
+//! 覆盖头部解析：行的 `header` 优先于节点的，节点的又优先于 [`MortarHeaderSettings`]；空头部表示移除；
+//! 头部中的占位符会被插值；[`MortarHeaderChanged`] 只在显示的头部真正变化时发出。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use std::path::Path;
+
+const PATH: &str = "header.mortared";
+
+#[derive(Resource, Default)]
+struct HeaderChanges(Vec<String>);
+
+fn record_changes(
+    mut events: MessageReader<MortarHeaderChanged>,
+    mut changes: ResMut<HeaderChanges>,
+) {
+    changes
+        .0
+        .extend(events.read().map(|event| event.header.clone()));
+}
+
+fn header_asset() -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [
+            {
+                "name": "Harbor",
+                "header": "— {location}, Night —",
+                "content": [
+                    { "type": "text", "value": "Waves" },
+                    { "type": "text", "value": "Gulls" },
+                    { "type": "text", "value": "The storm", "header": "Chapter Three" },
+                    { "type": "text", "value": "Silence", "header": "" }
+                ]
+            },
+            { "name": "Plain", "content": [{ "type": "text", "value": "Hi" }] }
+        ],
+        "functions": [],
+        "variables": [{ "name": "location", "type": "String", "value": "Harbor" }]
+    });
+    MortarAssetLoader::load_asset_bytes(json.to_string().as_bytes(), Path::new(PATH))
+        .expect("fixture should load")
+}
+
+fn setup_app(settings: MortarHeaderSettings, node: &str) -> (App, Entity) {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+    ))
+    .insert_resource(settings)
+    .init_resource::<HeaderChanges>()
+    .add_systems(Last, record_changes);
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(header_asset());
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+    let target = app
+        .world_mut()
+        .spawn((Text::new(""), MortarTextTarget))
+        .id();
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, node));
+    for _ in 0..3 {
+        app.update();
+    }
+    (app, target)
+}
+
+fn shown(app: &App, entity: Entity) -> (String, String) {
+    let text = app.world().get::<MortarDialogueText>(entity).unwrap();
+    (text.header.clone(), text.body.clone())
+}
+
+fn advance(app: &mut App) {
+    app.world_mut().write_message(MortarCommand::next_text());
+    for _ in 0..3 {
+        app.update();
+    }
+}
+
+#[test]
+fn test_node_header_is_interpolated() {
+    let (app, target) = setup_app(MortarHeaderSettings::default(), "Harbor");
+    assert_eq!(
+        shown(&app, target),
+        ("— Harbor, Night —\n\n".to_owned(), "Waves".to_owned())
+    );
+    assert_eq!(
+        app.world().get::<Text>(target).unwrap().0,
+        "— Harbor, Night —\n\nWaves"
+    );
+}
+
+#[test]
+fn test_line_header_beats_node_header() {
+    let (mut app, target) = setup_app(MortarHeaderSettings::default(), "Harbor");
+    advance(&mut app);
+    assert_eq!(shown(&app, target).0, "— Harbor, Night —\n\n");
+    advance(&mut app);
+    assert_eq!(
+        shown(&app, target),
+        ("Chapter Three\n\n".to_owned(), "The storm".to_owned())
+    );
+    advance(&mut app);
+    assert_eq!(shown(&app, target), (String::new(), "Silence".to_owned()));
+
+    // "Gulls" kept the node header, so it raised no change.
+    //
+    // "Gulls" 沿用了节点头部，因此没有发出变化消息。
+    let changes = &app.world().resource::<HeaderChanges>().0;
+    assert_eq!(changes, &["— Harbor, Night —", "Chapter Three", ""]);
+}
+
+#[test]
+fn test_plugin_format_applies_without_overrides() {
+    let (app, target) = setup_app(MortarHeaderSettings::default(), "Plain");
+    assert_eq!(shown(&app, target).0, "[header.mortared / Plain]\n\n");
+
+    let settings = MortarHeaderSettings {
+        format: "{node}:".to_owned(),
+        separator: " ".to_owned(),
+    };
+    let (app, target) = setup_app(settings, "Plain");
+    assert_eq!(app.world().get::<Text>(target).unwrap().0, "Plain: Hi");
+
+    let settings = MortarHeaderSettings {
+        format: String::new(),
+        ..default()
+    };
+    let (app, target) = setup_app(settings, "Plain");
+    assert_eq!(shown(&app, target), (String::new(), "Hi".to_owned()));
+    assert!(app.world().resource::<HeaderChanges>().0.is_empty());
+}
@@ -0,0 +1,79 @@
+//! Covers a headless conversation: with no text target and whatever features are enabled, the
+//! dialogue plugin still starts a node, advances through its lines on `NextText` and follows the
+//! node's `next` into the following node. The test runs under `--no-default-features` as well, so
+//! it doubles as the check that the core works without Bevy's UI and audio.
+//!
+//! 覆盖无界面的对话：没有文本目标且无论启用哪些特性，对话插件仍会开始节点、在 `NextText` 时逐行
+//! 推进，并沿节点的 `next` 进入下一个节点。该测试同样在 `--no-default-features` 下运行，因此也用于
+//! 检查核心在没有 Bevy UI 与音频时能否工作。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use mortar_compiler::Deserializer;
+
+const PATH: &str = "server.mortar";
+
+fn server_asset() -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [
+            {
+                "name": "Start",
+                "content": [
+                    { "type": "text", "value": "Hello" },
+                    { "type": "text", "value": "Still here" }
+                ],
+                "next": "End"
+            },
+            { "name": "End", "content": [{ "type": "text", "value": "Bye" }] }
+        ],
+        "functions": []
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+fn current(app: &App) -> Option<(String, String)> {
+    let state = app
+        .world()
+        .resource::<MortarRuntime>()
+        .primary_dialogue_state()?;
+    Some((state.current_node.clone(), state.current_text()?.to_owned()))
+}
+
+fn next_text(app: &mut App) {
+    app.world_mut().write_message(MortarCommand::next_text());
+    for _ in 0..3 {
+        app.update();
+    }
+}
+
+#[test]
+fn test_conversation_advances_without_text_targets() {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+    ));
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(server_asset());
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, "Start"));
+    for _ in 0..3 {
+        app.update();
+    }
+
+    let line = |node: &str, text: &str| Some((node.to_owned(), text.to_owned()));
+    assert_eq!(current(&app), line("Start", "Hello"));
+    next_text(&mut app);
+    assert_eq!(current(&app), line("Start", "Still here"));
+    next_text(&mut app);
+    assert_eq!(current(&app), line("End", "Bye"));
+}
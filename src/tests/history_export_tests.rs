@@ -0,0 +1,211 @@
+//! Covers the dialogue backlog and its partial export: shown lines are recorded once each,
+//! exports at several byte budgets stay under the cap and keep the newest entries, compact
+//! exports rebuild bodies from the loaded file or leave placeholders and keep the text of
+//! confirmed options, and a blob travels inside the save data.
+//!
+//! 覆盖对话回顾记录及其部分导出：显示过的行各记录一次；不同字节预算下的导出都不超过上限并保留最新的
+//! 记录；紧凑导出会根据已加载的文件重建正文，找不到时保留占位，并保留已确认选项的文本；导出数据可以
+//! 随存档一起保存。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use mortar_compiler::Deserializer;
+
+const PATH: &str = "tavern.mortar";
+const LINES: usize = 12;
+
+fn line(index: usize) -> String {
+    format!("Line {index}: {}", "la".repeat(index))
+}
+
+fn tavern_asset() -> MortarAsset {
+    let content: Vec<_> = (0..LINES)
+        .map(|index| serde_json::json!({ "type": "text", "value": line(index) }))
+        .collect();
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [{ "name": "Start", "content": content }],
+        "functions": []
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+fn setup_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+    ));
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(tavern_asset());
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+    app.world_mut().spawn((Text::new(""), MortarTextTarget));
+    app.world_mut().spawn((Text::new(""), MortarTextTarget));
+    app
+}
+
+/// Plays every line so the backlog holds the whole node.
+fn play_all(app: &mut App) {
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, "Start"));
+    for _ in 0..LINES {
+        for _ in 0..3 {
+            app.update();
+        }
+        app.world_mut().write_message(MortarCommand::next_text());
+    }
+}
+
+fn bodies(history: &MortarDialogueHistory) -> Vec<Option<String>> {
+    history
+        .entries()
+        .iter()
+        .map(|entry| entry.body.clone())
+        .collect()
+}
+
+fn import(app: &App, bytes: &[u8]) -> (MortarDialogueHistory, usize) {
+    let mut history = MortarDialogueHistory::default();
+    let placeholders = history
+        .import(
+            bytes,
+            app.world().resource::<MortarRegistry>(),
+            app.world().resource::<Assets<MortarAsset>>(),
+        )
+        .expect("export should import");
+    (history, placeholders)
+}
+
+#[test]
+fn test_shown_lines_are_recorded_once() {
+    let mut app = setup_app();
+    play_all(&mut app);
+    let history = app.world().resource::<MortarDialogueHistory>();
+    let expected: Vec<_> = (0..LINES).map(|index| Some(line(index))).collect();
+    assert_eq!(bodies(history), expected);
+    assert!(history.entries().iter().all(|entry| entry.node == "Start"));
+}
+
+#[test]
+fn test_budgets_are_respected_and_keep_newest_entries() {
+    let mut app = setup_app();
+    play_all(&mut app);
+    let history = app.world().resource::<MortarDialogueHistory>();
+    let all = bodies(history);
+
+    for fields in [MortarHistoryFields::Full, MortarHistoryFields::LineIds] {
+        let mut previous = 0;
+        for max_bytes in [14, 200, 600, 1200, usize::MAX] {
+            let policy = MortarHistoryExportPolicy {
+                max_bytes: Some(max_bytes),
+                fields,
+                ..default()
+            };
+            let bytes = history.export(&policy);
+            assert!(bytes.len() <= max_bytes, "{} > {max_bytes}", bytes.len());
+
+            let (imported, placeholders) = import(&app, &bytes);
+            let kept = bodies(&imported);
+            assert_eq!(placeholders, 0);
+            assert_eq!(kept, all[all.len() - kept.len()..]);
+            assert!(kept.len() >= previous);
+            previous = kept.len();
+        }
+        assert_eq!(previous, LINES);
+    }
+
+    let policy = MortarHistoryExportPolicy {
+        max_entries: Some(3),
+        ..default()
+    };
+    let (imported, _) = import(&app, &history.export(&policy));
+    assert_eq!(bodies(&imported), all[LINES - 3..]);
+}
+
+#[test]
+fn test_compact_entries_without_their_file_become_placeholders() {
+    let mut app = setup_app();
+    play_all(&mut app);
+    let policy = MortarHistoryExportPolicy {
+        fields: MortarHistoryFields::LineIds,
+        ..default()
+    };
+    let bytes = app
+        .world()
+        .resource::<MortarDialogueHistory>()
+        .export(&policy);
+
+    let mut imported = MortarDialogueHistory::default();
+    let placeholders = imported
+        .import(
+            &bytes,
+            &MortarRegistry::default(),
+            &Assets::<MortarAsset>::default(),
+        )
+        .expect("export should import");
+    assert_eq!(placeholders, LINES);
+    assert!(imported.entries().iter().all(|entry| entry.body.is_none()));
+    assert!(
+        imported
+            .entries()
+            .iter()
+            .all(|entry| !entry.line_id.is_empty())
+    );
+}
+
+#[test]
+fn test_compact_exports_keep_confirmed_option_text() {
+    let app = setup_app();
+    let mut history = MortarDialogueHistory::default();
+    history.push(MortarDialogueHistoryEntry {
+        mortar_path: PATH.to_string(),
+        node: "Start".to_string(),
+        body: Some("Stay a while".to_string()),
+        choice: Some(1),
+        ..default()
+    });
+    let policy = MortarHistoryExportPolicy {
+        fields: MortarHistoryFields::LineIds,
+        ..default()
+    };
+    let (imported, placeholders) = import(&app, &history.export(&policy));
+    assert_eq!(placeholders, 0);
+    assert_eq!(imported.entries(), history.entries());
+}
+
+#[test]
+fn test_history_blob_travels_inside_save_data() {
+    let mut app = setup_app();
+    play_all(&mut app);
+    let policy = MortarHistoryExportPolicy {
+        max_bytes: Some(400),
+        ..default()
+    };
+    let blob = app
+        .world()
+        .resource::<MortarDialogueHistory>()
+        .export(&policy);
+    let save = MortarSaveData::capture(
+        app.world().resource::<MortarRuntime>(),
+        app.world().resource::<MortarDialogueVariables>(),
+    );
+    assert!(
+        !String::from_utf8(save.to_bytes())
+            .unwrap()
+            .contains("history")
+    );
+
+    let bytes = save.with_history(&blob).unwrap().to_bytes();
+    let loaded = MortarSaveData::from_bytes(&bytes, &MortarSaveMigrations::default()).unwrap();
+    let (imported, _) = import(&app, &loaded.history_blob().unwrap());
+    let (expected, _) = import(&app, &blob);
+    assert_eq!(imported.entries(), expected.entries());
+    assert!(!imported.entries().is_empty());
+}
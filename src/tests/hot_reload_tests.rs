@@ -0,0 +1,117 @@
+//! Covers hot reloading a file under an active dialogue: a changed node is rebuilt on the same
+//! line, a line that is gone moves the dialogue to the node's last line, a removed node stops the
+//! dialogue, and nothing is rebuilt when the plugin's `hot_reload` is off.
+//!
+//! 覆盖活跃对话所属文件的热重载：变化的节点在同一行上重建；当前行已不存在时对话移到节点最后一行；
+//! 节点被移除时对话停止；插件的 `hot_reload` 关闭时不会重建。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use mortar_compiler::Deserializer;
+
+const PATH: &str = "inn.mortar";
+
+fn inn_asset(node: &str, lines: &[&str]) -> MortarAsset {
+    let content: Vec<_> = lines
+        .iter()
+        .map(|line| serde_json::json!({ "type": "text", "value": line }))
+        .collect();
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [{ "name": node, "content": content }],
+        "functions": []
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+fn run(app: &mut App) {
+    for _ in 0..3 {
+        app.update();
+    }
+}
+
+/// Starts the inn on its third line.
+fn setup_app(hot_reload: bool) -> (App, Handle<MortarAsset>) {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin {
+            hot_reload,
+            ..default()
+        },
+        MortarDialoguePlugin::default(),
+    ));
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(inn_asset("Inn", &["Welcome.", "A room?", "Sleep well."]));
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle.clone());
+    app.world_mut().spawn((Text::new(""), MortarTextTarget));
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, "Inn"));
+    run(&mut app);
+    for _ in 0..2 {
+        app.world_mut().write_message(MortarCommand::next_text());
+        run(&mut app);
+    }
+    (app, handle)
+}
+
+fn reload(app: &mut App, handle: &Handle<MortarAsset>, asset: MortarAsset) {
+    app.world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .insert(handle.id(), asset)
+        .expect("asset should be replaced");
+    run(app);
+}
+
+fn current_line(app: &App) -> Option<(usize, String)> {
+    let state = app
+        .world()
+        .resource::<MortarRuntime>()
+        .primary_dialogue_state()?;
+    Some((state.text_index, state.current_text()?.to_owned()))
+}
+
+#[test]
+fn test_changed_node_is_rebuilt_on_the_same_line() {
+    let (mut app, handle) = setup_app(true);
+    assert_eq!(current_line(&app), Some((2, "Sleep well.".to_owned())));
+    let lines = [
+        "Welcome!",
+        "A room?",
+        "Sweet dreams.",
+        "Checkout is at noon.",
+    ];
+    reload(&mut app, &handle, inn_asset("Inn", &lines));
+    assert_eq!(current_line(&app), Some((2, "Sweet dreams.".to_owned())));
+}
+
+#[test]
+fn test_missing_line_moves_to_the_last_one() {
+    let (mut app, handle) = setup_app(true);
+    reload(&mut app, &handle, inn_asset("Inn", &["Closed."]));
+    assert_eq!(current_line(&app), Some((0, "Closed.".to_owned())));
+}
+
+#[test]
+fn test_removed_node_stops_the_dialogue() {
+    let (mut app, handle) = setup_app(true);
+    reload(&mut app, &handle, inn_asset("Tavern", &["Cheers."]));
+    assert!(
+        !app.world()
+            .resource::<MortarRuntime>()
+            .has_active_dialogues()
+    );
+}
+
+#[test]
+fn test_disabled_hot_reload_keeps_the_old_node() {
+    let (mut app, handle) = setup_app(false);
+    reload(&mut app, &handle, inn_asset("Inn", &["Closed."]));
+    assert_eq!(current_line(&app), Some((2, "Sleep well.".to_owned())));
+}
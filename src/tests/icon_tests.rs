@@ -0,0 +1,156 @@
+//! Checks inline icon tokens: each `{icon:NAME}` collapses to one placeholder char, so an event
+//! placed after two icons fires exactly when the reveal reaches it, and the icons are reported on
+//! [`MortarDialogueText`] with their char indices.
+//!
+//! 检查行内图标标记：每个 `{icon:NAME}` 折叠为一个占位字符，因此放在两个图标之后的事件恰好在逐字
+//! 显示到达该位置时触发，并且图标及其字符索引会记录在 [`MortarDialogueText`] 上。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use bevy::time::TimeUpdateStrategy;
+use mortar_compiler::Deserializer;
+use std::time::Duration;
+
+const PATH: &str = "icons.mortar";
+const P: char = DEFAULT_ICON_PLACEHOLDER;
+
+fn icon(char_index: usize, name: &str) -> InlineIcon {
+    InlineIcon {
+        char_index,
+        name: name.to_owned(),
+    }
+}
+
+fn icon_asset() -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [{
+            "name": "Start",
+            "content": [{
+                "type": "text",
+                "value": "Press {icon:button_a} or {icon:button_b} to jump",
+                "events": [{ "index": 12, "actions": [{ "type": "bell" }] }]
+            }]
+        }],
+        "functions": []
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+#[derive(Resource, Default)]
+struct Fired(Vec<(String, String)>);
+
+fn record_events(
+    mut events: MessageReader<MortarGameEvent>,
+    texts: Query<&Text, With<MortarTextTarget>>,
+    mut fired: ResMut<Fired>,
+) {
+    for event in events.read() {
+        let text = texts
+            .single()
+            .map(|text| text.0.clone())
+            .unwrap_or_default();
+        fired.0.push((event.name.clone(), text));
+    }
+}
+
+fn setup_app() -> (App, Entity) {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+    ))
+    .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+        100,
+    )))
+    .init_resource::<Fired>()
+    .add_systems(PostUpdate, record_events);
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(icon_asset());
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+    let target = app
+        .world_mut()
+        .spawn((Text::new(""), MortarTextTarget, MortarTextReveal::new(10.0)))
+        .id();
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, "Start"));
+    (app, target)
+}
+
+#[test]
+fn test_extract_inline_icons() {
+    let (body, icons) = extract_inline_icons("A{icon:x}b{icon:}{icon:y z}{icon:q", P);
+    assert_eq!(body, format!("A{P}b{{icon:}}{{icon:y z}}{{icon:q"));
+    assert_eq!(icons, [icon(1, "x")]);
+
+    let (body, icons) = extract_inline_icons("{icon:a}{icon:b}", '#');
+    assert_eq!(body, "##");
+    assert_eq!(icons, [icon(0, "a"), icon(1, "b")]);
+}
+
+#[test]
+fn test_event_after_two_icons_fires_at_its_index() {
+    let (mut app, target) = setup_app();
+    for _ in 0..40 {
+        app.update();
+    }
+
+    let dialogue_text = app.world().get::<MortarDialogueText>(target).unwrap();
+    assert_eq!(dialogue_text.body, format!("Press {P} or {P} to jump"));
+    assert_eq!(
+        dialogue_text.icons,
+        [icon(6, "button_a"), icon(11, "button_b")]
+    );
+
+    let fired = &app.world().resource::<Fired>().0;
+    assert_eq!(fired.len(), 1);
+    assert_eq!(fired[0].0, "bell");
+    assert_eq!(
+        fired[0].1,
+        format!("{}Press {P} or {P}", dialogue_text.header)
+    );
+}
+
+#[cfg(feature = "ui-icons")]
+#[test]
+fn test_icon_plugin_spawns_images_and_reports_unknown_names() {
+    let (mut app, target) = setup_app();
+    app.add_plugins(MortarIconPlugin);
+    app.world_mut()
+        .resource_mut::<MortarIconAtlas>()
+        .insert("button_a", Handle::default());
+    for _ in 0..3 {
+        app.update();
+    }
+
+    let world = app.world_mut();
+    let mut icons: Vec<_> = world
+        .query::<(&MortarInlineIcon, &ChildOf, Has<ImageNode>, Option<&Text>)>()
+        .iter(world)
+        .map(|(icon, parent, image, text)| {
+            assert_eq!(parent.parent(), target);
+            (icon.name.clone(), image, text.map(|text| text.0.clone()))
+        })
+        .collect();
+    icons.sort();
+    assert_eq!(
+        icons,
+        [
+            ("button_a".to_owned(), true, None),
+            (
+                "button_b".to_owned(),
+                false,
+                Some(MISSING_ICON_GLYPH.to_owned())
+            ),
+        ]
+    );
+    let unknown = &world.resource::<MortarIconDiagnostics>().unknown;
+    assert_eq!(unknown.iter().collect::<Vec<_>>(), ["button_b"]);
+}
@@ -0,0 +1,98 @@
+//! Covers the `MortarInternal` marker: the pending execution of a timeline that waits carries it
+//! while the wait lasts and is gone once the timeline completes, and `despawn_all` clears it.
+//!
+//! 覆盖 `MortarInternal` 标记：带等待的时间线的挂起执行在等待期间带有该标记，时间线完成后即被
+//! 移除；`despawn_all` 可以清除它。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use bevy::time::TimeUpdateStrategy;
+use mortar_compiler::Deserializer;
+use std::time::Duration;
+
+const PATH: &str = "internal.mortar";
+
+fn waiting_asset() -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [{
+            "name": "Start",
+            "content": [
+                { "type": "text", "value": "Wait for it" },
+                { "type": "run_event", "name": "Pause" },
+                { "type": "text", "value": "Done" }
+            ]
+        }],
+        "functions": [],
+        "events": [{ "name": "shake", "action": { "type": "shake" } }],
+        "timelines": [{ "name": "Pause", "statements": [
+            { "type": "run", "event_name": "shake" },
+            { "type": "wait", "duration": 0.5 },
+            { "type": "run", "event_name": "shake" }
+        ] }]
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+/// Starts the dialogue and advances onto the timeline.
+fn setup_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+    ))
+    .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+        100,
+    )));
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(waiting_asset());
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+    app.world_mut().spawn((Text::new(""), MortarTextTarget));
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, "Start"));
+    for _ in 0..3 {
+        app.update();
+    }
+    app.world_mut().write_message(MortarCommand::next_text());
+    for _ in 0..2 {
+        app.update();
+    }
+    app
+}
+
+fn internal_count(app: &mut App) -> usize {
+    app.world_mut()
+        .query_filtered::<Entity, With<MortarInternal>>()
+        .iter(app.world())
+        .count()
+}
+
+#[test]
+fn test_pending_timeline_is_marked_until_it_completes() {
+    let mut app = setup_app();
+    assert!(app.world().resource::<MortarRunsExecuting>().executing);
+    assert_eq!(internal_count(&mut app), 1);
+
+    for _ in 0..10 {
+        app.update();
+    }
+    assert!(!app.world().resource::<MortarRunsExecuting>().executing);
+    assert_eq!(internal_count(&mut app), 0);
+}
+
+#[test]
+fn test_despawn_all_clears_internal_entities() {
+    let mut app = setup_app();
+    assert_eq!(MortarInternal::despawn_all(app.world_mut()), 1);
+    assert_eq!(internal_count(&mut app), 0);
+
+    app.update();
+    assert!(!app.world().resource::<MortarRunsExecuting>().executing);
+}
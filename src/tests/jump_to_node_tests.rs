@@ -0,0 +1,94 @@
+//! Covers `MortarCommand::JumpToNode`: jumping mid-node moves the active dialogue to the first
+//! line of the target node of the same file, while a jump to a missing node or with no dialogue
+//! active is ignored.
+//!
+//! 覆盖 `MortarCommand::JumpToNode`：在节点中途跳转会把活跃对话移动到同一文件中目标节点的第一行；
+//! 跳转到不存在的节点或没有活跃对话时的跳转会被忽略。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use mortar_compiler::Deserializer;
+
+const PATH: &str = "quest.mortar";
+
+fn quest_asset() -> MortarAsset {
+    let text = |value: &str| serde_json::json!({ "type": "text", "value": value });
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [
+            { "name": "Start", "content": [text("One."), text("Two."), text("Three.")] },
+            { "name": "Ambush", "content": [text("Bandits!"), text("Run!")] }
+        ],
+        "functions": []
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+fn setup_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+    ));
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(quest_asset());
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+    app
+}
+
+fn send(app: &mut App, event: MortarCommand) {
+    app.world_mut().write_message(event);
+    for _ in 0..3 {
+        app.update();
+    }
+}
+
+fn position(app: &App) -> Option<(String, usize, String)> {
+    let state = app
+        .world()
+        .resource::<MortarRuntime>()
+        .primary_dialogue_state()?;
+    Some((
+        state.current_node.clone(),
+        state.text_index,
+        state.current_text_data()?.value.clone(),
+    ))
+}
+
+#[test]
+fn test_jump_mid_node_starts_target_at_first_line() {
+    let mut app = setup_app();
+    send(&mut app, MortarCommand::start_node(PATH, "Start"));
+    send(&mut app, MortarCommand::next_text());
+    assert_eq!(
+        position(&app),
+        Some(("Start".to_owned(), 1, "Two.".to_owned()))
+    );
+
+    send(&mut app, MortarCommand::jump_to_node("Ambush"));
+    assert_eq!(
+        position(&app),
+        Some(("Ambush".to_owned(), 0, "Bandits!".to_owned()))
+    );
+}
+
+#[test]
+fn test_jump_to_missing_node_or_without_dialogue_is_ignored() {
+    let mut app = setup_app();
+    send(&mut app, MortarCommand::jump_to_node("Ambush"));
+    assert_eq!(position(&app), None);
+
+    send(&mut app, MortarCommand::start_node(PATH, "Start"));
+    send(&mut app, MortarCommand::next_text());
+    send(&mut app, MortarCommand::jump_to_node("Nowhere"));
+    assert_eq!(
+        position(&app),
+        Some(("Start".to_owned(), 1, "Two.".to_owned()))
+    );
+}
@@ -0,0 +1,171 @@
+//! Covers keeping the last good version of a file: after a failed reload that left the asset
+//! missing, lookups resolve against the previous data and `ReloadFailed` is reported, a later
+//! successful reload replaces it and reports `ReloadSucceeded`, turning retention off only
+//! reports the failure, and compile errors carry the line and column.
+//!
+//! 覆盖保留文件最近一次成功加载的版本：重载失败并导致资源缺失后，查询会基于之前的数据解析并报告
+//! `ReloadFailed`；之后成功的重载会替换该版本并报告 `ReloadSucceeded`；关闭保留后只报告失败；
+//! 编译错误会携带行号与列号。
+
+use crate::*;
+use bevy::asset::{AssetLoadError, AssetLoadFailedEvent, AssetPlugin};
+use mortar_compiler::Deserializer;
+use std::path::Path;
+
+const PATH: &str = "pier.mortar";
+
+#[derive(Resource, Default)]
+struct Lifecycle(Vec<MortarLifecycleEvent>);
+
+fn record(mut recorded: ResMut<Lifecycle>, mut events: MessageReader<MortarLifecycleEvent>) {
+    recorded.0.extend(events.read().cloned());
+}
+
+fn pier_asset(lines: &[&str]) -> MortarAsset {
+    let content: Vec<_> = lines
+        .iter()
+        .map(|line| serde_json::json!({ "type": "text", "value": line }))
+        .collect();
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [{ "name": "Pier", "content": content }],
+        "functions": []
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+fn setup_app(retain_last_good: bool) -> (App, AssetId<MortarAsset>) {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin {
+            retain_last_good,
+            ..default()
+        },
+    ))
+    .init_resource::<Lifecycle>()
+    .add_systems(Last, record);
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(pier_asset(&["Gulls circle.", "A boat docks."]));
+    let id = handle.id();
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+    // Asset events reach the cache a frame after the asset was added.
+    //
+    // 资源事件会在资源添加后的下一帧到达缓存。
+    app.update();
+    app.update();
+    (app, id)
+}
+
+/// Drops the asset and reports a failed load for it, as a broken reload can leave it.
+fn fail_reload(app: &mut App, id: AssetId<MortarAsset>) {
+    app.world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .remove(id);
+    app.world_mut()
+        .write_message(AssetLoadFailedEvent::<MortarAsset> {
+            id,
+            path: PATH.into(),
+            error: AssetLoadError::AssetLoaderPanic {
+                path: PATH.into(),
+                loader_name: "MortarAssetLoader",
+            },
+        });
+    app.update();
+}
+
+fn first_line(app: &App, id: AssetId<MortarAsset>) -> Option<String> {
+    let asset = app.world().resource::<Assets<MortarAsset>>().get(id)?;
+    let lines = asset.line_ids("Pier")?;
+    lines.into_iter().next().map(|(_, text)| text)
+}
+
+fn lifecycle(app: &App) -> &[MortarLifecycleEvent] {
+    &app.world().resource::<Lifecycle>().0
+}
+
+#[test]
+fn test_failed_reload_keeps_serving_the_last_good_version() {
+    let (mut app, id) = setup_app(true);
+    assert!(lifecycle(&app).is_empty());
+    fail_reload(&mut app, id);
+
+    assert_eq!(first_line(&app, id).as_deref(), Some("Gulls circle."));
+    let [MortarLifecycleEvent::ReloadFailed { path, error }] = lifecycle(&app) else {
+        panic!("expected one failure, got {:?}", lifecycle(&app));
+    };
+    assert_eq!(path, PATH);
+    assert!(error.contains(PATH));
+
+    // Dialogue still starts from the restored data.
+    //
+    // 对话仍能基于恢复的数据启动。
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, "Pier"));
+    for _ in 0..3 {
+        app.update();
+    }
+    let runtime = app.world().resource::<MortarRuntime>();
+    let state = runtime
+        .primary_dialogue_state()
+        .expect("dialogue is active");
+    assert_eq!(state.text_items()[1].value, "A boat docks.");
+
+    app.world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .insert(id, pier_asset(&["The tide turns."]))
+        .expect("the id is still alive");
+    app.update();
+    app.update();
+    assert_eq!(first_line(&app, id).as_deref(), Some("The tide turns."));
+    assert!(matches!(
+        lifecycle(&app),
+        [
+            MortarLifecycleEvent::ReloadFailed { .. },
+            MortarLifecycleEvent::ReloadSucceeded { path }
+        ] if path == PATH
+    ));
+    let last_good = app.world().resource::<MortarLastGood>();
+    let cached = last_good.get(id).expect("the new version is cached");
+    assert_eq!(cached.nodes[0].content.len(), 1);
+}
+
+#[test]
+fn test_without_retention_failures_are_only_reported() {
+    let (mut app, id) = setup_app(false);
+    assert!(!app.world().resource::<MortarLastGood>().is_retaining());
+    fail_reload(&mut app, id);
+
+    assert_eq!(first_line(&app, id), None);
+    assert!(app.world().resource::<MortarLastGood>().get(id).is_none());
+    assert!(matches!(
+        lifecycle(&app),
+        [MortarLifecycleEvent::ReloadFailed { .. }]
+    ));
+}
+
+#[test]
+fn test_compile_errors_carry_line_and_column() {
+    let source = "node Pier {\n    text: \"Gulls circle.\"\n    text: \"unclosed\n}\n";
+    let error = MortarAssetLoader::load_asset_bytes(source.as_bytes(), Path::new("pier.mortar"))
+        .expect_err("the source is broken");
+    let message = error.to_string();
+    assert!(message.contains("pier.mortar:"), "{message}");
+    let location = message
+        .split("pier.mortar:")
+        .nth(1)
+        .and_then(|rest| rest.split(": ").next())
+        .expect("the error has a location");
+    let numbers: Vec<usize> = location
+        .split(':')
+        .map(|number| number.parse().expect("line and column are numbers"))
+        .collect();
+    assert_eq!(numbers.len(), 2, "{message}");
+    assert!(numbers[0] >= 3, "{message}");
+}
@@ -0,0 +1,139 @@
+//! Covers startup-order independence: a function registered after `StartNode` re-renders the
+//! first node's line with the real value, lines past the first node are not re-rendered for a
+//! registration, and a file registered later in the frame that wrote `StartNode` still starts.
+//!
+//! 覆盖与启动顺序无关的行为：在 `StartNode` 之后注册的函数会使第一个节点的行以真实值重新渲染；
+//! 第一个节点之后的行不会因注册而重新渲染；在写入 `StartNode` 的同一帧稍后才注册的文件仍会开始。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use mortar_compiler::Deserializer;
+
+const PATH: &str = "late.mortar";
+
+fn call_line(prefix: &str, function: &str) -> serde_json::Value {
+    serde_json::json!({
+        "type": "text",
+        "value": format!("{prefix}{{{function}}}"),
+        "interpolated_parts": [
+            { "type": "text", "content": prefix },
+            {
+                "type": "expression",
+                "content": format!("{{{function}}}"),
+                "function_name": function,
+                "args": []
+            }
+        ]
+    })
+}
+
+fn late_asset() -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [
+            { "name": "Start", "content": [call_line("Hello ", "player_name")], "next": "Road" },
+            { "name": "Road", "content": [call_line("Weather: ", "weather")] }
+        ],
+        "functions": [
+            { "name": "player_name", "params": [], "return_type": "String" },
+            { "name": "weather", "params": [], "return_type": "String" }
+        ]
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+fn setup_app() -> (App, Entity) {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+    ));
+    let target = app
+        .world_mut()
+        .spawn((Text::new(""), MortarTextTarget))
+        .id();
+    (app, target)
+}
+
+fn register_asset(world: &mut World) {
+    let handle = world
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(late_asset());
+    world
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+}
+
+fn register_function(app: &mut App, name: &'static str, value: &'static str) {
+    let mut runtime = app.world_mut().resource_mut::<MortarRuntime>();
+    runtime
+        .functions
+        .register(name, move |_| MortarValue::from(value));
+}
+
+fn run(app: &mut App, frames: usize) {
+    for _ in 0..frames {
+        app.update();
+    }
+}
+
+fn body(app: &App, target: Entity) -> String {
+    app.world()
+        .get::<MortarDialogueText>(target)
+        .unwrap()
+        .body
+        .clone()
+}
+
+#[test]
+fn test_late_function_rerenders_first_line() {
+    let (mut app, target) = setup_app();
+    register_asset(app.world_mut());
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, "Start"));
+    run(&mut app, 3);
+    assert_ne!(body(&app, target), "Hello Mira");
+
+    register_function(&mut app, "player_name", "Mira");
+    run(&mut app, 2);
+    assert_eq!(body(&app, target), "Hello Mira");
+}
+
+#[test]
+fn test_registration_past_first_node_keeps_line() {
+    let (mut app, target) = setup_app();
+    register_asset(app.world_mut());
+    register_function(&mut app, "player_name", "Mira");
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, "Start"));
+    run(&mut app, 3);
+    app.world_mut().write_message(MortarCommand::next_text());
+    run(&mut app, 3);
+    let shown = body(&app, target);
+    assert!(shown.starts_with("Weather: "));
+
+    register_function(&mut app, "weather", "rain");
+    run(&mut app, 2);
+    assert_eq!(body(&app, target), shown);
+}
+
+#[test]
+fn test_start_node_waits_for_file_registered_later_in_frame() {
+    let (mut app, target) = setup_app();
+    register_function(&mut app, "player_name", "Mira");
+    // Registers the file after the dialogue systems handled `StartNode` this frame.
+    //
+    // 在本帧对话系统处理完 `StartNode` 之后才注册文件。
+    app.add_systems(PostUpdate, |world: &mut World, mut done: Local<bool>| {
+        if !std::mem::replace(&mut *done, true) {
+            register_asset(world);
+        }
+    });
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, "Start"));
+    run(&mut app, 4);
+    assert_eq!(body(&app, target), "Hello Mira");
+}
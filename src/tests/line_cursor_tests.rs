@@ -0,0 +1,114 @@
+//! Covers [`LineCursor`]: entering a node again at the same text index yields a cursor unequal to,
+//! and ordered after, the one of the previous visit, the entered event carries the cursor of the
+//! entry line, and a hand-built finish notice carries the default cursor.
+//!
+//! 覆盖 [`LineCursor`]：在相同的文本索引处再次进入节点时，得到的游标与上一次访问的游标不相等，且排在
+//! 其后；进入事件携带进入行的游标；手动构建的结束通知携带默认游标。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use mortar_compiler::Deserializer;
+
+const PATH: &str = "well.mortar";
+
+fn well_asset() -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [{
+            "name": "Well",
+            "content": [
+                { "type": "text", "value": "The water is cold." },
+                { "type": "text", "value": "A coin glints below." }
+            ]
+        }],
+        "functions": [],
+        "variables": []
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+/// Cursors carried by the entered events so far.
+#[derive(Resource, Default)]
+struct Entered(Vec<LineCursor>);
+
+fn record(mut entered: ResMut<Entered>, mut reader: MessageReader<MortarNodeEntered>) {
+    entered
+        .0
+        .extend(reader.read().map(|message| message.cursor));
+}
+
+fn enter(app: &mut App) -> LineCursor {
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, "Well"));
+    app.update();
+    app.world()
+        .resource::<MortarRuntime>()
+        .primary_dialogue_state()
+        .expect("the node should be active")
+        .cursor()
+}
+
+#[test]
+fn test_reentered_node_yields_a_new_cursor() {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+    ))
+    .init_resource::<Entered>()
+    .add_systems(Last, record);
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(well_asset());
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+
+    let first = enter(&mut app);
+    app.world_mut()
+        .write_message(MortarCommand::stop_dialogue());
+    app.update();
+    let second = enter(&mut app);
+
+    assert_eq!(first.text_index(), second.text_index());
+    assert_ne!(first, second);
+    assert!(first < second);
+    assert!(!first.same_visit(&second));
+    assert_eq!(app.world().resource::<Entered>().0, vec![first, second]);
+
+    app.world_mut().write_message(MortarCommand::next_text());
+    app.update();
+    let state = app
+        .world()
+        .resource::<MortarRuntime>()
+        .primary_dialogue_state()
+        .expect("the node should still be active");
+    assert!(state.cursor().same_visit(&second));
+    assert!(state.cursor() > second);
+}
+
+#[test]
+fn test_hand_built_finish_notice_stands_before_every_line() {
+    let finished = MortarDialogueFinished::new(None, PATH, "Well");
+    assert_eq!(finished.mortar_path, PATH);
+    assert_eq!(finished.node, "Well");
+    assert_eq!(finished.cursor, LineCursor::default());
+
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+    ));
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(well_asset());
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+    assert!(finished.cursor < enter(&mut app));
+}
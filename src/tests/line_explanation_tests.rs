@@ -0,0 +1,134 @@
+//! Covers `MortarRuntime::explain_current_line` on a line with a condition, a placeholder, a
+//! function call and an event, reached after a skipped line: the explanation names each, and
+//! producing it calls no bound function again.
+//!
+//! 覆盖 `MortarRuntime::explain_current_line` 在一行带条件、占位符、函数调用与事件、且之前有一行
+//! 被跳过的文本上的表现：说明会列出以上各项，并且生成说明时不会再次调用绑定函数。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use mortar_compiler::Deserializer;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+const PATH: &str = "explain.mortar";
+
+fn gold_at_least(amount: &str) -> serde_json::Value {
+    serde_json::json!({
+        "type": "binary",
+        "operator": ">=",
+        "left": { "type": "identifier", "value": "gold" },
+        "right": { "type": "literal", "value": amount }
+    })
+}
+
+fn explain_asset() -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [{
+            "name": "Start",
+            "content": [
+                { "type": "text", "value": "Rich!", "condition": gold_at_least("30") },
+                {
+                    "type": "text",
+                    "value": "{gold} gold, {player_name}.",
+                    "condition": gold_at_least("10"),
+                    "interpolated_parts": [
+                        { "type": "placeholder", "content": "{gold}" },
+                        { "type": "text", "content": " gold, " },
+                        {
+                            "type": "expression",
+                            "content": "{player_name}",
+                            "function_name": "player_name",
+                            "args": []
+                        },
+                        { "type": "text", "content": "." }
+                    ],
+                    "events": [{ "index": 2, "actions": [{ "type": "bounce", "args": ["2"] }] }]
+                }
+            ]
+        }],
+        "functions": [{ "name": "player_name", "params": [], "return_type": "String" }],
+        "variables": [{ "name": "gold", "type": "Number", "value": 20 }]
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+fn setup_app(calls: Arc<AtomicUsize>) -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+    ));
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(explain_asset());
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+    app.world_mut()
+        .resource_mut::<MortarRuntime>()
+        .functions
+        .register("player_name", move |_| {
+            calls.fetch_add(1, Ordering::SeqCst);
+            MortarValue::from("Mira")
+        });
+    app.world_mut().spawn((Text::new(""), MortarTextTarget));
+    app
+}
+
+fn run(app: &mut App, frames: usize) {
+    for _ in 0..frames {
+        app.update();
+    }
+}
+
+#[test]
+fn test_explanation_lists_condition_parts_and_events() {
+    let mut app = setup_app(Arc::default());
+    assert_eq!(
+        app.world()
+            .resource::<MortarRuntime>()
+            .explain_current_line(),
+        None
+    );
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, "Start"));
+    run(&mut app, 4);
+
+    let explanation = app
+        .world()
+        .resource::<MortarRuntime>()
+        .explain_current_line()
+        .expect("the line has been rendered");
+    assert!(explanation.starts_with("Line 1 of node 'Start' in 'explain.mortar'"));
+    assert!(explanation.contains("\"20 gold, Mira.\""));
+    assert!(explanation.contains("skipped line 0: condition gold [= 20] >= 30 -> false"));
+    assert!(explanation.contains("condition gold [= 20] >= 10 -> true, this branch shown"));
+    assert!(explanation.contains("{gold} -> \"20\""));
+    assert!(explanation.contains("{player_name} -> \"Mira\""));
+    // Events are listed at the index they were mapped to in the rendered line, not the declared one.
+    //
+    // 事件按其映射到渲染后行中的索引列出，而非声明时的索引。
+    assert!(explanation.contains("event at index 4: bounce(2)"));
+}
+
+#[test]
+fn test_explanation_calls_no_function_again() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let mut app = setup_app(calls.clone());
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, "Start"));
+    run(&mut app, 4);
+    let before = calls.load(Ordering::SeqCst);
+    assert!(before > 0);
+
+    let runtime = app.world().resource::<MortarRuntime>();
+    let first = runtime.explain_current_line();
+    assert_eq!(runtime.explain_current_line(), first);
+    assert_eq!(calls.load(Ordering::SeqCst), before);
+}
@@ -0,0 +1,98 @@
+//! Pins the stable line id algorithm with golden values, so a change to it cannot slip into a
+//! release unnoticed, and checks that ids survive inserted lines and reach the text target.
+//!
+//! 用固定的期望值锁定稳定行标识符算法，避免其改动在不知不觉中进入发布版本，并检查标识符在插入新行后
+//! 保持不变且会传递到文本目标上。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use mortar_compiler::Deserializer;
+
+fn line_asset(intro: serde_json::Value) -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [
+            { "name": "Intro", "content": intro },
+            { "name": "Outro", "content": [{ "type": "text", "value": "Hello!" }] }
+        ],
+        "functions": []
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+fn golden_asset() -> MortarAsset {
+    line_asset(serde_json::json!([
+        { "type": "text", "value": "Hello!" },
+        { "type": "text", "value": "Hi {name}." },
+        { "type": "text", "value": "Hello!" },
+        { "type": "text", "value": "Named", "id": "intro.named" }
+    ]))
+}
+
+fn ids(asset: &MortarAsset, node: &str) -> Vec<String> {
+    asset
+        .line_ids(node)
+        .expect("node should exist")
+        .into_iter()
+        .map(|(id, _)| id)
+        .collect()
+}
+
+#[test]
+fn test_line_ids_match_golden_values() {
+    let asset = golden_asset();
+    assert_eq!(
+        asset.line_ids("Intro").expect("node should exist"),
+        [
+            ("7f5f2019294f61e6".to_string(), "Hello!".to_string()),
+            ("b6309fffc5cd137f".to_string(), "Hi {name}.".to_string()),
+            ("7f5f2119294f6399".to_string(), "Hello!".to_string()),
+            ("intro.named".to_string(), "Named".to_string()),
+        ]
+    );
+    assert_eq!(ids(&asset, "Outro"), ["391ee1eb8610c029"]);
+    assert!(asset.line_ids("Missing").is_none());
+}
+
+#[test]
+fn test_line_ids_survive_inserted_lines() {
+    let edited = line_asset(serde_json::json!([
+        { "type": "text", "value": "A new opening line" },
+        { "type": "text", "value": "Hello!" },
+        { "type": "text", "value": "Hi {name}." },
+        { "type": "text", "value": "Hello!" },
+        { "type": "text", "value": "Named", "id": "intro.named" }
+    ]));
+    assert_eq!(ids(&edited, "Intro")[1..], ids(&golden_asset(), "Intro"));
+}
+
+#[test]
+fn test_dialogue_text_carries_line_id() {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+    ));
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(golden_asset());
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register("lines.mortar", handle);
+    let target = app
+        .world_mut()
+        .spawn((Text::new(""), MortarTextTarget))
+        .id();
+
+    app.world_mut()
+        .write_message(MortarCommand::start_node("lines.mortar", "Intro"));
+    for _ in 0..3 {
+        app.update();
+    }
+    let dialogue_text = app.world().get::<MortarDialogueText>(target).unwrap();
+    assert_eq!(dialogue_text.line_id, "7f5f2019294f61e6");
+}
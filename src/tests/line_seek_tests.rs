@@ -0,0 +1,173 @@
+//! Covers seeking inside a line: the revealed text, the event binding, the line status and the
+//! tracker must all follow a seek within the same frame, and window-mode trackers re-arm events
+//! when the line is scrubbed backwards.
+//!
+//! 覆盖行内定位：已显示文本、事件绑定、行状态和事件跟踪器都必须在同一帧内跟随定位变化；
+//! 窗口模式的跟踪器在向后拖动时会让事件重新就绪。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use mortar_compiler::Deserializer;
+
+const PATH: &str = "seek.mortar";
+const HEADER: &str = "[seek.mortar / Start]\n\n";
+const BODY: &str = "Hello brave traveler";
+
+#[derive(Resource, Default)]
+struct Fired(Vec<String>);
+
+fn record_fired(mut fired: ResMut<Fired>, mut events: MessageReader<MortarGameEvent>) {
+    let names: Vec<_> = events.read().map(|event| event.name.clone()).collect();
+    fired.0.extend(names);
+}
+
+fn seek_asset() -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [{
+            "name": "Start",
+            "content": [{
+                "type": "text",
+                "value": BODY,
+                "events": [
+                    { "index": 2, "actions": [{ "type": "blip" }] },
+                    { "index": 8, "actions": [{ "type": "chime" }] }
+                ]
+            }]
+        }],
+        "functions": []
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+fn setup_app(mode: MortarTrackerMode) -> (App, Entity) {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+    ))
+    .insert_resource(mode)
+    .init_resource::<Fired>()
+    .add_systems(Last, record_fired);
+
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(seek_asset());
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+    let mut reveal = MortarTextReveal::new(10.0);
+    reveal.playing = false;
+    let target = app
+        .world_mut()
+        .spawn((Text::new(""), MortarTextTarget, reveal))
+        .id();
+
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, "Start"));
+    for _ in 0..3 {
+        app.update();
+    }
+    (app, target)
+}
+
+fn seek(app: &mut App, position: LinePosition) {
+    app.world_mut()
+        .write_message(MortarCommand::SeekLine { position });
+    app.update();
+}
+
+fn take_fired(app: &mut App) -> Vec<String> {
+    std::mem::take(&mut app.world_mut().resource_mut::<Fired>().0)
+}
+
+#[test]
+fn test_seek_to_half_updates_text_binding_and_status() {
+    let (mut app, target) = setup_app(MortarTrackerMode::Once);
+    assert_eq!(app.world().get::<Text>(target).unwrap().0, HEADER);
+
+    seek(&mut app, LinePosition::Fraction(0.5));
+
+    let world = app.world();
+    assert_eq!(
+        world.get::<Text>(target).unwrap().0,
+        format!("{HEADER}{}", &BODY[..10])
+    );
+    assert_eq!(
+        world
+            .get::<MortarTextReveal>(target)
+            .unwrap()
+            .revealed_chars(),
+        10
+    );
+    assert_eq!(
+        world
+            .get::<MortarEventBinding>(target)
+            .unwrap()
+            .current_index,
+        10.0
+    );
+    let status = world.get::<MortarLineStatus>(target).unwrap();
+    assert!(!status.reveal_complete);
+    assert!(!status.blocked_by_runs);
+    assert_eq!(take_fired(&mut app), vec!["blip", "chime"]);
+
+    seek(&mut app, LinePosition::Fraction(1.0));
+    assert!(
+        app.world()
+            .get::<MortarLineStatus>(target)
+            .unwrap()
+            .reveal_complete
+    );
+    assert_eq!(
+        app.world().get::<Text>(target).unwrap().0,
+        format!("{HEADER}{BODY}")
+    );
+}
+
+#[test]
+fn test_window_mode_rearms_when_seeking_backwards() {
+    let (mut app, _) = setup_app(MortarTrackerMode::Window);
+
+    seek(&mut app, LinePosition::Fraction(0.5));
+    assert_eq!(take_fired(&mut app), vec!["blip", "chime"]);
+
+    seek(&mut app, LinePosition::Chars(5));
+    assert!(take_fired(&mut app).is_empty());
+
+    seek(&mut app, LinePosition::Chars(12));
+    assert_eq!(take_fired(&mut app), vec!["chime"]);
+}
+
+#[test]
+fn test_once_mode_does_not_refire() {
+    let (mut app, _) = setup_app(MortarTrackerMode::Once);
+
+    seek(&mut app, LinePosition::Fraction(0.5));
+    seek(&mut app, LinePosition::Chars(0));
+    seek(&mut app, LinePosition::Chars(12));
+    assert_eq!(take_fired(&mut app), vec!["blip", "chime"]);
+}
+
+#[test]
+fn test_direct_seek_and_position_conversions() {
+    let (mut app, target) = setup_app(MortarTrackerMode::Once);
+
+    MortarRuntime::seek_line(app.world_mut(), LinePosition::Seconds(0.5));
+    assert_eq!(
+        app.world()
+            .get::<MortarTextReveal>(target)
+            .unwrap()
+            .revealed_chars(),
+        5
+    );
+
+    let accented = "Cafe\u{301} au lait";
+    assert_eq!(LinePosition::Graphemes(4).to_chars(accented, None), Some(5));
+    assert_eq!(LinePosition::Chars(99).to_chars(accented, None), Some(13));
+    assert_eq!(LinePosition::Seconds(1.0).to_chars(accented, None), None);
+}
@@ -0,0 +1,233 @@
+//! Covers the lint pass: each rule is run alone against a small fixture that trips it and one
+//! that does not, rules can be switched off through [`MortarLintConfig`], and the load-time pass
+//! stores its report on the asset.
+//!
+//! 覆盖 lint 流程：每条规则都单独针对一个会触发它的小样例和一个不会触发它的样例运行，规则可以通过
+//! [`MortarLintConfig`] 关闭，加载时的 lint 流程会把报告保存在资源上。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use mortar_compiler::{Deserializer, MortaredData};
+
+fn data(nodes: serde_json::Value, variables: serde_json::Value) -> MortaredData {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": nodes,
+        "functions": [],
+        "variables": variables
+    });
+    Deserializer::from_json(&json.to_string()).expect("fixture should deserialize")
+}
+
+fn nodes(nodes: serde_json::Value) -> MortaredData {
+    data(nodes, serde_json::json!([]))
+}
+
+fn single_node(content: serde_json::Value) -> MortaredData {
+    nodes(serde_json::json!([{ "name": "Start", "content": content }]))
+}
+
+/// Lints `data` with only `rule` enabled.
+fn lint_rule(data: &MortaredData, rule: MortarLintRule) -> Vec<MortarLint> {
+    let config = MortarLintRule::ALL
+        .into_iter()
+        .fold(MortarLintConfig::default(), |config, other| {
+            config.with_rule(other, other == rule)
+        });
+    let lints = MortarLinter::new(config).lint(data);
+    assert!(lints.iter().all(|lint| lint.rule == rule));
+    lints
+}
+
+#[test]
+fn test_text_too_long() {
+    let long = "a".repeat(DEFAULT_MAX_TEXT_CHARS + 1);
+    let data = single_node(serde_json::json!([
+        { "type": "text", "value": "short" },
+        { "type": "text", "value": long }
+    ]));
+    let lints = lint_rule(&data, MortarLintRule::TextTooLong);
+    assert_eq!(lints.len(), 1);
+    assert_eq!(lints[0].content_index, Some(1));
+    assert_eq!(lints[0].node.as_deref(), Some("Start"));
+
+    let config = MortarLintConfig {
+        max_text_chars: 1000,
+        ..MortarLintConfig::default()
+    };
+    assert!(lint_mortared_data(&data, &config).is_empty());
+}
+
+#[test]
+fn test_event_index_out_of_range() {
+    let data = single_node(serde_json::json!([
+        {
+            "type": "text",
+            "value": "Hello",
+            "events": [
+                { "index": 5, "actions": [{ "type": "end" }] },
+                { "index": 12, "actions": [{ "type": "typo" }] }
+            ]
+        }
+    ]));
+    let lints = lint_rule(&data, MortarLintRule::EventIndexOutOfRange);
+    assert_eq!(lints.len(), 1);
+    assert!(lints[0].message.contains("12"), "{}", lints[0].message);
+}
+
+#[test]
+fn test_duplicate_choice_label() {
+    let data = single_node(serde_json::json!([
+        { "type": "text", "value": "Pick" },
+        { "type": "choice", "options": [
+            { "text": "Yes" },
+            { "text": "No", "choice": [{ "text": "Sure" }, { "text": "Sure" }] },
+            { "text": "Yes " }
+        ] }
+    ]));
+    let lints = lint_rule(&data, MortarLintRule::DuplicateChoiceLabel);
+    assert_eq!(lints.len(), 2);
+    assert!(lints.iter().all(|lint| lint.content_index == Some(1)));
+}
+
+#[test]
+fn test_no_reachable_end() {
+    let data = nodes(serde_json::json!([
+        { "name": "Start", "content": [{ "type": "text", "value": "a" }], "next": "Loop" },
+        { "name": "Loop", "content": [{ "type": "text", "value": "b" }], "next": "Start" },
+        {
+            "name": "Menu",
+            "content": [
+                { "type": "text", "value": "c" },
+                { "type": "choice", "options": [
+                    { "text": "Again", "next": "Menu" },
+                    { "text": "Leave", "next": "return" }
+                ] }
+            ]
+        },
+        { "name": "Done", "content": [{ "type": "text", "value": "d" }] }
+    ]));
+    let lints = lint_rule(&data, MortarLintRule::NoReachableEnd);
+    let flagged: Vec<_> = lints
+        .iter()
+        .filter_map(|lint| lint.node.as_deref())
+        .collect();
+    assert_eq!(flagged, ["Start", "Loop"]);
+}
+
+#[test]
+fn test_empty_text() {
+    let data = single_node(serde_json::json!([
+        { "type": "text", "value": "  " },
+        { "type": "text", "value": "", "events": [{ "index": 0, "actions": [{ "type": "beep" }] }] },
+        { "type": "text", "value": "fine" }
+    ]));
+    let lints = lint_rule(&data, MortarLintRule::EmptyText);
+    assert_eq!(lints.len(), 1);
+    assert_eq!(lints[0].content_index, Some(0));
+}
+
+#[test]
+fn test_unused_variable() {
+    let data = data(
+        serde_json::json!([{
+            "name": "Start",
+            "content": [{
+                "type": "text",
+                "value": "You have {gold} gold and a sword",
+                "interpolated_parts": [
+                    { "type": "text", "content": "You have " },
+                    { "type": "placeholder", "content": "{gold}" },
+                    { "type": "text", "content": " gold and a sword" }
+                ]
+            }]
+        }]),
+        serde_json::json!([
+            { "name": "gold", "type": "Number", "value": 3 },
+            { "name": "sword", "type": "Boolean", "value": false }
+        ]),
+    );
+    let lints = lint_rule(&data, MortarLintRule::UnusedVariable);
+    assert_eq!(lints.len(), 1);
+    assert!(lints[0].message.contains("'sword'"), "{}", lints[0].message);
+}
+
+#[test]
+fn test_silent_loop() {
+    let hidden = |value: &str| {
+        serde_json::json!({
+            "type": "text",
+            "value": value,
+            "condition": { "type": "identifier", "value": "met" }
+        })
+    };
+    let data = nodes(serde_json::json!([
+        { "name": "Start", "content": [{ "type": "text", "value": "a" }], "next": "Ping" },
+        { "name": "Ping", "content": [hidden("b")], "next": "Pong" },
+        { "name": "Pong", "content": [hidden("c"), hidden("d")], "next": "Ping" },
+        { "name": "Echo", "content": [hidden("e")], "next": "Start" }
+    ]));
+    let lints = lint_rule(&data, MortarLintRule::SilentLoop);
+    assert_eq!(lints.len(), 1);
+    assert_eq!(lints[0].node.as_deref(), Some("Ping"));
+    assert!(
+        lints[0].message.starts_with("Ping -> Pong -> Ping"),
+        "{}",
+        lints[0].message
+    );
+}
+
+#[test]
+fn test_disabled_rules_are_skipped() {
+    let data = single_node(serde_json::json!([{ "type": "text", "value": "" }]));
+    let config = MortarLintConfig::default();
+    assert!(config.is_enabled(MortarLintRule::EmptyText));
+    assert_eq!(lint_mortared_data(&data, &config).len(), 1);
+
+    let config = config.with_rule(MortarLintRule::EmptyText, false);
+    assert!(lint_mortared_data(&data, &config).is_empty());
+}
+
+#[test]
+fn test_load_time_pass_stores_report_on_asset() {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+    ))
+    .insert_resource(MortarLintConfig {
+        lint_on_load: true,
+        ..MortarLintConfig::default()
+    });
+    let asset = MortarAsset::new(single_node(
+        serde_json::json!([{ "type": "text", "value": "" }]),
+    ));
+    assert!(asset.lints().is_empty());
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(asset);
+    // The pass runs on a task and is collected on a later frame.
+    //
+    // 该流程在任务中运行，并在之后的某一帧被收集。
+    app.update();
+    let analyzed = |app: &App| {
+        let assets = app.world().resource::<Assets<MortarAsset>>();
+        assets.get(&handle).unwrap().is_analyzed()
+    };
+    assert!(!analyzed(&app));
+    for _ in 0..1000 {
+        if analyzed(&app) {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(1));
+        app.update();
+    }
+
+    let assets = app.world().resource::<Assets<MortarAsset>>();
+    let lints = assets.get(&handle).unwrap().lints();
+    assert_eq!(lints.len(), 1);
+    assert_eq!(lints[0].rule, MortarLintRule::EmptyText);
+    assert_eq!(lints[0].severity, MortarIssueSeverity::Warning);
+}
@@ -0,0 +1,183 @@
+//! Covers load progress of a start waiting for its file: served by a deliberately slow source, the
+//! status stays queued while the wait grows, follows the loader's stages once the file arrives, and
+//! clears when the node activates; a file that cannot be loaded fails the start and leaves a failed
+//! status.
+//!
+//! 覆盖等待文件的开始请求的加载进度：在故意放慢的资源源下，状态保持排队且等待时间不断增长；文件
+//! 到达后跟随加载器的各个阶段；节点激活时状态清除。无法加载的文件会使开始请求失败，并留下失败
+//! 状态。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use bevy::asset::io::memory::{Dir, MemoryAssetReader};
+use bevy::asset::io::{AssetReader, AssetReaderError, AssetSourceBuilder, PathStream, Reader};
+use bevy::time::TimeUpdateStrategy;
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+const SOURCE: &str = "node Start {\n    text: \"Welcome aboard.\"\n}\n";
+
+/// Serves files from memory, but only once the test opens the gate.
+struct SlowReader {
+    inner: MemoryAssetReader,
+    open: Arc<AtomicBool>,
+}
+
+impl AssetReader for SlowReader {
+    async fn read<'a>(&'a self, path: &'a Path) -> Result<impl Reader + 'a, AssetReaderError> {
+        while !self.open.load(Ordering::Acquire) {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        self.inner.read(path).await
+    }
+
+    async fn read_meta<'a>(&'a self, path: &'a Path) -> Result<impl Reader + 'a, AssetReaderError> {
+        self.inner.read_meta(path).await
+    }
+
+    async fn read_directory<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> Result<Box<PathStream>, AssetReaderError> {
+        self.inner.read_directory(path).await
+    }
+
+    async fn is_directory<'a>(&'a self, path: &'a Path) -> Result<bool, AssetReaderError> {
+        self.inner.is_directory(path).await
+    }
+}
+
+#[derive(Resource, Default)]
+struct Recorded {
+    stages: Vec<MortarAssetLoadStage>,
+    failed: Vec<MortarStartFailed>,
+}
+
+fn record(
+    mut recorded: ResMut<Recorded>,
+    mut stages: MessageReader<MortarAssetLoadStage>,
+    mut failed: MessageReader<MortarStartFailed>,
+) {
+    recorded.stages.extend(stages.read().cloned());
+    recorded.failed.extend(failed.read().cloned());
+}
+
+fn setup_app(open: Arc<AtomicBool>) -> App {
+    let root = Dir::default();
+    root.insert_asset_text(Path::new("harbor.mortar"), SOURCE);
+    let mut app = App::new();
+    app.register_asset_source(
+        "slow",
+        AssetSourceBuilder::new(move || {
+            Box::new(SlowReader {
+                inner: MemoryAssetReader { root: root.clone() },
+                open: open.clone(),
+            })
+        }),
+    )
+    .add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+    ))
+    .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+        100,
+    )))
+    .init_resource::<Recorded>()
+    .add_systems(Last, record);
+    app
+}
+
+fn status(app: &App) -> Option<PendingStatus> {
+    app.world()
+        .resource::<MortarRuntime>()
+        .pending_start_status()
+}
+
+/// Updates until `done` holds, giving the loader threads time between frames.
+fn update_until(app: &mut App, done: impl Fn(&App) -> bool) {
+    for _ in 0..2_000 {
+        if done(app) {
+            return;
+        }
+        app.update();
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    panic!("the load should finish");
+}
+
+#[test]
+fn test_status_progresses_and_clears_on_activation() {
+    let open = Arc::new(AtomicBool::new(false));
+    let mut app = setup_app(open.clone());
+    app.world_mut()
+        .write_message(MortarCommand::start_node("slow://harbor.mortar", "Start"));
+    for _ in 0..4 {
+        app.update();
+    }
+
+    let waiting = status(&app).expect("the start should be waiting");
+    assert_eq!(waiting.node, "Start");
+    assert_eq!(waiting.stage, MortarLoadStage::Queued);
+    assert!(waiting.waited_secs >= 0.2);
+    app.update();
+    assert!(status(&app).unwrap().waited_secs > waiting.waited_secs);
+
+    open.store(true, Ordering::Release);
+    update_until(&mut app, |app| {
+        app.world()
+            .resource::<MortarRuntime>()
+            .has_active_dialogues()
+    });
+    assert_eq!(status(&app), None);
+
+    let stages: Vec<_> = app
+        .world()
+        .resource::<Recorded>()
+        .stages
+        .iter()
+        .map(|message| message.stage)
+        .collect();
+    assert_eq!(
+        stages,
+        [
+            MortarLoadStage::Reading,
+            MortarLoadStage::Parsing,
+            MortarLoadStage::Compiling,
+            MortarLoadStage::Decoding,
+            MortarLoadStage::Loaded,
+        ]
+    );
+    assert_eq!(
+        app.world().resource::<Recorded>().stages[0].path,
+        "slow://harbor.mortar"
+    );
+}
+
+#[test]
+fn test_missing_file_fails_the_start_and_keeps_a_failed_status() {
+    let mut app = setup_app(Arc::new(AtomicBool::new(true)));
+    app.world_mut()
+        .write_message(MortarCommand::start_node("slow://missing.mortar", "Start"));
+    update_until(&mut app, |app| {
+        !app.world().resource::<Recorded>().failed.is_empty()
+    });
+
+    let failed = &app.world().resource::<Recorded>().failed;
+    assert_eq!(failed[0].reason, MortarStartFailure::LoadFailed);
+    assert_eq!(status(&app).unwrap().stage, MortarLoadStage::Failed);
+    assert!(
+        app.world()
+            .resource::<MortarRuntime>()
+            .pending_starts
+            .is_empty()
+    );
+
+    app.world_mut()
+        .write_message(MortarCommand::stop_dialogue());
+    app.update();
+    assert_eq!(status(&app), None);
+}
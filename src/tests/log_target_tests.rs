@@ -0,0 +1,142 @@
+//! Covers log targets and runtime log switches: representative log sites report under their
+//! `mortar::*` target, and unbound-function warnings follow [`MortarLogConfig`].
+//!
+//! 覆盖日志目标和运行时日志开关：代表性的日志点使用各自的 `mortar::*` 目标输出，
+//! 未绑定函数的警告遵循 [`MortarLogConfig`]。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use bevy::log::Level;
+use bevy::log::tracing::{self, Subscriber, field::Field, field::Visit};
+use bevy::log::tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+use bevy::log::tracing_subscriber::registry::Registry;
+use mortar_compiler::IfCondition;
+use std::sync::{Arc, Mutex};
+
+pub(super) type Record = (String, Level, String);
+
+#[derive(Clone, Default)]
+struct Capture(Arc<Mutex<Vec<Record>>>);
+
+struct MessageVisitor(String);
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.0 = format!("{value:?}");
+        }
+    }
+}
+
+impl<S: Subscriber> Layer<S> for Capture {
+    fn on_event(&self, event: &tracing::Event<'_>, _ctx: Context<'_, S>) {
+        let mut message = MessageVisitor(String::new());
+        event.record(&mut message);
+        let metadata = event.metadata();
+        self.0
+            .lock()
+            .unwrap()
+            .push((metadata.target().to_string(), *metadata.level(), message.0));
+    }
+}
+
+pub(super) fn capture(run: impl FnOnce()) -> Vec<Record> {
+    let capture = Capture::default();
+    let subscriber = Registry::default().with(capture.clone());
+    tracing::subscriber::with_default(subscriber, run);
+    std::mem::take(&mut capture.0.lock().unwrap())
+}
+
+fn unbound_call_condition() -> IfCondition {
+    IfCondition {
+        cond_type: "func_call".to_string(),
+        operator: None,
+        left: None,
+        right: None,
+        operand: Some(Box::new(IfCondition {
+            cond_type: String::new(),
+            operator: None,
+            left: None,
+            right: None,
+            operand: None,
+            value: Some("has_key".to_string()),
+        })),
+        value: None,
+    }
+}
+
+#[test]
+fn test_representative_sites_use_mortar_targets() {
+    let records = capture(|| {
+        let mut registry = MortarRegistry::default();
+        registry.register("pub.mortar", Handle::default());
+        registry.register("./pub.mortar", Handle::default());
+
+        let mut state = DialogueState::new(
+            "test.mortar".to_string(),
+            "TestNode".to_string(),
+            super::core_tests::create_test_node(),
+        );
+        state.enter_at(MortarNodeEntry::at(99));
+
+        let functions = MortarFunctionRegistry::new();
+        evaluate_if_condition(
+            &unbound_call_condition(),
+            &functions,
+            &MortarVariableState::new(),
+        );
+    });
+
+    let targets: Vec<_> = records
+        .iter()
+        .map(|(target, level, _)| (target.as_str(), *level))
+        .collect();
+    assert_eq!(
+        targets,
+        [
+            ("mortar::asset", Level::WARN),
+            ("mortar::dialogue", Level::WARN),
+            ("mortar::binder", Level::WARN),
+        ]
+    );
+    assert!(records[2].2.contains("has_key"));
+}
+
+#[test]
+fn test_unbound_warnings_follow_log_config() {
+    let mut functions = MortarFunctionRegistry::new();
+    functions.set_unbound_warnings(false);
+    let records = capture(|| {
+        evaluate_if_condition(
+            &unbound_call_condition(),
+            &functions,
+            &MortarVariableState::new(),
+        );
+    });
+    assert!(records.is_empty());
+
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+    ));
+    app.update();
+    assert!(
+        app.world()
+            .resource::<MortarRuntime>()
+            .functions
+            .unbound_warnings()
+    );
+    app.world_mut().insert_resource(MortarLogConfig {
+        unbound_functions: false,
+        ..default()
+    });
+    app.update();
+    assert!(
+        !app.world()
+            .resource::<MortarRuntime>()
+            .functions
+            .unbound_warnings()
+    );
+}
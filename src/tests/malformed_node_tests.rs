@@ -0,0 +1,162 @@
+//! Covers files whose node list is malformed: duplicated node names are reported by validation
+//! and resolve to the first declaration on both the start and the jump path, and a file without
+//! nodes fails a start with [`MortarStartFailed`] instead of leaving it pending.
+//!
+//! 覆盖节点列表格式错误的文件：重复的节点名会被校验报告，并且在开始与跳转路径上都解析为第一个声明；
+//! 没有节点的文件会以 [`MortarStartFailed`] 使开始请求失败，而不是让它一直等待。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use mortar_compiler::Deserializer;
+
+const PATH: &str = "malformed.mortar";
+
+#[derive(Resource, Default)]
+struct Failures(Vec<MortarStartFailed>);
+
+fn record_failures(mut events: MessageReader<MortarStartFailed>, mut failures: ResMut<Failures>) {
+    failures.0.extend(events.read().cloned());
+}
+
+fn asset(nodes: serde_json::Value) -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": nodes,
+        "functions": []
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+fn duplicate_asset() -> MortarAsset {
+    asset(serde_json::json!([
+        { "name": "Start", "content": [{ "type": "text", "value": "first" }] },
+        {
+            "name": "Detour",
+            "content": [{ "type": "text", "value": "detour" }],
+            "next": "Start"
+        },
+        { "name": "Start", "content": [{ "type": "text", "value": "second" }] }
+    ]))
+}
+
+fn setup_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+    ))
+    .init_resource::<Failures>()
+    .add_systems(PostUpdate, record_failures);
+    app
+}
+
+fn register(app: &mut App, asset: MortarAsset) {
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(asset);
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+}
+
+fn current_text(app: &App) -> Option<String> {
+    let runtime = app.world().resource::<MortarRuntime>();
+    let state = runtime.primary_dialogue_state()?;
+    state.current_text().map(str::to_owned)
+}
+
+#[test]
+fn test_validation_reports_duplicate_and_missing_nodes() {
+    let duplicate = duplicate_asset();
+    let issues = validate_mortared_data(&duplicate.data, None);
+    let issue = issues
+        .iter()
+        .find(|issue| issue.kind == MortarIssueKind::DuplicateNode)
+        .expect("duplicate node should be reported");
+    assert_eq!(issue.severity, MortarIssueSeverity::Error);
+    assert_eq!(issue.node.as_deref(), Some("Start"));
+    assert!(issue.message.contains("0, 2"), "{}", issue.message);
+    assert!(duplicate.has_node("Detour"));
+    assert!(!duplicate.has_node("Missing"));
+
+    let empty = asset(serde_json::json!([]));
+    let issues = validate_mortared_data(&empty.data, None);
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues[0].kind, MortarIssueKind::NoNodes);
+    assert!(!empty.has_node("Start"));
+}
+
+#[test]
+fn test_duplicate_node_uses_first_declaration_on_start_and_jump() {
+    let mut app = setup_app();
+    register(&mut app, duplicate_asset());
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, "Start"));
+    app.update();
+    assert_eq!(current_text(&app).as_deref(), Some("first"));
+
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, "Detour"));
+    app.update();
+    assert_eq!(current_text(&app).as_deref(), Some("detour"));
+    app.world_mut().write_message(MortarCommand::next_text());
+    for _ in 0..3 {
+        app.update();
+    }
+    assert_eq!(current_text(&app).as_deref(), Some("first"));
+    assert!(app.world().resource::<Failures>().0.is_empty());
+}
+
+#[test]
+fn test_empty_file_fails_start_immediately() {
+    let mut app = setup_app();
+    register(&mut app, asset(serde_json::json!([])));
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, "Start"));
+    app.update();
+
+    let runtime = app.world().resource::<MortarRuntime>();
+    assert!(!runtime.has_active_dialogues());
+    assert!(runtime.pending_starts.is_empty());
+    let failures = &app.world().resource::<Failures>().0;
+    assert_eq!(failures.len(), 1);
+    assert_eq!(failures[0].reason, MortarStartFailure::NoNodes);
+    assert_eq!(failures[0].node, "Start");
+}
+
+#[test]
+fn test_pending_start_fails_once_empty_file_loads() {
+    let mut app = setup_app();
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .reserve_handle();
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle.clone());
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, "Start"));
+    app.update();
+    assert_eq!(
+        app.world().resource::<MortarRuntime>().pending_starts.len(),
+        1
+    );
+
+    app.world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .insert(handle.id(), asset(serde_json::json!([])))
+        .unwrap();
+    app.update();
+    assert!(
+        app.world()
+            .resource::<MortarRuntime>()
+            .pending_starts
+            .is_empty()
+    );
+    let failures = &app.world().resource::<Failures>().0;
+    assert_eq!(failures.len(), 1);
+    assert_eq!(failures[0].reason, MortarStartFailure::NoNodes);
+}
@@ -0,0 +1,134 @@
+//! Covers mandatory text events: skipping a line before its reveal reaches a `set_flag` fires the
+//! flag before the next line's condition reads it while a cosmetic event is dropped, a raw event
+//! marked `"mandatory": true` is forced too, and each flush is counted in the diagnostics.
+//!
+//! 覆盖必需文本事件：在逐字显示到达 `set_flag` 之前跳过一行时，该标志会在下一行的条件读取之前
+//! 触发，而装饰性事件被丢弃；原始数据中标记为 `"mandatory": true` 的事件同样会被强制触发；每次
+//! 清算都会计入诊断信息。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use mortar_compiler::Deserializer;
+use std::sync::{Arc, Mutex};
+
+const PATH: &str = "bell.mortar";
+
+fn bell_asset(chime_mandatory: bool) -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [{
+            "name": "Tower",
+            "content": [
+                {
+                    "type": "text",
+                    "value": "Ring the bell.",
+                    "events": [
+                        { "index": 4, "actions": [{ "type": "set_flag", "args": ["\"rang\""] }] },
+                        { "index": 8, "actions": [{ "type": "sparkle", "args": [] }] },
+                        {
+                            "index": 10,
+                            "mandatory": chime_mandatory,
+                            "actions": [{ "type": "chime", "args": [] }]
+                        }
+                    ]
+                },
+                {
+                    "type": "text",
+                    "value": "The bell rang.",
+                    "condition": {
+                        "type": "func_call",
+                        "operand": { "type": "identifier", "value": "has_flag" }
+                    }
+                },
+                { "type": "text", "value": "Silence." }
+            ]
+        }],
+        "functions": []
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+fn run(app: &mut App) {
+    for _ in 0..3 {
+        app.update();
+    }
+}
+
+/// Starts the tower with every event action recorded in the returned log.
+fn setup_app(chime_mandatory: bool) -> (App, Arc<Mutex<Vec<String>>>) {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+    ));
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let mut runtime = app.world_mut().resource_mut::<MortarRuntime>();
+    for name in ["set_flag", "sparkle", "chime"] {
+        let log = log.clone();
+        runtime.functions.register(name, move |_: &[MortarValue]| {
+            log.lock().unwrap().push(name.to_owned());
+            MortarValue::Void
+        });
+    }
+    let flags = log.clone();
+    runtime
+        .functions
+        .register("has_flag", move |_: &[MortarValue]| {
+            let rang = flags.lock().unwrap().iter().any(|call| call == "set_flag");
+            MortarValue::Boolean(MortarBoolean(rang))
+        });
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(bell_asset(chime_mandatory));
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+    app.world_mut().spawn((Text::new(""), MortarTextTarget));
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, "Tower"));
+    run(&mut app);
+    (app, log)
+}
+
+fn current_text(app: &App) -> Option<String> {
+    let runtime = app.world().resource::<MortarRuntime>();
+    Some(runtime.primary_dialogue_state()?.current_text()?.to_owned())
+}
+
+#[test]
+fn test_skipped_flag_fires_before_the_next_condition() {
+    let (mut app, log) = setup_app(false);
+    assert!(log.lock().unwrap().is_empty());
+
+    app.world_mut().write_message(MortarCommand::next_text());
+    run(&mut app);
+
+    assert_eq!(current_text(&app).as_deref(), Some("The bell rang."));
+    assert_eq!(*log.lock().unwrap(), ["set_flag"]);
+    let flushes = &app.world().resource::<MortarEventDiagnostics>().flushes;
+    assert_eq!(
+        flushes,
+        &[MortarEventFlush {
+            path: PATH.to_owned(),
+            node: "Tower".to_owned(),
+            text_index: 0,
+            forced: 1,
+            suppressed: 2,
+        }]
+    );
+}
+
+#[test]
+fn test_event_marked_mandatory_is_forced() {
+    let (mut app, log) = setup_app(true);
+    app.world_mut().write_message(MortarCommand::next_text());
+    run(&mut app);
+
+    assert_eq!(*log.lock().unwrap(), ["set_flag", "chime"]);
+    let flushes = &app.world().resource::<MortarEventDiagnostics>().flushes;
+    assert_eq!((flushes[0].forced, flushes[0].suppressed), (2, 1));
+}
@@ -0,0 +1,154 @@
+//! Runs a thousand short conversations through the plugin and checks that the runtime's caches
+//! stay under their configured caps, and that run collection over a node with a thousand content
+//! items stays linear.
+//!
+//! 通过插件运行一千段简短对话，检查运行时缓存始终低于配置的上限，并检查在拥有一千个内容项的节点上
+//! 收集 run 的耗时保持线性。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use mortar_compiler::Deserializer;
+use std::time::{Duration, Instant};
+
+const FILES: usize = 6;
+const NODES: usize = 5;
+
+fn short_asset() -> MortarAsset {
+    let nodes: Vec<_> = (0..NODES)
+        .map(|node| {
+            serde_json::json!({
+                "name": format!("N{node}"),
+                "content": [{ "type": "text", "value": format!("Line {node}") }]
+            })
+        })
+        .collect();
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": nodes,
+        "functions": [],
+        "variables": [{ "name": "gold", "type": "Number", "value": 3 }]
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+fn setup_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+    ));
+    for file in 0..FILES {
+        let handle = app
+            .world_mut()
+            .resource_mut::<Assets<MortarAsset>>()
+            .add(short_asset());
+        app.world_mut()
+            .resource_mut::<MortarRegistry>()
+            .register(format!("f{file}.mortar"), handle);
+    }
+    app
+}
+
+#[test]
+fn test_caches_stay_bounded_over_many_conversations() {
+    let mut app = setup_app();
+    let policy = MortarTrimPolicy {
+        max_prepared: Some(4),
+        max_warm_files: Some(2),
+        shrink_on_stop: true,
+    };
+    app.world_mut().resource_mut::<MortarRuntime>().trim_policy = policy.clone();
+
+    for round in 0..1_000 {
+        let path = format!("f{}.mortar", round % FILES);
+        let next = format!("f{}.mortar", (round + 1) % FILES);
+        let world = app.world_mut();
+        world.write_message(MortarCommand::prepare_node(
+            &next,
+            format!("N{}", (round + 1) % NODES),
+        ));
+        world.write_message(MortarCommand::start_node(
+            &path,
+            format!("N{}", round % NODES),
+        ));
+        app.update();
+        app.world_mut().write_message(MortarCommand::next_text());
+        app.update();
+        if round % 2 == 0 {
+            app.world_mut()
+                .write_message(MortarCommand::stop_dialogue());
+            app.update();
+        }
+
+        let runtime = app.world().resource::<MortarRuntime>();
+        assert!(runtime.prepared.len() <= 4, "round {round}: prepared grew");
+        assert!(
+            runtime.warm_variables.len() <= 2,
+            "round {round}: warm variables grew"
+        );
+    }
+
+    let runtime = app.world().resource::<MortarRuntime>();
+    assert!(!runtime.has_active_dialogues());
+    assert!(runtime.pending_starts.is_empty());
+    assert!(runtime.pending_jumps.is_empty());
+    assert!(runtime.pending_entries.is_empty());
+    assert!(runtime.pending_prepares.is_empty());
+    assert!(runtime.warmed_assets.len() <= 4);
+}
+
+#[test]
+fn test_trim_drops_oldest_preparations() {
+    let mut app = setup_app();
+    app.world_mut()
+        .resource_mut::<MortarRuntime>()
+        .trim_policy
+        .max_prepared = None;
+    for node in 0..NODES {
+        app.world_mut()
+            .write_message(MortarCommand::prepare_node("f0.mortar", format!("N{node}")));
+        app.update();
+    }
+
+    let mut runtime = app.world_mut().resource_mut::<MortarRuntime>();
+    assert_eq!(runtime.prepared.len(), NODES);
+    runtime.trim(&MortarTrimPolicy {
+        max_prepared: Some(2),
+        ..default()
+    });
+    assert!(!runtime.is_prepared("f0.mortar", "N0"));
+    assert!(runtime.is_prepared("f0.mortar", "N3"));
+    assert!(runtime.is_prepared("f0.mortar", "N4"));
+}
+
+#[test]
+fn test_run_collection_is_linear_in_content_length() {
+    let mut content: Vec<_> = (0..1_000)
+        .map(|index| serde_json::json!({ "type": "run_event", "name": format!("e{index}") }))
+        .collect();
+    content.push(serde_json::json!({ "type": "text", "value": "After the runs" }));
+    let node: mortar_compiler::Node = serde_json::from_value(serde_json::json!({
+        "name": "Big",
+        "content": content
+    }))
+    .expect("node should deserialize");
+
+    let started = Instant::now();
+    let mut state = DialogueState::new("big.mortar".into(), "Big".into(), node.clone());
+    for index in 0..1_000 {
+        assert_eq!(state.get_runs_at_content_position(index).len(), 1);
+        state.mark_content_executed(index);
+    }
+    assert!(state.collect_run_items_from(0).is_empty());
+
+    let mut entered = DialogueState::new("big.mortar".into(), "Big".into(), node);
+    entered.enter_at(MortarNodeEntry::at(0));
+    assert_eq!(entered.executed_content_indices.len(), 1_000);
+    assert!(
+        started.elapsed() < Duration::from_secs(1),
+        "run collection took {:?}",
+        started.elapsed()
+    );
+}
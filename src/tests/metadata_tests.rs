@@ -0,0 +1,132 @@
+//! Covers file-level metadata: headers read from `.mortar` comments and from unknown keys of
+//! `.mortared` files, title lookup through the registry, and the metadata carried by the
+//! dialogue-started event.
+//!
+//! 覆盖文件级元数据：从 `.mortar` 注释和 `.mortared` 未知键中读取的头部、通过注册表按标题查找，
+//! 以及对话开始事件携带的元数据。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use std::path::Path;
+
+const MORTARED_FIXTURE: &str = r#"{
+    "metadata": {
+        "version": "0.5.0",
+        "generated_at": "2026-01-01T00:00:00Z",
+        "chapter": 3
+    },
+    "title": "Chapter 3 — The Harbor",
+    "author": "Mira",
+    "version": 2,
+    "content_warnings": ["violence", "drowning"],
+    "nodes": [{ "name": "Start", "content": [{ "type": "text", "value": "Waves." }] }],
+    "functions": []
+}"#;
+
+const SOURCE_FIXTURE: &str = r#"// @title: The Harbor
+// @author: Mira
+// @version: 1.4-loc
+// @content_warnings: ["violence"]
+
+// Regular comments after the header are ignored.
+// @ignored: true
+node Start {
+    text: "Waves."
+}
+"#;
+
+fn harbor_asset() -> MortarAsset {
+    MortarAssetLoader::load_asset_bytes(MORTARED_FIXTURE.as_bytes(), Path::new("harbor.mortared"))
+        .expect("fixture should load")
+}
+
+#[test]
+fn test_mortared_metadata_round_trip() {
+    let asset = harbor_asset();
+    let metadata = asset.metadata();
+
+    assert_eq!(metadata.title.as_deref(), Some("Chapter 3 — The Harbor"));
+    assert_eq!(metadata.author.as_deref(), Some("Mira"));
+    assert_eq!(metadata.version.as_deref(), Some("2"));
+    assert_eq!(
+        metadata.get("content_warnings"),
+        Some(&serde_json::json!(["violence", "drowning"]))
+    );
+    assert_eq!(metadata.get("chapter"), Some(&serde_json::json!(3)));
+    assert!(metadata.get("generated_at").is_none());
+    assert_eq!(asset.data.metadata.version, "0.5.0");
+}
+
+#[test]
+fn test_source_header_comments() {
+    let asset =
+        MortarAssetLoader::load_asset_bytes(SOURCE_FIXTURE.as_bytes(), Path::new("harbor.mortar"))
+            .expect("source should compile");
+    let metadata = asset.metadata();
+
+    assert_eq!(metadata.title.as_deref(), Some("The Harbor"));
+    assert_eq!(metadata.version.as_deref(), Some("1.4-loc"));
+    assert_eq!(
+        metadata.get("content_warnings"),
+        Some(&serde_json::json!(["violence"]))
+    );
+    assert!(metadata.get("ignored").is_none());
+}
+
+#[test]
+fn test_missing_metadata_yields_defaults() {
+    let data = mortar_compiler::Deserializer::from_json(
+        r#"{"metadata":{"version":"0.5.0","generated_at":"2026-01-01T00:00:00Z"},"nodes":[],"functions":[]}"#,
+    )
+    .unwrap();
+    assert_eq!(
+        *MortarAsset::new(data).metadata(),
+        MortarMetadata::default()
+    );
+    assert_eq!(
+        MortarMetadata::from_source("node Start { text: \"Hi\" }"),
+        MortarMetadata::default()
+    );
+}
+
+#[test]
+fn test_find_by_title_and_started_event() {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+    ));
+
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(harbor_asset());
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register("harbor.mortared", handle.clone());
+
+    let world = app.world();
+    let found = world.resource::<MortarRegistry>().find_by_title(
+        "Chapter 3 — The Harbor",
+        world.resource::<Assets<MortarAsset>>(),
+    );
+    assert_eq!(found, Some(("harbor.mortared", &handle)));
+
+    app.world_mut()
+        .write_message(MortarCommand::start_node("harbor.mortared", "Start"));
+    app.update();
+
+    let started: Vec<_> = app
+        .world()
+        .resource::<Messages<MortarDialogueStarted>>()
+        .iter_current_update_messages()
+        .cloned()
+        .collect();
+    assert_eq!(started.len(), 1);
+    assert_eq!(started[0].node, "Start");
+    assert_eq!(
+        started[0].metadata.title.as_deref(),
+        Some("Chapter 3 — The Harbor")
+    );
+}
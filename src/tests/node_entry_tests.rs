@@ -0,0 +1,176 @@
+//! Covers entering a node part-way through. A choice may carry the player to a given line of
+//! its target node, and runs skipped on the way either stay silent or fire once before the entry
+//! line renders.
+//!
+//! 覆盖从节点中途进入。选项可以把玩家带到目标节点的指定行，途中跳过的 run 要么保持静默，
+//! 要么在入口行显示之前恰好触发一次。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use mortar_compiler::Deserializer;
+
+const PATH: &str = "lore.mortar";
+
+#[derive(Resource, Default)]
+struct Observed {
+    chimes: usize,
+    entered: Vec<(String, usize)>,
+}
+
+fn observe(
+    mut observed: ResMut<Observed>,
+    mut game_events: MessageReader<MortarGameEvent>,
+    mut entered: MessageReader<MortarNodeEntered>,
+) {
+    observed.chimes += game_events
+        .read()
+        .filter(|event| event.name == "chime")
+        .count();
+    let entered: Vec<_> = entered
+        .read()
+        .map(|event| (event.node.clone(), event.entry_index))
+        .collect();
+    observed.entered.extend(entered);
+}
+
+fn lore_asset(execute_skipped_runs: bool) -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [
+            {
+                "name": "Hub",
+                "content": [
+                    { "type": "text", "value": "Ask about?" },
+                    { "type": "choice", "options": [{
+                        "text": "Skip to the part about the treasure",
+                        "next": "Lore",
+                        "entry_index": 2,
+                        "execute_skipped_runs": execute_skipped_runs
+                    }] }
+                ]
+            },
+            {
+                "name": "Lore",
+                "content": [
+                    { "type": "text", "value": "Long ago..." },
+                    { "type": "run_event", "name": "chime" },
+                    { "type": "text", "value": "The king fell." },
+                    { "type": "text", "value": "His treasure sank." },
+                    { "type": "text", "value": "Nobody found it." }
+                ]
+            }
+        ],
+        "functions": [],
+        "events": [{ "name": "chime", "action": { "type": "chime" } }]
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+fn setup_app(execute_skipped_runs: bool, start: MortarCommand) -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+    ))
+    .init_resource::<Observed>()
+    .add_systems(Update, observe);
+
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(lore_asset(execute_skipped_runs));
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+    app.world_mut().spawn((Text::new(""), MortarTextTarget));
+
+    app.world_mut().write_message(start);
+    for _ in 0..3 {
+        app.update();
+    }
+    app
+}
+
+fn pick_first_choice(app: &mut App) {
+    app.world_mut().write_message(MortarCommand::SelectChoice {
+        index: 0,
+        target: None,
+        group: None,
+        source: None,
+    });
+    app.world_mut().write_message(MortarCommand::ConfirmChoice {
+        target: None,
+        group: None,
+    });
+    for _ in 0..4 {
+        app.update();
+    }
+}
+
+fn primary_state(app: &App) -> &DialogueState {
+    app.world()
+        .resource::<MortarRuntime>()
+        .primary_dialogue_state()
+        .expect("dialogue should be active")
+}
+
+fn rendered_text(app: &mut App) -> String {
+    let mut query = app
+        .world_mut()
+        .query_filtered::<&Text, With<MortarTextTarget>>();
+    query.single(app.world()).unwrap().0.clone()
+}
+
+#[test]
+fn test_choice_entry_index_lands_on_third_line() {
+    let mut app = setup_app(false, MortarCommand::start_node(PATH, "Hub"));
+    pick_first_choice(&mut app);
+
+    let state = primary_state(&app);
+    assert_eq!(state.current_node, "Lore");
+    assert_eq!(state.text_index, 2);
+    assert_eq!(state.entry_index, 2);
+    assert_eq!(state.current_text(), Some("His treasure sank."));
+    assert!(rendered_text(&mut app).contains("His treasure sank."));
+
+    let observed = app.world().resource::<Observed>();
+    assert_eq!(observed.chimes, 0);
+    assert_eq!(
+        observed.entered,
+        vec![("Hub".to_string(), 0), ("Lore".to_string(), 2)]
+    );
+}
+
+#[test]
+fn test_skipped_runs_execute_once_when_requested() {
+    let mut app = setup_app(true, MortarCommand::start_node(PATH, "Hub"));
+    pick_first_choice(&mut app);
+
+    assert_eq!(
+        primary_state(&app).current_text(),
+        Some("His treasure sank.")
+    );
+    assert!(rendered_text(&mut app).contains("His treasure sank."));
+    assert_eq!(app.world().resource::<Observed>().chimes, 1);
+
+    app.world_mut().write_message(MortarCommand::next_text());
+    for _ in 0..3 {
+        app.update();
+    }
+    assert_eq!(primary_state(&app).current_text(), Some("Nobody found it."));
+    assert_eq!(app.world().resource::<Observed>().chimes, 1);
+}
+
+#[test]
+fn test_out_of_range_entry_is_clamped() {
+    let app = setup_app(
+        false,
+        MortarCommand::start_node_at(PATH, "Lore", MortarNodeEntry::at(10)),
+    );
+    let state = primary_state(&app);
+    assert_eq!(state.entry_index, 3);
+    assert_eq!(state.current_text(), Some("Nobody found it."));
+}
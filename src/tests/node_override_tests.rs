@@ -0,0 +1,202 @@
+//! Covers node-scoped function overrides: inside a node whose `overrides` map `play_sound`,
+//! `sky` and the `Chime` run, its event actions, interpolation and runs reach the targets, the
+//! same script outside it reaches the originals, coming back into the node redirects again, the
+//! explanation lists the overrides, and an override pointing nowhere is a validation warning.
+//!
+//! 覆盖节点作用域的函数覆盖：在 `overrides` 映射了 `play_sound`、`sky` 与 `Chime` run 的节点中，
+//! 事件动作、插值与 run 都会到达覆盖目标；同样的脚本在节点外到达原函数；重新进入节点后再次被改写；
+//! 行说明会列出覆盖；指向不存在目标的覆盖会产生校验警告。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+const PATH: &str = "sleep.mortared";
+
+#[derive(Resource, Default)]
+struct Fired(Vec<String>);
+
+fn record_fired(mut fired: ResMut<Fired>, mut events: MessageReader<MortarGameEvent>) {
+    let names: Vec<_> = events.read().map(|event| event.name.clone()).collect();
+    fired.0.extend(names);
+}
+
+fn bell_line() -> serde_json::Value {
+    serde_json::json!({
+        "type": "text",
+        "value": "The sky is {sky}.",
+        "interpolated_parts": [
+            { "type": "text", "content": "The sky is " },
+            { "type": "expression", "content": "{sky}", "function_name": "sky", "args": [] },
+            { "type": "text", "content": "." }
+        ],
+        "events": [{ "index": 0, "actions": [{ "type": "play_sound", "args": ["\"bell\""] }] }]
+    })
+}
+
+fn sleep_asset(dream_overrides: serde_json::Value) -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [
+            {
+                "name": "Dream",
+                "overrides": dream_overrides,
+                "content": [
+                    bell_line(),
+                    { "type": "run_event", "name": "Chime" },
+                    { "type": "text", "value": "Deeper." }
+                ],
+                "next": "Wake"
+            },
+            {
+                "name": "Wake",
+                "content": [
+                    bell_line(),
+                    { "type": "run_event", "name": "Chime" },
+                    { "type": "text", "value": "Deeper." }
+                ],
+                "next": "Dream"
+            }
+        ],
+        "functions": [
+            { "name": "play_sound", "params": [], "return_type": null },
+            { "name": "play_sound_dream", "params": [], "return_type": null },
+            { "name": "sky", "params": [], "return_type": "String" },
+            { "name": "sky_dream", "params": [], "return_type": "String" }
+        ],
+        "events": [
+            { "name": "Chime", "action": { "type": "chime" } },
+            { "name": "DreamChime", "action": { "type": "dream_chime" } }
+        ]
+    });
+    MortarAssetLoader::load_asset_bytes(json.to_string().as_bytes(), Path::new(PATH))
+        .expect("fixture should load")
+}
+
+fn dream_overrides() -> serde_json::Value {
+    serde_json::json!({
+        "play_sound": "play_sound_dream",
+        "sky": "sky_dream",
+        "Chime": "DreamChime"
+    })
+}
+
+fn run(app: &mut App) {
+    for _ in 0..3 {
+        app.update();
+    }
+}
+
+fn setup_app() -> (App, Arc<Mutex<Vec<String>>>) {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+    ))
+    .init_resource::<Fired>()
+    .add_systems(Last, record_fired);
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let mut runtime = app.world_mut().resource_mut::<MortarRuntime>();
+    for name in ["play_sound", "play_sound_dream"] {
+        let calls = calls.clone();
+        runtime.functions.register(name, move |_: &[MortarValue]| {
+            calls.lock().unwrap().push(name.to_owned());
+            MortarValue::Void
+        });
+    }
+    runtime.functions.register("sky", |_| "blue".into());
+    runtime.functions.register("sky_dream", |_| "violet".into());
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(sleep_asset(dream_overrides()));
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+    app.world_mut().spawn((Text::new(""), MortarTextTarget));
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, "Dream"));
+    run(&mut app);
+    (app, calls)
+}
+
+fn shown(app: &mut App) -> String {
+    let mut query = app
+        .world_mut()
+        .query_filtered::<&MortarDialogueText, With<MortarTextTarget>>();
+    query.single(app.world()).unwrap().body.clone()
+}
+
+/// Advances past the current node's run and last line to the next node's first line.
+fn next_node(app: &mut App) {
+    for _ in 0..2 {
+        app.world_mut().write_message(MortarCommand::next_text());
+        run(app);
+    }
+}
+
+#[test]
+fn test_overrides_apply_only_inside_their_node() {
+    let (mut app, calls) = setup_app();
+    assert_eq!(shown(&mut app), "The sky is violet.");
+    assert_eq!(*calls.lock().unwrap(), ["play_sound_dream"]);
+
+    next_node(&mut app);
+    assert_eq!(shown(&mut app), "The sky is blue.");
+    assert_eq!(*calls.lock().unwrap(), ["play_sound_dream", "play_sound"]);
+
+    next_node(&mut app);
+    assert_eq!(shown(&mut app), "The sky is violet.");
+    assert_eq!(
+        *calls.lock().unwrap(),
+        ["play_sound_dream", "play_sound", "play_sound_dream"]
+    );
+    let fired = &app.world().resource::<Fired>().0;
+    let runs: Vec<_> = fired
+        .iter()
+        .filter(|name| name.ends_with("chime"))
+        .map(String::as_str)
+        .collect();
+    assert_eq!(runs, ["dream_chime", "chime"]);
+}
+
+#[test]
+fn test_explanation_lists_the_overrides() {
+    let (app, _) = setup_app();
+    let explanation = app
+        .world()
+        .resource::<MortarRuntime>()
+        .explain_current_line()
+        .expect("the line has been rendered");
+    assert!(explanation.contains("override Chime -> DreamChime"));
+    assert!(explanation.contains("override play_sound -> play_sound_dream"));
+}
+
+#[test]
+fn test_unknown_override_target_is_a_warning() {
+    let asset = sleep_asset(serde_json::json!({ "play_sound": "play_sound_nightmare" }));
+    let mut issues = Vec::new();
+    crate::validation::check_overrides(
+        &asset.metadata.node_overrides,
+        &asset.data,
+        None,
+        &mut issues,
+    );
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues[0].severity, MortarIssueSeverity::Warning);
+    assert_eq!(issues[0].kind, MortarIssueKind::UnknownOverrideTarget);
+    assert_eq!(issues[0].node.as_deref(), Some("Dream"));
+
+    let asset = sleep_asset(dream_overrides());
+    let mut issues = Vec::new();
+    crate::validation::check_overrides(
+        &asset.metadata.node_overrides,
+        &asset.data,
+        None,
+        &mut issues,
+    );
+    assert!(issues.is_empty());
+}
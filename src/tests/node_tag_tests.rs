@@ -0,0 +1,182 @@
+//! Covers node tags: they are read from `.mortared` nodes, queried through the asset and the
+//! runtime while a conversation runs, reported on node entry, and visible to scripts through the
+//! built-in `node_tagged` condition. Untagged and missing nodes have no tags.
+//!
+//! 覆盖节点标签：它们从 `.mortared` 节点中读取，可在对话进行时通过资源与运行时查询，会在进入节点时
+//! 报告，并可通过内置的 `node_tagged` 条件供脚本使用。没有标签或不存在的节点没有标签。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use std::path::Path;
+
+const PATH: &str = "tags.mortared";
+
+#[derive(Resource, Default)]
+struct EnteredTags(Vec<(String, Vec<String>)>);
+
+fn record_entered(mut events: MessageReader<MortarNodeEntered>, mut entered: ResMut<EnteredTags>) {
+    entered.0.extend(
+        events
+            .read()
+            .map(|event| (event.node.clone(), event.tags.clone())),
+    );
+}
+
+fn tagged_line(value: &str, tag: &str) -> serde_json::Value {
+    serde_json::json!({
+        "type": "line",
+        "value": value,
+        "condition": {
+            "type": "func_call",
+            "operand": { "type": "", "value": "node_tagged" },
+            "right": { "type": "", "value": format!("\"{tag}\"") }
+        }
+    })
+}
+
+fn tag_asset() -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [
+            {
+                "name": "Market",
+                "tags": ["shop", "calm"],
+                "content": [
+                    tagged_line("Welcome, customer.", "shop"),
+                    tagged_line("Keep your guard up.", "combat_locked"),
+                    { "type": "text", "value": "Come back soon." }
+                ],
+                "next": "Road"
+            },
+            {
+                "name": "Road",
+                "content": [{ "type": "text", "value": "The road is quiet." }],
+                "next": "Smithy"
+            },
+            {
+                "name": "Smithy",
+                "tags": ["shop", 3],
+                "content": [
+                    tagged_line("Blades for sale.", "shop"),
+                    tagged_line("The forge is calm.", "calm")
+                ]
+            }
+        ],
+        "functions": [
+            {
+                "name": "node_tagged",
+                "params": [{ "name": "tag", "type": "String" }],
+                "return_type": "Boolean"
+            }
+        ]
+    });
+    MortarAssetLoader::load_asset_bytes(json.to_string().as_bytes(), Path::new(PATH))
+        .expect("fixture should load")
+}
+
+fn setup_app() -> (App, Entity) {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+    ))
+    .init_resource::<EnteredTags>()
+    .add_systems(Last, record_entered);
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(tag_asset());
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+    let target = app
+        .world_mut()
+        .spawn((Text::new(""), MortarTextTarget))
+        .id();
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, "Market"));
+    for _ in 0..3 {
+        app.update();
+    }
+    (app, target)
+}
+
+fn body(app: &App, target: Entity) -> String {
+    app.world()
+        .get::<MortarDialogueText>(target)
+        .unwrap()
+        .body
+        .clone()
+}
+
+fn advance(app: &mut App) {
+    app.world_mut().write_message(MortarCommand::next_text());
+    for _ in 0..3 {
+        app.update();
+    }
+}
+
+#[test]
+fn test_runtime_queries_tags_mid_conversation() {
+    let asset = tag_asset();
+    assert_eq!(asset.nodes_with_tag("shop"), ["Market", "Smithy"]);
+    assert!(asset.nodes_with_tag("combat_locked").is_empty());
+    assert!(asset.metadata().node_tags("Road").is_empty());
+    assert!(asset.metadata().node_tags("Nowhere").is_empty());
+
+    let (mut app, _) = setup_app();
+    let runtime = app.world().resource::<MortarRuntime>();
+    assert!(runtime.active_has_tag("shop"));
+    assert!(!runtime.active_has_tag("combat_locked"));
+    assert_eq!(runtime.active_tags(), ["shop", "calm"]);
+    assert_eq!(
+        runtime.primary_dialogue_state().unwrap().tags(),
+        ["shop", "calm"]
+    );
+
+    advance(&mut app);
+    advance(&mut app);
+    let runtime = app.world().resource::<MortarRuntime>();
+    assert_eq!(
+        runtime.primary_dialogue_state().unwrap().current_node,
+        "Road"
+    );
+    assert!(!runtime.active_has_tag("shop"));
+    assert!(runtime.active_tags().is_empty());
+
+    advance(&mut app);
+    assert_eq!(
+        app.world().resource::<MortarRuntime>().active_tags(),
+        ["shop"]
+    );
+    let entered = &app.world().resource::<EnteredTags>().0;
+    let expected = [
+        ("Market", vec!["shop", "calm"]),
+        ("Road", vec![]),
+        ("Smithy", vec!["shop"]),
+    ];
+    assert_eq!(entered.len(), expected.len());
+    for ((node, tags), (expected_node, expected_tags)) in entered.iter().zip(expected) {
+        assert_eq!(node, expected_node);
+        assert_eq!(tags, &expected_tags);
+    }
+}
+
+#[test]
+fn test_scripts_branch_on_node_tagged() {
+    let (mut app, target) = setup_app();
+    assert_eq!(body(&app, target), "Welcome, customer.");
+
+    advance(&mut app);
+    advance(&mut app);
+    advance(&mut app);
+    assert_eq!(body(&app, target), "Blades for sale.");
+
+    let functions = &app.world().resource::<MortarRuntime>().functions;
+    let value = functions
+        .call(NODE_TAGGED_FUNCTION, &["shop".into()])
+        .unwrap();
+    assert!(!value.is_truthy(), "outside a dialogue no node is tagged");
+}
@@ -0,0 +1,149 @@
+//! Covers overlay files layered on a registered base: an overlay replacing one node, adding
+//! another and patching a line into a third plays merged content without touching the base, a
+//! reloaded overlay re-merges, and two overlays replacing the same node report the shadowing.
+//!
+//! 覆盖注册在基础文件上的覆盖层文件：替换一个节点、新增一个节点并向第三个节点拼接一行的覆盖层会
+//! 播放合并后的内容且不修改基础文件；覆盖层重新加载后会重新合并；两个覆盖层替换同一节点时会报告
+//! 遮蔽。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use std::path::Path;
+
+const PATH: &str = "harbor.mortared";
+
+fn load(nodes: serde_json::Value) -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": nodes,
+        "functions": []
+    });
+    MortarAssetLoader::load_asset_bytes(json.to_string().as_bytes(), Path::new("layer.mortared"))
+        .expect("fixture should load")
+}
+
+fn node(name: &str, lines: &[&str]) -> serde_json::Value {
+    let content: Vec<_> = lines
+        .iter()
+        .map(|line| serde_json::json!({ "type": "text", "value": line }))
+        .collect();
+    serde_json::json!({ "name": name, "content": content })
+}
+
+fn base_asset() -> MortarAsset {
+    load(serde_json::json!([
+        node("Dock", &["The tide is out."]),
+        node("Market", &["Fish for sale.", "Two coins."]),
+    ]))
+}
+
+fn mod_asset(addition: &str) -> MortarAsset {
+    load(serde_json::json!([
+        node("Dock", &["The tide is in."]),
+        node("Lighthouse", &["The lamp turns."]),
+        {
+            "name": "Market",
+            "patch": true,
+            "content": [{ "type": "text", "value": addition, "insert_after": 0 }]
+        },
+    ]))
+}
+
+fn app_with_base() -> (App, Handle<MortarAsset>) {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+    ));
+    let base = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(base_asset());
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, base.clone());
+    (app, base)
+}
+
+/// Starts `node` and advances through it, returning the lines shown.
+fn play(app: &mut App, node: &str) -> Vec<String> {
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, node));
+    let mut lines = Vec::new();
+    for _ in 0..10 {
+        for _ in 0..3 {
+            app.update();
+        }
+        let runtime = app.world().resource::<MortarRuntime>();
+        let Some(line) = runtime
+            .primary_dialogue_state()
+            .and_then(|state| state.current_text_data())
+        else {
+            break;
+        };
+        lines.push(line.value.clone());
+        app.world_mut().write_message(MortarCommand::next_text());
+    }
+    app.world_mut()
+        .write_message(MortarCommand::stop_dialogue());
+    app.update();
+    lines
+}
+
+#[test]
+fn test_overlay_replaces_adds_and_patches_nodes() {
+    let (mut app, base) = app_with_base();
+    let overlay = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(mod_asset("Also crabs."));
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register_overlay(PATH, overlay.clone(), 0);
+
+    assert_eq!(play(&mut app, "Dock"), vec!["The tide is in."]);
+    assert_eq!(play(&mut app, "Lighthouse"), vec!["The lamp turns."]);
+    assert_eq!(
+        play(&mut app, "Market"),
+        vec!["Fish for sale.", "Also crabs.", "Two coins."]
+    );
+    let assets = app.world().resource::<Assets<MortarAsset>>();
+    assert_eq!(assets.get(&base).unwrap().data.nodes.len(), 2);
+
+    app.world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .insert(overlay.id(), mod_asset("Also eels."))
+        .expect("overlay should be replaced");
+    assert_eq!(
+        play(&mut app, "Market"),
+        vec!["Fish for sale.", "Also eels.", "Two coins."]
+    );
+}
+
+#[test]
+fn test_overlays_report_shadowed_nodes() {
+    let (mut app, _) = app_with_base();
+    let mut assets = app.world_mut().resource_mut::<Assets<MortarAsset>>();
+    let high = assets.add(load(serde_json::json!([node("Dock", &["Storm."])])));
+    let low = assets.add(load(serde_json::json!([node("Dock", &["Calm."])])));
+    let mut registry = app.world_mut().resource_mut::<MortarRegistry>();
+    registry.register_overlay(PATH, high.clone(), 10);
+    registry.register_overlay(PATH, low.clone(), 1);
+
+    assert_eq!(play(&mut app, "Dock"), vec!["Storm."]);
+    let registry = app.world().resource::<MortarRegistry>();
+    let shadows = app
+        .world()
+        .resource::<MortarOverlayDiagnostics>()
+        .shadows(registry, PATH);
+    assert_eq!(
+        shadows,
+        [MortarOverlayShadow {
+            kind: "node",
+            name: "Dock".to_owned(),
+            shadowed: low.id(),
+            by: high.id(),
+        }]
+    );
+}
@@ -0,0 +1,194 @@
+//! Covers parallel texts: two voices of one item render on the targets of their channels and
+//! each tracker fires its own event, a voice whose condition fails leaves its target blank, the
+//! line status stays incomplete until every voice is revealed, `MortarTextAdvanced` fires once
+//! with the shown voices, and one `NextText` advances past the whole item.
+//!
+//! 覆盖并行文本：同一项的两个声部渲染在各自通道的目标上，每个跟踪器触发各自的事件；条件不成立的
+//! 声部会使其目标保持空白；在所有声部显示完毕之前行状态保持未完成；`MortarTextAdvanced` 只针对
+//! 所显示的声部发出一次；一次 `NextText` 即可越过整个项。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use bevy::time::TimeUpdateStrategy;
+use mortar_compiler::Deserializer;
+use std::time::Duration;
+
+const PATH: &str = "duet.mortar";
+
+#[derive(Resource, Default)]
+struct Recorded {
+    game_events: Vec<(Option<Entity>, String)>,
+    advanced: Vec<MortarTextAdvanced>,
+}
+
+fn record(
+    mut recorded: ResMut<Recorded>,
+    mut game_events: MessageReader<MortarGameEvent>,
+    mut advanced: MessageReader<MortarTextAdvanced>,
+) {
+    for event in game_events.read() {
+        recorded
+            .game_events
+            .push((event.source, event.name.clone()));
+    }
+    recorded.advanced.extend(advanced.read().cloned());
+}
+
+fn duet_asset() -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [{
+            "name": "Start",
+            "content": [
+                {
+                    "type": "parallel_text",
+                    "texts": [
+                        {
+                            "value": "Hi",
+                            "target": "alice",
+                            "id": "alice_hi",
+                            "events": [{ "index": 1, "actions": [{ "type": "nod" }] }]
+                        },
+                        {
+                            "value": "Hello there",
+                            "speaker": "bob",
+                            "id": "bob_hello",
+                            "events": [{ "index": 6, "actions": [{ "type": "wave" }] }]
+                        },
+                        {
+                            "value": "Psst",
+                            "target": "carol",
+                            "condition": { "type": "identifier", "value": "carol_awake" }
+                        }
+                    ]
+                },
+                { "type": "text", "value": "After" }
+            ]
+        }],
+        "functions": []
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+/// 20 chars per second at 100ms per frame reveals 2 characters each frame.
+fn setup_app() -> (App, [Entity; 3]) {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+    ))
+    .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+        100,
+    )))
+    .init_resource::<Recorded>()
+    .add_systems(Last, record);
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(duet_asset());
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+    let targets = ["alice", "bob", "carol"].map(|channel| {
+        app.world_mut()
+            .spawn((
+                Text::new(""),
+                MortarTextTarget,
+                MortarTextChannel(channel.to_owned()),
+                MortarTextReveal::new(20.0),
+            ))
+            .id()
+    });
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, "Start"));
+    for _ in 0..3 {
+        app.update();
+    }
+    (app, targets)
+}
+
+fn body(app: &App, target: Entity) -> String {
+    app.world()
+        .get::<MortarDialogueText>(target)
+        .unwrap()
+        .body
+        .clone()
+}
+
+fn complete(app: &App, target: Entity) -> bool {
+    app.world()
+        .get::<MortarLineStatus>(target)
+        .unwrap()
+        .reveal_complete
+}
+
+#[test]
+fn test_voices_render_on_their_channels() {
+    let (app, [alice, bob, carol]) = setup_app();
+
+    assert_eq!(body(&app, alice), "Hi");
+    assert_eq!(body(&app, bob), "Hello there");
+    assert_eq!(body(&app, carol), "");
+    let voice = app.world().get::<MortarDialogueText>(bob).unwrap();
+    assert_eq!(voice.voice.as_deref(), Some("bob"));
+    assert_eq!(voice.line_id, "bob_hello");
+
+    let advanced = &app.world().resource::<Recorded>().advanced;
+    assert_eq!(advanced.len(), 1);
+    assert_eq!(advanced[0].line_ids, ["alice_hi", "bob_hello"]);
+}
+
+#[test]
+fn test_each_voice_fires_its_own_events_and_status_waits_for_all() {
+    let (mut app, [alice, bob, _]) = setup_app();
+
+    // "Hi" is fully revealed while "Hello there" is still playing.
+    assert_eq!(
+        app.world()
+            .get::<MortarTextReveal>(alice)
+            .unwrap()
+            .revealed_chars(),
+        2
+    );
+    assert!(!complete(&app, alice));
+    assert!(!complete(&app, bob));
+    assert_eq!(
+        app.world().resource::<MortarRuntime>().advance_intent(),
+        AdvanceIntent::RevealRemaining
+    );
+
+    for _ in 0..5 {
+        app.update();
+    }
+    assert!(complete(&app, alice));
+    assert!(complete(&app, bob));
+    let fired = &app.world().resource::<Recorded>().game_events;
+    assert_eq!(
+        *fired,
+        [
+            (Some(alice), "nod".to_owned()),
+            (Some(bob), "wave".to_owned()),
+        ]
+    );
+}
+
+#[test]
+fn test_one_next_text_advances_past_the_item() {
+    let (mut app, [alice, bob, carol]) = setup_app();
+    for _ in 0..5 {
+        app.update();
+    }
+
+    app.world_mut().write_message(MortarCommand::next_text());
+    for _ in 0..3 {
+        app.update();
+    }
+    for target in [alice, bob, carol] {
+        assert_eq!(body(&app, target), "After");
+    }
+    let state = app.world().resource::<MortarRuntime>();
+    assert_eq!(state.primary_dialogue_state().unwrap().text_index, 1);
+}
@@ -0,0 +1,140 @@
+//! Covers the parsed nodes of an asset: a node is read once and every dialogue started on it
+//! shares that parse, starting a hub again through the command path reuses it, and editing the
+//! data in place takes effect after a reindex.
+//!
+//! 覆盖资源的已解析节点：节点只读取一次，在其上启动的每个对话共享该解析结果；通过命令路径再次启动
+//! 枢纽节点时会复用它；直接修改数据后需重建索引才会生效。
+
+use crate::dialogue_state::parses;
+use crate::*;
+use bevy::asset::AssetPlugin;
+use mortar_compiler::Deserializer;
+use std::sync::Arc;
+
+const PATH: &str = "hub.mortar";
+
+fn hub_asset() -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [
+            {
+                "name": "Hub",
+                "content": [
+                    { "type": "text", "value": "The square is busy." },
+                    { "type": "run_event", "name": "Bell" },
+                    {
+                        "type": "text",
+                        "value": "A guard nods.",
+                        "condition": { "type": "identifier", "value": "known" }
+                    },
+                    {
+                        "type": "choice",
+                        "options": [{ "text": "Market", "next": "Market" }, { "text": "Stay" }]
+                    }
+                ]
+            },
+            { "name": "Market", "content": [{ "type": "text", "value": "Stalls line the road." }] }
+        ],
+        "functions": [],
+        "events": [{ "name": "Bell", "action": { "type": "bell" } }],
+        "variables": [{ "name": "known", "type": "Boolean", "value": true }]
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+#[test]
+fn test_dialogues_share_one_parse_per_node() {
+    let asset = hub_asset();
+    parses::take();
+    asset.parse_nodes();
+    assert_eq!(parses::take(), 2);
+
+    let mut runtime = MortarRuntime::default();
+    let node_data = &asset.data.nodes[0];
+    let states: Vec<DialogueState> = (0..5)
+        .map(|_| runtime.parse_node(PATH, "Hub", node_data, &asset))
+        .collect();
+    assert_eq!(parses::take(), 0, "starting a node reads no JSON");
+    assert_eq!(runtime.node_parses(), 5);
+    let cached = asset.parsed_node("Hub").expect("the node exists");
+    for state in &states {
+        assert!(Arc::ptr_eq(state.parsed_node(), &cached));
+    }
+    assert!(asset.parsed_node("Harbor").is_none());
+
+    // A state built from the raw node still parses it, with the same result.
+    //
+    // 基于原始节点构建的状态仍会解析它，结果相同。
+    let fresh = DialogueState::new(PATH.to_owned(), "Hub".to_owned(), node_data.clone());
+    assert_eq!(parses::take(), 1);
+    let values = |items: &[TextData]| -> Vec<String> {
+        items.iter().map(|item| item.line_id.clone()).collect()
+    };
+    assert_eq!(values(fresh.text_items()), values(cached.text_items()));
+    assert_eq!(fresh.get_choices().map(Vec::len), Some(2));
+    assert_eq!(states[0].get_runs_at_content_position(1).len(), 1);
+}
+
+#[test]
+fn test_restarting_a_hub_reuses_its_parse() {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+    ));
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(hub_asset());
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle.clone());
+
+    let mut parsed = Vec::new();
+    for _ in 0..2 {
+        for command in [
+            MortarCommand::start_node(PATH, "Hub"),
+            MortarCommand::stop_dialogue(),
+        ] {
+            app.world_mut().write_message(command);
+            for _ in 0..3 {
+                app.update();
+            }
+            let runtime = app.world().resource::<MortarRuntime>();
+            if let Some(state) = runtime.primary_dialogue_state() {
+                parsed.push(state.parsed_node().clone());
+            }
+        }
+    }
+    let asset = app
+        .world()
+        .resource::<Assets<MortarAsset>>()
+        .get(&handle)
+        .expect("the asset is loaded");
+    let cached = asset.parsed_node("Hub").expect("the node exists");
+    assert_eq!(parsed.len(), 2);
+    assert!(parsed.iter().all(|node| Arc::ptr_eq(node, &cached)));
+}
+
+#[test]
+fn test_edited_nodes_are_read_again_after_reindex() {
+    let mut asset = hub_asset();
+    let before = asset.parsed_node("Market").expect("the node exists");
+    asset.data.nodes[1]
+        .content
+        .push(serde_json::json!({ "type": "text", "value": "A dog barks." }));
+    assert_eq!(
+        asset
+            .parsed_node("Market")
+            .map(|node| node.text_items().len()),
+        Some(1),
+        "the parse follows edits only after a reindex"
+    );
+    asset.reindex();
+    let after = asset.parsed_node("Market").expect("the node exists");
+    assert!(!Arc::ptr_eq(&before, &after));
+    assert_eq!(after.text_items().len(), 2);
+    assert_eq!(after.text_items()[1].value, "A dog barks.");
+}
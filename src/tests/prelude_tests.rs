@@ -0,0 +1,115 @@
+//! Covers the prelude: an app built from `prelude::*` alone starts a dialogue, binds functions,
+//! renders onto a text target, advances and hears the dialogue finish, and the deprecated
+//! `MortarEvent` name still drives it.
+//!
+//! 覆盖 prelude：仅凭 `prelude::*` 构建的应用能够启动对话、绑定函数、渲染到文本目标、推进并收到
+//! 对话结束通知；已弃用的 `MortarEvent` 名称仍可驱动对话。
+
+use crate::prelude::*;
+use bevy::asset::AssetPlugin;
+use bevy::prelude::*;
+use mortar_compiler::Deserializer;
+
+const PATH: &str = "gate.mortar";
+
+#[derive(MortarFunctions)]
+struct GateFunctions;
+
+#[mortar_functions]
+impl GateFunctions {
+    fn toll(coins: MortarNumber) -> f64 {
+        coins.as_f64() * 2.0
+    }
+}
+
+#[derive(Resource, Default)]
+struct Finished(usize);
+
+fn record_finished(
+    mut finished: ResMut<Finished>,
+    mut events: MessageReader<MortarDialogueFinished>,
+) {
+    finished.0 += events.read().count();
+}
+
+fn gate_asset() -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [{
+            "name": "Gate",
+            "content": [
+                { "type": "text", "value": "Halt." },
+                { "type": "text", "value": "Pass." }
+            ]
+        }],
+        "functions": []
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+fn setup_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+    ))
+    .init_resource::<Finished>()
+    .add_systems(Last, record_finished);
+    GateFunctions::register(&mut app.world_mut().resource_mut::<MortarRuntime>().functions);
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(gate_asset());
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+    app.world_mut().spawn((Text::new(""), MortarTextTarget));
+    app
+}
+
+fn send(app: &mut App, command: MortarCommand) {
+    app.world_mut().write_message(command);
+    for _ in 0..3 {
+        app.update();
+    }
+}
+
+fn shown(app: &mut App) -> String {
+    let mut query = app
+        .world_mut()
+        .query_filtered::<&MortarDialogueText, With<MortarTextTarget>>();
+    query.single(app.world()).unwrap().body.clone()
+}
+
+#[test]
+fn test_prelude_covers_the_main_integration_path() {
+    let mut app = setup_app();
+    let runtime = app.world().resource::<MortarRuntime>();
+    let toll = runtime.functions.call("toll", &[MortarValue::from(3.0)]);
+    assert_eq!(
+        toll.and_then(|value| value.as_number()).map(|n| n.as_f64()),
+        Some(6.0)
+    );
+
+    send(&mut app, MortarCommand::start_node(PATH, "Gate"));
+    assert_eq!(shown(&mut app), "Halt.");
+    send(&mut app, MortarCommand::next_text());
+    assert_eq!(shown(&mut app), "Pass.");
+    send(&mut app, MortarCommand::next_text());
+    assert_eq!(app.world().resource::<Finished>().0, 1);
+}
+
+#[test]
+#[expect(deprecated, reason = "checks that the old name still compiles")]
+fn test_deprecated_event_name_still_drives_the_dialogue() {
+    let mut app = setup_app();
+    app.world_mut()
+        .write_message(crate::MortarEvent::start_node(PATH, "Gate"));
+    for _ in 0..3 {
+        app.update();
+    }
+    assert_eq!(shown(&mut app), "Halt.");
+}
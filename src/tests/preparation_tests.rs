@@ -0,0 +1,126 @@
+//! Covers preparing a node ahead of its start: the later start must reuse the prepared state
+//! without parsing the node again, and an expired preparation must fall back to a normal start.
+//!
+//! 覆盖在开始之前准备节点：之后的开始必须复用已准备的状态而不再次解析节点，
+//! 过期的准备条目则必须退回到普通的开始流程。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use bevy::time::TimeUpdateStrategy;
+use mortar_compiler::Deserializer;
+use std::time::Duration;
+
+const PATH: &str = "smith.mortar";
+
+fn smith_asset() -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [{
+            "name": "Greet",
+            "content": [
+                { "type": "text", "value": "Need a blade?" },
+                { "type": "text", "value": "Finest steel in the valley." }
+            ]
+        }],
+        "functions": [],
+        "variables": [{ "name": "price", "type": "Number", "value": 40 }]
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+fn setup_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+    ));
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(smith_asset());
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+    app
+}
+
+fn node_parses(app: &App) -> u64 {
+    app.world().resource::<MortarRuntime>().node_parses()
+}
+
+#[test]
+fn test_prepared_start_does_not_reparse() {
+    let mut app = setup_app();
+
+    app.world_mut()
+        .write_message(MortarCommand::prepare_node(PATH, "Greet"));
+    app.update();
+
+    let prepared: Vec<_> = app
+        .world()
+        .resource::<Messages<MortarNodePrepared>>()
+        .iter_current_update_messages()
+        .map(|event| (event.path.clone(), event.node.clone()))
+        .collect();
+    assert_eq!(prepared, vec![(PATH.to_string(), "Greet".to_string())]);
+    assert_eq!(node_parses(&app), 1);
+    let runtime = app.world().resource::<MortarRuntime>();
+    assert!(runtime.is_prepared(PATH, "Greet"));
+    assert!(runtime.warm_variables(PATH).is_some());
+    assert!(runtime.primary_dialogue_state().is_none());
+
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, "Greet"));
+    app.update();
+
+    assert_eq!(node_parses(&app), 1);
+    let runtime = app.world().resource::<MortarRuntime>();
+    assert!(!runtime.is_prepared(PATH, "Greet"));
+    assert_eq!(
+        runtime.primary_dialogue_state().unwrap().current_text(),
+        Some("Need a blade?")
+    );
+}
+
+#[test]
+fn test_expired_preparation_falls_back_to_parsing() {
+    let mut app = setup_app();
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+        100,
+    )));
+    app.world_mut().resource_mut::<MortarRuntime>().prepared_ttl = Some(Duration::from_millis(150));
+
+    app.world_mut()
+        .write_message(MortarCommand::prepare_node(PATH, "Greet"));
+    app.update();
+    assert!(
+        app.world()
+            .resource::<MortarRuntime>()
+            .is_prepared(PATH, "Greet")
+    );
+
+    for _ in 0..3 {
+        app.update();
+    }
+    assert!(
+        !app.world()
+            .resource::<MortarRuntime>()
+            .is_prepared(PATH, "Greet")
+    );
+
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, "Greet"));
+    app.update();
+
+    assert_eq!(node_parses(&app), 2);
+    assert_eq!(
+        app.world()
+            .resource::<MortarRuntime>()
+            .primary_dialogue_state()
+            .unwrap()
+            .current_text(),
+        Some("Need a blade?")
+    );
+}
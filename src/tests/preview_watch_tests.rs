@@ -0,0 +1,183 @@
+//! Covers what watchers see of a preview. `simulate_confirm` on an option with nested choices
+//! writes the capture in a variable transaction, and a presented option watches the captured
+//! index. The re-evaluation of presented choices, the selection, the variable revision and the
+//! state history all stay as they were; the real confirm afterwards changes them.
+//!
+//! 覆盖观察者能看到的预览内容。对带嵌套选项的选项调用 `simulate_confirm` 时，会在变量事务中写入
+//! 捕获，而某个已显示的选项正在监视被捕获的索引。已显示选项的重新求值、当前选择、变量修订号与状态
+//! 历史都保持不变；随后真正的确认才会改变它们。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use mortar_compiler::Deserializer;
+
+const PATH: &str = "square.mortar";
+
+#[derive(Resource, Default)]
+struct Deselections(usize);
+
+fn count_deselections(
+    mut deselections: ResMut<Deselections>,
+    mut deselected: MessageReader<MortarChoiceDeselected>,
+) {
+    deselections.0 += deselected.read().count();
+}
+
+fn square_asset() -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [
+            {
+                "name": "Start",
+                "content": [
+                    { "type": "text", "value": "A stranger waves" },
+                    { "type": "choice", "capture": "pick", "options": [
+                        {
+                            "text": "Wave back",
+                            "condition": { "type": "pick_index", "args": ["<", "1"] },
+                            "next": "End"
+                        },
+                        { "text": "Ask who they are", "choice": [
+                            { "text": "About the town", "next": "End" }
+                        ] }
+                    ] }
+                ]
+            },
+            { "name": "End", "content": [{ "type": "text", "value": "Farewell" }] }
+        ],
+        "functions": [],
+        "variables": [{ "name": "pick_index", "type": "Number", "value": 0 }]
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+fn setup_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+    ))
+    .insert_resource(MortarStateHistory::new(DEFAULT_STATE_HISTORY_CAPACITY))
+    .init_resource::<Deselections>()
+    .add_systems(Last, count_deselections);
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(square_asset());
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+    app.world_mut().spawn((Text::new(""), MortarTextTarget));
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, "Start"));
+    run(&mut app);
+    app
+}
+
+fn run(app: &mut App) {
+    for _ in 0..3 {
+        app.update();
+    }
+}
+
+fn simulate(app: &mut App, index: usize) -> ConfirmOutcome {
+    app.world_mut()
+        .resource_scope(|world, mut runtime: Mut<MortarRuntime>| {
+            world.resource_scope(|world, mut variables: Mut<MortarDialogueVariables>| {
+                runtime.simulate_confirm(
+                    index,
+                    world.resource::<MortarRegistry>(),
+                    world.resource::<Assets<MortarAsset>>(),
+                    &mut variables,
+                )
+            })
+        })
+}
+
+fn variables(app: &App) -> &MortarVariableState {
+    app.world()
+        .resource::<MortarDialogueVariables>()
+        .state
+        .as_ref()
+        .expect("variables initialized by the text system")
+}
+
+fn enabled_flags(app: &App) -> Vec<bool> {
+    app.world()
+        .resource::<MortarChoicesPresented>()
+        .views
+        .iter()
+        .map(|view| view.enabled)
+        .collect()
+}
+
+fn selected_choice(app: &App) -> Option<usize> {
+    app.world()
+        .resource::<MortarRuntime>()
+        .primary_dialogue_state()
+        .and_then(|state| state.selected_choice)
+}
+
+fn recorded_index_changes(app: &App) -> usize {
+    let history = app.world().resource::<MortarStateHistory>();
+    history
+        .records()
+        .iter()
+        .flat_map(|record| &record.variable_changes)
+        .filter(|(name, _)| &**name == "pick_index")
+        .count()
+}
+
+#[test]
+fn test_watchers_do_not_see_a_simulated_capture() {
+    let mut app = setup_app();
+    app.world_mut()
+        .write_message(MortarCommand::select_choice(0));
+    run(&mut app);
+    assert_eq!(enabled_flags(&app), [true, true]);
+    assert_eq!(selected_choice(&app), Some(0));
+    let revision = variables(&app).revision();
+    let records = app.world().resource::<MortarStateHistory>().records().len();
+    let index_changes = recorded_index_changes(&app);
+
+    let outcome = simulate(&mut app, 1);
+    assert_eq!(outcome.effect, ConfirmEffect::EnterNested);
+    assert!(outcome.capture.is_some());
+    assert_eq!(variables(&app).transaction_depth(), 0);
+    run(&mut app);
+
+    assert_eq!(variables(&app).revision(), revision);
+    assert_eq!(
+        variables(&app).get("pick_index"),
+        Some(&MortarVariableValue::Number(0.0))
+    );
+    assert_eq!(enabled_flags(&app), [true, true]);
+    assert_eq!(selected_choice(&app), Some(0));
+    assert_eq!(app.world().resource::<Deselections>().0, 0);
+    let history = app.world().resource::<MortarStateHistory>();
+    assert_eq!(
+        history.records().len(),
+        records,
+        "{}",
+        history.dump_pretty()
+    );
+    assert_eq!(recorded_index_changes(&app), index_changes);
+
+    // The capture of the real confirm is seen by every watcher.
+    //
+    // 真正确认时的捕获会被所有观察者看到。
+    app.world_mut()
+        .write_message(MortarCommand::select_choice(1));
+    app.world_mut()
+        .write_message(MortarCommand::confirm_choice());
+    run(&mut app);
+    assert_ne!(variables(&app).revision(), revision);
+    assert_eq!(
+        variables(&app).get("pick_index"),
+        Some(&MortarVariableValue::Number(1.0))
+    );
+    assert_eq!(recorded_index_changes(&app), index_changes + 1);
+}
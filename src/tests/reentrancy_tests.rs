@@ -0,0 +1,76 @@
+//! Covers re-entrant call protection in the function registry: mutually recursive functions are
+//! stopped with the cycle reported instead of overflowing the stack, and long non-cyclic chains
+//! stop at the configured depth.
+//!
+//! 覆盖函数注册表的重入调用保护：相互递归的函数会被拦截并报告调用环，而不是导致栈溢出；
+//! 不成环的长调用链会在配置的深度处停止。
+
+use crate::*;
+use std::sync::{Arc, OnceLock};
+
+type SharedRegistry = Arc<OnceLock<MortarFunctionRegistry>>;
+
+fn forwarding(
+    shared: &SharedRegistry,
+    next: &'static str,
+) -> impl Fn(&[MortarValue]) -> MortarValue + use<> {
+    let shared = Arc::clone(shared);
+    move |args| {
+        let registry = shared.get().expect("registry should be installed");
+        match registry.try_call(next, args) {
+            Ok(value) => value,
+            Err(err) => MortarValue::String(MortarString(err.to_string())),
+        }
+    }
+}
+
+#[test]
+fn test_mutual_recursion_reports_cycle() {
+    let shared = SharedRegistry::default();
+    let mut registry = MortarFunctionRegistry::new();
+    registry.register("ping", forwarding(&shared, "pong"));
+    registry.register("pong", forwarding(&shared, "ping"));
+    assert!(shared.set(registry).is_ok());
+    let registry = shared.get().unwrap();
+
+    let Ok(MortarValue::String(message)) = registry.try_call("ping", &[]) else {
+        panic!("inner call should be refused, not the outer one");
+    };
+    assert_eq!(
+        message.as_str(),
+        "re-entrant function call: ping → pong → ping"
+    );
+
+    // The stack unwinds fully, so the outermost call can be made again.
+    assert!(registry.try_call("pong", &[]).is_ok());
+    assert!(matches!(
+        registry.try_call("missing", &[]),
+        Err(MortarCallError::NotFound(name)) if name == "missing"
+    ));
+}
+
+#[test]
+fn test_call_depth_is_capped() {
+    const CHAIN: [&str; 5] = ["a", "b", "c", "d", "e"];
+    let shared = SharedRegistry::default();
+    let mut registry = MortarFunctionRegistry::new();
+    for pair in CHAIN.windows(2) {
+        registry.register(pair[0], forwarding(&shared, pair[1]));
+    }
+    registry.register("e", |_| MortarValue::Number(MortarNumber(1.0)));
+    registry.set_max_call_depth(3);
+    assert!(shared.set(registry).is_ok());
+    let registry = shared.get().unwrap();
+
+    let MortarValue::String(message) = registry.call("a", &[]).unwrap() else {
+        panic!("depth error should surface as a message");
+    };
+    assert_eq!(
+        message.as_str(),
+        "function call depth limit 3 exceeded: a → b → c → d"
+    );
+    assert!(matches!(
+        registry.call("c", &[]),
+        Some(MortarValue::Number(MortarNumber(value))) if value == 1.0
+    ));
+}
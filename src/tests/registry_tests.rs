@@ -0,0 +1,101 @@
+//! Covers registry path normalization: spellings of the same path share one entry, genuinely
+//! different paths stay apart, and dialogues started through any spelling store the canonical path.
+//!
+//! 覆盖注册表路径规范化：同一路径的不同写法共享一个条目，真正不同的路径保持独立，
+//! 通过任意写法开始的对话都保存规范化路径。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use mortar_compiler::Deserializer;
+
+fn pub_asset(line: &str) -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [{ "name": "Start", "content": [{ "type": "text", "value": line }] }],
+        "functions": []
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+#[test]
+fn test_canonical_key_normalization() {
+    let registry = MortarRegistry::default();
+    assert_eq!(registry.canonical_key("./pub.mortar"), "pub.mortar");
+    assert_eq!(
+        registry.canonical_key(".\\dialogue\\\\pub.mortar"),
+        "dialogue/pub.mortar"
+    );
+    assert_eq!(
+        registry.canonical_key("dialogue/./Pub.mortar"),
+        "dialogue/Pub.mortar"
+    );
+
+    let folded = MortarRegistry::with_case_insensitive_keys();
+    assert_eq!(
+        folded.canonical_key("./Dialogue/Pub.mortar"),
+        "dialogue/pub.mortar"
+    );
+}
+
+#[test]
+fn test_dot_slash_and_plain_path_share_an_entry() {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+    ));
+
+    let (stale, fresh) = {
+        let mut assets = app.world_mut().resource_mut::<Assets<MortarAsset>>();
+        (
+            assets.add(pub_asset("Stale")),
+            assets.add(pub_asset("Fresh")),
+        )
+    };
+    {
+        let mut registry = app.world_mut().resource_mut::<MortarRegistry>();
+        registry.register("pub.mortar", stale);
+        registry.register("./pub.mortar", fresh.clone());
+        assert_eq!(registry.get("pub.mortar"), Some(&fresh));
+        assert_eq!(registry.get("./pub.mortar"), Some(&fresh));
+    }
+
+    app.world_mut()
+        .write_message(MortarCommand::start_node("./pub.mortar", "Start"));
+    app.update();
+
+    let state = app
+        .world()
+        .resource::<MortarRuntime>()
+        .primary_dialogue_state()
+        .expect("dialogue should start");
+    assert_eq!(state.mortar_path, "pub.mortar");
+    assert_eq!(state.current_text(), Some("Fresh"));
+}
+
+#[test]
+fn test_different_paths_are_not_merged() {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+    ));
+
+    let (tavern, old_tavern) = {
+        let mut assets = app.world_mut().resource_mut::<Assets<MortarAsset>>();
+        (
+            assets.add(pub_asset("Tavern")),
+            assets.add(pub_asset("Old tavern")),
+        )
+    };
+    let mut registry = app.world_mut().resource_mut::<MortarRegistry>();
+    registry.register("pub.mortar", tavern.clone());
+    registry.register("old/pub.mortar", old_tavern.clone());
+
+    assert_eq!(registry.get("./pub.mortar"), Some(&tavern));
+    assert_eq!(registry.get("old/pub.mortar"), Some(&old_tavern));
+    assert_eq!(registry.get("Pub.mortar"), None);
+}
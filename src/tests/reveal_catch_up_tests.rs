@@ -0,0 +1,145 @@
+//! Covers revealing several characters in one frame: each character and the events it reaches
+//! are written as `MortarRevealStep`s in strict positional order, `max_chars_per_frame` spreads a
+//! fast reveal across frames, and `catch_up` decides whether held-back characters carry over.
+//!
+//! 覆盖一帧内显示多个字符的情况：每个字符及其到达的事件都按严格的位置顺序写成
+//! `MortarRevealStep`；`max_chars_per_frame` 会把快速显示分摊到多帧；`catch_up` 决定被限制的字符
+//! 是否顺延。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use bevy::time::TimeUpdateStrategy;
+use mortar_compiler::Deserializer;
+use std::time::Duration;
+
+const PATH: &str = "catch_up.mortar";
+
+/// Each frame's reveal steps: a character, or `!name` for an event.
+#[derive(Resource, Default)]
+struct Steps(Vec<Vec<String>>);
+
+fn record_steps(mut steps: ResMut<Steps>, mut reader: MessageReader<MortarRevealStep>) {
+    let frame = reader
+        .read()
+        .map(|step| match step {
+            MortarRevealStep::Char { character, .. } => character.to_string(),
+            MortarRevealStep::Event(event) => format!("!{}", event.name),
+        })
+        .collect();
+    steps.0.push(frame);
+}
+
+fn catch_up_asset() -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [{
+            "name": "Start",
+            "content": [{
+                "type": "text",
+                "value": "Hello",
+                "events": [
+                    { "index": 2, "actions": [{ "type": "blip" }] },
+                    { "index": 3, "actions": [{ "type": "chime" }] }
+                ]
+            }]
+        }],
+        "functions": []
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+/// 40 chars per second at 100ms per frame owes 4 characters each frame.
+fn setup_app(reveal: MortarTextReveal) -> (App, Entity) {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+    ))
+    .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+        100,
+    )))
+    .init_resource::<Steps>()
+    .add_systems(Last, record_steps);
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(catch_up_asset());
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+    let target = app
+        .world_mut()
+        .spawn((Text::new(""), MortarTextTarget, reveal))
+        .id();
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, "Start"));
+    (app, target)
+}
+
+fn run(app: &mut App, frames: usize) -> Vec<Vec<String>> {
+    for _ in 0..frames {
+        app.update();
+    }
+    let steps = &app.world().resource::<Steps>().0;
+    steps
+        .iter()
+        .filter(|frame| !frame.is_empty())
+        .cloned()
+        .collect()
+}
+
+#[test]
+fn test_multi_char_frame_interleaves_in_positional_order() {
+    let (mut app, _) = setup_app(MortarTextReveal::new(40.0));
+    let frames = run(&mut app, 6);
+
+    assert_eq!(frames[0], ["H", "e", "!blip", "l", "!chime", "l"]);
+    assert_eq!(frames[1], ["o"]);
+    assert_eq!(frames.len(), 2);
+}
+
+#[test]
+fn test_cap_spreads_fast_reveal_across_frames() {
+    let mut reveal = MortarTextReveal::new(40.0);
+    reveal.max_chars_per_frame = Some(2);
+    let (mut app, _) = setup_app(reveal);
+    let frames = run(&mut app, 6);
+
+    assert_eq!(
+        frames,
+        [vec!["H", "e", "!blip"], vec!["l", "!chime", "l"], vec!["o"],]
+    );
+}
+
+#[test]
+fn test_catch_up_decides_whether_held_back_chars_carry_over() {
+    for (catch_up, expected) in [(true, "Hell"), (false, "He")] {
+        let mut reveal = MortarTextReveal::new(40.0);
+        reveal.max_chars_per_frame = Some(2);
+        reveal.catch_up = catch_up;
+        let (mut app, target) = setup_app(reveal);
+        while app
+            .world()
+            .get::<MortarTextReveal>(target)
+            .unwrap()
+            .revealed_chars()
+            == 0
+        {
+            app.update();
+        }
+        app.world_mut()
+            .get_mut::<MortarTextReveal>(target)
+            .unwrap()
+            .chars_per_second = 0.0;
+        run(&mut app, 3);
+
+        let text = &app.world().get::<Text>(target).unwrap().0;
+        assert!(
+            text.ends_with(&format!("\n{expected}")),
+            "catch_up {catch_up}: {text:?}"
+        );
+    }
+}
@@ -0,0 +1,214 @@
+//! Covers reveal checkpoints: a line with two `{pause}` tokens takes three advance inputs, the
+//! reveal holds with `awaiting_advance` at each pause after the events before it fired, skipping
+//! passes every checkpoint, and a `__pause_reveal` event action pauses like a token.
+//!
+//! 覆盖显示检查点：带有两个 `{pause}` 标记的行需要三次推进输入；显示会在每个暂停处停住并设置
+//! `awaiting_advance`，且其之前的事件都已触发；跳过会越过所有检查点；`__pause_reveal` 事件动作
+//! 与标记一样会暂停显示。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use bevy::time::TimeUpdateStrategy;
+use mortar_compiler::Deserializer;
+use std::time::Duration;
+
+const PATH: &str = "checkpoints.mortar";
+
+/// Reveal steps so far: a character, or `!name` for an event.
+#[derive(Resource, Default)]
+struct Steps(String);
+
+fn record_steps(mut steps: ResMut<Steps>, mut reader: MessageReader<MortarRevealStep>) {
+    for step in reader.read() {
+        match step {
+            MortarRevealStep::Char { character, .. } => steps.0.push(*character),
+            MortarRevealStep::Event(event) => steps.0.push_str(&format!("!{}", event.name)),
+        }
+    }
+}
+
+fn checkpoint_asset() -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [{
+            "name": "Start",
+            "content": [
+                {
+                    "type": "text",
+                    "value": "Wait{pause}for{pause}it",
+                    "interpolated_parts": [
+                        { "type": "text", "content": "Wait" },
+                        { "type": "placeholder", "content": "{pause}" },
+                        { "type": "text", "content": "for" },
+                        { "type": "placeholder", "content": "{pause}" },
+                        { "type": "text", "content": "it" }
+                    ],
+                    "events": [
+                        { "index": 2, "actions": [{ "type": "a" }] },
+                        { "index": 5, "actions": [{ "type": "b" }] },
+                        { "index": 8, "actions": [{ "type": "c" }] }
+                    ]
+                },
+                {
+                    "type": "text",
+                    "value": "Boom",
+                    "events": [{ "index": 2, "actions": [{ "type": "__pause_reveal" }] }]
+                }
+            ]
+        }],
+        "functions": []
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+/// 20 chars per second at 100ms per frame reveals 2 characters each frame.
+fn setup_app() -> (App, Entity) {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+    ))
+    .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+        100,
+    )))
+    .init_resource::<Steps>()
+    .add_systems(Last, record_steps);
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(checkpoint_asset());
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+    let target = app
+        .world_mut()
+        .spawn((Text::new(""), MortarTextTarget, MortarTextReveal::new(20.0)))
+        .id();
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, "Start"));
+    (app, target)
+}
+
+fn send(app: &mut App, event: MortarCommand, frames: usize) {
+    app.world_mut().write_message(event);
+    for _ in 0..frames {
+        app.update();
+    }
+}
+
+/// Steps so far, the line status and the intent.
+fn progress(app: &App, target: Entity) -> (String, MortarLineStatus, AdvanceIntent) {
+    let world = app.world();
+    (
+        world.resource::<Steps>().0.clone(),
+        *world.get::<MortarLineStatus>(target).unwrap(),
+        world.resource::<MortarRuntime>().advance_intent(),
+    )
+}
+
+fn status(reveal_complete: bool, awaiting_advance: bool) -> MortarLineStatus {
+    MortarLineStatus {
+        reveal_complete,
+        awaiting_advance,
+        ..default()
+    }
+}
+
+fn current_text(app: &App) -> Option<String> {
+    let runtime = app.world().resource::<MortarRuntime>();
+    Some(runtime.primary_dialogue_state()?.current_text()?.to_owned())
+}
+
+#[test]
+fn test_two_pauses_take_three_advance_inputs() {
+    let (mut app, target) = setup_app();
+    for _ in 0..10 {
+        app.update();
+    }
+    assert_eq!(
+        app.world().get::<MortarDialogueText>(target).unwrap().body,
+        "Waitforit"
+    );
+    assert_eq!(
+        progress(&app, target),
+        (
+            "Wa!ait".to_owned(),
+            status(false, true),
+            AdvanceIntent::ContinueReveal
+        )
+    );
+
+    send(&mut app, MortarCommand::next_text(), 10);
+    assert_eq!(
+        progress(&app, target),
+        (
+            "Wa!aitf!bor".to_owned(),
+            status(false, true),
+            AdvanceIntent::ContinueReveal
+        )
+    );
+
+    send(&mut app, MortarCommand::continue_reveal(), 10);
+    assert_eq!(
+        progress(&app, target),
+        (
+            "Wa!aitf!bori!ct".to_owned(),
+            status(true, false),
+            AdvanceIntent::NextLine
+        )
+    );
+    assert!(current_text(&app).unwrap().starts_with("Wait"));
+
+    send(&mut app, MortarCommand::next_text(), 3);
+    assert_eq!(current_text(&app).as_deref(), Some("Boom"));
+}
+
+#[test]
+fn test_skipping_passes_every_checkpoint() {
+    let (mut app, target) = setup_app();
+    for _ in 0..2 {
+        app.update();
+    }
+    let (_, line, intent) = progress(&app, target);
+    assert_eq!(line, status(false, false));
+    assert_eq!(intent, AdvanceIntent::RevealRemaining);
+
+    send(&mut app, MortarCommand::next_text(), 3);
+    assert_eq!(
+        progress(&app, target),
+        (
+            "Wa!aitf!bori!ct".to_owned(),
+            status(true, false),
+            AdvanceIntent::NextLine
+        )
+    );
+}
+
+#[test]
+fn test_event_action_pauses_reveal() {
+    let (mut app, target) = setup_app();
+    for _ in 0..10 {
+        app.update();
+    }
+    for _ in 0..3 {
+        send(&mut app, MortarCommand::next_text(), 10);
+    }
+    assert_eq!(current_text(&app).as_deref(), Some("Boom"));
+    assert_eq!(
+        app.world()
+            .get::<MortarTextReveal>(target)
+            .unwrap()
+            .revealed_chars(),
+        2
+    );
+    let (_, line, intent) = progress(&app, target);
+    assert_eq!(line, status(false, true));
+    assert_eq!(intent, AdvanceIntent::ContinueReveal);
+
+    send(&mut app, MortarCommand::next_text(), 10);
+    let (_, line, _) = progress(&app, target);
+    assert_eq!(line, status(true, false));
+}
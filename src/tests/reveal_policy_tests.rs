@@ -0,0 +1,148 @@
+//! Shows one line on several targets with different [`MortarRevealPolicy`] values: a gradual main
+//! box next to an instant or timed subtitle. Each target renders its own body, the line's events
+//! fire exactly once, and a timed subtitle clears without touching the main box.
+//!
+//! 在多个具有不同 [`MortarRevealPolicy`] 的目标上显示同一行：逐字显示的主对话框旁边放一个立即或定时
+//! 显示的字幕。每个目标渲染各自的正文，该行的事件只触发一次，定时字幕清空时不会影响主对话框。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use bevy::time::TimeUpdateStrategy;
+use mortar_compiler::Deserializer;
+use std::time::Duration;
+
+const PATH: &str = "policy.mortar";
+const FULL: &str = "[policy.mortar / Start]\n\nHello world";
+
+#[derive(Resource, Default)]
+struct Fired(Vec<String>);
+
+fn record_events(mut events: MessageReader<MortarGameEvent>, mut fired: ResMut<Fired>) {
+    fired
+        .0
+        .extend(events.read().map(|event| event.name.clone()));
+}
+
+fn policy_asset() -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [{
+            "name": "Start",
+            "content": [{
+                "type": "text",
+                "value": "Hello world",
+                "events": [
+                    { "index": 1, "actions": [{ "type": "blip" }] },
+                    { "index": 4, "actions": [{ "type": "chime" }] }
+                ]
+            }]
+        }],
+        "functions": []
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+/// Spawns a gradual main box and a subtitle with `subtitle_policy`, then starts the line.
+fn setup_app(subtitle_policy: MortarRevealPolicy) -> (App, Entity, Entity) {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+    ))
+    .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+        100,
+    )))
+    .init_resource::<Fired>()
+    .add_systems(PostUpdate, record_events);
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(policy_asset());
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+    let main_box = app
+        .world_mut()
+        .spawn((Text::new(""), MortarTextTarget, MortarTextReveal::new(10.0)))
+        .id();
+    let subtitle = app
+        .world_mut()
+        .spawn((
+            Text::new(""),
+            MortarTextTarget,
+            MortarTextReveal::new(10.0),
+            subtitle_policy,
+        ))
+        .id();
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, "Start"));
+    (app, main_box, subtitle)
+}
+
+fn text(app: &App, entity: Entity) -> String {
+    app.world().get::<Text>(entity).unwrap().0.clone()
+}
+
+#[test]
+fn test_gradual_and_instant_targets_share_one_line() {
+    let (mut app, main_box, subtitle) = setup_app(MortarRevealPolicy::Instant);
+    for _ in 0..4 {
+        app.update();
+    }
+    assert_ne!(text(&app, main_box), FULL);
+    assert!(FULL.starts_with(&text(&app, main_box)));
+    assert_eq!(text(&app, subtitle), FULL);
+    assert!(
+        app.world()
+            .get::<MortarLineStatus>(subtitle)
+            .unwrap()
+            .reveal_complete
+    );
+    assert!(app.world().get::<MortarEventTracker>(subtitle).is_none());
+
+    for _ in 0..20 {
+        app.update();
+    }
+    assert_eq!(text(&app, main_box), FULL);
+    assert_eq!(text(&app, subtitle), FULL);
+    assert_eq!(app.world().resource::<Fired>().0, ["blip", "chime"]);
+}
+
+#[test]
+fn test_timed_target_clears_without_affecting_others() {
+    let (mut app, main_box, subtitle) = setup_app(MortarRevealPolicy::Timed { seconds: 0.5 });
+    for _ in 0..4 {
+        app.update();
+    }
+    assert_eq!(text(&app, subtitle), FULL);
+
+    // 0.5s plus 11 chars at the reading speed, at 0.1s per frame.
+    //
+    // 0.5 秒加上以阅读速度阅读 11 个字符的时间，每帧 0.1 秒。
+    let expires = 0.5 + estimate_read_seconds("Hello world");
+    for _ in 0..(expires * 10.0).ceil() as usize + 2 {
+        app.update();
+    }
+    assert_eq!(text(&app, subtitle), "");
+    assert_eq!(text(&app, main_box), FULL);
+    assert_eq!(app.world().resource::<Fired>().0, ["blip", "chime"]);
+}
+
+#[test]
+fn test_settings_can_track_events_on_every_target() {
+    let (mut app, _, subtitle) = setup_app(MortarRevealPolicy::Instant);
+    app.insert_resource(MortarRevealPolicySettings {
+        track_events_on_all_targets: true,
+    });
+    for _ in 0..20 {
+        app.update();
+    }
+    assert!(app.world().get::<MortarEventTracker>(subtitle).is_some());
+    assert_eq!(
+        app.world().resource::<Fired>().0,
+        ["blip", "chime", "blip", "chime"]
+    );
+}
@@ -0,0 +1,174 @@
+//! Exercises reversible effects: actions with registered apply/revert pairs are recorded
+//! when they fire and reverted in reverse order once their line, node or conversation ends.
+//!
+//! 验证可撤销效果：注册了应用/撤销处理函数的动作在触发时被记录，并在所属的行、节点或对话结束时
+//! 按相反顺序撤销。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use mortar_compiler::Deserializer;
+use std::sync::{Arc, Mutex};
+
+const PATH: &str = "effects.mortar";
+
+type EffectLog = Arc<Mutex<Vec<String>>>;
+
+fn effects_asset() -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [
+            {
+                "name": "Start",
+                "content": [{ "type": "text", "value": "Hello" }],
+                "next": "Second"
+            },
+            {
+                "name": "Second",
+                "content": [
+                    { "type": "text", "value": "One" },
+                    { "type": "text", "value": "Two" }
+                ]
+            }
+        ],
+        "functions": []
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+fn logging_pair(effects: &mut MortarReversibleEffects, name: &'static str, log: &EffectLog) {
+    let apply_log = log.clone();
+    let revert_log = log.clone();
+    effects.on_effect(
+        name,
+        move |_, args| {
+            apply_log
+                .lock()
+                .unwrap()
+                .push(format!("apply {name} {}", args.join(",")));
+        },
+        move |_, args| {
+            revert_log
+                .lock()
+                .unwrap()
+                .push(format!("revert {name} {}", args.join(",")));
+        },
+    );
+}
+
+fn setup_app(log: &EffectLog) -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+    ));
+    {
+        let mut effects = app.world_mut().resource_mut::<MortarReversibleEffects>();
+        logging_pair(&mut effects, "set_color", log);
+        logging_pair(&mut effects, "set_animation", log);
+    }
+
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(effects_asset());
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+    app.world_mut().spawn((Text::new(""), MortarTextTarget));
+
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, "Start"));
+    for _ in 0..3 {
+        app.update();
+    }
+    app
+}
+
+fn fire(app: &mut App, name: &str, args: &[&str]) {
+    app.world_mut().write_message(MortarGameEvent::new(
+        None,
+        name,
+        args.iter().map(|arg| arg.to_string()).collect(),
+    ));
+    app.update();
+}
+
+fn reverts(log: &EffectLog) -> Vec<String> {
+    log.lock()
+        .unwrap()
+        .iter()
+        .filter(|entry| entry.starts_with("revert"))
+        .cloned()
+        .collect()
+}
+
+#[test]
+fn test_node_scoped_effects_revert_in_reverse_order_on_node_exit() {
+    let log = EffectLog::default();
+    let mut app = setup_app(&log);
+
+    fire(&mut app, "set_color", &["\"#FF0000\"", "scope=node"]);
+    fire(&mut app, "set_animation", &["angry", "scope=node"]);
+    assert_eq!(
+        app.world().resource::<MortarReversibleEffects>().applied()[0],
+        MortarAppliedEffect {
+            name: "set_color".to_string(),
+            args: vec!["#FF0000".to_string()],
+            scope: MortarEffectScope::Node,
+        }
+    );
+    assert!(reverts(&log).is_empty());
+
+    app.world_mut().write_message(MortarCommand::next_text());
+    for _ in 0..3 {
+        app.update();
+    }
+
+    let state = app.world().resource::<MortarRuntime>();
+    assert_eq!(
+        state.primary_dialogue_state().unwrap().current_node,
+        "Second"
+    );
+    assert_eq!(
+        reverts(&log),
+        vec!["revert set_animation angry", "revert set_color #FF0000"]
+    );
+    assert!(
+        app.world()
+            .resource::<MortarReversibleEffects>()
+            .applied()
+            .is_empty()
+    );
+}
+
+#[test]
+fn test_stop_dialogue_reverts_conversation_scoped_effects() {
+    let log = EffectLog::default();
+    let mut app = setup_app(&log);
+
+    fire(&mut app, "set_color", &["red"]);
+    fire(&mut app, "set_animation", &["wave", "scope=line"]);
+    fire(&mut app, "set_color", &["blue"]);
+    fire(&mut app, "unpaired_action", &[]);
+
+    // Moving on reverts the line-scoped effect but keeps the conversation-scoped one.
+    app.world_mut().write_message(MortarCommand::next_text());
+    for _ in 0..3 {
+        app.update();
+    }
+    assert_eq!(reverts(&log), vec!["revert set_animation wave"]);
+
+    app.world_mut()
+        .write_message(MortarCommand::stop_dialogue());
+    app.update();
+    app.update();
+
+    // Re-applying set_color replaced the pending revert instead of stacking.
+    assert_eq!(
+        reverts(&log),
+        vec!["revert set_animation wave", "revert set_color blue"]
+    );
+}
@@ -0,0 +1,160 @@
+//! Exercises how dialogue text targets behave while `run` statements execute.
+//! A minimal Bevy app drives a node whose line is followed by a timed run sequence
+//! and a choice, so the text stays on the same line while the runs play out.
+//!
+//! 验证 `run` 语句执行期间对话文本目标的表现。这里用一个最小的 Bevy 应用驱动一个
+//! 节点：文本后面跟着带时长的 run 序列和选项，因此 run 播放期间文本一直停留在同一行。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use bevy::time::TimeUpdateStrategy;
+use mortar_compiler::Deserializer;
+use std::time::Duration;
+
+const PATH: &str = "runs.mortar";
+const LINE: &str = "[runs.mortar / Start]\n\nPick one";
+
+fn runs_asset() -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [{
+            "name": "Start",
+            "content": [
+                { "type": "text", "value": "Pick one" },
+                { "type": "run_event", "name": "flash" },
+                { "type": "run_event", "name": "shake" },
+                { "type": "choice", "options": [{ "text": "Ok", "next": "return" }] }
+            ]
+        }],
+        "functions": [],
+        "events": [
+            { "name": "flash", "action": { "type": "flash" }, "duration": 0.1 },
+            { "name": "shake", "action": { "type": "shake" } }
+        ]
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+fn setup_app(behavior: RunTextBehavior) -> (App, Entity) {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+    ))
+    .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+        30,
+    )))
+    .insert_resource(behavior);
+
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(runs_asset());
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+    let target = app
+        .world_mut()
+        .spawn((Text::new(""), MortarTextTarget))
+        .id();
+
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, "Start"));
+    for _ in 0..3 {
+        app.update();
+    }
+    (app, target)
+}
+
+fn text_of(app: &App, entity: Entity) -> String {
+    app.world().get::<Text>(entity).unwrap().0.clone()
+}
+
+/// Sends `NextText` and gives the run system a frame to observe the runtime change.
+fn advance_into_runs(app: &mut App) {
+    app.world_mut().write_message(MortarCommand::next_text());
+    app.update();
+    app.update();
+}
+
+#[test]
+fn test_keep_leaves_text_intact_during_runs() {
+    let (mut app, target) = setup_app(RunTextBehavior::Keep);
+    assert_eq!(text_of(&app, target), LINE);
+
+    advance_into_runs(&mut app);
+    assert!(app.world().resource::<MortarRunsExecuting>().executing);
+    assert_eq!(text_of(&app, target), LINE);
+
+    for _ in 0..10 {
+        app.update();
+    }
+    assert!(!app.world().resource::<MortarRunsExecuting>().executing);
+    assert_eq!(text_of(&app, target), LINE);
+}
+
+#[test]
+fn test_clear_restores_text_after_runs_without_advance() {
+    let (mut app, target) = setup_app(RunTextBehavior::Clear);
+    assert_eq!(text_of(&app, target), LINE);
+
+    advance_into_runs(&mut app);
+    assert!(app.world().resource::<MortarRunsExecuting>().executing);
+    assert_eq!(text_of(&app, target), "");
+    assert_eq!(
+        app.world().get::<MortarDialogueText>(target).unwrap().body,
+        "",
+        "clearing should go through MortarDialogueText"
+    );
+
+    for _ in 0..10 {
+        app.update();
+    }
+    assert_eq!(text_of(&app, target), LINE);
+    assert_eq!(
+        app.world().get::<MortarDialogueText>(target).unwrap().body,
+        "Pick one"
+    );
+}
+
+#[test]
+fn test_dim_flags_line_status_while_blocked() {
+    let (mut app, target) = setup_app(RunTextBehavior::Dim);
+
+    advance_into_runs(&mut app);
+    assert_eq!(text_of(&app, target), LINE);
+    assert_eq!(
+        app.world().get::<MortarLineStatus>(target),
+        Some(&MortarLineStatus {
+            blocked_by_runs: true,
+            reveal_complete: false,
+            awaiting_advance: false,
+        })
+    );
+
+    for _ in 0..10 {
+        app.update();
+    }
+    assert_eq!(
+        app.world().get::<MortarLineStatus>(target),
+        Some(&MortarLineStatus {
+            blocked_by_runs: false,
+            reveal_complete: false,
+            awaiting_advance: false,
+        })
+    );
+}
+
+#[test]
+fn test_component_overrides_resource_behavior() {
+    let (mut app, target) = setup_app(RunTextBehavior::Clear);
+    app.world_mut()
+        .entity_mut(target)
+        .insert(RunTextBehavior::Keep);
+
+    advance_into_runs(&mut app);
+    assert_eq!(text_of(&app, target), LINE);
+}
@@ -0,0 +1,223 @@
+//! Covers saves taken and restored on the runtime: a snapshot inside a nested choice keeps the
+//! choice stack, the selection and the executed runs through a serde round trip, older saves
+//! without them still decode, restoring rebuilds the dialogue and its variables in a fresh app,
+//! and restoring into a changed file clamps what no longer fits or fails on a removed node.
+//!
+//! 覆盖在运行时上生成与恢复的存档：在嵌套选项中生成的快照经 serde 往返后保留选项栈、当前选择与已执行
+//! 的 run；不含这些字段的旧存档仍可解码；恢复会在新的应用中重建对话及其变量；恢复到已修改的文件时，
+//! 不再适用的部分会被限制或丢弃，节点被移除时恢复失败。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use mortar_compiler::Deserializer;
+
+const PATH: &str = "market.mortar";
+
+fn market_asset(content: serde_json::Value) -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [{ "name": "Market", "content": content }],
+        "functions": [],
+        "events": [{ "name": "Chime", "action": { "type": "chime" } }],
+        "variables": [{ "name": "gold", "type": "Number", "value": 20 }]
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+fn full_market() -> MortarAsset {
+    market_asset(serde_json::json!([
+        { "type": "text", "value": "Welcome." },
+        { "type": "run_event", "name": "Chime" },
+        { "type": "text", "value": "Buy something?" },
+        {
+            "type": "choice",
+            "options": [
+                { "text": "Food", "choice": [{ "text": "Bread" }, { "text": "Fish" }] },
+                { "text": "Leave" }
+            ]
+        }
+    ]))
+}
+
+fn setup_app(asset: MortarAsset) -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+    ));
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(asset);
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+    app.world_mut().spawn((Text::new(""), MortarTextTarget));
+    app
+}
+
+fn send(app: &mut App, command: MortarCommand) {
+    app.world_mut().write_message(command);
+    for _ in 0..3 {
+        app.update();
+    }
+}
+
+/// Plays into the nested food choice with "Fish" selected and gold spent.
+fn shopping_save() -> MortarSaveData {
+    let mut app = setup_app(full_market());
+    for command in [
+        MortarCommand::start_node(PATH, "Market"),
+        MortarCommand::next_text(),
+        MortarCommand::select_choice(0),
+        MortarCommand::confirm_choice(),
+        MortarCommand::select_choice(1),
+    ] {
+        send(&mut app, command);
+    }
+    app.world_mut()
+        .resource_mut::<MortarDialogueVariables>()
+        .state
+        .as_mut()
+        .expect("variables should be initialized")
+        .set("gold", MortarVariableValue::Number(12.0));
+    let world = app.world();
+    world
+        .resource::<MortarRuntime>()
+        .snapshot(world.resource::<MortarDialogueVariables>())
+        .expect("a dialogue is active")
+}
+
+fn restore(app: &mut App, save: &MortarSaveData) -> Result<(), MortarRestoreError> {
+    app.world_mut()
+        .resource_scope(|world, mut runtime: Mut<MortarRuntime>| {
+            world.resource_scope(|world, mut variables: Mut<MortarDialogueVariables>| {
+                let assets = world.resource::<Assets<MortarAsset>>();
+                let registry = world.resource::<MortarRegistry>();
+                runtime.restore(save, assets, registry, &mut variables)
+            })
+        })
+}
+
+fn primary(app: &App) -> &DialogueState {
+    app.world()
+        .resource::<MortarRuntime>()
+        .primary_dialogue_state()
+        .expect("a dialogue is active")
+}
+
+#[test]
+fn test_snapshot_round_trips_choice_and_run_progress() {
+    let save = shopping_save();
+    let dialogue = save.dialogue.as_ref().expect("the dialogue is saved");
+    assert_eq!(dialogue.node, "Market");
+    assert_eq!(dialogue.text_index, 1);
+    assert_eq!(dialogue.choice_stack, [0]);
+    assert_eq!(dialogue.selected_choice, Some(1));
+    assert!(!dialogue.choices_broken);
+    assert_eq!(dialogue.executed_content_indices, [1]);
+    assert_eq!(
+        save.variables.get("gold"),
+        Some(&MortarVariableValue::Number(12.0))
+    );
+
+    let bytes = save.to_bytes();
+    let loaded = MortarSaveData::from_bytes(&bytes, &MortarSaveMigrations::default())
+        .expect("save should load");
+    assert_eq!(loaded, save);
+
+    let old = r#"{"version":1,"dialogue":{"mortar_path":"market.mortar","node":"Market","text_index":1},"variables":{}}"#;
+    let old = MortarSaveData::from_bytes(old.as_bytes(), &MortarSaveMigrations::default())
+        .expect("saves without choice progress still load");
+    let dialogue = old.dialogue.expect("the dialogue is saved");
+    assert!(dialogue.choice_stack.is_empty());
+    assert_eq!(dialogue.selected_choice, None);
+    assert!(dialogue.executed_content_indices.is_empty());
+
+    let idle = setup_app(full_market());
+    let world = idle.world();
+    let runtime = world.resource::<MortarRuntime>();
+    assert_eq!(
+        runtime.snapshot(world.resource::<MortarDialogueVariables>()),
+        None
+    );
+}
+
+#[test]
+fn test_restore_rebuilds_the_dialogue_in_a_fresh_app() {
+    let save = shopping_save();
+    let mut app = setup_app(full_market());
+    app.update();
+    restore(&mut app, &save).expect("the save fits the file");
+
+    let state = primary(&app);
+    assert_eq!(state.current_node, "Market");
+    assert_eq!(state.text_index, 1);
+    assert_eq!(state.choice_stack, [0]);
+    assert_eq!(state.selected_choice, Some(1));
+    assert!(state.executed_content_indices.contains(&1));
+    let options: Vec<&str> = state
+        .get_choices()
+        .expect("the nested choice is shown")
+        .iter()
+        .map(|choice| choice.text.as_str())
+        .collect();
+    assert_eq!(options, ["Bread", "Fish"]);
+
+    for _ in 0..3 {
+        app.update();
+    }
+    let gold = app
+        .world()
+        .resource::<MortarDialogueVariables>()
+        .state
+        .as_ref()
+        .and_then(|state| state.get("gold").cloned());
+    assert_eq!(gold, Some(MortarVariableValue::Number(12.0)));
+    let mut targets = app.world_mut().query::<&MortarDialogueText>();
+    let shown = targets.single(app.world()).expect("one text target");
+    assert_eq!(shown.body, "Buy something?");
+}
+
+#[test]
+fn test_restore_into_a_changed_file_clamps_or_fails() {
+    let save = shopping_save();
+    let changed = market_asset(serde_json::json!([
+        { "type": "text", "value": "Closed today." },
+        { "type": "choice", "options": [{ "text": "Leave" }] }
+    ]));
+    let mut app = setup_app(changed);
+    app.update();
+    restore(&mut app, &save).expect("the node still exists");
+
+    let state = primary(&app);
+    assert_eq!(state.text_index, 0);
+    assert!(state.choice_stack.is_empty());
+    assert_eq!(state.selected_choice, None);
+    assert!(state.executed_content_indices.contains(&1));
+
+    let mut missing = save.clone();
+    if let Some(dialogue) = missing.dialogue.as_mut() {
+        dialogue.node = "Harbor".to_owned();
+    }
+    assert_eq!(
+        restore(&mut app, &missing),
+        Err(MortarRestoreError::NodeNotFound {
+            mortar_path: PATH.to_owned(),
+            node: "Harbor".to_owned(),
+        })
+    );
+    assert_eq!(primary(&app).current_node, "Market");
+
+    let mut unknown = save;
+    if let Some(dialogue) = unknown.dialogue.as_mut() {
+        dialogue.mortar_path = "harbor.mortar".to_owned();
+    }
+    assert!(matches!(
+        restore(&mut app, &unknown),
+        Err(MortarRestoreError::UnknownFile { .. })
+    ));
+}
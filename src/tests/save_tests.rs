@@ -0,0 +1,155 @@
+//! Covers the versioned save format: progress round-trips through bytes and resumes on the saved
+//! line, older saves are upgraded by registered migrations, and saves from a newer version are
+//! rejected with a typed error.
+//!
+//! 覆盖带版本号的存档格式：进度可以经字节往返并在存档所在行恢复，旧存档由注册的迁移函数升级，
+//! 来自更新版本的存档会以强类型错误拒绝。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use mortar_compiler::Deserializer;
+
+const PATH: &str = "save.mortar";
+
+fn save_asset() -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [{
+            "name": "Start",
+            "content": [
+                { "type": "text", "value": "One" },
+                { "type": "text", "value": "Two" },
+                { "type": "text", "value": "Three" }
+            ]
+        }],
+        "functions": [],
+        "variables": [{ "name": "gold", "type": "Number", "value": 20 }]
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+fn setup_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+    ));
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(save_asset());
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+    app.world_mut().spawn((Text::new(""), MortarTextTarget));
+    app
+}
+
+fn send(app: &mut App, event: MortarCommand) {
+    app.world_mut().write_message(event);
+    for _ in 0..3 {
+        app.update();
+    }
+}
+
+fn gold(app: &App) -> Option<&MortarVariableValue> {
+    app.world()
+        .resource::<MortarDialogueVariables>()
+        .state
+        .as_ref()?
+        .get("gold")
+}
+
+#[test]
+fn test_save_round_trips_and_resumes() {
+    let mut app = setup_app();
+    send(&mut app, MortarCommand::start_node(PATH, "Start"));
+    send(&mut app, MortarCommand::next_text());
+    app.world_mut()
+        .resource_mut::<MortarDialogueVariables>()
+        .state
+        .as_mut()
+        .expect("variables should be initialized")
+        .set("gold", MortarVariableValue::Number(7.0));
+
+    let world = app.world();
+    let save = MortarSaveData::capture(
+        world.resource::<MortarRuntime>(),
+        world.resource::<MortarDialogueVariables>(),
+    );
+    assert_eq!(save.version, MORTAR_SAVE_VERSION);
+    let bytes = save.to_bytes();
+    let loaded = MortarSaveData::from_bytes(&bytes, &MortarSaveMigrations::default())
+        .expect("save should load");
+    assert_eq!(loaded, save);
+
+    let mut resumed = setup_app();
+    let event = loaded
+        .restore(
+            &mut resumed
+                .world_mut()
+                .resource_mut::<MortarDialogueVariables>(),
+        )
+        .expect("save should hold a dialogue");
+    send(&mut resumed, event);
+    let state = resumed
+        .world()
+        .resource::<MortarRuntime>()
+        .primary_dialogue_state()
+        .expect("dialogue should resume");
+    assert_eq!(state.current_text(), Some("Two"));
+    assert_eq!(gold(&resumed), Some(&MortarVariableValue::Number(7.0)));
+}
+
+fn rename_vars(_version: u32, tree: &mut serde_json::Value) -> Result<(), String> {
+    let fields = tree.as_object_mut().ok_or("save is not an object")?;
+    let vars = fields.remove("vars").ok_or("missing 'vars'")?;
+    fields.insert("variables".to_owned(), vars);
+    Ok(())
+}
+
+#[test]
+fn test_registered_migration_upgrades_old_save() {
+    let old = serde_json::json!({
+        "version": 0,
+        "dialogue": null,
+        "vars": { "gold": { "Number": 3.0 } }
+    })
+    .to_string();
+
+    let missing = MortarSaveData::from_bytes(old.as_bytes(), &MortarSaveMigrations::default());
+    assert!(matches!(
+        missing,
+        Err(MortarSaveError::MissingMigration { from: 0 })
+    ));
+
+    let mut migrations = MortarSaveMigrations::default();
+    migrations.register(0, rename_vars);
+    let save = MortarSaveData::from_bytes(old.as_bytes(), &migrations).expect("save should load");
+    assert_eq!(save.version, MORTAR_SAVE_VERSION);
+    assert_eq!(
+        save.variables.get("gold"),
+        Some(&MortarVariableValue::Number(3.0))
+    );
+}
+
+#[test]
+fn test_newer_and_malformed_saves_are_rejected() {
+    let newer = serde_json::json!({ "version": MORTAR_SAVE_VERSION + 1 }).to_string();
+    assert!(matches!(
+        MortarSaveData::from_bytes(newer.as_bytes(), &MortarSaveMigrations::default()),
+        Err(MortarSaveError::NewerVersion { found, supported })
+            if found == MORTAR_SAVE_VERSION + 1 && supported == MORTAR_SAVE_VERSION
+    ));
+    assert!(matches!(
+        MortarSaveData::from_bytes(b"{}", &MortarSaveMigrations::default()),
+        Err(MortarSaveError::MissingVersion)
+    ));
+    assert!(matches!(
+        MortarSaveData::from_bytes(b"not json", &MortarSaveMigrations::default()),
+        Err(MortarSaveError::Format(_))
+    ));
+}
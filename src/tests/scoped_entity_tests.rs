@@ -0,0 +1,174 @@
+//! Covers dialogue-scoped entities: entities spawned for a line, node or conversation are
+//! despawned, children included, exactly when that scope ends, and an entity tagged with the
+//! generation of a finished scope never survives into the next one.
+//!
+//! 覆盖对话范围实体：为行、节点或整段对话生成的实体会在该范围结束时（连同子实体）被销毁；
+//! 带有已结束范围代数的实体不会存活到下一个范围。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use bevy::ecs::system::RunSystemOnce;
+use mortar_compiler::Deserializer;
+
+const PATH: &str = "scoped.mortar";
+
+#[derive(Component)]
+struct Emote;
+
+fn scoped_asset() -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [
+            {
+                "name": "Start",
+                "content": [
+                    { "type": "text", "value": "First" },
+                    { "type": "text", "value": "Second" }
+                ],
+                "next": "Other"
+            },
+            {
+                "name": "Other",
+                "content": [
+                    { "type": "text", "value": "Third" },
+                    { "type": "text", "value": "Fourth" }
+                ]
+            }
+        ],
+        "functions": []
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+fn setup_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+    ));
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(scoped_asset());
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+    app.world_mut().spawn((Text::new(""), MortarTextTarget));
+
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, "Start"));
+    for _ in 0..3 {
+        app.update();
+    }
+    app
+}
+
+fn spawn(app: &mut App, scope: MortarEffectScope) -> Entity {
+    app.world_mut()
+        .run_system_once(move |mut scoped: MortarScopedCommands| {
+            scoped.spawn_scoped(scope, Emote).id()
+        })
+        .expect("spawn system should run")
+}
+
+fn advance(app: &mut App) {
+    app.world_mut().write_message(MortarCommand::next_text());
+    for _ in 0..3 {
+        app.update();
+    }
+}
+
+fn alive(app: &App, entities: &[Entity]) -> Vec<bool> {
+    entities
+        .iter()
+        .map(|entity| app.world().get_entity(*entity).is_ok())
+        .collect()
+}
+
+fn current_text(app: &App) -> Option<&str> {
+    app.world()
+        .resource::<MortarRuntime>()
+        .primary_dialogue_state()
+        .and_then(DialogueState::current_text)
+}
+
+#[test]
+fn test_scoped_entities_despawn_when_their_scope_ends() {
+    let mut app = setup_app();
+    let line = spawn(&mut app, MortarEffectScope::Line);
+    let child = app.world_mut().spawn(ChildOf(line)).id();
+    let node = spawn(&mut app, MortarEffectScope::Node);
+    let conversation = spawn(&mut app, MortarEffectScope::Conversation);
+    app.update();
+    assert_eq!(alive(&app, &[line, child, node, conversation]), [true; 4]);
+
+    advance(&mut app);
+    assert_eq!(current_text(&app), Some("Second"));
+    assert_eq!(
+        alive(&app, &[line, child, node, conversation]),
+        [false, false, true, true]
+    );
+    let second_line = spawn(&mut app, MortarEffectScope::Line);
+
+    advance(&mut app);
+    assert_eq!(current_text(&app), Some("Third"));
+    assert_eq!(
+        alive(&app, &[second_line, node, conversation]),
+        [false, false, true]
+    );
+    let other_node = spawn(&mut app, MortarEffectScope::Node);
+
+    advance(&mut app);
+    assert_eq!(current_text(&app), Some("Fourth"));
+    assert_eq!(alive(&app, &[other_node, conversation]), [true, true]);
+
+    advance(&mut app);
+    assert!(
+        !app.world()
+            .resource::<MortarRuntime>()
+            .has_active_dialogues()
+    );
+    assert_eq!(alive(&app, &[other_node, conversation]), [false, false]);
+}
+
+#[test]
+fn test_stale_generation_does_not_leak_into_next_scope() {
+    let mut app = setup_app();
+    let stale_generation = app
+        .world()
+        .resource::<MortarScopeGenerations>()
+        .current(MortarEffectScope::Line)
+        .expect("dialogue should be active");
+    advance(&mut app);
+
+    let late = app
+        .world_mut()
+        .spawn((
+            Emote,
+            MortarScoped {
+                scope: MortarEffectScope::Line,
+                generation: stale_generation,
+            },
+        ))
+        .id();
+    let current = spawn(&mut app, MortarEffectScope::Line);
+    app.update();
+    assert_eq!(alive(&app, &[late, current]), [false, true]);
+}
+
+#[test]
+fn test_spawn_without_dialogue_is_cleaned_up() {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+    ));
+    let orphan = spawn(&mut app, MortarEffectScope::Conversation);
+    app.update();
+    assert_eq!(alive(&app, &[orphan]), [false]);
+}
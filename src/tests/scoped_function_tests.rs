@@ -0,0 +1,120 @@
+//! Covers functions scoped to a Mortar path: two files interpolating the same `get_name` render
+//! their own scoped bindings, a file without one falls back to the global binding, and the
+//! layered view of `MortarRuntime::functions_for` resolves the same way.
+//!
+//! 覆盖作用于 Mortar 路径的函数：两个文件插值同一个 `get_name` 时各自渲染自己的作用域绑定，没有
+//! 作用域绑定的文件回退到全局绑定，`MortarRuntime::functions_for` 的分层视图也按同样方式解析。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use mortar_compiler::Deserializer;
+
+const FILES: [&str; 3] = ["pub.mortar", "demo.mortar", "plain.mortar"];
+
+fn name_asset() -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [{
+            "name": "Start",
+            "content": [{
+                "type": "text",
+                "value": "Hello {get_name}",
+                "interpolated_parts": [
+                    { "type": "text", "content": "Hello " },
+                    {
+                        "type": "expression",
+                        "content": "{get_name}",
+                        "function_name": "get_name",
+                        "args": []
+                    }
+                ]
+            }]
+        }],
+        "functions": [{ "name": "get_name", "params": [], "return_type": "String" }]
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+fn register_names(functions: &mut MortarFunctionRegistry) {
+    functions.register("get_name", |_| "stranger".into());
+    functions.register_scoped("pub.mortar", "get_name", |_| "barkeep".into());
+    functions.register_scoped("demo.mortar", "get_name", |_| "tester".into());
+}
+
+/// The line rendered when starting the file at `path`.
+fn render(path: &str) -> String {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+    ));
+    register_names(&mut app.world_mut().resource_mut::<MortarRuntime>().functions);
+    for file in FILES {
+        let handle = app
+            .world_mut()
+            .resource_mut::<Assets<MortarAsset>>()
+            .add(name_asset());
+        app.world_mut()
+            .resource_mut::<MortarRegistry>()
+            .register(file, handle);
+    }
+    let target = app
+        .world_mut()
+        .spawn((Text::new(""), MortarTextTarget))
+        .id();
+    app.world_mut()
+        .write_message(MortarCommand::start_node(path, "Start"));
+    for _ in 0..3 {
+        app.update();
+    }
+    app.world()
+        .get::<MortarDialogueText>(target)
+        .unwrap()
+        .body
+        .clone()
+}
+
+#[test]
+fn test_scoped_function_shadows_global_in_its_file() {
+    assert_eq!(render("pub.mortar"), "Hello barkeep");
+    assert_eq!(render("demo.mortar"), "Hello tester");
+}
+
+#[test]
+fn test_file_without_scoped_function_falls_back_to_global() {
+    assert_eq!(render("plain.mortar"), "Hello stranger");
+}
+
+fn text(value: Option<MortarValue>) -> Option<String> {
+    value.and_then(|value| String::try_from(value).ok())
+}
+
+#[test]
+fn test_functions_for_layers_scoped_over_global() {
+    let mut runtime = MortarRuntime::default();
+    register_names(&mut runtime.functions);
+    runtime.functions.register("get_title", |_| "Sir".into());
+
+    let scoped = runtime.functions_for("pub.mortar");
+    assert_eq!(
+        text(scoped.call("get_name", &[])),
+        Some("barkeep".to_owned())
+    );
+    assert!(scoped.is_scoped("get_name"));
+    assert_eq!(text(scoped.call("get_title", &[])), Some("Sir".to_owned()));
+    assert!(!scoped.is_scoped("get_title"));
+    assert!(!scoped.contains("get_age"));
+
+    assert_eq!(
+        text(runtime.functions.call("get_name", &[])),
+        Some("stranger".to_owned())
+    );
+    assert!(runtime.functions.remove_scoped("pub.mortar"));
+    assert_eq!(
+        text(runtime.functions_for("pub.mortar").call("get_name", &[])),
+        Some("stranger".to_owned())
+    );
+}
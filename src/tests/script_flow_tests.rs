@@ -0,0 +1,179 @@
+//! Covers script-driven flow control: a `__next_text` event in the middle of a revealing line
+//! advances once the reveal passes it, either finishing the reveal or waiting for it, a `__jump`
+//! fired from a timeline lands on the named node, and the whole feature can be switched off.
+//!
+//! 覆盖由脚本驱动的流程控制：逐字显示中的行内 `__next_text` 事件会在显示越过它之后推进，可以选择补全
+//! 显示或等待显示完毕；时间线触发的 `__jump` 会跳到指定节点；整个功能也可以关闭。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use bevy::time::TimeUpdateStrategy;
+use mortar_compiler::Deserializer;
+use std::time::Duration;
+
+const PATH: &str = "flow.mortar";
+
+#[derive(Resource, Default)]
+struct Flows(Vec<MortarScriptFlow>);
+
+fn record_flows(mut events: MessageReader<MortarScriptFlow>, mut flows: ResMut<Flows>) {
+    flows.0.extend(events.read().cloned());
+}
+
+fn flow_asset() -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [
+            {
+                "name": "Start",
+                "content": [
+                    {
+                        "type": "text",
+                        "value": "Wait for it, then go",
+                        "events": [{ "index": 5, "actions": [{ "type": "__next_text" }] }]
+                    },
+                    { "type": "text", "value": "Moved on" }
+                ]
+            },
+            {
+                "name": "Alarm",
+                "content": [
+                    { "type": "text", "value": "Quiet" },
+                    { "type": "run_event", "name": "Siren" },
+                    { "type": "text", "value": "Unreachable" }
+                ]
+            },
+            { "name": "Panic", "content": [{ "type": "text", "value": "Run!" }] }
+        ],
+        "functions": [],
+        "events": [{ "name": "sound", "action": { "type": "__jump", "args": ["Panic"] } }],
+        "timelines": [{ "name": "Siren", "statements": [{ "type": "run", "event_name": "sound" }] }]
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+fn setup_app(settings: MortarScriptFlowSettings, node: &str) -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+    ))
+    .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+        100,
+    )))
+    .insert_resource(settings)
+    .init_resource::<Flows>()
+    .add_systems(PostUpdate, record_flows);
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(flow_asset());
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+    app.world_mut()
+        .spawn((Text::new(""), MortarTextTarget, MortarTextReveal::new(10.0)));
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, node));
+    app
+}
+
+fn current_text(app: &App) -> Option<String> {
+    let runtime = app.world().resource::<MortarRuntime>();
+    let state = runtime.primary_dialogue_state()?;
+    state.current_text().map(str::to_owned)
+}
+
+fn revealed(app: &mut App) -> Vec<usize> {
+    let mut query = app.world_mut().query::<&MortarTextReveal>();
+    query
+        .iter(app.world())
+        .map(|reveal| reveal.revealed_chars())
+        .collect()
+}
+
+#[test]
+fn test_next_text_event_finishes_reveal_then_advances() {
+    let mut app = setup_app(MortarScriptFlowSettings::default(), "Start");
+    for _ in 0..3 {
+        app.update();
+    }
+    assert_eq!(current_text(&app).as_deref(), Some("Wait for it, then go"));
+    assert!(app.world().resource::<Flows>().0.is_empty());
+
+    // 10 chars per second at 0.1s per frame passes index 5 after about six frames.
+    //
+    // 每秒 10 个字符、每帧 0.1 秒，约六帧后越过索引 5。
+    for _ in 0..10 {
+        app.update();
+    }
+    assert_eq!(current_text(&app).as_deref(), Some("Moved on"));
+    let flows = &app.world().resource::<Flows>().0;
+    assert_eq!(flows.len(), 1);
+    assert_eq!(flows[0].action, "__next_text");
+    assert!(matches!(flows[0].event, MortarCommand::NextText { .. }));
+}
+
+#[test]
+fn test_next_text_event_can_wait_for_reveal() {
+    let settings = MortarScriptFlowSettings {
+        advance: MortarScriptAdvance::WaitForReveal,
+        ..default()
+    };
+    let mut app = setup_app(settings, "Start");
+    for _ in 0..13 {
+        app.update();
+    }
+    assert_eq!(current_text(&app).as_deref(), Some("Wait for it, then go"));
+    assert!(revealed(&mut app).iter().all(|&chars| chars < 20));
+
+    for _ in 0..20 {
+        app.update();
+    }
+    assert_eq!(current_text(&app).as_deref(), Some("Moved on"));
+}
+
+#[test]
+fn test_jump_from_timeline_lands_on_target_node() {
+    let mut app = setup_app(MortarScriptFlowSettings::default(), "Alarm");
+    for _ in 0..3 {
+        app.update();
+    }
+    assert_eq!(current_text(&app).as_deref(), Some("Quiet"));
+
+    // The first advance finishes the reveal, the second runs the timeline.
+    //
+    // 第一次推进补全逐字显示，第二次执行时间线。
+    for _ in 0..2 {
+        app.world_mut().write_message(MortarCommand::next_text());
+        for _ in 0..5 {
+            app.update();
+        }
+    }
+    let runtime = app.world().resource::<MortarRuntime>();
+    assert_eq!(
+        runtime.primary_dialogue_state().unwrap().current_node,
+        "Panic"
+    );
+    assert_eq!(current_text(&app).as_deref(), Some("Run!"));
+    let flows = &app.world().resource::<Flows>().0;
+    assert_eq!(flows.len(), 1);
+    assert_eq!(flows[0].action, "__jump");
+}
+
+#[test]
+fn test_flow_control_can_be_disabled() {
+    let settings = MortarScriptFlowSettings {
+        allow_script_flow_control: false,
+        ..default()
+    };
+    let mut app = setup_app(settings, "Start");
+    for _ in 0..40 {
+        app.update();
+    }
+    assert_eq!(current_text(&app).as_deref(), Some("Wait for it, then go"));
+    assert!(app.world().resource::<Flows>().0.is_empty());
+}
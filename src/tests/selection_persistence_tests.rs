@@ -0,0 +1,182 @@
+//! Covers `SelectionPersistence` around the advance-then-present sequence: a guard speaks two
+//! lines before offering a bribe that needs gold. Selecting the bribe on the first line is
+//! refused and a stale selection is cleared on advance by default, kept through the advance by
+//! the keep policies, kept even while the bribe is disabled only across group rebuilds, and never
+//! confirmed on its own under one-step confirmation.
+//!
+//! 覆盖推进后再呈现这一流程中的 `SelectionPersistence`：守卫先说两行，再提供一个需要金币的贿赂
+//! 选项。默认情况下在第一行选中贿赂会被拒绝，残留的选中会在推进时清除；保留策略会让选中在推进后
+//! 保留；只有跨选项组重建保留时，选中在贿赂被禁用期间也会保留；在一步确认下被保留的选中永远不会
+//! 自行确认。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use mortar_compiler::Deserializer;
+
+const PATH: &str = "gate.mortar";
+
+fn gate_asset() -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [
+            {
+                "name": "Start",
+                "content": [
+                    { "type": "text", "value": "Halt!" },
+                    { "type": "text", "value": "State your business." },
+                    { "type": "choice", "options": [
+                        {
+                            "text": "Bribe the guard (10g)",
+                            "condition": { "type": "gold", "args": [">=", "10"] },
+                            "next": "Bribed"
+                        },
+                        { "text": "Leave", "next": "return" }
+                    ] }
+                ]
+            },
+            { "name": "Bribed", "content": [{ "type": "text", "value": "Go on." }] }
+        ],
+        "functions": [],
+        "variables": [{ "name": "gold", "type": "Number", "value": 20 }]
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+fn setup_app(persistence: SelectionPersistence, confirm: ChoiceConfirmMode) -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+    ));
+    {
+        let mut runtime = app.world_mut().resource_mut::<MortarRuntime>();
+        runtime.selection_persistence = persistence;
+        runtime.choice_confirm = confirm;
+    }
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(gate_asset());
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+    app.world_mut().spawn((Text::new(""), MortarTextTarget));
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, "Start"));
+    for _ in 0..3 {
+        app.update();
+    }
+    app
+}
+
+fn send(app: &mut App, event: MortarCommand) {
+    app.world_mut().write_message(event);
+    for _ in 0..3 {
+        app.update();
+    }
+}
+
+fn select_bribe(app: &mut App) {
+    send(
+        app,
+        MortarCommand::SelectChoice {
+            index: 0,
+            target: None,
+            group: None,
+            source: None,
+        },
+    );
+}
+
+fn set_gold(app: &mut App, gold: f64) {
+    app.world_mut()
+        .resource_mut::<MortarDialogueVariables>()
+        .state
+        .as_mut()
+        .expect("variables initialized by the text system")
+        .set("gold", MortarVariableValue::Number(gold));
+    app.update();
+}
+
+fn primary_state(app: &App) -> &DialogueState {
+    app.world()
+        .resource::<MortarRuntime>()
+        .primary_dialogue_state()
+        .expect("dialogue should still be active")
+}
+
+/// Selects the bribe on the first line and advances to the line that presents the group.
+fn preselect_and_advance(app: &mut App) {
+    select_bribe(app);
+    send(app, MortarCommand::next_text());
+    assert!(primary_state(app).choices_presentable());
+}
+
+#[test]
+fn test_clear_on_advance_refuses_and_clears_preselection() {
+    let mut app = setup_app(SelectionPersistence::default(), ChoiceConfirmMode::TwoStep);
+    preselect_and_advance(&mut app);
+    assert_eq!(primary_state(&app).selected_choice, None);
+
+    let mut app = setup_app(
+        SelectionPersistence::ClearOnAdvance,
+        ChoiceConfirmMode::TwoStep,
+    );
+    app.world_mut()
+        .resource_mut::<MortarRuntime>()
+        .primary_dialogue_state_mut()
+        .unwrap()
+        .selected_choice = Some(0);
+    send(&mut app, MortarCommand::next_text());
+    assert_eq!(primary_state(&app).selected_choice, None);
+}
+
+#[test]
+fn test_keep_until_group_presents_drops_disabled_selection() {
+    let mut app = setup_app(
+        SelectionPersistence::KeepUntilGroupPresents,
+        ChoiceConfirmMode::TwoStep,
+    );
+    preselect_and_advance(&mut app);
+    assert_eq!(primary_state(&app).selected_choice, Some(0));
+
+    set_gold(&mut app, 5.0);
+    assert_eq!(primary_state(&app).selected_choice, None);
+}
+
+#[test]
+fn test_keep_across_group_rebuilds_holds_disabled_selection() {
+    let mut app = setup_app(
+        SelectionPersistence::KeepAcrossGroupRebuilds,
+        ChoiceConfirmMode::TwoStep,
+    );
+    preselect_and_advance(&mut app);
+
+    set_gold(&mut app, 5.0);
+    assert_eq!(primary_state(&app).selected_choice, Some(0));
+    send(&mut app, MortarCommand::confirm_choice());
+    assert_eq!(primary_state(&app).current_node, "Start");
+
+    set_gold(&mut app, 20.0);
+    send(&mut app, MortarCommand::confirm_choice());
+    assert_eq!(primary_state(&app).current_node, "Bribed");
+}
+
+#[test]
+fn test_kept_selection_is_not_confirmed_under_one_step() {
+    let mut app = setup_app(
+        SelectionPersistence::KeepUntilGroupPresents,
+        ChoiceConfirmMode::OneStep,
+    );
+    preselect_and_advance(&mut app);
+    send(&mut app, MortarCommand::next_text());
+    let state = primary_state(&app);
+    assert_eq!(state.current_node, "Start");
+    assert_eq!(state.selected_choice, Some(0));
+
+    select_bribe(&mut app);
+    assert_eq!(primary_state(&app).current_node, "Bribed");
+}
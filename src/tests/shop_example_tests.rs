@@ -0,0 +1,180 @@
+//! Plays the `shop_npc` example headlessly, with its own bindings and `assets/shop.mortar`: a
+//! purchase takes gold and fills the inventory, an unaffordable one changes neither, the sold
+//! elixir stays disabled when the shop is entered again, and haggling plays its timeline.
+//!
+//! 以无头方式运行 `shop_npc` 示例，使用其自身的绑定与 `assets/shop.mortar`：购买会扣除金币并
+//! 填充物品栏，买不起时两者都不变，售出的灵药在再次进入商店时保持禁用，讨价还价会播放其时间线。
+
+#[path = "../../examples/utils/shop.rs"]
+mod shop;
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use bevy::time::TimeUpdateStrategy;
+use shop::{Inventory, SHOP_PATH, ShopPlugin, ShopStock, ShopkeeperPose};
+use std::path::Path;
+use std::time::Duration;
+
+fn setup_app(gold: f64) -> (App, Entity) {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+        ShopPlugin,
+    ));
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+        100,
+    )));
+    let asset = MortarAssetLoader::load_asset_bytes(
+        include_bytes!("../../assets/shop.mortar"),
+        Path::new(SHOP_PATH),
+    )
+    .expect("shop.mortar should compile");
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(asset);
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(SHOP_PATH, handle);
+    let target = app
+        .world_mut()
+        .spawn((Text::new(""), MortarTextTarget))
+        .id();
+    app.world_mut().resource_mut::<Inventory>().gold = gold;
+    open(&mut app);
+    (app, target)
+}
+
+fn send(app: &mut App, event: MortarCommand) {
+    app.world_mut().write_message(event);
+    for _ in 0..3 {
+        app.update();
+    }
+}
+
+fn body(app: &App, target: Entity) -> String {
+    app.world()
+        .get::<MortarDialogueText>(target)
+        .unwrap()
+        .body
+        .clone()
+}
+
+/// Walks into the shop the way the example does, seeding the game's gold and stock.
+fn open(app: &mut App) {
+    let event =
+        app.world_mut()
+            .resource_scope(|world, mut variables: Mut<MortarDialogueVariables>| {
+                let inventory = world.resource::<Inventory>();
+                shop::open_shop(inventory, world.resource::<ShopStock>(), &mut variables)
+            });
+    send(app, event);
+}
+
+/// Advances past the greeting and picks option `index` of the shop menu.
+fn choose(app: &mut App, index: usize) {
+    send(app, MortarCommand::next_text());
+    send(app, MortarCommand::select_choice(index));
+    send(app, MortarCommand::confirm_choice());
+}
+
+fn gold(app: &App) -> Option<MortarVariableValue> {
+    let variables = app.world().resource::<MortarDialogueVariables>();
+    variables.state.as_ref()?.get("gold").cloned()
+}
+
+fn inventory(app: &App, id: &str) -> u32 {
+    let items = &app.world().resource::<Inventory>().items;
+    items.get(id).copied().unwrap_or(0)
+}
+
+fn elixir_enabled(app: &App) -> bool {
+    let presented = app.world().resource::<MortarChoicesPresented>();
+    presented
+        .views
+        .iter()
+        .find(|view| view.index == 1)
+        .unwrap()
+        .enabled
+}
+
+#[test]
+fn test_purchase_takes_gold_and_fills_inventory() {
+    let (mut app, target) = setup_app(20.0);
+    assert_eq!(body(&app, target), "Welcome, traveler! You have 20 gold.");
+    choose(&mut app, 0);
+    assert_eq!(body(&app, target), "One potion, fresh from the cauldron.");
+
+    send(&mut app, MortarCommand::next_text());
+    assert_eq!(inventory(&app, "potion"), 1);
+    assert_eq!(app.world().resource::<Inventory>().gold, 12.0);
+    assert_eq!(gold(&app), Some(MortarVariableValue::Number(12.0)));
+    assert_eq!(body(&app, target), "Welcome, traveler! You have 12 gold.");
+
+    let functions = &app.world().resource::<MortarRuntime>().functions;
+    assert!(matches!(
+        functions.try_call("price_of", &[3.0.into()]),
+        Err(MortarCallError::Coercion { .. })
+    ));
+}
+
+#[test]
+fn test_unaffordable_purchase_changes_nothing() {
+    let (mut app, target) = setup_app(20.0);
+    choose(&mut app, 1);
+    assert_eq!(body(&app, target), "Come back when you can afford it.");
+
+    send(&mut app, MortarCommand::next_text());
+    assert_eq!(inventory(&app, "elixir"), 0);
+    assert_eq!(gold(&app), Some(MortarVariableValue::Number(20.0)));
+    send(&mut app, MortarCommand::next_text());
+    assert!(elixir_enabled(&app), "the elixir is still in stock");
+}
+
+#[test]
+fn test_sold_out_elixir_stays_disabled_on_reentry() {
+    let (mut app, target) = setup_app(40.0);
+    choose(&mut app, 1);
+    assert_eq!(body(&app, target), "My last elixir. Use it well.");
+    send(&mut app, MortarCommand::next_text());
+    assert_eq!(inventory(&app, "elixir"), 1);
+    assert_eq!(gold(&app), Some(MortarVariableValue::Number(15.0)));
+    assert!(app.world().resource::<ShopStock>().sold_out);
+
+    // Script variables are dropped with the conversation; walking in again seeds them from
+    // the game's state.
+    //
+    // 脚本变量随对话一起被丢弃；再次进店时会根据游戏状态重新预设。
+    send(&mut app, MortarCommand::stop_dialogue());
+    assert!(gold(&app).is_none());
+    open(&mut app);
+    assert_eq!(body(&app, target), "Welcome, traveler! You have 15 gold.");
+    send(&mut app, MortarCommand::next_text());
+    assert!(!elixir_enabled(&app));
+}
+
+#[test]
+fn test_haggling_plays_timeline_and_gives_mint() {
+    let (mut app, target) = setup_app(20.0);
+    choose(&mut app, 2);
+    assert_eq!(body(&app, target), "Haggle? Let me think...");
+
+    app.world_mut().write_message(MortarCommand::next_text());
+    app.update();
+    app.update();
+    assert_eq!(app.world().resource::<ShopkeeperPose>().0, "frown");
+    for _ in 0..12 {
+        app.update();
+    }
+    assert_eq!(app.world().resource::<ShopkeeperPose>().0, "sigh");
+    assert_eq!(inventory(&app, "mint"), 0);
+    for _ in 0..12 {
+        app.update();
+    }
+    assert_eq!(app.world().resource::<ShopkeeperPose>().0, "smile");
+    assert_eq!(inventory(&app, "mint"), 1);
+    assert_eq!(body(&app, target), "No. But have a mint, on the house.");
+}
@@ -0,0 +1,149 @@
+//! Covers signal waits in timelines: a `wait_signal` step holds the rest of the timeline, and so
+//! the dialogue, until the game sends the named signal, signals sent before the wait only count
+//! when the wait is latched, and a wait with a duration gives up once it runs out.
+//!
+//! 覆盖时间线中的信号等待：`wait_signal` 步骤会阻塞时间线的后续步骤以及对话，直到游戏发送对应的
+//! 信号；等待开始之前发送的信号只有在锁存等待时才算数；带持续时间的等待在超时后放弃等待。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use bevy::time::TimeUpdateStrategy;
+use mortar_compiler::Deserializer;
+use std::time::Duration;
+
+const PATH: &str = "door.mortar";
+
+#[derive(Resource, Default)]
+struct Fired(Vec<String>);
+
+fn record_fired(mut events: MessageReader<MortarGameEvent>, mut fired: ResMut<Fired>) {
+    fired
+        .0
+        .extend(events.read().map(|event| event.name.clone()));
+}
+
+fn door_asset(wait: serde_json::Value) -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [{
+            "name": "Door",
+            "content": [
+                { "type": "text", "value": "Knock" },
+                { "type": "run_event", "name": "Opening" },
+                { "type": "text", "value": "Inside" }
+            ]
+        }],
+        "functions": [],
+        "events": [
+            { "name": "open", "action": { "type": "open_door" } },
+            { "name": "enter", "action": { "type": "walk_in" } }
+        ],
+        "timelines": [{ "name": "Opening", "statements": [
+            { "type": "run", "event_name": "open" },
+            wait,
+            { "type": "run", "event_name": "enter" }
+        ] }]
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+/// Starts the dialogue and advances onto the timeline.
+fn setup_app(wait: serde_json::Value, early_signal: bool) -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+    ))
+    .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+        100,
+    )))
+    .init_resource::<Fired>()
+    .add_systems(PostUpdate, record_fired);
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(door_asset(wait));
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+    app.world_mut().spawn((Text::new(""), MortarTextTarget));
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, "Door"));
+    for _ in 0..3 {
+        app.update();
+    }
+    if early_signal {
+        app.world_mut()
+            .write_message(MortarCommand::signal("door_opened"));
+    }
+    app.world_mut().write_message(MortarCommand::next_text());
+    for _ in 0..5 {
+        app.update();
+    }
+    app
+}
+
+fn fired(app: &App) -> &[String] {
+    &app.world().resource::<Fired>().0
+}
+
+fn waiting(app: &App) -> bool {
+    app.world().resource::<MortarRunsExecuting>().executing
+}
+
+fn current_text(app: &App) -> Option<String> {
+    let runtime = app.world().resource::<MortarRuntime>();
+    runtime
+        .primary_dialogue_state()?
+        .current_text()
+        .map(str::to_owned)
+}
+
+#[test]
+fn test_signal_releases_the_timeline() {
+    let wait = serde_json::json!({ "type": "wait_signal", "event_name": "door_opened" });
+    let mut app = setup_app(wait, false);
+    assert_eq!(fired(&app), ["open_door"]);
+    assert!(waiting(&app));
+
+    // Player input cannot skip past the wait.
+    //
+    // 玩家输入无法跳过等待。
+    app.world_mut().write_message(MortarCommand::next_text());
+    for _ in 0..20 {
+        app.update();
+    }
+    assert_eq!(fired(&app), ["open_door"]);
+    assert_eq!(current_text(&app).as_deref(), Some("Inside"));
+
+    app.world_mut()
+        .write_message(MortarCommand::signal("door_opened"));
+    for _ in 0..3 {
+        app.update();
+    }
+    assert_eq!(fired(&app), ["open_door", "walk_in"]);
+    assert!(!waiting(&app));
+}
+
+#[test]
+fn test_early_signal_needs_latch_and_timeout_moves_on() {
+    let wait = serde_json::json!({
+        "type": "wait_signal", "event_name": "door_opened", "args": ["latch"]
+    });
+    let app = setup_app(wait, true);
+    assert_eq!(fired(&app), ["open_door", "walk_in"]);
+
+    let wait = serde_json::json!({
+        "type": "wait_signal", "event_name": "door_opened", "duration": 1.0
+    });
+    let mut app = setup_app(wait, true);
+    assert_eq!(fired(&app), ["open_door"]);
+    for _ in 0..12 {
+        app.update();
+    }
+    assert_eq!(fired(&app), ["open_door", "walk_in"]);
+    assert!(!waiting(&app));
+}
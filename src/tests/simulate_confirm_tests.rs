@@ -0,0 +1,353 @@
+//! Covers `MortarRuntime::simulate_confirm`: for jumping, ending, nested and capturing options the
+//! preview equals the `MortarChoiceResolved` the real confirm publishes right after. Simulating
+//! changes nothing: the capture is rolled back, the runs of a jump's entry and their events are
+//! only described, and the bound functions the confirm leads to are listed without being called.
+//! A confirm that would be rejected resolves to a rejection.
+//!
+//! 覆盖 `MortarRuntime::simulate_confirm`：对于跳转、结束、嵌套以及带捕获的选项，预览结果与随后
+//! 真正确认时发布的 `MortarChoiceResolved` 相同。模拟不会改变任何状态：捕获会被回滚，跳转入口的
+//! run 及其事件只会被描述，确认将导致调用的绑定函数只会被列出而不会被调用。会被拒绝的确认结算为
+//! 拒绝。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use mortar_compiler::Deserializer;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+const PATH: &str = "inn.mortar";
+
+#[derive(Resource, Default)]
+struct Recorded {
+    resolved: Vec<MortarChoiceResolved>,
+    captured: usize,
+    events: Vec<MortarEventAction>,
+}
+
+fn record(
+    mut recorded: ResMut<Recorded>,
+    mut resolved: MessageReader<MortarChoiceResolved>,
+    mut captured: MessageReader<MortarChoiceCaptured>,
+    mut game_events: MessageReader<MortarGameEvent>,
+) {
+    recorded.resolved.extend(resolved.read().cloned());
+    recorded.captured += captured.read().count();
+    let events: Vec<_> = game_events
+        .read()
+        .map(|event| MortarEventAction {
+            action_name: event.name.clone(),
+            args: event.args.clone(),
+        })
+        .collect();
+    recorded.events.extend(events);
+}
+
+fn inn_asset() -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [
+            {
+                "name": "Start",
+                "content": [
+                    { "type": "text", "value": "Welcome to the inn" },
+                    { "type": "choice", "capture": "pick", "options": [
+                        { "text": "Leave", "action": "return" },
+                        { "text": "Ask", "choice": [
+                            { "text": "About the town", "next": "Town" },
+                            { "text": "Never mind", "action": "break" }
+                        ] },
+                        { "text": "Go", "next": "Town", "remove_after_pick": true },
+                        { "text": "Sleep" }
+                    ] },
+                    { "type": "text", "value": "The innkeeper nods" }
+                ]
+            },
+            {
+                "name": "Town",
+                "content": [{ "type": "text", "value": "The town" }]
+            }
+        ],
+        "functions": []
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+/// A jump into the middle of `Vault` that fires the skipped `chime` and lands on a line calling
+/// `count_coins`, and a nested group whose options check the captured index and `has_key`.
+fn cellar_asset() -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [
+            {
+                "name": "Start",
+                "content": [
+                    { "type": "text", "value": "The cellar door creaks" },
+                    { "type": "choice", "capture": "pick", "options": [
+                        {
+                            "text": "Search the vault",
+                            "next": "Vault",
+                            "entry_index": 1,
+                            "execute_skipped_runs": true
+                        },
+                        { "text": "Listen", "choice": [
+                            {
+                                "text": "Knock again",
+                                "condition": { "type": "pick_index", "args": ["1"] }
+                            },
+                            {
+                                "text": "Use the key",
+                                "condition": { "type": "has_key", "args": [] }
+                            }
+                        ] }
+                    ] }
+                ]
+            },
+            {
+                "name": "Vault",
+                "content": [
+                    { "type": "text", "value": "Dust everywhere" },
+                    { "type": "run_event", "name": "chime" },
+                    {
+                        "type": "text",
+                        "value": "You count {count_coins} coins",
+                        "interpolated_parts": [
+                            { "type": "text", "content": "You count " },
+                            {
+                                "type": "expression",
+                                "content": "{count_coins}",
+                                "function_name": "count_coins",
+                                "args": []
+                            },
+                            { "type": "text", "content": " coins" }
+                        ]
+                    }
+                ]
+            }
+        ],
+        "functions": [],
+        "events": [{
+            "name": "chime",
+            "action": { "type": "play_sound", "args": ["\"chime.wav\""] }
+        }]
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+fn setup_app() -> App {
+    setup_app_with(inn_asset())
+}
+
+fn setup_app_with(asset: MortarAsset) -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+    ))
+    .init_resource::<Recorded>()
+    .add_systems(Last, record);
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(asset);
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+    app.world_mut().spawn((Text::new(""), MortarTextTarget));
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, "Start"));
+    run(&mut app);
+    app
+}
+
+fn run(app: &mut App) {
+    for _ in 0..3 {
+        app.update();
+    }
+}
+
+fn simulate(app: &mut App, index: usize) -> ConfirmOutcome {
+    app.world_mut()
+        .resource_scope(|world, mut runtime: Mut<MortarRuntime>| {
+            world.resource_scope(|world, mut variables: Mut<MortarDialogueVariables>| {
+                runtime.simulate_confirm(
+                    index,
+                    world.resource::<MortarRegistry>(),
+                    world.resource::<Assets<MortarAsset>>(),
+                    &mut variables,
+                )
+            })
+        })
+}
+
+fn variable(app: &App, name: &str) -> Option<MortarVariableValue> {
+    let variables = app.world().resource::<MortarDialogueVariables>();
+    variables.state.as_ref()?.get(name).cloned()
+}
+
+fn revision(app: &App) -> u64 {
+    let variables = app.world().resource::<MortarDialogueVariables>();
+    variables
+        .state
+        .as_ref()
+        .map_or(0, MortarVariableState::revision)
+}
+
+/// Simulates option `index`, confirms it and checks the confirm resolved to the preview.
+fn confirm_matches_simulation(app: &mut App, index: usize) -> ConfirmOutcome {
+    let simulated = simulate(app, index);
+    assert_ne!(simulated.effect, ConfirmEffect::Rejected);
+    app.world_mut()
+        .write_message(MortarCommand::select_choice(index));
+    app.world_mut()
+        .write_message(MortarCommand::confirm_choice());
+    run(app);
+    let resolved = app
+        .world_mut()
+        .resource_mut::<Recorded>()
+        .resolved
+        .pop()
+        .expect("the confirm resolved");
+    assert_eq!(resolved.outcome, simulated);
+    simulated
+}
+
+fn current_node(app: &App) -> Option<String> {
+    app.world()
+        .resource::<MortarRuntime>()
+        .primary_dialogue_state()
+        .map(|state| state.current_node.clone())
+}
+
+#[test]
+fn test_jump_with_side_effects_matches_confirm() {
+    let mut app = setup_app();
+    let before = simulate(&mut app, 2);
+    assert_eq!(simulate(&mut app, 2), before);
+    assert_eq!(app.world().resource::<Recorded>().captured, 0);
+    assert_eq!(simulate(&mut app, 9).effect, ConfirmEffect::Rejected);
+
+    let outcome = confirm_matches_simulation(&mut app, 2);
+    assert_eq!(
+        outcome.effect,
+        ConfirmEffect::Jump {
+            node: "Town".to_owned(),
+            entry: None,
+        }
+    );
+    assert_eq!(
+        outcome.capture,
+        Some((
+            "pick".to_owned(),
+            MortarVariableValue::String("Go".to_owned())
+        ))
+    );
+    assert!(outcome.removed_after_pick);
+    assert_eq!(app.world().resource::<Recorded>().captured, 1);
+    assert_eq!(current_node(&app).as_deref(), Some("Town"));
+    assert_eq!(simulate(&mut app, 0).effect, ConfirmEffect::Rejected);
+}
+
+#[test]
+fn test_ending_options_match_confirm() {
+    for index in [0, 3] {
+        let mut app = setup_app();
+        let outcome = confirm_matches_simulation(&mut app, index);
+        assert_eq!(outcome.effect, ConfirmEffect::EndDialogue);
+        assert!(!outcome.removed_after_pick);
+        assert_eq!(current_node(&app), None);
+    }
+}
+
+#[test]
+fn test_nested_options_match_confirm() {
+    let mut app = setup_app();
+    let outcome = confirm_matches_simulation(&mut app, 1);
+    assert_eq!(outcome.effect, ConfirmEffect::EnterNested);
+
+    assert_eq!(
+        simulate(&mut app, 0).effect,
+        ConfirmEffect::Jump {
+            node: "Town".to_owned(),
+            entry: None,
+        }
+    );
+    let outcome = confirm_matches_simulation(&mut app, 1);
+    assert_eq!(outcome.effect, ConfirmEffect::Break);
+    assert_eq!(outcome.text, "Never mind");
+    assert_eq!(simulate(&mut app, 1).effect, ConfirmEffect::Rejected);
+}
+
+fn count_calls(app: &mut App, name: &str, result: MortarValue) -> Arc<AtomicUsize> {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counter = calls.clone();
+    app.world_mut()
+        .resource_mut::<MortarRuntime>()
+        .functions
+        .register(name, move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            result.clone()
+        });
+    calls
+}
+
+#[test]
+fn test_entry_runs_are_described_not_dispatched() {
+    let mut app = setup_app_with(cellar_asset());
+    let coins = count_calls(&mut app, "count_coins", MortarValue::from(3.0));
+    let revision_before = revision(&app);
+
+    let simulated = simulate(&mut app, 0);
+    assert_eq!(simulated.runs, ["chime"]);
+    assert_eq!(
+        simulated.events,
+        [MortarEventAction {
+            action_name: "play_sound".to_owned(),
+            args: vec!["chime.wav".to_owned()],
+        }]
+    );
+    assert_eq!(simulated.unsimulated_calls, ["count_coins"]);
+    assert_eq!(coins.load(Ordering::SeqCst), 0);
+    assert!(app.world().resource::<Recorded>().events.is_empty());
+    assert_eq!(variable(&app, "pick"), None);
+    assert_eq!(revision(&app), revision_before);
+
+    let outcome = confirm_matches_simulation(&mut app, 0);
+    assert_eq!(app.world().resource::<Recorded>().events, outcome.events);
+    assert!(coins.load(Ordering::SeqCst) > 0);
+    assert_eq!(current_node(&app).as_deref(), Some("Vault"));
+    assert_eq!(
+        variable(&app, "pick"),
+        Some(MortarVariableValue::String("Search the vault".to_owned()))
+    );
+}
+
+#[test]
+fn test_nested_conditions_see_the_rolled_back_capture() {
+    let mut app = setup_app_with(cellar_asset());
+    let keys = count_calls(
+        &mut app,
+        "has_key",
+        MortarValue::Boolean(MortarBoolean(true)),
+    );
+    let revision_before = revision(&app);
+
+    // `pick_index` only exists once the capture is written, so it is read as a variable rather
+    // than called as a function.
+    let simulated = simulate(&mut app, 1);
+    assert_eq!(simulated.effect, ConfirmEffect::EnterNested);
+    assert_eq!(simulated.unsimulated_calls, ["has_key"]);
+    assert_eq!(keys.load(Ordering::SeqCst), 0);
+    assert_eq!(variable(&app, "pick_index"), None);
+    assert_eq!(revision(&app), revision_before);
+
+    confirm_matches_simulation(&mut app, 1);
+    assert!(keys.load(Ordering::SeqCst) > 0);
+    assert_eq!(
+        variable(&app, "pick_index"),
+        Some(MortarVariableValue::Number(1.0))
+    );
+}
@@ -0,0 +1,154 @@
+//! Covers speaker focus hints: a scene alternating narration, a speaker, the same speaker again,
+//! another speaker's thought and a `parallel_text` line writes one hint per change of focus, with
+//! speakers resolved through the registry or a `MortarSpeaker` component, keeps the latest hint
+//! in `MortarSpeakerFocus`, and clears it when the dialogue ends.
+//!
+//! 覆盖说话者焦点提示：依次经过旁白、一名说话者、同一说话者、另一说话者的内心独白以及
+//! `parallel_text` 行的场景，每次焦点变化写入一条提示，说话者通过注册表或 `MortarSpeaker` 组件解析；
+//! `MortarSpeakerFocus` 保存最新的提示，对话结束时将其清空。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use mortar_compiler::Deserializer;
+
+const PATH: &str = "porch.mortar";
+
+#[derive(Resource, Default)]
+struct Hints(Vec<MortarFocusHint>);
+
+fn record(mut recorded: ResMut<Hints>, mut hints: MessageReader<MortarFocusHint>) {
+    recorded.0.extend(hints.read().cloned());
+}
+
+fn porch_asset() -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [{
+            "name": "Porch",
+            "content": [
+                { "type": "text", "value": "Dawn breaks." },
+                { "type": "text", "value": "Morning!", "speaker": "Alice" },
+                { "type": "text", "value": "Lovely day.", "speaker": "Alice" },
+                {
+                    "type": "text",
+                    "value": "She looks tired.",
+                    "speaker": "Bob",
+                    "tags": ["thought"]
+                },
+                {
+                    "type": "parallel_text",
+                    "texts": [
+                        { "value": "Let's go!", "speaker": "Alice" },
+                        { "value": "Let's go!", "target": "Bob" }
+                    ]
+                }
+            ]
+        }],
+        "functions": []
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+fn setup_app() -> (App, Entity, Entity) {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+    ))
+    .init_resource::<Hints>()
+    .add_systems(Last, record);
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(porch_asset());
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+    app.world_mut().spawn((Text::new(""), MortarTextTarget));
+    let alice = app.world_mut().spawn_empty().id();
+    app.world_mut()
+        .resource_mut::<MortarSpeakerRegistry>()
+        .register("Alice", alice);
+    let bob = app.world_mut().spawn(MortarSpeaker("Bob".to_owned())).id();
+    (app, alice, bob)
+}
+
+fn send(app: &mut App, command: MortarCommand) {
+    app.world_mut().write_message(command);
+    for _ in 0..3 {
+        app.update();
+    }
+}
+
+fn hint(speaker: &str, entity: Option<Entity>, kind: MortarFocusKind) -> MortarFocusHint {
+    MortarFocusHint {
+        speaker: speaker.to_owned(),
+        entity,
+        kind,
+    }
+}
+
+fn current(app: &App) -> Option<MortarFocusHint> {
+    app.world().resource::<MortarSpeakerFocus>().current.clone()
+}
+
+#[test]
+fn test_hints_follow_changes_of_focus() {
+    let (mut app, alice, bob) = setup_app();
+    send(&mut app, MortarCommand::start_node(PATH, "Porch"));
+    let narration = hint("", None, MortarFocusKind::Narration);
+    assert_eq!(current(&app), Some(narration.clone()));
+
+    send(&mut app, MortarCommand::next_text());
+    let alice_line = hint("Alice", Some(alice), MortarFocusKind::Line);
+    assert_eq!(current(&app), Some(alice_line.clone()));
+
+    // Alice speaking again does not move the focus.
+    //
+    // Alice 再次说话不会移动焦点。
+    send(&mut app, MortarCommand::next_text());
+    assert_eq!(app.world().resource::<Hints>().0.len(), 2);
+
+    send(&mut app, MortarCommand::next_text());
+    let bob_thought = hint("Bob", Some(bob), MortarFocusKind::Thought);
+    assert_eq!(current(&app), Some(bob_thought.clone()));
+
+    send(&mut app, MortarCommand::next_text());
+    let both = MortarFocusKind::MultiFocus(vec![
+        MortarFocusTarget {
+            speaker: "Alice".to_owned(),
+            entity: Some(alice),
+        },
+        MortarFocusTarget {
+            speaker: "Bob".to_owned(),
+            entity: Some(bob),
+        },
+    ]);
+    let duet = hint("Alice", Some(alice), both);
+    assert_eq!(
+        app.world().resource::<Hints>().0,
+        [narration, alice_line, bob_thought, duet.clone()]
+    );
+    assert_eq!(current(&app), Some(duet));
+
+    send(&mut app, MortarCommand::next_text());
+    assert_eq!(current(&app), None);
+    assert_eq!(app.world().resource::<Hints>().0.len(), 4);
+}
+
+#[test]
+fn test_unknown_speakers_have_no_entity() {
+    let (mut app, _, _) = setup_app();
+    app.world_mut()
+        .resource_mut::<MortarSpeakerRegistry>()
+        .unregister("Alice");
+    send(&mut app, MortarCommand::start_node(PATH, "Porch"));
+    send(&mut app, MortarCommand::next_text());
+    assert_eq!(
+        current(&app),
+        Some(hint("Alice", None, MortarFocusKind::Line))
+    );
+}
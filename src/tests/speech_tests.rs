@@ -0,0 +1,117 @@
+//! Covers speakable text: a line with an inline icon and a markup span is spoken with the icon's
+//! words, without the tags and through the game's formatter, announced exactly once; a presented
+//! choice group is announced with all its labels; and nothing is computed without the resources.
+//!
+//! 覆盖可朗读文本：带有行内图标与标记片段的行会以图标对应的文字、去掉标签并经过游戏的格式化器朗读，
+//! 且只播报一次；呈现的选项组会连同全部标签一起播报；没有相关资源时不进行任何计算。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use mortar_compiler::Deserializer;
+
+const PATH: &str = "tutorial.mortar";
+
+#[derive(Resource, Default)]
+struct Spoken(Vec<String>);
+
+fn record_spoken(mut changes: MessageReader<MortarSpeakableChanged>, mut spoken: ResMut<Spoken>) {
+    spoken
+        .0
+        .extend(changes.read().map(|change| change.text.clone()));
+}
+
+fn tutorial_asset() -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [{
+            "name": "Tutorial",
+            "content": [
+                { "type": "text", "value": "Press {icon:button_a} to <b>jump</b> 3 times" },
+                { "type": "text", "value": "Ready?" },
+                { "type": "choice", "options": [
+                    { "text": "<i>Yes</i>", "next": "return" },
+                    { "id": "skip", "icon": "button_b", "next": "return" }
+                ] }
+            ]
+        }],
+        "functions": []
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+fn setup_app(speech: bool) -> (App, Entity) {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+    ))
+    .init_resource::<Spoken>()
+    .add_systems(PostUpdate, record_spoken);
+    if speech {
+        app.insert_resource(
+            MortarIconSpeechMap::default()
+                .with("button_a", "button A")
+                .with("button_b", "button B"),
+        )
+        .insert_resource(MortarSpeechFormat::new(|text: &str| {
+            text.replace('3', "three")
+        }));
+    }
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(tutorial_asset());
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+    let target = app
+        .world_mut()
+        .spawn((Text::new(""), MortarTextTarget))
+        .id();
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, "Tutorial"));
+    for _ in 0..5 {
+        app.update();
+    }
+    (app, target)
+}
+
+fn spoken(app: &App) -> &[String] {
+    &app.world().resource::<Spoken>().0
+}
+
+#[test]
+fn test_icon_and_markup_line_is_spoken_once() {
+    let (app, target) = setup_app(true);
+    let text = app.world().get::<MortarDialogueText>(target).unwrap();
+    assert_eq!(text.speakable, "Press button A to jump three times");
+    assert_eq!(spoken(&app), ["Press button A to jump three times"]);
+}
+
+#[test]
+fn test_choice_group_is_announced_with_all_labels() {
+    let (mut app, _) = setup_app(true);
+    app.world_mut().write_message(MortarCommand::next_text());
+    for _ in 0..5 {
+        app.update();
+    }
+    assert_eq!(
+        spoken(&app),
+        [
+            "Press button A to jump three times",
+            "Ready?",
+            "Yes\nbutton B"
+        ]
+    );
+}
+
+#[test]
+fn test_nothing_is_spoken_without_speech_resources() {
+    let (app, target) = setup_app(false);
+    let text = app.world().get::<MortarDialogueText>(target).unwrap();
+    assert!(text.speakable.is_empty());
+    assert!(spoken(&app).is_empty());
+}
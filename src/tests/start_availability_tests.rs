@@ -0,0 +1,179 @@
+//! Covers the availability rules of conversation starts: a cooldown suppresses an immediate
+//! restart and lets it through once the clock moves past it, a session cap suppresses the third
+//! of three starts, and a retried start blocked by a custom check is reported once and starts
+//! when the check passes.
+//!
+//! 覆盖对话开始的准入规则：冷却时间会拦下立即重新开始的请求，并在时钟越过冷却后放行；会话上限会
+//! 拦下三次开始中的第三次；被自定义检查拦下的重试请求只报告一次，并在检查通过后开始。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use bevy::time::TimeUpdateStrategy;
+use mortar_compiler::Deserializer;
+use std::time::Duration;
+
+const PATH: &str = "square.mortar";
+
+fn square_asset() -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [{
+            "name": "Bark",
+            "content": [{ "type": "text", "value": "Fresh bread!" }]
+        }],
+        "functions": [],
+        "variables": []
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+/// Suppressed starts reported so far.
+#[derive(Resource, Default)]
+struct Suppressed(Vec<MortarStartSuppressed>);
+
+fn record(mut suppressed: ResMut<Suppressed>, mut reader: MessageReader<MortarStartSuppressed>) {
+    suppressed.0.extend(reader.read().cloned());
+}
+
+/// Whether the baker is at the stall, for the custom check.
+#[derive(Resource, Default)]
+struct BakerPresent(bool);
+
+fn setup_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+    ))
+    .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs(1)))
+    .init_resource::<Suppressed>()
+    .add_systems(Last, record);
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(square_asset());
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+    // Virtual time caps a frame at 250ms by default; let each frame cover the full step.
+    //
+    // 虚拟时间默认将每帧限制为 250ms；让每帧覆盖完整的步长。
+    app.world_mut()
+        .resource_mut::<Time<Virtual>>()
+        .set_max_delta(Duration::from_secs(60));
+    app
+}
+
+fn start(app: &mut App) {
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, "Bark"));
+    app.update();
+}
+
+fn is_running(app: &App) -> bool {
+    app.world()
+        .resource::<MortarRuntime>()
+        .primary_dialogue_state()
+        .is_some()
+}
+
+fn reasons(app: &App) -> Vec<MortarStartSuppression> {
+    app.world()
+        .resource::<Suppressed>()
+        .0
+        .iter()
+        .map(|suppressed| suppressed.reason)
+        .collect()
+}
+
+#[test]
+fn test_cooldown_suppresses_restart_until_time_passes() {
+    let mut app = setup_app();
+    app.world_mut()
+        .resource_mut::<MortarAvailability>()
+        .rule(PATH, "Bark")
+        .cooldown(Duration::from_secs(30));
+
+    start(&mut app);
+    assert!(is_running(&app));
+    app.world_mut().write_message(MortarCommand::next_text());
+    app.update();
+    assert!(!is_running(&app));
+
+    start(&mut app);
+    assert!(!is_running(&app));
+    let [MortarStartSuppression::Cooldown { remaining }] = reasons(&app)[..] else {
+        panic!("the restart should be suppressed by the cooldown");
+    };
+    assert_eq!(remaining, Duration::from_secs(29));
+    assert!(
+        app.world()
+            .resource::<MortarRuntime>()
+            .pending_starts
+            .is_empty()
+    );
+
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs(30)));
+    start(&mut app);
+    assert!(is_running(&app));
+    assert_eq!(reasons(&app).len(), 1);
+}
+
+#[test]
+fn test_session_limit_suppresses_the_third_start() {
+    let mut app = setup_app();
+    app.world_mut()
+        .resource_mut::<MortarAvailability>()
+        .rule(PATH, "Bark")
+        .max_per_session(2);
+
+    for attempt in 0..3 {
+        start(&mut app);
+        assert_eq!(is_running(&app), attempt < 2, "attempt {attempt}");
+        app.world_mut()
+            .write_message(MortarCommand::stop_dialogue());
+        app.update();
+    }
+
+    assert_eq!(
+        reasons(&app),
+        vec![MortarStartSuppression::SessionLimit { max: 2 }]
+    );
+    let world = app.world();
+    let registry = world.resource::<MortarRegistry>();
+    let availability = world.resource::<MortarAvailability>();
+    assert_eq!(availability.starts_this_session(registry, PATH, "Bark"), 2);
+
+    app.world_mut()
+        .resource_mut::<MortarAvailability>()
+        .reset_session();
+    start(&mut app);
+    assert!(is_running(&app));
+}
+
+#[test]
+fn test_retried_start_waits_for_custom_check() {
+    let mut app = setup_app();
+    app.init_resource::<BakerPresent>();
+    {
+        let mut availability = app.world_mut().resource_mut::<MortarAvailability>();
+        availability.policy = SuppressedStartPolicy::Retry;
+        availability
+            .rule(format!("./{PATH}"), "Bark")
+            .custom(|world| world.resource::<BakerPresent>().0);
+    }
+
+    start(&mut app);
+    for _ in 0..3 {
+        app.update();
+    }
+    assert!(!is_running(&app));
+    assert_eq!(reasons(&app), vec![MortarStartSuppression::Unavailable]);
+
+    app.world_mut().resource_mut::<BakerPresent>().0 = true;
+    app.update();
+    assert!(is_running(&app));
+    assert_eq!(reasons(&app).len(), 1);
+}
@@ -0,0 +1,154 @@
+//! Covers the state history: a short conversation leaves one record per position it visited, a
+//! variable assignment shows up in a record and in the diff across it, the buffer stays within its
+//! capacity, and nothing is recorded while the history is disabled.
+//!
+//! 覆盖状态历史：一段简短对话在其经过的每个位置各留下一条记录，变量赋值会出现在记录及跨越它的差异
+//! 中，缓冲区不会超出容量，历史关闭时不会记录任何内容。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use mortar_compiler::Deserializer;
+
+const PATH: &str = "history.mortar";
+
+fn history_asset() -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [
+            {
+                "name": "Start",
+                "content": [
+                    { "type": "text", "value": "Hello" },
+                    {
+                        "type": "text",
+                        "value": "Here is some gold",
+                        "pre_statements": [
+                            { "type": "assignment", "var_name": "gold", "value": "5" }
+                        ]
+                    }
+                ],
+                "next": "End"
+            },
+            { "name": "End", "content": [{ "type": "text", "value": "Bye" }] }
+        ],
+        "functions": [],
+        "variables": [{ "name": "gold", "type": "Number", "value": 0 }]
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+fn setup_app(history: MortarStateHistory) -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+    ))
+    .insert_resource(history);
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(history_asset());
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+    app.world_mut().spawn((Text::new(""), MortarTextTarget));
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, "Start"));
+    app
+}
+
+/// Starts the conversation and advances through both lines of `Start`.
+fn play(app: &mut App) {
+    for _ in 0..3 {
+        app.update();
+    }
+    for _ in 0..2 {
+        app.world_mut().write_message(MortarCommand::next_text());
+        for _ in 0..3 {
+            app.update();
+        }
+    }
+}
+
+fn positions(history: &MortarStateHistory) -> Vec<(String, usize)> {
+    let mut positions: Vec<(String, usize)> = Vec::new();
+    for record in history.records() {
+        let Some(node) = &record.node else {
+            continue;
+        };
+        let position = (node.to_string(), record.text_index);
+        if positions.last() != Some(&position) {
+            positions.push(position);
+        }
+    }
+    positions
+}
+
+#[test]
+fn test_records_follow_the_conversation() {
+    let mut app = setup_app(MortarStateHistory::new(DEFAULT_STATE_HISTORY_CAPACITY));
+    play(&mut app);
+
+    let history = app.world().resource::<MortarStateHistory>();
+    assert_eq!(
+        positions(history),
+        [
+            ("Start".to_owned(), 0),
+            ("Start".to_owned(), 1),
+            ("End".to_owned(), 0)
+        ],
+        "{}",
+        history.dump_pretty()
+    );
+    assert!(history.records().iter().any(|record| {
+        record.events.iter().any(|event| {
+            matches!(event, MortarHistoryEvent::DialogueStarted { node } if &**node == "Start")
+        })
+    }));
+
+    let gold = MortarVariableValue::Number(5.0);
+    let assigned = history
+        .records()
+        .iter()
+        .position(|record| {
+            record
+                .variable_changes
+                .iter()
+                .any(|(name, value)| &**name == "gold" && *value == gold)
+        })
+        .expect("the assignment should be recorded");
+    assert!(assigned > 0);
+    let diff = history.diff(0, history.records().len() - 1).unwrap();
+    assert_eq!(diff.variables.len(), 1);
+    assert_eq!(&*diff.variables[0].0, "gold");
+    assert_eq!(diff.variables[0].1, gold);
+    assert!(diff.node.is_some());
+    assert!(history.diff(assigned, assigned).unwrap().is_empty());
+    assert!(history.dump_pretty().contains("gold=5"));
+}
+
+#[test]
+fn test_capacity_bounds_the_buffer() {
+    let mut app = setup_app(MortarStateHistory::new(2));
+    play(&mut app);
+
+    let history = app.world().resource::<MortarStateHistory>();
+    assert_eq!(history.records().len(), 2);
+    assert_eq!(
+        history.latest().and_then(|record| record.node.as_deref()),
+        Some("End")
+    );
+}
+
+#[test]
+fn test_disabled_history_records_nothing() {
+    let mut app = setup_app(MortarStateHistory::default());
+    play(&mut app);
+
+    let history = app.world().resource::<MortarStateHistory>();
+    assert!(!history.enabled);
+    assert!(history.records().is_empty());
+}
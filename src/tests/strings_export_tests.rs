@@ -0,0 +1,141 @@
+//! Checks the localization strings export: a golden CSV for a fixture with quotes, line breaks,
+//! CJK text, an interpolated line, a voice event and nested choices, the optional condition
+//! column, the JSON rows, and the registry export with its file column.
+//!
+//! 检查本地化字符串导出：针对包含引号、换行、中日韩文本、插值行、语音事件与嵌套选项的 fixture
+//! 比对 CSV 黄金输出，并检查可选的条件列、JSON 行以及带文件列的注册表导出。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use std::path::Path;
+
+const PATH: &str = "pier.mortared";
+
+fn pier_asset() -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [
+            {
+                "name": "Pier",
+                "header": "Narrator",
+                "tags": ["act1", "harbor"],
+                "content": [
+                    {
+                        "type": "text",
+                        "value": "He said \"hi\", then left.",
+                        "id": "pier_hi",
+                        "speaker": "Mara",
+                        "events": [{ "index": 0, "actions": [{ "type": "play_voice", "args": ["vo/mara_hi.ogg"] }] }]
+                    },
+                    { "type": "text", "value": "Line one\nLine two" },
+                    {
+                        "type": "text",
+                        "value": "Welcome, {name}!",
+                        "interpolated_parts": [
+                            { "type": "text", "content": "Welcome, " },
+                            { "type": "placeholder", "content": "{name}" },
+                            { "type": "text", "content": "!" }
+                        ],
+                        "condition": { "type": "identifier", "value": "met_mara" }
+                    },
+                    {
+                        "type": "choice",
+                        "options": [
+                            { "text": "你好，世界", "next": "Pier" },
+                            {
+                                "text": "Ask",
+                                "condition": { "type": "has_item", "args": ["map"] },
+                                "choice": [{ "text": "About the \"storm\"", "id": "ask_storm" }]
+                            }
+                        ]
+                    }
+                ]
+            },
+            { "name": "Dock", "content": [{ "type": "text", "value": "你好，世界" }] }
+        ],
+        "functions": []
+    });
+    MortarAssetLoader::load_asset_bytes(json.to_string().as_bytes(), Path::new(PATH))
+        .expect("fixture should load")
+}
+
+const GOLDEN_CSV: &str = "\
+line_id,node,kind,index,speaker,raw_text,placeholders,voice_key,tags
+pier_hi,Pier,text,0,Mara,\"He said \"\"hi\"\", then left.\",,vo/mara_hi.ogg,act1;harbor
+ecf19140e1dd2d31,Pier,text,1,Narrator,\"Line one
+Line two\",,,act1;harbor
+4106565e25ce5669,Pier,text,2,Narrator,\"Welcome, {name}!\",{name},,act1;harbor
+0c3b27468af33730,Pier,choice,0,,你好，世界,,,act1;harbor
+4dfa0678a0f35b14,Pier,choice,1,,Ask,,,act1;harbor
+ask_storm,Pier,choice,1.0,,\"About the \"\"storm\"\"\",,,act1;harbor
+8897cfc1c217aed7,Dock,text,0,,你好，世界,,,
+";
+
+#[test]
+fn test_csv_matches_golden_output() {
+    let csv = pier_asset().export_strings(StringsFormat::Csv);
+    assert_eq!(csv, GOLDEN_CSV);
+}
+
+#[test]
+fn test_line_ids_match_the_runtime() {
+    let asset = pier_asset();
+    let node = asset.data.nodes[0].clone();
+    let state = DialogueState::new(PATH.to_owned(), node.name.clone(), node);
+    let csv = asset.export_strings(StringsFormat::Csv);
+    for item in state.text_items() {
+        assert!(csv.contains(&format!("\n{},Pier,text,", item.line_id)));
+    }
+}
+
+#[test]
+fn test_conditions_column_is_optional() {
+    let csv = pier_asset().export_strings(StringsExport {
+        format: StringsFormat::Csv,
+        include_conditions: true,
+    });
+    let mut lines = csv.lines();
+    assert!(lines.next().unwrap().ends_with(",tags,condition"));
+    assert!(csv.contains(",{name},,act1;harbor,met_mara\n"));
+    assert!(csv.contains(",Ask,,,act1;harbor,has_item(map)\n"));
+}
+
+#[test]
+fn test_json_rows_keep_text_verbatim() {
+    let json = pier_asset().export_strings(StringsFormat::Json);
+    let rows: Vec<serde_json::Value> = serde_json::from_str(&json).unwrap();
+    assert_eq!(rows.len(), 7);
+    assert_eq!(rows[1]["raw_text"], "Line one\nLine two");
+    assert_eq!(rows[2]["placeholders"], serde_json::json!(["{name}"]));
+    assert_eq!(rows[5]["index"], "1.0");
+    assert!(rows[0].get("condition").is_none());
+}
+
+#[test]
+fn test_registry_export_adds_a_file_column() {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+    ));
+    let world = app.world_mut();
+    let pier = world
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(pier_asset());
+    let dock = world
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(pier_asset());
+    let mut registry = world.resource_mut::<MortarRegistry>();
+    registry.register("pier.mortared", pier);
+    registry.register("dock.mortared", dock);
+
+    let world = app.world();
+    let csv = world
+        .resource::<MortarRegistry>()
+        .export_all_strings(world.resource::<Assets<MortarAsset>>(), StringsFormat::Csv);
+    let lines: Vec<&str> = csv.lines().collect();
+    assert!(lines[0].starts_with("file,line_id,"));
+    assert!(lines[1].starts_with("dock.mortared,pier_hi,"));
+    assert!(csv.contains("\npier.mortared,pier_hi,"));
+}
@@ -0,0 +1,143 @@
+//! Covers `switch` content items: after a choice captured into a variable, a three-case switch on
+//! it shows only the lines and runs of the picked case, including runs that lead or trail a case,
+//! and a pick no case matches skips the whole switch.
+//!
+//! 覆盖 `switch` 内容项：选项被捕获到变量之后，以该变量为键的三分支 switch 只显示被选中分支的行并
+//! 只执行其 run（包括位于分支开头或结尾的 run）；没有分支匹配时会跳过整个 switch。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use mortar_compiler::Deserializer;
+
+const PATH: &str = "stall.mortar";
+
+fn stall_asset() -> MortarAsset {
+    let text = |value: &str| serde_json::json!({ "type": "text", "value": value });
+    let run = |name: &str| serde_json::json!({ "type": "run_event", "name": name });
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [{
+            "name": "Stall",
+            "content": [
+                text("What catches your eye?"),
+                { "type": "choice", "capture": "pick", "options": [
+                    { "id": "bread", "text": "Bread", "action": "break" },
+                    { "id": "cheese", "text": "Cheese", "action": "break" },
+                    { "id": "wine", "text": "Wine", "action": "break" },
+                    { "id": "nothing", "text": "Just looking", "action": "break" }
+                ] },
+                { "type": "switch", "on": "pick", "cases": {
+                    "bread": [text("Fresh this morning."), run("bake"), text("Two coins.")],
+                    "cheese": [text("Aged a full year."), run("slice")],
+                    "wine": [run("pour"), text("A fine vintage.")]
+                } },
+                text("Anything else?")
+            ]
+        }],
+        "functions": [],
+        "events": [
+            { "name": "bake", "action": { "type": "bake" } },
+            { "name": "slice", "action": { "type": "slice" } },
+            { "name": "pour", "action": { "type": "pour" } }
+        ]
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+/// Game events dispatched so far.
+#[derive(Resource, Default)]
+struct Dispatched(Vec<String>);
+
+fn record(mut dispatched: ResMut<Dispatched>, mut reader: MessageReader<MortarGameEvent>) {
+    dispatched
+        .0
+        .extend(reader.read().map(|event| event.name.clone()));
+}
+
+/// Picks option `index` and advances to the end, returning the lines shown after the choice and
+/// the events dispatched.
+fn play(index: usize) -> (Vec<String>, Vec<String>) {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+    ))
+    .init_resource::<Dispatched>()
+    .add_systems(Last, record);
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(stall_asset());
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+    let target = app
+        .world_mut()
+        .spawn((Text::new(""), MortarTextTarget))
+        .id();
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, "Stall"));
+    for _ in 0..3 {
+        app.update();
+    }
+    app.world_mut()
+        .write_message(MortarCommand::select_choice(index));
+    app.world_mut()
+        .write_message(MortarCommand::confirm_choice());
+
+    let mut lines = Vec::new();
+    for _ in 0..10 {
+        for _ in 0..3 {
+            app.update();
+        }
+        if !app
+            .world()
+            .resource::<MortarRuntime>()
+            .has_active_dialogues()
+        {
+            break;
+        }
+        let body = &app.world().get::<MortarDialogueText>(target).unwrap().body;
+        lines.push(body.clone());
+        app.world_mut().write_message(MortarCommand::next_text());
+    }
+    let dispatched = std::mem::take(&mut app.world_mut().resource_mut::<Dispatched>().0);
+    (lines, dispatched)
+}
+
+#[test]
+fn test_switch_shows_only_the_picked_case() {
+    assert_eq!(
+        play(0),
+        (
+            vec![
+                "Fresh this morning.".to_owned(),
+                "Two coins.".to_owned(),
+                "Anything else?".to_owned()
+            ],
+            vec!["bake".to_owned()]
+        )
+    );
+    assert_eq!(
+        play(1),
+        (
+            vec!["Aged a full year.".to_owned(), "Anything else?".to_owned()],
+            vec!["slice".to_owned()]
+        )
+    );
+    assert_eq!(
+        play(2),
+        (
+            vec!["A fine vintage.".to_owned(), "Anything else?".to_owned()],
+            vec!["pour".to_owned()]
+        )
+    );
+}
+
+#[test]
+fn test_switch_without_matching_case_is_skipped() {
+    assert_eq!(play(3), (vec!["Anything else?".to_owned()], Vec::new()));
+}
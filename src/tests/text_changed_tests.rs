@@ -0,0 +1,125 @@
+//! Covers `MortarTextChanged`: it is written once per line shown even without a text target,
+//! carrying the interpolated body, its header and events, is not repeated while the line stays,
+//! and keeps coming when `Text` writing is turned off, which leaves the targets' `Text` alone.
+//!
+//! 覆盖 `MortarTextChanged`：即使没有文本目标，每显示一行也会写入一次，携带插值后的正文、头部与
+//! 事件；该行保持不变时不会重复写入；关闭 `Text` 写入后仍会继续写入，目标的 `Text` 则保持不动。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use mortar_compiler::Deserializer;
+
+const PATH: &str = "well.mortar";
+
+#[derive(Resource, Default)]
+struct Changes(Vec<MortarTextChanged>);
+
+fn record(mut changes: ResMut<Changes>, mut reader: MessageReader<MortarTextChanged>) {
+    changes.0.extend(reader.read().cloned());
+}
+
+fn well_asset() -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [{
+            "name": "Well",
+            "content": [
+                {
+                    "type": "text",
+                    "value": "You toss {coins} coins.",
+                    "interpolated_parts": [
+                        { "type": "text", "content": "You toss " },
+                        { "type": "placeholder", "content": "{coins}" },
+                        { "type": "text", "content": " coins." }
+                    ],
+                    "events": [{ "index": 4, "actions": [{ "type": "splash" }] }]
+                },
+                { "type": "text", "value": "Nothing happens." }
+            ]
+        }],
+        "functions": [],
+        "variables": [{ "name": "coins", "type": "Number", "value": 3 }]
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+fn setup_app(plugin: MortarDialoguePlugin) -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        plugin,
+    ))
+    .init_resource::<Changes>()
+    .add_systems(Last, record);
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(well_asset());
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+    app
+}
+
+fn send(app: &mut App, command: MortarCommand) {
+    app.world_mut().write_message(command);
+    for _ in 0..3 {
+        app.update();
+    }
+}
+
+fn bodies(app: &App) -> Vec<&str> {
+    let changes = &app.world().resource::<Changes>().0;
+    changes.iter().map(|change| change.body.as_str()).collect()
+}
+
+#[test]
+fn test_changes_are_written_once_per_line_without_targets() {
+    let mut app = setup_app(MortarDialoguePlugin::default());
+    send(&mut app, MortarCommand::start_node(PATH, "Well"));
+    assert_eq!(bodies(&app), ["You toss 3 coins."]);
+
+    let change = &app.world().resource::<Changes>().0[0];
+    assert_eq!(change.mortar_path, PATH);
+    assert_eq!(change.node, "Well");
+    assert_eq!(change.text_index, 0);
+    assert_eq!(change.header, "[well.mortar / Well]\n\n");
+    assert_eq!(change.events.len(), 1);
+    assert_eq!(change.events[0].index, 4.0);
+
+    // The line staying on screen is not announced again.
+    //
+    // 停留在屏幕上的行不会再次通知。
+    for _ in 0..5 {
+        app.update();
+    }
+    assert_eq!(bodies(&app).len(), 1);
+
+    send(&mut app, MortarCommand::next_text());
+    assert_eq!(bodies(&app), ["You toss 3 coins.", "Nothing happens."]);
+    assert_eq!(app.world().resource::<Changes>().0[1].text_index, 1);
+}
+
+#[test]
+fn test_text_writing_can_be_turned_off() {
+    let mut app = setup_app(MortarDialoguePlugin {
+        write_text: false,
+        ..default()
+    });
+    let target = app
+        .world_mut()
+        .spawn((Text::new(""), MortarTextTarget))
+        .id();
+    send(&mut app, MortarCommand::start_node(PATH, "Well"));
+
+    assert_eq!(bodies(&app), ["You toss 3 coins."]);
+    assert_eq!(app.world().get::<Text>(target).unwrap().0, "");
+    let shown = app.world().get::<MortarDialogueText>(target).unwrap();
+    assert_eq!(shown.body, "You toss 3 coins.");
+
+    send(&mut app, MortarCommand::stop_dialogue());
+    assert_eq!(app.world().get::<Text>(target).unwrap().0, "");
+}
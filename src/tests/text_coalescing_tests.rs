@@ -0,0 +1,124 @@
+//! Checks that the text system leaves [`MortarDialogueText`] and `Text` untouched when a refresh
+//! computes the line already on screen, so `Changed<MortarDialogueText>` consumers such as a
+//! typewriter only restart for a real change.
+//!
+//! 检查当刷新计算出的行与屏幕上已有的行相同时，文本系统不会改动 [`MortarDialogueText`] 与 `Text`，
+//! 从而打字机等 `Changed<MortarDialogueText>` 的使用者只会在内容真正变化时重新开始。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use mortar_compiler::Deserializer;
+
+const PATH: &str = "coalesce.mortar";
+
+#[derive(Resource, Default)]
+struct TextChanges {
+    dialogue_text: usize,
+    text: usize,
+}
+
+fn count_changes(
+    dialogue_texts: Query<(), Changed<MortarDialogueText>>,
+    texts: Query<(), (Changed<Text>, With<MortarTextTarget>)>,
+    mut changes: ResMut<TextChanges>,
+) {
+    changes.dialogue_text += dialogue_texts.iter().count();
+    changes.text += texts.iter().count();
+}
+
+fn coalesce_asset() -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [{
+            "name": "Start",
+            "content": [
+                { "type": "text", "value": "Before the flash" },
+                { "type": "run_event", "name": "flash" },
+                { "type": "text", "value": "After the flash" }
+            ]
+        }],
+        "functions": [],
+        "events": [{ "name": "flash", "action": { "type": "flash" } }]
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+fn setup_app() -> (App, Entity) {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+    ))
+    .init_resource::<TextChanges>()
+    .add_systems(Last, count_changes);
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(coalesce_asset());
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+    let target = app
+        .world_mut()
+        .spawn((Text::new(""), MortarTextTarget))
+        .id();
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, "Start"));
+    for _ in 0..3 {
+        app.update();
+    }
+    (app, target)
+}
+
+fn changes(app: &App) -> (usize, usize) {
+    let changes = app.world().resource::<TextChanges>();
+    (changes.dialogue_text, changes.text)
+}
+
+fn body(app: &App, entity: Entity) -> String {
+    app.world()
+        .get::<MortarDialogueText>(entity)
+        .unwrap()
+        .body
+        .clone()
+}
+
+#[test]
+fn test_zero_duration_run_does_not_touch_unchanged_line() {
+    let (mut app, target) = setup_app();
+    assert_eq!(body(&app, target), "Before the flash");
+    let before = changes(&app);
+
+    // Leaving the first line runs the flash, which completes within a frame and wakes the text
+    // system again while the next line is already on screen.
+    //
+    // 离开第一行会执行 flash，它在一帧内完成，并在下一行已显示时再次唤醒文本系统。
+    app.world_mut().write_message(MortarCommand::next_text());
+    app.update();
+    assert_eq!(body(&app, target), "After the flash");
+    assert_eq!(changes(&app), (before.0 + 1, before.1 + 1));
+
+    for _ in 0..4 {
+        app.update();
+    }
+    assert!(!app.world().resource::<MortarRunsExecuting>().executing);
+    assert_eq!(body(&app, target), "After the flash");
+    assert_eq!(changes(&app), (before.0 + 1, before.1 + 1));
+}
+
+#[test]
+fn test_wake_up_without_line_change_is_quiet() {
+    let (mut app, target) = setup_app();
+    let before = changes(&app);
+    for _ in 0..3 {
+        app.world_mut()
+            .resource_mut::<MortarRuntime>()
+            .set_changed();
+        app.update();
+    }
+    assert_eq!(body(&app, target), "Before the flash");
+    assert_eq!(changes(&app), before);
+}
@@ -0,0 +1,79 @@
+//! Covers the thread-safety of the function registry: closures capturing a channel can be
+//! registered, one shared callable serves several names, and a registry shared with a spawned
+//! thread answers calls there while the main thread keeps calling it.
+//!
+//! 覆盖函数注册表的线程安全性：可以注册捕获通道的闭包，同一个共享的可调用对象可以对应多个名称，
+//! 与新线程共享的注册表在主线程持续调用的同时也能响应该线程上的调用。
+
+use crate::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, mpsc};
+
+const CALLS: usize = 200;
+
+#[test]
+fn test_channel_closure_forwards_calls() {
+    let (sender, receiver) = mpsc::channel::<String>();
+    let mut functions = MortarFunctionRegistry::new();
+    functions.register("play_sound", move |args| {
+        let name = args.first().map(MortarValue::to_display_string);
+        sender.send(name.unwrap_or_default()).unwrap();
+        MortarValue::Void
+    });
+
+    functions.call("play_sound", &["door".into()]);
+    functions.call("play_sound", &["bell".into()]);
+    assert_eq!(receiver.try_iter().collect::<Vec<_>>(), ["door", "bell"]);
+}
+
+#[test]
+fn test_register_arc_shares_one_callable() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let counter = count.clone();
+    let shared: MortarFunction =
+        Arc::new(move |_, _| (counter.fetch_add(1, Ordering::SeqCst) as f64 + 1.0).into());
+    let mut functions = MortarFunctionRegistry::new();
+    functions.register_arc("tick", shared.clone());
+    functions.register_arc("tock", shared.clone());
+
+    functions.call("tick", &[]);
+    let value = functions.call("tock", &[]).unwrap();
+    assert_eq!(value.to_display_string(), "2");
+    assert_eq!(count.load(Ordering::SeqCst), 2);
+    assert_eq!(Arc::strong_count(&shared), 3);
+}
+
+#[test]
+fn test_registry_is_called_from_spawned_thread() {
+    let (sender, receiver) = mpsc::channel::<f64>();
+    let mut functions = MortarFunctionRegistry::new();
+    functions.register_with_arity("double", 1, move |args| {
+        let number = match args.first() {
+            Some(MortarValue::Number(number)) => number.0,
+            _ => 0.0,
+        };
+        sender.send(number).unwrap();
+        (number * 2.0).into()
+    });
+    let functions = Arc::new(functions);
+
+    let worker = {
+        let functions = functions.clone();
+        std::thread::spawn(move || {
+            (0..CALLS)
+                .map(|i| functions.call("double", &[(i as f64).into()]).unwrap())
+                .filter(|value| value.is_truthy())
+                .count()
+        })
+    };
+    for i in 0..CALLS {
+        let value = functions.call("double", &[(-(i as f64)).into()]).unwrap();
+        assert_eq!(value.to_display_string(), (-2.0 * i as f64).to_string());
+    }
+
+    // Only the worker's zero doubles to a falsy value.
+    //
+    // 只有工作线程传入的 0 加倍后为假值。
+    assert_eq!(worker.join().unwrap(), CALLS - 1);
+    assert_eq!(receiver.try_iter().count(), CALLS * 2);
+}
@@ -0,0 +1,146 @@
+//! Covers long chains of zero-duration timeline steps: without a budget a 5,000-step timeline
+//! completes within one frame on a small stack, and with the default budget it is spread over
+//! many frames in order, blocking advance input until the last step.
+//!
+//! 覆盖由零时长时间线步骤组成的长链：不设预算时，5000 步的时间线在较小的栈上于一帧内完成；使用
+//! 默认预算时会按顺序分摊到多帧，并在最后一步之前一直阻止推进输入。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use mortar_compiler::Deserializer;
+
+const PATH: &str = "ticks.mortar";
+const STEPS: usize = 5_000;
+
+#[derive(Resource, Default)]
+struct Ticks(Vec<usize>);
+
+fn record_ticks(mut ticks: ResMut<Ticks>, mut events: MessageReader<MortarGameEvent>) {
+    for event in events.read() {
+        if event.name == "tick" {
+            ticks.0.push(event.args[0].parse().unwrap());
+        }
+    }
+}
+
+fn ticks_asset() -> MortarAsset {
+    let events: Vec<_> = (0..STEPS)
+        .map(|step| {
+            serde_json::json!({
+                "name": format!("tick_{step}"),
+                "action": { "type": "tick", "args": [step.to_string()] }
+            })
+        })
+        .collect();
+    let statements: Vec<_> = (0..STEPS)
+        .map(|step| serde_json::json!({ "type": "run", "event_name": format!("tick_{step}") }))
+        .collect();
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [{
+            "name": "Start",
+            "content": [
+                { "type": "text", "value": "Before" },
+                { "type": "run_event", "name": "Ticks" },
+                { "type": "text", "value": "After" }
+            ]
+        }],
+        "functions": [],
+        "events": events,
+        "timelines": [{ "name": "Ticks", "statements": statements }]
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+fn setup_app(settings: MortarTimelineSettings) -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+    ))
+    .insert_resource(settings)
+    .init_resource::<Ticks>()
+    .add_systems(Last, record_ticks);
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(ticks_asset());
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+    app.world_mut()
+        .spawn((Text::new(""), MortarTextTarget, RunTextBehavior::Keep));
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, "Start"));
+    for _ in 0..3 {
+        app.update();
+    }
+    app
+}
+
+fn tick_count(app: &App) -> usize {
+    app.world().resource::<Ticks>().0.len()
+}
+
+/// Advances past "Before"; the runs start the frame after the text moved on.
+fn start_runs(app: &mut App) {
+    app.world_mut().write_message(MortarCommand::next_text());
+    app.update();
+    assert_eq!(tick_count(app), 0);
+    app.update();
+}
+
+fn executing(app: &App) -> bool {
+    app.world().resource::<MortarRunsExecuting>().executing
+}
+
+#[test]
+fn test_unbudgeted_chain_completes_in_one_frame_on_a_small_stack() {
+    std::thread::Builder::new()
+        .stack_size(1024 * 1024)
+        .spawn(|| {
+            let mut app = setup_app(MortarTimelineSettings {
+                immediate_steps_per_frame: None,
+            });
+            start_runs(&mut app);
+            assert_eq!(tick_count(&app), STEPS);
+            assert!(!executing(&app));
+        })
+        .unwrap()
+        .join()
+        .expect("the chain should not overflow the stack");
+}
+
+#[test]
+fn test_budget_spreads_chain_over_frames_in_order() {
+    let mut app = setup_app(MortarTimelineSettings::default());
+    start_runs(&mut app);
+    assert_eq!(tick_count(&app), DEFAULT_IMMEDIATE_STEPS_PER_FRAME);
+
+    let mut frames = 1;
+    while tick_count(&app) < STEPS {
+        assert!(executing(&app));
+        assert_eq!(
+            app.world().resource::<MortarRuntime>().advance_intent(),
+            AdvanceIntent::BlockedByRuns { skippable: false }
+        );
+        app.update();
+        frames += 1;
+        assert!(frames <= STEPS, "the chain should keep making progress");
+    }
+    assert_eq!(frames, STEPS.div_ceil(DEFAULT_IMMEDIATE_STEPS_PER_FRAME));
+    assert!(
+        app.world()
+            .resource::<Ticks>()
+            .0
+            .iter()
+            .copied()
+            .eq(0..STEPS)
+    );
+
+    app.update();
+    assert!(!executing(&app));
+}
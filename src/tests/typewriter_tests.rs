@@ -0,0 +1,171 @@
+//! Drives one line through [`MortarTypewriterAdapter`] with a stand-in typewriter and checks it
+//! against the built-in [`MortarTextReveal`] path: the same events fire in the same order, the
+//! typed text is composed into `Text`, and `NextText` fast-forwards the typewriter.
+//!
+//! 使用一个替身打字机通过 [`MortarTypewriterAdapter`] 驱动一行文本，并与内置的
+//! [`MortarTextReveal`] 路径对比：相同的事件按相同顺序触发，已打出的文本会组合写入 `Text`，
+//! 并且 `NextText` 会让打字机快进。
+
+use crate::*;
+use bevy::asset::AssetPlugin;
+use bevy::time::TimeUpdateStrategy;
+use mortar_compiler::Deserializer;
+use std::time::Duration;
+
+const PATH: &str = "typewriter.mortar";
+
+/// Types one character per frame while playing.
+#[derive(Component, Default)]
+struct StubTypewriter {
+    len: usize,
+    typed: usize,
+    playing: bool,
+}
+
+impl MortarTypewriter for StubTypewriter {
+    fn start(&mut self, text: &str, _seconds_per_char: f32, auto_play: bool) {
+        self.len = text.chars().count();
+        self.typed = 0;
+        self.playing = auto_play;
+    }
+
+    fn typed_chars(&self) -> usize {
+        self.typed
+    }
+
+    fn fast_forward(&mut self) {
+        self.typed = self.len;
+    }
+}
+
+fn tick_stub(mut typewriters: Query<&mut StubTypewriter>) {
+    for mut typewriter in &mut typewriters {
+        if typewriter.playing && typewriter.typed < typewriter.len {
+            typewriter.typed += 1;
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+struct Fired(Vec<String>);
+
+fn record_events(mut events: MessageReader<MortarGameEvent>, mut fired: ResMut<Fired>) {
+    fired
+        .0
+        .extend(events.read().map(|event| event.name.clone()));
+}
+
+fn typewriter_asset() -> MortarAsset {
+    let json = serde_json::json!({
+        "metadata": { "version": "0.5.0", "generated_at": "2026-01-01T00:00:00Z" },
+        "nodes": [{
+            "name": "Start",
+            "content": [
+                {
+                    "type": "text",
+                    "value": "Hello world",
+                    "events": [
+                        { "index": 1, "actions": [{ "type": "blip" }] },
+                        { "index": 4, "actions": [{ "type": "chime" }] },
+                        { "index": 8, "actions": [{ "type": "bell" }] }
+                    ]
+                },
+                { "type": "text", "value": "Bye" }
+            ]
+        }],
+        "functions": []
+    });
+    let data = Deserializer::from_json(&json.to_string()).expect("fixture should deserialize");
+    MortarAsset::new(data)
+}
+
+fn setup_app(driver: impl Bundle) -> (App, Entity) {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        MortarPlugin::default(),
+        MortarDialoguePlugin::default(),
+        MortarTypewriterPlugin::<StubTypewriter>::default(),
+    ))
+    .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+        100,
+    )))
+    .init_resource::<Fired>()
+    .add_systems(Update, tick_stub)
+    .add_systems(PostUpdate, record_events);
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<MortarAsset>>()
+        .add(typewriter_asset());
+    app.world_mut()
+        .resource_mut::<MortarRegistry>()
+        .register(PATH, handle);
+    let target = app
+        .world_mut()
+        .spawn((Text::new(""), MortarTextTarget, driver))
+        .id();
+    app.world_mut()
+        .write_message(MortarCommand::start_node(PATH, "Start"));
+    (app, target)
+}
+
+fn play_line(app: &mut App) -> Vec<String> {
+    for _ in 0..20 {
+        app.update();
+    }
+    app.world().resource::<Fired>().0.clone()
+}
+
+fn adapter() -> (StubTypewriter, MortarTypewriterAdapter) {
+    (StubTypewriter::default(), MortarTypewriterAdapter::new(0.1))
+}
+
+#[test]
+fn test_adapter_fires_events_like_builtin_reveal() {
+    let (mut builtin, _) = setup_app(MortarTextReveal::new(10.0));
+    let expected = play_line(&mut builtin);
+    assert_eq!(expected, ["blip", "chime", "bell"]);
+
+    let (mut adapted, target) = setup_app(adapter());
+    assert_eq!(play_line(&mut adapted), expected);
+    assert_eq!(
+        adapted.world().get::<Text>(target).unwrap().0,
+        "[typewriter.mortar / Start]\n\nHello world"
+    );
+    assert!(
+        adapted
+            .world()
+            .get::<MortarLineStatus>(target)
+            .unwrap()
+            .reveal_complete
+    );
+}
+
+#[test]
+fn test_next_text_fast_forwards_typewriter() {
+    let (mut app, target) = setup_app(adapter());
+    for _ in 0..5 {
+        app.update();
+    }
+    let typed = app.world().get::<Text>(target).unwrap().0.clone();
+    assert_ne!(typed, "[typewriter.mortar / Start]\n\nHello world");
+    assert_eq!(
+        app.world().resource::<MortarRuntime>().advance_intent(),
+        AdvanceIntent::RevealRemaining
+    );
+
+    app.world_mut().write_message(MortarCommand::next_text());
+    for _ in 0..3 {
+        app.update();
+    }
+    assert_eq!(
+        app.world().get::<Text>(target).unwrap().0,
+        "[typewriter.mortar / Start]\n\nHello world"
+    );
+    assert_eq!(app.world().resource::<Fired>().0, ["blip", "chime", "bell"]);
+    assert_eq!(
+        app.world().resource::<MortarRuntime>().advance_intent(),
+        AdvanceIntent::NextLine
+    );
+}
@@ -0,0 +1,122 @@
+//! Covers the offline validation pass shared with the `mortar-check` CLI: good and bad
+//! fixtures are run through the same loader path the plugin uses, and the bindings manifest is
+//! round-tripped through JSON.
+//!
+//! 覆盖与 `mortar-check` 命令行工具共用的离线校验流程：正常与错误的样例都经过插件相同的
+//! 加载路径检查，并验证绑定清单的 JSON 往返。
+
+use crate::{
+    MortarAssetLoader, MortarFunctionManifest, MortarFunctionRegistry, MortarIssueKind,
+    MortarIssueSeverity, MortarValue, validate_mortar_file, validate_mortared_data,
+};
+use std::path::Path;
+
+fn basic_manifest() -> MortarFunctionManifest {
+    let mut registry = MortarFunctionRegistry::new();
+    for (name, arity) in [
+        ("play_sound", 1),
+        ("set_animation", 1),
+        ("set_color", 1),
+        ("get_name", 0),
+        ("has_map", 0),
+        ("has_backpack", 0),
+        ("TestWarning2", 0),
+    ] {
+        registry.register_with_arity(name, arity, |_| MortarValue::Void);
+    }
+    registry.export_manifest()
+}
+
+const BAD_FIXTURE: &str = r#"{
+    "metadata": { "version": "0.5", "generated_at": "2025-01-01T00:00:00Z" },
+    "nodes": [
+        {
+            "name": "Start",
+            "content": [
+                { "type": "text", "value": "Hello" },
+                { "type": "run_event", "name": "missing_event" },
+                { "type": "choice", "options": [ { "text": "Go", "next": "Nowhere" } ] },
+                { "type": "mystery" }
+            ],
+            "next": "AlsoMissing"
+        },
+        { "name": "Orphan", "content": [] }
+    ],
+    "functions": [
+        { "name": "needs_two", "params": [
+            { "name": "a", "type": "Number" },
+            { "name": "b", "type": "Number" }
+        ] }
+    ],
+    "variables": [],
+    "constants": [],
+    "enums": [],
+    "events": [],
+    "timelines": []
+}"#;
+
+#[test]
+fn good_fixture_has_no_errors() {
+    let report = validate_mortar_file(Path::new("assets/basic.mortar"), Some(&basic_manifest()));
+    assert!(!report.has_errors(), "unexpected errors:\n{report}");
+    assert!(
+        report
+            .issues
+            .iter()
+            .all(|issue| issue.severity == MortarIssueSeverity::Warning)
+    );
+}
+
+#[test]
+fn bad_fixture_reports_each_problem() {
+    let data = MortarAssetLoader::load_bytes(BAD_FIXTURE.as_bytes(), Path::new("bad.mortared"))
+        .expect("fixture should parse");
+    let mut manifest = MortarFunctionManifest::default();
+    manifest.functions.push(crate::MortarFunctionSignature {
+        name: "needs_two".to_string(),
+        arity: Some(1),
+    });
+
+    let issues = validate_mortared_data(&data, Some(&manifest));
+    let kinds: Vec<MortarIssueKind> = issues.iter().map(|issue| issue.kind).collect();
+
+    assert_eq!(
+        kinds
+            .iter()
+            .filter(|kind| **kind == MortarIssueKind::BrokenNodeReference)
+            .count(),
+        2
+    );
+    for expected in [
+        MortarIssueKind::UnknownRunTarget,
+        MortarIssueKind::MalformedContent,
+        MortarIssueKind::ArityMismatch,
+        MortarIssueKind::UnreachableNode,
+    ] {
+        assert!(
+            kinds.contains(&expected),
+            "missing {expected:?} in {kinds:?}"
+        );
+    }
+}
+
+#[test]
+fn missing_file_reports_load_failure() {
+    let report = validate_mortar_file(Path::new("assets/does_not_exist.mortar"), None);
+    assert!(report.has_errors());
+    assert_eq!(report.issues[0].kind, MortarIssueKind::LoadFailed);
+    assert_eq!(report.to_json_value()["ok"], false);
+}
+
+#[test]
+fn manifest_roundtrips_through_json() {
+    let manifest = basic_manifest();
+    assert_eq!(manifest.get("play_sound").and_then(|f| f.arity), Some(1));
+
+    let parsed = MortarFunctionManifest::from_json(&manifest.to_json()).unwrap();
+    assert_eq!(parsed, manifest);
+
+    let mut registry = MortarFunctionRegistry::new();
+    registry.import_manifest(&parsed);
+    assert!(registry.call("get_name", &[]).is_some());
+}
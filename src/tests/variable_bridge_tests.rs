@@ -0,0 +1,142 @@
+//! Covers moving game structs in and out of the dialogue variables: a nested struct with a
+//! renamed field and an enum stored as a string round-trips under a prefix, and exporting names
+//! the dotted path of a missing or mistyped field.
+//!
+//! 覆盖在对话变量与游戏结构体之间搬运数据：带有重命名字段、并以字符串存放枚举的嵌套结构体可以在前缀下
+//! 往返，导出时会给出缺失或类型不符字段的点分路径。
+
+use crate::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+enum Class {
+    Warrior,
+    Mage,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Stats {
+    hp: i32,
+    speed: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct PlayerData {
+    gold: u32,
+    name: String,
+    class: Class,
+    stats: Stats,
+    #[serde(rename = "met_mayor")]
+    met: bool,
+    title: Option<String>,
+    inventory: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct WithMana {
+    #[serde(rename = "stats")]
+    _stats: Mana,
+}
+
+#[derive(Debug, Deserialize)]
+struct Mana {
+    #[serde(rename = "mana")]
+    _mana: u32,
+}
+
+fn player() -> PlayerData {
+    PlayerData {
+        gold: 120,
+        name: "Mira".to_owned(),
+        class: Class::Mage,
+        stats: Stats { hp: -3, speed: 1.5 },
+        met: true,
+        title: None,
+        inventory: vec!["lamp".to_owned()],
+    }
+}
+
+fn seeded(variables: &MortarDialogueVariables, name: &str) -> Option<MortarVariableValue> {
+    variables
+        .restored
+        .iter()
+        .find(|(seeded, _)| seeded == name)
+        .map(|(_, value)| value.clone())
+}
+
+#[test]
+fn test_nested_struct_round_trips_under_a_prefix() {
+    let mut variables = MortarDialogueVariables::default();
+    variables.import_from(&player(), Some("player"));
+
+    assert_eq!(
+        seeded(&variables, "player.stats.hp"),
+        Some(MortarVariableValue::Number(-3.0))
+    );
+    assert_eq!(
+        seeded(&variables, "player.class"),
+        Some(MortarVariableValue::String("Mage".to_owned()))
+    );
+    assert_eq!(
+        seeded(&variables, "player.met_mayor"),
+        Some(MortarVariableValue::Boolean(true))
+    );
+    assert_eq!(seeded(&variables, "player.title"), None);
+    assert_eq!(seeded(&variables, "player.inventory"), None);
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Exported {
+        gold: u32,
+        name: String,
+        class: Class,
+        stats: Stats,
+        met_mayor: bool,
+        title: Option<String>,
+    }
+    let exported: Exported = variables.export_into(Some("player")).unwrap();
+    assert_eq!(
+        exported,
+        Exported {
+            gold: 120,
+            name: "Mira".to_owned(),
+            class: Class::Mage,
+            stats: Stats { hp: -3, speed: 1.5 },
+            met_mayor: true,
+            title: None,
+        }
+    );
+    let stats: Stats = variables.export_into(Some("player.stats")).unwrap();
+    assert_eq!(stats, player().stats);
+}
+
+#[test]
+fn test_export_names_a_missing_field() {
+    let mut variables = MortarDialogueVariables::default();
+    variables.import_from(&player(), Some("player"));
+    let error = variables
+        .export_into::<WithMana>(Some("player"))
+        .unwrap_err();
+    assert_eq!(
+        error,
+        MortarExportError::MissingField {
+            field: "player.stats.mana".to_owned()
+        }
+    );
+    assert!(error.to_string().contains("player.stats.mana"));
+}
+
+#[test]
+fn test_export_names_a_mistyped_field() {
+    let mut variables = MortarDialogueVariables::default();
+    variables.import_from(&player().stats, Some("stats"));
+    variables.seed("stats.hp", MortarVariableValue::String("full".to_owned()));
+    let Err(MortarExportError::MistypedField { field, .. }) =
+        variables.export_into::<Stats>(Some("stats"))
+    else {
+        panic!("a string hp should not export");
+    };
+    assert_eq!(field, "stats.hp");
+
+    variables.seed("stats.hp", MortarVariableValue::Number(2.5));
+    assert!(variables.export_into::<Stats>(Some("stats")).is_err());
+}
@@ -0,0 +1,571 @@
+//! # validation.rs
+//!
+//! # validation.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Offline checks for compiled Mortar data, shared by the `mortar-check` CLI and any
+//! tooling that wants to gate content before it reaches the runtime. Files are decoded through
+//! [`MortarAssetLoader::load_bytes`], the same path the asset loader uses, and then inspected for
+//! broken node references, unbound functions, unreachable nodes, malformed content items and node
+//! overrides pointing nowhere.
+//! The plugin runs the same checks on loaded assets in the background, see `analysis.rs`.
+//!
+//! 对编译后的 Mortar 数据做离线检查，供 `mortar-check` 命令行工具以及其他希望在内容进入
+//! 运行时之前进行把关的工具共用。文件通过与资源加载器相同的 [`MortarAssetLoader::load_bytes`]
+//! 解码，随后检查失效的节点引用、未绑定的函数、不可达节点、格式错误的内容项以及指向不存在目标的
+//! 节点覆盖。插件会在后台对
+//! 已加载的资源运行相同的检查，参见 `analysis.rs`。
+
+mod analysis;
+mod lint;
+
+#[cfg(test)]
+pub(crate) use analysis::{AnalysisPass, Findings};
+pub(crate) use analysis::{AnalysisTasks, StartGate, analyze_loaded_assets};
+pub use analysis::{MortarAnalysisComplete, MortarStrictStart};
+pub use lint::{
+    DEFAULT_MAX_TEXT_CHARS, MortarLint, MortarLintConfig, MortarLintRule, MortarLinter,
+    lint_mortared_data,
+};
+
+use crate::asset::{find_node, node_declarations};
+use crate::debug::LOG_ASSET;
+use crate::{MortarAssetLoader, MortarFunctionManifest};
+use bevy::log::{Level, debug, error, info, trace, warn};
+use mortar_compiler::{Choice, ContentItem, MortaredData};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::path::Path;
+
+/// How serious a validation issue is.
+///
+/// 校验问题的严重程度。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MortarIssueSeverity {
+    Error,
+    Warning,
+}
+
+/// Category of a validation issue.
+///
+/// 校验问题的类别。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MortarIssueKind {
+    /// The file could not be read or compiled.
+    ///
+    /// 文件无法读取或编译。
+    LoadFailed,
+    /// A `next` or choice target names a node that does not exist.
+    ///
+    /// `next` 或选项目标指向了不存在的节点。
+    BrokenNodeReference,
+    /// A `run` statement names an event or timeline that does not exist.
+    ///
+    /// `run` 语句指向了不存在的事件或时间线。
+    UnknownRunTarget,
+    /// A declared function is missing from the bindings manifest.
+    ///
+    /// 声明的函数不在绑定清单中。
+    UnboundFunction,
+    /// A declared function's parameter count differs from the bound one.
+    ///
+    /// 声明的函数参数个数与绑定的不一致。
+    ArityMismatch,
+    /// A node cannot be reached from the entry node.
+    ///
+    /// 从入口节点无法到达该节点。
+    UnreachableNode,
+    /// A content item does not match any known content shape.
+    ///
+    /// 内容项不符合任何已知的结构。
+    MalformedContent,
+    /// Several nodes share a name; the runtime uses the first declaration.
+    ///
+    /// 多个节点同名；运行时使用第一个声明。
+    DuplicateNode,
+    /// The file declares no nodes, so no dialogue can start from it.
+    ///
+    /// 文件没有声明任何节点，因此无法从中开始对话。
+    NoNodes,
+    /// A node override points at a function, event or timeline that does not exist.
+    ///
+    /// 节点覆盖指向了不存在的函数、事件或时间线。
+    UnknownOverrideTarget,
+    /// A content item holds several texts through a `continuation`. They play in declaration
+    /// order; the warning flags compiler output that no longer maps one text per content item.
+    ///
+    /// 内容项通过 `continuation` 包含多个文本。它们按声明顺序播放；该警告用于标记不再是每个内容项
+    /// 对应一个文本的编译器输出。
+    SharedContentIndex,
+}
+
+impl MortarIssueKind {
+    /// Stable snake_case identifier used in machine-readable output.
+    ///
+    /// 机器可读输出中使用的稳定 snake_case 标识。
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::LoadFailed => "load_failed",
+            Self::BrokenNodeReference => "broken_node_reference",
+            Self::UnknownRunTarget => "unknown_run_target",
+            Self::UnboundFunction => "unbound_function",
+            Self::ArityMismatch => "arity_mismatch",
+            Self::UnreachableNode => "unreachable_node",
+            Self::MalformedContent => "malformed_content",
+            Self::DuplicateNode => "duplicate_node",
+            Self::NoNodes => "no_nodes",
+            Self::UnknownOverrideTarget => "unknown_override_target",
+            Self::SharedContentIndex => "shared_content_index",
+        }
+    }
+}
+
+/// A single finding produced by validation.
+///
+/// 校验产生的单条结果。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MortarValidationIssue {
+    pub severity: MortarIssueSeverity,
+    pub kind: MortarIssueKind,
+    /// Node the issue was found in, if any.
+    ///
+    /// 问题所在的节点（如有）。
+    pub node: Option<String>,
+    pub message: String,
+}
+
+impl MortarValidationIssue {
+    fn error(kind: MortarIssueKind, node: Option<&str>, message: String) -> Self {
+        Self {
+            severity: MortarIssueSeverity::Error,
+            kind,
+            node: node.map(str::to_string),
+            message,
+        }
+    }
+
+    fn warning(kind: MortarIssueKind, node: Option<&str>, message: String) -> Self {
+        Self {
+            severity: MortarIssueSeverity::Warning,
+            ..Self::error(kind, node, message)
+        }
+    }
+
+    fn to_json_value(&self) -> serde_json::Value {
+        serde_json::json!({
+            "severity": match self.severity {
+                MortarIssueSeverity::Error => "error",
+                MortarIssueSeverity::Warning => "warning",
+            },
+            "kind": self.kind.as_str(),
+            "node": self.node,
+            "message": self.message,
+        })
+    }
+}
+
+impl fmt::Display for MortarValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            MortarIssueSeverity::Error => "error",
+            MortarIssueSeverity::Warning => "warning",
+        };
+        write!(f, "{severity}[{}]", self.kind.as_str())?;
+        if let Some(node) = &self.node {
+            write!(f, " in node '{node}'")?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+/// Validation result for one file.
+///
+/// 单个文件的校验结果。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MortarValidationReport {
+    pub path: String,
+    pub issues: Vec<MortarValidationIssue>,
+    /// Lint findings; empty unless the file was checked with [`lint_mortar_file`].
+    ///
+    /// lint 结果；仅当文件通过 [`lint_mortar_file`] 检查时才非空。
+    pub lints: Vec<MortarLint>,
+}
+
+impl MortarValidationReport {
+    /// Returns true if any issue is an error.
+    ///
+    /// 存在任意错误级问题时返回 true。
+    pub fn has_errors(&self) -> bool {
+        self.issues
+            .iter()
+            .any(|issue| issue.severity == MortarIssueSeverity::Error)
+    }
+
+    /// Machine-readable representation used by `--format json`.
+    ///
+    /// `--format json` 使用的机器可读表示。
+    pub fn to_json_value(&self) -> serde_json::Value {
+        serde_json::json!({
+            "path": self.path,
+            "ok": !self.has_errors(),
+            "issues": self.issues.iter().map(MortarValidationIssue::to_json_value).collect::<Vec<_>>(),
+            "lints": self.lints.iter().map(MortarLint::to_json_value).collect::<Vec<_>>(),
+        })
+    }
+}
+
+impl fmt::Display for MortarValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = if self.has_errors() { "FAIL" } else { "ok" };
+        writeln!(f, "{status}: {}", self.path)?;
+        for issue in &self.issues {
+            writeln!(f, "    {issue}")?;
+        }
+        for lint in &self.lints {
+            writeln!(f, "    {lint}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads, decodes and validates a `.mortar` or `.mortared` file.
+///
+/// 读取、解码并校验 `.mortar` 或 `.mortared` 文件。
+pub fn validate_mortar_file(
+    path: &Path,
+    manifest: Option<&MortarFunctionManifest>,
+) -> MortarValidationReport {
+    check_file(path, manifest, None)
+}
+
+/// Like [`validate_mortar_file`], then lints files that decoded with `config`.
+///
+/// 与 [`validate_mortar_file`] 相同，随后使用 `config` 对成功解码的文件进行 lint。
+pub fn lint_mortar_file(
+    path: &Path,
+    manifest: Option<&MortarFunctionManifest>,
+    config: &MortarLintConfig,
+) -> MortarValidationReport {
+    check_file(path, manifest, Some(config))
+}
+
+fn check_file(
+    path: &Path,
+    manifest: Option<&MortarFunctionManifest>,
+    lint: Option<&MortarLintConfig>,
+) -> MortarValidationReport {
+    let display_path = path.display().to_string();
+    let asset = std::fs::read(path)
+        .map_err(|err| err.to_string())
+        .and_then(|bytes| {
+            MortarAssetLoader::load_asset_bytes(&bytes, path).map_err(|err| err.to_string())
+        });
+
+    let (issues, lints) = match asset {
+        Ok(asset) => {
+            let mut issues = validate_mortared_data(&asset.data, manifest);
+            check_overrides(
+                &asset.metadata.node_overrides,
+                &asset.data,
+                manifest,
+                &mut issues,
+            );
+            let lints = lint
+                .map(|config| lint_mortared_data(&asset.data, config))
+                .unwrap_or_default();
+            (issues, lints)
+        }
+        Err(message) => (
+            vec![MortarValidationIssue::error(
+                MortarIssueKind::LoadFailed,
+                None,
+                message,
+            )],
+            Vec::new(),
+        ),
+    };
+
+    MortarValidationReport {
+        path: display_path,
+        issues,
+        lints,
+    }
+}
+
+/// Validates already-decoded Mortar data. Function bindings are only checked when a
+/// manifest is supplied.
+///
+/// 校验已解码的 Mortar 数据。仅在提供清单时检查函数绑定。
+pub fn validate_mortared_data(
+    data: &MortaredData,
+    manifest: Option<&MortarFunctionManifest>,
+) -> Vec<MortarValidationIssue> {
+    let mut issues = Vec::new();
+    check_node_names(data, &mut issues);
+    let node_names: HashSet<&str> = data.nodes.iter().map(|node| node.name.as_str()).collect();
+
+    for node in &data.nodes {
+        if let Some(next) = node.next.as_deref() {
+            check_target(&node_names, &node.name, next, &mut issues);
+        }
+        for (idx, item) in node.content.iter().enumerate() {
+            check_content_item(data, &node_names, &node.name, idx, item, &mut issues);
+        }
+    }
+
+    if let Some(manifest) = manifest {
+        check_functions(data, manifest, &mut issues);
+    }
+    check_reachability(data, &mut issues);
+    issues
+}
+
+fn check_node_names(data: &MortaredData, issues: &mut Vec<MortarValidationIssue>) {
+    if data.nodes.is_empty() {
+        issues.push(MortarValidationIssue::error(
+            MortarIssueKind::NoNodes,
+            None,
+            "file declares no nodes".to_string(),
+        ));
+    }
+    let mut reported = HashSet::new();
+    for node in &data.nodes {
+        let declarations = node_declarations(data, &node.name);
+        if declarations.len() < 2 || !reported.insert(node.name.as_str()) {
+            continue;
+        }
+        let positions: Vec<String> = declarations.iter().map(ToString::to_string).collect();
+        issues.push(MortarValidationIssue::error(
+            MortarIssueKind::DuplicateNode,
+            Some(&node.name),
+            format!(
+                "node is declared {} times, at node positions {}; the first declaration is used",
+                declarations.len(),
+                positions.join(", ")
+            ),
+        ));
+    }
+}
+
+fn check_target(
+    node_names: &HashSet<&str>,
+    node: &str,
+    target: &str,
+    issues: &mut Vec<MortarValidationIssue>,
+) {
+    if matches!(target, "return" | "break") || node_names.contains(target) {
+        return;
+    }
+    issues.push(MortarValidationIssue::error(
+        MortarIssueKind::BrokenNodeReference,
+        Some(node),
+        format!("target node '{target}' does not exist"),
+    ));
+}
+
+fn check_choice_targets(
+    node_names: &HashSet<&str>,
+    node: &str,
+    options: &[Choice],
+    issues: &mut Vec<MortarValidationIssue>,
+) {
+    for option in options {
+        if let Some(next) = option.next.as_deref() {
+            check_target(node_names, node, next, issues);
+        }
+        if let Some(nested) = &option.choice {
+            check_choice_targets(node_names, node, nested, issues);
+        }
+    }
+}
+
+fn check_content_item(
+    data: &MortaredData,
+    node_names: &HashSet<&str>,
+    node: &str,
+    idx: usize,
+    item: &serde_json::Value,
+    issues: &mut Vec<MortarValidationIssue>,
+) {
+    let parsed = match serde_json::from_value::<ContentItem>(item.clone()) {
+        Ok(parsed) => parsed,
+        Err(err) => {
+            issues.push(MortarValidationIssue::error(
+                MortarIssueKind::MalformedContent,
+                Some(node),
+                format!("content item {idx} is malformed: {err}"),
+            ));
+            return;
+        }
+    };
+
+    match parsed {
+        ContentItem::Choice { options } => check_choice_targets(node_names, node, &options, issues),
+        ContentItem::RunEvent { name, .. } | ContentItem::RunTimeline { name } => {
+            let known = data.events.iter().any(|event| event.name == name)
+                || data.timelines.iter().any(|timeline| timeline.name == name);
+            if !known {
+                issues.push(MortarValidationIssue::error(
+                    MortarIssueKind::UnknownRunTarget,
+                    Some(node),
+                    format!("content item {idx} runs unknown event or timeline '{name}'"),
+                ));
+            }
+        }
+        ContentItem::Text { .. } | ContentItem::Line { .. } => {
+            let continued = item
+                .get("continuation")
+                .and_then(|value| value.as_array())
+                .map_or(0, Vec::len);
+            if continued > 0 {
+                issues.push(MortarValidationIssue::warning(
+                    MortarIssueKind::SharedContentIndex,
+                    Some(node),
+                    format!(
+                        "content item {idx} holds {} texts; they play in declaration order",
+                        continued + 1
+                    ),
+                ));
+            }
+        }
+    }
+}
+
+fn check_functions(
+    data: &MortaredData,
+    manifest: &MortarFunctionManifest,
+    issues: &mut Vec<MortarValidationIssue>,
+) {
+    for function in &data.functions {
+        let Some(bound) = manifest.get(&function.name) else {
+            issues.push(MortarValidationIssue::error(
+                MortarIssueKind::UnboundFunction,
+                None,
+                format!("function '{}' is not bound by the game", function.name),
+            ));
+            continue;
+        };
+        if let Some(arity) = bound.arity
+            && arity != function.params.len()
+        {
+            issues.push(MortarValidationIssue::error(
+                MortarIssueKind::ArityMismatch,
+                None,
+                format!(
+                    "function '{}' is declared with {} parameter(s) but bound with {}",
+                    function.name,
+                    function.params.len(),
+                    arity
+                ),
+            ));
+        }
+    }
+}
+
+/// Warns about node overrides whose target is neither a declared or bound function nor an event
+/// or timeline of the file.
+pub(crate) fn check_overrides(
+    overrides: &HashMap<String, HashMap<String, String>>,
+    data: &MortaredData,
+    manifest: Option<&MortarFunctionManifest>,
+    issues: &mut Vec<MortarValidationIssue>,
+) {
+    let known = |target: &str| {
+        data.functions
+            .iter()
+            .any(|function| function.name == target)
+            || manifest.is_some_and(|manifest| manifest.get(target).is_some())
+            || data.events.iter().any(|event| event.name == target)
+            || data
+                .timelines
+                .iter()
+                .any(|timeline| timeline.name == target)
+    };
+    let mut nodes: Vec<_> = overrides.iter().collect();
+    nodes.sort_by_key(|(node, _)| *node);
+    for (node, map) in nodes {
+        let mut entries: Vec<_> = map.iter().collect();
+        entries.sort();
+        for (name, target) in entries {
+            if !known(target) {
+                issues.push(MortarValidationIssue::warning(
+                    MortarIssueKind::UnknownOverrideTarget,
+                    Some(node),
+                    format!("override of '{name}' points at unknown '{target}'"),
+                ));
+            }
+        }
+    }
+}
+
+/// Returns the jump targets of a node: its `next` plus every (nested) choice target.
+///
+/// 返回节点的跳转目标：`next` 以及所有（嵌套）选项目标。
+fn node_targets(node: &mortar_compiler::Node) -> Vec<String> {
+    fn collect(options: &[Choice], targets: &mut Vec<String>) {
+        for option in options {
+            targets.extend(option.next.clone());
+            if let Some(nested) = &option.choice {
+                collect(nested, targets);
+            }
+        }
+    }
+
+    let mut targets: Vec<String> = node.next.iter().cloned().collect();
+    for item in &node.content {
+        if let Ok(ContentItem::Choice { options }) =
+            serde_json::from_value::<ContentItem>(item.clone())
+        {
+            collect(&options, &mut targets);
+        }
+    }
+    targets
+}
+
+fn check_reachability(data: &MortaredData, issues: &mut Vec<MortarValidationIssue>) {
+    let entry = find_node(data, "Start").or_else(|| data.nodes.first());
+    let Some(entry) = entry else {
+        return;
+    };
+
+    let mut reached: HashSet<&str> = HashSet::from([entry.name.as_str()]);
+    let mut queue = VecDeque::from([entry]);
+    while let Some(node) = queue.pop_front() {
+        for target in node_targets(node) {
+            let Some(next) = find_node(data, &target) else {
+                continue;
+            };
+            if reached.insert(next.name.as_str()) {
+                queue.push_back(next);
+            }
+        }
+    }
+
+    for node in &data.nodes {
+        if !reached.contains(node.name.as_str()) {
+            issues.push(MortarValidationIssue::warning(
+                MortarIssueKind::UnreachableNode,
+                Some(&node.name),
+                format!("node is not reachable from '{}'", entry.name),
+            ));
+        }
+    }
+}
+
+/// Logs a load-time finding about `path` at `level`.
+fn log_finding(level: Level, path: &str, finding: &dyn fmt::Display) {
+    if level == Level::ERROR {
+        error!(target: LOG_ASSET, "{path}: {finding}");
+    } else if level == Level::WARN {
+        warn!(target: LOG_ASSET, "{path}: {finding}");
+    } else if level == Level::INFO {
+        info!(target: LOG_ASSET, "{path}: {finding}");
+    } else if level == Level::DEBUG {
+        debug!(target: LOG_ASSET, "{path}: {finding}");
+    } else {
+        trace!(target: LOG_ASSET, "{path}: {finding}");
+    }
+}
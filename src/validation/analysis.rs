@@ -0,0 +1,233 @@
+//! # analysis.rs
+//!
+//! # analysis.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! The analysis the plugin runs when an asset loads or hot reloads. Validation and lint are pure
+//! passes over [`MortaredData`], so each version of a file is cloned into a task on the
+//! [`AsyncComputeTaskPool`] instead of being checked on the frame its asset event arrives. A task
+//! is polled from the next frame on; its findings are stored on the asset and announced with
+//! [`MortarAnalysisComplete`]. Each asset has at most one task, for its current revision: a newer
+//! save drops the task of the version it replaced, which cancels it, and results that finish for
+//! a replaced version are discarded. [`MortarStrictStart`] lets start requests refuse files whose
+//! latest analysis found errors.
+//!
+//! 插件在资源加载或热重载时运行的分析。校验与 lint 都是作用于 [`MortaredData`] 的纯函数流程，因此
+//! 每个版本的文件都会被克隆进 [`AsyncComputeTaskPool`] 上的任务，而不是在资源事件到达的那一帧
+//! 进行检查。任务从下一帧起开始轮询；其结果保存在资源上，并通过 [`MortarAnalysisComplete`] 宣布。
+//! 每个资源最多只有一个针对其当前版本的任务：较新的保存会丢弃被替换版本的任务，从而取消它；为已被
+//! 替换的版本完成的结果会被丢弃。[`MortarStrictStart`] 让开始请求拒绝最近一次分析发现错误的文件。
+
+use super::{MortarValidationIssue, check_overrides, log_finding, validate_mortared_data};
+use crate::{MortarAsset, MortarLint, MortarLintConfig, MortarLinter, MortarRuntime};
+use bevy::asset::{AssetId, AssetServer, Assets};
+use bevy::platform::time::Instant;
+use bevy::prelude::*;
+use bevy::tasks::futures::check_ready;
+use bevy::tasks::{AsyncComputeTaskPool, Task};
+use mortar_compiler::MortaredData;
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Whether start requests check the load-time analysis of their file.
+///
+/// 开始请求是否检查其文件的加载时分析结果。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MortarStrictStart {
+    /// Files start whatever their analysis found.
+    ///
+    /// 无论分析结果如何，文件都会开始。
+    #[default]
+    Off,
+    /// A start waits until the analysis of the current version completes, then refuses the file
+    /// if it has validation errors.
+    ///
+    /// 开始请求会等待当前版本的分析完成，若存在校验错误则拒绝该文件。
+    Wait,
+    /// A start proceeds while the analysis is still running, and is only refused once a completed
+    /// analysis has found validation errors.
+    ///
+    /// 分析仍在进行时开始请求照常进行，只有在已完成的分析发现校验错误后才会被拒绝。
+    Optimistic,
+}
+
+/// What a start request may do with a file under a [`MortarStrictStart`] policy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum StartGate {
+    Open,
+    Wait,
+    Refuse,
+}
+
+impl MortarStrictStart {
+    pub(crate) fn gate(self, asset: &MortarAsset) -> StartGate {
+        match self {
+            Self::Off => StartGate::Open,
+            _ if asset.is_analyzed() && asset.has_validation_errors() => StartGate::Refuse,
+            Self::Wait if !asset.is_analyzed() => StartGate::Wait,
+            _ => StartGate::Open,
+        }
+    }
+}
+
+/// Emitted when the analysis of a loaded or reloaded file completes and its results are stored
+/// on the asset.
+///
+/// 当已加载或重载文件的分析完成、结果已保存到资源上时发出。
+#[derive(Message, Debug, Clone, PartialEq)]
+pub struct MortarAnalysisComplete {
+    pub asset: AssetId<MortarAsset>,
+    /// Asset path of the file, or the asset id when it has none.
+    ///
+    /// 文件的资源路径；没有路径时为资源 id。
+    pub path: String,
+    /// Validation findings, warnings included; empty when validation is off.
+    ///
+    /// 校验结果（包含警告）；未开启校验时为空。
+    pub errors: Vec<MortarValidationIssue>,
+    /// Lints; empty when [`MortarLintConfig::lint_on_load`] is off.
+    ///
+    /// lint 结果；[`MortarLintConfig::lint_on_load`] 关闭时为空。
+    pub lints: Vec<MortarLint>,
+    /// Time the passes took on their task.
+    ///
+    /// 各流程在其任务中耗费的时间。
+    pub duration: Duration,
+}
+
+/// The passes one analysis runs.
+#[derive(Debug, Clone)]
+pub(crate) struct AnalysisPass {
+    pub(crate) validate: bool,
+    pub(crate) lint: Option<MortarLintConfig>,
+}
+
+/// Findings of one analysis: validation issues, and lints when the pass linted.
+pub(crate) type Findings = (Vec<MortarValidationIssue>, Option<Vec<MortarLint>>);
+
+impl AnalysisPass {
+    pub(crate) fn run(&self, data: &MortaredData) -> Findings {
+        let issues = if self.validate {
+            validate_mortared_data(data, None)
+        } else {
+            Vec::new()
+        };
+        let lints = self
+            .lint
+            .as_ref()
+            .map(|config| MortarLinter::new(config.clone()).lint(data));
+        (issues, lints)
+    }
+}
+
+/// An analysis running for one revision of an asset.
+struct InFlight {
+    revision: u64,
+    task: Task<(Findings, Duration)>,
+}
+
+/// Analyses in flight, at most one per asset.
+#[derive(Resource)]
+pub(crate) struct AnalysisTasks {
+    in_flight: HashMap<AssetId<MortarAsset>, InFlight>,
+    /// The function tasks run; tests swap in a slowed one.
+    pub(crate) analyze: fn(&AnalysisPass, &MortaredData) -> Findings,
+}
+
+impl Default for AnalysisTasks {
+    fn default() -> Self {
+        Self {
+            in_flight: HashMap::new(),
+            analyze: AnalysisPass::run,
+        }
+    }
+}
+
+/// Collects finished analyses, cancels those of replaced or removed assets, and starts one for
+/// every asset version that has not been analyzed.
+pub(crate) fn analyze_loaded_assets(
+    config: Res<MortarLintConfig>,
+    runtime: Res<MortarRuntime>,
+    asset_server: Res<AssetServer>,
+    mut tasks: ResMut<AnalysisTasks>,
+    mut assets: ResMut<Assets<MortarAsset>>,
+    mut complete: MessageWriter<MortarAnalysisComplete>,
+) {
+    let finished: Vec<_> = tasks
+        .in_flight
+        .iter_mut()
+        .filter_map(|(id, in_flight)| {
+            check_ready(&mut in_flight.task).map(|output| (*id, in_flight.revision, output))
+        })
+        .collect();
+    for (id, revision, ((issues, lints), duration)) in finished {
+        tasks.in_flight.remove(&id);
+        // Untracked so storing the results does not raise another `Modified`.
+        //
+        // 使用不追踪的访问，避免保存结果时再次触发 `Modified`。
+        let Some(asset) = assets
+            .get_mut_untracked(id)
+            .filter(|asset| asset.revision() == revision)
+        else {
+            continue;
+        };
+        asset.apply_analysis(issues.clone(), lints.clone());
+        let path = asset_server
+            .get_path(id)
+            .map_or_else(|| id.to_string(), |path| path.to_string());
+        for issue in &issues {
+            log_finding(config.log_level, &path, issue);
+        }
+        let lints = lints.unwrap_or_default();
+        for lint in &lints {
+            log_finding(config.log_level, &path, lint);
+        }
+        complete.write(MortarAnalysisComplete {
+            asset: id,
+            path,
+            errors: issues,
+            lints,
+            duration,
+        });
+    }
+
+    // Dropping a task cancels it.
+    //
+    // 丢弃任务即取消它。
+    tasks.in_flight.retain(|id, in_flight| {
+        assets
+            .get(*id)
+            .is_some_and(|asset| asset.revision() == in_flight.revision)
+    });
+
+    let pass = AnalysisPass {
+        validate: config.validate_on_load || runtime.strict_start != MortarStrictStart::Off,
+        lint: config.lint_on_load.then(|| config.clone()),
+    };
+    if !pass.validate && pass.lint.is_none() {
+        return;
+    }
+    let analyze = tasks.analyze;
+    let pool = AsyncComputeTaskPool::get();
+    for (id, asset) in assets.iter() {
+        if asset.is_analyzed() || tasks.in_flight.contains_key(&id) {
+            continue;
+        }
+        let pass = pass.clone();
+        let data = asset.data.clone();
+        let overrides = asset.metadata.node_overrides.clone();
+        let task = pool.spawn(async move {
+            let started = Instant::now();
+            let (mut issues, lints) = analyze(&pass, &data);
+            if pass.validate {
+                check_overrides(&overrides, &data, None, &mut issues);
+            }
+            ((issues, lints), started.elapsed())
+        });
+        let revision = asset.revision();
+        tasks.in_flight.insert(id, InFlight { revision, task });
+    }
+}
@@ -0,0 +1,521 @@
+//! # lint.rs
+//!
+//! # lint.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Soft checks that run after validation. Lints flag content that loads and plays but is
+//! probably not what the authors meant: lines that overflow the text box, events placed past the
+//! end of their line, duplicated choice labels, nodes that can never end the dialogue, empty
+//! lines that do nothing, variables nobody reads, and loops that skip through conditional lines
+//! forever. Each rule can be switched off through [`MortarLintConfig`]. The same [`MortarLinter`]
+//! backs the `mortar-check --lint` flag and the optional lint pass the plugin runs when an asset
+//! loads, whose report is kept on the asset.
+//!
+//! 在校验之后运行的软性检查。lint 标记那些能够加载和播放、但很可能并非作者本意的内容：超出文本框的行、
+//! 放在行尾之后的事件、重复的选项文本、永远无法结束对话的节点、什么也不做的空行、无人读取的变量，以及
+//! 无休止地跳过条件行的循环。每条规则都可以通过 [`MortarLintConfig`] 关闭。同一个
+//! [`MortarLinter`] 同时支撑 `mortar-check --lint` 参数以及插件在资源加载时可选运行的 lint 流程，
+//! 其报告保存在资源上。
+
+use super::{MortarIssueSeverity, node_targets};
+use crate::asset::find_node;
+use bevy::log::Level;
+use bevy::prelude::*;
+use mortar_compiler::{Choice, ContentItem, MortaredData, Node};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+mod silent_loop;
+
+/// Default [`MortarLintConfig::max_text_chars`].
+///
+/// [`MortarLintConfig::max_text_chars`] 的默认值。
+pub const DEFAULT_MAX_TEXT_CHARS: usize = 200;
+
+/// A lint rule.
+///
+/// lint 规则。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MortarLintRule {
+    /// A line is longer than [`MortarLintConfig::max_text_chars`].
+    ///
+    /// 行长度超过 [`MortarLintConfig::max_text_chars`]。
+    TextTooLong,
+    /// An event index lies past the end of the raw line.
+    ///
+    /// 事件索引超出原始行的末尾。
+    EventIndexOutOfRange,
+    /// Two options of one choice share a label.
+    ///
+    /// 同一选择中的两个选项文本相同。
+    DuplicateChoiceLabel,
+    /// No path from the node ever ends the dialogue.
+    ///
+    /// 从该节点出发的任何路径都无法结束对话。
+    NoReachableEnd,
+    /// A line is empty and carries neither statements nor events.
+    ///
+    /// 行为空，且既没有语句也没有事件。
+    EmptyText,
+    /// A variable is declared but never referenced by the script.
+    ///
+    /// 变量已声明但从未被脚本引用。
+    UnusedVariable,
+    /// `next` jumps loop through nodes made only of conditional lines, which advance on their
+    /// own when every condition fails.
+    ///
+    /// `next` 跳转经由仅包含条件行的节点形成环；当所有条件都不成立时，这些节点会自行推进。
+    SilentLoop,
+}
+
+impl MortarLintRule {
+    /// Every rule, in report order.
+    ///
+    /// 所有规则，按报告顺序排列。
+    pub const ALL: [Self; 7] = [
+        Self::TextTooLong,
+        Self::EventIndexOutOfRange,
+        Self::DuplicateChoiceLabel,
+        Self::NoReachableEnd,
+        Self::EmptyText,
+        Self::UnusedVariable,
+        Self::SilentLoop,
+    ];
+
+    /// Stable snake_case identifier used in machine-readable output.
+    ///
+    /// 机器可读输出中使用的稳定 snake_case 标识。
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::TextTooLong => "text_too_long",
+            Self::EventIndexOutOfRange => "event_index_out_of_range",
+            Self::DuplicateChoiceLabel => "duplicate_choice_label",
+            Self::NoReachableEnd => "no_reachable_end",
+            Self::EmptyText => "empty_text",
+            Self::UnusedVariable => "unused_variable",
+            Self::SilentLoop => "silent_loop",
+        }
+    }
+}
+
+/// A single finding produced by the linter.
+///
+/// lint 产生的单条结果。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MortarLint {
+    pub severity: MortarIssueSeverity,
+    pub rule: MortarLintRule,
+    /// Node the lint was found in, if any.
+    ///
+    /// lint 所在的节点（如有）。
+    pub node: Option<String>,
+    /// Position of the offending item in the node's content, if any.
+    ///
+    /// 问题内容项在节点内容中的位置（如有）。
+    pub content_index: Option<usize>,
+    pub message: String,
+}
+
+impl MortarLint {
+    fn warning(
+        rule: MortarLintRule,
+        node: Option<&str>,
+        content_index: Option<usize>,
+        message: String,
+    ) -> Self {
+        Self {
+            severity: MortarIssueSeverity::Warning,
+            rule,
+            node: node.map(str::to_string),
+            content_index,
+            message,
+        }
+    }
+
+    pub(super) fn to_json_value(&self) -> serde_json::Value {
+        serde_json::json!({
+            "severity": match self.severity {
+                MortarIssueSeverity::Error => "error",
+                MortarIssueSeverity::Warning => "warning",
+            },
+            "rule": self.rule.as_str(),
+            "node": self.node,
+            "content_index": self.content_index,
+            "message": self.message,
+        })
+    }
+}
+
+impl fmt::Display for MortarLint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "lint[{}]", self.rule.as_str())?;
+        if let Some(node) = &self.node {
+            write!(f, " in node '{node}'")?;
+        }
+        if let Some(index) = self.content_index {
+            write!(f, " at item {index}")?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+/// Lint settings. As a resource, it also controls the analysis run when assets load, see
+/// [`MortarAnalysisComplete`](crate::MortarAnalysisComplete).
+///
+/// lint 设置。作为资源时，它也控制资源加载时运行的分析，参见
+/// [`MortarAnalysisComplete`](crate::MortarAnalysisComplete)。
+#[derive(Resource, Debug, Clone, PartialEq, Eq)]
+pub struct MortarLintConfig {
+    /// Lint every [`MortarAsset`](crate::MortarAsset) as it loads; off by default.
+    ///
+    /// 在每个 [`MortarAsset`](crate::MortarAsset) 加载时进行 lint；默认关闭。
+    pub lint_on_load: bool,
+    /// Validate every [`MortarAsset`](crate::MortarAsset) as it loads; off by default. Always on while
+    /// [`MortarRuntime::strict_start`](crate::MortarRuntime::strict_start) is set.
+    ///
+    /// 在每个 [`MortarAsset`](crate::MortarAsset) 加载时进行校验；默认关闭。设置了
+    /// [`MortarRuntime::strict_start`](crate::MortarRuntime::strict_start) 时始终开启。
+    pub validate_on_load: bool,
+    /// Level load-time lints and validation issues are logged at.
+    ///
+    /// 加载时 lint 与校验问题的日志级别。
+    pub log_level: Level,
+    /// Character budget of one line, counted on the raw text.
+    ///
+    /// 单行的字符预算，按原始文本计算。
+    pub max_text_chars: usize,
+    /// Rules that are switched off.
+    ///
+    /// 被关闭的规则。
+    pub disabled_rules: HashSet<MortarLintRule>,
+}
+
+impl Default for MortarLintConfig {
+    fn default() -> Self {
+        Self {
+            lint_on_load: false,
+            validate_on_load: false,
+            log_level: Level::WARN,
+            max_text_chars: DEFAULT_MAX_TEXT_CHARS,
+            disabled_rules: HashSet::new(),
+        }
+    }
+}
+
+impl MortarLintConfig {
+    /// Returns the config with `rule` switched on or off.
+    ///
+    /// 返回将 `rule` 打开或关闭后的配置。
+    pub fn with_rule(mut self, rule: MortarLintRule, enabled: bool) -> Self {
+        if enabled {
+            self.disabled_rules.remove(&rule);
+        } else {
+            self.disabled_rules.insert(rule);
+        }
+        self
+    }
+
+    pub fn is_enabled(&self, rule: MortarLintRule) -> bool {
+        !self.disabled_rules.contains(&rule)
+    }
+}
+
+/// Runs the enabled lint rules over compiled Mortar data.
+///
+/// 对编译后的 Mortar 数据运行已启用的 lint 规则。
+#[derive(Debug, Clone, Default)]
+pub struct MortarLinter {
+    pub config: MortarLintConfig,
+}
+
+impl MortarLinter {
+    pub fn new(config: MortarLintConfig) -> Self {
+        Self { config }
+    }
+
+    /// Lints `data`, returning findings grouped by rule.
+    ///
+    /// 对 `data` 进行 lint，按规则分组返回结果。
+    pub fn lint(&self, data: &MortaredData) -> Vec<MortarLint> {
+        let mut lints = Vec::new();
+        for rule in MortarLintRule::ALL {
+            if !self.config.is_enabled(rule) {
+                continue;
+            }
+            match rule {
+                MortarLintRule::TextTooLong => self.check_text_length(data, &mut lints),
+                MortarLintRule::EventIndexOutOfRange => check_event_indices(data, &mut lints),
+                MortarLintRule::DuplicateChoiceLabel => check_choice_labels(data, &mut lints),
+                MortarLintRule::NoReachableEnd => check_endings(data, &mut lints),
+                MortarLintRule::EmptyText => check_empty_text(data, &mut lints),
+                MortarLintRule::UnusedVariable => check_unused_variables(data, &mut lints),
+                MortarLintRule::SilentLoop => silent_loop::check_silent_loops(data, &mut lints),
+            }
+        }
+        lints
+    }
+
+    fn check_text_length(&self, data: &MortaredData, lints: &mut Vec<MortarLint>) {
+        let budget = self.config.max_text_chars;
+        for_each_line(data, |node, index, line| {
+            let chars = line.value.chars().count();
+            if chars > budget {
+                lints.push(MortarLint::warning(
+                    MortarLintRule::TextTooLong,
+                    Some(&node.name),
+                    Some(index),
+                    format!("line has {chars} characters, over the budget of {budget}"),
+                ));
+            }
+        });
+    }
+}
+
+/// Lints `data` with `config`; shorthand for [`MortarLinter::lint`].
+///
+/// 使用 `config` 对 `data` 进行 lint；[`MortarLinter::lint`] 的简写。
+pub fn lint_mortared_data(data: &MortaredData, config: &MortarLintConfig) -> Vec<MortarLint> {
+    MortarLinter::new(config.clone()).lint(data)
+}
+
+/// The parts of a text or line item the rules look at.
+struct LineItem<'a> {
+    value: &'a str,
+    has_statements: bool,
+    events: &'a [mortar_compiler::Event],
+}
+
+fn for_each_line(data: &MortaredData, mut visit: impl FnMut(&Node, usize, &LineItem)) {
+    for node in &data.nodes {
+        for (index, item) in node.content.iter().enumerate() {
+            let Ok(item) = serde_json::from_value::<ContentItem>(item.clone()) else {
+                continue;
+            };
+            let (ContentItem::Text {
+                value,
+                pre_statements,
+                events,
+                ..
+            }
+            | ContentItem::Line {
+                value,
+                pre_statements,
+                events,
+                ..
+            }) = &item
+            else {
+                continue;
+            };
+            let line = LineItem {
+                value,
+                has_statements: !pre_statements.is_empty(),
+                events: events.as_deref().unwrap_or_default(),
+            };
+            visit(node, index, &line);
+        }
+    }
+}
+
+fn check_event_indices(data: &MortaredData, lints: &mut Vec<MortarLint>) {
+    for_each_line(data, |node, index, line| {
+        let chars = line.value.chars().count();
+        for event in line.events {
+            if event.index > chars as f64 {
+                lints.push(MortarLint::warning(
+                    MortarLintRule::EventIndexOutOfRange,
+                    Some(&node.name),
+                    Some(index),
+                    format!(
+                        "event at index {} lies past the end of a {chars}-character line",
+                        event.index
+                    ),
+                ));
+            }
+        }
+    });
+}
+
+fn check_empty_text(data: &MortaredData, lints: &mut Vec<MortarLint>) {
+    for_each_line(data, |node, index, line| {
+        if line.value.trim().is_empty() && !line.has_statements && line.events.is_empty() {
+            lints.push(MortarLint::warning(
+                MortarLintRule::EmptyText,
+                Some(&node.name),
+                Some(index),
+                "line is empty and has no statements or events".to_string(),
+            ));
+        }
+    });
+}
+
+fn check_choice_labels(data: &MortaredData, lints: &mut Vec<MortarLint>) {
+    fn check(options: &[Choice], node: &str, index: usize, lints: &mut Vec<MortarLint>) {
+        let mut seen = HashSet::new();
+        for option in options {
+            let label = option.text.trim();
+            if !seen.insert(label) {
+                lints.push(MortarLint::warning(
+                    MortarLintRule::DuplicateChoiceLabel,
+                    Some(node),
+                    Some(index),
+                    format!("choice label '{label}' appears more than once"),
+                ));
+            }
+            if let Some(nested) = &option.choice {
+                check(nested, node, index, lints);
+            }
+        }
+    }
+
+    for node in &data.nodes {
+        for (index, item) in node.content.iter().enumerate() {
+            if let Ok(ContentItem::Choice { options }) =
+                serde_json::from_value::<ContentItem>(item.clone())
+            {
+                check(&options, &node.name, index, lints);
+            }
+        }
+    }
+}
+
+/// Whether picking some option lets the flow carry on past the choice.
+fn falls_through(options: &[Choice]) -> bool {
+    options.iter().any(|option| match option.next.as_deref() {
+        None | Some("break") => option.choice.as_deref().is_none_or(falls_through),
+        Some(_) => false,
+    })
+}
+
+/// Whether some option leaves the dialogue with `return`.
+fn returns(options: &[Choice]) -> bool {
+    options.iter().any(|option| {
+        option.next.as_deref() == Some("return") || option.choice.as_deref().is_some_and(returns)
+    })
+}
+
+/// Whether the dialogue can end inside `node`.
+fn ends_in(node: &Node) -> bool {
+    let choices: Vec<Vec<Choice>> = node
+        .content
+        .iter()
+        .filter_map(
+            |item| match serde_json::from_value::<ContentItem>(item.clone()) {
+                Ok(ContentItem::Choice { options }) => Some(options),
+                _ => None,
+            },
+        )
+        .collect();
+    if choices.iter().any(|options| returns(options)) {
+        return true;
+    }
+    if node.next.is_some() {
+        return false;
+    }
+    let trailing = node
+        .content
+        .last()
+        .and_then(|item| serde_json::from_value::<ContentItem>(item.clone()).ok());
+    match trailing {
+        Some(ContentItem::Choice { options }) => falls_through(&options),
+        _ => true,
+    }
+}
+
+fn check_endings(data: &MortaredData, lints: &mut Vec<MortarLint>) {
+    // Walks the jump graph backwards from every node the dialogue can end in.
+    //
+    // 从每个可以结束对话的节点出发，沿跳转图反向遍历。
+    let mut sources: HashMap<&str, Vec<&str>> = HashMap::new();
+    for node in &data.nodes {
+        for target in node_targets(node) {
+            if let Some(target) = find_node(data, &target) {
+                sources
+                    .entry(target.name.as_str())
+                    .or_default()
+                    .push(node.name.as_str());
+            }
+        }
+    }
+    let mut ending: HashSet<&str> = data
+        .nodes
+        .iter()
+        .filter(|node| ends_in(node))
+        .map(|node| node.name.as_str())
+        .collect();
+    let mut stack: Vec<&str> = ending.iter().copied().collect();
+    while let Some(name) = stack.pop() {
+        for &source in sources.get(name).into_iter().flatten() {
+            if ending.insert(source) {
+                stack.push(source);
+            }
+        }
+    }
+
+    let mut reported = HashSet::new();
+    for node in &data.nodes {
+        if !ending.contains(node.name.as_str()) && reported.insert(node.name.as_str()) {
+            lints.push(MortarLint::warning(
+                MortarLintRule::NoReachableEnd,
+                Some(&node.name),
+                None,
+                "no path from this node ends the dialogue".to_string(),
+            ));
+        }
+    }
+}
+
+/// Collects identifiers from script strings, skipping the display text of lines, options and the
+/// literal parts of interpolated lines.
+fn collect_identifiers<'a>(value: &'a serde_json::Value, names: &mut HashSet<&'a str>) {
+    match value {
+        serde_json::Value::String(text) => names.extend(
+            text.split(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .filter(|word| !word.is_empty()),
+        ),
+        serde_json::Value::Array(items) => {
+            for item in items {
+                collect_identifiers(item, names);
+            }
+        }
+        serde_json::Value::Object(map) => {
+            let is_text = map.get("type").and_then(serde_json::Value::as_str) == Some("text");
+            for (key, item) in map {
+                let display = match key.as_str() {
+                    "value" | "text" => true,
+                    "content" => is_text,
+                    _ => false,
+                };
+                if !display {
+                    collect_identifiers(item, names);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn check_unused_variables(data: &MortaredData, lints: &mut Vec<MortarLint>) {
+    if data.variables.is_empty() {
+        return;
+    }
+    let script = serde_json::json!({
+        "nodes": data.nodes.iter().map(|node| &node.content).collect::<Vec<_>>(),
+        "events": serde_json::to_value(&data.events).unwrap_or_default(),
+        "timelines": serde_json::to_value(&data.timelines).unwrap_or_default(),
+    });
+    let mut referenced = HashSet::new();
+    collect_identifiers(&script, &mut referenced);
+    for variable in &data.variables {
+        if !referenced.contains(variable.name.as_str()) {
+            lints.push(MortarLint::warning(
+                MortarLintRule::UnusedVariable,
+                None,
+                None,
+                format!("variable '{}' is declared but never used", variable.name),
+            ));
+        }
+    }
+}
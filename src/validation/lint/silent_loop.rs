@@ -0,0 +1,88 @@
+//! # silent_loop.rs
+//!
+//! # silent_loop.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! The [`MortarLintRule::SilentLoop`] rule. A node is silent when every item in it is a line with
+//! a condition: if all conditions fail, the runtime skips through it without showing anything or
+//! waiting for input. A cycle of `next` jumps through silent nodes then advances forever, which
+//! the runtime only stops once [`crate::MortarRuntime::auto_advance_budget`] runs out.
+//!
+//! [`MortarLintRule::SilentLoop`] 规则。若节点中的每一项都是带条件的行，则该节点是静默的：一旦所有
+//! 条件都不成立，运行时会直接跳过它，既不显示任何内容，也不等待输入。经由静默节点构成的 `next`
+//! 跳转环会无休止地推进，运行时只有在 [`crate::MortarRuntime::auto_advance_budget`] 耗尽后才会
+//! 停止它。
+
+use super::{MortarLint, MortarLintRule};
+use crate::asset::find_node;
+use mortar_compiler::{ContentItem, MortaredData, Node};
+use std::collections::HashSet;
+
+/// Whether every item of `node` is a conditional line.
+fn is_silent(node: &Node) -> bool {
+    !node.content.is_empty()
+        && node.content.iter().all(|item| {
+            matches!(
+                serde_json::from_value::<ContentItem>(item.clone()),
+                Ok(ContentItem::Text {
+                    condition: Some(_),
+                    ..
+                } | ContentItem::Line {
+                    condition: Some(_),
+                    ..
+                })
+            )
+        })
+}
+
+/// The silent node `node` jumps to with `next`, if any.
+fn silent_next<'a>(data: &'a MortaredData, node: &Node) -> Option<&'a Node> {
+    node.next
+        .as_deref()
+        .filter(|next| *next != "return")
+        .and_then(|next| find_node(data, next))
+        .filter(|next| is_silent(next))
+}
+
+pub(super) fn check_silent_loops(data: &MortaredData, lints: &mut Vec<MortarLint>) {
+    let mut reported = HashSet::new();
+    for node in data.nodes.iter().filter(|node| is_silent(node)) {
+        if reported.contains(node.name.as_str()) {
+            continue;
+        }
+        // Follows `next` until the walk leaves silent nodes or comes back to one it passed.
+        //
+        // 沿 `next` 前进，直到离开静默节点或回到已经过的节点。
+        let mut path = vec![node];
+        while let Some(next) = silent_next(data, path[path.len() - 1]) {
+            let Some(start) = path.iter().position(|seen| seen.name == next.name) else {
+                path.push(next);
+                continue;
+            };
+            let cycle = &path[start..];
+            if cycle
+                .iter()
+                .any(|member| reported.contains(member.name.as_str()))
+            {
+                break;
+            }
+            reported.extend(cycle.iter().map(|member| member.name.as_str()));
+            let names: Vec<&str> = cycle.iter().map(|member| member.name.as_str()).collect();
+            lints.push(MortarLint::warning(
+                MortarLintRule::SilentLoop,
+                Some(&cycle[0].name),
+                None,
+                format!(
+                    "{} -> {} loops through conditional lines only; if every condition fails it \
+                     advances forever",
+                    names.join(" -> "),
+                    names[0]
+                ),
+            ));
+            break;
+        }
+    }
+}
@@ -6,10 +6,19 @@ use bevy::prelude::*;
 use mortar_compiler::{Constant, Enum, IfCondition, Variable};
 use std::collections::HashMap;
 
+use crate::debug::LOG_EVAL;
+
+mod expression;
+mod transaction;
+mod typed;
+
+pub use transaction::{MAX_TRANSACTION_DEPTH, MortarTransactionError};
+
 /// Runtime value for a Mortar variable.
 ///
 /// Mortar 变量的运行时值。
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "save", derive(serde::Serialize, serde::Deserialize))]
 pub enum MortarVariableValue {
     String(String),
     Number(f64),
@@ -63,6 +72,8 @@ struct BranchCase {
 pub struct MortarVariableState {
     variables: HashMap<String, MortarVariableValue>,
     branches: HashMap<String, BranchDef>,
+    revision: u64,
+    undo: transaction::UndoLog,
 }
 
 impl Default for MortarVariableState {
@@ -187,6 +198,8 @@ impl MortarVariableState {
         Self {
             variables: HashMap::new(),
             branches: HashMap::new(),
+            revision: 0,
+            undo: transaction::UndoLog::default(),
         }
     }
 
@@ -202,7 +215,7 @@ impl MortarVariableState {
                 state.set(&constant.name, parsed_value);
             } else {
                 // Fallback for constants if json parsing fails, though unlikely for compiled output
-                warn!("Failed to parse value for constant: {}", constant.name);
+                warn!(target: LOG_EVAL, "Failed to parse value for constant: {}", constant.name);
             }
         }
 
@@ -238,7 +251,19 @@ impl MortarVariableState {
     ///
     /// 设置变量值。
     pub fn set(&mut self, name: &str, value: MortarVariableValue) {
-        self.variables.insert(name.to_string(), value);
+        let previous = self.variables.insert(name.to_string(), value);
+        if self.undo.is_open() {
+            self.undo.record(name, previous);
+        } else {
+            self.revision = self.revision.wrapping_add(1);
+        }
+    }
+
+    /// Counter bumped on every committed write, so observers can skip work when nothing changed.
+    ///
+    /// 每次已提交的写入都会递增的计数器，观察者可据此在无变化时跳过处理。
+    pub fn revision(&self) -> u64 {
+        self.revision
     }
 
     /// Get a variable value.
@@ -248,10 +273,52 @@ impl MortarVariableState {
         self.variables.get(name)
     }
 
-    /// Execute an assignment statement.
+    /// Iterates over every variable and constant value.
+    ///
+    /// 遍历所有变量与常量的值。
+    pub fn values(&self) -> impl Iterator<Item = (&str, &MortarVariableValue)> {
+        self.variables
+            .iter()
+            .map(|(name, value)| (name.as_str(), value))
+    }
+
+    /// Runs the assignments among a line's pre-statements, in order.
     ///
-    /// 执行赋值语句。
+    /// 按顺序执行一行前置语句中的赋值。
+    pub fn execute_statements(&mut self, statements: &[mortar_compiler::Statement]) {
+        for stmt in statements {
+            if stmt.stmt_type == "assignment"
+                && let (Some(var_name), Some(value)) = (&stmt.var_name, &stmt.value)
+            {
+                self.execute_assignment(var_name, value);
+            }
+        }
+    }
+
+    /// Execute an assignment statement. An arithmetic value such as `score + 10` is evaluated
+    /// against the current variables; one that cannot be, for an unknown variable or a division
+    /// by zero, warns and keeps the previous value.
+    ///
+    /// 执行赋值语句。`score + 10` 这样的算术值会基于当前变量求值；若无法求值（变量未知或除以零），
+    /// 会发出警告并保留原值。
     pub fn execute_assignment(&mut self, var_name: &str, value_str: &str) {
+        match expression::evaluate(value_str, self) {
+            Some(Ok(value)) => {
+                self.set(var_name, value);
+                return;
+            }
+            Some(Err(reason)) => {
+                warn!(
+                    target: LOG_EVAL,
+                    "Assignment '{} = {}' skipped, keeping the previous value: {}",
+                    var_name,
+                    value_str,
+                    reason
+                );
+                return;
+            }
+            None => {}
+        }
         // Parse the value string.
         //
         // 解析值字符串。
@@ -360,7 +427,7 @@ impl MortarVariableState {
             "literal" => self.evaluate_literal_condition(condition),
             "func_call" => self.evaluate_func_call_condition(condition),
             _ => {
-                warn!("Unknown condition type: {}", condition.cond_type);
+                warn!(target: LOG_EVAL, "Unknown condition type: {}", condition.cond_type);
                 false
             }
         }
@@ -391,7 +458,7 @@ impl MortarVariableState {
             "==" => self.compare_values_eq(left, right, true),
             "!=" => self.compare_values_eq(left, right, false),
             _ => {
-                warn!("Unknown binary operator: {}", operator);
+                warn!(target: LOG_EVAL, "Unknown binary operator: {}", operator);
                 false
             }
         }
@@ -404,7 +471,7 @@ impl MortarVariableState {
         match operator.as_str() {
             "!" => !self.evaluate_condition(operand),
             _ => {
-                warn!("Unknown unary operator: {}", operator);
+                warn!(target: LOG_EVAL, "Unknown unary operator: {}", operator);
                 false
             }
         }
@@ -416,13 +483,14 @@ impl MortarVariableState {
             Some(MortarVariableValue::Boolean(b)) => *b,
             Some(_) => {
                 warn!(
+                    target: LOG_EVAL,
                     "Variable '{}' is not a boolean, cannot evaluate as condition",
                     identifier
                 );
                 false
             }
             None => {
-                warn!("Variable '{}' not found", identifier);
+                warn!(target: LOG_EVAL, "Variable '{}' not found", identifier);
                 false
             }
         }
@@ -434,7 +502,7 @@ impl MortarVariableState {
             "true" => true,
             "false" => false,
             _ => {
-                warn!("Unknown literal value: {}", value);
+                warn!(target: LOG_EVAL, "Unknown literal value: {}", value);
                 false
             }
         }
@@ -450,7 +518,10 @@ impl MortarVariableState {
         // 而 MortarVariableState 无法获取。
         // 这部分应由同时拥有 variable_state 与函数注册表的上层逻辑处理。
         // 当前返回 false 并输出提示日志。
-        dev_info!("Function call in condition requires runtime function evaluation");
+        dev_info!(
+            target: LOG_EVAL,
+            "Function call in condition requires runtime function evaluation"
+        );
         false
     }
 
@@ -464,7 +535,7 @@ impl MortarVariableState {
         match (left_num, right_num) {
             (Some(l), Some(r)) => cmp(l, r),
             _ => {
-                warn!("Cannot compare non-numeric values");
+                warn!(target: LOG_EVAL, "Cannot compare non-numeric values");
                 false
             }
         }
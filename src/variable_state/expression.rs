@@ -0,0 +1,278 @@
+//! # expression.rs
+//!
+//! # expression.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Arithmetic on the right-hand side of an assignment, such as `score = score + 10`. Numbers take
+//! `+`, `-`, `*` and `/` with the usual precedence, parentheses and unary minus; `+` with a string
+//! on either side concatenates display strings. Identifiers are looked up in the current
+//! [`MortarVariableState`]. A value only counts as an expression when it parses as one, has a
+//! binary operator and at least one operand that is a literal or a known variable, so plain text
+//! such as `well-known` is still assigned as a string.
+//!
+//! 赋值右侧的算术，例如 `score = score + 10`。数字支持 `+`、`-`、`*`、`/`（按常规优先级）、括号与
+//! 一元负号；任意一侧为字符串时，`+` 会拼接显示字符串。标识符在当前 [`MortarVariableState`] 中
+//! 查找。只有能解析为表达式、包含二元运算符且至少一个操作数是字面量或已知变量的值才视为表达式，
+//! 因此像 `well-known` 这样的普通文本仍按字符串赋值。
+
+use super::{MortarVariableState, MortarVariableValue};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Text(String),
+    Ident(String),
+    Op(char),
+    Open,
+    Close,
+}
+
+#[derive(Debug)]
+enum Expr {
+    Literal(MortarVariableValue),
+    Var(String),
+    Neg(Box<Expr>),
+    Binary(char, Box<Expr>, Box<Expr>),
+}
+
+/// Evaluates `text` as an expression against `state`.
+///
+/// `None` when `text` is not an expression and should be assigned as a literal; `Err` with the
+/// reason when it is one but cannot be evaluated.
+pub(super) fn evaluate(
+    text: &str,
+    state: &MortarVariableState,
+) -> Option<Result<MortarVariableValue, String>> {
+    let tokens = tokenize(text)?;
+    let mut parser = Parser { tokens, pos: 0 };
+    let expr = parser.expr()?;
+    if parser.pos != parser.tokens.len() || !expr.has_operator() || !expr.is_anchored(state) {
+        return None;
+    }
+    Some(expr.eval(state))
+}
+
+fn tokenize(text: &str) -> Option<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            _ if c.is_whitespace() => {
+                chars.next();
+            }
+            '+' | '-' | '*' | '/' => {
+                chars.next();
+                tokens.push(Token::Op(c));
+            }
+            '(' | ')' => {
+                chars.next();
+                tokens.push(if c == '(' { Token::Open } else { Token::Close });
+            }
+            '"' | '\'' => {
+                chars.next();
+                let mut literal = String::new();
+                loop {
+                    match chars.next()? {
+                        end if end == c => break,
+                        other => literal.push(other),
+                    }
+                }
+                tokens.push(Token::Text(literal));
+            }
+            _ if c.is_ascii_digit() => {
+                let mut number = String::new();
+                while let Some(&d) = chars.peek().filter(|d| d.is_ascii_digit() || **d == '.') {
+                    number.push(d);
+                    chars.next();
+                }
+                tokens.push(Token::Number(number.parse().ok()?));
+            }
+            _ if c.is_alphabetic() || c == '_' => {
+                let mut ident = String::new();
+                while let Some(&d) = chars
+                    .peek()
+                    .filter(|d| d.is_alphanumeric() || **d == '_' || **d == '.')
+                {
+                    ident.push(d);
+                    chars.next();
+                }
+                tokens.push(Token::Ident(ident));
+            }
+            _ => return None,
+        }
+    }
+    Some(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek_op(&self, ops: &[char]) -> Option<char> {
+        match self.tokens.get(self.pos) {
+            Some(Token::Op(op)) if ops.contains(op) => Some(*op),
+            _ => None,
+        }
+    }
+
+    fn expr(&mut self) -> Option<Expr> {
+        let mut left = self.term()?;
+        while let Some(op) = self.peek_op(&['+', '-']) {
+            self.pos += 1;
+            left = Expr::Binary(op, Box::new(left), Box::new(self.term()?));
+        }
+        Some(left)
+    }
+
+    fn term(&mut self) -> Option<Expr> {
+        let mut left = self.unary()?;
+        while let Some(op) = self.peek_op(&['*', '/']) {
+            self.pos += 1;
+            left = Expr::Binary(op, Box::new(left), Box::new(self.unary()?));
+        }
+        Some(left)
+    }
+
+    fn unary(&mut self) -> Option<Expr> {
+        if self.peek_op(&['-']).is_some() {
+            self.pos += 1;
+            return Some(Expr::Neg(Box::new(self.unary()?)));
+        }
+        let token = self.tokens.get(self.pos)?.clone();
+        self.pos += 1;
+        match token {
+            Token::Number(n) => Some(Expr::Literal(MortarVariableValue::Number(n))),
+            Token::Text(s) => Some(Expr::Literal(MortarVariableValue::String(s))),
+            Token::Ident(name) => Some(Expr::Var(name)),
+            Token::Open => {
+                let inner = self.expr()?;
+                (self.tokens.get(self.pos) == Some(&Token::Close)).then_some(())?;
+                self.pos += 1;
+                Some(inner)
+            }
+            Token::Op(_) | Token::Close => None,
+        }
+    }
+}
+
+impl Expr {
+    fn has_operator(&self) -> bool {
+        match self {
+            Expr::Binary(..) => true,
+            Expr::Neg(inner) => inner.has_operator(),
+            Expr::Literal(_) | Expr::Var(_) => false,
+        }
+    }
+
+    fn is_anchored(&self, state: &MortarVariableState) -> bool {
+        match self {
+            Expr::Literal(_) => true,
+            Expr::Var(name) => state.get(name).is_some(),
+            Expr::Neg(inner) => inner.is_anchored(state),
+            Expr::Binary(_, left, right) => left.is_anchored(state) || right.is_anchored(state),
+        }
+    }
+
+    fn eval(&self, state: &MortarVariableState) -> Result<MortarVariableValue, String> {
+        use MortarVariableValue::{Number, String as Text};
+        match self {
+            Expr::Literal(value) => Ok(value.clone()),
+            Expr::Var(name) => state
+                .get(name)
+                .cloned()
+                .ok_or_else(|| format!("unknown variable '{name}'")),
+            Expr::Neg(inner) => match inner.eval(state)? {
+                Number(n) => Ok(Number(-n)),
+                other => Err(format!("cannot negate {other:?}")),
+            },
+            Expr::Binary(op, left, right) => match (*op, left.eval(state)?, right.eval(state)?) {
+                ('+', Number(a), Number(b)) => Ok(Number(a + b)),
+                ('+', a @ Text(_), b) | ('+', a, b @ Text(_)) => {
+                    Ok(Text(a.to_display_string() + &b.to_display_string()))
+                }
+                ('-', Number(a), Number(b)) => Ok(Number(a - b)),
+                ('*', Number(a), Number(b)) => Ok(Number(a * b)),
+                ('/', Number(_), Number(0.0)) => Err("division by zero".to_owned()),
+                ('/', Number(a), Number(b)) => Ok(Number(a / b)),
+                (op, a, b) => Err(format!("cannot apply '{op}' to {a:?} and {b:?}")),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state() -> MortarVariableState {
+        let mut state = MortarVariableState::new();
+        state.set("count", MortarVariableValue::Number(2.0));
+        state.set("name", MortarVariableValue::String("Ada".to_owned()));
+        state
+    }
+
+    #[test]
+    fn test_count_plus_one() {
+        let mut state = state();
+        state.execute_assignment("count", "count + 1");
+        state.execute_assignment("count", "count+1");
+        assert_eq!(state.get("count"), Some(&MortarVariableValue::Number(4.0)));
+    }
+
+    #[test]
+    fn test_literals_and_variables_follow_precedence() {
+        let mut state = state();
+        state.execute_assignment("total", "10 - count * 3");
+        assert_eq!(state.get("total"), Some(&MortarVariableValue::Number(4.0)));
+        state.execute_assignment("total", "(10 - count) * -3");
+        assert_eq!(
+            state.get("total"),
+            Some(&MortarVariableValue::Number(-24.0))
+        );
+    }
+
+    #[test]
+    fn test_string_concatenation() {
+        let mut state = state();
+        state.execute_assignment("title", "name + \" the Brave, level \" + count");
+        assert_eq!(
+            state.get("title"),
+            Some(&MortarVariableValue::String(
+                "Ada the Brave, level 2".to_owned()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_division_by_zero_keeps_the_previous_value() {
+        let mut state = state();
+        state.execute_assignment("count", "count / 0");
+        assert_eq!(state.get("count"), Some(&MortarVariableValue::Number(2.0)));
+    }
+
+    #[test]
+    fn test_unknown_identifier_keeps_the_previous_value() {
+        let mut state = state();
+        state.execute_assignment("count", "count + bonus");
+        assert_eq!(state.get("count"), Some(&MortarVariableValue::Number(2.0)));
+    }
+
+    #[test]
+    fn test_plain_text_is_still_a_literal() {
+        let mut state = state();
+        for text in ["well-known", "Hello, world"] {
+            state.execute_assignment("motto", text);
+            assert_eq!(
+                state.get("motto"),
+                Some(&MortarVariableValue::String(text.to_owned()))
+            );
+        }
+        state.execute_assignment("motto", "-5");
+        assert_eq!(state.get("motto"), Some(&MortarVariableValue::Number(-5.0)));
+    }
+}
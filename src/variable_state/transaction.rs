@@ -0,0 +1,257 @@
+//! # transaction.rs
+//!
+//! # transaction.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Transactions on [`MortarVariableState`], for evaluating script logic without committing its
+//! assignments. While a transaction is open every write records the previous value in an undo
+//! log; [`MortarVariableState::rollback`] restores them in reverse order and
+//! [`MortarVariableState::commit`] keeps them. Transactions nest up to
+//! [`MAX_TRANSACTION_DEPTH`]. Writes inside a transaction do not advance
+//! [`MortarVariableState::revision`]; only committing the outermost transaction does, so watchers
+//! never see rolled-back values. Previews, such as [`crate::MortarRuntime::simulate_confirm`], go
+//! through [`MortarVariableState::preview`], which always rolls back.
+//!
+//! [`MortarVariableState`] 上的事务，用于在不提交赋值的情况下求值脚本逻辑。事务打开期间，每次写入
+//! 都会把旧值记录到撤销日志中；[`MortarVariableState::rollback`] 按相反顺序恢复这些值，
+//! [`MortarVariableState::commit`] 则保留写入。事务最多可嵌套 [`MAX_TRANSACTION_DEPTH`] 层。
+//! 事务内的写入不会推进 [`MortarVariableState::revision`]，只有提交最外层事务时才会推进，
+//! 因此观察者永远看不到被回滚的值。预览（例如 [`crate::MortarRuntime::simulate_confirm`]）通过
+//! [`MortarVariableState::preview`] 进行，它总是会回滚。
+
+use super::{MortarVariableState, MortarVariableValue};
+
+/// Deepest nesting of transactions.
+///
+/// 事务的最大嵌套深度。
+pub const MAX_TRANSACTION_DEPTH: usize = 8;
+
+/// Why a transaction operation was refused. The state is left unchanged.
+///
+/// 事务操作被拒绝的原因。此时状态保持不变。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MortarTransactionError {
+    /// `commit` or `rollback` was called without an open transaction.
+    ///
+    /// 在没有打开事务时调用了 `commit` 或 `rollback`。
+    NoTransaction,
+    /// `begin_transaction` would nest deeper than [`MAX_TRANSACTION_DEPTH`].
+    ///
+    /// `begin_transaction` 的嵌套深度将超过 [`MAX_TRANSACTION_DEPTH`]。
+    DepthExceeded,
+}
+
+impl std::fmt::Display for MortarTransactionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoTransaction => write!(f, "no variable transaction is open"),
+            Self::DepthExceeded => write!(
+                f,
+                "variable transactions nest deeper than {MAX_TRANSACTION_DEPTH}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for MortarTransactionError {}
+
+/// Undo log shared by all open transactions; `marks` holds where each one starts.
+#[derive(Debug, Clone, Default)]
+pub(super) struct UndoLog {
+    entries: Vec<(String, Option<MortarVariableValue>)>,
+    marks: Vec<usize>,
+}
+
+impl UndoLog {
+    pub(super) fn is_open(&self) -> bool {
+        !self.marks.is_empty()
+    }
+
+    pub(super) fn record(&mut self, name: &str, previous: Option<MortarVariableValue>) {
+        self.entries.push((name.to_owned(), previous));
+    }
+}
+
+impl MortarVariableState {
+    /// Opens a transaction, nested inside any transaction already open.
+    ///
+    /// 打开一个事务；若已有事务打开，则嵌套在其中。
+    pub fn begin_transaction(&mut self) -> Result<(), MortarTransactionError> {
+        if self.undo.marks.len() >= MAX_TRANSACTION_DEPTH {
+            return Err(MortarTransactionError::DepthExceeded);
+        }
+        self.undo.marks.push(self.undo.entries.len());
+        Ok(())
+    }
+
+    /// Keeps the writes of the innermost transaction. They become part of the enclosing
+    /// transaction, or are published with a new revision when it was the outermost one.
+    ///
+    /// 保留最内层事务的写入。这些写入会并入外层事务；若它是最外层事务，则以新的修订号发布。
+    pub fn commit(&mut self) -> Result<(), MortarTransactionError> {
+        self.undo
+            .marks
+            .pop()
+            .ok_or(MortarTransactionError::NoTransaction)?;
+        if !self.undo.is_open() && !self.undo.entries.is_empty() {
+            self.undo.entries.clear();
+            self.revision = self.revision.wrapping_add(1);
+        }
+        Ok(())
+    }
+
+    /// Undoes every write of the innermost transaction, latest first.
+    ///
+    /// 撤销最内层事务的全部写入，从最近的写入开始。
+    pub fn rollback(&mut self) -> Result<(), MortarTransactionError> {
+        let start = self
+            .undo
+            .marks
+            .pop()
+            .ok_or(MortarTransactionError::NoTransaction)?;
+        for (name, previous) in self.undo.entries.drain(start..).rev() {
+            match previous {
+                Some(value) => self.variables.insert(name, value),
+                None => self.variables.remove(&name),
+            };
+        }
+        Ok(())
+    }
+
+    /// Runs `preview` inside a transaction and rolls back everything it wrote, including
+    /// transactions it left open. Fails like [`Self::begin_transaction`], without running
+    /// `preview`, when transactions already nest as deep as they may.
+    ///
+    /// 在事务中运行 `preview` 并回滚它写入的一切，包括它未关闭的事务。若事务已达到最大嵌套深度，
+    /// 则与 [`Self::begin_transaction`] 一样失败，且不会运行 `preview`。
+    pub fn preview<R>(
+        &mut self,
+        preview: impl FnOnce(&mut Self) -> R,
+    ) -> Result<R, MortarTransactionError> {
+        let depth = self.transaction_depth();
+        self.begin_transaction()?;
+        let result = preview(self);
+        while self.transaction_depth() > depth {
+            self.rollback()?;
+        }
+        Ok(result)
+    }
+
+    /// Number of open transactions.
+    ///
+    /// 当前打开的事务数量。
+    pub fn transaction_depth(&self) -> usize {
+        self.undo.marks.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn number(state: &MortarVariableState, name: &str) -> Option<f64> {
+        match state.get(name) {
+            Some(MortarVariableValue::Number(value)) => Some(*value),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_rollback_restores_repeated_assignments() {
+        let mut state = MortarVariableState::new();
+        state.set("gold", MortarVariableValue::Number(10.0));
+
+        state.begin_transaction().unwrap();
+        state.execute_assignment("gold", "20");
+        state.execute_assignment("gold", "30");
+        state.execute_assignment("fresh", "true");
+        assert_eq!(number(&state, "gold"), Some(30.0));
+        state.rollback().unwrap();
+
+        assert_eq!(number(&state, "gold"), Some(10.0));
+        assert_eq!(state.get("fresh"), None);
+        assert_eq!(state.transaction_depth(), 0);
+    }
+
+    #[test]
+    fn test_nested_transactions() {
+        let mut state = MortarVariableState::new();
+        state.set("gold", MortarVariableValue::Number(1.0));
+
+        state.begin_transaction().unwrap();
+        state.set("gold", MortarVariableValue::Number(2.0));
+        state.begin_transaction().unwrap();
+        state.set("gold", MortarVariableValue::Number(3.0));
+        state.rollback().unwrap();
+        assert_eq!(number(&state, "gold"), Some(2.0));
+
+        state.begin_transaction().unwrap();
+        state.set("gold", MortarVariableValue::Number(4.0));
+        state.commit().unwrap();
+        assert_eq!(number(&state, "gold"), Some(4.0));
+
+        // The inner commit merged into the outer transaction, so this undoes both.
+        //
+        // 内层提交已并入外层事务，因此这里会同时撤销两者。
+        state.rollback().unwrap();
+        assert_eq!(number(&state, "gold"), Some(1.0));
+    }
+
+    #[test]
+    fn test_revision_only_advances_on_commit() {
+        let mut state = MortarVariableState::new();
+        state.set("gold", MortarVariableValue::Number(1.0));
+        let revision = state.revision();
+
+        state.begin_transaction().unwrap();
+        state.set("gold", MortarVariableValue::Number(2.0));
+        assert_eq!(state.revision(), revision);
+        state.rollback().unwrap();
+        assert_eq!(state.revision(), revision);
+
+        state.begin_transaction().unwrap();
+        state.begin_transaction().unwrap();
+        state.set("gold", MortarVariableValue::Number(3.0));
+        state.commit().unwrap();
+        assert_eq!(state.revision(), revision);
+        state.commit().unwrap();
+        assert_ne!(state.revision(), revision);
+    }
+
+    #[test]
+    fn test_preview_rolls_back_transactions_left_open() {
+        let mut state = MortarVariableState::new();
+        state.set("gold", MortarVariableValue::Number(1.0));
+        let revision = state.revision();
+
+        let seen = state.preview(|state| {
+            state.execute_assignment("gold", "gold + 1");
+            state.begin_transaction().unwrap();
+            state.execute_assignment("gold", "gold * 10");
+            number(state, "gold")
+        });
+        assert_eq!(seen, Ok(Some(20.0)));
+        assert_eq!(number(&state, "gold"), Some(1.0));
+        assert_eq!(state.transaction_depth(), 0);
+        assert_eq!(state.revision(), revision);
+    }
+
+    #[test]
+    fn test_misuse_is_refused() {
+        let mut state = MortarVariableState::new();
+        assert_eq!(state.rollback(), Err(MortarTransactionError::NoTransaction));
+        assert_eq!(state.commit(), Err(MortarTransactionError::NoTransaction));
+
+        for _ in 0..MAX_TRANSACTION_DEPTH {
+            state.begin_transaction().unwrap();
+        }
+        assert_eq!(
+            state.begin_transaction(),
+            Err(MortarTransactionError::DepthExceeded)
+        );
+        assert_eq!(state.transaction_depth(), MAX_TRANSACTION_DEPTH);
+    }
+}
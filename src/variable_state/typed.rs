@@ -0,0 +1,140 @@
+//! # typed.rs
+//!
+//! # typed.rs 文件
+//!
+//! ## Module Overview
+//!
+//! ## 模块概述
+//!
+//! Typed access to variables, so game code reads and writes plain `f64`, `bool` and `String`
+//! instead of matching on [`MortarVariableValue`]. Reading converts where it is unambiguous: a
+//! numeric string reads as a number, `"true"` and `"false"` read as booleans, and every value
+//! reads as its display string. A boolean never reads as a number, nor a number as a boolean.
+//!
+//! 对变量的类型化访问，使游戏代码直接读写 `f64`、`bool` 与 `String`，而无需匹配
+//! [`MortarVariableValue`]。读取时只做无歧义的转换：数字字符串可读作数字，`"true"` 与 `"false"`
+//! 可读作布尔值，任何值都可读作其显示字符串。布尔值不会读作数字，数字也不会读作布尔值。
+
+use super::{MortarVariableState, MortarVariableValue};
+
+impl MortarVariableValue {
+    /// The value as a number, parsing a numeric string.
+    ///
+    /// 以数字读取该值，数字字符串会被解析。
+    pub fn as_number(&self) -> Option<f64> {
+        match self {
+            MortarVariableValue::Number(n) => Some(*n),
+            MortarVariableValue::String(s) => s.trim().parse().ok(),
+            MortarVariableValue::Boolean(_) => None,
+        }
+    }
+
+    /// The value as a boolean, reading the strings `"true"` and `"false"`.
+    ///
+    /// 以布尔值读取该值，可识别字符串 `"true"` 与 `"false"`。
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            MortarVariableValue::Boolean(b) => Some(*b),
+            MortarVariableValue::String(s) => s.trim().parse().ok(),
+            MortarVariableValue::Number(_) => None,
+        }
+    }
+}
+
+impl MortarVariableState {
+    /// A variable as a number, see [`MortarVariableValue::as_number`].
+    ///
+    /// 以数字读取变量，参见 [`MortarVariableValue::as_number`]。
+    pub fn get_number(&self, name: &str) -> Option<f64> {
+        self.get(name)?.as_number()
+    }
+
+    /// A variable as a boolean, see [`MortarVariableValue::as_bool`].
+    ///
+    /// 以布尔值读取变量，参见 [`MortarVariableValue::as_bool`]。
+    pub fn get_bool(&self, name: &str) -> Option<bool> {
+        self.get(name)?.as_bool()
+    }
+
+    /// A variable as its display string.
+    ///
+    /// 以显示字符串读取变量。
+    pub fn get_string(&self, name: &str) -> Option<String> {
+        Some(self.get(name)?.to_display_string())
+    }
+
+    /// Sets a variable to a number.
+    ///
+    /// 将变量设为数字。
+    pub fn set_number(&mut self, name: &str, value: f64) {
+        self.set(name, MortarVariableValue::Number(value));
+    }
+
+    /// Sets a variable to a boolean.
+    ///
+    /// 将变量设为布尔值。
+    pub fn set_bool(&mut self, name: &str, value: bool) {
+        self.set(name, MortarVariableValue::Boolean(value));
+    }
+
+    /// Sets a variable to a string.
+    ///
+    /// 将变量设为字符串。
+    pub fn set_string(&mut self, name: &str, value: impl Into<String>) {
+        self.set(name, MortarVariableValue::String(value.into()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state() -> MortarVariableState {
+        let mut state = MortarVariableState::new();
+        state.set_number("gold", 3.0);
+        state.set_bool("open", true);
+        state.set_string("count", " 3 ");
+        state.set_string("flag", "false");
+        state.set_string("name", "Ada");
+        state
+    }
+
+    #[test]
+    fn test_get_number_conversions() {
+        let state = state();
+        assert_eq!(state.get_number("gold"), Some(3.0));
+        assert_eq!(state.get_number("count"), Some(3.0));
+        assert_eq!(state.get_number("name"), None);
+        assert_eq!(state.get_number("open"), None);
+        assert_eq!(state.get_number("missing"), None);
+    }
+
+    #[test]
+    fn test_get_bool_conversions() {
+        let state = state();
+        assert_eq!(state.get_bool("open"), Some(true));
+        assert_eq!(state.get_bool("flag"), Some(false));
+        assert_eq!(state.get_bool("name"), None);
+        assert_eq!(state.get_bool("gold"), None);
+    }
+
+    #[test]
+    fn test_get_string_conversions() {
+        let state = state();
+        assert_eq!(state.get_string("name").as_deref(), Some("Ada"));
+        assert_eq!(state.get_string("gold").as_deref(), Some("3"));
+        assert_eq!(state.get_string("open").as_deref(), Some("true"));
+        assert_eq!(state.get_string("missing"), None);
+    }
+
+    #[test]
+    fn test_setters_store_typed_values() {
+        let state = state();
+        assert_eq!(state.get("gold"), Some(&MortarVariableValue::Number(3.0)));
+        assert_eq!(state.get("open"), Some(&MortarVariableValue::Boolean(true)));
+        assert_eq!(
+            state.get("name"),
+            Some(&MortarVariableValue::String("Ada".to_owned()))
+        );
+    }
+}